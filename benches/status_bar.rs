@@ -0,0 +1,109 @@
+//! Benchmarks for the status bar's per-redraw work
+//!
+//! Every redraw measures each label with `strip_ansi_len` and writes the frame
+//! into a `ByteWriter`; a whole frame should stay well under 1ms. The redraw
+//! benches compare writing every frame with `StatusBarRenderer`, which drops
+//! frames repeating the one on screen.
+
+use std::hint::black_box;
+use std::io::Write;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use crabigator::terminal::escape::{self, color, RESET};
+use crabigator::terminal::theme::Palette;
+use crabigator::ui::{utils, ByteWriter, StatusBarRenderer, FRAME_CAPACITY};
+
+/// Rows of a typical frame: colored labels, a hyperlinked file name, plain text
+fn frame_lines() -> Vec<String> {
+    let link = escape::hyperlink("file:///Users/me/src/crabigator/src/app.rs", "app.rs");
+    let [green, purple, yellow, red] =
+        [color::GREEN, color::PURPLE, color::YELLOW, color::RED].map(|c| Palette::default().fg(c));
+    (0..24)
+        .map(|row| match row % 3 {
+            0 => format!("{}Thinking{} 12m 04s  {}▸ main{}", green, RESET, purple, RESET),
+            1 => format!("{}M{} {} {}+42{} {}-7{}", yellow, RESET, link, green, RESET, red, RESET),
+            _ => "ƒ draw_status_bar  ƒ handle_resize  ƒ forward_key_to_pty".to_string(),
+        })
+        .collect()
+}
+
+fn bench_strip_ansi_len(c: &mut Criterion) {
+    let lines = frame_lines();
+    c.bench_function("strip_ansi_len frame", |b| {
+        b.iter(|| lines.iter().map(|line| utils::strip_ansi_len(black_box(line))).sum::<usize>())
+    });
+    let plain = "x".repeat(200);
+    c.bench_function("find_escape 200 plain bytes", |b| b.iter(|| utils::find_escape(black_box(plain.as_bytes()))));
+}
+
+fn bench_frame(c: &mut Criterion) {
+    let lines = frame_lines();
+    let mut frame = ByteWriter::new();
+    let mut out = Vec::with_capacity(FRAME_CAPACITY);
+    c.bench_function("status bar frame", |b| {
+        b.iter(|| {
+            for (row, line) in lines.iter().enumerate() {
+                let pad = 120usize.saturating_sub(utils::strip_ansi_len(line));
+                write!(frame, "{}{}{:pad$}", escape::cursor_to(row as u16 + 30, 1), line, "", pad = pad).unwrap();
+            }
+            out.clear();
+            frame.flush_into(&mut out).unwrap();
+            black_box(out.len())
+        })
+    });
+}
+
+/// Write one frame of `lines` into `frame`, with `state` in the first row
+fn render_frame(frame: &mut ByteWriter, lines: &[String], state: &str) {
+    for (row, line) in lines.iter().enumerate() {
+        let line = if row == 0 { state } else { line.as_str() };
+        let pad = 120usize.saturating_sub(utils::strip_ansi_len(line));
+        write!(frame, "{}{}{:pad$}", escape::cursor_to(row as u16 + 30, 1), line, "", pad = pad).unwrap();
+    }
+}
+
+/// 100 redraws triggered by PTY output, of which the state changes on 10
+fn bench_redraw_limiter(c: &mut Criterion) {
+    let lines = frame_lines();
+    let states: Vec<String> = (0..100).map(|i| format!("Thinking {}s", i / 10)).collect();
+    let mut frame = ByteWriter::new();
+    let mut out = Vec::with_capacity(FRAME_CAPACITY * 100);
+
+    c.bench_function("100 redraws, every frame written", |b| {
+        b.iter(|| {
+            out.clear();
+            for state in &states {
+                render_frame(&mut frame, &lines, state);
+                frame.flush_into(&mut out).unwrap();
+            }
+            black_box(out.len())
+        })
+    });
+
+    let mut limiter = StatusBarRenderer::new();
+    c.bench_function("100 redraws, changed frames written", |b| {
+        b.iter(|| {
+            out.clear();
+            limiter.invalidate();
+            for state in &states {
+                render_frame(&mut frame, &lines, state);
+                limiter.flush_if_changed(&mut out, &mut frame).unwrap();
+            }
+            black_box(out.len())
+        })
+    });
+
+    limiter.invalidate();
+    let written = states
+        .iter()
+        .filter(|state| {
+            render_frame(&mut frame, &lines, state);
+            limiter.flush_if_changed(&mut out, &mut frame).unwrap()
+        })
+        .count();
+    println!("redraw limiter: {} of {} frames written", written, states.len());
+}
+
+criterion_group!(benches, bench_strip_ansi_len, bench_frame, bench_redraw_limiter);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+//! Fuzz `strip_ansi_len`, which walks every string drawn in the status bar
+//!
+//! Terminal titles and file names come from outside crabigator, so any
+//! string, escape sequences included, must measure without panicking.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use crabigator::ui::utils;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let len = utils::strip_ansi_len(&text);
+    // Truncation relies on the same measure
+    let _ = utils::take_graphemes(&utils::strip_ansi(&text), len / 2);
+});
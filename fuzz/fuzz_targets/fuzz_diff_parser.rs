@@ -0,0 +1,54 @@
+//! Fuzz the diff splitter and every language parser
+//!
+//! `git diff` output embeds file contents, so whatever lands in a repository
+//! reaches these parsers. Arbitrary bytes must never panic, blow up the number
+//! of files, or take long enough to stall a refresh.
+
+#![no_main]
+
+use std::time::{Duration, Instant};
+
+use libfuzzer_sys::fuzz_target;
+
+use crabigator::parsers::{
+    parse_diff_into_files, CssParser, DiffParser, GenericParser, JsonParser, ObjCParser, PythonParser, RustParser,
+    SwiftParser, TypeScriptParser, YamlParser,
+};
+
+/// More files than this from one input means the splitter is inventing them
+const MAX_FILES: usize = 1000;
+
+/// Longest the splitter, or one parser on one file, may take. A refresh runs
+/// one parser per file, so that's the unit that has to stay fast.
+const TIME_BUDGET: Duration = Duration::from_millis(100);
+
+const PARSERS: [&dyn DiffParser; 9] = [
+    &RustParser,
+    &TypeScriptParser,
+    &PythonParser,
+    &SwiftParser,
+    &ObjCParser,
+    &CssParser,
+    &YamlParser,
+    &JsonParser,
+    &GenericParser,
+];
+
+fuzz_target!(|data: &[u8]| {
+    let diff = String::from_utf8_lossy(data);
+
+    let start = Instant::now();
+    let files = parse_diff_into_files(&diff);
+    assert!(start.elapsed() < TIME_BUDGET, "splitting took {:?}", start.elapsed());
+    assert!(files.len() < MAX_FILES, "{} files from {} bytes", files.len(), data.len());
+
+    for (filename, file_diff) in &files {
+        for parser in PARSERS {
+            let start = Instant::now();
+            let changes = parser.parse(file_diff, filename);
+            let elapsed = start.elapsed();
+            assert!(elapsed < TIME_BUDGET, "{} took {:?} on {}", parser.language(), elapsed, filename);
+            assert!(changes.len() < MAX_FILES, "{} changes from {}", changes.len(), parser.language());
+        }
+    }
+});
@@ -7,40 +7,94 @@
 //! - PTY output passes through untouched
 
 use anyhow::Result;
-use crossterm::event::{self, Event, MouseEvent};
+use crossterm::event::{Event, MouseEvent};
+use std::collections::HashMap;
 use std::io::{stdout, Write};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot, Notify};
 
-use crate::capture::{CaptureConfig, CaptureManager, ScrollbackUpdate};
-use crate::cloud::{CloudClient, SessionEventBuilder};
+use crate::background::{BackgroundTaskQueue, TaskPriority};
+use crate::capture::{CaptureConfig, CaptureFormat, CaptureManager, ScrollbackUpdate};
+use crate::cloud::{CloudClient, SessionEventBuilder, TelemetryReceiver, TelemetrySender};
 use crate::config::Config;
-use crate::git::GitState;
-use crate::hooks::SessionStats;
+use crate::copy_stats::StatsSummary;
+use crate::git::{generate_commit_suggestion, head_commit, staged_paths, GitState, GitWatcher};
+use crate::history::{CarriedCounts, HistoryStore, SessionRecord};
+use crate::tags::TagStore;
+use crate::hooks::{load_previous_stats, previous_session_id, SessionStats, StatsWatcher};
 use crate::ide::{self, IdeKind};
 use crate::platforms::{Platform, SessionState};
 use crate::mirror::MirrorPublisher;
-use crate::parsers::DiffSummary;
-use crate::terminal::{escape, forward_key_to_pty, DsrChunk, DsrHandler, OscScanner, PlatformPty};
-use crate::ui::{draw_status_bar, Layout};
-
-/// Result from background git refresh
-struct GitRefreshResult {
-    git_state: GitState,
-    diff_summary: DiffSummary,
-    /// Time taken for git status refresh (ms)
-    git_time_ms: u64,
-    /// Time taken for diff summary parsing (ms)
-    diff_time_ms: u64,
+use crate::notify::NotificationManager;
+use crate::parsers::{DiffCache, DiffMode, DiffSummary};
+use crate::system::copy_to_clipboard;
+use crate::terminal::{
+    encode_focus, encode_mouse_event, escape, forward_key_to_pty, DsrChunk, DsrHandler, FocusScanner, KittyGraphicsBackend, MouseModeTracker, OscScanner, PlatformPty, PtyBackend, PtyExit, SIGNALS,
+};
+use crate::terminal::theme::Palette;
+use crate::tmux::TmuxStatusWriter;
+use crate::ui::{
+    draw_budget_warning, draw_status_bar, plugin, ByteWriter, CustomWidget, HelpOverlay, Layout, MessageOverlay,
+    SeparatorStyle, StatusBarRenderer, WidgetSpec,
+};
+use crate::ui::utils::strip_ansi;
+
+/// How long the Ctrl+A ? help overlay stays up
+const HELP_OVERLAY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Event poll timeout while the terminal window has focus
+const FOCUSED_POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Event poll timeout while the window is in the background, to save CPU.
+/// PTY output and watcher events still wake the loop at once; only timer
+/// driven work (throbber, refresh timers) slows down.
+const UNFOCUSED_POLL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Result from a background git status or diff summary refresh
+struct RefreshResult<T> {
+    value: T,
+    /// Time the refresh took (ms)
+    elapsed_ms: u64,
+}
+
+/// How this session was started: the command line on top of config.toml
+pub struct AppOptions {
+    /// Arguments passed through to the assistant CLI
+    pub platform_args: Vec<String>,
+    /// Stream output to the capture files (--capture)
+    pub capture: bool,
+    /// --record / --no-record, overriding config `capture_format`
+    pub record: Option<bool>,
+    /// Where the tmux status line is written (--tmux-status-file)
+    pub tmux_status_file: Option<std::path::PathBuf>,
+    /// Extra environment for the CLI
+    pub platform_env: HashMap<String, String>,
+    pub status_bar: StatusBarEnabled,
+    /// --max-git-files, overriding config `max_git_files`
+    pub max_git_files: Option<usize>,
+    /// --diff-mode, overriding config `diff_mode`
+    pub diff_mode: Option<DiffMode>,
+    /// Theme and color mode for the status bar and overlays
+    pub palette: Palette,
 }
 
 pub struct App {
     pub running: bool,
     pub platform: Box<dyn Platform>,
-    pub platform_pty: PlatformPty,
+    pub platform_pty: Box<dyn PtyBackend>,
     pub git_state: GitState,
     pub diff_summary: DiffSummary,
     pub session_stats: SessionStats,
+    /// Flags git refreshes when .git changes (stops its thread on drop)
+    git_watcher: GitWatcher,
+    /// Signals when the hook stats file changes
+    stats_watcher: StatsWatcher,
+    /// Bounded pool for git refreshes and other off-loop work
+    task_queue: BackgroundTaskQueue,
+    /// Last diff summary, reused while the raw diff is unchanged
+    diff_cache: DiffCache,
     pub last_mouse_event: Option<MouseEvent>,
 
     // Layout
@@ -48,7 +102,28 @@ pub struct App {
     pub total_cols: u16,
     pub pty_rows: u16,
     pub status_rows: u16,
-
+    /// Off with `--no-status-bar`: the CLI gets the whole terminal
+    status_bar: StatusBarEnabled,
+    /// `status_bar_height_percent` from config, kept for resizes
+    status_bar_percent: u8,
+
+    /// Where the CLI output and status widgets are drawn (stdout outside tests)
+    out: Box<dyn Write + Send>,
+    /// Status bar frame, written to `out` in one piece
+    frame: ByteWriter,
+    /// Skips frames identical to the one on screen
+    renderer: StatusBarRenderer,
+    /// Inline logo drawing (Kitty only)
+    kitty: KittyGraphicsBackend,
+    /// How often to refresh git state when nothing on disk signals a change
+    git_refresh_interval: Duration,
+    /// How long each loop iteration waits for terminal events
+    poll_timeout: Duration,
+    /// Raised by the git and stats watchers to cut the wait short
+    wake: Arc<Notify>,
+    /// Background git refreshes applied so far (initial load included)
+    #[cfg(test)]
+    git_refreshes: u64,
     /// Current working directory for platform stats
     cwd: std::path::PathBuf,
     /// Detected IDE for clickable hyperlinks
@@ -62,14 +137,77 @@ pub struct App {
     dsr_handler: DsrHandler,
     /// Scans for OSC title sequences from the CLI
     osc_scanner: OscScanner,
+    /// Mouse tracking mode and encoding requested by the child
+    mouse_tracker: MouseModeTracker,
+    /// Whether the child asked for focus reports (mode 1004)
+    focus_scanner: FocusScanner,
     /// Terminal title extracted from OSC sequences (e.g., "Claude Code Ghostty Integration")
     terminal_title: Option<String>,
+    /// Session state the title was last updated for
+    title_state: Option<SessionState>,
+    /// We replaced the CLI's title and should put it back
+    crabigator_set_title: bool,
     /// Time taken for initial git refresh (set once on first load)
     initial_git_time_ms: Option<u64>,
     /// Time taken for initial diff parsing (set once on first load)
     initial_diff_time_ms: Option<u64>,
     /// Cloud client for streaming to drinkcrabigator.com (optional)
     cloud_client: Option<CloudClient>,
+    /// Cloud connection telemetry, collected only with --profile
+    telemetry_rx: Option<TelemetryReceiver>,
+    /// Where telemetry is appended (telemetry.log in the session directory)
+    telemetry_log_path: std::path::PathBuf,
+    /// Writes a one-line summary for the tmux status bar (--tmux-status-file)
+    tmux_writer: Option<TmuxStatusWriter>,
+    /// Where a record of this session is appended on exit (None in tests)
+    history_store: Option<HistoryStore>,
+    /// Session this one resumed (CRABIGATOR_PREVIOUS_SESSION_ID); its stats
+    /// file is removed when this session ends
+    previous_session_id: Option<String>,
+    /// Cap on files listed in the git widget (config `max_git_files` or --max-git-files)
+    max_git_files: Option<usize>,
+    /// Below this terminal width the widgets collapse to one-line summaries
+    compact_threshold: u16,
+    /// Separator row style from config
+    separator_style: SeparatorStyle,
+    /// Session length budget from config (countdown in the stats widget)
+    max_session_minutes: Option<u64>,
+    /// Token budget from config; cleared by Ctrl+A u
+    token_budget: Option<u64>,
+    /// Show words changed in the changes header (config `show_word_count`)
+    show_word_count: bool,
+    /// Leave test file changes out of the changes widget (config `hide_test_changes`)
+    hide_test_changes: bool,
+    /// Width of each custom widget column (config `custom_widget_width`)
+    custom_widget_width: u16,
+    /// Column order and widths of the built-in widgets (config `layout.widgets`)
+    widget_layout: Vec<WidgetSpec>,
+    /// Widgets from `[custom_widgets]` in config, drawn after the built-in ones
+    pub custom_widgets: Vec<Box<dyn CustomWidget>>,
+    /// Reparse only files whose diff changed on periodic refreshes (config `incremental_diff`)
+    incremental_diff: bool,
+    /// Changes the diff summary covers (config `diff_mode` or --diff-mode)
+    diff_mode: DiffMode,
+    palette: Palette,
+    /// HEAD when the session started, the base for `DiffMode::Session`/`All`
+    session_start_commit: Option<String>,
+    /// Ctrl+A was pressed; the next key is a crabigator command
+    prefix_pending: bool,
+    /// Key binding help drawn over the status widgets (Ctrl+A ?)
+    help_overlay: Option<HelpOverlay>,
+    /// Suggested commit message (Ctrl+A m) or copied stats (Ctrl+A c) shown over the widgets
+    message_overlay: Option<MessageOverlay>,
+    /// Preview still being built off the loop (git and the clipboard block)
+    pending_preview: Option<oneshot::Receiver<Option<MessageOverlay>>>,
+    /// How the assistant CLI exited (None if we quit first)
+    pub exit_status: Option<PtyExit>,
+    /// Bell or desktop notification when a task completes
+    notification_manager: NotificationManager,
+    /// State of the last transition considered for a notification, so each
+    /// transition notifies at most once
+    last_notified_state: SessionState,
+    /// Git refresh asked for by the user (Ctrl+A r)
+    git_refresh_requested: bool,
     /// Last state sent to cloud (to avoid duplicate events)
     last_cloud_state: Option<SessionState>,
     /// Last scrollback line count sent to cloud (for diffs)
@@ -85,54 +223,100 @@ impl App {
         cols: u16,
         rows: u16,
         platform: Box<dyn Platform>,
-        platform_args: Vec<String>,
-        capture_enabled: bool,
+        config: &Config,
+        options: AppOptions,
     ) -> Result<Self> {
         let (pty_tx, pty_rx) = mpsc::channel(256);
 
-        // Reserve bottom 20% for our status widgets (minimum 2 rows: separator + header)
-        // Also ensure pty_rows is at least 1 to avoid PTY errors
-        // Guard: ensure max >= min for clamp (handles very short terminals)
-        let status_rows = ((rows as f32 * 0.2) as u16).clamp(2, rows.saturating_sub(1).max(2));
-        let pty_rows = rows.saturating_sub(status_rows).max(1);
-
-        // Give the assistant CLI only the top portion
-        let platform_pty = PlatformPty::new(
-            pty_tx,
-            cols,
-            pty_rows,
-            platform.command(),
+        let AppOptions {
             platform_args,
-        )
-        .await?;
+            capture: capture_enabled,
+            record,
+            tmux_status_file,
+            platform_env,
+            status_bar,
+            max_git_files,
+            diff_mode,
+            palette,
+        } = options;
+        let status_bar_percent = config.status_bar_height_percent;
+        let (status_rows, pty_rows) = split_rows(rows, status_bar, status_bar_percent);
+
+        // Give the assistant CLI only the top portion. A binary set in
+        // `[platform.binaries]` wins; otherwise the PTY looks the command up on PATH.
+        let binary = config.platform.binary(platform.kind()).unwrap_or(Path::new(platform.command()));
+        let platform_pty =
+            Box::new(PlatformPty::new(pty_tx, cols, pty_rows, binary, platform_args, platform_env).await?);
+        crate::system::resource::track_child(platform_pty.process_id());
         let git_state = GitState::new();
         let diff_summary = DiffSummary::new();
-        let session_stats = SessionStats::new();
+        let history_store = HistoryStore::open_default();
+
+        // A resumed conversation keeps counting from the previous session's stats
+        let previous_session_id = previous_session_id();
+        let session_stats = match previous_session_id
+            .as_deref()
+            .and_then(|id| load_previous_stats(platform.as_ref(), id, Some(&history_store)))
+        {
+            Some(previous) => SessionStats::new_resumed(previous),
+            None => SessionStats::new(),
+        };
 
         // Get current working directory for platform stats
         let cwd = std::env::current_dir().unwrap_or_default();
         let cwd_str = cwd.to_string_lossy().to_string();
+        let wake = Arc::new(Notify::new());
+        let git_watcher = GitWatcher::start(&cwd, wake.clone());
+        let stats_watcher = platform
+            .stats_path(&cwd_str)
+            .map(|path| StatsWatcher::start(&path, wake.clone()))
+            .unwrap_or_default();
+        let session_start_commit = head_commit(&cwd).await;
+
+        let custom_widgets = plugin::widgets_from_config(&config.custom_widgets, &cwd);
 
         // Detect IDE from config or environment
-        let ide = Config::load()
-            .ok()
-            .and_then(|c| c.ide)
-            .and_then(|s| IdeKind::from_config(&s))
+        let ide = config
+            .ide
+            .as_deref()
+            .and_then(IdeKind::from_config)
             .unwrap_or_else(ide::detect_ide);
 
         // Create mirror publisher (always enabled for inspection by other instances)
         let session_id = std::env::var("CRABIGATOR_SESSION_ID").unwrap_or_default();
-        let mirror_publisher = MirrorPublisher::new(true, session_id.clone(), cwd_str.clone(), capture_enabled);
+        let mut mirror_publisher = MirrorPublisher::new(true, session_id.clone(), cwd_str.clone(), capture_enabled);
 
         // Create capture manager for output streaming
         let capture_config = CaptureConfig {
             enabled: capture_enabled,
             session_id: session_id.clone(),
+            format: match (record, config.capture_format) {
+                (Some(false), _) => CaptureFormat::Plain,
+                (Some(true), CaptureFormat::Plain) => CaptureFormat::Timed,
+                (_, format) => format,
+            },
+            compress: config.capture_compress,
+        };
+        let mut capture_manager = CaptureManager::new(capture_config)?;
+        capture_manager.record_resize(cols, pty_rows)?;
+        mirror_publisher.set_scrollback_path(capture_manager.scrollback_path());
+        mirror_publisher.set_platform(platform.kind().as_str());
+
+        // Connection telemetry is only worth collecting when profiling
+        let (telemetry_tx, telemetry_rx) = if std::env::var("CRABIGATOR_PROFILE").is_ok() {
+            let (tx, rx) = mpsc::channel(64);
+            (Some(tx), Some(rx))
+        } else {
+            (None, None)
         };
-        let capture_manager = CaptureManager::new(capture_config)?;
+        let telemetry_log_path = mirror_publisher.session_dir().join("telemetry.log");
 
         // Initialize cloud client (optional - don't fail if cloud is unreachable)
-        let cloud_client = Self::init_cloud_client(&session_id, &cwd_str, platform.as_ref()).await;
+        let cloud_client =
+            Self::init_cloud_client(&session_id, &cwd_str, platform.as_ref(), config, telemetry_tx).await;
+
+        let tmux_writer =
+            tmux_status_file.map(|path| TmuxStatusWriter::new(path, config.tmux_status_format.clone()));
 
         Ok(Self {
             running: true,
@@ -141,11 +325,26 @@ impl App {
             git_state,
             diff_summary,
             session_stats,
+            git_watcher,
+            stats_watcher,
+            task_queue: BackgroundTaskQueue::new(),
+            diff_cache: DiffCache::new(config.max_diff_bytes),
             last_mouse_event: None,
             total_rows: rows,
             total_cols: cols,
             pty_rows,
             status_rows,
+            status_bar,
+            status_bar_percent,
+            out: Box::new(stdout()),
+            frame: ByteWriter::new(),
+            renderer: StatusBarRenderer::new(),
+            kitty: KittyGraphicsBackend::new(),
+            git_refresh_interval: Duration::from_secs(3),
+            poll_timeout: FOCUSED_POLL_TIMEOUT,
+            wake,
+            #[cfg(test)]
+            git_refreshes: 0,
             cwd,
             ide,
             pty_rx,
@@ -153,10 +352,41 @@ impl App {
             capture_manager,
             dsr_handler: DsrHandler::new(),
             osc_scanner: OscScanner::new(),
+            mouse_tracker: MouseModeTracker::new(),
+            focus_scanner: FocusScanner::new(),
             terminal_title: None,
+            title_state: None,
+            crabigator_set_title: false,
             initial_git_time_ms: None,
             initial_diff_time_ms: None,
             cloud_client,
+            telemetry_rx,
+            telemetry_log_path,
+            tmux_writer,
+            history_store: Some(history_store),
+            previous_session_id,
+            max_git_files: max_git_files.or(config.max_git_files),
+            compact_threshold: config.compact_threshold,
+            separator_style: config.separator_style,
+            max_session_minutes: config.max_session_minutes,
+            token_budget: config.token_budget,
+            show_word_count: config.show_word_count,
+            hide_test_changes: config.hide_test_changes,
+            custom_widget_width: config.custom_widget_width,
+            widget_layout: config.layout.widgets.clone(),
+            custom_widgets,
+            incremental_diff: config.incremental_diff,
+            diff_mode: diff_mode.unwrap_or(config.diff_mode),
+            palette,
+            session_start_commit,
+            prefix_pending: false,
+            help_overlay: None,
+            message_overlay: None,
+            pending_preview: None,
+            exit_status: None,
+            notification_manager: NotificationManager::new(config.notify_on_complete),
+            last_notified_state: SessionState::default(),
+            git_refresh_requested: false,
             last_cloud_state: None,
             last_cloud_scrollback_lines: 0,
             last_cloud_title: None,
@@ -169,10 +399,21 @@ impl App {
         session_id: &str,
         cwd: &str,
         platform: &dyn Platform,
+        config: &Config,
+        telemetry: Option<TelemetrySender>,
     ) -> Option<CloudClient> {
         // Try to create cloud client
         let mut client = match CloudClient::new() {
-            Ok(c) => c,
+            Ok(c) => {
+                let c = c
+                    .with_batching(config.cloud_batch_size, config.cloud_batch_interval_ms)
+                    .with_jitter_factor(config.cloud_reconnect_jitter)
+                    .with_max_queue_bytes(config.cloud_max_queue_bytes);
+                match telemetry {
+                    Some(tx) => c.with_telemetry(tx),
+                    None => c,
+                }
+            }
             Err(e) => {
                 // Style: dim gray label, red X, dim error
                 eprintln!(
@@ -204,9 +445,83 @@ impl App {
         }
     }
 
+    /// Append this session to the history store for `crabigator summary`
+    fn record_history(&self) {
+        let Some(ref store) = self.history_store else {
+            return;
+        };
+        let stats = &self.session_stats.platform_stats;
+        // This session's own counts, with what it resumed from kept apart so
+        // totals aren't counted twice but a later resume can still add them up
+        let baseline = self.session_stats.baseline();
+        let session_id = std::env::var("CRABIGATOR_SESSION_ID").unwrap_or_default();
+        // The session directory is removed on cleanup, so take the name now
+        let project_name = TagStore::read(&self.mirror_publisher.session_dir()).and_then(|tags| tags.name);
+        let record = SessionRecord {
+            session_id,
+            platform: self.platform.kind().as_str().to_string(),
+            cwd: self.cwd.to_string_lossy().into_owned(),
+            project_name,
+            started_at: self.session_stats.started_unix(),
+            ended_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64(),
+            prompts: stats.prompts.saturating_sub(baseline.prompts),
+            completions: stats.completions.saturating_sub(baseline.completions),
+            thinking_seconds: self.session_stats.thinking_seconds(),
+            compressions: stats.compressions.saturating_sub(baseline.compressions),
+            tools: stats
+                .tools
+                .iter()
+                .map(|(tool, count)| {
+                    let carried = baseline.tools.get(tool).copied().unwrap_or(0);
+                    (tool.clone(), count.saturating_sub(carried))
+                })
+                .filter(|(_, count)| *count > 0)
+                .collect(),
+            input_tokens: stats.input_tokens.saturating_sub(baseline.input_tokens),
+            output_tokens: stats.output_tokens.saturating_sub(baseline.output_tokens),
+            files_modified: self.git_state.files.iter().map(|f| f.path.clone()).collect(),
+            carried: CarriedCounts::from_stats(baseline),
+        };
+        // Still on the alternate layout here, so a failure can't be shown
+        if let Err(e) = store.append(&record) {
+            tracing::warn!(error = %e, "failed to record session history");
+        }
+    }
+
+    /// Append pending cloud telemetry to telemetry.log (--profile only)
+    fn log_telemetry(&mut self) {
+        let Some(rx) = self.telemetry_rx.as_mut() else {
+            return;
+        };
+        let mut lines = String::new();
+        while let Ok(event) = rx.try_recv() {
+            lines.push_str(&format!("{} {}\n", chrono::Utc::now().to_rfc3339(), event));
+        }
+        if lines.is_empty() {
+            return;
+        }
+        if let Some(dir) = self.telemetry_log_path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(mut f) = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.telemetry_log_path)
+        {
+            let _ = f.write_all(lines.as_bytes());
+        }
+    }
+
     /// Set scroll region to constrain PTY output to top area
-    fn setup_scroll_region(&self, initial: bool) -> Result<()> {
-        let mut stdout = stdout();
+    fn setup_scroll_region(&mut self, initial: bool) -> Result<()> {
+        // Without a status bar the CLI keeps the whole screen
+        if self.status_bar == StatusBarEnabled::No {
+            return Ok(());
+        }
+        let stdout = &mut self.out;
 
         // On initial setup, scroll existing terminal content up to make room
         // for our status bar. This preserves the user's last commands.
@@ -216,6 +531,9 @@ impl App {
             write!(stdout, "{}", escape::scroll_up(self.status_rows))?;
         }
 
+        // Whatever the status rows showed may be gone after a resize
+        self.renderer.invalidate();
+
         // DECSTBM: Set Top and Bottom Margins (1-indexed)
         // This constrains scrolling to rows 1 through pty_rows
         write!(stdout, "{}", escape::scroll_region(1, self.pty_rows))?;
@@ -227,22 +545,29 @@ impl App {
     }
 
     /// Reset scroll region to full screen
-    fn reset_scroll_region(&self) -> Result<()> {
-        let mut stdout = stdout();
+    fn reset_scroll_region(&mut self) -> Result<()> {
+        let stdout = &mut self.out;
         write!(stdout, "{}", escape::SCROLL_REGION_RESET)?;
         stdout.flush()?;
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     pub async fn run(&mut self) -> Result<()> {
         let mut last_git_refresh = Instant::now();
         let mut last_hook_refresh = Instant::now();
         let mut last_status_draw = Instant::now();
         let mut last_throbber_draw = Instant::now();
-        let git_refresh_interval = Duration::from_secs(3);
-        let hook_refresh_interval = Duration::from_millis(500);
+        let git_refresh_interval = self.git_refresh_interval;
+        // With the stats file watched, the timer is only a heartbeat
+        let hook_refresh_interval = if self.stats_watcher.is_active() {
+            Duration::from_secs(2)
+        } else {
+            Duration::from_millis(500)
+        };
         let status_debounce = Duration::from_millis(100);
-        let throbber_interval = Duration::from_millis(100);
+        // Focused, the throbber spins every 100ms; unfocused, no faster than the poll
+        let throbber_interval = |poll_timeout: Duration| poll_timeout.max(Duration::from_millis(100));
 
         // Set up scroll region to constrain the CLI to the top area
         // Pass true to scroll existing content up and make room for status bar
@@ -251,64 +576,62 @@ impl App {
         // Initial status bar draw (shows "loading" state for git widgets)
         self.draw_status_bar()?;
 
-        // Channel for receiving background git refresh results
-        let (git_tx, mut git_rx) = mpsc::channel::<GitRefreshResult>(1);
-        let mut git_refresh_pending = true; // Start with refresh pending
-
-        // Spawn initial git refresh in background (non-blocking)
-        // This allows the PTY to be visible immediately while git loads
-        {
-            let tx = git_tx.clone();
-            tokio::spawn(async move {
-                let git_state_tmp = GitState::new();
-                let diff_summary_tmp = DiffSummary::new();
-
-                // Time each refresh separately
-                let git_start = Instant::now();
-                let git_result = git_state_tmp.refresh().await;
-                let git_time_ms = git_start.elapsed().as_millis() as u64;
-
-                let diff_start = Instant::now();
-                let diff_result = diff_summary_tmp.refresh().await;
-                let diff_time_ms = diff_start.elapsed().as_millis() as u64;
-
-                let git_state = git_result.unwrap_or_default();
-                let diff_summary = diff_result.unwrap_or_default();
-                let _ = tx.send(GitRefreshResult {
-                    git_state,
-                    diff_summary,
-                    git_time_ms,
-                    diff_time_ms,
-                }).await;
-            });
-        }
+        // Spawn initial git and diff refreshes in background (non-blocking)
+        // This allows the PTY to be visible immediately while git loads.
+        // Each holds its pending refresh's result receiver until it lands.
+        let mut git_rx = Some(self.spawn_git_refresh(GitState::new()));
+        let mut diff_rx = Some(self.spawn_diff_refresh(None));
 
         // Track whether we've sent an initial screen capture (after PTY has rendered)
         let mut sent_initial_screen = false;
         let session_start = std::time::Instant::now();
         let mut last_initial_screen_attempt = session_start;
+        // PTY output that cut the last wait short, already written
+        let mut woke_on_output = false;
 
         while self.running {
+            // The terminal went away: wind down as if the CLI had exited
+            if SIGNALS.hung_up() {
+                break;
+            }
+            // SIGWINCH seen outside crossterm's event stream (tmux, screen);
+            // skipped when an Event::Resize already caught up
+            if SIGNALS.take_resize_request() {
+                if let Ok((width, height)) = crossterm::terminal::size() {
+                    if (width, height) != (self.total_cols, self.total_rows) {
+                        self.handle_resize(width, height)?;
+                    }
+                }
+            }
+
             // Receive PTY output and write directly to stdout
-            let mut got_output = false;
+            let mut got_output = std::mem::take(&mut woke_on_output);
             while let Ok(data) = self.pty_rx.try_recv() {
                 self.write_pty_output(&data)?;
                 got_output = true;
             }
 
-            // Check for completed background git refresh (non-blocking)
-            if let Ok(result) = git_rx.try_recv() {
-                self.git_state = result.git_state;
-                self.diff_summary = result.diff_summary;
-                git_refresh_pending = false;
-
-                // Stream git + changes snapshot to cloud
-                self.send_cloud_git_changes_events();
-
-                // Capture initial timing (only set once, on first load)
-                if self.initial_git_time_ms.is_none() {
-                    self.initial_git_time_ms = Some(result.git_time_ms);
-                    self.initial_diff_time_ms = Some(result.diff_time_ms);
+            // Check for completed background refreshes (non-blocking).
+            // Initial timing is only captured once, on first load.
+            let mut refreshed = false;
+            if let Some(result) = take_finished(&mut git_rx) {
+                self.git_state = result.value;
+                self.initial_git_time_ms.get_or_insert(result.elapsed_ms);
+                refreshed = true;
+            }
+            if let Some(result) = take_finished(&mut diff_rx) {
+                self.diff_summary = result.value;
+                self.initial_diff_time_ms.get_or_insert(result.elapsed_ms);
+                refreshed = true;
+            }
+            if refreshed {
+                // Stream git + changes snapshot to cloud once both have landed
+                if git_rx.is_none() && diff_rx.is_none() {
+                    #[cfg(test)]
+                    {
+                        self.git_refreshes += 1;
+                    }
+                    self.send_cloud_git_changes_events();
                 }
 
                 // Redraw with new data
@@ -316,39 +639,36 @@ impl App {
                 last_status_draw = Instant::now();
             }
 
-            // Spawn background git refresh periodically (if not already pending)
-            if !git_refresh_pending && last_git_refresh.elapsed() >= git_refresh_interval {
-                git_refresh_pending = true;
+            // Spawn background refreshes periodically, when .git changed or on request (if not already pending)
+            if git_rx.is_none()
+                && diff_rx.is_none()
+                && (std::mem::take(&mut self.git_refresh_requested)
+                    || last_git_refresh.elapsed() >= git_refresh_interval
+                    || self.git_watcher.take_needs_refresh())
+            {
                 last_git_refresh = Instant::now();
-                let tx = git_tx.clone();
-                tokio::spawn(async move {
-                    let git_state_tmp = GitState::new();
-                    let diff_summary_tmp = DiffSummary::new();
-                    let (git_result, diff_result) = tokio::join!(
-                        git_state_tmp.refresh(),
-                        diff_summary_tmp.refresh()
-                    );
-                    let git_state = git_result.unwrap_or_default();
-                    let diff_summary = diff_result.unwrap_or_default();
-                    // Timing not tracked for periodic refreshes (only initial)
-                    let _ = tx.send(GitRefreshResult {
-                        git_state,
-                        diff_summary,
-                        git_time_ms: 0,
-                        diff_time_ms: 0,
-                    }).await;
-                });
+                // Refresh from the current state so per-branch caches carry over
+                git_rx = Some(self.spawn_git_refresh(self.git_state.clone()));
+                // Incremental refreshes build on the summary currently shown
+                diff_rx = Some(self.spawn_diff_refresh(self.incremental_diff.then(|| self.diff_summary.clone())));
             }
 
-            // Refresh platform stats more frequently and redraw if state changed
-            if last_hook_refresh.elapsed() >= hook_refresh_interval {
+            // Refresh platform stats when the hook writes them (or on the timer)
+            // and redraw if state changed
+            if self.stats_watcher.try_recv() || last_hook_refresh.elapsed() >= hook_refresh_interval {
                 let old_effective_state = self.session_stats.effective_state();
                 let old_last_updated = self.session_stats.platform_stats.last_updated;
                 self.session_stats
                     .refresh_platform_stats(self.platform.as_ref(), &self.cwd.to_string_lossy());
+                if self.session_stats.check_token_budget(self.token_budget) {
+                    // Stop whatever is running; input stays blocked until Ctrl+A u
+                    self.platform_pty.write(&[0x03])?;
+                }
                 let new_effective_state = self.session_stats.effective_state();
                 let new_last_updated = self.session_stats.platform_stats.last_updated;
 
+                self.notify_state_change(old_effective_state, new_effective_state)?;
+
                 // Redraw immediately if effective state changed (e.g., Thinking -> Complete, or Interrupted -> Thinking)
                 if old_effective_state != new_effective_state {
                     self.draw_status_bar()?;
@@ -374,6 +694,13 @@ impl App {
             // Check for commands from cloud (answers + key sequences)
             self.check_cloud_commands()?;
 
+            // Send any batched cloud events whose interval has elapsed
+            if let Some(ref mut client) = self.cloud_client {
+                client.check_connection();
+                client.flush_due_events();
+            }
+            self.log_telemetry();
+
             // Redraw status bar after PTY output settles (debounced)
             if got_output && last_status_draw.elapsed() >= status_debounce {
                 self.draw_status_bar()?;
@@ -381,12 +708,40 @@ impl App {
                 last_throbber_draw = Instant::now();
             }
 
+            // Show a commit or stats preview once it's built
+            if let Some(rx) = self.pending_preview.as_mut() {
+                match rx.try_recv() {
+                    Ok(preview) => {
+                        self.pending_preview = None;
+                        self.message_overlay = preview;
+                        self.draw_status_bar()?;
+                        last_status_draw = Instant::now();
+                    }
+                    Err(oneshot::error::TryRecvError::Closed) => self.pending_preview = None,
+                    Err(oneshot::error::TryRecvError::Empty) => {}
+                }
+            }
+
+            // Restore the widgets once the help overlay times out
+            let help_expired = self.help_overlay.as_ref().is_some_and(|overlay| !overlay.is_active());
+            let preview_expired = self.message_overlay.as_ref().is_some_and(|preview| !preview.is_active());
+            if help_expired || preview_expired {
+                if help_expired {
+                    self.help_overlay = None;
+                }
+                if preview_expired {
+                    self.message_overlay = None;
+                }
+                self.draw_status_bar()?;
+                last_status_draw = Instant::now();
+            }
+
             // Animate throbber independently when in active states (Thinking/Permission)
             let needs_throbber = matches!(
                 self.session_stats.effective_state(),
                 SessionState::Thinking | SessionState::Permission
             );
-            if needs_throbber && last_throbber_draw.elapsed() >= throbber_interval {
+            if needs_throbber && last_throbber_draw.elapsed() >= throbber_interval(self.poll_timeout) {
                 self.draw_status_bar()?;
                 last_throbber_draw = Instant::now();
             }
@@ -430,25 +785,40 @@ impl App {
             // Check if the platform CLI has exited
             if !self.platform_pty.is_running() {
                 self.running = false;
+                self.exit_status = self.platform_pty.exit_status();
                 break;
             }
 
-            // Poll for terminal events
-            if event::poll(Duration::from_millis(50))? {
-                match event::read()? {
+            // Wait for terminal input; PTY output (DSR queries want a prompt
+            // reply) and watcher events cut the wait short
+            let event = tokio::select! {
+                event = self.platform_pty.next_event(self.poll_timeout) => event?,
+                Some(data) = self.pty_rx.recv() => {
+                    self.write_pty_output(&data)?;
+                    woke_on_output = true;
+                    None
+                }
+                _ = self.wake.notified() => None,
+            };
+            if let Some(event) = event {
+                match event {
                     Event::Key(key) => {
                         self.handle_key_event(key).await?;
                     }
                     Event::Resize(width, height) => {
                         self.handle_resize(width, height)?;
                     }
+                    Event::Paste(_) if self.session_stats.is_paused() => {}
                     Event::Paste(text) => {
+                        self.session_stats.record_paste(text.len());
                         self.platform_pty.write(text.as_bytes())?;
                     }
                     Event::Mouse(mouse) => {
                         self.last_mouse_event = Some(mouse);
+                        self.forward_mouse_event(mouse)?;
                     }
-                    _ => {}
+                    Event::FocusGained => self.handle_focus_change(true)?,
+                    Event::FocusLost => self.handle_focus_change(false)?,
                 }
             }
         }
@@ -458,36 +828,79 @@ impl App {
             self.session_stats.tick();
             self.send_cloud_stats_event();
             let tool_calls = self.session_stats.platform_stats.total_tool_calls();
-            if let Some(ref client) = self.cloud_client {
-                let _ = client
+            if let Some(ref mut client) = self.cloud_client {
+                client.flush_events();
+                if let Err(e) = client
                     .end_session(
                         self.session_stats.platform_stats.prompts,
                         self.session_stats.platform_stats.completions,
                         tool_calls,
                         self.session_stats.thinking_seconds(),
                     )
-                    .await;
+                    .await
+                {
+                    tracing::warn!(error = %e, "failed to end cloud session");
+                }
             }
+            self.log_telemetry();
         }
 
+        self.record_history();
+
         // Clean up capture directory before exit
         self.capture_manager.cleanup();
 
         // Clean up mirror file before exit
         self.mirror_publisher.cleanup();
 
-        // Clean up stats file before exit
+        if let Some(ref writer) = self.tmux_writer {
+            writer.cleanup();
+        }
+
+        // Clean up stats file before exit (and the resumed session's, kept until now)
         self.platform.cleanup_stats(&self.cwd.to_string_lossy());
+        if let Some(path) = self.previous_session_id.as_deref().and_then(|id| self.platform.session_stats_path(id)) {
+            let _ = std::fs::remove_file(path);
+        }
 
-        // Reset scroll region before exit
+        self.restore_terminal_title()?;
+
+        // Remove the logo and reset scroll region before exit
+        self.kitty.clear(&mut self.out)?;
         self.reset_scroll_region()?;
 
         Ok(())
     }
 
+    /// Queue a git status refresh starting from `git_state`
+    fn spawn_git_refresh(&self, git_state: GitState) -> oneshot::Receiver<RefreshResult<GitState>> {
+        let cwd = self.cwd.clone();
+        self.task_queue.spawn(TaskPriority::High, async move {
+            let start = Instant::now();
+            let value = git_state.refresh_in_dir(&cwd).await.unwrap_or_default();
+            RefreshResult { value, elapsed_ms: start.elapsed().as_millis() as u64 }
+        })
+    }
+
+    /// Queue a diff summary refresh; with a `previous` summary only files whose
+    /// diff changed are reparsed
+    fn spawn_diff_refresh(&self, previous: Option<DiffSummary>) -> oneshot::Receiver<RefreshResult<DiffSummary>> {
+        let cwd = self.cwd.clone();
+        let diff_cache = self.diff_cache.clone();
+        let (diff_mode, start_commit) = (self.diff_mode, self.session_start_commit.clone());
+        self.task_queue.spawn(TaskPriority::Normal, async move {
+            let start = Instant::now();
+            let start_commit = start_commit.as_deref();
+            let summary = match &previous {
+                Some(previous) => diff_cache.refresh_incremental(&cwd, previous, diff_mode, start_commit).await,
+                None => diff_cache.refresh(&cwd, diff_mode, start_commit).await.map(|(summary, _)| summary),
+            };
+            RefreshResult { value: summary.unwrap_or_default(), elapsed_ms: start.elapsed().as_millis() as u64 }
+        })
+    }
+
     /// Write PTY output directly to stdout - transparent passthrough
     fn write_pty_output(&mut self, data: &[u8]) -> Result<()> {
-        let mut stdout = stdout();
         let mut wrote_output = false;
 
         let chunks = self.dsr_handler.scan(data);
@@ -508,13 +921,16 @@ impl App {
                     if passthrough.is_empty() {
                         continue;
                     }
+                    self.mouse_tracker.scan(&passthrough);
+                    self.focus_scanner.scan(&passthrough);
                     wrote_output = true;
                     // Capture through our internal vt100 parser
                     if let Err(e) = self.capture_manager.capture_output(&passthrough) {
-                        eprintln!("Capture error: {}", e);
+                        tracing::warn!(error = %e, "capture failed");
                     }
                     self.platform_pty.process_output(&passthrough);
-                    stdout.write_all(&passthrough)?;
+                    self.renderer.scan_output(&passthrough);
+                    self.out.write_all(&passthrough)?;
                 }
                 DsrChunk::Request => {
                     let (row, col) = self.platform_pty.screen().cursor_position();
@@ -528,7 +944,7 @@ impl App {
         }
 
         if wrote_output {
-            stdout.flush()?;
+            self.out.flush()?;
         }
 
         Ok(())
@@ -538,20 +954,86 @@ impl App {
     fn draw_status_bar(&mut self) -> Result<()> {
         // Update stats each draw
         self.session_stats.tick();
+        tracing::debug!(rows = self.status_rows, cols = self.total_cols, "draw status bar");
 
         let layout = Layout {
             pty_rows: self.pty_rows,
             total_cols: self.total_cols,
             status_rows: self.status_rows,
+            max_git_files: self.max_git_files,
+            compact_threshold: self.compact_threshold,
+            separator_style: self.separator_style,
+            max_session_minutes: self.max_session_minutes,
+            token_budget: self.token_budget,
+            show_word_count: self.show_word_count,
+            hide_test_changes: self.hide_test_changes,
+            custom_widget_width: self.custom_widget_width,
+            widgets: self.widget_layout.clone(),
+            palette: self.palette,
         };
 
+        if self.status_bar == StatusBarEnabled::Yes {
+            let overlay_drawn = self.draw_widgets(&layout)?;
+            self.renderer.flush_if_changed(&mut self.out, &mut self.frame)?;
+            if overlay_drawn {
+                return Ok(());
+            }
+        }
+
+        self.update_terminal_title()?;
+
+        if let Some(ref writer) = self.tmux_writer {
+            if let Err(e) = writer.update(&self.session_stats, &self.git_state) {
+                tracing::warn!(error = %e, "failed to write tmux status");
+            }
+        }
+
+        // Publish mirror state (throttled, only when --profile); the file is
+        // written on the background queue
+        match self.mirror_publisher.maybe_publish(
+            &self.session_stats,
+            &self.git_state,
+            &self.diff_summary,
+            self.terminal_title.as_deref(),
+            self.initial_git_time_ms,
+            self.initial_diff_time_ms,
+        ) {
+            Ok(Some(write)) => {
+                self.task_queue.spawn(TaskPriority::Low, async move {
+                    if let Err(e) = write.write() {
+                        tracing::warn!(error = %e, "failed to publish mirror");
+                    }
+                });
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "failed to publish mirror"),
+        }
+
+        Ok(())
+    }
+
+    /// Draw the status area into `frame`: an overlay when one is up (returns
+    /// true), otherwise the widgets
+    fn draw_widgets(&mut self, layout: &Layout) -> Result<bool> {
+        if let Some(overlay) = self.help_overlay.as_ref().filter(|o| o.is_active()) {
+            overlay.draw(&mut self.frame, layout)?;
+            return Ok(true);
+        }
+        if let Some(preview) = self.message_overlay.as_ref().filter(|p| p.is_active()) {
+            preview.draw(&mut self.frame, layout)?;
+            return Ok(true);
+        }
+        if let (true, Some(budget)) = (self.session_stats.is_paused(), self.token_budget) {
+            draw_budget_warning(&mut self.frame, layout, budget)?;
+            return Ok(true);
+        }
+
         // Get cloud status if connected
         let cloud_status = self.cloud_client.as_ref().map(|c| c.status());
 
-        let mut stdout = stdout();
         draw_status_bar(
-            &mut stdout,
-            &layout,
+            &mut self.frame,
+            layout,
             &self.session_stats,
             &self.git_state,
             &self.diff_summary,
@@ -559,18 +1041,52 @@ impl App {
             self.ide,
             &self.cwd,
             cloud_status.as_ref(),
+            &self.custom_widgets,
         )?;
 
-        // Publish mirror state (throttled, only when --profile)
-        let _ = self.mirror_publisher.maybe_publish(
-            &self.session_stats,
-            &self.git_state,
-            &self.diff_summary,
-            self.terminal_title.as_deref(),
-            self.initial_git_time_ms,
-            self.initial_diff_time_ms,
-        );
+        // Logo sits at the left end of the separator; it persists, so this
+        // only writes after startup and resizes
+        self.kitty
+            .draw_logo_once(&mut self.frame, self.platform.kind().logo_png(), self.pty_rows + 1)?;
+        Ok(false)
+    }
+
+    /// Send the completion notification on a Thinking → Complete transition
+    fn notify_state_change(&mut self, old: SessionState, new: SessionState) -> Result<()> {
+        if old == new || new == self.last_notified_state {
+            return Ok(());
+        }
+        self.last_notified_state = new;
+        if old == SessionState::Thinking && new == SessionState::Complete {
+            self.notification_manager.notify_completion(&self.session_stats, &mut self.out)?;
+        }
+        Ok(())
+    }
 
+    /// Show the session state in the window title when it changes
+    fn update_terminal_title(&mut self) -> Result<()> {
+        let state = self.session_stats.effective_state();
+        if self.title_state == Some(state) {
+            return Ok(());
+        }
+        self.title_state = Some(state);
+        match state_title(state, &self.git_state.branch) {
+            Some(title) => {
+                self.out.write_all(escape::set_terminal_title(&title).as_bytes())?;
+                self.crabigator_set_title = true;
+            }
+            None => self.restore_terminal_title()?,
+        }
+        Ok(())
+    }
+
+    /// Put back the CLI's last title if we replaced it
+    fn restore_terminal_title(&mut self) -> Result<()> {
+        if self.crabigator_set_title {
+            let title = self.terminal_title.as_deref().unwrap_or_default();
+            self.out.write_all(escape::set_terminal_title(title).as_bytes())?;
+            self.crabigator_set_title = false;
+        }
         Ok(())
     }
 
@@ -581,6 +1097,21 @@ impl App {
             return Ok(());
         }
 
+        // Ctrl+A prefix: the following key is a crabigator command
+        if self.prefix_pending {
+            self.prefix_pending = false;
+            return self.handle_prefix_command(key);
+        }
+        if key.code == KeyCode::Char('a') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.prefix_pending = true;
+            return Ok(());
+        }
+
+        // Out of token budget: nothing reaches the CLI until Ctrl+A u
+        if self.session_stats.is_paused() {
+            return Ok(());
+        }
+
         // Detect interrupt keys (ESC or Ctrl+C) while thinking
         let is_interrupt = key.code == KeyCode::Esc
             || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
@@ -593,7 +1124,122 @@ impl App {
             self.draw_status_bar()?;
         }
 
-        forward_key_to_pty(key, &mut self.platform_pty)?;
+        forward_key_to_pty(key, self.dsr_handler.cursor_mode(), self.platform_pty.as_mut())?;
+        Ok(())
+    }
+
+    /// Run the command bound to the key pressed after Ctrl+A
+    fn handle_prefix_command(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        match key.code {
+            KeyCode::Char('q') => self.running = false,
+            KeyCode::Char('a') => self.platform_pty.write(&[0x01])?,
+            KeyCode::Char('r') => self.git_refresh_requested = true,
+            KeyCode::Char('t') => {
+                let paused = !self.capture_manager.is_paused();
+                self.capture_manager.set_paused(paused);
+            }
+            KeyCode::Char('s') => self.send_screen_to_platform()?,
+            KeyCode::Char('u') => {
+                self.token_budget = None;
+                self.session_stats.unpause();
+                self.send_cloud_state_event(self.session_stats.effective_state());
+                self.draw_status_bar()?;
+            }
+            KeyCode::Char('m') => self.suggest_commit_message(),
+            KeyCode::Char('c') => self.copy_session_stats(),
+            KeyCode::Char('?') | KeyCode::Char('h') => {
+                self.help_overlay = Some(HelpOverlay::new(HELP_OVERLAY_TIMEOUT));
+                self.draw_status_bar()?;
+            }
+            // Not a binding: pass both keys through so nothing is swallowed
+            _ => {
+                self.platform_pty.write(&[0x01])?;
+                forward_key_to_pty(key, self.dsr_handler.cursor_mode(), self.platform_pty.as_mut())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Type the current screen into the CLI's input as a fenced block, for
+    /// asking about an error without copying it by hand (Ctrl+A s)
+    ///
+    /// Lines are joined with LF, which the CLIs take as a newline rather than
+    /// Enter, so the prompt waits for the user to add a question and submit.
+    /// A CLI that enabled bracketed paste gets the block as one paste.
+    fn send_screen_to_platform(&mut self) -> Result<()> {
+        let screen = strip_ansi(&self.platform_pty.screen().contents());
+        let formatted = format_screen_prompt(&screen, SEND_SCREEN_MAX_LINES);
+        if !self.dsr_handler.bracketed_paste() {
+            return self.platform_pty.write(formatted.as_bytes());
+        }
+        let mut bytes = Vec::with_capacity(formatted.len() + 2 * escape::key::PASTE_START.len());
+        bytes.extend_from_slice(&escape::key::PASTE_START);
+        bytes.extend_from_slice(formatted.as_bytes());
+        bytes.extend_from_slice(&escape::key::PASTE_END);
+        self.platform_pty.write(&bytes)
+    }
+
+    /// Suggest a commit message for the staged files and copy it to the
+    /// clipboard (Ctrl+A m)
+    ///
+    /// The staged paths pick the files, but the definitions named come from
+    /// the status bar's `DiffSummary`, which (in the default `working` diff
+    /// mode) also covers unstaged hunks of those files.
+    fn suggest_commit_message(&mut self) {
+        let (cwd, git_state, diff) = (self.cwd.clone(), self.git_state.clone(), self.diff_summary.clone());
+        self.build_preview(move || {
+            let staged = staged_paths(&cwd);
+            let git = GitState {
+                files: git_state.files.iter().filter(|f| staged.contains(&f.path)).cloned().collect(),
+                ..git_state
+            };
+            let suggestion = generate_commit_suggestion(&diff, &git);
+            let copied = !suggestion.is_empty() && copy_to_clipboard(&suggestion);
+            MessageOverlay::new(suggestion, copied, HELP_OVERLAY_TIMEOUT)
+        });
+    }
+
+    /// Copy this session's stats to the clipboard (Ctrl+A c)
+    fn copy_session_stats(&mut self) {
+        let session_id = std::env::var("CRABIGATOR_SESSION_ID").unwrap_or_default();
+        let summary = StatsSummary::from_session(&session_id, self.platform.kind().as_str(), &self.session_stats);
+        self.build_preview(move || {
+            let text = summary.format();
+            let copied = copy_to_clipboard(&text);
+            MessageOverlay::stats(text, copied, HELP_OVERLAY_TIMEOUT)
+        });
+    }
+
+    /// Run `build` on a blocking thread; the loop shows its preview when it lands
+    fn build_preview(&mut self, build: impl FnOnce() -> MessageOverlay + Send + 'static) {
+        self.pending_preview = Some(self.task_queue.spawn(TaskPriority::High, async move {
+            tokio::task::spawn_blocking(build).await.ok()
+        }));
+    }
+
+    /// Pass a mouse report on to the PTY if the child turned tracking on
+    ///
+    /// The PTY occupies the top rows at the same columns, so coordinates
+    /// carry over unchanged; clicks on the status bar are dropped.
+    fn forward_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
+        if !self.mouse_tracker.is_tracking() || mouse.row >= self.pty_rows {
+            return Ok(());
+        }
+        let bytes = encode_mouse_event(mouse, self.mouse_tracker.encoding());
+        if !bytes.is_empty() {
+            self.platform_pty.write(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Poll less while in the background, and tell the child if it asked
+    fn handle_focus_change(&mut self, gained: bool) -> Result<()> {
+        self.poll_timeout = if gained { FOCUSED_POLL_TIMEOUT } else { UNFOCUSED_POLL_TIMEOUT };
+        if self.focus_scanner.is_enabled() {
+            self.platform_pty.write(encode_focus(gained))?;
+        }
         Ok(())
     }
 
@@ -602,9 +1248,13 @@ impl App {
         self.total_rows = height;
 
         // Recalculate layout with same guards as App::new
-        // Guard: ensure max >= min for clamp (handles very short terminals)
-        self.status_rows = ((height as f32 * 0.2) as u16).clamp(2, height.saturating_sub(1).max(2));
-        self.pty_rows = height.saturating_sub(self.status_rows).max(1);
+        (self.status_rows, self.pty_rows) = split_rows(height, self.status_bar, self.status_bar_percent);
+        if let Err(e) = self.capture_manager.record_resize(width, self.pty_rows) {
+            tracing::warn!(error = %e, "capture failed");
+        }
+
+        // The logo would be left behind at the old separator row
+        self.kitty.clear(&mut self.out)?;
 
         // Re-setup scroll region for new size (not initial, don't scroll content)
         self.setup_scroll_region(false)?;
@@ -739,6 +1389,47 @@ impl App {
     }
 }
 
+/// Whether crabigator draws its status bar (`--no-status-bar` turns it off)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusBarEnabled {
+    Yes,
+    /// Pure PTY passthrough; hooks, capture and mirror publishing still run
+    No,
+}
+
+/// Output of a finished background task, clearing `rx` so another can be
+/// spawned; a task dropped without a result clears it too
+fn take_finished<T>(rx: &mut Option<oneshot::Receiver<T>>) -> Option<T> {
+    let result = rx.as_mut()?.try_recv();
+    match result {
+        Ok(value) => {
+            *rx = None;
+            Some(value)
+        }
+        Err(oneshot::error::TryRecvError::Closed) => {
+            *rx = None;
+            None
+        }
+        Err(oneshot::error::TryRecvError::Empty) => None,
+    }
+}
+
+/// Split the terminal into (status_rows, pty_rows)
+///
+/// Reserve the bottom `percent`% for our status widgets (minimum 2 rows:
+/// separator + header, whatever the percentage)
+/// Also ensure pty_rows is at least 1 to avoid PTY errors
+/// Guard: ensure max >= min for clamp (handles very short terminals)
+fn split_rows(rows: u16, status_bar: StatusBarEnabled, percent: u8) -> (u16, u16) {
+    if status_bar == StatusBarEnabled::No {
+        return (0, rows.max(1));
+    }
+    let share = (rows as u32 * percent as u32 / 100) as u16;
+    let status_rows = share.clamp(2, rows.saturating_sub(1).max(2));
+    let pty_rows = rows.saturating_sub(status_rows).max(1);
+    (status_rows, pty_rows)
+}
+
 fn session_state_label(state: SessionState) -> &'static str {
     match state {
         SessionState::Ready => "ready",
@@ -747,5 +1438,500 @@ fn session_state_label(state: SessionState) -> &'static str {
         SessionState::Question => "question",
         SessionState::Complete => "complete",
         SessionState::Interrupted => "interrupted",
+        SessionState::Paused => "paused",
+    }
+}
+
+/// Most screen lines Ctrl+A s sends; the bottom of the screen is kept
+const SEND_SCREEN_MAX_LINES: usize = 50;
+
+/// `Here is the current screen output:` and the screen in a fenced block,
+/// without trailing blank lines and cut to its last `max_lines` lines
+fn format_screen_prompt(screen: &str, max_lines: usize) -> String {
+    let mut lines: Vec<&str> = screen.lines().map(str::trim_end).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let skipped = lines.len().saturating_sub(max_lines);
+
+    let mut out = String::from("Here is the current screen output:\n```\n");
+    if skipped > 0 {
+        out.push_str(&format!("... ({} earlier lines omitted)\n", skipped));
+    }
+    for line in &lines[skipped..] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str("```\n");
+    out
+}
+
+/// Window title for a session state, or None to leave the CLI's title
+fn state_title(state: SessionState, branch: &str) -> Option<String> {
+    let with_branch = |title: &str| {
+        if branch.is_empty() {
+            title.to_string()
+        } else {
+            format!("{} - {}", title, branch)
+        }
+    };
+    match state {
+        SessionState::Thinking => Some(with_branch("🦀 Thinking")),
+        SessionState::Complete => Some(with_branch("✓ crabigator")),
+        SessionState::Question => Some("? crabigator".to_string()),
+        SessionState::Paused => Some("⏸ Token budget exhausted".to_string()),
+        SessionState::Ready | SessionState::Permission | SessionState::Interrupted => None,
+    }
+}
+
+/// App wired to a scripted PTY and an in-memory terminal
+#[cfg(test)]
+pub struct TestApp {
+    pub app: App,
+    pub output: crate::terminal::mock::SharedOutput,
+    pub record: std::sync::Arc<std::sync::Mutex<crate::terminal::mock::MockRecord>>,
+    /// Feeds PTY output outside the mock's script
+    pub pty_tx: mpsc::Sender<Vec<u8>>,
+    _cwd: tempfile::TempDir,
+}
+
+#[cfg(test)]
+impl TestApp {
+    /// Build an App around `mock_platform_pty`, bypassing `PlatformPty::new`
+    ///
+    /// Capture, mirror publishing and cloud streaming are all disabled.
+    pub fn new(mock_platform_pty: crate::terminal::mock::MockPlatformPty, cols: u16, rows: u16) -> Self {
+        Self::with_status_bar(mock_platform_pty, cols, rows, StatusBarEnabled::Yes)
+    }
+
+    /// `new`, with the status bar on or off
+    pub fn with_status_bar(
+        mut mock_platform_pty: crate::terminal::mock::MockPlatformPty,
+        cols: u16,
+        rows: u16,
+        status_bar: StatusBarEnabled,
+    ) -> Self {
+        use crate::platforms::{platform_for, PlatformKind};
+        use crate::terminal::mock::SharedOutput;
+
+        let (pty_tx, pty_rx) = mpsc::channel(256);
+        mock_platform_pty.connect(pty_tx.clone());
+        let status_bar_percent = Config::default().status_bar_height_percent;
+        let (status_rows, pty_rows) = split_rows(rows, status_bar, status_bar_percent);
+        mock_platform_pty.resize(cols, pty_rows).unwrap();
+        let record = mock_platform_pty.record();
+
+        // Platform stats files are keyed by cwd, so keep them away from real sessions
+        let cwd = tempfile::tempdir().unwrap();
+        let output = SharedOutput::default();
+        let capture_manager = CaptureManager::new(CaptureConfig {
+            enabled: false,
+            session_id: String::new(),
+            format: CaptureFormat::Plain,
+            compress: false,
+        })
+        .unwrap();
+        record.lock().unwrap().resizes.clear();
+
+        let app = App {
+            running: true,
+            platform: platform_for(PlatformKind::Claude),
+            platform_pty: Box::new(mock_platform_pty),
+            git_state: GitState::new(),
+            diff_summary: DiffSummary::new(),
+            session_stats: SessionStats::new(),
+            git_watcher: GitWatcher::start(cwd.path(), Arc::default()),
+            stats_watcher: StatsWatcher::default(),
+            task_queue: BackgroundTaskQueue::new(),
+            diff_cache: DiffCache::default(),
+            last_mouse_event: None,
+            total_rows: rows,
+            total_cols: cols,
+            pty_rows,
+            status_rows,
+            status_bar,
+            status_bar_percent,
+            out: Box::new(output.clone()),
+            frame: ByteWriter::new(),
+            renderer: StatusBarRenderer::new(),
+            kitty: KittyGraphicsBackend::inactive(),
+            git_refresh_interval: Duration::from_secs(3),
+            poll_timeout: FOCUSED_POLL_TIMEOUT,
+            wake: Arc::default(),
+            git_refreshes: 0,
+            cwd: cwd.path().to_path_buf(),
+            ide: IdeKind::None,
+            pty_rx,
+            mirror_publisher: MirrorPublisher::new(false, String::new(), String::new(), false),
+            capture_manager,
+            dsr_handler: DsrHandler::new(),
+            osc_scanner: OscScanner::new(),
+            mouse_tracker: MouseModeTracker::new(),
+            focus_scanner: FocusScanner::new(),
+            terminal_title: None,
+            title_state: None,
+            crabigator_set_title: false,
+            initial_git_time_ms: None,
+            initial_diff_time_ms: None,
+            cloud_client: None,
+            telemetry_rx: None,
+            telemetry_log_path: std::path::PathBuf::new(),
+            tmux_writer: None,
+            history_store: None,
+            previous_session_id: None,
+            max_git_files: None,
+            compact_threshold: 80,
+            separator_style: SeparatorStyle::default(),
+            max_session_minutes: None,
+            token_budget: None,
+            show_word_count: false,
+            hide_test_changes: false,
+            custom_widget_width: 24,
+            widget_layout: Vec::new(),
+            custom_widgets: Vec::new(),
+            incremental_diff: false,
+            diff_mode: DiffMode::Working,
+            palette: Palette::default(),
+            session_start_commit: None,
+            prefix_pending: false,
+            help_overlay: None,
+            message_overlay: None,
+            pending_preview: None,
+            exit_status: None,
+            notification_manager: NotificationManager::new(crate::notify::NotifyMethod::None),
+            last_notified_state: SessionState::default(),
+            git_refresh_requested: false,
+            last_cloud_state: None,
+            last_cloud_scrollback_lines: 0,
+            last_cloud_title: None,
+            cloud_stats_sent: false,
+        };
+
+        Self {
+            app,
+            output,
+            record,
+            pty_tx,
+            _cwd: cwd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::mock::MockPlatformPty;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_no_status_bar_gives_pty_full_screen() {
+        let mock = MockPlatformPty::new(vec![b"hello".to_vec()], vec![]);
+        let mut t = TestApp::with_status_bar(mock, 80, 24, StatusBarEnabled::No);
+        assert_eq!((t.app.status_rows, t.app.pty_rows), (0, t.app.total_rows));
+        t.app.run().await.unwrap();
+
+        let output = t.output.contents();
+        assert!(output.contains("hello"));
+        assert!(!output.contains(&escape::scroll_region(1, 24)));
+        assert!(!output.contains('━'));
+        assert_eq!(split_rows(24, StatusBarEnabled::No, 20), (0, 24));
+        assert_eq!(split_rows(24, StatusBarEnabled::Yes, 20), (4, 20));
+    }
+
+    #[test]
+    fn test_split_rows_percentage() {
+        assert_eq!(split_rows(24, StatusBarEnabled::Yes, 10), (2, 22));
+        assert_eq!(split_rows(24, StatusBarEnabled::Yes, 40), (9, 15));
+        assert_eq!(split_rows(50, StatusBarEnabled::Yes, 40), (20, 30));
+        // Very short terminals keep separator + one content row
+        assert_eq!(split_rows(10, StatusBarEnabled::Yes, 20), (2, 8));
+        assert_eq!(split_rows(3, StatusBarEnabled::Yes, 10), (2, 1));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sets_up_scroll_region() {
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], vec![]), 80, 24);
+        t.app.run().await.unwrap();
+
+        let output = t.output.contents();
+        assert_eq!((t.app.status_rows, t.app.pty_rows), (4, 20));
+        assert!(output.starts_with(&escape::cursor_to(24, 1)));
+        assert!(output.contains(&escape::scroll_region(1, 20)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_redraws_status_bar_after_pty_output() {
+        // The title only reaches the screen through a status bar redraw
+        let mut frames = vec![b"\x1b]0;Mock Title\x07hello from mock\r\n".to_vec()];
+        frames.extend(std::iter::repeat_n(b".".to_vec(), 4));
+        let mut t = TestApp::new(MockPlatformPty::new(frames, vec![]), 120, 24);
+        t.app.run().await.unwrap();
+
+        let output = t.output.contents();
+        let hello = output.find("hello from mock").expect("PTY output passed through");
+        let redraw = output[hello..]
+            .find(&escape::cursor_to(21, 1))
+            .expect("status bar redrawn after output");
+        assert!(output[hello + redraw..].contains("Mock Title"));
+    }
+
+    #[test]
+    fn test_state_titles() {
+        assert_eq!(state_title(SessionState::Thinking, "main").as_deref(), Some("🦀 Thinking - main"));
+        assert_eq!(state_title(SessionState::Complete, "main").as_deref(), Some("✓ crabigator - main"));
+        assert_eq!(state_title(SessionState::Complete, "").as_deref(), Some("✓ crabigator"));
+        assert_eq!(state_title(SessionState::Question, "main").as_deref(), Some("? crabigator"));
+        assert_eq!(state_title(SessionState::Ready, "main"), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_title_follows_state_and_restores_cli_title() {
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], vec![]), 80, 24);
+        t.app.terminal_title = Some("Claude Code".to_string());
+        t.app.git_state.branch = "main".to_string();
+        t.app.draw_status_bar().unwrap();
+        assert!(!t.output.contents().contains("\x1b]2;"));
+
+        t.app.session_stats.platform_stats.state = SessionState::Thinking;
+        t.app.draw_status_bar().unwrap();
+        t.app.draw_status_bar().unwrap();
+        assert_eq!(t.output.contents().matches("\x1b]2;🦀 Thinking - main\x07").count(), 1);
+
+        t.app.session_stats.platform_stats.state = SessionState::Ready;
+        t.app.draw_status_bar().unwrap();
+        assert!(t.output.contents().contains("\x1b]2;Claude Code\x07"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_completion_notifies_once_per_transition() {
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], vec![]), 80, 24);
+        t.app.notification_manager = NotificationManager::new(crate::notify::NotifyMethod::Bell);
+        let bells = |t: &TestApp| t.output.contents().matches('\x07').count();
+
+        t.app.notify_state_change(SessionState::Thinking, SessionState::Complete).unwrap();
+        t.app.notify_state_change(SessionState::Thinking, SessionState::Complete).unwrap();
+        assert_eq!(bells(&t), 1);
+
+        // Only finishing a task notifies
+        t.app.notify_state_change(SessionState::Complete, SessionState::Ready).unwrap();
+        t.app.notify_state_change(SessionState::Ready, SessionState::Complete).unwrap();
+        assert_eq!(bells(&t), 1);
+
+        t.app.notify_state_change(SessionState::Complete, SessionState::Thinking).unwrap();
+        t.app.notify_state_change(SessionState::Thinking, SessionState::Complete).unwrap();
+        assert_eq!(bells(&t), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_resize_updates_layout() {
+        let mock = MockPlatformPty::new(vec![], vec![]).with_event(Event::Resize(100, 50));
+        let mut t = TestApp::new(mock, 80, 24);
+        t.app.run().await.unwrap();
+
+        assert_eq!((t.app.total_cols, t.app.total_rows), (100, 50));
+        assert_eq!((t.app.pty_rows, t.app.status_rows), (40, 10));
+        assert_eq!(t.record.lock().unwrap().resizes, vec![(100, 40)]);
+        assert!(t.output.contents().contains(&escape::scroll_region(1, 40)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_records_cli_exit_status() {
+        let mock = MockPlatformPty::new(vec![], vec![]).with_exit(PtyExit::Signal("SIGSEGV".to_string()));
+        let mut t = TestApp::new(mock, 80, 24);
+        t.app.run().await.unwrap();
+        assert_eq!(t.app.exit_status, Some(PtyExit::Signal("SIGSEGV".to_string())));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_git_refreshes_after_interval() {
+        let mock = MockPlatformPty::new(vec![], vec![]).with_idle(Duration::from_millis(500));
+        let mut t = TestApp::new(mock, 80, 24);
+        t.app.git_refresh_interval = Duration::from_millis(50);
+        t.app.run().await.unwrap();
+
+        assert!(t.app.git_refreshes >= 2, "only {} git refreshes", t.app.git_refreshes);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_forwards_keys_and_exits_when_cli_ends() {
+        let keys = vec![
+            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        ];
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], keys), 80, 24);
+        t.app.run().await.unwrap();
+
+        assert!(!t.app.running);
+        assert_eq!(t.record.lock().unwrap().written, b"hi\r");
+        assert!(t.output.contents().ends_with(escape::SCROLL_REGION_RESET));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_token_budget_pause_blocks_keys_until_unset() {
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let keys = vec![key('x'), ctrl_a, key('u'), key('y')];
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], keys), 100, 24);
+        t.app.token_budget = Some(1_000);
+        t.app.session_stats.total_tokens = 1_200;
+        assert!(t.app.session_stats.check_token_budget(t.app.token_budget));
+        t.app.run().await.unwrap();
+
+        // Only the key after Ctrl+A u reaches the CLI
+        assert_eq!(t.record.lock().unwrap().written, b"y");
+        assert!(!t.app.session_stats.is_paused());
+        assert_eq!(t.app.token_budget, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ctrl_a_prefix_commands() {
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let keys = vec![ctrl_a, key('a'), ctrl_a, key('x'), ctrl_a, key('t'), ctrl_a, key('q')];
+        // Stays alive well past the script, so only Ctrl+A q can end the loop quickly
+        let mock = MockPlatformPty::new(vec![], keys).with_idle(Duration::from_secs(30));
+        let mut t = TestApp::new(mock, 80, 24);
+
+        let started = Instant::now();
+        t.app.run().await.unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(t.app.capture_manager.is_paused());
+        // Ctrl+A a sends a literal Ctrl+A; unbound keys pass through with it
+        assert_eq!(t.record.lock().unwrap().written, b"\x01\x01x");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_forwards_mouse_once_child_enables_tracking() {
+        use crossterm::event::{MouseButton, MouseEventKind};
+        let click = |row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 2,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let frames = vec![b"\x1b[?1000h\x1b[?1006h".to_vec()];
+        // The second click lands on the status bar
+        let mock = MockPlatformPty::new(frames, vec![]).with_event(click(4)).with_event(click(21));
+        let mut t = TestApp::new(mock, 80, 24);
+        t.app.run().await.unwrap();
+        assert_eq!(t.record.lock().unwrap().written, b"\x1b[<0;3;5M");
+
+        // Without tracking, clicks stay with the real terminal
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], vec![]).with_event(click(4)), 80, 24);
+        t.app.run().await.unwrap();
+        assert!(t.record.lock().unwrap().written.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_focus_changes_poll_rate_and_reach_child() {
+        let mock = MockPlatformPty::new(vec![], vec![]).with_event(Event::FocusLost);
+        let mut t = TestApp::new(mock, 80, 24);
+        t.app.run().await.unwrap();
+        assert_eq!(t.app.poll_timeout, UNFOCUSED_POLL_TIMEOUT);
+        // The child never asked for focus reports
+        assert!(t.record.lock().unwrap().written.is_empty());
+
+        let frames = vec![b"\x1b[?1004h".to_vec()];
+        let mock = MockPlatformPty::new(frames, vec![])
+            .with_event(Event::FocusLost)
+            .with_event(Event::FocusGained);
+        let mut t = TestApp::new(mock, 80, 24);
+        t.app.run().await.unwrap();
+        assert_eq!(t.app.poll_timeout, FOCUSED_POLL_TIMEOUT);
+        assert_eq!(t.record.lock().unwrap().written, b"\x1b[O\x1b[I");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unfocused_loop_wakes_on_pty_output() {
+        let mock = MockPlatformPty::new(vec![], vec![])
+            .with_event(Event::FocusLost)
+            .with_idle(Duration::from_secs(2));
+        let mut t = TestApp::new(mock, 80, 24);
+        let (pty_tx, record) = (t.pty_tx.clone(), t.record.clone());
+
+        // A cursor position query lands mid-way through a slow poll
+        let query = async move {
+            tokio::time::sleep(Duration::from_millis(700)).await;
+            let sent = Instant::now();
+            pty_tx.send(b"\x1b[6n".to_vec()).await.unwrap();
+            while record.lock().unwrap().written.is_empty() {
+                assert!(sent.elapsed() < Duration::from_secs(2), "DSR query never answered");
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            sent.elapsed()
+        };
+        let (result, latency) = tokio::join!(t.app.run(), query);
+        result.unwrap();
+        assert_eq!(t.app.poll_timeout, UNFOCUSED_POLL_TIMEOUT);
+        assert!(latency < Duration::from_millis(250), "replied after {:?}", latency);
+    }
+
+    #[test]
+    fn test_format_screen_prompt() {
+        let screen = "$ cargo test\nerror[E0425]: cannot find value `x`   \n\n\n";
+        assert_eq!(
+            format_screen_prompt(screen, 50),
+            "Here is the current screen output:\n```\n$ cargo test\nerror[E0425]: cannot find value `x`\n```\n"
+        );
+
+        let long: String = (1..=60).map(|n| format!("line {}\n", n)).collect();
+        let formatted = format_screen_prompt(&long, 50);
+        assert!(formatted.contains("```\n... (10 earlier lines omitted)\nline 11\n"));
+        assert!(formatted.ends_with("line 60\n```\n"));
+        assert!(!formatted.contains("line 10\n"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ctrl_a_s_sends_screen() {
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let keys = vec![ctrl_a, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE)];
+        let frames = vec![b"\x1b[31mpanicked at src/main.rs:4\x1b[0m\r\n".to_vec()];
+        let mut t = TestApp::new(MockPlatformPty::new(frames, keys), 80, 24);
+        t.app.run().await.unwrap();
+        assert_eq!(
+            String::from_utf8(t.record.lock().unwrap().written.clone()).unwrap(),
+            "Here is the current screen output:\n```\npanicked at src/main.rs:4\n```\n"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ctrl_a_s_uses_bracketed_paste() {
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let keys = vec![ctrl_a, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE)];
+        let frames = vec![b"\x1b[?2004hpanicked at src/main.rs:4\r\n".to_vec()];
+        let mut t = TestApp::new(MockPlatformPty::new(frames, keys), 80, 24);
+        t.app.run().await.unwrap();
+        assert_eq!(
+            String::from_utf8(t.record.lock().unwrap().written.clone()).unwrap(),
+            "\x1b[200~Here is the current screen output:\n```\npanicked at src/main.rs:4\n```\n\x1b[201~"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_help_overlay_replaces_widgets() {
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let keys = vec![ctrl_a, KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE)];
+        let mut t = TestApp::new(MockPlatformPty::new(vec![], keys), 100, 24);
+        t.app.run().await.unwrap();
+
+        assert!(t.app.help_overlay.as_ref().is_some_and(|o| o.is_active()));
+        assert!(t.output.contents().contains("r=refresh git"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_commit_preview_without_staged_changes() {
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let keys = vec![ctrl_a, KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE)];
+        // Built off the loop, so stay up long enough for it to land
+        let mock = MockPlatformPty::new(vec![], keys).with_idle(Duration::from_millis(500));
+        let mut t = TestApp::new(mock, 100, 24);
+        t.app.run().await.unwrap();
+
+        assert!(t.app.message_overlay.as_ref().is_some_and(|p| p.is_active()));
+        assert!(t.output.contents().contains("nothing staged"));
     }
 }
@@ -0,0 +1,192 @@
+//! Background task queue for non-critical async work
+//!
+//! Git refreshes, diff parsing and mirror publishing all run off the main
+//! loop. Rather than each call site doing its own `tokio::spawn`, they go
+//! through a shared pool capped at `MAX_CONCURRENT` tasks. Work that arrives
+//! while the pool is saturated waits in a buffer and is started highest
+//! priority first (FIFO within a priority) as permits free up.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::BoxFuture;
+use tokio::sync::{oneshot, Semaphore};
+
+/// Tasks allowed to run at once
+const MAX_CONCURRENT: usize = 4;
+
+/// Which buffered task starts first when the pool frees up
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaskPriority {
+    /// Mirror publishing and other bookkeeping
+    Low,
+    /// Diff parsing
+    Normal,
+    /// Git status refresh
+    High,
+}
+
+/// Bounded pool for background work, shared by cloning
+#[derive(Clone)]
+pub struct BackgroundTaskQueue {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    permits: Arc<Semaphore>,
+    /// Waiting tasks, kept sorted highest priority first
+    pending: Mutex<VecDeque<(TaskPriority, BoxFuture<'static, ()>)>>,
+}
+
+impl BackgroundTaskQueue {
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_CONCURRENT)
+    }
+
+    /// Pool running at most `max_concurrent` tasks at once
+    pub fn with_capacity(max_concurrent: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                permits: Arc::new(Semaphore::new(max_concurrent)),
+                pending: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    /// Queue `task`, returning a receiver for its output
+    ///
+    /// The task starts immediately if the pool has room. Dropping the
+    /// receiver doesn't cancel it. Must be called from within a tokio runtime.
+    pub fn spawn<F, T>(&self, priority: TaskPriority, task: F) -> oneshot::Receiver<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let job: BoxFuture<'static, ()> = Box::pin(async move {
+            let _ = tx.send(task.await);
+        });
+
+        {
+            let mut pending = self.lock_pending();
+            let at = pending
+                .iter()
+                .position(|(p, _)| *p < priority)
+                .unwrap_or(pending.len());
+            pending.insert(at, (priority, job));
+        }
+        self.dispatch();
+        rx
+    }
+
+    /// Tasks waiting for a free slot
+    #[cfg(test)]
+    pub fn pending_len(&self) -> usize {
+        self.lock_pending().len()
+    }
+
+    /// Start buffered tasks until the pool is full or the buffer is empty
+    fn dispatch(&self) {
+        let mut pending = self.lock_pending();
+        while !pending.is_empty() {
+            let Ok(permit) = self.inner.permits.clone().try_acquire_owned() else {
+                break;
+            };
+            let Some((_, job)) = pending.pop_front() else {
+                break;
+            };
+            let queue = self.clone();
+            tokio::spawn(async move {
+                job.await;
+                drop(permit);
+                queue.dispatch();
+            });
+        }
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, VecDeque<(TaskPriority, BoxFuture<'static, ()>)>> {
+        self.inner.pending.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+impl Default for BackgroundTaskQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_results_come_back_on_the_receiver() {
+        let queue = BackgroundTaskQueue::new();
+        let rx = queue.spawn(TaskPriority::Normal, async { 6 * 7 });
+        assert_eq!(rx.await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_tasks_start_by_priority() {
+        let queue = BackgroundTaskQueue::with_capacity(1);
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // Occupy the only slot so everything after it is buffered
+        let (release, blocked) = oneshot::channel::<()>();
+        let blocker = queue.spawn(TaskPriority::High, async move {
+            let _ = blocked.await;
+        });
+
+        let receivers: Vec<_> = [
+            (TaskPriority::Low, "low"),
+            (TaskPriority::Normal, "normal-1"),
+            (TaskPriority::High, "high"),
+            (TaskPriority::Normal, "normal-2"),
+        ]
+        .into_iter()
+        .map(|(priority, name)| {
+            let order = order.clone();
+            queue.spawn(priority, async move { order.lock().unwrap().push(name) })
+        })
+        .collect();
+        assert_eq!(queue.pending_len(), 4);
+
+        release.send(()).unwrap();
+        blocker.await.unwrap();
+        for rx in receivers {
+            rx.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec!["high", "normal-1", "normal-2", "low"]);
+    }
+
+    #[tokio::test]
+    async fn test_saturated_pool_applies_backpressure() {
+        let queue = BackgroundTaskQueue::new();
+        let mut releases = Vec::new();
+        let mut receivers = Vec::new();
+        for _ in 0..MAX_CONCURRENT + 2 {
+            let (release, blocked) = oneshot::channel::<()>();
+            releases.push(release);
+            receivers.push(queue.spawn(TaskPriority::Normal, async move {
+                let _ = blocked.await;
+            }));
+        }
+        // Only MAX_CONCURRENT run; the rest wait for a slot
+        assert_eq!(queue.pending_len(), 2);
+
+        // Finishing one task lets exactly one buffered task start
+        releases.remove(0).send(()).unwrap();
+        receivers.remove(0).await.unwrap();
+        tokio::task::yield_now().await;
+        assert_eq!(queue.pending_len(), 1);
+
+        for release in releases {
+            let _ = release.send(());
+        }
+        for rx in receivers {
+            rx.await.unwrap();
+        }
+        assert_eq!(queue.pending_len(), 0);
+    }
+}
@@ -3,18 +3,27 @@
 //! Prints styled session start/end banners with version, platform, and date.
 
 use crate::platforms::PlatformKind;
-use crate::terminal::escape::{BOLD, FG_BLUE, FG_CYAN, FG_GRAY, FG_ORANGE, FG_PURPLE, RESET};
-#[cfg(debug_assertions)]
-use crate::terminal::escape::DIM;
+use crate::terminal::escape::{ansi, color, BOLD, DIM, RESET};
+use crate::terminal::theme::Palette;
+use crate::terminal::PtyExit;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Print session info banner with file paths
 #[allow(unused_variables)]
-pub fn print_session_banner(session_id: &str, platform: PlatformKind, cols: u16) {
+pub fn print_session_banner(
+    session_id: &str,
+    platform: PlatformKind,
+    cols: u16,
+    passthrough: bool,
+    resumed_from: Option<&str>,
+    palette: &Palette,
+) {
     use chrono::Local;
 
     println!();
+    let [gray, orange, cyan, purple, blue] =
+        [color::GRAY, color::ORANGE, color::CYAN, color::PURPLE, color::BLUE].map(|c| palette.fg(c));
 
     // Format date: "Saturday, December 21st, 2025 5:58 PM"
     let now = Local::now();
@@ -27,12 +36,16 @@ pub fn print_session_banner(session_id: &str, platform: PlatformKind, cols: u16)
     let date_str = format!("{}, {} {}{}, {} {}", day, month, date_num, suffix, year, time);
 
     // Header line: 🦀 Crabigator v0.1.0 ⛵ ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ Platform · Date
-    let platform_name = platform.display_name();
-    let version_str = format!("{FG_GRAY}v{VERSION}{RESET}");
+    let platform_name = if passthrough {
+        format!("{} (no status bar)", platform.display_name())
+    } else {
+        platform.display_name().to_string()
+    };
+    let version_str = format!("{gray}v{VERSION}{RESET}");
     let title = format!(
-        "{FG_ORANGE}🦀{RESET} {BOLD}{FG_CYAN}Crabigator{RESET} {version_str} {FG_ORANGE}⛵{RESET}"
+        "{orange}🦀{RESET} {BOLD}{cyan}Crabigator{RESET} {version_str} {orange}⛵{RESET}"
     );
-    let right_side = format!("{FG_PURPLE}{platform_name}{RESET} {FG_BLUE}·{RESET} {FG_BLUE}{date_str}{RESET}");
+    let right_side = format!("{purple}{platform_name}{RESET} {blue}·{RESET} {blue}{date_str}{RESET}");
 
     // Plain lengths
     let version_plain_len = 1 + VERSION.len(); // "v" + version
@@ -40,24 +53,53 @@ pub fn print_session_banner(session_id: &str, platform: PlatformKind, cols: u16)
     let right_plain_len = platform_name.len() + 3 + date_str.len(); // "Platform · Date"
     // +4 accounts for: leading space, 2 spaces around rule, trailing space
     let rule_len = (cols as usize).saturating_sub(title_plain_len + right_plain_len + 4);
-    let rule = format!("{FG_BLUE}{}{RESET}", "━".repeat(rule_len));
+    let rule = format!("{blue}{}{RESET}", "━".repeat(rule_len));
     println!(" {title} {rule} {right_side} ");
 
+    if let Some(previous) = resumed_from {
+        println!("    {purple}Resumed from{RESET} {DIM}{previous}{RESET}");
+    }
+
     // Only show session directory in debug builds
     #[cfg(debug_assertions)]
     {
         let session_dir = format!("/tmp/crabigator-{}/", session_id);
-        println!("    {FG_PURPLE}Session{RESET}  {DIM}{session_dir}{RESET}");
+        println!("    {purple}Session{RESET}  {DIM}{session_dir}{RESET}");
     }
 
     println!();
 }
 
+/// One-line explanation of why the assistant CLI stopped
+///
+/// `✓ Session ended cleanly (0)`, `✗ Claude exited with code 1`, or
+/// `✗ Claude killed by SIGSEGV`.
+pub fn exit_message(exit: &PtyExit, platform: PlatformKind) -> String {
+    let name = platform.display_name();
+    match exit {
+        PtyExit::Code(0) => format!("{}✓ Session ended cleanly (0){}", ansi::GREEN, RESET),
+        PtyExit::Code(code) => format!("{}✗ {} exited with code {}{}", ansi::RED, name, code, RESET),
+        PtyExit::Signal(signal) => format!("{}✗ {} killed by {}{}", ansi::RED, name, signal, RESET),
+    }
+}
+
+/// Short exit label for the end banner: `exit 0`, `exit 1`, `SIGSEGV`
+fn exit_label(exit: &PtyExit) -> String {
+    match exit {
+        PtyExit::Code(code) => format!("exit {}", code),
+        PtyExit::Signal(signal) => signal.clone(),
+    }
+}
+
 /// Print session end line matching banner style with date
-pub fn print_session_end_line(platform: PlatformKind, cols: u16) {
+///
+/// The CLI's exit status, when known, sits between the platform and the date.
+pub fn print_session_end_line(platform: PlatformKind, cols: u16, exit: Option<&PtyExit>, palette: &Palette) {
     use chrono::Local;
 
     let width = cols as usize;
+    let [gray, orange, cyan, purple, blue] =
+        [color::GRAY, color::ORANGE, color::CYAN, color::PURPLE, color::BLUE].map(|c| palette.fg(c));
 
     // Format date: "Saturday, December 21st, 2025 5:58 PM"
     let now = Local::now();
@@ -71,23 +113,34 @@ pub fn print_session_end_line(platform: PlatformKind, cols: u16) {
     let date_str = format!("{}, {} {}{}, {} {}", day, month, date_num, suffix, year, time);
 
     // Left side: 🦀 Crabigator v0.1.0 ⛵
-    let version_str = format!("{FG_GRAY}v{VERSION}{RESET}");
+    let version_str = format!("{gray}v{VERSION}{RESET}");
     let title = format!(
-        "{FG_ORANGE}🦀{RESET} {BOLD}{FG_CYAN}Crabigator{RESET} {version_str} {FG_ORANGE}⛵{RESET}"
+        "{orange}🦀{RESET} {BOLD}{cyan}Crabigator{RESET} {version_str} {orange}⛵{RESET}"
     );
 
-    // Right side: Platform · Date
+    // Right side: Platform · [exit ·] Date
     let platform_name = platform.display_name();
-    let right_side = format!("{FG_PURPLE}{platform_name}{RESET} {FG_BLUE}·{RESET} {FG_BLUE}{date_str}{RESET}");
+    let (exit_part, exit_plain_len) = match exit {
+        Some(exit) => {
+            let label = exit_label(exit);
+            let exit_color = if exit.success() { color::GREEN } else { color::RED };
+            let len = label.len() + 3; // "exit N · "
+            (format!("{}{label}{RESET} {blue}·{RESET} ", palette.fg(exit_color)), len)
+        }
+        None => (String::new(), 0),
+    };
+    let right_side = format!(
+        "{purple}{platform_name}{RESET} {blue}·{RESET} {exit_part}{blue}{date_str}{RESET}"
+    );
 
     // Calculate plain lengths
     let version_plain_len = 1 + VERSION.len(); // "v" + version
     let title_plain_len = 2 + 1 + 10 + 1 + version_plain_len + 1 + 2; // 🦀 Crabigator vX.X.X ⛵
-    let right_plain_len = platform_name.len() + 3 + date_str.len(); // "Platform · Date"
+    let right_plain_len = platform_name.len() + 3 + exit_plain_len + date_str.len(); // "Platform · [exit · ]Date"
 
     // +4 accounts for: leading space, 2 spaces around rule, trailing space
     let rule_len = width.saturating_sub(title_plain_len + right_plain_len + 4);
-    let rule = format!("{FG_BLUE}{}{RESET}", "━".repeat(rule_len));
+    let rule = format!("{blue}{}{RESET}", "━".repeat(rule_len));
 
     println!(" {title} {rule} {right_side} ");
 }
@@ -101,3 +154,29 @@ fn date_suffix(date_num: &str) -> &'static str {
         _ => "th",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::strip_ansi;
+
+    #[test]
+    fn test_exit_message() {
+        let plain = |exit: PtyExit, platform| strip_ansi(&exit_message(&exit, platform));
+        assert_eq!(plain(PtyExit::Code(0), PlatformKind::Claude), "✓ Session ended cleanly (0)");
+        assert_eq!(plain(PtyExit::Code(1), PlatformKind::Claude), "✗ Claude exited with code 1");
+        assert_eq!(plain(PtyExit::Code(137), PlatformKind::Codex), "✗ Codex exited with code 137");
+        assert_eq!(
+            plain(PtyExit::Signal("SIGSEGV".to_string()), PlatformKind::Claude),
+            "✗ Claude killed by SIGSEGV"
+        );
+        assert!(exit_message(&PtyExit::Code(0), PlatformKind::Claude).starts_with(ansi::GREEN));
+        assert!(exit_message(&PtyExit::Code(2), PlatformKind::Claude).starts_with(ansi::RED));
+    }
+
+    #[test]
+    fn test_exit_label() {
+        assert_eq!(exit_label(&PtyExit::Code(0)), "exit 0");
+        assert_eq!(exit_label(&PtyExit::Signal("SIGKILL".to_string())), "SIGKILL");
+    }
+}
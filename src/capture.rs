@@ -1,29 +1,215 @@
 //! Output capture for streaming-ready session recording.
 //!
 //! Captures assistant CLI PTY output to files:
-//! - `scrollback.log`: Clean text transcript without ANSI codes (append-only)
+//! - `scrollback.log`: Clean text transcript without ANSI codes (append-only),
+//!   or `scrollback.log.gz` when compression is enabled
 //! - `screen.txt`: Current screen snapshot with ANSI codes (rendered by vt100)
+//! - `session.rec`: Raw PTY output with per-chunk timing, for `crabigator replay`
+//!   (only with [`CaptureFormat::Timed`])
+//! - `session.jsonl`: The same as JSON lines, plus resize events (only with
+//!   [`CaptureFormat::JsonLines`]; see `capture/jsonl.rs`)
 //!
 //! Uses a separate vt100 parser with a huge virtual screen to capture
 //! all output without losing anything to scrollback.
 
-use std::fs::{self, OpenOptions};
-#[cfg(debug_assertions)]
-use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+mod jsonl;
+
+pub use jsonl::{decode_json_lines, JsonFrame, JsonLinesWriter, PTY_OUTPUT};
 
 /// Maximum size for raw PTY log before rotation (50MB)
 #[cfg(debug_assertions)]
 const RAW_LOG_MAX_SIZE: u64 = 50 * 1024 * 1024;
 
+/// Magic header identifying a timed recording file
+pub const TIMED_MAGIC: &[u8; 8] = b"CRBREC1\n";
+
+/// Size of the per-chunk header: u64 milliseconds + u32 length (little-endian)
+const CHUNK_HEADER_LEN: usize = 12;
+
+/// What the capture system records.
+///
+/// `capture_format` in config.toml picks the recording: `plain` (default, no
+/// recording), `raw` or `jsonl`. A recording holds everything the CLI printed,
+/// secrets included, and outlives the session in /tmp, so it is opt-in:
+/// `--record` asks for `raw` for one session, `--no-record` turns it off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureFormat {
+    /// Plain-text transcript and screen snapshot only
+    #[default]
+    Plain,
+    /// Also record raw PTY output with timing metadata for replay
+    #[serde(rename = "raw")]
+    Timed,
+    /// Also record PTY output and resizes as JSON lines
+    #[serde(rename = "jsonl")]
+    JsonLines,
+}
+
+impl CaptureFormat {
+    /// Parse a format name as used in config.toml and `replay --format`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "plain" => Some(Self::Plain),
+            "raw" | "rec" => Some(Self::Timed),
+            "jsonl" | "json" => Some(Self::JsonLines),
+            _ => None,
+        }
+    }
+}
+
 /// Configuration for output capture.
 pub struct CaptureConfig {
     /// Whether capture is enabled (default: true, disabled with --no-capture)
     pub enabled: bool,
     /// Session ID for unique directory naming
     pub session_id: String,
+    /// Whether to also write a timed recording
+    pub format: CaptureFormat,
+    /// Gzip the scrollback transcript
+    pub compress: bool,
+}
+
+/// Append-only writer for capture files, optionally gzip-compressed
+pub enum CaptureWriter {
+    Plain(BufWriter<File>),
+    Compressed(GzEncoder<BufWriter<File>>),
+    /// Each write is a `pty_output` frame stamped with the current time
+    JsonLines(JsonLinesWriter),
+}
+
+impl CaptureWriter {
+    /// Open a capture file for appending (plain) or as a fresh gzip stream (compressed)
+    pub fn open(path: &Path, compress: bool) -> io::Result<Self> {
+        if compress {
+            let file = File::create(path)?;
+            Ok(Self::Compressed(GzEncoder::new(BufWriter::new(file), Compression::default())))
+        } else {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Ok(Self::Plain(BufWriter::new(file)))
+        }
+    }
+
+    pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.write_all(data),
+            Self::Compressed(w) => w.write_all(data),
+            Self::JsonLines(w) => w.write_frame(unix_now(), PTY_OUTPUT, data),
+        }
+    }
+
+    /// Flush buffered data so readers see it. For gzip this emits a sync
+    /// flush, which lets a decoder read everything written so far.
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Compressed(w) => w.flush(),
+            Self::JsonLines(w) => w.flush(),
+        }
+    }
+
+    /// Finish the stream, writing the gzip trailer
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut w) => w.flush(),
+            Self::Compressed(w) => w.finish()?.flush(),
+            Self::JsonLines(mut w) => w.flush(),
+        }
+    }
+}
+
+/// Current Unix time in seconds (JSON-lines timestamps)
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Read a capture file, transparently decompressing `.gz` files.
+///
+/// Compressed logs from a running session have no gzip trailer yet, so
+/// everything decoded before the stream ends is returned.
+pub fn read_capture_file(path: &Path) -> io::Result<Vec<u8>> {
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let mut decoder = GzDecoder::new(File::open(path)?);
+        let mut contents = Vec::new();
+        let mut buf = [0u8; 8192];
+        loop {
+            match decoder.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => contents.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) if contents.is_empty() => return Err(e),
+                Err(_) => break,
+            }
+        }
+        Ok(contents)
+    } else {
+        fs::read(path)
+    }
+}
+
+/// One chunk of PTY output from a timed recording
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedChunk {
+    /// Milliseconds since the session started
+    pub elapsed_ms: u64,
+    pub data: Vec<u8>,
+}
+
+/// Encode a chunk as `[elapsed_ms: u64 LE][len: u32 LE][data]`
+pub fn encode_timed_chunk(elapsed_ms: u64, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(CHUNK_HEADER_LEN + data.len());
+    out.extend_from_slice(&elapsed_ms.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+/// Decode a timed recording (including its magic header) into chunks.
+///
+/// A truncated trailing chunk (from a session still writing) is ignored.
+pub fn decode_timed_chunks(bytes: &[u8]) -> Result<Vec<TimedChunk>> {
+    let Some(mut rest) = bytes.strip_prefix(TIMED_MAGIC.as_slice()) else {
+        bail!("not a timed recording (missing header)");
+    };
+
+    let mut chunks = Vec::new();
+    while rest.len() >= CHUNK_HEADER_LEN {
+        let elapsed_ms = u64::from_le_bytes(rest[0..8].try_into()?);
+        let len = u32::from_le_bytes(rest[8..12].try_into()?) as usize;
+        let Some(data) = rest.get(CHUNK_HEADER_LEN..CHUNK_HEADER_LEN + len) else {
+            break;
+        };
+        chunks.push(TimedChunk {
+            elapsed_ms,
+            data: data.to_vec(),
+        });
+        rest = &rest[CHUNK_HEADER_LEN + len..];
+    }
+    Ok(chunks)
+}
+
+/// Path a finished session's recording is kept at after the capture directory is removed
+pub fn saved_recording_path(session_id: &str) -> PathBuf {
+    PathBuf::from(format!("/tmp/crabigator-{}.rec", session_id))
+}
+
+/// `saved_recording_path` for a JSON-lines recording
+pub fn saved_json_lines_path(session_id: &str) -> PathBuf {
+    PathBuf::from(format!("/tmp/crabigator-{}.jsonl", session_id))
 }
 
 /// Incremental scrollback update for streaming
@@ -37,6 +223,8 @@ pub struct ScrollbackUpdate {
 /// Manages output capture to scrollback and screen files.
 pub struct CaptureManager {
     config: CaptureConfig,
+    /// Output capture suspended mid-session (Ctrl+A t)
+    paused: bool,
     /// Base directory: /tmp/crabigator-{session_id}/
     capture_dir: PathBuf,
     /// Our own vt100 parser with huge screen to capture all output
@@ -51,6 +239,12 @@ pub struct CaptureManager {
     screen_update_interval: Duration,
     /// Last cursor row written to scrollback (for incremental updates)
     last_scrollback_row: u16,
+    /// scrollback.log (or scrollback.log.gz) writer
+    scrollback: Option<CaptureWriter>,
+    /// session.rec (CaptureFormat::Timed) or session.jsonl (CaptureFormat::JsonLines)
+    recording: Option<CaptureWriter>,
+    /// Session start, the zero point for recording timestamps
+    started_at: Instant,
     /// Raw PTY output log file (debug builds only)
     #[cfg(debug_assertions)]
     raw_log: Option<File>,
@@ -69,6 +263,7 @@ impl CaptureManager {
         if !config.enabled {
             return Ok(Self {
                 config,
+                paused: false,
                 capture_dir: PathBuf::new(),
                 capture_parser,
                 last_scrollback_update: Instant::now(),
@@ -76,6 +271,9 @@ impl CaptureManager {
                 last_screen_update: Instant::now(),
                 screen_update_interval: Duration::from_millis(100),
                 last_scrollback_row: 0,
+                scrollback: None,
+                recording: None,
+                started_at: Instant::now(),
                 #[cfg(debug_assertions)]
                 raw_log: None,
                 #[cfg(debug_assertions)]
@@ -103,8 +301,24 @@ impl CaptureManager {
             (Some(file), size)
         };
 
+        let scrollback = CaptureWriter::open(&scrollback_path(&capture_dir, config.compress), config.compress)?;
+
+        let recording = match config.format {
+            CaptureFormat::Plain => None,
+            CaptureFormat::Timed => {
+                let mut writer = BufWriter::new(fs::File::create(capture_dir.join("session.rec"))?);
+                writer.write_all(TIMED_MAGIC)?;
+                writer.flush()?;
+                Some(CaptureWriter::Plain(writer))
+            }
+            CaptureFormat::JsonLines => Some(CaptureWriter::JsonLines(JsonLinesWriter::create(
+                &capture_dir.join("session.jsonl"),
+            )?)),
+        };
+
         Ok(Self {
             config,
+            paused: false,
             capture_dir,
             capture_parser,
             last_scrollback_update: Instant::now() - Duration::from_secs(10),
@@ -112,6 +326,9 @@ impl CaptureManager {
             last_screen_update: Instant::now() - Duration::from_secs(10),
             screen_update_interval: Duration::from_millis(100),
             last_scrollback_row: 0,
+            scrollback: Some(scrollback),
+            recording,
+            started_at: Instant::now(),
             #[cfg(debug_assertions)]
             raw_log,
             #[cfg(debug_assertions)]
@@ -124,7 +341,7 @@ impl CaptureManager {
     /// This feeds the bytes to our internal vt100 parser which has a huge
     /// virtual screen, so all content accumulates without scrolling off.
     pub fn capture_output(&mut self, data: &[u8]) -> std::io::Result<()> {
-        if !self.config.enabled || data.is_empty() {
+        if !self.config.enabled || self.paused || data.is_empty() {
             return Ok(());
         }
 
@@ -142,12 +359,35 @@ impl CaptureManager {
             }
         }
 
+        // Append to the recording; flushed per chunk so live replays keep up
+        if let Some(ref mut recording) = self.recording {
+            if let CaptureWriter::JsonLines(_) = recording {
+                recording.write_all(data)?;
+            } else {
+                let elapsed_ms = self.started_at.elapsed().as_millis() as u64;
+                recording.write_all(&encode_timed_chunk(elapsed_ms, data))?;
+            }
+            recording.flush()?;
+        }
+
         // Process through our capture parser
         self.capture_parser.process(data);
 
         Ok(())
     }
 
+    /// Note the PTY size in a JSON-lines recording (other formats don't keep it).
+    pub fn record_resize(&mut self, cols: u16, rows: u16) -> std::io::Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        if let Some(CaptureWriter::JsonLines(writer)) = self.recording.as_mut() {
+            writer.write_resize(unix_now(), cols, rows)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
     /// Rotate the raw PTY log by truncating it (debug builds only).
     #[cfg(debug_assertions)]
     fn rotate_raw_log(&mut self) -> std::io::Result<()> {
@@ -212,12 +452,10 @@ impl CaptureManager {
         }
 
         // Append to scrollback file
-        let scrollback_path = self.capture_dir.join("scrollback.log");
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&scrollback_path)?;
-        file.write_all(&content)?;
+        if let Some(ref mut scrollback) = self.scrollback {
+            scrollback.write_all(&content)?;
+            scrollback.flush()?;
+        }
 
         self.last_scrollback_row = cursor_row;
         self.last_scrollback_update = Instant::now();
@@ -283,12 +521,27 @@ impl CaptureManager {
         Ok(String::from_utf8_lossy(&content).to_string())
     }
 
+    /// Path of the scrollback transcript (depends on compression)
+    pub fn scrollback_path(&self) -> PathBuf {
+        scrollback_path(&self.capture_dir, self.config.compress)
+    }
+
     /// Get the capture directory path.
     #[allow(dead_code)]
     pub fn capture_dir(&self) -> &PathBuf {
         &self.capture_dir
     }
 
+    /// Suspend or resume recording PTY output (files stay open).
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Whether output capture is currently suspended.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /// Check if capture is enabled.
     #[allow(dead_code)]
     pub fn is_enabled(&self) -> bool {
@@ -296,9 +549,166 @@ impl CaptureManager {
     }
 
     /// Cleanup - remove capture directory on exit.
-    pub fn cleanup(&self) {
+    ///
+    /// The recording is moved out first so the session can still be replayed.
+    pub fn cleanup(&mut self) {
+        if let Some(scrollback) = self.scrollback.take() {
+            let _ = scrollback.finish();
+        }
+        if let Some(recording) = self.recording.take() {
+            let _ = recording.finish();
+        }
         if self.config.enabled && self.capture_dir.exists() {
+            let session_id = &self.config.session_id;
+            for (name, saved) in [
+                ("session.rec", saved_recording_path(session_id)),
+                ("session.jsonl", saved_json_lines_path(session_id)),
+            ] {
+                let recording = self.capture_dir.join(name);
+                if recording.exists() {
+                    let _ = fs::rename(&recording, saved);
+                }
+            }
             let _ = fs::remove_dir_all(&self.capture_dir);
         }
     }
 }
+
+/// Scrollback transcript path within a capture directory
+fn scrollback_path(capture_dir: &Path, compress: bool) -> PathBuf {
+    capture_dir.join(if compress { "scrollback.log.gz" } else { "scrollback.log" })
+}
+
+/// Whether a file starts with the timed recording header
+pub fn is_timed_recording(path: &Path) -> bool {
+    let mut header = [0u8; 8];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .map(|_| &header == TIMED_MAGIC)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timed_chunks_round_trip() {
+        let mut bytes = TIMED_MAGIC.to_vec();
+        bytes.extend(encode_timed_chunk(0, b"\x1b[31mhello"));
+        bytes.extend(encode_timed_chunk(250, b""));
+        bytes.extend(encode_timed_chunk(u64::MAX, "wörld\r\n".as_bytes()));
+
+        let chunks = decode_timed_chunks(&bytes).unwrap();
+        assert_eq!(
+            chunks,
+            vec![
+                TimedChunk { elapsed_ms: 0, data: b"\x1b[31mhello".to_vec() },
+                TimedChunk { elapsed_ms: 250, data: Vec::new() },
+                TimedChunk { elapsed_ms: u64::MAX, data: "wörld\r\n".as_bytes().to_vec() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncated_trailing_chunk_is_ignored() {
+        let mut bytes = TIMED_MAGIC.to_vec();
+        bytes.extend(encode_timed_chunk(10, b"complete"));
+        let partial = encode_timed_chunk(20, b"partial");
+        bytes.extend(&partial[..partial.len() - 3]);
+
+        let chunks = decode_timed_chunks(&bytes).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].data, b"complete");
+    }
+
+    #[test]
+    fn test_missing_header_is_an_error() {
+        assert!(decode_timed_chunks(b"plain scrollback\n").is_err());
+    }
+
+    #[test]
+    fn test_compressed_writer_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scrollback.log.gz");
+
+        let mut writer = CaptureWriter::open(&path, true).unwrap();
+        writer.write_all(b"first line\n").unwrap();
+        writer.write_all(b"second line\n").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(read_capture_file(&path).unwrap(), b"first line\nsecond line\n");
+        // The file on disk is actually gzip, not plain text
+        assert_ne!(fs::read(&path).unwrap(), b"first line\nsecond line\n");
+    }
+
+    #[test]
+    fn test_unfinished_compressed_stream_is_readable_after_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scrollback.log.gz");
+
+        // A live session flushes after every update but never writes the trailer
+        let mut writer = CaptureWriter::open(&path, true).unwrap();
+        writer.write_all(b"still running\n").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(read_capture_file(&path).unwrap(), b"still running\n");
+        drop(writer);
+    }
+
+    #[test]
+    fn test_json_lines_recording_has_output_and_resizes() {
+        let session_id = format!("test-jsonl-{}", std::process::id());
+        let mut manager = CaptureManager::new(CaptureConfig {
+            enabled: true,
+            session_id: session_id.clone(),
+            format: CaptureFormat::JsonLines,
+            compress: false,
+        })
+        .unwrap();
+        manager.record_resize(80, 20).unwrap();
+        manager.capture_output(b"\x1b[1mhi").unwrap();
+        manager.record_resize(120, 40).unwrap();
+
+        let path = manager.capture_dir().join("session.jsonl");
+        let frames = decode_json_lines(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(!manager.capture_dir().join("session.rec").exists());
+        manager.cleanup();
+
+        assert!(matches!(frames[0], JsonFrame::Resize { cols: 80, rows: 20, .. }));
+        assert!(matches!(&frames[1], JsonFrame::PtyOutput { data, .. } if data == b"\x1b[1mhi"));
+        assert!(matches!(frames[2], JsonFrame::Resize { cols: 120, rows: 40, .. }));
+
+        // Kept after the capture directory is removed
+        let saved = saved_json_lines_path(&session_id);
+        assert_eq!(decode_json_lines(&fs::read_to_string(&saved).unwrap()).unwrap(), frames);
+        fs::remove_file(saved).unwrap();
+    }
+
+    #[test]
+    fn test_capture_format_names() {
+        let parse = |s: &str| toml::from_str::<toml::Table>(&format!("f = \"{}\"", s)).unwrap()["f"].clone();
+        assert_eq!(parse("raw").try_into::<CaptureFormat>().unwrap(), CaptureFormat::Timed);
+        assert_eq!(parse("plain").try_into::<CaptureFormat>().unwrap(), CaptureFormat::Plain);
+        assert_eq!(CaptureFormat::default(), CaptureFormat::Plain);
+        assert_eq!(parse("jsonl").try_into::<CaptureFormat>().unwrap(), CaptureFormat::JsonLines);
+        assert_eq!(CaptureFormat::parse("JSONL"), Some(CaptureFormat::JsonLines));
+        assert_eq!(CaptureFormat::parse("raw"), Some(CaptureFormat::Timed));
+        assert_eq!(CaptureFormat::parse("yaml"), None);
+    }
+
+    #[test]
+    fn test_plain_files_read_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scrollback.log");
+
+        let mut writer = CaptureWriter::open(&path, false).unwrap();
+        writer.write_all(b"plain\n").unwrap();
+        writer.finish().unwrap();
+        let mut writer = CaptureWriter::open(&path, false).unwrap();
+        writer.write_all(b"appended\n").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(read_capture_file(&path).unwrap(), b"plain\nappended\n");
+    }
+}
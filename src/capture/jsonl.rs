@@ -0,0 +1,163 @@
+//! JSON-lines recording (`session.jsonl`)
+//!
+//! An alternative to the binary `session.rec` for tools that want to read a
+//! session without a custom decoder. One object per line:
+//!
+//! ```text
+//! {"ts":1767225600.123,"type":"pty_output","data":"G1szMW1oaQ=="}
+//! {"ts":1767225601.5,"type":"resize","cols":120,"rows":40}
+//! ```
+//!
+//! `ts` is Unix time in seconds and `data` is the raw PTY output, base64
+//! encoded (it is arbitrary bytes, not necessarily UTF-8).
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Frame type of PTY output
+pub const PTY_OUTPUT: &str = "pty_output";
+
+/// Frame type of a terminal size change
+pub const RESIZE: &str = "resize";
+
+/// Writes recording frames as newline-delimited JSON
+pub struct JsonLinesWriter {
+    writer: BufWriter<File>,
+}
+
+impl JsonLinesWriter {
+    /// Start a new recording at `path`, replacing any existing file
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    /// Write a frame carrying bytes (base64 in the `data` field)
+    pub fn write_frame(&mut self, ts: f64, frame_type: &str, data: &[u8]) -> io::Result<()> {
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        self.write_line(&json!({ "ts": ts, "type": frame_type, "data": data }))
+    }
+
+    /// Write a `resize` frame
+    pub fn write_resize(&mut self, ts: f64, cols: u16, rows: u16) -> io::Result<()> {
+        self.write_line(&json!({ "ts": ts, "type": RESIZE, "cols": cols, "rows": rows }))
+    }
+
+    fn write_line(&mut self, frame: &serde_json::Value) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, frame)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// One decoded frame of a JSON-lines recording
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonFrame {
+    PtyOutput { ts: f64, data: Vec<u8> },
+    Resize { ts: f64, cols: u16, rows: u16 },
+}
+
+/// A line as written, before the type is looked at
+#[derive(Deserialize)]
+struct RawFrame {
+    ts: f64,
+    #[serde(rename = "type")]
+    frame_type: String,
+    data: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+}
+
+/// Decode a JSON-lines recording
+///
+/// Frames of unknown types are skipped, as is an unterminated last line (from
+/// a session still writing).
+pub fn decode_json_lines(text: &str) -> Result<Vec<JsonFrame>> {
+    let complete = match text.rfind('\n') {
+        Some(end) => &text[..end],
+        None => "",
+    };
+
+    let mut frames = Vec::new();
+    for (index, line) in complete.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let raw: RawFrame = serde_json::from_str(line).with_context(|| format!("line {}", index + 1))?;
+        let frame = match (raw.frame_type.as_str(), raw.data, raw.cols, raw.rows) {
+            (PTY_OUTPUT, Some(data), _, _) => JsonFrame::PtyOutput {
+                ts: raw.ts,
+                data: base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .with_context(|| format!("line {}: invalid base64", index + 1))?,
+            },
+            (RESIZE, _, Some(cols), Some(rows)) => JsonFrame::Resize { ts: raw.ts, cols, rows },
+            (PTY_OUTPUT | RESIZE, ..) => bail!("line {}: incomplete {} frame", index + 1, raw.frame_type),
+            _ => continue,
+        };
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+
+        let mut writer = JsonLinesWriter::create(&path).unwrap();
+        writer.write_resize(1000.0, 120, 40).unwrap();
+        writer.write_frame(1000.25, PTY_OUTPUT, b"\x1b[31mhello").unwrap();
+        writer.write_frame(1001.5, PTY_OUTPUT, &[0xff, 0x00, b'\n']).unwrap();
+        writer.write_frame(1002.0, PTY_OUTPUT, b"").unwrap();
+        writer.flush().unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().next().unwrap(), r#"{"cols":120,"rows":40,"ts":1000.0,"type":"resize"}"#);
+        assert_eq!(
+            decode_json_lines(&text).unwrap(),
+            vec![
+                JsonFrame::Resize { ts: 1000.0, cols: 120, rows: 40 },
+                JsonFrame::PtyOutput { ts: 1000.25, data: b"\x1b[31mhello".to_vec() },
+                JsonFrame::PtyOutput { ts: 1001.5, data: vec![0xff, 0x00, b'\n'] },
+                JsonFrame::PtyOutput { ts: 1002.0, data: Vec::new() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_frames_and_partial_last_line_are_skipped() {
+        let text = concat!(
+            r#"{"ts":1.0,"type":"pty_output","data":"aGk="}"#,
+            "\n",
+            r#"{"ts":2.0,"type":"marker","label":"x"}"#,
+            "\n",
+            r#"{"ts":3.0,"type":"pty_out"#,
+        );
+        assert_eq!(
+            decode_json_lines(text).unwrap(),
+            vec![JsonFrame::PtyOutput { ts: 1.0, data: b"hi".to_vec() }]
+        );
+    }
+
+    #[test]
+    fn test_malformed_frames_are_errors() {
+        assert!(decode_json_lines("not json\n").is_err());
+        assert!(decode_json_lines("{\"ts\":1.0,\"type\":\"pty_output\",\"data\":\"***\"}\n").is_err());
+        assert!(decode_json_lines("{\"ts\":1.0,\"type\":\"resize\",\"cols\":80}\n").is_err());
+    }
+}
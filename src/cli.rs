@@ -4,11 +4,13 @@
 //! and debug timing infrastructure.
 
 use std::env;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicU8;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use crate::config::Config;
+use crate::parsers::DiffMode;
 use crate::platforms::PlatformKind;
 
 /// Subcommand to run
@@ -24,6 +26,76 @@ pub enum Command {
         raw: bool,
         /// Show hook event history for debugging
         history: bool,
+        /// Show aggregate stats across all instances
+        summary: bool,
+        /// Show only what changed between publishes (implies watch)
+        diff: bool,
+        /// Read mirrors from another machine (`USER@HOST`) over SSH
+        ssh: Option<String>,
+    },
+    /// List running sessions; exits 1 if none is in the current directory
+    Status {
+        /// Emit a JSON array instead of one line per session
+        json: bool,
+    },
+    /// Remove and freshly reinstall the platform's hooks
+    Reinstall {
+        platform: Option<PlatformKind>,
+        /// Print what would change without modifying files
+        dry_run: bool,
+    },
+    /// Search captured scrollback logs
+    Search(crate::search::SearchOptions),
+    /// Replay a captured session
+    Replay {
+        session_id: Option<String>,
+        /// Explicit recording or scrollback file
+        file: Option<String>,
+        /// Recording format (`raw` or `jsonl`); guessed from the file if unset
+        format: Option<crate::capture::CaptureFormat>,
+        /// Playback speed factor (1.0 = real time, 0 = dump all at once)
+        speed: f64,
+    },
+    /// Print the resolved configuration
+    Config(crate::config::ConfigAction),
+    /// Print a shell completion script
+    Completions(crate::completions::ShellKind),
+    /// Print a Markdown summary of a day's sessions from the history store
+    Summary {
+        /// Day to summarize (default today)
+        date: Option<chrono::NaiveDate>,
+        /// Project directory (default the current directory)
+        project: Option<PathBuf>,
+    },
+    /// Name or tag a session (written to its tags.json)
+    Tag {
+        session_id: Option<String>,
+        /// Tag the most recently active session instead
+        current: bool,
+        name: Option<String>,
+        tags: Vec<String>,
+    },
+    /// Print the active session's state for a Starship prompt module
+    Starship {
+        /// Print the module for ~/.config/starship.toml instead
+        config: bool,
+    },
+    /// Copy a session's stats to the clipboard (default the latest session)
+    CopyStats { session_id: Option<String> },
+    /// Print a session's tool call flow as a Mermaid or Graphviz diagram
+    Diagram {
+        session_id: String,
+        format: crate::export::DiagramFormat,
+    },
+    /// Count (or with --show, list) cloud events waiting in the offline queue
+    Queue { show: bool },
+    /// Check the environment the assistant CLI needs
+    Doctor { platform: Option<PlatformKind> },
+    /// Print (or install) a Zellij layout with an inspect pane
+    ZellijPlugin {
+        platform: Option<PlatformKind>,
+        /// Write to ~/.config/zellij/layouts/crabigator.yaml instead of printing
+        install: bool,
     },
 }
 
@@ -36,6 +108,19 @@ pub struct Args {
     pub command: Command,
     /// Whether to capture output (default: true)
     pub capture: bool,
+    /// `--record`/`--no-record`: override `capture_format` for the replay
+    /// recording (None follows config, which records nothing by default)
+    pub record: Option<bool>,
+    /// File to write a one-line status summary to for tmux
+    pub tmux_status_file: Option<PathBuf>,
+    /// Cap on files listed in the git widget (overrides config)
+    pub max_git_files: Option<usize>,
+    /// Changes the diff summary covers (overrides config)
+    pub diff_mode: Option<DiffMode>,
+    /// `--set-env KEY=VALUE` pairs for the CLI, in order (override `[platform.env]`)
+    pub env_overrides: Vec<(String, String)>,
+    /// Whether to draw the status bar (default: true)
+    pub status_bar: bool,
 }
 
 impl Default for Args {
@@ -46,6 +131,12 @@ impl Default for Args {
             profile: false,
             command: Command::default(),
             capture: true, // On by default
+            record: None,
+            tmux_status_file: None,
+            max_git_files: None,
+            diff_mode: None,
+            env_overrides: Vec::new(),
+            status_bar: true,
         }
     }
 }
@@ -65,12 +156,22 @@ pub fn parse_args() -> Args {
                 let mut watch = false;
                 let mut raw = false;
                 let mut history = false;
+                let mut summary = false;
+                let mut diff = false;
+                let mut ssh = None;
 
-                for arg in iter {
+                while let Some(arg) = iter.next() {
                     match arg.as_str() {
+                        "--dir" => dir_filter = iter.next(),
+                        "--ssh" => ssh = iter.next(),
                         "--watch" | "-w" => watch = true,
                         "--raw" | "-r" => raw = true,
                         "--history" | "-H" => history = true,
+                        "--summary" | "-s" => summary = true,
+                        "--diff" | "-d" => {
+                            diff = true;
+                            watch = true;
+                        }
                         _ if !arg.starts_with('-') && dir_filter.is_none() => {
                             dir_filter = Some(arg);
                         }
@@ -83,7 +184,304 @@ pub fn parse_args() -> Args {
                     watch,
                     raw,
                     history,
+                    summary,
+                    diff,
+                    ssh,
+                };
+                return args;
+            }
+            "status" => {
+                iter.next(); // consume "status"
+                let json = iter.any(|arg| arg == "--json");
+                args.command = Command::Status { json };
+                return args;
+            }
+            "reinstall" => {
+                iter.next(); // consume "reinstall"
+                let mut platform = None;
+                let mut dry_run = false;
+
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--dry-run" | "-n" => dry_run = true,
+                        "--platform" | "-p" => {
+                            let value = iter.next().unwrap_or_default();
+                            match PlatformKind::parse(&value) {
+                                Some(kind) => platform = Some(kind),
+                                None => {
+                                    eprintln!("Unknown platform: {}. Use 'claude', 'codex' or 'cline'.", value);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                args.command = Command::Reinstall { platform, dry_run };
+                return args;
+            }
+            "search" => {
+                iter.next(); // consume "search"
+                let mut positional = Vec::new();
+                let mut context = crate::search::DEFAULT_CONTEXT;
+                let mut ignore_case = false;
+                let mut invert = false;
+
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--ignore-case" | "-i" => ignore_case = true,
+                        "--invert" | "-v" => invert = true,
+                        "--context" | "-C" => {
+                            let value = iter.next().unwrap_or_default();
+                            match value.parse() {
+                                Ok(n) => context = n,
+                                Err(_) => {
+                                    eprintln!("Invalid context line count: {}", value);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        _ => positional.push(arg),
+                    }
+                }
+
+                let mut positional = positional.into_iter();
+                let Some(pattern) = positional.next() else {
+                    eprintln!("Usage: crabigator search PATTERN [SESSION_ID] [-C N] [-i] [-v]");
+                    std::process::exit(1);
+                };
+                args.command = Command::Search(crate::search::SearchOptions {
+                    pattern,
+                    session_id: positional.next(),
+                    context,
+                    ignore_case,
+                    invert,
+                });
+                return args;
+            }
+            "replay" => {
+                iter.next(); // consume "replay"
+                let mut session_id = None;
+                let mut file = None;
+                let mut format = None;
+                let mut speed = 1.0;
+
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--file" | "-f" => file = iter.next(),
+                        "--format" => {
+                            let value = iter.next().unwrap_or_default();
+                            match crate::capture::CaptureFormat::parse(&value) {
+                                Some(f) => format = Some(f),
+                                None => {
+                                    eprintln!("Unknown format: {}. Use 'raw' or 'jsonl'.", value);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        "--speed" | "-s" => {
+                            let value = iter.next().unwrap_or_default();
+                            match crate::replay::parse_speed(&value) {
+                                Ok(s) => speed = s,
+                                Err(e) => {
+                                    eprintln!("{}", e);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        _ if !arg.starts_with('-') && session_id.is_none() => {
+                            session_id = Some(arg);
+                        }
+                        _ => {}
+                    }
+                }
+
+                args.command = Command::Replay {
+                    session_id,
+                    file,
+                    format,
+                    speed,
+                };
+                return args;
+            }
+            "config" => {
+                iter.next(); // consume "config"
+                let action = match (iter.next().as_deref(), iter.next()) {
+                    (None | Some("show"), _) => crate::config::ConfigAction::Show,
+                    (Some("get"), Some(key)) => crate::config::ConfigAction::Get(key),
+                    _ => {
+                        eprintln!("Usage: crabigator config [show | get KEY]");
+                        std::process::exit(1);
+                    }
                 };
+                args.command = Command::Config(action);
+                return args;
+            }
+            "completions" => {
+                iter.next(); // consume "completions"
+                let value = match (iter.next().as_deref(), iter.next()) {
+                    (Some("--shell"), Some(value)) => value,
+                    (Some(value), None) if !value.starts_with('-') => value.to_string(),
+                    _ => {
+                        eprintln!("Usage: crabigator completions --shell nushell");
+                        std::process::exit(1);
+                    }
+                };
+                match crate::completions::ShellKind::parse(&value) {
+                    Some(shell) => args.command = Command::Completions(shell),
+                    None => {
+                        eprintln!("Unsupported shell: {}. Supported: nushell.", value);
+                        std::process::exit(1);
+                    }
+                }
+                return args;
+            }
+            "summary" => {
+                iter.next(); // consume "summary"
+                let mut date = None;
+                let mut project = None;
+
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--date" => {
+                            let value = iter.next().unwrap_or_default();
+                            match chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                                Ok(d) => date = Some(d),
+                                Err(_) => {
+                                    eprintln!("Invalid date: {} (expected YYYY-MM-DD)", value);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        "--project" => project = iter.next().map(PathBuf::from),
+                        _ => {
+                            eprintln!("Usage: crabigator summary [--date YYYY-MM-DD] [--project PATH]");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+
+                args.command = Command::Summary { date, project };
+                return args;
+            }
+            "tag" => {
+                iter.next(); // consume "tag"
+                let mut session_id = None;
+                let mut current = false;
+                let mut name = None;
+                let mut tags = vec![];
+
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--current" => current = true,
+                        "--name" | "-n" => name = iter.next(),
+                        "--tag" | "-t" => tags.extend(iter.next()),
+                        _ if !arg.starts_with('-') && session_id.is_none() => {
+                            session_id = Some(arg);
+                        }
+                        _ => {}
+                    }
+                }
+
+                if (session_id.is_none() && !current) || (name.is_none() && tags.is_empty()) {
+                    eprintln!("Usage: crabigator tag (SESSION_ID | --current) [--name NAME] [--tag TAG]...");
+                    std::process::exit(1);
+                }
+                args.command = Command::Tag {
+                    session_id,
+                    current,
+                    name,
+                    tags,
+                };
+                return args;
+            }
+            "starship" => {
+                iter.next(); // consume "starship"
+                let config = iter.any(|arg| arg == "--config");
+                args.command = Command::Starship { config };
+                return args;
+            }
+            "copy-stats" => {
+                iter.next(); // consume "copy-stats"
+                let session_id = iter.find(|arg| !arg.starts_with('-'));
+                args.command = Command::CopyStats { session_id };
+                return args;
+            }
+            "diagram" => {
+                iter.next(); // consume "diagram"
+                let mut session_id = None;
+                let mut format = crate::export::DiagramFormat::default();
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--format" | "-f" => {
+                            let value = iter.next().unwrap_or_default();
+                            match crate::export::DiagramFormat::parse(&value) {
+                                Some(f) => format = f,
+                                None => {
+                                    eprintln!("Unknown diagram format: {}. Use 'mermaid' or 'dot'.", value);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        _ if !arg.starts_with('-') && session_id.is_none() => session_id = Some(arg),
+                        _ => {}
+                    }
+                }
+                let Some(session_id) = session_id else {
+                    eprintln!("Usage: crabigator diagram SESSION_ID [--format mermaid|dot]");
+                    std::process::exit(1);
+                };
+                args.command = Command::Diagram { session_id, format };
+                return args;
+            }
+            "queue" => {
+                iter.next(); // consume "queue"
+                let show = iter.any(|arg| arg == "--show");
+                args.command = Command::Queue { show };
+                return args;
+            }
+            "doctor" => {
+                iter.next(); // consume "doctor"
+                let mut platform = None;
+                while let Some(arg) = iter.next() {
+                    if matches!(arg.as_str(), "--platform" | "-p") {
+                        let value = iter.next().unwrap_or_default();
+                        match PlatformKind::parse(&value) {
+                            Some(kind) => platform = Some(kind),
+                            None => {
+                                eprintln!("Unknown platform: {}. Use 'claude', 'codex' or 'cline'.", value);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+                }
+                args.command = Command::Doctor { platform };
+                return args;
+            }
+            "zellij-plugin" => {
+                iter.next(); // consume "zellij-plugin"
+                let mut platform = None;
+                let mut install = false;
+
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--install" => install = true,
+                        "--platform" | "-p" => {
+                            let value = iter.next().unwrap_or_default();
+                            match PlatformKind::parse(&value) {
+                                Some(kind) => platform = Some(kind),
+                                None => {
+                                    eprintln!("Unknown platform: {}. Use 'claude', 'codex' or 'cline'.", value);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                args.command = Command::ZellijPlugin { platform, install };
                 return args;
             }
             "continue" | "c" => {
@@ -112,7 +510,7 @@ pub fn parse_args() -> Args {
                         args.platform = Some(platform);
                         platform_selected = true;
                     } else {
-                        eprintln!("Unknown platform: {}. Use 'claude' or 'codex'.", value);
+                        eprintln!("Unknown platform: {}. Use 'claude', 'codex' or 'cline'.", value);
                         std::process::exit(1);
                     }
                 }
@@ -126,6 +524,50 @@ pub fn parse_args() -> Args {
             "--no-capture" => {
                 args.capture = false;
             }
+            "--record" => {
+                args.record = Some(true);
+            }
+            "--no-record" => {
+                args.record = Some(false);
+            }
+            "--no-status-bar" | "--passthrough" => {
+                args.status_bar = false;
+            }
+            "--tmux-status-file" => {
+                args.tmux_status_file = iter.next().map(PathBuf::from);
+            }
+            "--max-git-files" => {
+                let value = iter.next().unwrap_or_default();
+                match value.parse() {
+                    Ok(n) => args.max_git_files = Some(n),
+                    Err(_) => {
+                        eprintln!("Invalid --max-git-files value: {:?}. Expected a number.", value);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--diff-mode" => {
+                let value = iter.next().unwrap_or_default();
+                match DiffMode::parse(&value) {
+                    Some(mode) => args.diff_mode = Some(mode),
+                    None => {
+                        eprintln!("Invalid --diff-mode value: {:?}. Use 'working', 'session' or 'all'.", value);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--set-env" => {
+                let value = iter.next().unwrap_or_default();
+                match value.split_once('=') {
+                    Some((key, val)) if !key.is_empty() => {
+                        args.env_overrides.push((key.to_string(), val.to_string()));
+                    }
+                    _ => {
+                        eprintln!("Invalid --set-env value: {:?}. Expected KEY=VALUE.", value);
+                        std::process::exit(1);
+                    }
+                }
+            }
             _ => {
                 if !platform_selected && !arg.starts_with('-') {
                     if let Some(platform) = PlatformKind::parse(&arg) {
@@ -145,9 +587,9 @@ pub fn parse_args() -> Args {
 
 /// Resolve platform from explicit arg, env var, config file, or default
 /// If explicitly selected, saves preference to config for future use.
-pub fn resolve_platform(explicit: Option<PlatformKind>) -> PlatformKind {
+pub fn resolve_platform(explicit: Option<PlatformKind>, config: &Config) -> PlatformKind {
     if let Some(kind) = explicit {
-        let _ = save_platform_preference(kind);
+        let _ = save_platform_preference(kind, config);
         return kind;
     }
 
@@ -157,19 +599,20 @@ pub fn resolve_platform(explicit: Option<PlatformKind>) -> PlatformKind {
         }
     }
 
-    if let Ok(config) = Config::load() {
-        if let Some(kind) = PlatformKind::parse(&config.default_platform) {
-            return kind;
-        }
+    if let Some(kind) = PlatformKind::parse(&config.default_platform) {
+        return kind;
     }
 
     PlatformKind::Claude
 }
 
-/// Save platform preference to config file
-pub fn save_platform_preference(platform: PlatformKind) -> anyhow::Result<()> {
-    let mut config = Config::load().unwrap_or_default();
-    config.set_default_platform(platform.as_str())
+/// Save platform preference to config file (only that key; a config.toml
+/// that doesn't parse is never overwritten)
+pub fn save_platform_preference(platform: PlatformKind, config: &Config) -> anyhow::Result<()> {
+    if config.default_platform == platform.as_str() {
+        return Ok(());
+    }
+    Config::save_default_platform(platform.as_str())
 }
 
 /// Startup trace for measuring performance.
@@ -4,7 +4,8 @@
 //! - Device identity management (device_id, device_secret)
 //! - CloudClient for registering sessions and streaming events
 //! - WebSocket connection for bidirectional communication
-//! - Offline queue for when cloud is unreachable
+//! - Offline queue for when cloud is unreachable, kept on disk across crashes
+//! - Connection telemetry for debugging (--profile)
 
 mod client;
 mod device;
@@ -12,5 +13,6 @@ mod events;
 mod queue;
 mod websocket;
 
-pub use client::{CloudClient, CloudStatus};
-pub use events::SessionEventBuilder;
+pub use client::{CloudClient, CloudStatus, DEFAULT_JITTER_FACTOR};
+pub use events::{SessionEventBuilder, TelemetryReceiver, TelemetrySender};
+pub use queue::{run_queue, DEFAULT_MAX_QUEUE_BYTES};
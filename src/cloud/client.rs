@@ -5,19 +5,29 @@
 //! - Session registration
 //! - Event streaming via WebSocket
 //! - Offline queuing
+//! - Connection telemetry (optional, see `with_telemetry`)
 
 use anyhow::{Context, Result};
+use rand::Rng;
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 
 use super::device::DeviceIdentity;
-use super::events::CloudEvent;
-use super::queue::OfflineQueue;
-use super::websocket::{CloudWebSocket, WebSocketHandle};
+use super::events::{CloudEvent, TelemetryEvent, TelemetrySender};
+use super::queue::PersistentOfflineQueue;
+use super::websocket::{
+    BatchingWebSocketHandle, CloudWebSocket, WebSocketHandle, DEFAULT_BATCH_INTERVAL_MS,
+    DEFAULT_BATCH_SIZE,
+};
 
 /// Default API URL
 const DEFAULT_API_URL: &str = "https://drinkcrabigator.com/api";
 
+/// Longest wait between reconnection attempts
+const MAX_BACKOFF_SECS: u64 = 30;
+
+/// Default share of the backoff that is randomized (1.0 = full jitter)
+pub const DEFAULT_JITTER_FACTOR: f64 = 1.0;
 
 /// Response from POST /api/sessions
 #[derive(Debug, Deserialize)]
@@ -51,8 +61,8 @@ pub struct CloudStatus {
     pub connected: bool,
     /// Number of reconnection attempts since last successful connection
     pub reconnect_attempts: u32,
-    /// Current backoff in seconds before next retry
-    pub _backoff_secs: u64,
+    /// Delay in seconds before the next retry (jitter included)
+    pub backoff_secs: u64,
     /// Number of queued events waiting to be sent
     pub _queue_len: usize,
 }
@@ -65,10 +75,14 @@ pub struct CloudClient {
     session_id: Option<String>,
     /// WebSocket URL for reconnection
     ws_url: Option<String>,
-    /// WebSocket handle for bidirectional communication
-    ws_handle: Option<WebSocketHandle>,
+    /// WebSocket handle for bidirectional communication (batches outgoing events)
+    ws_handle: Option<BatchingWebSocketHandle>,
+    /// Maximum events per WebSocket frame
+    batch_size: usize,
+    /// Maximum time an event waits before its batch is flushed
+    batch_interval: std::time::Duration,
     /// Offline queue for when cloud is unreachable
-    queue: OfflineQueue,
+    queue: PersistentOfflineQueue,
     /// HTTP client
     http: HttpClient,
     /// API base URL
@@ -77,12 +91,18 @@ pub struct CloudClient {
     device_registered: bool,
     /// Last reconnection attempt time
     last_reconnect_attempt: Option<std::time::Instant>,
-    /// Reconnection backoff (starts at 1s, max 30s)
+    /// Delay before the next reconnection attempt (starts at 1s, jittered, max 30s)
     reconnect_backoff_secs: u64,
+    /// How much of the backoff is randomized (0.0 = none, 1.0 = full jitter)
+    jitter_factor: f64,
     /// Number of reconnection attempts since last successful connection
     reconnect_attempts: u32,
     /// Pending reconnection attempt (receiver for async connection result)
     pending_reconnect: Option<std::sync::mpsc::Receiver<anyhow::Result<WebSocketHandle>>>,
+    /// When the current WebSocket connected (None once its drop is noticed)
+    connected_at: Option<std::time::Instant>,
+    /// Where connection telemetry goes, if anyone is listening
+    telemetry: Option<TelemetrySender>,
 }
 
 impl CloudClient {
@@ -92,7 +112,7 @@ impl CloudClient {
     /// Call `register_device()` and `register_session()` to connect to the cloud.
     pub fn new() -> Result<Self> {
         let device = DeviceIdentity::load_or_create()?;
-        let queue = OfflineQueue::new()?;
+        let queue = PersistentOfflineQueue::new()?;
         let http = HttpClient::builder()
             .timeout(std::time::Duration::from_secs(30))
             .build()?;
@@ -102,14 +122,19 @@ impl CloudClient {
             session_id: None,
             ws_url: None,
             ws_handle: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            batch_interval: std::time::Duration::from_millis(DEFAULT_BATCH_INTERVAL_MS),
             queue,
             http,
             api_url: DEFAULT_API_URL.to_string(),
             device_registered: false,
             last_reconnect_attempt: None,
             reconnect_backoff_secs: 1,
+            jitter_factor: DEFAULT_JITTER_FACTOR,
             reconnect_attempts: 0,
             pending_reconnect: None,
+            connected_at: None,
+            telemetry: None,
         })
     }
 
@@ -120,6 +145,75 @@ impl CloudClient {
         self
     }
 
+    /// Set event batching limits (from `cloud_batch_size` / `cloud_batch_interval_ms`)
+    pub fn with_batching(mut self, batch_size: usize, interval_ms: u64) -> Self {
+        self.batch_size = batch_size;
+        self.batch_interval = std::time::Duration::from_millis(interval_ms);
+        self
+    }
+
+    /// Cap the offline queue file (from `cloud_max_queue_bytes`)
+    pub fn with_max_queue_bytes(mut self, max_queue_bytes: usize) -> Self {
+        self.queue = self.queue.with_max_queue_bytes(max_queue_bytes);
+        self
+    }
+
+    /// Set how much of the reconnection backoff is randomized (from `cloud_reconnect_jitter`)
+    ///
+    /// 1.0 picks uniformly from zero to the full backoff, so clients dropped by
+    /// the same server restart don't all retry at once; 0.0 is plain doubling.
+    pub fn with_jitter_factor(mut self, jitter_factor: f64) -> Self {
+        self.jitter_factor = jitter_factor.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Report connection telemetry to `sender` (used with --profile)
+    pub fn with_telemetry(mut self, sender: TelemetrySender) -> Self {
+        self.telemetry = Some(sender);
+        self
+    }
+
+    /// Send a telemetry event without blocking; dropped if nobody keeps up
+    fn emit(&self, event: TelemetryEvent) {
+        if let Some(ref tx) = self.telemetry {
+            let _ = tx.try_send(event);
+        }
+    }
+
+    /// Note that a fresh connection is up
+    fn mark_connected(&mut self, started: std::time::Instant) {
+        self.connected_at = Some(std::time::Instant::now());
+        self.emit(TelemetryEvent::Connected {
+            latency_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    /// Report a dropped WebSocket once (call from the main loop)
+    pub fn check_connection(&mut self) {
+        if self.is_connected() {
+            return;
+        }
+        if let Some(connected_at) = self.connected_at.take() {
+            self.emit(TelemetryEvent::Disconnected {
+                after_secs: connected_at.elapsed().as_secs(),
+            });
+        }
+    }
+
+    /// Queue an event for later, reporting anything the queue discards
+    fn queue_event(&mut self, event: CloudEvent) {
+        if let Some(reason) = self.queue.enqueue(event) {
+            self.emit(TelemetryEvent::EventDropped {
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    /// Wrap a raw socket handle with this client's batching limits
+    fn batching(&self, handle: WebSocketHandle) -> BatchingWebSocketHandle {
+        BatchingWebSocketHandle::new(handle, self.batch_size, self.batch_interval)
+    }
+
     /// Get the device ID (for future CLI commands)
     #[allow(dead_code)]
     pub fn device_id(&self) -> &str {
@@ -142,7 +236,7 @@ impl CloudClient {
         CloudStatus {
             connected: self.is_connected(),
             reconnect_attempts: self.reconnect_attempts,
-            _backoff_secs: self.reconnect_backoff_secs,
+            backoff_secs: self.reconnect_backoff_secs,
             _queue_len: self.queue.len(),
         }
     }
@@ -279,6 +373,7 @@ impl CloudClient {
         let message = format!("GET:/api/sessions/{}/connect:{}", self.session_id.as_ref().unwrap(), timestamp);
         let signature = self.device.sign(&message)?;
 
+        let started = std::time::Instant::now();
         let ws = CloudWebSocket::connect(
             ws_url,
             &self.device.device_id,
@@ -290,7 +385,8 @@ impl CloudClient {
         // Split into handle and shutdown receiver
         // For initial connection, we're in the main runtime so tasks stay alive
         let (handle, _shutdown_rx) = ws.into_parts();
-        self.ws_handle = Some(handle);
+        self.ws_handle = Some(self.batching(handle));
+        self.mark_connected(started);
         // Reset backoff and attempts on successful connection
         self.reconnect_backoff_secs = 1;
         self.reconnect_attempts = 0;
@@ -309,13 +405,17 @@ impl CloudClient {
             self.pending_reconnect = None;
             return true;
         }
+        self.check_connection();
 
         // Check if there's a pending reconnection attempt
         if let Some(ref rx) = self.pending_reconnect {
             match rx.try_recv() {
                 Ok(Ok(handle)) => {
                     // Connection succeeded!
-                    self.ws_handle = Some(handle);
+                    self.ws_handle = Some(self.batching(handle));
+                    if let Some(started) = self.last_reconnect_attempt {
+                        self.mark_connected(started);
+                    }
                     self.reconnect_backoff_secs = 1;
                     self.reconnect_attempts = 0;
                     self.pending_reconnect = None;
@@ -323,8 +423,9 @@ impl CloudClient {
                     return true;
                 }
                 Ok(Err(_)) => {
-                    // Connection failed - increase backoff and clear pending
-                    self.reconnect_backoff_secs = (self.reconnect_backoff_secs * 2).min(30);
+                    // Connection failed - back off (with jitter) and clear pending
+                    self.reconnect_backoff_secs =
+                        jittered_backoff_secs(self.reconnect_attempts, self.jitter_factor, &mut rand::thread_rng());
                     self.last_reconnect_attempt = Some(std::time::Instant::now());
                     self.pending_reconnect = None;
                     return false; // Wait for backoff before retrying
@@ -356,6 +457,10 @@ impl CloudClient {
         // Start new reconnection attempt
         self.last_reconnect_attempt = Some(std::time::Instant::now());
         self.reconnect_attempts += 1;
+        self.emit(TelemetryEvent::ReconnectAttempt {
+            attempt: self.reconnect_attempts,
+            backoff_secs: self.reconnect_backoff_secs,
+        });
 
         let timestamp = chrono::Utc::now().timestamp_millis().to_string();
         let session_id = match &self.session_id {
@@ -411,31 +516,45 @@ impl CloudClient {
 
     /// Send an event to the cloud
     ///
-    /// If not connected, attempts to reconnect. Events are queued if offline.
+    /// Events are buffered and sent in batches. If not connected, attempts to
+    /// reconnect. Events are queued if offline.
     pub fn send_event(&mut self, event: CloudEvent) {
-        // Try to send if connected
-        if let Some(ref handle) = self.ws_handle {
-            if handle.is_alive() {
-                if !handle.try_send(event.clone()) {
-                    // Channel full, queue it
-                    self.queue.enqueue(event);
-                }
-                return;
-            }
+        // Not connected - try to reconnect, queue if still offline
+        if !self.is_connected() && !self.try_reconnect() {
+            self.queue_event(event);
+            return;
         }
 
-        // Not connected - try to reconnect
-        if self.try_reconnect() {
-            // Reconnected! Try to send
-            if let Some(ref handle) = self.ws_handle {
-                if handle.try_send(event.clone()) {
-                    return;
-                }
-            }
+        let rejected = match self.ws_handle {
+            Some(ref mut handle) => handle.try_send(event),
+            None => vec![event],
+        };
+        // Channel full or closed mid-flush, queue what didn't make it
+        for event in rejected {
+            self.queue_event(event);
+        }
+    }
+
+    /// Flush the pending batch if its interval has elapsed (call from the main loop)
+    pub fn flush_due_events(&mut self) {
+        let rejected = match self.ws_handle {
+            Some(ref mut handle) => handle.flush_if_due(),
+            None => return,
+        };
+        for event in rejected {
+            self.queue_event(event);
         }
+    }
 
-        // Still not connected, queue it
-        self.queue.enqueue(event);
+    /// Flush the pending batch immediately (e.g. before ending the session)
+    pub fn flush_events(&mut self) {
+        let rejected = match self.ws_handle {
+            Some(ref mut handle) => handle.flush(),
+            None => return,
+        };
+        for event in rejected {
+            self.queue_event(event);
+        }
     }
 
     /// Try to receive an answer from mobile (non-blocking)
@@ -450,13 +569,23 @@ impl CloudClient {
 
     /// Drain queued events after reconnection
     fn drain_queue(&mut self) {
+        let Some(ref mut handle) = self.ws_handle else {
+            return;
+        };
         if self.queue.is_empty() {
             return;
         }
 
-        let events = self.queue.drain();
-        for queued in events {
-            self.send_event(queued.event);
+        let events: Vec<CloudEvent> = self.queue.drain().into_iter().map(|q| q.event).collect();
+        let total = events.len();
+        // Send the backlog in full batches rather than one frame per event
+        let rejected = handle.send_all(events);
+        let sent = total - rejected.len();
+        for event in rejected {
+            self.queue_event(event);
+        }
+        if sent > 0 {
+            self.emit(TelemetryEvent::QueueFlushed { count: sent });
         }
     }
 
@@ -567,3 +696,116 @@ impl CloudClient {
         Ok(())
     }
 }
+
+/// Delay before the next attempt after `attempts` failures in a row
+///
+/// The cap doubles per failure up to `MAX_BACKOFF_SECS`; `jitter_factor` of it
+/// is drawn at random, so full jitter gives a delay anywhere in `[0, cap]`.
+fn jittered_backoff_secs(attempts: u32, jitter_factor: f64, rng: &mut impl Rng) -> u64 {
+    let cap = 1u64.checked_shl(attempts).unwrap_or(u64::MAX).min(MAX_BACKOFF_SECS);
+    let random = rng.gen_range(0..=cap) as f64;
+    let delay = cap as f64 * (1.0 - jitter_factor) + random * jitter_factor;
+    (delay.round() as u64).min(cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::SessionEventBuilder;
+    use tokio::sync::mpsc;
+
+    /// Client with a throwaway identity and queue, already "connected"
+    fn connected_client() -> (CloudClient, mpsc::Receiver<Vec<CloudEvent>>, mpsc::Receiver<TelemetryEvent>) {
+        let (telemetry_tx, telemetry_rx) = mpsc::channel(16);
+        let (handle, socket_rx) = WebSocketHandle::detached();
+        // Kept for the rest of the test run; each client gets its own queue file
+        let queue_path = tempfile::tempdir().unwrap().keep().join("offline-queue.jsonl");
+        let mut client = CloudClient {
+            device: DeviceIdentity {
+                device_id: "test-device".to_string(),
+                device_secret: "c2VjcmV0".to_string(),
+                name: None,
+            },
+            session_id: None,
+            ws_url: None,
+            ws_handle: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            batch_interval: std::time::Duration::from_millis(DEFAULT_BATCH_INTERVAL_MS),
+            queue: PersistentOfflineQueue::at_path(queue_path),
+            http: HttpClient::new(),
+            api_url: DEFAULT_API_URL.to_string(),
+            device_registered: false,
+            last_reconnect_attempt: None,
+            reconnect_backoff_secs: 1,
+            jitter_factor: DEFAULT_JITTER_FACTOR,
+            reconnect_attempts: 0,
+            pending_reconnect: None,
+            connected_at: None,
+            telemetry: None,
+        }
+        .with_telemetry(telemetry_tx);
+        client.ws_handle = Some(client.batching(handle));
+        client.mark_connected(std::time::Instant::now());
+        (client, socket_rx, telemetry_rx)
+    }
+
+    #[test]
+    fn test_jittered_backoff_stays_in_range() {
+        let mut rng = rand::thread_rng();
+        for attempts in 0..40 {
+            let cap = (1u64 << attempts.min(63)).min(30);
+            for _ in 0..200 {
+                let delay = jittered_backoff_secs(attempts, 1.0, &mut rng);
+                assert!(delay <= cap, "{}s after {} failures", delay, attempts);
+            }
+        }
+        // Full jitter actually spreads the delays out
+        let delays: std::collections::HashSet<u64> =
+            (0..200).map(|_| jittered_backoff_secs(10, 1.0, &mut rng)).collect();
+        assert!(delays.len() > 5);
+
+        // No jitter is plain doubling, capped
+        assert_eq!(jittered_backoff_secs(1, 0.0, &mut rng), 2);
+        assert_eq!(jittered_backoff_secs(3, 0.0, &mut rng), 8);
+        assert_eq!(jittered_backoff_secs(5, 0.0, &mut rng), 30);
+        // Half jitter never drops below half the cap
+        for _ in 0..200 {
+            assert!((4..=8).contains(&jittered_backoff_secs(3, 0.5, &mut rng)));
+        }
+    }
+
+    #[test]
+    fn test_disconnected_emitted_when_socket_drops() {
+        let (mut client, socket_rx, mut telemetry) = connected_client();
+        assert!(matches!(telemetry.try_recv(), Ok(TelemetryEvent::Connected { .. })));
+
+        // Still up: nothing to report
+        client.check_connection();
+        assert!(telemetry.try_recv().is_err());
+
+        drop(socket_rx);
+        client.check_connection();
+        assert_eq!(telemetry.try_recv(), Ok(TelemetryEvent::Disconnected { after_secs: 0 }));
+
+        // The drop is only reported once
+        client.check_connection();
+        assert!(telemetry.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_sending_after_drop_reports_disconnect_and_dropped_events() {
+        let (mut client, socket_rx, mut telemetry) = connected_client();
+        let _ = telemetry.try_recv();
+        drop(socket_rx);
+
+        // No ws_url, so no reconnect is attempted; the title is queued offline
+        client.send_event(SessionEventBuilder::title("queued".to_string()));
+        assert_eq!(telemetry.try_recv(), Ok(TelemetryEvent::Disconnected { after_secs: 0 }));
+        assert!(telemetry.try_recv().is_err());
+        assert_eq!(client.status()._queue_len, 1);
+
+        // Screen events are never queued, which counts as a drop
+        client.send_event(SessionEventBuilder::screen("frame".to_string()));
+        assert!(matches!(telemetry.try_recv(), Ok(TelemetryEvent::EventDropped { .. })));
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! These types mirror the TypeScript SessionEvent types on the server.
 
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::git::GitState;
 use crate::parsers::{ChangeType, DiffSummary, NodeKind};
@@ -27,7 +30,10 @@ impl From<crate::platforms::SessionState> for CloudSessionState {
             crate::platforms::SessionState::Permission => CloudSessionState::Permission,
             crate::platforms::SessionState::Question => CloudSessionState::Question,
             crate::platforms::SessionState::Complete => CloudSessionState::Complete,
-            crate::platforms::SessionState::Interrupted => CloudSessionState::Interrupted,
+            // The server has no paused state; input is stopped either way
+            crate::platforms::SessionState::Interrupted | crate::platforms::SessionState::Paused => {
+                CloudSessionState::Interrupted
+            }
         }
     }
 }
@@ -183,6 +189,7 @@ pub struct StatsEvent {
 }
 
 impl StatsEvent {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         prompts: u32,
         completions: u32,
@@ -269,6 +276,44 @@ pub enum CloudToDesktopMessage {
     Key { key: String },
 }
 
+/// Connection-quality event from the cloud client, for local debugging only
+///
+/// Never sent to the server; with --profile the app appends these to
+/// `telemetry.log` in the session directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelemetryEvent {
+    /// WebSocket (re)connected, `latency_ms` after the attempt started
+    Connected { latency_ms: u64 },
+    /// WebSocket dropped after being up for `after_secs`
+    Disconnected { after_secs: u64 },
+    /// Reconnection attempt started
+    ReconnectAttempt { attempt: u32, backoff_secs: u64 },
+    /// An event was discarded instead of sent or queued
+    EventDropped { reason: String },
+    /// Queued events were resent after reconnecting
+    QueueFlushed { count: usize },
+}
+
+impl fmt::Display for TelemetryEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connected { latency_ms } => write!(f, "connected latency_ms={}", latency_ms),
+            Self::Disconnected { after_secs } => write!(f, "disconnected after_secs={}", after_secs),
+            Self::ReconnectAttempt { attempt, backoff_secs } => {
+                write!(f, "reconnect_attempt attempt={} backoff_secs={}", attempt, backoff_secs)
+            }
+            Self::EventDropped { reason } => write!(f, "event_dropped reason={:?}", reason),
+            Self::QueueFlushed { count } => write!(f, "queue_flushed count={}", count),
+        }
+    }
+}
+
+/// Where the cloud client reports telemetry
+pub type TelemetrySender = mpsc::Sender<TelemetryEvent>;
+
+/// App-side end of the telemetry channel
+pub type TelemetryReceiver = mpsc::Receiver<TelemetryEvent>;
+
 /// Helper for building events from crabigator's internal state
 pub struct SessionEventBuilder;
 
@@ -381,6 +426,8 @@ fn node_kind_label(kind: &NodeKind) -> &'static str {
         NodeKind::Impl => "Impl",
         NodeKind::Module => "Module",
         NodeKind::Const => "Const",
+        NodeKind::Selector => "Selector",
+        NodeKind::MediaQuery => "MediaQuery",
         NodeKind::Other => "Other",
     }
 }
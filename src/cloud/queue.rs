@@ -1,25 +1,35 @@
 //! Offline queue for events when cloud is unreachable
 //!
-//! Events are queued in memory and persisted to disk, then drained
-//! when the cloud connection is restored.
+//! Each queued event is appended as one JSON line to
+//! `~/.crabigator/offline-queue.jsonl` as it arrives, so a crash or kill
+//! loses nothing: the next session finds the file and sends its events once
+//! connected. The file is capped at `max_queue_bytes`, oldest events first
+//! out. `crabigator queue --show` lists what is waiting.
+//!
+//! Every crabigator instance shares the file, so each read, append and
+//! rewrite happens under an exclusive `flock` on `offline-queue.jsonl.lock`;
+//! otherwise one instance's drain could delete lines another just appended.
 //!
 //! IMPORTANT: We don't queue screen events because they're large (~200KB each)
-//! and ephemeral. Queuing them causes O(n) serialization on every enqueue.
+//! and ephemeral.
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::fs;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use super::events::CloudEvent;
+use crate::coordinator::open_lock_file;
+
+/// Default cap on the queue file (`cloud_max_queue_bytes`)
+pub const DEFAULT_MAX_QUEUE_BYTES: usize = 10 * 1024 * 1024;
 
-/// Maximum number of events to keep in the queue
-const MAX_QUEUE_SIZE: usize = 100;
+/// Queue file written by earlier versions (a JSON array rewritten every 10s)
+const LEGACY_QUEUE_FILE: &str = "offline_queue.json";
 
-/// Minimum interval between persists (avoid O(n) serialization storm)
-const PERSIST_INTERVAL: Duration = Duration::from_secs(10);
+const QUEUE_FILE: &str = "offline-queue.jsonl";
 
 /// Queued item with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,129 +38,364 @@ pub struct QueuedEvent {
     pub queued_at: u64,
 }
 
-/// Offline event queue with disk persistence
-pub struct OfflineQueue {
-    queue: VecDeque<QueuedEvent>,
+/// Offline event queue kept in a JSON-lines file
+///
+/// The file is the queue: `enqueue` appends a line, `drain` reads the file
+/// and removes it. Other instances may change it at any time, so only a
+/// count for display is kept in memory.
+pub struct PersistentOfflineQueue {
     queue_path: PathBuf,
-    max_size: usize,
-    /// Last time we persisted to disk (throttled to avoid O(n) storms)
-    last_persist: Instant,
-    /// Whether queue has been modified since last persist
-    dirty: bool,
+    max_queue_bytes: usize,
+    /// Events in the file when this instance last read or wrote it
+    len: usize,
 }
 
-impl OfflineQueue {
-    /// Create a new offline queue, loading from disk if exists
+impl PersistentOfflineQueue {
+    /// Open the queue, picking up events a previous session left behind
     pub fn new() -> Result<Self> {
-        let queue_path = Self::queue_path()?;
+        let dir = Self::queue_dir()?;
+        let mut queue = Self::at_path(dir.join(QUEUE_FILE));
+        queue.import_legacy(&dir.join(LEGACY_QUEUE_FILE));
+        Ok(queue)
+    }
 
-        let queue = if queue_path.exists() {
-            let content = fs::read_to_string(&queue_path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            VecDeque::new()
+    /// Queue backed by `queue_path`, loading any events already in it
+    pub fn at_path(queue_path: PathBuf) -> Self {
+        let mut queue = Self {
+            queue_path,
+            max_queue_bytes: DEFAULT_MAX_QUEUE_BYTES,
+            len: 0,
         };
+        queue.reload();
+        queue
+    }
 
-        Ok(Self {
-            queue,
-            queue_path,
-            max_size: MAX_QUEUE_SIZE,
-            last_persist: Instant::now(),
-            dirty: false,
-        })
+    /// Cap the queue file at `max_queue_bytes`, dropping the oldest events
+    /// if it is already over
+    pub fn with_max_queue_bytes(mut self, max_queue_bytes: usize) -> Self {
+        self.max_queue_bytes = max_queue_bytes;
+        let _lock = self.lock();
+        if self.file_bytes() > self.max_queue_bytes {
+            let _ = self.trim(read_lines(&self.queue_path));
+        }
+        self
     }
 
-    /// Get the queue file path
-    fn queue_path() -> Result<PathBuf> {
+    fn queue_dir() -> Result<PathBuf> {
         let home = dirs::home_dir().context("Could not determine home directory")?;
-        Ok(home.join(".crabigator").join("offline_queue.json"))
+        Ok(home.join(".crabigator"))
+    }
+
+    /// Path of the queue file in ~/.crabigator
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(Self::queue_dir()?.join(QUEUE_FILE))
+    }
+
+    /// Exclusive lock on the queue file, held until the returned file is
+    /// dropped. Without one (unwritable directory) the queue works unlocked.
+    fn lock(&self) -> Option<File> {
+        let file = open_lock_file(&self.queue_path.with_extension("jsonl.lock")).ok()?;
+        file.lock().ok()?;
+        Some(file)
+    }
+
+    /// Size of the queue file in bytes, as other instances left it
+    fn file_bytes(&self) -> usize {
+        fs::metadata(&self.queue_path).map(|m| m.len() as usize).unwrap_or(0)
+    }
+
+    /// Recount the file, rewriting it without lines that don't parse (e.g. one
+    /// cut short by a crash)
+    fn reload(&mut self) {
+        let _lock = self.lock();
+        let contents = fs::read_to_string(&self.queue_path).unwrap_or_default();
+        let lines = read_lines(&self.queue_path);
+        let valid_bytes: usize = lines.iter().map(|line| line.len() + 1).sum();
+        if valid_bytes != contents.len() || valid_bytes > self.max_queue_bytes {
+            let _ = self.trim(lines);
+        } else {
+            self.len = lines.len();
+        }
+    }
+
+    /// Move events from an old `offline_queue.json` into the queue file
+    fn import_legacy(&mut self, legacy_path: &Path) {
+        let Ok(content) = fs::read_to_string(legacy_path) else {
+            return;
+        };
+        let _lock = self.lock();
+        let events: VecDeque<QueuedEvent> = serde_json::from_str(&content).unwrap_or_default();
+        for queued in events {
+            if let Ok(line) = serde_json::to_string(&queued) {
+                self.push_line(line);
+            }
+        }
+        let _ = fs::remove_file(legacy_path);
     }
 
     /// Add an event to the queue
     ///
-    /// Note: Screen and scrollback events are skipped - they're large (~200KB) and
-    /// ephemeral. Queuing them causes O(n) serialization overhead.
-    pub fn enqueue(&mut self, event: CloudEvent) {
+    /// Note: Screen and scrollback events are skipped - they're large (~200KB)
+    /// and ephemeral.
+    ///
+    /// Returns why an event was lost, if one was (this one, or the oldest queued).
+    pub fn enqueue(&mut self, event: CloudEvent) -> Option<&'static str> {
         // Skip large ephemeral events - they're not worth queuing
         if matches!(event, CloudEvent::Screen { .. } | CloudEvent::Scrollback { .. }) {
-            return;
-        }
-
-        // Drop oldest if at capacity
-        while self.queue.len() >= self.max_size {
-            self.queue.pop_front();
+            return Some("screen/scrollback events are not queued offline");
         }
 
         let queued = QueuedEvent {
             event,
             queued_at: chrono::Utc::now().timestamp_millis() as u64,
         };
+        let Ok(line) = serde_json::to_string(&queued) else {
+            return Some("event could not be serialized for the offline queue");
+        };
+        if line.len() + 1 > self.max_queue_bytes {
+            return Some("event larger than the offline queue");
+        }
+        let _lock = self.lock();
+        self.push_line(line)
+    }
+
+    /// Append one line, trimming the oldest events if that passes the cap;
+    /// the caller holds the lock
+    fn push_line(&mut self, line: String) -> Option<&'static str> {
+        if self.file_bytes() + line.len() + 1 > self.max_queue_bytes {
+            let mut lines = read_lines(&self.queue_path);
+            lines.push(line);
+            let before = lines.len();
+            let _ = self.trim(lines);
+            return (self.len < before).then_some("offline queue full, oldest event dropped");
+        }
 
-        self.queue.push_back(queued);
-        self.dirty = true;
+        if let Err(e) = append_line(&self.queue_path, &line) {
+            tracing::warn!(error = %e, "failed to append to offline queue");
+            return Some("offline queue file could not be written");
+        }
+        self.len += 1;
+        None
+    }
 
-        // Throttle persistence to avoid O(n) serialization storm
-        if self.last_persist.elapsed() >= PERSIST_INTERVAL {
-            let _ = self.persist();
-            self.last_persist = Instant::now();
-            self.dirty = false;
+    /// Rewrite the file with the newest of `lines` that fit the cap; the
+    /// caller holds the lock
+    fn trim(&mut self, mut lines: Vec<String>) -> Result<()> {
+        let mut bytes: usize = lines.iter().map(|line| line.len() + 1).sum();
+        let mut dropped = 0;
+        while bytes > self.max_queue_bytes && dropped < lines.len() {
+            bytes -= lines[dropped].len() + 1;
+            dropped += 1;
         }
+        lines.drain(..dropped);
+        self.len = lines.len();
+        write_lines(&self.queue_path, &lines)
     }
 
-    /// Drain all events from the queue
+    /// Take every queued event, oldest first, and empty the file
     pub fn drain(&mut self) -> Vec<QueuedEvent> {
-        let events: Vec<_> = self.queue.drain(..).collect();
-        self.dirty = false;
-        let _ = self.persist();
+        let _lock = self.lock();
+        let events = read_events(&self.queue_path);
+        let _ = fs::remove_file(&self.queue_path);
+        self.len = 0;
         events
     }
 
-    /// Persist if dirty (call on shutdown or connection restore)
+    /// Nothing to do: every event is written as it is queued
     #[allow(dead_code)]
-    pub fn flush(&mut self) {
-        if self.dirty {
-            let _ = self.persist();
-            self.dirty = false;
-        }
-    }
+    pub fn flush(&mut self) {}
 
-    /// Check if queue is empty
+    /// Whether the file is empty, including events other instances queued
     pub fn is_empty(&self) -> bool {
-        self.queue.is_empty()
+        self.file_bytes() == 0
     }
 
-    /// Get queue length
+    /// Events in the file as of this instance's last read or write (for
+    /// display; other instances may have changed it since)
     pub fn len(&self) -> usize {
-        self.queue.len()
-    }
-
-    /// Persist queue to disk
-    fn persist(&self) -> Result<()> {
-        if let Some(parent) = self.queue_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        let content = serde_json::to_string(&self.queue)?;
-        fs::write(&self.queue_path, content)?;
-        Ok(())
+        self.len
     }
 
     /// Clear the queue and remove the file (for testing)
     #[allow(dead_code)]
     pub fn clear(&mut self) {
-        self.queue.clear();
-        let _ = fs::remove_file(&self.queue_path);
+        self.drain();
     }
 }
 
-impl Default for OfflineQueue {
+impl Default for PersistentOfflineQueue {
     fn default() -> Self {
-        Self::new().unwrap_or_else(|_| Self {
-            queue: VecDeque::new(),
-            queue_path: PathBuf::from("/tmp/crabigator_offline_queue.json"),
-            max_size: MAX_QUEUE_SIZE,
-            last_persist: Instant::now(),
-            dirty: false,
-        })
+        Self::new().unwrap_or_else(|_| Self::at_path(std::env::temp_dir().join("crabigator-offline-queue.jsonl")))
+    }
+}
+
+/// Lines of the queue file that parse as events
+fn read_lines(path: &Path) -> Vec<String> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    contents
+        .lines()
+        .filter(|line| serde_json::from_str::<QueuedEvent>(line).is_ok())
+        .map(str::to_string)
+        .collect()
+}
+
+fn read_events(path: &Path) -> Vec<QueuedEvent> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Append `line` in a single write, so a crash leaves at most one partial line
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format!("{}\n", line).as_bytes())
+}
+
+/// Replace the file with `lines`: write a temp file, then rename over it
+fn write_lines(path: &Path, lines: &[String]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("jsonl.tmp");
+    let contents: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    fs::write(&tmp_path, contents).with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+/// `crabigator queue [--show]`: count the pending events, or list them
+pub fn run_queue(show: bool) -> Result<()> {
+    let path = PersistentOfflineQueue::default_path()?;
+    let events = read_events(&path);
+    let bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    println!("{} queued event(s), {} bytes ({})", events.len(), bytes, path.display());
+    if show {
+        for queued in &events {
+            println!("{}", describe(queued));
+        }
+    }
+    Ok(())
+}
+
+/// `2026-01-05 14:03:12  state  {"type":"state","state":"ready"}`, the JSON
+/// cut to 100 characters
+fn describe(queued: &QueuedEvent) -> String {
+    let when = chrono::DateTime::from_timestamp_millis(queued.queued_at as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| queued.queued_at.to_string());
+    let json = serde_json::to_value(&queued.event).unwrap_or_default();
+    let event_type = json.get("type").and_then(|t| t.as_str()).unwrap_or("?").to_string();
+    let mut body = json.to_string();
+    if body.chars().count() > 100 {
+        body = format!("{}…", body.chars().take(99).collect::<String>());
+    }
+    format!("{}  {:<10} {}", when, event_type, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::SessionEventBuilder;
+
+    fn title(text: &str) -> CloudEvent {
+        SessionEventBuilder::title(text.to_string())
+    }
+
+    fn titles(events: Vec<QueuedEvent>) -> Vec<String> {
+        events
+            .into_iter()
+            .map(|q| serde_json::to_value(q.event).unwrap()["title"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_events_survive_a_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(QUEUE_FILE);
+
+        let mut queue = PersistentOfflineQueue::at_path(path.clone());
+        assert_eq!(queue.enqueue(title("one")), None);
+        assert_eq!(queue.enqueue(title("two")), None);
+        // Killed mid-append: the last line is cut short
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"event\":{\"type\":\"ti").unwrap();
+        drop(queue);
+
+        let mut recovered = PersistentOfflineQueue::at_path(path.clone());
+        assert_eq!(recovered.len(), 2);
+        assert_eq!(recovered.enqueue(title("three")), None);
+        assert_eq!(titles(recovered.drain()), vec!["one", "two", "three"]);
+        assert!(recovered.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_size_limit_drops_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(QUEUE_FILE);
+        let line_len = serde_json::to_string(&QueuedEvent { event: title("event 0"), queued_at: 0 })
+            .unwrap()
+            .len();
+
+        // Room for three events (timestamps vary in length, so leave some slack)
+        let max = (line_len + 20) * 3;
+        let mut queue = PersistentOfflineQueue::at_path(path.clone()).with_max_queue_bytes(max);
+        for i in 0..3 {
+            assert_eq!(queue.enqueue(title(&format!("event {}", i))), None);
+        }
+        assert!(queue.enqueue(title("event 3")).unwrap().contains("oldest event dropped"));
+        assert_eq!(queue.len(), 3);
+        assert!(fs::metadata(&path).unwrap().len() as usize <= max);
+
+        // A later session with a smaller cap trims what it finds
+        let mut queue = PersistentOfflineQueue::at_path(path).with_max_queue_bytes(max / 3);
+        assert_eq!(titles(queue.drain()), vec!["event 3"]);
+    }
+
+    #[test]
+    fn test_instances_share_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(QUEUE_FILE);
+        let mut drainer = PersistentOfflineQueue::at_path(path.clone());
+        assert!(drainer.is_empty());
+
+        let writers: Vec<_> = (0..4)
+            .map(|w| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let mut queue = PersistentOfflineQueue::at_path(path);
+                    for i in 0..500 {
+                        assert_eq!(queue.enqueue(title(&format!("{}-{}", w, i))), None);
+                    }
+                })
+            })
+            .collect();
+        // Drains racing the appends must not lose any of them
+        let mut drained = Vec::new();
+        while writers.iter().any(|w| !w.is_finished()) {
+            if !drainer.is_empty() {
+                drained.extend(titles(drainer.drain()));
+            }
+        }
+        writers.into_iter().for_each(|w| w.join().unwrap());
+        drained.extend(titles(drainer.drain()));
+        assert_eq!(drained.len(), 2000);
+    }
+
+    #[test]
+    fn test_screen_events_and_legacy_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = PersistentOfflineQueue::at_path(dir.path().join(QUEUE_FILE));
+        assert!(queue.enqueue(SessionEventBuilder::screen("frame".to_string())).is_some());
+        assert!(queue.is_empty());
+
+        let legacy = dir.path().join(LEGACY_QUEUE_FILE);
+        let old = vec![QueuedEvent { event: title("old"), queued_at: 1 }];
+        fs::write(&legacy, serde_json::to_string(&old).unwrap()).unwrap();
+        queue.import_legacy(&legacy);
+        assert!(!legacy.exists());
+        assert_eq!(titles(queue.drain()), vec!["old"]);
     }
 }
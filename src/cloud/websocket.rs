@@ -1,8 +1,10 @@
 //! WebSocket connection to the cloud for bidirectional communication
 //!
-//! - Sends events from desktop to cloud
+//! - Sends events from desktop to cloud (batched into JSON arrays)
 //! - Receives answers from mobile devices
 
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
 use base64::Engine;
 use futures_util::{SinkExt, StreamExt};
@@ -14,10 +16,16 @@ use tokio_tungstenite::{
 
 use super::events::{CloudEvent, CloudToDesktopMessage};
 
+/// Default maximum number of events per frame
+pub const DEFAULT_BATCH_SIZE: usize = 10;
+
+/// Default maximum time an event waits in the batch buffer
+pub const DEFAULT_BATCH_INTERVAL_MS: u64 = 50;
+
 /// WebSocket connection handle
 pub struct CloudWebSocket {
-    /// Sender for outgoing events
-    event_tx: mpsc::Sender<CloudEvent>,
+    /// Sender for outgoing event batches
+    event_tx: mpsc::Sender<Vec<CloudEvent>>,
     /// Receiver for incoming answers
     answer_rx: mpsc::Receiver<String>,
     /// Receiver for incoming key commands
@@ -56,8 +64,8 @@ impl CloudWebSocket {
 
         let (mut write, mut read) = ws_stream.split();
 
-        // Channel for outgoing events (desktop -> cloud)
-        let (event_tx, mut event_rx) = mpsc::channel::<CloudEvent>(100);
+        // Channel for outgoing event batches (desktop -> cloud)
+        let (event_tx, mut event_rx) = mpsc::channel::<Vec<CloudEvent>>(100);
 
         // Channel for incoming answers (cloud -> desktop)
         let (answer_tx, answer_rx) = mpsc::channel::<String>(16);
@@ -68,12 +76,12 @@ impl CloudWebSocket {
         // Channel to signal when connection closes (read task will signal this)
         let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>(1);
 
-        // Spawn task to handle outgoing events
+        // Spawn task to handle outgoing events (one frame per batch)
         tokio::spawn(async move {
-            while let Some(event) = event_rx.recv().await {
-                let json = match serde_json::to_string(&event) {
-                    Ok(j) => j,
-                    Err(_) => continue,
+            while let Some(batch) = event_rx.recv().await {
+                let json = match encode_batch(&batch) {
+                    Some(j) => j,
+                    None => continue,
                 };
 
                 if write.send(Message::Text(json)).await.is_err() {
@@ -115,9 +123,20 @@ impl CloudWebSocket {
     }
 }
 
+/// Serialize a batch for a single WebSocket frame
+///
+/// A lone event is sent as a plain object; anything larger becomes a JSON array.
+fn encode_batch(batch: &[CloudEvent]) -> Option<String> {
+    match batch {
+        [] => None,
+        [event] => serde_json::to_string(event).ok(),
+        events => serde_json::to_string(events).ok(),
+    }
+}
+
 /// Non-async WebSocket handle for use in the main loop
 pub struct WebSocketHandle {
-    event_tx: mpsc::Sender<CloudEvent>,
+    event_tx: mpsc::Sender<Vec<CloudEvent>>,
     answer_rx: mpsc::Receiver<String>,
     key_rx: mpsc::Receiver<String>,
 }
@@ -135,9 +154,11 @@ impl CloudWebSocket {
 }
 
 impl WebSocketHandle {
-    /// Try to send an event (non-blocking)
-    pub fn try_send(&self, event: CloudEvent) -> bool {
-        self.event_tx.try_send(event).is_ok()
+    /// Try to send a batch of events as one frame (non-blocking)
+    ///
+    /// Returns the batch back if the channel is full or closed.
+    pub fn try_send_batch(&self, batch: Vec<CloudEvent>) -> Result<(), Vec<CloudEvent>> {
+        self.event_tx.try_send(batch).map_err(|e| e.into_inner())
     }
 
     /// Try to receive an answer (non-blocking)
@@ -155,3 +176,199 @@ impl WebSocketHandle {
         !self.event_tx.is_closed()
     }
 }
+
+#[cfg(test)]
+impl WebSocketHandle {
+    /// Handle backed by plain channels; dropping the returned receiver
+    /// looks like the connection closing
+    pub fn detached() -> (Self, mpsc::Receiver<Vec<CloudEvent>>) {
+        let (event_tx, event_rx) = mpsc::channel(100);
+        let (_answer_tx, answer_rx) = mpsc::channel(1);
+        let (_key_tx, key_rx) = mpsc::channel(1);
+        (Self { event_tx, answer_rx, key_rx }, event_rx)
+    }
+}
+
+/// Buffers outgoing events and hands them to a `WebSocketHandle` in batches
+///
+/// A batch is flushed once it holds `batch_size` events or once its oldest
+/// event has waited `interval`, whichever comes first. The time trigger relies
+/// on `flush_if_due()` being polled from the main loop.
+pub struct BatchingWebSocketHandle {
+    inner: WebSocketHandle,
+    buffer: Vec<CloudEvent>,
+    batch_size: usize,
+    interval: Duration,
+    /// When the oldest buffered event was pushed
+    oldest: Option<Instant>,
+}
+
+impl BatchingWebSocketHandle {
+    pub fn new(inner: WebSocketHandle, batch_size: usize, interval: Duration) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            batch_size: batch_size.max(1),
+            interval,
+            oldest: None,
+        }
+    }
+
+    /// Buffer an event, flushing if either trigger fires
+    ///
+    /// Returns any events that could not be handed to the socket.
+    pub fn try_send(&mut self, event: CloudEvent) -> Vec<CloudEvent> {
+        self.try_send_at(event, Instant::now())
+    }
+
+    fn try_send_at(&mut self, event: CloudEvent, now: Instant) -> Vec<CloudEvent> {
+        self.oldest.get_or_insert(now);
+        self.buffer.push(event);
+        if self.buffer.len() >= self.batch_size {
+            return self.flush();
+        }
+        self.flush_if_due_at(now)
+    }
+
+    /// Flush the buffer if its oldest event has waited long enough
+    pub fn flush_if_due(&mut self) -> Vec<CloudEvent> {
+        self.flush_if_due_at(Instant::now())
+    }
+
+    fn flush_if_due_at(&mut self, now: Instant) -> Vec<CloudEvent> {
+        match self.oldest {
+            Some(oldest) if now.duration_since(oldest) >= self.interval => self.flush(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Send everything buffered now, regardless of triggers
+    pub fn flush(&mut self) -> Vec<CloudEvent> {
+        self.oldest = None;
+        if self.buffer.is_empty() {
+            return Vec::new();
+        }
+        let batch = std::mem::take(&mut self.buffer);
+        self.inner.try_send_batch(batch).err().unwrap_or_default()
+    }
+
+    /// Send a backlog of events (e.g. the offline queue) in `batch_size` frames
+    ///
+    /// Anything already buffered goes first to preserve ordering.
+    pub fn send_all(&mut self, events: Vec<CloudEvent>) -> Vec<CloudEvent> {
+        let mut rejected = self.flush();
+        let mut events = events.into_iter().peekable();
+        while events.peek().is_some() {
+            let batch: Vec<CloudEvent> = events.by_ref().take(self.batch_size).collect();
+            if let Err(batch) = self.inner.try_send_batch(batch) {
+                rejected.extend(batch);
+            }
+        }
+        rejected
+    }
+
+    /// Number of events waiting for the next flush
+    #[cfg(test)]
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Try to receive an answer (non-blocking)
+    pub fn try_recv_answer(&mut self) -> Option<String> {
+        self.inner.try_recv_answer()
+    }
+
+    /// Try to receive a key command (non-blocking)
+    pub fn try_recv_key(&mut self) -> Option<String> {
+        self.inner.try_recv_key()
+    }
+
+    /// Check if the connection is still alive
+    pub fn is_alive(&self) -> bool {
+        self.inner.is_alive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::SessionEventBuilder;
+
+    fn test_handle(
+        batch_size: usize,
+        interval: Duration,
+    ) -> (BatchingWebSocketHandle, mpsc::Receiver<Vec<CloudEvent>>) {
+        let (inner, event_rx) = WebSocketHandle::detached();
+        (BatchingWebSocketHandle::new(inner, batch_size, interval), event_rx)
+    }
+
+    fn title(n: usize) -> CloudEvent {
+        SessionEventBuilder::title(format!("title {}", n))
+    }
+
+    #[test]
+    fn test_flushes_when_batch_is_full() {
+        let (mut handle, mut rx) = test_handle(3, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(handle.try_send_at(title(1), now).is_empty());
+        assert!(handle.try_send_at(title(2), now).is_empty());
+        assert!(rx.try_recv().is_err());
+        assert_eq!(handle.pending(), 2);
+
+        assert!(handle.try_send_at(title(3), now).is_empty());
+        assert_eq!(rx.try_recv().unwrap().len(), 3);
+        assert_eq!(handle.pending(), 0);
+    }
+
+    #[test]
+    fn test_flushes_when_interval_elapses() {
+        let (mut handle, mut rx) = test_handle(10, Duration::from_millis(50));
+        let start = Instant::now();
+
+        handle.try_send_at(title(1), start);
+        handle.try_send_at(title(2), start + Duration::from_millis(20));
+        assert!(handle.flush_if_due_at(start + Duration::from_millis(49)).is_empty());
+        assert!(rx.try_recv().is_err());
+
+        handle.flush_if_due_at(start + Duration::from_millis(50));
+        assert_eq!(rx.try_recv().unwrap().len(), 2);
+
+        // Timer restarts with the next event
+        handle.try_send_at(title(3), start + Duration::from_millis(60));
+        handle.flush_if_due_at(start + Duration::from_millis(100));
+        assert!(rx.try_recv().is_err());
+        handle.flush_if_due_at(start + Duration::from_millis(110));
+        assert_eq!(rx.try_recv().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_send_all_chunks_by_batch_size() {
+        let (mut handle, mut rx) = test_handle(4, Duration::from_secs(60));
+        handle.try_send(title(0));
+
+        let rejected = handle.send_all((1..=9).map(title).collect());
+        assert!(rejected.is_empty());
+
+        let sizes: Vec<usize> = std::iter::from_fn(|| rx.try_recv().ok()).map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![1, 4, 4, 1]);
+    }
+
+    #[test]
+    fn test_rejected_events_are_returned_when_closed() {
+        let (mut handle, rx) = test_handle(2, Duration::from_secs(60));
+        drop(rx);
+
+        assert!(handle.try_send(title(1)).is_empty());
+        assert_eq!(handle.try_send(title(2)).len(), 2);
+        assert!(!handle.is_alive());
+    }
+
+    #[test]
+    fn test_encode_batch_shapes() {
+        assert_eq!(encode_batch(&[]), None);
+        assert!(encode_batch(&[title(1)]).unwrap().starts_with('{'));
+        let multi = encode_batch(&[title(1), title(2)]).unwrap();
+        assert!(multi.starts_with('[') && multi.ends_with(']'));
+    }
+}
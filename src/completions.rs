@@ -0,0 +1,263 @@
+//! Shell completion scripts
+//!
+//! `crabigator completions --shell SHELL` prints a completion script for
+//! the given shell. Only Nushell is generated so far; its completions are
+//! `extern` signatures rather than a completion function, so the flags of
+//! every subcommand are spelled out here and must follow `cli.rs`.
+
+use crate::parsers::DiffMode;
+use crate::platforms::PlatformKind;
+
+/// Shells a completion script can be generated for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellKind {
+    Nushell,
+}
+
+impl ShellKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "nushell" | "nu" => Some(Self::Nushell),
+            _ => None,
+        }
+    }
+}
+
+/// Print the completion script for `shell`
+pub fn run_completions(shell: ShellKind) {
+    print!("{}", generate(shell));
+}
+
+pub fn generate(shell: ShellKind) -> String {
+    match shell {
+        ShellKind::Nushell => generate_nushell(),
+    }
+}
+
+/// Quoted Nushell list literal, e.g. `["claude", "codex"]`
+fn nu_list(items: &[&str]) -> String {
+    let quoted: Vec<String> = items.iter().map(|item| format!("\"{}\"", item)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// Nushell `extern` definitions for crabigator and its subcommands
+///
+/// Load with `source crabigator.nu` from config.nu.
+pub fn generate_nushell() -> String {
+    let platforms: Vec<&str> = PlatformKind::ALL.iter().map(|p| p.as_str()).collect();
+    let diff_modes: Vec<&str> = [DiffMode::Working, DiffMode::Session, DiffMode::All]
+        .iter()
+        .map(|m| m.as_str())
+        .collect();
+
+    format!(
+        r#"# crabigator completions for Nushell
+# Save with `crabigator completions --shell nushell | save crabigator.nu`
+# and `source crabigator.nu` from config.nu
+
+def crabigator-platforms [] {{ {platforms} }}
+
+def crabigator-diff-modes [] {{ {diff_modes} }}
+
+def crabigator-diagram-formats [] {{ ["mermaid", "dot"] }}
+
+def crabigator-capture-formats [] {{ ["raw", "jsonl"] }}
+
+def crabigator-config-actions [] {{ ["show", "get"] }}
+
+def crabigator-shells [] {{ ["nushell"] }}
+
+# Run an AI coding assistant with status widgets
+export extern "crabigator" [
+    platform?: string@crabigator-platforms      # Assistant CLI to run
+    --platform(-p): string@crabigator-platforms # Assistant CLI to run
+    --resume(-r)                                # Resume a previous conversation
+    --continue(-c)                              # Continue the last conversation
+    --no-capture                                # Don't capture output
+    --record                                    # Also record the session for replay
+    --no-record                                 # Capture without replay timing
+    --no-status-bar                             # Run the CLI full screen, no status bar
+    --profile                                   # Log startup timing and publish the mirror
+    --debug-startup                             # Same as --profile
+    --tmux-status-file: path                    # Write a status line for tmux
+    --max-git-files: int                        # Cap files listed in the git widget
+    --diff-mode: string@crabigator-diff-modes   # Changes the summary covers
+    --set-env: string                           # KEY=VALUE for the assistant CLI
+]
+
+# Inspect other running instances
+export extern "crabigator inspect" [
+    dir?: string         # Only sessions whose directory contains this
+    --dir: string        # Directory filter (globs allowed)
+    --watch(-w)          # Keep refreshing
+    --raw(-r)            # Print the raw mirror JSON
+    --history(-H)        # Show hook event history
+    --summary(-s)        # Aggregate stats across instances
+    --diff(-d)           # Show what changed between publishes
+    --ssh: string        # Read sessions on USER@HOST over ssh
+]
+
+# List running sessions
+export extern "crabigator status" [
+    --json               # Emit a JSON array
+]
+
+# Reinstall the platform's hooks
+export extern "crabigator reinstall" [
+    --platform(-p): string@crabigator-platforms # Platform to reinstall
+    --dry-run(-n)                               # Print the plan only
+]
+
+# Search captured scrollback
+export extern "crabigator search" [
+    pattern: string      # Regular expression
+    session_id?: string  # Limit to one session
+    --context(-C): int   # Lines of context
+    --ignore-case(-i)    # Case-insensitive match
+    --invert(-v)         # Show non-matching lines
+]
+
+# Replay a captured session
+export extern "crabigator replay" [
+    session_id?: string  # Session to replay
+    --file(-f): path     # Recording or scrollback file
+    --format: string@crabigator-capture-formats # Recording format
+    --speed(-s): string  # Playback speed (e.g. 2x, 0 = instant)
+]
+
+# Print the resolved configuration
+export extern "crabigator config" [
+    action?: string@crabigator-config-actions # show (default) or get
+    key?: string                              # Key for get
+]
+
+# Print a shell completion script
+export extern "crabigator completions" [
+    --shell: string@crabigator-shells # Shell to generate for
+]
+
+# Summarize a day's sessions as Markdown
+export extern "crabigator summary" [
+    --date: string       # Day to summarize (YYYY-MM-DD, default today)
+    --project: path      # Project directory (default current)
+]
+
+# Name or tag a session
+export extern "crabigator tag" [
+    session_id?: string  # Session to tag
+    --current            # Tag the most recently active session
+    --name(-n): string   # Name shown in inspect and summaries
+    --tag(-t): string    # Add a tag (repeatable)
+]
+
+# Print the active session's state for Starship
+export extern "crabigator starship" [
+    --config             # Print the module for starship.toml
+]
+
+# Copy a session's stats to the clipboard
+export extern "crabigator copy-stats" [
+    session_id?: string  # Session to copy (default the latest)
+]
+
+# Print a session's tool call flow as a diagram
+export extern "crabigator diagram" [
+    session_id: string                              # Session whose stats file to read
+    --format(-f): string@crabigator-diagram-formats # mermaid (default) or dot
+]
+
+# Count the cloud events waiting in the offline queue
+export extern "crabigator queue" [
+    --show               # List each pending event
+]
+
+# Check the environment the assistant CLI needs
+export extern "crabigator doctor" [
+    --platform(-p): string@crabigator-platforms # Platform to check (default the resolved one)
+]
+
+# Print or install a Zellij layout
+export extern "crabigator zellij-plugin" [
+    --platform(-p): string@crabigator-platforms # Assistant CLI for the main pane
+    --install                                   # Write ~/.config/zellij/layouts/crabigator.yaml
+]
+
+# Continue the last conversation
+export extern "crabigator continue" []
+
+# Resume a previous conversation
+export extern "crabigator resume" []
+"#,
+        platforms = nu_list(&platforms),
+        diff_modes = nu_list(&diff_modes),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nushell_script_declares_every_subcommand() {
+        let script = generate_nushell();
+        assert!(script.contains("export extern \"crabigator\" ["));
+        for subcommand in [
+            "inspect",
+            "status",
+            "reinstall",
+            "search",
+            "replay",
+            "config",
+            "completions",
+            "summary",
+            "tag",
+            "starship",
+            "copy-stats",
+            "diagram",
+            "queue",
+            "doctor",
+            "zellij-plugin",
+            "continue",
+            "resume",
+        ] {
+            assert!(
+                script.contains(&format!("export extern \"crabigator {}\" [", subcommand)),
+                "missing {}",
+                subcommand
+            );
+        }
+        assert!(script.contains("def crabigator-platforms [] { [\"claude\", \"codex\", \"cline\"] }"));
+        assert!(script.contains("--diff-mode: string@crabigator-diff-modes"));
+    }
+
+    #[test]
+    fn test_nushell_script_is_balanced() {
+        let script = generate_nushell();
+        // Comments may hold anything; only code has to nest
+        let code: String = script
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        for (open, close) in [('[', ']'), ('{', '}'), ('(', ')')] {
+            let mut depth = 0i32;
+            for c in code.chars() {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    depth -= 1;
+                }
+                assert!(depth >= 0, "unbalanced {}", close);
+            }
+            assert_eq!(depth, 0, "unbalanced {}", open);
+        }
+        assert_eq!(code.matches('"').count() % 2, 0);
+    }
+
+    #[test]
+    fn test_shell_names() {
+        assert_eq!(ShellKind::parse("nushell"), Some(ShellKind::Nushell));
+        assert_eq!(ShellKind::parse("NU"), Some(ShellKind::Nushell));
+        assert_eq!(ShellKind::parse("bash"), None);
+    }
+}
@@ -1,17 +1,27 @@
 //! Configuration management for Crabigator
 //!
 //! Handles loading and saving user preferences, including the default platform.
+//! `crabigator config show` prints the resolved values and where each came from.
 
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::capture::CaptureFormat;
+use crate::notify::NotifyMethod;
+use crate::parsers::DiffMode;
+use crate::platforms::{is_executable, PlatformKind};
+use crate::terminal::theme::Theme;
+use crate::ui::{validate_widget_specs, CommandWidgetConfig, SeparatorStyle, WidgetSpec};
+
 /// Crabigator configuration
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
-    /// Default platform: "claude" or "codex"
+    /// Default platform: "claude", "codex" or "cline"
     #[serde(default = "default_platform")]
     pub default_platform: String,
 
@@ -19,17 +29,358 @@ pub struct Config {
     /// If not set, auto-detects from environment
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ide: Option<String>,
+
+    /// Gzip the scrollback capture (scrollback.log.gz instead of scrollback.log)
+    #[serde(default)]
+    pub capture_compress: bool,
+
+    /// Session recording: plain (none, default), raw (session.rec) or jsonl
+    /// (session.jsonl)
+    #[serde(default)]
+    pub capture_format: CaptureFormat,
+
+    /// Maximum number of cloud events sent per WebSocket frame
+    #[serde(default = "default_cloud_batch_size")]
+    pub cloud_batch_size: usize,
+
+    /// Maximum time (ms) a cloud event waits before its batch is sent
+    #[serde(default = "default_cloud_batch_interval_ms")]
+    pub cloud_batch_interval_ms: u64,
+
+    /// Share of the cloud reconnection backoff that is randomized (0.0-1.0)
+    #[serde(default = "default_cloud_reconnect_jitter")]
+    pub cloud_reconnect_jitter: f64,
+
+    /// Cap on the offline queue file; the oldest events go first
+    #[serde(default = "default_cloud_max_queue_bytes")]
+    pub cloud_max_queue_bytes: usize,
+
+    /// Maximum files listed in the git widget (largest changes first); unset = all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_git_files: Option<usize>,
+
+    /// Template for the --tmux-status-file line
+    /// Placeholders: {state}, {time}, {prompts}, {completions}, {tools}, {branch}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux_status_format: Option<String>,
+
+    /// Template for `crabigator starship`
+    /// Placeholders: {state}, {prompts}, {completions}, {tools}, {branch}, {platform}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starship_format: Option<String>,
+
+    /// Terminals narrower than this many columns get single-line summary widgets
+    #[serde(default = "default_compact_threshold")]
+    pub compact_threshold: u16,
+
+    /// Share of the terminal height given to the status bar (10-40)
+    #[serde(default = "default_status_bar_height_percent")]
+    pub status_bar_height_percent: u8,
+
+    /// Line between the assistant and the widgets: thin, thick, double, dotted, none, or label
+    #[serde(default)]
+    pub separator_style: SeparatorStyle,
+
+    /// Session length budget; the stats widget counts down to it. Unset = no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_session_minutes: Option<u64>,
+
+    /// Tokens (input + output) after which input is paused until Ctrl+A u.
+    /// Unset = no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_budget: Option<u64>,
+
+    /// Show `~N words` changed in the changes header (useful for prose-heavy diffs)
+    #[serde(default)]
+    pub show_word_count: bool,
+
+    /// Leave changes in test files (`*_test.rs`, `*.test.ts`, `test_*.py`, ...)
+    /// out of the changes widget
+    #[serde(default)]
+    pub hide_test_changes: bool,
+
+    /// Reparse only the files whose diff changed on each git refresh
+    #[serde(default)]
+    pub incremental_diff: bool,
+
+    /// Changes the diff summary covers: working (uncommitted), session (since
+    /// the commit at session start), or all (session, staged kept apart)
+    #[serde(default)]
+    pub diff_mode: DiffMode,
+
+    /// Bytes of diff parsed per refresh; larger diffs stop at the last whole file
+    #[serde(default = "default_max_diff_bytes")]
+    pub max_diff_bytes: usize,
+
+    /// Announce when the assistant finishes: none, bell, system (desktop
+    /// notification), or osc777 (posted by Kitty/WezTerm)
+    #[serde(default)]
+    pub notify_on_complete: NotifyMethod,
+
+    /// Keep hand edits to the installed hook script instead of reinstalling
+    /// over them (until the hook version changes)
+    #[serde(default)]
+    pub watch_hook_script: bool,
+
+    /// Width of each `[custom_widgets]` column
+    #[serde(default = "default_custom_widget_width")]
+    pub custom_widget_width: u16,
+
+    /// Assistant CLI settings (`[platform]` table)
+    #[serde(default, skip_serializing_if = "PlatformConfig::is_empty")]
+    pub platform: PlatformConfig,
+
+    /// Status bar colors (`[theme]` table)
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// Column order and widths of the built-in widgets (`[layout]` table)
+    #[serde(default, skip_serializing_if = "LayoutConfig::is_empty")]
+    pub layout: LayoutConfig,
+
+    /// Status bar widgets that show a shell command's output (`[custom_widgets]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_widgets: BTreeMap<String, CommandWidgetConfig>,
+}
+
+/// Settings for the assistant CLI process
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PlatformConfig {
+    /// Extra environment variables for the CLI (`[platform.env]`)
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// CLI binaries outside PATH, by platform (`[platform.binaries]`,
+    /// e.g. `claude = "/opt/homebrew/bin/claude"`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub binaries: BTreeMap<String, PathBuf>,
+}
+
+impl PlatformConfig {
+    fn is_empty(&self) -> bool {
+        self.env.is_empty() && self.binaries.is_empty()
+    }
+
+    /// Configured binary for `platform`, if any
+    pub fn binary(&self, platform: PlatformKind) -> Option<&Path> {
+        self.binaries.get(platform.as_str()).map(PathBuf::as_path)
+    }
+}
+
+/// Status bar column settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "RawLayoutConfig")]
+pub struct LayoutConfig {
+    /// Built-in widgets left to right, each optionally with a width in percent
+    /// (`["git:40", "changes:40", "stats:20"]`); empty keeps the adaptive layout
+    pub widgets: Vec<WidgetSpec>,
+    /// Entries that didn't parse, reported (and the list reset) by `sanitize`
+    #[serde(skip)]
+    invalid_widgets: Vec<String>,
+}
+
+impl LayoutConfig {
+    fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+}
+
+/// `[layout]` as written, so one bad widget name doesn't fail the whole file
+#[derive(Deserialize)]
+struct RawLayoutConfig {
+    #[serde(default)]
+    widgets: Vec<String>,
+}
+
+impl From<RawLayoutConfig> for LayoutConfig {
+    fn from(raw: RawLayoutConfig) -> Self {
+        let mut layout = Self::default();
+        for entry in raw.widgets {
+            match WidgetSpec::try_from(entry) {
+                Ok(spec) => layout.widgets.push(spec),
+                Err(e) => layout.invalid_widgets.push(e),
+            }
+        }
+        layout
+    }
+}
+
+/// Color theme settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// `dark`, `light`, `solarized`, `dracula`, `nord`, `catppuccin`, or
+    /// `auto` (light or dark from `COLORFGBG`)
+    #[serde(default = "default_theme_preset")]
+    pub preset: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            preset: default_theme_preset(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// The preset to draw with; unknown names fall back to `auto`
+    pub fn resolve(&self) -> Theme {
+        Theme::from_name(&self.preset).unwrap_or_else(Theme::auto_detect)
+    }
+}
+
+fn default_theme_preset() -> String {
+    "auto".to_string()
+}
+
+/// Where a resolved config value came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// ~/.crabigator/config.toml
+    User,
+    /// Environment variable
+    Env(&'static str),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::User => write!(f, "user"),
+            ConfigSource::Env(var) => write!(f, "env:{}", var),
+        }
+    }
+}
+
+/// `crabigator config` subcommands
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Print every resolved value with its source
+    Show,
+    /// Print one value (dotted keys reach into tables, e.g. `platform.env.DEBUG`)
+    Get(String),
+}
+
+/// Overrides `default_platform`, as in `cli::resolve_platform`
+const PLATFORM_ENV_VAR: &str = "CRABIGATOR_PLATFORM";
+
+/// Variables that shouldn't live in config files or shell history
+const SECRET_ENV_VARS: &[&str] = &["ANTHROPIC_API_KEY"];
+
+/// Characters of a masked value left visible
+const MASK_VISIBLE_CHARS: usize = 8;
+
+/// First 8 characters then `...`; values too short to hide as many
+/// characters as they show are masked entirely
+pub fn mask_value(value: &str) -> String {
+    let len = value.chars().count();
+    if len < MASK_VISIBLE_CHARS * 2 {
+        return "...".to_string();
+    }
+    format!("{}...", value.chars().take(MASK_VISIBLE_CHARS).collect::<String>())
+}
+
+/// Whether a `[platform.env]` variable holds a credential worth masking
+fn is_secret_env_var(name: &str) -> bool {
+    SECRET_ENV_VARS.contains(&name)
+        || ["_KEY", "_TOKEN", "_SECRET", "_PASSWORD"]
+            .iter()
+            .any(|suffix| name.to_ascii_uppercase().ends_with(suffix))
+}
+
+/// Rewrite `path` with `default_platform` set, keeping its other keys
+fn set_default_platform_in(path: &Path, platform: &str) -> Result<()> {
+    let mut table = if path.exists() {
+        let contents =
+            fs::read_to_string(path).with_context(|| format!("Failed to read config from {}", path.display()))?;
+        toml::from_str::<toml::Table>(&contents)
+            .with_context(|| format!("Failed to parse config from {}; not saving", path.display()))?
+    } else {
+        toml::Table::new()
+    };
+    table.insert("default_platform".to_string(), toml::Value::String(platform.to_string()));
+    let contents = toml::to_string_pretty(&table).context("Failed to serialize config")?;
+
+    // Atomic write: write to temp file then rename
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &contents)
+        .with_context(|| format!("Failed to write config to {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to rename config file to {}", path.display()))?;
+    Ok(())
 }
 
 fn default_platform() -> String {
     "claude".to_string()
 }
 
+fn default_cloud_batch_size() -> usize {
+    10
+}
+
+fn default_cloud_batch_interval_ms() -> u64 {
+    50
+}
+
+fn default_cloud_reconnect_jitter() -> f64 {
+    crate::cloud::DEFAULT_JITTER_FACTOR
+}
+
+fn default_cloud_max_queue_bytes() -> usize {
+    crate::cloud::DEFAULT_MAX_QUEUE_BYTES
+}
+
+fn default_custom_widget_width() -> u16 {
+    24
+}
+
+fn default_max_diff_bytes() -> usize {
+    crate::parsers::DEFAULT_MAX_DIFF_BYTES
+}
+
+fn default_compact_threshold() -> u16 {
+    80
+}
+
+fn default_status_bar_height_percent() -> u8 {
+    20
+}
+
+/// Allowed `status_bar_height_percent`: visible, but never most of the screen
+const STATUS_BAR_HEIGHT_PERCENT_RANGE: std::ops::RangeInclusive<u8> = 10..=40;
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             default_platform: default_platform(),
             ide: None,
+            capture_compress: false,
+            capture_format: CaptureFormat::default(),
+            cloud_batch_size: default_cloud_batch_size(),
+            cloud_batch_interval_ms: default_cloud_batch_interval_ms(),
+            cloud_reconnect_jitter: default_cloud_reconnect_jitter(),
+            cloud_max_queue_bytes: default_cloud_max_queue_bytes(),
+            max_git_files: None,
+            tmux_status_format: None,
+            starship_format: None,
+            compact_threshold: default_compact_threshold(),
+            status_bar_height_percent: default_status_bar_height_percent(),
+            separator_style: SeparatorStyle::default(),
+            max_session_minutes: None,
+            token_budget: None,
+            show_word_count: false,
+            hide_test_changes: false,
+            incremental_diff: false,
+            diff_mode: DiffMode::default(),
+            max_diff_bytes: default_max_diff_bytes(),
+            notify_on_complete: NotifyMethod::default(),
+            watch_hook_script: false,
+            custom_widget_width: default_custom_widget_width(),
+            platform: PlatformConfig::default(),
+            theme: ThemeConfig::default(),
+            layout: LayoutConfig::default(),
+            custom_widgets: BTreeMap::new(),
         }
     }
 }
@@ -47,43 +398,461 @@ impl Config {
         Self::config_dir().join("config.toml")
     }
 
-    /// Load config from file, or return default if not found
-    pub fn load() -> Result<Self> {
+    /// Load config from file (the default if there is none), plus one
+    /// warning for each value `sanitize` had to reset
+    ///
+    /// Fails only when the file can't be read or parsed.
+    pub fn load_with_warnings() -> Result<(Self, Vec<String>)> {
         let path = Self::config_path();
         if !path.exists() {
-            return Ok(Self::default());
+            return Ok((Self::default(), Vec::new()));
         }
 
         let contents = fs::read_to_string(&path)
             .with_context(|| format!("Failed to read config from {}", path.display()))?;
 
-        toml::from_str(&contents)
-            .with_context(|| format!("Failed to parse config from {}", path.display()))
+        let mut config: Self = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config from {}", path.display()))?;
+        let warnings = config.sanitize();
+        Ok((config, warnings))
     }
 
-    /// Save config to file
-    pub fn save(&self) -> Result<()> {
+    /// Clamp or reset values that parse but make no sense, leaving the rest of
+    /// the config alone; returns what was changed and why
+    pub fn sanitize(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let range = STATUS_BAR_HEIGHT_PERCENT_RANGE;
+        if !range.contains(&self.status_bar_height_percent) {
+            let clamped = self.status_bar_height_percent.clamp(*range.start(), *range.end());
+            warnings.push(format!(
+                "status_bar_height_percent must be between {} and {} (got {}); using {}",
+                range.start(),
+                range.end(),
+                self.status_bar_height_percent,
+                clamped
+            ));
+            self.status_bar_height_percent = clamped;
+        }
+
+        let mut layout_errors = std::mem::take(&mut self.layout.invalid_widgets);
+        layout_errors.extend(validate_widget_specs(&self.layout.widgets));
+        if !layout_errors.is_empty() {
+            warnings.push(format!("{}; using the default layout", layout_errors.join("; ")));
+            self.layout.widgets.clear();
+        }
+
+        self.platform.binaries.retain(|name, path| {
+            let problem = if !PlatformKind::ALL.iter().any(|kind| kind.as_str() == name) {
+                format!("platform.binaries: unknown platform `{}` (expected claude, codex or cline)", name)
+            } else if !is_executable(path) {
+                format!("platform.binaries.{}: {} is not an executable file", name, path.display())
+            } else {
+                return true;
+            };
+            warnings.push(format!("{}; looking it up on PATH", problem));
+            false
+        });
+        warnings
+    }
+
+    /// Set `default_platform` in config.toml
+    ///
+    /// Only that key changes: every other value stays as written, including
+    /// ones `sanitize` resets on load. A file that doesn't parse is left alone.
+    pub fn save_default_platform(platform: &str) -> Result<()> {
         let dir = Self::config_dir();
         fs::create_dir_all(&dir)
             .with_context(|| format!("Failed to create config directory {}", dir.display()))?;
+        set_default_platform_in(&Self::config_path(), platform)
+    }
 
+    /// Environment for the assistant CLI on top of our own
+    ///
+    /// `[platform.env]` from config, with `--set-env KEY=VALUE` overrides
+    /// (later flags win) applied on top.
+    pub fn platform_env(&self, overrides: &[(String, String)]) -> HashMap<String, String> {
+        let mut env: HashMap<String, String> = self.platform.env.clone().into_iter().collect();
+        env.extend(overrides.iter().cloned());
+        env
+    }
+
+    /// Every resolved value as (dotted key, source, value), sorted by key
+    ///
+    /// Layers defaults, then the user config file, then environment variables.
+    /// Unset optional keys are left out.
+    pub fn sources() -> Result<Vec<(String, ConfigSource, toml::Value)>> {
         let path = Self::config_path();
-        let contents = toml::to_string_pretty(self)
-            .context("Failed to serialize config")?;
+        let user = if path.exists() {
+            Some(
+                fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read config from {}", path.display()))?,
+            )
+        } else {
+            None
+        };
+        resolve_sources(user.as_deref(), |var| std::env::var(var).ok())
+    }
+}
+
+/// Resolve config layers given the user file's contents and an env lookup
+fn resolve_sources(
+    user: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, ConfigSource, toml::Value)>> {
+    let user_table: toml::Table = toml::from_str(user.unwrap_or("")).context("Failed to parse config")?;
+    let config: Config = toml::Value::Table(user_table.clone())
+        .try_into()
+        .context("Failed to parse config")?;
+    let toml::Value::Table(resolved) = toml::Value::try_from(&config).context("Failed to serialize config")? else {
+        bail!("Config did not serialize to a table");
+    };
+
+    let mut values = Vec::new();
+    flatten_table("", &resolved, &mut values);
+
+    // Only a platform name the CLI would accept overrides the file
+    let env_platform = env(PLATFORM_ENV_VAR).filter(|v| PlatformKind::parse(v).is_some());
+
+    let mut sources: Vec<_> = values
+        .into_iter()
+        .map(|(key, value)| match &env_platform {
+            Some(platform) if key == "default_platform" => {
+                (key, ConfigSource::Env(PLATFORM_ENV_VAR), toml::Value::String(platform.clone()))
+            }
+            _ if lookup(&user_table, &key).is_some() => (key, ConfigSource::User, value),
+            _ => (key, ConfigSource::Default, value),
+        })
+        .collect();
+    sources.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(sources)
+}
+
+/// Collect leaf values of nested tables under dotted keys
+fn flatten_table(prefix: &str, table: &toml::Table, out: &mut Vec<(String, toml::Value)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(nested) => flatten_table(&key, nested, out),
+            _ => out.push((key, value.clone())),
+        }
+    }
+}
+
+/// Value at a dotted key path
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (head, rest) = match key.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (key, None),
+    };
+    match (table.get(head)?, rest) {
+        (value, None) => Some(value),
+        (toml::Value::Table(nested), Some(rest)) => lookup(nested, rest),
+        _ => None,
+    }
+}
+
+/// Strings bare, everything else as TOML
+fn format_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// `[user]    default_platform = claude`, with the source column aligned and
+/// credentials under `platform.env` masked
+fn format_sources(sources: &[(String, ConfigSource, toml::Value)]) -> Vec<String> {
+    let labels: Vec<String> = sources.iter().map(|(_, source, _)| format!("[{}]", source)).collect();
+    let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    sources
+        .iter()
+        .zip(labels)
+        .map(|((key, _, value), label)| {
+            let secret = key.strip_prefix("platform.env.").is_some_and(is_secret_env_var);
+            let value = if secret { mask_value(&format_value(value)) } else { format_value(value) };
+            format!("{:width$} {} = {}", label, key, value, width = width)
+        })
+        .collect()
+}
+
+/// Run `crabigator config show|get`
+pub fn run_config(action: ConfigAction) -> Result<()> {
+    let sources = Config::sources()?;
+    match action {
+        ConfigAction::Show => {
+            println!("# {}", Config::config_path().display());
+            for line in format_sources(&sources) {
+                println!("{}", line);
+            }
+        }
+        ConfigAction::Get(key) => match sources.iter().find(|(k, _, _)| *k == key) {
+            Some((_, _, value)) => println!("{}", format_value(value)),
+            None => {
+                eprintln!("No config value for {}", key);
+                std::process::exit(1);
+            }
+        },
+    }
+    Ok(())
+}
 
-        // Atomic write: write to temp file then rename
-        let tmp_path = path.with_extension("tmp");
-        fs::write(&tmp_path, &contents)
-            .with_context(|| format!("Failed to write config to {}", tmp_path.display()))?;
-        fs::rename(&tmp_path, &path)
-            .with_context(|| format!("Failed to rename config file to {}", path.display()))?;
+/// Warnings for secrets passed through `[platform.env]` or `--set-env`
+pub fn platform_env_warnings(env: &HashMap<String, String>) -> Vec<String> {
+    SECRET_ENV_VARS
+        .iter()
+        .filter(|name| env.contains_key(**name))
+        .map(|name| {
+            format!(
+                "Warning: {} is set via crabigator's platform env; prefer the system keychain (log in through the CLI) instead.",
+                name
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_platform_env_from_toml() {
+        let config: Config = toml::from_str(
+            "default_platform = \"claude\"\n\n[platform.env]\nANTHROPIC_MODEL = \"claude-sonnet\"\nHTTPS_PROXY = \"http://proxy:8080\"\n",
+        )
+        .unwrap();
+        let env = config.platform_env(&[]);
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["ANTHROPIC_MODEL"], "claude-sonnet");
+        assert_eq!(env["HTTPS_PROXY"], "http://proxy:8080");
+
+        // No table at all is fine too
+        let empty: Config = toml::from_str("").unwrap();
+        assert!(empty.platform_env(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_set_env_overrides_config() {
+        let mut config = Config::default();
+        config.platform.env.insert("ANTHROPIC_MODEL".into(), "from-config".into());
+        config.platform.env.insert("HTTPS_PROXY".into(), "http://proxy:8080".into());
+
+        let env = config.platform_env(&pairs(&[
+            ("ANTHROPIC_MODEL", "from-flag"),
+            ("DEBUG", "1"),
+            ("DEBUG", "2"),
+        ]));
+        assert_eq!(env["ANTHROPIC_MODEL"], "from-flag");
+        assert_eq!(env["HTTPS_PROXY"], "http://proxy:8080");
+        // The last --set-env for a key wins
+        assert_eq!(env["DEBUG"], "2");
+    }
+
+    #[test]
+    fn test_empty_platform_table_not_saved() {
+        let saved = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!saved.contains("[platform"));
+
+        let mut config = Config::default();
+        config.platform.env.insert("DEBUG".into(), "1".into());
+        assert!(toml::to_string_pretty(&config).unwrap().contains("[platform.env]"));
+    }
+
+    #[test]
+    fn test_theme_table() {
+        let config: Config = toml::from_str("[theme]\npreset = \"Dracula\"\n").unwrap();
+        assert_eq!(config.theme.resolve(), Theme::Dracula);
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.theme.preset, "auto");
+    }
+
+    #[test]
+    fn test_sanitize_status_bar_height_percent() {
+        assert!(Config::default().sanitize().is_empty());
+        for percent in [10, 40] {
+            let mut config: Config = toml::from_str(&format!("status_bar_height_percent = {}", percent)).unwrap();
+            assert!(config.sanitize().is_empty());
+        }
+        for (percent, clamped) in [(0, 10), (9, 10), (41, 40), (100, 40)] {
+            let mut config: Config = toml::from_str(&format!("status_bar_height_percent = {}", percent)).unwrap();
+            let warnings = config.sanitize().join("; ");
+            assert!(warnings.contains("status_bar_height_percent must be between 10 and 40"), "{}", warnings);
+            assert!(warnings.contains(&format!("got {}", percent)), "{}", warnings);
+            assert_eq!(config.status_bar_height_percent, clamped);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_resets_only_bad_keys() {
+        let toml = "status_bar_height_percent = 50\nseparator_style = \"double\"\n[platform.env]\nDEBUG = \"1\"\n";
+        let mut config: Config = toml::from_str(toml).unwrap();
+        let warnings = config.sanitize();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("got 50); using 40"), "{}", warnings[0]);
+        assert_eq!(config.status_bar_height_percent, 40);
+        assert_eq!(config.platform.env.get("DEBUG").map(String::as_str), Some("1"));
+        assert_eq!(config.separator_style, SeparatorStyle::Double);
+    }
+
+    #[test]
+    fn test_save_default_platform_keeps_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "status_bar_height_percent = 50\n[layout]\nwidgets = [\"clock\"]\n").unwrap();
+        set_default_platform_in(&path, "codex").unwrap();
+        let saved: toml::Table = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["default_platform"].as_str(), Some("codex"));
+        assert_eq!(saved["status_bar_height_percent"].as_integer(), Some(50));
+        assert_eq!(saved["layout"]["widgets"][0].as_str(), Some("clock"));
+
+        // A file that doesn't parse is never overwritten
+        fs::write(&path, "status_bar_height_percent = [").unwrap();
+        assert!(set_default_platform_in(&path, "claude").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "status_bar_height_percent = [");
+    }
+
+    #[test]
+    fn test_layout_widgets() {
+        let mut config: Config =
+            toml::from_str("[layout]\nwidgets = [\"git:40\", \"changes\", \"stats:20\"]\n").unwrap();
+        assert!(config.sanitize().is_empty());
+        assert_eq!(config.layout.widgets.len(), 3);
+        assert!(toml::to_string_pretty(&config).unwrap().contains("\"git:40\""));
+        assert!(!toml::to_string_pretty(&Config::default()).unwrap().contains("[layout]"));
+
+        // An unknown widget resets the layout instead of failing the file
+        let mut config: Config =
+            toml::from_str("theme.preset = \"nord\"\n[layout]\nwidgets = [\"clock\", \"git\"]\n").unwrap();
+        let warnings = config.sanitize();
+        assert!(warnings[0].contains("unknown widget `clock`"), "{:?}", warnings);
+        assert!(config.layout.widgets.is_empty());
+        assert_eq!(config.theme.preset, "nord");
+        let mut config: Config = toml::from_str("[layout]\nwidgets = [\"git:70\", \"changes:40\"]\n").unwrap();
+        assert!(config.sanitize()[0].contains("add up to 110%"));
+        assert!(config.layout.widgets.is_empty());
+    }
+
+    #[test]
+    fn test_platform_binaries() {
+        let mut config: Config = toml::from_str("[platform.binaries]\nclaude = \"/bin/sh\"\n").unwrap();
+        assert!(config.sanitize().is_empty());
+        assert_eq!(config.platform.binary(PlatformKind::Claude), Some(Path::new("/bin/sh")));
+        assert_eq!(config.platform.binary(PlatformKind::Codex), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("claude");
+        fs::write(&plain, "").unwrap();
+        let toml = format!(
+            "[platform.binaries]\nclaude = {:?}\ncodex = \"/nonexistent/codex\"\ngemini = \"/bin/sh\"\n",
+            plain
+        );
+        let mut config: Config = toml::from_str(&toml).unwrap();
+        let warnings = config.sanitize().join("; ");
+        assert!(warnings.contains("platform.binaries.claude"), "{}", warnings);
+        assert!(warnings.contains("/nonexistent/codex is not an executable file"), "{}", warnings);
+        assert!(warnings.contains("unknown platform `gemini`"), "{}", warnings);
+        assert!(config.platform.binaries.is_empty());
+    }
+
+    fn source_of<'a>(
+        sources: &'a [(String, ConfigSource, toml::Value)],
+        key: &str,
+    ) -> (ConfigSource, &'a toml::Value) {
+        sources
+            .iter()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, source, value)| (*source, value))
+            .unwrap_or_else(|| panic!("no config key {}", key))
+    }
+
+    #[test]
+    fn test_sources_default_and_user_layers() {
+        let user = "default_platform = \"codex\"\ncompact_threshold = 100\n\n[platform.env]\nDEBUG = \"1\"\n";
+        let sources = resolve_sources(Some(user), |_| None).unwrap();
+
+        let (source, value) = source_of(&sources, "default_platform");
+        assert_eq!((source, value.as_str()), (ConfigSource::User, Some("codex")));
+        let (source, value) = source_of(&sources, "compact_threshold");
+        assert_eq!((source, value.as_integer()), (ConfigSource::User, Some(100)));
+        assert_eq!(source_of(&sources, "platform.env.DEBUG").0, ConfigSource::User);
+        assert_eq!(source_of(&sources, "cloud_batch_size").0, ConfigSource::Default);
+        // Unset optional keys are omitted
+        assert!(!sources.iter().any(|(k, _, _)| k == "ide"));
+
+        let defaults = resolve_sources(None, |_| None).unwrap();
+        assert!(defaults.iter().all(|(_, source, _)| *source == ConfigSource::Default));
+    }
+
+    #[test]
+    fn test_sources_env_overrides_user() {
+        let env = |var: &str| (var == "CRABIGATOR_PLATFORM").then(|| "codex".to_string());
+        let sources = resolve_sources(Some("default_platform = \"claude\"\n"), env).unwrap();
+        let (source, value) = source_of(&sources, "default_platform");
+        assert_eq!(source, ConfigSource::Env("CRABIGATOR_PLATFORM"));
+        assert_eq!(value.as_str(), Some("codex"));
+
+        // resolve_platform ignores unknown platforms, so they don't count as a source
+        let bogus = |_: &str| Some("emacs".to_string());
+        let sources = resolve_sources(Some("default_platform = \"claude\"\n"), bogus).unwrap();
+        assert_eq!(source_of(&sources, "default_platform").0, ConfigSource::User);
+    }
+
+    #[test]
+    fn test_format_sources_aligns_labels() {
+        let sources = vec![
+            ("compact_threshold".to_string(), ConfigSource::Default, toml::Value::Integer(80)),
+            ("default_platform".to_string(), ConfigSource::Env("CRABIGATOR_PLATFORM"), toml::Value::String("codex".into())),
+            ("ide".to_string(), ConfigSource::User, toml::Value::String("zed".into())),
+        ];
+        assert_eq!(
+            format_sources(&sources),
+            vec![
+                "[default]                 compact_threshold = 80",
+                "[env:CRABIGATOR_PLATFORM] default_platform = codex",
+                "[user]                    ide = zed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_sources_masks_secrets() {
+        let key = format!("sk-ant-api03-{}", "x".repeat(40));
+        let sources = vec![
+            ("platform.env.ANTHROPIC_API_KEY".to_string(), ConfigSource::User, toml::Value::String(key)),
+            ("platform.env.GITHUB_TOKEN".to_string(), ConfigSource::User, toml::Value::String("ghp_short".into())),
+            ("platform.env.HTTPS_PROXY".to_string(), ConfigSource::User, toml::Value::String("http://proxy".into())),
+        ];
+        assert_eq!(
+            format_sources(&sources),
+            vec![
+                "[user] platform.env.ANTHROPIC_API_KEY = sk-ant-a...",
+                "[user] platform.env.GITHUB_TOKEN = ...",
+                "[user] platform.env.HTTPS_PROXY = http://proxy",
+            ]
+        );
+    }
 
-        Ok(())
+    #[test]
+    fn test_mask_value_by_length() {
+        assert_eq!(mask_value(""), "...");
+        assert_eq!(mask_value("sk-1234"), "...");
+        assert_eq!(mask_value("sk-ant-a"), "...");
+        assert_eq!(mask_value("sk-ant-api03-xy"), "...");
+        assert_eq!(mask_value("sk-ant-api03-xyz"), "sk-ant-a...");
+        assert_eq!(mask_value(&format!("sk-ant-api03-{}", "x".repeat(90))), "sk-ant-a...");
+        // Counts characters, not bytes
+        assert_eq!(mask_value("ключ-ключ-ключ-ключ"), "ключ-клю...");
     }
 
-    /// Update the default platform and save
-    pub fn set_default_platform(&mut self, platform: &str) -> Result<()> {
-        self.default_platform = platform.to_string();
-        self.save()
+    #[test]
+    fn test_api_key_warning() {
+        let env = Config::default().platform_env(&pairs(&[("ANTHROPIC_API_KEY", "sk-ant-x")]));
+        let warnings = platform_env_warnings(&env);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("ANTHROPIC_API_KEY"));
+        assert!(platform_env_warnings(&Config::default().platform_env(&[])).is_empty());
     }
 }
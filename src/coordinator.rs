@@ -0,0 +1,145 @@
+//! Coordination between crabigator instances started at the same time
+//!
+//! Two tabs opening crabigator together would both find the hooks out of date
+//! and rewrite `settings.json` at once. `SessionCoordinator` serializes hook
+//! installation with an exclusive `flock` on `coordinator.lock` in the hook
+//! directory (`~/.claude/crabigator/`): the instance that gets the lock
+//! installs, and one that can't get it within `INSTALL_LOCK_TIMEOUT` leaves
+//! the install to the holder.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+/// Lock file name, in the directory the hooks are installed to
+pub const LOCK_FILE: &str = "coordinator.lock";
+
+/// How long to wait for another instance's install before skipping ours
+pub const INSTALL_LOCK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Interval between attempts while another instance holds the lock
+const RETRY_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Serializes hook installation across crabigator processes
+pub struct SessionCoordinator {
+    lock_path: PathBuf,
+    timeout: Duration,
+}
+
+/// Holds the install lock; dropping it (closing the file) releases it
+pub struct InstallLockGuard {
+    _file: File,
+}
+
+impl SessionCoordinator {
+    /// Coordinate through `dir/coordinator.lock`
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            lock_path: dir.join(LOCK_FILE),
+            timeout: INSTALL_LOCK_TIMEOUT,
+        }
+    }
+
+    /// Take the install lock, waiting up to 500ms for another instance
+    ///
+    /// `None` means another instance held the lock the whole time and is
+    /// installing the hooks itself.
+    pub fn try_acquire_install_lock(&self) -> Result<Option<InstallLockGuard>> {
+        let file = open_lock_file(&self.lock_path)?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Some(InstallLockGuard { _file: file })),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(RETRY_INTERVAL),
+                Err(TryLockError::WouldBlock) => return Ok(None),
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).with_context(|| format!("Failed to lock {}", self.lock_path.display()))
+                }
+            }
+        }
+    }
+}
+
+/// Open `path` (creating it and its directory) for `flock`ing; the lock is
+/// released when the file is closed
+pub fn open_lock_file(path: &Path) -> Result<File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+
+    #[test]
+    fn test_second_instance_skips_while_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = SessionCoordinator::new(dir.path());
+        let second = SessionCoordinator::new(dir.path());
+
+        let guard = first.try_acquire_install_lock().unwrap();
+        assert!(guard.is_some());
+        assert!(dir.path().join(LOCK_FILE).exists());
+
+        let started = Instant::now();
+        assert!(second.try_acquire_install_lock().unwrap().is_none());
+        assert!(started.elapsed() >= INSTALL_LOCK_TIMEOUT);
+
+        // Released as soon as the install is done
+        drop(guard);
+        assert!(second.try_acquire_install_lock().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_waits_for_a_short_install() {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = SessionCoordinator::new(dir.path()).try_acquire_install_lock().unwrap();
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(guard);
+        });
+        assert!(SessionCoordinator::new(dir.path()).try_acquire_install_lock().unwrap().is_some());
+        releaser.join().unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_instances_install_one_at_a_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let installing = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(4));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let (dir, installing, barrier) = (dir.path().to_path_buf(), installing.clone(), barrier.clone());
+                thread::spawn(move || {
+                    let coordinator = SessionCoordinator::new(&dir);
+                    barrier.wait();
+                    let Some(_guard) = coordinator.try_acquire_install_lock().unwrap() else {
+                        return false;
+                    };
+                    use std::sync::atomic::Ordering;
+                    assert_eq!(installing.fetch_add(1, Ordering::SeqCst), 0, "two installs at once");
+                    thread::sleep(Duration::from_millis(20));
+                    installing.fetch_sub(1, Ordering::SeqCst);
+                    true
+                })
+            })
+            .collect();
+
+        let installed = handles.into_iter().map(|h| h.join().unwrap()).filter(|&ran| ran).count();
+        // Short installs finish within the timeout, so every instance gets a turn
+        assert_eq!(installed, 4);
+    }
+}
@@ -0,0 +1,184 @@
+//! `crabigator copy-stats` and `Ctrl+A c` - a session's stats on the clipboard
+//!
+//! Formats prompts, completions and tool calls as one line for pasting into
+//! a work log:
+//!
+//! ```text
+//! Crabigator session 1a2b3c (claude, 1h 12m): 14 prompts, 12 completions, 86 tool calls
+//! ```
+//!
+//! Live sessions are read from their mirror (`/tmp/crabigator-{id}/inspect.json`);
+//! sessions that have ended, whose session directory is gone, from the
+//! history store.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::history::{HistoryStore, SessionRecord};
+use crate::hooks::SessionStats;
+use crate::inspect::{discover_instances, latest_instance};
+use crate::system::clipboard::ClipboardWriter;
+use crate::ui::utils::format_duration;
+
+/// The counts copied for one session
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatsSummary {
+    pub session_id: String,
+    pub platform: String,
+    pub duration_secs: u64,
+    pub prompts: u32,
+    pub completions: u32,
+    pub tool_calls: u32,
+}
+
+impl StatsSummary {
+    /// From a session's mirror JSON; `None` if it has no stats widget
+    pub fn from_mirror(data: &Value) -> Option<Self> {
+        let stats = data.pointer("/widgets/stats/data")?;
+        let count = |key: &str| stats.get(key).and_then(Value::as_u64).unwrap_or(0);
+        let text = |key: &str| data.get(key).and_then(Value::as_str).unwrap_or("unknown").to_string();
+        Some(Self {
+            session_id: text("session_id"),
+            platform: text("platform"),
+            duration_secs: count("work_seconds"),
+            prompts: count("prompts") as u32,
+            completions: count("completions") as u32,
+            tool_calls: count("tools") as u32,
+        })
+    }
+
+    pub fn from_record(record: &SessionRecord) -> Self {
+        Self {
+            session_id: record.session_id.clone(),
+            platform: record.platform.clone(),
+            duration_secs: record.duration_secs(),
+            prompts: record.prompts,
+            completions: record.completions,
+            tool_calls: record.tools.values().sum(),
+        }
+    }
+
+    /// The running session's stats, for `Ctrl+A c`
+    pub fn from_session(session_id: &str, platform: &str, stats: &SessionStats) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            platform: platform.to_string(),
+            duration_secs: stats.work_seconds,
+            prompts: stats.platform_stats.prompts,
+            completions: stats.platform_stats.completions,
+            tool_calls: stats.platform_stats.total_tool_calls(),
+        }
+    }
+
+    pub fn format(&self) -> String {
+        format!(
+            "Crabigator session {} ({}, {}): {}, {}, {}",
+            self.session_id,
+            self.platform,
+            format_duration(self.duration_secs),
+            plural(self.prompts, "prompt"),
+            plural(self.completions, "completion"),
+            plural(self.tool_calls, "tool call"),
+        )
+    }
+}
+
+fn plural(count: u32, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Stats for `session_id`, or the most recent session: a live mirror first,
+/// then the history store
+fn find_summary(session_id: Option<&str>) -> Result<Option<StatsSummary>> {
+    let instances = discover_instances(&None)?;
+    let mirror = match session_id {
+        Some(id) => instances
+            .iter()
+            .map(|(_, data)| data)
+            .find(|data| data.get("session_id").and_then(Value::as_str) == Some(id)),
+        None => latest_instance(&instances),
+    };
+    if let Some(summary) = mirror.and_then(StatsSummary::from_mirror) {
+        return Ok(Some(summary));
+    }
+
+    let store = HistoryStore::open_default();
+    let record = match session_id {
+        Some(id) => store.find(id)?,
+        None => store.load()?.pop(),
+    };
+    Ok(record.as_ref().map(StatsSummary::from_record))
+}
+
+/// Copy a session's stats to the clipboard and print what was copied
+pub fn run_copy_stats(session_id: Option<String>) -> Result<()> {
+    let Some(summary) = find_summary(session_id.as_deref())? else {
+        match session_id {
+            Some(id) => bail!("No session {} found (checked running sessions and history)", id),
+            None => bail!("No sessions found (checked running sessions and history)"),
+        }
+    };
+    let text = summary.format();
+    ClipboardWriter::copy(&text)?;
+    println!("Copied: {}", text);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_matches_summary_sentence() {
+        let summary = StatsSummary {
+            session_id: "1a2b3c".to_string(),
+            platform: "claude".to_string(),
+            duration_secs: 4320,
+            prompts: 14,
+            completions: 1,
+            tool_calls: 86,
+        };
+        assert_eq!(
+            summary.format(),
+            "Crabigator session 1a2b3c (claude, 1h 12m): 14 prompts, 1 completion, 86 tool calls"
+        );
+    }
+
+    #[test]
+    fn test_summary_from_mirror_and_record() {
+        let mirror = json!({
+            "session_id": "abc",
+            "platform": "codex",
+            "widgets": { "stats": { "data": {
+                "work_seconds": 600, "prompts": 3, "completions": 2, "tools": 9
+            } } }
+        });
+        let summary = StatsSummary::from_mirror(&mirror).unwrap();
+        assert_eq!((summary.platform.as_str(), summary.duration_secs, summary.tool_calls), ("codex", 600, 9));
+        assert!(StatsSummary::from_mirror(&json!({ "session_id": "abc" })).is_none());
+
+        let record = SessionRecord {
+            session_id: "def".to_string(),
+            platform: "claude".to_string(),
+            cwd: "/work".to_string(),
+            project_name: None,
+            started_at: 1000.0,
+            ended_at: 1090.0,
+            prompts: 4,
+            completions: 4,
+            thinking_seconds: 30,
+            tools: [("Bash".to_string(), 5), ("Edit".to_string(), 2)].into_iter().collect(),
+            files_modified: Vec::new(),
+            ..Default::default()
+        };
+        assert_eq!(
+            StatsSummary::from_record(&record).format(),
+            "Crabigator session def (claude, 1m): 4 prompts, 4 completions, 7 tool calls"
+        );
+    }
+}
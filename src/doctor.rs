@@ -0,0 +1,309 @@
+//! `crabigator doctor` - check the setup for an assistant CLI
+//!
+//! The `[Environment]` section lists the variables the platform's CLI reads:
+//! each one's value (API keys masked to their first 8 characters), where it
+//! comes from (crabigator's own environment, or `[platform.env]` in
+//! `~/.crabigator/config.toml`, which wins), and a recommendation when it is
+//! missing or looks wrong.
+//!
+//! The `[Binary]` section shows which CLI binary would be launched: the path
+//! in `[platform.binaries]` when configured, otherwise the first match on PATH.
+
+use std::ffi::OsStr;
+use std::path::Path;
+
+use crate::config::{mask_value, Config};
+use crate::platforms::{resolve_binary, BinarySource, PlatformKind};
+use crate::terminal::escape::{ansi, RESET};
+
+/// Where a variable's value comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvSource {
+    Shell,
+    Config,
+    /// Binary found by searching PATH
+    SearchPath,
+    NotSet,
+}
+
+impl EnvSource {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Shell => "set in shell",
+            Self::Config => "set in config.toml",
+            Self::SearchPath => "found on PATH",
+            Self::NotSet => "not set",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    /// Optional and unset, or shown for reference
+    Info,
+}
+
+/// Outcome of one check
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticResult {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Value as displayed (masked for secrets)
+    pub value: Option<String>,
+    pub source: EnvSource,
+    pub recommendation: Option<String>,
+}
+
+/// An environment variable a platform's CLI reads
+pub struct EnvVarCheck {
+    pub name: &'static str,
+    pub required: bool,
+    /// Show only the first characters of the value
+    pub mask: bool,
+    /// Returns a recommendation when a set value looks wrong
+    pub validate: Option<fn(&str) -> Option<String>>,
+}
+
+impl EnvVarCheck {
+    pub fn run(&self, config: &Config) -> DiagnosticResult {
+        let (value, source) = env_value(self.name, config);
+        let mut result = diagnose(self.name, self.required, self.mask, value.clone(), source);
+        if let (Some(validate), Some(value)) = (self.validate, value) {
+            if let Some(recommendation) = validate(&value) {
+                result.status = CheckStatus::Warning;
+                result.recommendation = Some(recommendation);
+            }
+        }
+        result
+    }
+}
+
+/// Variables checked for each platform
+fn env_checks(platform: PlatformKind) -> Vec<EnvVarCheck> {
+    match platform {
+        PlatformKind::Claude => vec![
+            // Optional: Claude Code normally logs in through the keychain, and
+            // `platform_env_warnings` steers the key out of config.toml
+            EnvVarCheck {
+                name: "ANTHROPIC_API_KEY",
+                required: false,
+                mask: true,
+                validate: Some(|v| expect_prefix(v, "sk-ant-")),
+            },
+            EnvVarCheck {
+                name: "ANTHROPIC_MODEL",
+                required: false,
+                mask: false,
+                validate: Some(check_anthropic_model),
+            },
+            EnvVarCheck {
+                name: "CLAUDE_CODE_DISABLE_TELEMETRY",
+                required: false,
+                mask: false,
+                validate: None,
+            },
+        ],
+        PlatformKind::Codex => vec![EnvVarCheck {
+            name: "OPENAI_API_KEY",
+            required: true,
+            mask: true,
+            validate: Some(|v| expect_prefix(v, "sk-")),
+        }],
+        PlatformKind::Cline => Vec::new(),
+    }
+}
+
+fn expect_prefix(value: &str, prefix: &str) -> Option<String> {
+    (!value.starts_with(prefix))
+        .then(|| format!("API keys usually start with `{}`; check for a stray quote or paste", prefix))
+}
+
+/// Model aliases (`sonnet`, `opus`, `haiku`) or a full `claude-*` model name
+fn check_anthropic_model(value: &str) -> Option<String> {
+    let known = matches!(value, "sonnet" | "opus" | "haiku" | "opusplan") || value.starts_with("claude-");
+    (!known).then(|| format!("`{}` is not a model alias (sonnet, opus, haiku) or a claude-* model name", value))
+}
+
+/// Value and source: `[platform.env]` in config overrides the environment,
+/// as when the CLI is spawned
+fn env_value(name: &str, config: &Config) -> (Option<String>, EnvSource) {
+    resolve_value(std::env::var(name).ok(), config.platform.env.get(name).cloned())
+}
+
+fn resolve_value(shell: Option<String>, config: Option<String>) -> (Option<String>, EnvSource) {
+    match (config, shell) {
+        (Some(value), _) => (Some(value), EnvSource::Config),
+        (None, Some(value)) => (Some(value), EnvSource::Shell),
+        (None, None) => (None, EnvSource::NotSet),
+    }
+}
+
+/// Check one variable: missing required ones and empty values are warnings
+fn diagnose(name: &str, required: bool, mask: bool, value: Option<String>, source: EnvSource) -> DiagnosticResult {
+    let (status, recommendation) = match value.as_deref() {
+        None if required => (
+            CheckStatus::Warning,
+            Some(format!("export {} in your shell or set it under [platform.env] in config.toml", name)),
+        ),
+        None => (CheckStatus::Info, None),
+        Some("") => (CheckStatus::Warning, Some("set but empty".to_string())),
+        Some(_) => (CheckStatus::Ok, None),
+    };
+    DiagnosticResult {
+        name: name.to_string(),
+        status,
+        value: value.map(|v| if mask { mask_value(&v) } else { v }),
+        source,
+        recommendation,
+    }
+}
+
+/// Which binary `platform` would launch, checking `[platform.binaries]`
+/// before `search_path`
+fn check_binary(platform: PlatformKind, configured: Option<&Path>, search_path: Option<&OsStr>) -> DiagnosticResult {
+    let command = platform.command();
+    let (status, value, source, recommendation) = match resolve_binary(configured, command, search_path) {
+        Some((path, source)) => {
+            let source = match source {
+                BinarySource::Config => EnvSource::Config,
+                BinarySource::SearchPath => EnvSource::SearchPath,
+            };
+            (CheckStatus::Ok, Some(path.display().to_string()), source, None)
+        }
+        None => (
+            CheckStatus::Warning,
+            None,
+            EnvSource::NotSet,
+            Some(format!(
+                "`{}` is not on PATH; install it or set [platform.binaries] {} = \"/path/to/{}\" in config.toml",
+                command, command, command
+            )),
+        ),
+    };
+    DiagnosticResult {
+        name: command.to_string(),
+        status,
+        value,
+        source,
+        recommendation,
+    }
+}
+
+fn print_result(result: &DiagnosticResult, name_width: usize) {
+    let (mark, color) = match result.status {
+        CheckStatus::Ok => ("✓", ansi::GREEN),
+        CheckStatus::Warning => ("!", ansi::YELLOW),
+        CheckStatus::Info => ("-", ""),
+    };
+    let value = result.value.as_deref().unwrap_or("");
+    println!(
+        "  {}{}{RESET} {:<name_width$}  {} ({})",
+        color,
+        mark,
+        result.name,
+        value,
+        result.source.label()
+    );
+    if let Some(recommendation) = &result.recommendation {
+        println!("    {}→ {}{RESET}", ansi::YELLOW, recommendation);
+    }
+}
+
+/// Print the checks for `platform`
+pub fn run_doctor(platform: PlatformKind, config: &Config, config_warnings: &[String]) -> anyhow::Result<()> {
+    println!("crabigator doctor ({})", platform.as_str());
+
+    println!("\n[Binary]");
+    // An invalid config (such as a configured binary that isn't executable)
+    // is reported here; the checks then use the defaults main fell back to
+    for warning in config_warnings {
+        println!("  {}!{RESET} {}", ansi::YELLOW, warning);
+    }
+    let binary = check_binary(platform, config.platform.binary(platform), std::env::var_os("PATH").as_deref());
+    print_result(&binary, binary.name.len());
+
+    println!("\n[Environment]");
+    let checks = env_checks(platform);
+    if checks.is_empty() {
+        println!("  No environment variables to check for {}", platform.as_str());
+    }
+    let name_width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in &checks {
+        print_result(&check.run(config), name_width);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_prefers_config() {
+        let v = |s: &str| Some(s.to_string());
+        assert_eq!(resolve_value(v("a"), v("b")), (v("b"), EnvSource::Config));
+        assert_eq!(resolve_value(v("a"), None), (v("a"), EnvSource::Shell));
+        assert_eq!(resolve_value(None, None), (None, EnvSource::NotSet));
+    }
+
+    #[test]
+    fn test_anthropic_key_is_optional() {
+        let checks = env_checks(PlatformKind::Claude);
+        let key = checks.iter().find(|c| c.name == "ANTHROPIC_API_KEY").unwrap();
+        let unset = diagnose(key.name, key.required, key.mask, None, EnvSource::NotSet);
+        assert_eq!((unset.status, unset.recommendation), (CheckStatus::Info, None));
+
+        // Checked against the config passed in
+        let mut config = Config::default();
+        config.platform.env.insert(key.name.to_string(), "sk-ant-api03-0123456789".to_string());
+        let set = key.run(&config);
+        assert_eq!((set.status, set.source), (CheckStatus::Ok, EnvSource::Config));
+        assert_eq!(set.value.as_deref(), Some("sk-ant-a..."));
+    }
+
+    #[test]
+    fn test_diagnose_required_and_optional() {
+        let missing = diagnose("OPENAI_API_KEY", true, true, None, EnvSource::NotSet);
+        assert_eq!(missing.status, CheckStatus::Warning);
+        assert!(missing.recommendation.unwrap().contains("OPENAI_API_KEY"));
+
+        let optional = diagnose("ANTHROPIC_MODEL", false, false, None, EnvSource::NotSet);
+        assert_eq!((optional.status, optional.recommendation), (CheckStatus::Info, None));
+
+        let key = diagnose("OPENAI_API_KEY", true, true, Some("sk-proj-abcdefghijklmnop".into()), EnvSource::Shell);
+        assert_eq!(key.status, CheckStatus::Ok);
+        assert_eq!(key.value.as_deref(), Some("sk-proj-..."));
+    }
+
+    #[test]
+    fn test_check_binary_prefers_config() {
+        let bin = tempfile::tempdir().unwrap();
+        let on_path = bin.path().join("codex");
+        std::fs::write(&on_path, "").unwrap();
+        std::fs::set_permissions(&on_path, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+        let search_path = Some(bin.path().as_os_str());
+
+        let found = check_binary(PlatformKind::Codex, None, search_path);
+        assert_eq!((found.status, found.source), (CheckStatus::Ok, EnvSource::SearchPath));
+        assert_eq!(found.value, Some(on_path.display().to_string()));
+
+        let configured = check_binary(PlatformKind::Codex, Some(Path::new("/bin/sh")), search_path);
+        assert_eq!((configured.status, configured.source), (CheckStatus::Ok, EnvSource::Config));
+        assert_eq!(configured.value.as_deref(), Some("/bin/sh"));
+
+        let missing = check_binary(PlatformKind::Claude, None, search_path);
+        assert_eq!((missing.status, missing.source), (CheckStatus::Warning, EnvSource::NotSet));
+        assert!(missing.recommendation.unwrap().contains("[platform.binaries] claude"));
+    }
+
+    #[test]
+    fn test_validators() {
+        assert_eq!(check_anthropic_model("sonnet"), None);
+        assert_eq!(check_anthropic_model("claude-sonnet-4-5"), None);
+        assert!(check_anthropic_model("gpt-4o").is_some());
+        assert_eq!(expect_prefix("sk-ant-api03", "sk-ant-"), None);
+        assert!(expect_prefix("\"sk-ant-api03", "sk-ant-").is_some());
+    }
+}
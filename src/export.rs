@@ -0,0 +1,153 @@
+//! Diagram export of a session's tool call flow (`crabigator diagram`)
+//!
+//! The hook stats file keeps a rolling `event_history`; each event becomes a
+//! `FlowStep` (prompt, permission request, tool call, completion, ...), which
+//! `mermaid.rs` draws as a Mermaid `sequenceDiagram` and `dot.rs` as a
+//! Graphviz digraph.
+
+mod dot;
+mod mermaid;
+
+use anyhow::{bail, Context, Result};
+
+use crate::platforms::{platform_for, HookEvent, PlatformKind, PlatformStats};
+
+pub use dot::DotGenerator;
+pub use mermaid::MermaidGenerator;
+
+/// Output format of `crabigator diagram` (`--format`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiagramFormat {
+    #[default]
+    Mermaid,
+    Dot,
+}
+
+impl DiagramFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "mermaid" | "mmd" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Dot),
+            _ => None,
+        }
+    }
+}
+
+/// One interaction in the session, from a hook event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlowStep {
+    /// The user submitted a prompt
+    Prompt,
+    /// The assistant asked to use a tool
+    Permission { tool: String },
+    /// A tool ran and returned its result
+    ToolCall { tool: String },
+    /// The assistant finished its turn
+    Complete,
+    /// A subagent finished
+    SubagentComplete,
+    /// The context was compacted
+    Compact,
+    /// Any other event, by name
+    Other(String),
+}
+
+impl FlowStep {
+    /// Claude Code and Cline hook event names
+    pub fn from_event(event: &HookEvent) -> Self {
+        let tool = || {
+            event
+                .details
+                .as_ref()
+                .and_then(|d| d.get("tool"))
+                .and_then(|t| t.as_str())
+                .unwrap_or("tool")
+                .to_string()
+        };
+        match event.event.as_str() {
+            "UserPromptSubmit" | "onApiRequest" => Self::Prompt,
+            "PermissionRequest" => Self::Permission { tool: tool() },
+            "PostToolUse" | "onToolUse" => Self::ToolCall { tool: tool() },
+            "Stop" | "onChatComplete" => Self::Complete,
+            "SubagentStop" => Self::SubagentComplete,
+            "PreCompact" => Self::Compact,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// The session's events, oldest first
+///
+/// `event_history` when the hook kept one; otherwise tool calls rebuilt from
+/// `tool_calls`, so the diagram still shows them.
+pub fn session_events(stats: &PlatformStats) -> Vec<HookEvent> {
+    if !stats.event_history.is_empty() {
+        return stats.event_history.clone();
+    }
+    stats
+        .tool_calls
+        .iter()
+        .map(|call| HookEvent {
+            ts: call.ts,
+            event: "PostToolUse".to_string(),
+            state_before: String::new(),
+            state_after: String::new(),
+            details: Some([("tool".to_string(), serde_json::Value::from(call.tool.as_str()))].into_iter().collect()),
+        })
+        .collect()
+}
+
+/// Participant id for a name: Mermaid and DOT ids are letters, digits and `_`
+pub fn participant_id(name: &str) -> String {
+    let id: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if id.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", id)
+    } else {
+        id
+    }
+}
+
+/// Print the diagram for `session_id` from its hook stats file
+pub fn run_diagram(session_id: &str, format: DiagramFormat) -> Result<()> {
+    let found = PlatformKind::ALL.iter().find_map(|&kind| {
+        let path = platform_for(kind).session_stats_path(session_id)?;
+        path.exists().then_some((kind, path))
+    });
+    let Some((kind, path)) = found else {
+        bail!("No stats file for session {} (it may have ended)", session_id);
+    };
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let stats: PlatformStats =
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let events = session_events(&stats);
+    let diagram = match format {
+        DiagramFormat::Mermaid => MermaidGenerator::new(kind.display_name()).generate_sequence(&events),
+        DiagramFormat::Dot => DotGenerator::new(kind.display_name()).generate(&events),
+    };
+    print!("{}", diagram);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::ToolCall;
+
+    #[test]
+    fn test_events_fall_back_to_tool_calls() {
+        let stats = PlatformStats {
+            tool_calls: vec![ToolCall { tool: "Bash".to_string(), ts: 2.0 }],
+            ..PlatformStats::default()
+        };
+        let steps: Vec<FlowStep> = session_events(&stats).iter().map(FlowStep::from_event).collect();
+        assert_eq!(steps, vec![FlowStep::ToolCall { tool: "Bash".to_string() }]);
+    }
+
+    #[test]
+    fn test_participant_ids() {
+        assert_eq!(participant_id("Bash"), "Bash");
+        assert_eq!(participant_id("mcp__github__create-issue"), "mcp__github__create_issue");
+        assert_eq!(participant_id("1password"), "_1password");
+    }
+}
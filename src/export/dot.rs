@@ -0,0 +1,109 @@
+//! Graphviz digraph of a session
+//!
+//! Where the Mermaid diagram is a timeline, the graph sums it up: one node
+//! per participant and one edge per kind of interaction, labeled with how
+//! often it happened (`Claude -> Bash [label="tool call ×12"]`).
+
+use std::fmt::Write;
+
+use crate::platforms::HookEvent;
+
+use super::{participant_id, FlowStep};
+
+/// Builds a DOT digraph from hook events
+pub struct DotGenerator {
+    assistant: String,
+}
+
+impl DotGenerator {
+    pub fn new(assistant: &str) -> Self {
+        Self {
+            assistant: assistant.to_string(),
+        }
+    }
+
+    pub fn generate(&self, events: &[HookEvent]) -> String {
+        let assistant = participant_id(&self.assistant);
+        // (from, to, label) in first-seen order, with counts
+        let mut edges: Vec<((String, String, &str), usize)> = Vec::new();
+        let mut tools: Vec<String> = Vec::new();
+        let steps: Vec<FlowStep> = events.iter().map(FlowStep::from_event).collect();
+        for step in &steps {
+            let edge = match step {
+                FlowStep::Prompt => ("User".to_string(), assistant.clone(), "prompt"),
+                FlowStep::Permission { .. } => (assistant.clone(), "User".to_string(), "permission"),
+                FlowStep::ToolCall { tool } => {
+                    if !tools.contains(tool) {
+                        tools.push(tool.clone());
+                    }
+                    (assistant.clone(), participant_id(tool), "tool call")
+                }
+                FlowStep::Complete => (assistant.clone(), "User".to_string(), "Stop"),
+                FlowStep::SubagentComplete | FlowStep::Compact | FlowStep::Other(_) => continue,
+            };
+            match edges.iter_mut().find(|(e, _)| *e == edge) {
+                Some((_, count)) => *count += 1,
+                None => edges.push((edge, 1)),
+            }
+        }
+
+        let mut out = String::from("digraph session {\n    rankdir=LR;\n    node [shape=box];\n");
+        let _ = writeln!(out, "    User [shape=ellipse];");
+        let _ = writeln!(out, "    {} [label=\"{}\"];", assistant, escape(&self.assistant));
+        for tool in &tools {
+            let _ = writeln!(out, "    {} [label=\"{}\"];", participant_id(tool), escape(tool));
+        }
+        for ((from, to, label), count) in &edges {
+            let label = if *count == 1 { label.to_string() } else { format!("{} ×{}", label, count) };
+            let _ = writeln!(out, "    {} -> {} [label=\"{}\"];", from, to, label);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, tool: Option<&str>) -> HookEvent {
+        HookEvent {
+            ts: 0.0,
+            event: name.to_string(),
+            state_before: String::new(),
+            state_after: String::new(),
+            details: tool.map(|t| [("tool".to_string(), serde_json::Value::from(t))].into_iter().collect()),
+        }
+    }
+
+    #[test]
+    fn test_edges_are_counted() {
+        let events = vec![
+            event("UserPromptSubmit", None),
+            event("PostToolUse", Some("Bash")),
+            event("PostToolUse", Some("Read")),
+            event("PostToolUse", Some("Bash")),
+            event("Stop", None),
+        ];
+        assert_eq!(
+            DotGenerator::new("Claude").generate(&events),
+            r#"digraph session {
+    rankdir=LR;
+    node [shape=box];
+    User [shape=ellipse];
+    Claude [label="Claude"];
+    Bash [label="Bash"];
+    Read [label="Read"];
+    User -> Claude [label="prompt"];
+    Claude -> Bash [label="tool call ×2"];
+    Claude -> Read [label="tool call"];
+    Claude -> User [label="Stop"];
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Mermaid `sequenceDiagram` of a session
+//!
+//! ```text
+//! sequenceDiagram
+//!     actor User
+//!     participant Claude
+//!     participant Bash
+//!     User->>+Claude: prompt
+//!     Claude->>+Bash: tool call
+//!     Bash-->>-Claude: result
+//!     Claude->>-User: Stop
+//! ```
+//!
+//! Each kind of step has its own arrow: solid for requests, dashed for
+//! results, open (`-)`) for permission prompts. The assistant is activated
+//! from a prompt until its turn ends, and each tool for its call.
+
+use std::fmt::Write;
+
+use crate::platforms::HookEvent;
+
+use super::{participant_id, FlowStep};
+
+/// Builds a Mermaid sequence diagram from hook events
+pub struct MermaidGenerator {
+    assistant: String,
+}
+
+impl MermaidGenerator {
+    /// `assistant` is the platform's display name (`Claude`, `Codex`, `Cline`)
+    pub fn new(assistant: &str) -> Self {
+        Self {
+            assistant: assistant.to_string(),
+        }
+    }
+
+    pub fn generate_sequence(&self, events: &[HookEvent]) -> String {
+        let steps: Vec<FlowStep> = events.iter().map(FlowStep::from_event).collect();
+        let assistant = participant_id(&self.assistant);
+
+        let mut out = String::from("sequenceDiagram\n    actor User\n");
+        let _ = writeln!(out, "    participant {}", declaration(&assistant, &self.assistant));
+        let mut tools: Vec<&str> = Vec::new();
+        for step in &steps {
+            if let FlowStep::ToolCall { tool } | FlowStep::Permission { tool } = step {
+                if !tools.contains(&tool.as_str()) {
+                    tools.push(tool);
+                }
+            }
+        }
+        for tool in &tools {
+            let _ = writeln!(out, "    participant {}", declaration(&participant_id(tool), tool));
+        }
+
+        // A turn activates the assistant; a prompt while one is open (e.g.
+        // a missed Stop) doesn't stack another activation
+        let mut in_turn = false;
+        for step in &steps {
+            let line = match step {
+                FlowStep::Prompt if in_turn => format!("User->>{}: prompt", assistant),
+                FlowStep::Prompt => {
+                    in_turn = true;
+                    format!("User->>+{}: prompt", assistant)
+                }
+                FlowStep::Permission { tool } => format!("{}-)User: permission for {}", assistant, escape(tool)),
+                FlowStep::ToolCall { tool } => {
+                    let id = participant_id(tool);
+                    format!("{a}->>+{t}: tool call\n    {t}-->>-{a}: result", a = assistant, t = id)
+                }
+                FlowStep::Complete if in_turn => {
+                    in_turn = false;
+                    format!("{}->>-User: Stop", assistant)
+                }
+                FlowStep::Complete => format!("{}->>User: Stop", assistant),
+                FlowStep::SubagentComplete => format!("Note over {}: subagent finished", assistant),
+                FlowStep::Compact => format!("Note over {}: context compacted", assistant),
+                FlowStep::Other(event) => format!("Note over {}: {}", assistant, escape(event)),
+            };
+            let _ = writeln!(out, "    {}", line);
+        }
+        if in_turn {
+            let _ = writeln!(out, "    deactivate {}", assistant);
+        }
+        out
+    }
+}
+
+/// `id` alone, or `id as Label` when the name isn't a valid id
+fn declaration(id: &str, name: &str) -> String {
+    if id == name {
+        id.to_string()
+    } else {
+        format!("{} as {}", id, escape(name))
+    }
+}
+
+/// Message text can't hold `;` or `#` (statement separator, entity escape)
+fn escape(text: &str) -> String {
+    text.replace(';', ",").replace('#', "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn event(name: &str, tool: Option<&str>) -> HookEvent {
+        HookEvent {
+            ts: 0.0,
+            event: name.to_string(),
+            state_before: String::new(),
+            state_after: String::new(),
+            details: tool.map(|t| HashMap::from([("tool".to_string(), serde_json::Value::from(t))])),
+        }
+    }
+
+    /// Every `+` activation has a matching `-` or `deactivate`, never below zero
+    fn assert_balanced(diagram: &str) {
+        let mut open: HashMap<String, i32> = HashMap::new();
+        for line in diagram.lines().map(str::trim) {
+            let (arrow, target) = match line.split_once(':').map(|(head, _)| head) {
+                Some(head) if head.contains(">>+") => (1, head.rsplit(">>+").next().unwrap()),
+                // The sender of a `-` message is deactivated
+                Some(head) if head.contains(">>-") => (-1, head.split(">>-").next().unwrap().trim_end_matches('-')),
+                _ => match line.strip_prefix("deactivate ") {
+                    Some(target) => (-1, target),
+                    None => continue,
+                },
+            };
+            let count = open.entry(target.to_string()).or_default();
+            *count += arrow;
+            assert!(*count >= 0, "deactivated {} before activating it:\n{}", target, diagram);
+        }
+        assert!(open.values().all(|&count| count == 0), "unbalanced activations {:?}:\n{}", open, diagram);
+    }
+
+    /// Every participant in a message is declared before the messages
+    fn assert_declared(diagram: &str) {
+        let declared: Vec<&str> = diagram
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix("participant ").or_else(|| l.trim().strip_prefix("actor ")))
+            .map(|d| d.split(" as ").next().unwrap())
+            .collect();
+        for line in diagram.lines().map(str::trim).filter(|l| l.contains(">>") || l.contains("-)")) {
+            let head = line.split(':').next().unwrap();
+            for name in head.split(['-', '>', ')', '+']).filter(|s| !s.is_empty()) {
+                assert!(declared.contains(&name), "{} not declared:\n{}", name, diagram);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sequence_for_a_turn_with_tools() {
+        let events = vec![
+            event("UserPromptSubmit", None),
+            event("PermissionRequest", Some("Bash")),
+            event("PostToolUse", Some("Bash")),
+            event("PostToolUse", Some("mcp__github__create-issue")),
+            event("PreCompact", None),
+            event("Stop", None),
+        ];
+        let diagram = MermaidGenerator::new("Claude").generate_sequence(&events);
+        assert_eq!(
+            diagram,
+            "sequenceDiagram
+    actor User
+    participant Claude
+    participant Bash
+    participant mcp__github__create_issue as mcp__github__create-issue
+    User->>+Claude: prompt
+    Claude-)User: permission for Bash
+    Claude->>+Bash: tool call
+    Bash-->>-Claude: result
+    Claude->>+mcp__github__create_issue: tool call
+    mcp__github__create_issue-->>-Claude: result
+    Note over Claude: context compacted
+    Claude->>-User: Stop
+"
+        );
+        assert_balanced(&diagram);
+        assert_declared(&diagram);
+    }
+
+    #[test]
+    fn test_unfinished_and_doubled_turns_stay_balanced() {
+        let events = vec![
+            event("Stop", None),
+            event("UserPromptSubmit", None),
+            event("UserPromptSubmit", None),
+            event("PostToolUse", None),
+            event("SubagentStop", None),
+            event("Notification; #x", None),
+        ];
+        let diagram = MermaidGenerator::new("Claude").generate_sequence(&events);
+        assert!(diagram.ends_with("    Note over Claude: Notification, x\n    deactivate Claude\n"));
+        assert!(diagram.contains("    participant tool\n"));
+        assert_balanced(&diagram);
+        assert_declared(&diagram);
+
+        let empty = MermaidGenerator::new("Codex").generate_sequence(&[]);
+        assert_eq!(empty, "sequenceDiagram\n    actor User\n    participant Codex\n");
+    }
+}
@@ -14,7 +14,7 @@ mod fixtures {
     use crate::git::GitState;
     use crate::hooks::SessionStats;
     use crate::mirror::MirrorPublisher;
-    use crate::parsers::DiffSummary;
+    use crate::parsers::{DiffMode, DiffSummary, DEFAULT_MAX_DIFF_BYTES};
     use crate::platforms::{PlatformStats, SessionState};
 
     static INIT_ENV: Once = Once::new();
@@ -40,7 +40,7 @@ mod fixtures {
     }
 
     #[tokio::test]
-    async fn fixture_snapshots() -> Result<()> {
+    async fn test_fixture_snapshots() -> Result<()> {
         INIT_ENV.call_once(|| {
             std::env::set_var("GIT_CONFIG_GLOBAL", "/dev/null");
             std::env::set_var("GIT_CONFIG_SYSTEM", "/dev/null");
@@ -100,7 +100,8 @@ mod fixtures {
         stage_paths(repo_dir, &config.staged_paths).await?;
 
         let git_state = GitState::new().refresh_in_dir(repo_dir).await?;
-        let diff_summary = DiffSummary::new().refresh_in_dir(repo_dir).await?;
+        let diff_summary =
+            DiffSummary::new().refresh_in_dir(repo_dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
 
         let stats = build_stats(&config.stats);
         let mut publisher = MirrorPublisher::new(
@@ -110,10 +111,10 @@ mod fixtures {
             false,
         );
 
-        let published = publisher.maybe_publish(&stats, &git_state, &diff_summary, None, Some(42), Some(10))?;
-        if !published {
+        let Some(write) = publisher.maybe_publish(&stats, &git_state, &diff_summary, None, Some(42), Some(10))? else {
             bail!("mirror publish throttled or unchanged");
-        }
+        };
+        write.write()?;
 
         let mirror_path = publisher.mirror_path();
         let mirror_contents = fs::read_to_string(&mirror_path)
@@ -244,6 +245,7 @@ mod fixtures {
     fn normalize_mirror(value: &mut Value) {
         if let Some(obj) = value.as_object_mut() {
             obj.insert("last_updated".to_string(), Value::from(0.0));
+            obj.insert("pid".to_string(), Value::from(0));
         }
     }
 
@@ -251,3 +253,211 @@ mod fixtures {
         matches!(std::env::var("CRABIGATOR_UPDATE_FIXTURES"), Ok(v) if v == "1" || v == "true")
     }
 }
+
+#[cfg(test)]
+mod snapshots {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{bail, Context, Result};
+
+    use crate::git::{FileStatus, GitState};
+    use crate::hooks::SessionStats;
+    use crate::ide::IdeKind;
+    use crate::parsers::{ChangeNode, ChangeType, DiffSummary, FileChanges, NodeKind};
+    use crate::platforms::{PlatformStats, SessionState};
+    use crate::terminal::theme::Palette;
+    use crate::ui::utils::strip_ansi;
+    use crate::ui::{draw_changes_widget, draw_git_widget, draw_stats_widget, WidgetArea, WidgetMode};
+
+    /// Render every content row of a widget (row 0 is the separator, drawn by the status bar)
+    fn render_rows(
+        width: u16,
+        height: u16,
+        mut draw: impl FnMut(&mut Vec<u8>, WidgetArea) -> Result<()>,
+    ) -> Result<Vec<String>> {
+        (1..height)
+            .map(|row| {
+                let mut buf = Vec::new();
+                let area = WidgetArea {
+                    pty_rows: 0,
+                    col: 0,
+                    row,
+                    width,
+                    height,
+                };
+                draw(&mut buf, area)?;
+                Ok(String::from_utf8(buf)?)
+            })
+            .collect()
+    }
+
+    /// Snapshot file: escaped raw ANSI rows for inspection, then the visible text
+    fn format_snapshot(rows: &[String]) -> String {
+        let mut out = String::from("--- raw ---\n");
+        for row in rows {
+            out.push_str(&row.replace('\x1b', "\\e").replace('\x07', "\\a"));
+            out.push('\n');
+        }
+        out.push_str("--- plain ---\n");
+        for row in rows {
+            out.push('|');
+            out.push_str(&strip_ansi(row));
+            out.push_str("|\n");
+        }
+        out
+    }
+
+    fn assert_snapshot(name: &str, rows: &[String]) -> Result<()> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(format!("{}.txt", name));
+        let actual = format_snapshot(rows);
+
+        if should_update_snapshots() {
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(&path, &actual).with_context(|| format!("write {}", path.display()))?;
+            return Ok(());
+        }
+
+        let expected = fs::read_to_string(&path)
+            .with_context(|| format!("read {} (run with UPDATE_SNAPSHOTS=1 to create)", path.display()))?;
+        if expected != actual {
+            bail!(
+                "snapshot mismatch: {}\n--- expected ---\n{}\n--- actual ---\n{}",
+                path.display(),
+                expected,
+                actual
+            );
+        }
+        Ok(())
+    }
+
+    fn should_update_snapshots() -> bool {
+        matches!(std::env::var("UPDATE_SNAPSHOTS"), Ok(v) if v == "1" || v == "true")
+    }
+
+    fn file(path: &str, status: &str, additions: usize, deletions: usize) -> FileStatus {
+        FileStatus {
+            status: status.to_string(),
+            ..FileStatus::fixture(path, additions, deletions)
+        }
+    }
+
+    fn git_state(files: Vec<FileStatus>) -> GitState {
+        GitState {
+            files,
+            branch: "main".to_string(),
+            is_repo: true,
+            ..Default::default()
+        }
+    }
+
+    fn git_rows(files: Vec<FileStatus>, width: u16, height: u16) -> Result<Vec<String>> {
+        let state = git_state(files);
+        render_rows(width, height, |buf, area| {
+            let cwd = Path::new("/repo");
+            draw_git_widget(buf, area, &state, None, IdeKind::None, cwd, WidgetMode::Normal, &Palette::default())
+        })
+    }
+
+    fn numbered_files(count: usize) -> Vec<FileStatus> {
+        (0..count)
+            .map(|i| file(&format!("src/module_{}.rs", i), if i % 3 == 0 { "A" } else { "M" }, i * 7 + 1, i * 2))
+            .collect()
+    }
+
+    #[test]
+    fn test_git_widget_snapshots() -> Result<()> {
+        assert_snapshot("git_empty", &git_rows(Vec::new(), 40, 6)?)?;
+        assert_snapshot("git_one_file", &git_rows(vec![file("src/main.rs", "M", 12, 3)], 40, 6)?)?;
+        assert_snapshot("git_five_files", &git_rows(numbered_files(5), 48, 8)?)?;
+        assert_snapshot("git_multi_column", &git_rows(numbered_files(12), 96, 6)?)?;
+        Ok(())
+    }
+
+    fn session_stats() -> SessionStats {
+        let mut stats = SessionStats::new();
+        stats.work_seconds = 754;
+        stats.platform_stats = PlatformStats {
+            prompts: 6,
+            completions: 5,
+            compressions: 1,
+            tools: HashMap::from([("Read".to_string(), 9), ("Edit".to_string(), 4)]),
+            state: SessionState::Complete,
+            ..PlatformStats::default()
+        };
+        stats
+    }
+
+    #[test]
+    fn test_stats_widget_snapshots() -> Result<()> {
+        let stats = session_stats();
+        let palette = Palette::default();
+        let draw = |buf: &mut Vec<u8>, area: WidgetArea| {
+            draw_stats_widget(buf, area, &stats, None, None, None, WidgetMode::Normal, &palette)
+        };
+        assert_snapshot("stats_compact", &render_rows(44, 5, draw)?)?;
+        assert_snapshot("stats_normal", &render_rows(28, 8, draw)?)?;
+        Ok(())
+    }
+
+    fn change(kind: NodeKind, name: &str, change_type: ChangeType, path: &str, additions: usize, deletions: usize) -> ChangeNode {
+        ChangeNode {
+            kind,
+            change_type,
+            additions,
+            deletions,
+            line_number: Some(10),
+            ..ChangeNode::fixture(name, path)
+        }
+    }
+
+    #[test]
+    fn test_changes_widget_snapshots() -> Result<()> {
+        let summary = DiffSummary {
+            files: vec![
+                FileChanges {
+                    path: "src/app.rs".to_string(),
+                    language: "Rust".to_string(),
+                    changes: vec![
+                        change(NodeKind::Function, "run", ChangeType::Modified, "src/app.rs", 14, 6),
+                        change(NodeKind::Struct, "App", ChangeType::Modified, "src/app.rs", 2, 0),
+                    ],
+                    is_test: false,
+                },
+                FileChanges {
+                    path: "web/client.ts".to_string(),
+                    language: "TypeScript".to_string(),
+                    changes: vec![change(NodeKind::Class, "Client", ChangeType::Added, "web/client.ts", 40, 0)],
+                    is_test: false,
+                },
+                FileChanges {
+                    path: "scripts/sync.py".to_string(),
+                    language: "Python".to_string(),
+                    changes: vec![change(NodeKind::Function, "sync", ChangeType::Deleted, "scripts/sync.py", 0, 18)],
+                    is_test: false,
+                },
+            ],
+            loading: false,
+            ..Default::default()
+        };
+        let rows = render_rows(64, 9, |buf, area| {
+            draw_changes_widget(
+                buf,
+                area,
+                &summary,
+                Some("✳ Refactor"),
+                IdeKind::None,
+                Path::new("/repo"),
+                false,
+                false,
+                WidgetMode::Normal,
+                &Palette::default(),
+            )
+        })?;
+        assert_snapshot("changes_multi_language", &rows)?;
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+mod commit_suggest;
 mod status;
+mod watcher;
 
-pub use status::{FileStatus, GitState};
+pub use commit_suggest::{generate_commit_suggestion, staged_paths};
+pub use status::{head_commit, FileStatus, GitState};
+pub use watcher::GitWatcher;
@@ -0,0 +1,276 @@
+//! Commit message suggestion for staged changes (`Ctrl+A m`)
+//!
+//! Builds a conventional-commit style subject from the files in the index and
+//! the definitions the diff parsers found in them, e.g.
+//! `refactor(src/app): update draw_status_bar, handle_resize`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::parsers::{ChangeNode, ChangeType, DiffSummary, NodeKind};
+
+use super::GitState;
+
+/// Definitions named in the subject, most-changed first
+const MAX_NAMES: usize = 3;
+
+/// Modifications touching at most this many lines read as a fix rather than a
+/// refactor
+const FIX_MAX_LINES: usize = 10;
+
+/// Paths with staged changes, from `git diff --cached --stat`
+///
+/// Empty when nothing is staged or `dir` is not a repository.
+pub fn staged_paths(dir: &Path) -> Vec<String> {
+    let output = std::process::Command::new("git")
+        .args(["diff", "--cached", "--stat=1000,1000"])
+        .current_dir(dir)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => parse_stat_paths(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// File paths from `--stat` output (` path | 12 ++--`); renames give the new
+/// path (`old => new`, `dir/{old => new}/file`)
+fn parse_stat_paths(stat: &str) -> Vec<String> {
+    stat.lines()
+        .filter_map(|line| line.split_once(" | "))
+        .map(|(path, _)| renamed_path(path.trim()))
+        .collect()
+}
+
+fn renamed_path(path: &str) -> String {
+    if let (Some(open), Some(close)) = (path.find('{'), path.find('}')) {
+        if let Some((_, new)) = path[open + 1..close].split_once(" => ") {
+            return format!("{}{}{}", &path[..open], new, &path[close + 1..]).replace("//", "/");
+        }
+    }
+    match path.split_once(" => ") {
+        Some((_, new)) => new.to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Suggest a commit subject for the files in `git`
+///
+/// Callers pass a `GitState` narrowed to the staged files; `diff` supplies the
+/// definitions changed in them. Returns an empty string when there are no files.
+pub fn generate_commit_suggestion(diff: &DiffSummary, git: &GitState) -> String {
+    let files: Vec<_> = git.files.iter().filter(|f| !f.is_folder).collect();
+    if files.is_empty() {
+        return String::new();
+    }
+
+    let changes: Vec<&ChangeNode> = diff
+        .files
+        .iter()
+        .filter(|file| files.iter().any(|f| f.path == file.path))
+        .flat_map(|file| &file.changes)
+        .filter(|change| !change.is_binary())
+        .collect();
+
+    let total_lines: usize = files.iter().map(|f| f.total_changes()).sum();
+    let statuses: Vec<&str> = files.iter().map(|f| f.status.as_str()).collect();
+    let commit_type = match dominant_change_type(&changes, &statuses) {
+        ChangeType::Added => "feat",
+        ChangeType::Deleted => "chore",
+        ChangeType::Modified if total_lines <= FIX_MAX_LINES => "fix",
+        ChangeType::Modified => "refactor",
+    };
+    let verb = match commit_type {
+        "feat" => "add",
+        "chore" => "remove",
+        _ => "update",
+    };
+
+    let mut names = changed_names(&changes);
+    if names.is_empty() {
+        names = files.iter().map(|f| f.path.rsplit('/').next().unwrap_or(&f.path).to_string()).collect();
+    }
+    names.truncate(MAX_NAMES);
+
+    let paths: Vec<(&str, usize)> = files.iter().map(|f| (f.path.as_str(), f.total_changes())).collect();
+    match scope(&paths) {
+        Some(scope) => format!("{}({}): {} {}", commit_type, scope, verb, names.join(", ")),
+        None => format!("{}: {} {}", commit_type, verb, names.join(", ")),
+    }
+}
+
+/// The most common kind of change; without parsed definitions, the file
+/// statuses decide (all new files read as added, all removed as deleted)
+fn dominant_change_type(changes: &[&ChangeNode], statuses: &[&str]) -> ChangeType {
+    if changes.is_empty() {
+        return if statuses.iter().all(|s| matches!(*s, "A" | "??")) {
+            ChangeType::Added
+        } else if statuses.iter().all(|s| *s == "D") {
+            ChangeType::Deleted
+        } else {
+            ChangeType::Modified
+        };
+    }
+
+    let count = |change_type: ChangeType| changes.iter().filter(|c| c.change_type == change_type).count();
+    let added = count(ChangeType::Added);
+    let modified = count(ChangeType::Modified);
+    let deleted = count(ChangeType::Deleted);
+    if added >= modified && added >= deleted {
+        ChangeType::Added
+    } else if modified >= deleted {
+        ChangeType::Modified
+    } else {
+        ChangeType::Deleted
+    }
+}
+
+/// Changed definition names, functions and methods before anything else, then
+/// by lines changed
+fn changed_names(changes: &[&ChangeNode]) -> Vec<String> {
+    let mut sorted = changes.to_vec();
+    sorted.sort_by_key(|c| {
+        let is_function = matches!(c.kind, NodeKind::Function | NodeKind::Method);
+        (!is_function, std::cmp::Reverse(c.additions + c.deletions))
+    });
+
+    let mut names: Vec<String> = Vec::new();
+    for change in sorted {
+        if !names.contains(&change.name) {
+            names.push(change.name.clone());
+        }
+    }
+    names
+}
+
+/// A single file scopes to its path without the extension; otherwise the
+/// directory with the most changed lines (ties go to the first alphabetically).
+/// Files at the repository root have no scope.
+fn scope(paths: &[(&str, usize)]) -> Option<String> {
+    if let [(path, _)] = paths {
+        let without_ext = match path.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() && !stem.ends_with('/') => stem,
+            _ => path,
+        };
+        return Some(without_ext.to_string());
+    }
+
+    let mut by_dir: BTreeMap<&str, usize> = BTreeMap::new();
+    for (path, lines) in paths {
+        let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        *by_dir.entry(dir).or_default() += lines;
+    }
+    let mut busiest: Option<(&str, usize)> = None;
+    for (dir, lines) in by_dir {
+        if busiest.is_none_or(|(_, most)| lines > most) {
+            busiest = Some((dir, lines));
+        }
+    }
+    busiest.map(|(dir, _)| dir).filter(|dir| !dir.is_empty()).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::FileStatus;
+    use crate::parsers::FileChanges;
+
+    fn file(status: &str, path: &str, additions: usize, deletions: usize) -> FileStatus {
+        FileStatus {
+            status: status.to_string(),
+            file_count: 1,
+            staged: true,
+            ..FileStatus::fixture(path, additions, deletions)
+        }
+    }
+
+    fn change(kind: NodeKind, name: &str, change_type: ChangeType, path: &str, lines: usize) -> ChangeNode {
+        ChangeNode {
+            kind,
+            change_type,
+            additions: lines,
+            ..ChangeNode::fixture(name, path)
+        }
+    }
+
+    fn summary(changes: Vec<ChangeNode>) -> DiffSummary {
+        let mut by_path: BTreeMap<String, Vec<ChangeNode>> = BTreeMap::new();
+        for c in changes {
+            by_path.entry(c.file_path.clone().unwrap()).or_default().push(c);
+        }
+        DiffSummary {
+            files: by_path
+                .into_iter()
+                .map(|(path, changes)| FileChanges { path, language: "Rust".to_string(), changes, is_test: false })
+                .collect(),
+            ..DiffSummary::default()
+        }
+    }
+
+    fn git(files: Vec<FileStatus>) -> GitState {
+        GitState { files, is_repo: true, ..GitState::default() }
+    }
+
+    #[test]
+    fn test_modified_functions_in_one_file() {
+        let diff = summary(vec![
+            change(NodeKind::Function, "handle_resize", ChangeType::Modified, "src/app.rs", 12),
+            change(NodeKind::Function, "draw_status_bar", ChangeType::Modified, "src/app.rs", 30),
+        ]);
+        let git = git(vec![file("M", "src/app.rs", 30, 12)]);
+        assert_eq!(generate_commit_suggestion(&diff, &git), "refactor(src/app): update draw_status_bar, handle_resize");
+    }
+
+    #[test]
+    fn test_small_modification_is_a_fix() {
+        let diff = summary(vec![change(NodeKind::Method, "parse", ChangeType::Modified, "src/parser.rs", 2)]);
+        let git = git(vec![file("M", "src/parser.rs", 2, 1)]);
+        assert_eq!(generate_commit_suggestion(&diff, &git), "fix(src/parser): update parse");
+    }
+
+    #[test]
+    fn test_added_definitions_scope_to_busiest_directory() {
+        let diff = summary(vec![
+            change(NodeKind::Struct, "Widget", ChangeType::Added, "src/ui/widget.rs", 40),
+            change(NodeKind::Function, "draw_widget", ChangeType::Added, "src/ui/widget.rs", 20),
+            change(NodeKind::Function, "render", ChangeType::Added, "src/ui/render.rs", 25),
+            change(NodeKind::Function, "layout", ChangeType::Added, "src/ui/render.rs", 5),
+            change(NodeKind::Function, "main", ChangeType::Modified, "src/main.rs", 3),
+        ]);
+        let git = git(vec![
+            file("A", "src/ui/widget.rs", 60, 0),
+            file("A", "src/ui/render.rs", 30, 0),
+            file("M", "src/main.rs", 2, 1),
+        ]);
+        // Functions come before the struct, and only three names are listed
+        assert_eq!(generate_commit_suggestion(&diff, &git), "feat(src/ui): add render, draw_widget, layout");
+    }
+
+    #[test]
+    fn test_deleted_files_without_definitions() {
+        let git = git(vec![file("D", "old.txt", 0, 10), file("D", "notes.md", 0, 4)]);
+        // Root files have no scope; file names stand in for definitions
+        assert_eq!(generate_commit_suggestion(&DiffSummary::default(), &git), "chore: remove old.txt, notes.md");
+    }
+
+    #[test]
+    fn test_only_staged_files_are_described() {
+        let diff = summary(vec![
+            change(NodeKind::Function, "staged_fn", ChangeType::Added, "src/a.rs", 5),
+            change(NodeKind::Function, "unstaged_fn", ChangeType::Deleted, "src/b.rs", 50),
+        ]);
+        assert_eq!(generate_commit_suggestion(&diff, &git(vec![file("M", "src/a.rs", 5, 0)])), "feat(src/a): add staged_fn");
+        assert_eq!(generate_commit_suggestion(&diff, &git(Vec::new())), "");
+    }
+
+    #[test]
+    fn test_parse_stat_paths() {
+        let stat = concat!(
+            " src/app.rs                 | 12 ++++++------\n",
+            " src/{old => new}/lib.rs    |  2 +-\n",
+            " README.md => docs/README.md |  0\n",
+            " 3 files changed, 7 insertions(+), 7 deletions(-)\n",
+        );
+        assert_eq!(parse_stat_paths(stat), vec!["src/app.rs", "src/new/lib.rs", "docs/README.md"]);
+        assert_eq!(renamed_path("src/{ => ui}/x.rs"), "src/ui/x.rs");
+    }
+}
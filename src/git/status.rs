@@ -2,7 +2,7 @@ use anyhow::Result;
 use std::path::Path;
 use tokio::process::Command;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct FileStatus {
     pub status: String,
     pub path: String,
@@ -10,6 +10,13 @@ pub struct FileStatus {
     pub deletions: usize,
     pub is_folder: bool,
     pub file_count: usize,
+    /// All of the file's changes are in the index (ready to commit)
+    pub staged: bool,
+    /// A submodule whose checkout moved to other commits or has changes inside
+    pub is_submodule: bool,
+    /// Commits between the submodule's recorded and checked-out commit, from
+    /// `git submodule summary` (None when only its content changed)
+    pub submodule_commits: Option<usize>,
 }
 
 impl FileStatus {
@@ -17,6 +24,18 @@ impl FileStatus {
     pub fn total_changes(&self) -> usize {
         self.additions + self.deletions
     }
+
+    /// A modified, unstaged file, for tests to adjust with `..`
+    #[cfg(test)]
+    pub fn fixture(path: &str, additions: usize, deletions: usize) -> Self {
+        Self {
+            status: "M".to_string(),
+            path: path.to_string(),
+            additions,
+            deletions,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -25,6 +44,12 @@ pub struct GitState {
     pub branch: String,
     pub is_repo: bool,
     pub loading: bool,
+    /// Web URL for the branch (its PR if one exists), cached per branch name
+    pub branch_url: Option<String>,
+    /// Running in a linked worktree (`git worktree add`), not the main checkout
+    pub is_worktree: bool,
+    /// The linked worktree's name under `.git/worktrees/`
+    pub worktree_name: Option<String>,
 }
 
 impl GitState {
@@ -35,11 +60,6 @@ impl GitState {
         }
     }
 
-    pub async fn refresh(&self) -> Result<Self> {
-        let cwd = std::env::current_dir()?;
-        self.refresh_in_dir(&cwd).await
-    }
-
     pub async fn refresh_in_dir(&self, dir: &Path) -> Result<Self> {
         let profile = std::env::var("CRABIGATOR_PROFILE").is_ok();
         let start = std::time::Instant::now();
@@ -61,6 +81,9 @@ impl GitState {
             }
         }
 
+        state.worktree_name = linked_worktree_name(dir);
+        state.is_worktree = state.worktree_name.is_some();
+
         // Get current branch
         if let Ok(output) = Command::new("git")
             .args(["branch", "--show-current"])
@@ -73,31 +96,33 @@ impl GitState {
             }
         }
 
-        // Get file statuses using porcelain format
+        // Resolve the branch's web URL only when the branch changes
+        state.branch_url = if state.branch == self.branch && !self.loading {
+            self.branch_url.clone()
+        } else {
+            Self::remote_branch_url(dir, &state.branch).await
+        };
+
+        // Get file statuses using porcelain v2 (separates index and worktree state)
         if let Ok(output) = Command::new("git")
-            .args(["status", "--porcelain"])
+            .args(["status", "--porcelain=v2"])
             .current_dir(dir)
             .output()
             .await
         {
             if output.status.success() {
                 let stdout = String::from_utf8_lossy(&output.stdout);
-                for line in stdout.lines() {
-                    if line.len() >= 3 {
-                        let status = line[0..2].trim().to_string();
-                        let path = unquote_git_path(&line[3..]);
-
-                        // Detect if this is an untracked folder
-                        let is_folder = status == "??" && path.ends_with('/');
-
-                        state.files.push(FileStatus {
-                            status,
-                            path,
-                            additions: 0,
-                            deletions: 0,
-                            is_folder,
-                            file_count: 0,
-                        });
+                state.files = parse_porcelain_v2(&stdout);
+            }
+        }
+
+        // Commit counts for submodules checked out at other commits
+        if state.files.iter().any(|f| f.is_submodule) {
+            if let Ok(output) = Command::new("git").args(["submodule", "summary"]).current_dir(dir).output().await {
+                if output.status.success() {
+                    let counts = parse_submodule_summary(&String::from_utf8_lossy(&output.stdout));
+                    for file in state.files.iter_mut().filter(|f| f.is_submodule) {
+                        file.submodule_commits = counts.iter().find(|(path, _)| *path == file.path).map(|(_, n)| *n);
                     }
                 }
             }
@@ -146,8 +171,8 @@ impl GitState {
 
         // Count lines for untracked files and newly added files without diff stats
         for file in &mut state.files {
-            // Skip folders and files that already have stats
-            if file.is_folder || file.additions > 0 || file.deletions > 0 {
+            // Skip folders, submodules and files that already have stats
+            if file.is_folder || file.is_submodule || file.additions > 0 || file.deletions > 0 {
                 continue;
             }
             // Count lines for untracked (??) or newly added (A) files
@@ -169,6 +194,45 @@ impl GitState {
         Ok(state)
     }
 
+    /// Web URL for `branch` on the `origin` remote
+    ///
+    /// Prefers the open PR's URL from `gh pr view` (GitHub only), falling back
+    /// to the branch's tree page. None for detached HEAD or unknown hosts.
+    async fn remote_branch_url(dir: &Path, branch: &str) -> Option<String> {
+        if branch.is_empty() {
+            return None;
+        }
+        let output = Command::new("git")
+            .args(["remote", "get-url", "origin"])
+            .current_dir(dir)
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let remote = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let tree_url = branch_url_from_remote(&remote, branch)?;
+
+        if tree_url.contains("github") {
+            let pr = tokio::time::timeout(
+                std::time::Duration::from_secs(2),
+                Command::new("gh")
+                    .args(["pr", "view", branch, "--json", "url", "--jq", ".url"])
+                    .current_dir(dir)
+                    .output(),
+            )
+            .await;
+            if let Ok(Ok(output)) = pr {
+                let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if output.status.success() && url.starts_with("https://") {
+                    return Some(url);
+                }
+            }
+        }
+        Some(tree_url)
+    }
+
     fn parse_numstat(numstat: &str, files: &mut [FileStatus]) {
         for line in numstat.lines() {
             let parts: Vec<&str> = line.split('\t').collect();
@@ -177,8 +241,9 @@ impl GitState {
                 let deletions = parts[1].parse::<usize>().unwrap_or(0);
                 let path = parts[2];
 
-                // Find matching file and update stats
-                if let Some(file) = files.iter_mut().find(|f| f.path == path) {
+                // Find matching file and update stats (a submodule's is just
+                // its `Subproject commit` line, so it shows commits instead)
+                if let Some(file) = files.iter_mut().find(|f| f.path == path && !f.is_submodule) {
                     file.additions += additions;
                     file.deletions += deletions;
                 }
@@ -238,6 +303,163 @@ impl GitState {
     }
 }
 
+/// Commit hash HEAD points at in `dir`; None outside a repository or before
+/// the first commit
+pub async fn head_commit(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .await
+        .ok()?;
+    let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !hash.is_empty()).then_some(hash)
+}
+
+/// Name of the linked worktree containing `dir`, if any
+///
+/// The main checkout has a `.git` directory; a linked worktree has a `.git`
+/// file whose `gitdir:` points at `<main>/.git/worktrees/<name>`. Submodules
+/// also use a `.git` file, but theirs points into `.git/modules/`.
+fn linked_worktree_name(dir: &Path) -> Option<String> {
+    let dot_git = dir.ancestors().map(|d| d.join(".git")).find(|p| p.exists())?;
+    if dot_git.is_dir() {
+        return None;
+    }
+    worktree_name_from_gitdir(&std::fs::read_to_string(dot_git).ok()?)
+}
+
+/// Parse a `.git` file's `gitdir: <path>` pointer into a worktree name
+fn worktree_name_from_gitdir(contents: &str) -> Option<String> {
+    let gitdir = contents.lines().find_map(|l| l.strip_prefix("gitdir:"))?.trim();
+    let gitdir = Path::new(gitdir);
+    let parent = gitdir.parent()?;
+    if parent.file_name()? != "worktrees" {
+        return None;
+    }
+    Some(gitdir.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Build the branch's web URL from a remote URL
+///
+/// Handles scp-style SSH (`git@host:owner/repo.git`), `ssh://` and
+/// `https://` remotes, with or without the `.git` suffix. Only GitHub, GitLab
+/// and Bitbucket hosts (including self-hosted ones named after them) are
+/// recognised, since each lays out branch pages differently.
+fn branch_url_from_remote(remote: &str, branch: &str) -> Option<String> {
+    let remote = remote.trim();
+    let (host, path) = if let Some((_, rest)) = remote.split_once("://") {
+        // ssh://git@host:22/owner/repo, https://user@host/owner/repo
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?;
+        (host.split(':').next()?, path)
+    } else {
+        // git@host:owner/repo
+        let (authority, path) = remote.split_once(':')?;
+        (authority.rsplit('@').next()?, path)
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || !path.contains('/') {
+        return None;
+    }
+
+    let branch = encode_branch(branch);
+    let base = format!("https://{}/{}", host, path);
+    if host.contains("github") {
+        Some(format!("{}/tree/{}", base, branch))
+    } else if host.contains("gitlab") {
+        Some(format!("{}/-/tree/{}", base, branch))
+    } else if host.contains("bitbucket") {
+        Some(format!("{}/src/{}", base, branch))
+    } else {
+        None
+    }
+}
+
+/// Percent-encode the characters in a branch name that would break a URL path
+fn encode_branch(branch: &str) -> String {
+    let mut encoded = String::with_capacity(branch.len());
+    for c in branch.chars() {
+        match c {
+            '%' | '#' | '?' | ' ' | '"' | '<' | '>' => encoded.push_str(&format!("%{:02X}", c as u32)),
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// Parse `git status --porcelain=v2` output
+///
+/// Status codes are reported the way v1 shows them (`M`, `A`, `MM`, `??`), so
+/// the index and worktree columns with `.` for "unchanged" are collapsed.
+/// Ignored entries (`!`, only listed with `--ignored`) are left out.
+/// Submodules are flagged from the `sub` field (`S<c><m><u>`, where v1 would
+/// say `(new commits)` or `(modified content)`).
+fn parse_porcelain_v2(output: &str) -> Vec<FileStatus> {
+    let mut files = Vec::new();
+    for line in output.lines() {
+        let (xy, sub, path) = match line.split_once(' ') {
+            // Ordinary change: 1 XY sub mH mI mW hH hI path
+            Some(("1", rest)) => match rest.splitn(8, ' ').collect::<Vec<_>>()[..] {
+                [xy, sub, .., path] => (xy, sub, path),
+                _ => continue,
+            },
+            // Rename or copy: 2 XY sub mH mI mW hH hI Xscore path<TAB>origPath
+            Some(("2", rest)) => match rest.splitn(9, ' ').collect::<Vec<_>>()[..] {
+                [xy, sub, .., paths] => (xy, sub, paths.split('\t').next().unwrap_or(paths)),
+                _ => continue,
+            },
+            // Unmerged: u XY sub m1 m2 m3 mW h1 h2 h3 path
+            Some(("u", rest)) => match rest.splitn(10, ' ').collect::<Vec<_>>()[..] {
+                [xy, sub, .., path] => (xy, sub, path),
+                _ => continue,
+            },
+            Some(("?", path)) => ("??", "N...", path),
+            _ => continue,
+        };
+        if xy.len() != 2 {
+            continue;
+        }
+
+        let path = unquote_git_path(path);
+        let (index, worktree) = xy.split_at(1);
+        let status = if xy == "??" {
+            xy.to_string()
+        } else {
+            xy.replace('.', " ").trim().to_string()
+        };
+        files.push(FileStatus {
+            // Detect if this is an untracked folder
+            is_folder: xy == "??" && path.ends_with('/'),
+            staged: xy != "??" && index != "." && worktree == ".",
+            is_submodule: sub.starts_with('S'),
+            status,
+            path,
+            additions: 0,
+            deletions: 0,
+            file_count: 0,
+            submodule_commits: None,
+        });
+    }
+    files
+}
+
+/// Commit counts from `git submodule summary`, whose entries start
+/// `* path old...new (N):`; rewinds and other non-numeric counts are skipped
+fn parse_submodule_summary(output: &str) -> Vec<(String, usize)> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("* "))
+        .filter_map(|entry| {
+            let (head, count) = entry.rsplit_once(" (")?;
+            let count = count.strip_suffix("):")?.parse().ok()?;
+            let (path, _range) = head.rsplit_once(' ')?;
+            Some((path.to_string(), count))
+        })
+        .collect()
+}
+
 /// Unquote a git-quoted path (paths with spaces are quoted by git status --porcelain)
 /// Git uses C-style quoting: "path with \"quotes\" and spaces"
 fn unquote_git_path(path: &str) -> String {
@@ -270,3 +492,204 @@ fn unquote_git_path(path: &str) -> String {
         path.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OID: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn ordinary(xy: &str, path: &str) -> String {
+        format!("1 {} N... 100644 100644 100644 {} {} {}", xy, OID, OID, path)
+    }
+
+    fn parse_one(line: &str) -> FileStatus {
+        let files = parse_porcelain_v2(line);
+        assert_eq!(files.len(), 1, "expected one entry for {:?}", line);
+        files.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_branch_url_from_github_remotes() {
+        let expected = Some("https://github.com/samuelclay/crabigator/tree/feature/x".to_string());
+        for remote in [
+            "git@github.com:samuelclay/crabigator.git",
+            "git@github.com:samuelclay/crabigator",
+            "ssh://git@github.com/samuelclay/crabigator.git",
+            "ssh://git@github.com:22/samuelclay/crabigator",
+            "https://github.com/samuelclay/crabigator.git",
+            "https://github.com/samuelclay/crabigator",
+            "https://token@github.com/samuelclay/crabigator.git\n",
+        ] {
+            assert_eq!(branch_url_from_remote(remote, "feature/x"), expected, "remote {}", remote);
+        }
+    }
+
+    #[test]
+    fn test_branch_url_from_gitlab_and_bitbucket_remotes() {
+        assert_eq!(
+            branch_url_from_remote("git@gitlab.com:group/sub/project.git", "main").as_deref(),
+            Some("https://gitlab.com/group/sub/project/-/tree/main")
+        );
+        assert_eq!(
+            branch_url_from_remote("https://gitlab.example.com/team/app", "fix-1").as_deref(),
+            Some("https://gitlab.example.com/team/app/-/tree/fix-1")
+        );
+        assert_eq!(
+            branch_url_from_remote("https://sam@bitbucket.org/team/app.git", "main").as_deref(),
+            Some("https://bitbucket.org/team/app/src/main")
+        );
+    }
+
+    #[test]
+    fn test_branch_url_unknown_or_malformed_remotes() {
+        assert_eq!(branch_url_from_remote("https://git.example.com/team/app.git", "main"), None);
+        assert_eq!(branch_url_from_remote("/srv/git/app.git", "main"), None);
+        assert_eq!(branch_url_from_remote("git@github.com:app.git", "main"), None);
+        assert_eq!(branch_url_from_remote("", "main"), None);
+    }
+
+    #[test]
+    fn test_branch_url_encodes_special_characters() {
+        assert_eq!(
+            branch_url_from_remote("git@github.com:o/r.git", "fix#12 ?").as_deref(),
+            Some("https://github.com/o/r/tree/fix%2312%20%3F")
+        );
+    }
+
+    #[test]
+    fn test_worktree_name_from_gitdir() {
+        assert_eq!(
+            worktree_name_from_gitdir("gitdir: /src/app/.git/worktrees/feature-x\n").as_deref(),
+            Some("feature-x")
+        );
+        // Submodules point into .git/modules
+        assert_eq!(worktree_name_from_gitdir("gitdir: ../.git/modules/vendor/lib\n"), None);
+        assert_eq!(worktree_name_from_gitdir("not a pointer"), None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_detects_linked_worktree() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let main = temp.path().join("main");
+        let linked = temp.path().join("linked");
+        std::fs::create_dir(&main)?;
+        std::fs::write(main.join("README.md"), "hello\n")?;
+
+        let git = |dir: &Path, args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=Tests", "-c", "user.email=tests@example.com", "-c", "commit.gpgsign=false"])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .map(|o| o.status.success());
+            assert!(matches!(status, Ok(true)), "git {:?} failed", args);
+        };
+        git(&main, &["init", "-q", "-b", "main"]);
+        git(&main, &["add", "."]);
+        git(&main, &["commit", "-q", "-m", "base"]);
+        git(&main, &["worktree", "add", "-q", "-b", "feature", linked.to_str().unwrap()]);
+        std::fs::create_dir(linked.join("src"))?;
+
+        let main_state = GitState::new().refresh_in_dir(&main).await?;
+        assert_eq!(main_state.branch, "main");
+        assert!(!main_state.is_worktree);
+        assert_eq!(main_state.worktree_name, None);
+
+        // Detection works from a subdirectory of the linked worktree too
+        let linked_state = GitState::new().refresh_in_dir(&linked.join("src")).await?;
+        assert_eq!(linked_state.branch, "feature");
+        assert!(linked_state.is_worktree);
+        assert_eq!(linked_state.worktree_name.as_deref(), Some("linked"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_porcelain_v2_staged_addition() {
+        let file = parse_one(&ordinary("A.", "src/new.rs"));
+        assert_eq!(file.status, "A");
+        assert_eq!(file.path, "src/new.rs");
+        assert!(file.staged);
+    }
+
+    #[test]
+    fn test_porcelain_v2_staged_and_unstaged_modifications() {
+        let staged = parse_one(&ordinary("M.", "src/lib.rs"));
+        assert_eq!(staged.status, "M");
+        assert!(staged.staged);
+
+        let unstaged = parse_one(&ordinary(".M", "src/main.rs"));
+        assert_eq!(unstaged.status, "M");
+        assert!(!unstaged.staged);
+
+        // Partially staged files still have worktree changes, so they're unstaged
+        let both = parse_one(&ordinary("MM", "src/app.rs"));
+        assert_eq!(both.status, "MM");
+        assert!(!both.staged);
+
+        let deleted = parse_one(&ordinary(".D", "old.rs"));
+        assert_eq!(deleted.status, "D");
+        assert!(!deleted.staged);
+    }
+
+    #[test]
+    fn test_porcelain_v2_renames_untracked_and_unmerged() {
+        let output = format!(
+            "# branch.oid {}\n\
+             2 R. N... 100644 100644 100644 {} {} R100 src/renamed.rs\tsrc/original.rs\n\
+             u UU N... 100644 100644 100644 100644 {} {} {} conflict.rs\n\
+             ? notes/\n\
+             ? \"with space.txt\"\n\
+             ! target/\n",
+            OID, OID, OID, OID, OID, OID
+        );
+        let files = parse_porcelain_v2(&output);
+        let summary: Vec<(&str, &str, bool, bool)> = files
+            .iter()
+            .map(|f| (f.status.as_str(), f.path.as_str(), f.staged, f.is_folder))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("R", "src/renamed.rs", true, false),
+                ("UU", "conflict.rs", false, false),
+                ("??", "notes/", false, true),
+                ("??", "with space.txt", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_porcelain_v2_submodules() {
+        let output = format!(
+            "1 .M SC.. 160000 160000 160000 {} {} vendor/lib\n\
+             1 .M S.M. 160000 160000 160000 {} {} vendor/dirty\n\
+             1 .M N... 100644 100644 100644 {} {} vendor.rs\n\
+             u UU SC.. 160000 160000 160000 160000 {} {} {} vendor/conflicted\n",
+            OID, OID, OID, OID, OID, OID, OID, OID, OID
+        );
+        let files = parse_porcelain_v2(&output);
+        let summary: Vec<(&str, &str, bool)> =
+            files.iter().map(|f| (f.status.as_str(), f.path.as_str(), f.is_submodule)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("M", "vendor/lib", true),
+                ("M", "vendor/dirty", true),
+                ("M", "vendor.rs", false),
+                ("UU", "vendor/conflicted", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_submodule_summary_counts() {
+        let summary = "* vendor/lib 33409f4...52d0664 (2):\n  > c\n  > b\n\n\
+                       * libs/with space 0000000...1a2b3c4 (14):\n  > init\n\n\
+                       * vendor/old 52d0664...33409f4 (rewind):\n";
+        assert_eq!(
+            parse_submodule_summary(summary),
+            vec![("vendor/lib".to_string(), 2), ("libs/with space".to_string(), 14)]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Filesystem watcher for git cache invalidation
+//!
+//! Watches the `.git` directory (inotify on Linux, FSEvents on macOS) and
+//! raises a `needs_refresh` flag when the index, HEAD or refs change, so
+//! commits, checkouts and staging show up without waiting for the refresh
+//! timer. Built only with the `fs-watch` feature; otherwise the flag is
+//! never set and the timer alone drives refreshes.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Watches a repository's git directory and flags when a refresh is needed
+pub struct GitWatcher {
+    needs_refresh: Arc<AtomicBool>,
+    /// Held for the watch's lifetime; dropping it stops the thread
+    #[cfg(feature = "fs-watch")]
+    _thread: Option<imp::WatcherThread>,
+}
+
+impl GitWatcher {
+    /// Start watching the git directory for `dir`, notifying `wake` whenever
+    /// the flag is raised
+    ///
+    /// Never fails: outside a repo, or if the platform watcher can't be
+    /// created, the flag simply stays unset.
+    pub fn start(dir: &Path, wake: Arc<Notify>) -> Self {
+        let needs_refresh = Arc::new(AtomicBool::new(false));
+
+        #[cfg(feature = "fs-watch")]
+        let thread = find_git_dir(dir)
+            .and_then(|git_dir| imp::WatcherThread::spawn(&git_dir, needs_refresh.clone(), wake));
+        #[cfg(not(feature = "fs-watch"))]
+        let _ = (dir, wake);
+
+        Self {
+            needs_refresh,
+            #[cfg(feature = "fs-watch")]
+            _thread: thread,
+        }
+    }
+
+    /// Whether a filesystem watch is actually running
+    #[cfg(test)]
+    pub fn is_active(&self) -> bool {
+        #[cfg(feature = "fs-watch")]
+        return self._thread.is_some();
+        #[cfg(not(feature = "fs-watch"))]
+        return false;
+    }
+
+    /// Consume the refresh flag (returns true once per burst of changes)
+    pub fn take_needs_refresh(&self) -> bool {
+        self.needs_refresh.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Locate the git directory for `dir`, following `.git` files used by worktrees
+#[cfg_attr(not(feature = "fs-watch"), allow(dead_code))]
+fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    for ancestor in dir.ancestors() {
+        let dot_git = ancestor.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if dot_git.is_file() {
+            let content = std::fs::read_to_string(&dot_git).ok()?;
+            let target = content.trim().strip_prefix("gitdir:")?.trim();
+            return Some(ancestor.join(target));
+        }
+    }
+    None
+}
+
+/// Whether a change to `path` (inside the git directory) affects `git status`
+///
+/// Object writes, reflogs and lock files churn constantly during git
+/// operations but are always followed by a change to a file we do track.
+#[cfg_attr(not(feature = "fs-watch"), allow(dead_code))]
+fn affects_status(git_dir: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(git_dir) else {
+        return false;
+    };
+    if path.extension().is_some_and(|ext| ext == "lock") {
+        return false;
+    }
+    match relative.components().next() {
+        Some(first) => !matches!(
+            first.as_os_str().to_str(),
+            Some("objects" | "logs" | "hooks" | "info" | "lfs")
+        ),
+        None => false,
+    }
+}
+
+#[cfg(feature = "fs-watch")]
+mod imp {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread::JoinHandle;
+
+    use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use tokio::sync::Notify;
+
+    use super::affects_status;
+
+    /// Platform watcher plus the thread that turns its events into the flag
+    pub(super) struct WatcherThread {
+        watcher: Option<RecommendedWatcher>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl WatcherThread {
+        pub(super) fn spawn(git_dir: &Path, needs_refresh: Arc<AtomicBool>, wake: Arc<Notify>) -> Option<Self> {
+            let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+            let mut watcher = notify::recommended_watcher(tx).ok()?;
+            watcher.watch(git_dir, RecursiveMode::Recursive).ok()?;
+
+            let git_dir = git_dir.to_path_buf();
+            let thread = std::thread::spawn(move || {
+                // Ends when the watcher (and with it the sender) is dropped
+                for event in rx.into_iter().flatten() {
+                    if handle_event(&git_dir, &event, &needs_refresh) {
+                        wake.notify_one();
+                    }
+                }
+            });
+
+            Some(Self {
+                watcher: Some(watcher),
+                thread: Some(thread),
+            })
+        }
+    }
+
+    impl Drop for WatcherThread {
+        fn drop(&mut self) {
+            // Dropping the watcher closes the channel so the thread can exit
+            self.watcher.take();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    /// Set the flag if the event touches anything `git status` depends on;
+    /// returns whether it did
+    pub(super) fn handle_event(git_dir: &Path, event: &Event, needs_refresh: &AtomicBool) -> bool {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return false;
+        }
+        let affects = event.paths.iter().any(|path| affects_status(git_dir, path));
+        if affects {
+            needs_refresh.store(true, Ordering::Release);
+        }
+        affects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affects_status_filters_internal_churn() {
+        let git_dir = Path::new("/repo/.git");
+        assert!(affects_status(git_dir, Path::new("/repo/.git/index")));
+        assert!(affects_status(git_dir, Path::new("/repo/.git/HEAD")));
+        assert!(affects_status(git_dir, Path::new("/repo/.git/refs/heads/main")));
+        assert!(!affects_status(git_dir, Path::new("/repo/.git/index.lock")));
+        assert!(!affects_status(git_dir, Path::new("/repo/.git/objects/ab/cdef")));
+        assert!(!affects_status(git_dir, Path::new("/repo/.git/logs/HEAD")));
+        assert!(!affects_status(git_dir, Path::new("/repo/src/main.rs")));
+    }
+
+    #[test]
+    fn test_find_git_dir_follows_worktree_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main = tmp.path().join("main");
+        let worktree = tmp.path().join("wt");
+        std::fs::create_dir_all(main.join(".git/worktrees/wt")).unwrap();
+        std::fs::create_dir_all(worktree.join("src")).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../main/.git/worktrees/wt\n").unwrap();
+
+        assert_eq!(find_git_dir(&main), Some(main.join(".git")));
+        assert_eq!(
+            find_git_dir(&worktree.join("src")),
+            Some(worktree.join("../main/.git/worktrees/wt"))
+        );
+    }
+
+    #[cfg(feature = "fs-watch")]
+    #[test]
+    fn test_mock_event_sets_flag() {
+        use notify::event::{AccessKind, CreateKind, ModifyKind};
+        use notify::{Event, EventKind};
+
+        let git_dir = PathBuf::from("/repo/.git");
+        let flag = AtomicBool::new(false);
+
+        let access = Event::new(EventKind::Access(AccessKind::Any)).add_path(git_dir.join("index"));
+        assert!(!imp::handle_event(&git_dir, &access, &flag));
+        assert!(!flag.load(Ordering::Acquire));
+
+        let object = Event::new(EventKind::Create(CreateKind::File)).add_path(git_dir.join("objects/ab/cd"));
+        assert!(!imp::handle_event(&git_dir, &object, &flag));
+        assert!(!flag.load(Ordering::Acquire));
+
+        let index = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(git_dir.join("index"));
+        assert!(imp::handle_event(&git_dir, &index, &flag));
+        assert!(flag.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_take_needs_refresh_clears_flag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let watcher = GitWatcher::start(tmp.path(), Arc::default());
+        assert!(!watcher.is_active());
+
+        watcher.needs_refresh.store(true, Ordering::Release);
+        assert!(watcher.take_needs_refresh());
+        assert!(!watcher.take_needs_refresh());
+    }
+}
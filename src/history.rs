@@ -0,0 +1,233 @@
+//! Session history
+//!
+//! Every session appends one record to `~/.crabigator/history.jsonl` when it
+//! exits: when and where it ran, prompt/completion counts, tool usage, and the
+//! files git showed as modified. `crabigator summary` reads these back to
+//! report on a day's work. One JSON object per line keeps appends cheap and
+//! lets a half-written line from a crash be skipped instead of losing the file.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::platforms::PlatformStats;
+
+/// One finished session
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub platform: String,
+    pub cwd: String,
+    /// Name given with `crabigator tag --name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+    /// Unix timestamps
+    pub started_at: f64,
+    pub ended_at: f64,
+    pub prompts: u32,
+    pub completions: u32,
+    #[serde(default)]
+    pub thinking_seconds: u64,
+    /// Context compactions
+    #[serde(default)]
+    pub compressions: u32,
+    /// Calls per tool name
+    #[serde(default)]
+    pub tools: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// Paths git listed as changed when the session ended
+    #[serde(default)]
+    pub files_modified: Vec<String>,
+    /// Counts carried over from the session this one resumed (and that one's
+    /// predecessors); the counts above are this session's own
+    #[serde(default, skip_serializing_if = "CarriedCounts::is_empty")]
+    pub carried: CarriedCounts,
+}
+
+/// What a resumed session started from, so a chain of resumes (A, then B
+/// resuming A, then C resuming B) still counts A's work in C
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CarriedCounts {
+    #[serde(default)]
+    pub prompts: u32,
+    #[serde(default)]
+    pub completions: u32,
+    #[serde(default)]
+    pub compressions: u32,
+    #[serde(default)]
+    pub tools: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+impl CarriedCounts {
+    /// The counts of `baseline` this record keeps
+    pub fn from_stats(baseline: &PlatformStats) -> Self {
+        Self {
+            prompts: baseline.prompts,
+            completions: baseline.completions,
+            compressions: baseline.compressions,
+            tools: baseline.tools.iter().map(|(tool, count)| (tool.clone(), *count)).collect(),
+            input_tokens: baseline.input_tokens,
+            output_tokens: baseline.output_tokens,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn to_platform_stats(&self) -> PlatformStats {
+        PlatformStats {
+            prompts: self.prompts,
+            completions: self.completions,
+            compressions: self.compressions,
+            tools: self.tools.iter().map(|(tool, count)| (tool.clone(), *count)).collect(),
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            ..PlatformStats::default()
+        }
+    }
+}
+
+impl SessionRecord {
+    pub fn duration_secs(&self) -> u64 {
+        (self.ended_at - self.started_at).max(0.0) as u64
+    }
+
+    /// Local calendar date the session started on
+    pub fn start_date(&self) -> Option<NaiveDate> {
+        Local
+            .timestamp_opt(self.started_at as i64, 0)
+            .single()
+            .map(|t| t.date_naive())
+    }
+
+    /// Whether the session ran in `project` or a directory below it
+    pub fn is_in(&self, project: &Path) -> bool {
+        Path::new(&self.cwd).starts_with(project)
+    }
+
+    /// The counts a resumed session carries over: this session's own plus
+    /// what it carried itself
+    pub fn to_platform_stats(&self) -> PlatformStats {
+        let mut stats = self.carried.to_platform_stats();
+        stats.prompts += self.prompts;
+        stats.completions += self.completions;
+        stats.compressions += self.compressions;
+        stats.input_tokens += self.input_tokens;
+        stats.output_tokens += self.output_tokens;
+        for (tool, count) in &self.tools {
+            *stats.tools.entry(tool.clone()).or_default() += count;
+        }
+        stats
+    }
+}
+
+/// Append-only session log
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// The store at `~/.crabigator/history.jsonl`
+    pub fn open_default() -> Self {
+        Self::at_path(Config::config_dir().join("history.jsonl"))
+    }
+
+    pub fn at_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, record: &SessionRecord) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// All records, oldest first; unreadable lines are skipped
+    pub fn load(&self) -> Result<Vec<SessionRecord>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// The most recent record of `session_id`
+    pub fn find(&self, session_id: &str) -> Result<Option<SessionRecord>> {
+        Ok(self.load()?.into_iter().rev().find(|r| r.session_id == session_id))
+    }
+
+    /// Sessions that started on `date` (local time) in `project` or below it
+    pub fn sessions_on(&self, date: NaiveDate, project: &Path) -> Result<Vec<SessionRecord>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|r| r.start_date() == Some(date) && r.is_in(project))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::at_path(dir.path().join("history.jsonl"));
+        assert!(store.load().unwrap().is_empty());
+
+        let noon = Local.with_ymd_and_hms(2026, 3, 14, 12, 0, 0).unwrap().timestamp() as f64;
+        let record = |cwd: &str, started_at: f64| SessionRecord {
+            session_id: format!("s{}", started_at),
+            cwd: cwd.to_string(),
+            started_at,
+            ended_at: started_at + 600.0,
+            ..Default::default()
+        };
+        store.append(&record("/work/app", noon)).unwrap();
+        store.append(&record("/work/app/server", noon + 60.0)).unwrap();
+        store.append(&record("/work/other", noon)).unwrap();
+        store.append(&record("/work/app", noon + 86_400.0)).unwrap();
+        // A line cut short by a crash doesn't hide the rest
+        fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("history.jsonl"))
+            .unwrap()
+            .write_all(b"{\"session_id\":\"tru")
+            .unwrap();
+
+        assert_eq!(store.load().unwrap().len(), 4);
+        let date = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let sessions = store.sessions_on(date, Path::new("/work/app")).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].duration_secs(), 600);
+        assert_eq!(sessions[1].cwd, "/work/app/server");
+    }
+}
@@ -1,3 +1,7 @@
+mod heatmap;
 mod session_stats;
+mod watcher;
 
-pub use session_stats::SessionStats;
+pub use heatmap::HeatmapData;
+pub use session_stats::{load_previous_stats, previous_session_id, SessionStats};
+pub use watcher::StatsWatcher;
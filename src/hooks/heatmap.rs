@@ -0,0 +1,98 @@
+//! Per-tool activity heatmap
+//!
+//! Buckets the session's tool-call timeline by tool name and time, so the
+//! stats widget can show which tools were busy when (one row per tool).
+
+use std::collections::HashMap;
+
+use crate::platforms::ToolCall;
+use crate::ui::sparkline::bin_timestamps;
+
+/// Tool calls over a time window, ready to be bucketed
+pub struct HeatmapData<'a> {
+    calls: &'a [ToolCall],
+    start: f64,
+    end: f64,
+}
+
+impl<'a> HeatmapData<'a> {
+    pub fn new(calls: &'a [ToolCall], start: f64, end: f64) -> Self {
+        Self { calls, start, end }
+    }
+
+    /// The `n` most-called tools, busiest first (ties broken by name)
+    pub fn top_tools(&self, n: usize) -> Vec<&'a str> {
+        let mut counts: HashMap<&str, u32> = HashMap::new();
+        for call in self.calls {
+            *counts.entry(call.tool.as_str()).or_insert(0) += 1;
+        }
+        let mut tools: Vec<(&str, u32)> = counts.into_iter().collect();
+        tools.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        tools.into_iter().take(n).map(|(tool, _)| tool).collect()
+    }
+
+    /// Count calls per (tool, time bucket)
+    ///
+    /// Rows follow `top_tools(n_tools)`; each row is binned like the tools
+    /// sparkline (`n_buckets` columns spanning start..end, oldest first).
+    pub fn bucket_by_tool(&self, n_tools: usize, n_buckets: usize) -> Vec<Vec<u32>> {
+        self.top_tools(n_tools)
+            .into_iter()
+            .map(|tool| {
+                let timestamps: Vec<f64> = self
+                    .calls
+                    .iter()
+                    .filter(|call| call.tool == tool)
+                    .map(|call| call.ts)
+                    .collect();
+                bin_timestamps(&timestamps, self.start, self.end, n_buckets)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(tool: &str, ts: f64) -> ToolCall {
+        ToolCall {
+            tool: tool.to_string(),
+            ts,
+        }
+    }
+
+    #[test]
+    fn test_top_tools_orders_by_count() {
+        let calls = vec![call("Read", 1.0), call("Bash", 2.0), call("Read", 3.0), call("Edit", 4.0)];
+        let data = HeatmapData::new(&calls, 0.0, 10.0);
+        assert_eq!(data.top_tools(3), vec!["Read", "Bash", "Edit"]);
+        assert_eq!(data.top_tools(1), vec!["Read"]);
+    }
+
+    #[test]
+    fn test_bucket_by_tool() {
+        let calls = vec![
+            call("Read", 0.5),
+            call("Read", 1.5),
+            call("Read", 9.9),
+            call("Bash", 5.0),
+            call("Edit", 7.0),
+        ];
+        let data = HeatmapData::new(&calls, 0.0, 10.0);
+        let rows = data.bucket_by_tool(2, 5);
+        assert_eq!(rows, vec![vec![2, 0, 0, 0, 1], vec![0, 0, 1, 0, 0]]);
+    }
+
+    #[test]
+    fn test_bucket_by_tool_edges() {
+        let calls = vec![call("Read", -1.0), call("Read", 10.0), call("Read", 11.0)];
+        let data = HeatmapData::new(&calls, 0.0, 10.0);
+        // Out-of-window calls are dropped; `end` lands in the last bucket
+        assert_eq!(data.bucket_by_tool(4, 2), vec![vec![0, 1]]);
+        // Degenerate windows yield empty rows rather than dividing by zero
+        assert_eq!(HeatmapData::new(&calls, 5.0, 5.0).bucket_by_tool(1, 3), vec![Vec::<u32>::new()]);
+        assert_eq!(data.bucket_by_tool(1, 0), vec![Vec::<u32>::new()]);
+        assert!(HeatmapData::new(&[], 0.0, 10.0).bucket_by_tool(3, 4).is_empty());
+    }
+}
@@ -1,7 +1,58 @@
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+use crate::history::HistoryStore;
 use crate::platforms::{Platform, PlatformStats, SessionState};
 use crate::ui::sparkline::bin_timestamps;
+use super::HeatmapData;
+
+/// Session whose stats a resumed session (`--resume`/`--continue`) builds on
+pub const PREVIOUS_SESSION_ENV_VAR: &str = "CRABIGATOR_PREVIOUS_SESSION_ID";
+
+/// `CRABIGATOR_PREVIOUS_SESSION_ID`, if set and not blank
+pub fn previous_session_id() -> Option<String> {
+    let id = std::env::var(PREVIOUS_SESSION_ENV_VAR).ok()?;
+    let id = id.trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Stats of an earlier session: its hook's stats file while that still
+/// exists, otherwise the counts in its history record
+pub fn load_previous_stats(
+    platform: &dyn Platform,
+    session_id: &str,
+    history: Option<&HistoryStore>,
+) -> Option<PlatformStats> {
+    let from_file: Option<PlatformStats> = platform
+        .session_stats_path(session_id)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let record = history.and_then(|history| history.find(session_id).ok().flatten());
+    match (from_file, record) {
+        // The hook's file only has that session's own counts; what it carried
+        // from its own predecessor is in its history record
+        (Some(stats), Some(record)) => Some(add_baseline(stats, &record.carried.to_platform_stats())),
+        (Some(stats), None) => Some(stats),
+        (None, Some(record)) => Some(record.to_platform_stats()),
+        (None, None) => None,
+    }
+}
+
+/// `stats` with a previous session's counts added on
+fn add_baseline(mut stats: PlatformStats, baseline: &PlatformStats) -> PlatformStats {
+    stats.prompts += baseline.prompts;
+    stats.completions += baseline.completions;
+    stats.subagent_messages += baseline.subagent_messages;
+    stats.compressions += baseline.compressions;
+    for (tool, count) in &baseline.tools {
+        *stats.tools.entry(tool.clone()).or_default() += count;
+    }
+    if !baseline.thinking_samples.is_empty() {
+        stats.thinking_samples = [baseline.thinking_samples.as_slice(), &stats.thinking_samples].concat();
+    }
+    stats.input_tokens += baseline.input_tokens;
+    stats.output_tokens += baseline.output_tokens;
+    stats
+}
 
 #[derive(Clone, Debug)]
 pub struct SessionStats {
@@ -32,6 +83,16 @@ pub struct SessionStats {
     /// Whether the user interrupted during thinking (ESC/Ctrl+C)
     /// Cleared when platform reports a new state
     interrupted: bool,
+    /// Bracketed pastes forwarded to the PTY: (unix timestamp, byte count)
+    pub paste_events: Vec<(f64, usize)>,
+    /// Thinking time of each completed turn in seconds (from the hook's `thinking_samples`)
+    pub thinking_time_samples: Vec<u64>,
+    /// Input plus output tokens reported by the platform
+    pub total_tokens: u64,
+    /// Input is held back because the token budget ran out (until Ctrl+A u)
+    paused: bool,
+    /// Counts carried over from the session this one resumed (zero otherwise)
+    baseline: PlatformStats,
 }
 
 impl SessionStats {
@@ -55,17 +116,113 @@ impl SessionStats {
             last_compressions: 0,
             compressions_changed_at: None,
             interrupted: false,
+            paste_events: Vec::new(),
+            thinking_time_samples: Vec::new(),
+            total_tokens: 0,
+            paused: false,
+            baseline: PlatformStats::default(),
         }
     }
 
+    /// Start from a previous session's counts (prompts, completions, tools,
+    /// compressions, turn times, tokens); this session's hook counts from zero
+    /// and is added on top
+    pub fn new_resumed(previous: PlatformStats) -> Self {
+        let mut stats = Self::new();
+        let baseline = PlatformStats {
+            prompts: previous.prompts,
+            completions: previous.completions,
+            subagent_messages: previous.subagent_messages,
+            compressions: previous.compressions,
+            tools: previous.tools,
+            thinking_samples: previous.thinking_samples,
+            input_tokens: previous.input_tokens,
+            output_tokens: previous.output_tokens,
+            ..PlatformStats::default()
+        };
+        stats.platform_stats = add_baseline(PlatformStats::default(), &baseline);
+        stats.last_prompts = baseline.prompts;
+        stats.last_completions = baseline.completions;
+        stats.last_compressions = baseline.compressions;
+        stats.thinking_time_samples = baseline.thinking_samples.clone();
+        stats.total_tokens = baseline.input_tokens + baseline.output_tokens;
+        stats.baseline = baseline;
+        stats
+    }
+
+    /// Counts carried over from a resumed session
+    pub fn baseline(&self) -> &PlatformStats {
+        &self.baseline
+    }
+
     /// Mark as interrupted (called when ESC/Ctrl+C during thinking)
     pub fn set_interrupted(&mut self) {
         self.interrupted = true;
     }
 
-    /// Get the effective session state (considering interrupt override)
+    /// When this session started
+    pub fn started_at(&self) -> Instant {
+        self.session_start
+    }
+
+    /// Unix timestamp when this session started
+    pub fn started_unix(&self) -> f64 {
+        self.session_start_unix
+    }
+
+    /// Record a paste of `bytes` bytes into the prompt
+    pub fn record_paste(&mut self, bytes: usize) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.paste_events.push((now, bytes));
+    }
+
+    /// Bytes pasted over the whole session
+    pub fn total_pasted_bytes(&self) -> usize {
+        self.paste_events.iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Size of the largest single paste (0 if nothing was pasted)
+    pub fn largest_paste_bytes(&self) -> usize {
+        self.paste_events.iter().map(|(_, bytes)| *bytes).max().unwrap_or(0)
+    }
+
+    /// Whether input is held back by the token budget
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause once `total_tokens` reaches `budget`; true only on the call that
+    /// pauses, so the caller interrupts the CLI once
+    pub fn check_token_budget(&mut self, budget: Option<u64>) -> bool {
+        let exhausted = budget.is_some_and(|budget| self.total_tokens >= budget);
+        if exhausted && !self.paused {
+            self.paused = true;
+            return true;
+        }
+        false
+    }
+
+    /// Lift a budget pause (Ctrl+A u)
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    /// Share of `budget` used, capped at 100
+    pub fn budget_percent(&self, budget: u64) -> u8 {
+        if budget == 0 {
+            return 100;
+        }
+        (self.total_tokens.saturating_mul(100) / budget).min(100) as u8
+    }
+
+    /// Get the effective session state (considering pause and interrupt overrides)
     pub fn effective_state(&self) -> SessionState {
-        if self.interrupted {
+        if self.paused {
+            SessionState::Paused
+        } else if self.interrupted {
             SessionState::Interrupted
         } else {
             self.platform_stats.state
@@ -98,6 +255,52 @@ impl SessionStats {
                 .unwrap_or(0)
     }
 
+    /// Seconds since the last tool call finished, while thinking
+    ///
+    /// This is how long the current operation (e.g. a long `Bash` run) has
+    /// been going. None outside the thinking state or before any tool call.
+    pub fn elapsed_thinking_secs(&self) -> Option<u64> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.elapsed_thinking_secs_at(now)
+    }
+
+    fn elapsed_thinking_secs_at(&self, now: f64) -> Option<u64> {
+        if self.effective_state() != SessionState::Thinking {
+            return None;
+        }
+        let since = self.platform_stats.last_tool_timestamp?;
+        Some((now - since).max(0.0) as u64)
+    }
+
+    /// Mean thinking time per completed turn, None before the first one
+    pub fn avg_thinking_secs(&self) -> Option<f64> {
+        let samples = &self.thinking_time_samples;
+        if samples.is_empty() {
+            return None;
+        }
+        Some(samples.iter().sum::<u64>() as f64 / samples.len() as f64)
+    }
+
+    /// Median thinking time per completed turn, None before the first one
+    ///
+    /// Less swayed than the mean by the odd hour-long refactor.
+    pub fn median_thinking_secs(&self) -> Option<f64> {
+        let mut sorted = self.thinking_time_samples.clone();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        Some(if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) as f64 / 2.0
+        } else {
+            sorted[mid] as f64
+        })
+    }
+
     /// Refresh platform stats from the platform's data source
     pub fn refresh_platform_stats(&mut self, platform: &dyn Platform, cwd: &str) {
         if let Ok(stats) = platform.load_stats(cwd) {
@@ -105,6 +308,7 @@ impl SessionStats {
             let last_updated = stats.last_updated.unwrap_or(0.0);
             if last_updated > self.last_stats_check {
                 self.last_stats_check = last_updated;
+                let stats = add_baseline(stats, &self.baseline);
 
                 // Clear interrupted flag when any hook event occurs
                 // (e.g., user submitted new prompt, Stop event, tool use, etc.)
@@ -132,6 +336,8 @@ impl SessionStats {
                     self.compressions_changed_at = Some(now);
                 }
 
+                self.thinking_time_samples = stats.thinking_samples.clone();
+                self.total_tokens = stats.input_tokens + stats.output_tokens;
                 self.platform_stats = stats;
             }
         }
@@ -191,13 +397,31 @@ impl SessionStats {
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs_f64();
+        let timestamps: Vec<f64> = self.platform_stats.tool_calls.iter().map(|call| call.ts).collect();
         bin_timestamps(
-            &self.platform_stats.tool_timestamps,
+            &timestamps,
             self.session_start_unix,
             now,
             num_bins,
         )
     }
+
+    /// Per-tool call counts over the session for the heatmap
+    ///
+    /// Returns up to `n_tools` (name, buckets) rows, busiest tool first.
+    pub fn tool_heatmap_bins(&self, n_tools: usize, n_buckets: usize) -> Vec<(String, Vec<u32>)> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let data = HeatmapData::new(&self.platform_stats.tool_calls, self.session_start_unix, now);
+        let names = data.top_tools(n_tools);
+        names
+            .into_iter()
+            .map(str::to_string)
+            .zip(data.bucket_by_tool(n_tools, n_buckets))
+            .collect()
+    }
 }
 
 impl Default for SessionStats {
@@ -205,3 +429,228 @@ impl Default for SessionStats {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_aggregation() {
+        let mut stats = SessionStats::new();
+        assert_eq!(stats.total_pasted_bytes(), 0);
+        assert_eq!(stats.largest_paste_bytes(), 0);
+
+        for bytes in [1200, 1843, 1257] {
+            stats.record_paste(bytes);
+        }
+        assert_eq!(stats.paste_events.len(), 3);
+        assert_eq!(stats.total_pasted_bytes(), 4300);
+        assert_eq!(stats.largest_paste_bytes(), 1843);
+    }
+
+    fn with_samples(samples: &[u64]) -> SessionStats {
+        let mut stats = SessionStats::new();
+        stats.thinking_time_samples = samples.to_vec();
+        stats
+    }
+
+    #[test]
+    fn test_thinking_average_and_median() {
+        let stats = with_samples(&[]);
+        assert_eq!(stats.avg_thinking_secs(), None);
+        assert_eq!(stats.median_thinking_secs(), None);
+
+        let stats = with_samples(&[42]);
+        assert_eq!(stats.avg_thinking_secs(), Some(42.0));
+        assert_eq!(stats.median_thinking_secs(), Some(42.0));
+
+        // Odd count: the middle sample, whatever the order
+        let stats = with_samples(&[90, 10, 30]);
+        assert_eq!(stats.avg_thinking_secs(), Some(130.0 / 3.0));
+        assert_eq!(stats.median_thinking_secs(), Some(30.0));
+
+        // Even count: halfway between the middle two
+        let stats = with_samples(&[10, 40, 20, 35]);
+        assert_eq!(stats.avg_thinking_secs(), Some(26.25));
+        assert_eq!(stats.median_thinking_secs(), Some(27.5));
+
+        // One long outlier moves the mean, not the median
+        let stats = with_samples(&[20, 25, 30, 3600]);
+        assert_eq!(stats.avg_thinking_secs(), Some(918.75));
+        assert_eq!(stats.median_thinking_secs(), Some(27.5));
+    }
+
+    fn thinking_since_tool(timestamp: Option<f64>) -> SessionStats {
+        let mut stats = SessionStats::new();
+        stats.platform_stats.state = SessionState::Thinking;
+        stats.platform_stats.last_tool_timestamp = timestamp;
+        stats
+    }
+
+    #[test]
+    fn test_elapsed_thinking_secs() {
+        let stats = thinking_since_tool(Some(1000.0));
+        assert_eq!(stats.elapsed_thinking_secs_at(1000.4), Some(0));
+        assert_eq!(stats.elapsed_thinking_secs_at(1012.9), Some(12));
+        // Clock skew between the hook and us never goes negative
+        assert_eq!(stats.elapsed_thinking_secs_at(990.0), Some(0));
+
+        assert_eq!(thinking_since_tool(None).elapsed_thinking_secs_at(1000.0), None);
+    }
+
+    #[test]
+    fn test_elapsed_thinking_secs_only_while_thinking() {
+        let mut stats = thinking_since_tool(Some(1000.0));
+        stats.platform_stats.state = SessionState::Complete;
+        assert_eq!(stats.elapsed_thinking_secs_at(1030.0), None);
+
+        stats.platform_stats.state = SessionState::Thinking;
+        stats.set_interrupted();
+        assert_eq!(stats.elapsed_thinking_secs_at(1030.0), None);
+    }
+
+    fn previous() -> PlatformStats {
+        PlatformStats {
+            prompts: 4,
+            completions: 3,
+            compressions: 1,
+            tools: [("Read".to_string(), 5), ("Bash".to_string(), 2)].into_iter().collect(),
+            thinking_samples: vec![30, 50],
+            input_tokens: 1_000,
+            output_tokens: 500,
+            state: SessionState::Complete,
+            ..PlatformStats::default()
+        }
+    }
+
+    #[test]
+    fn test_resumed_stats_start_from_baseline() {
+        let stats = SessionStats::new_resumed(previous());
+        assert_eq!(stats.platform_stats.prompts, 4);
+        assert_eq!(stats.platform_stats.completions, 3);
+        assert_eq!(stats.platform_stats.tools["Read"], 5);
+        assert_eq!(stats.thinking_time_samples, vec![30, 50]);
+        assert_eq!(stats.total_tokens, 1_500);
+        // The previous session's state doesn't carry over
+        assert_eq!(stats.effective_state(), SessionState::Ready);
+        assert_eq!(stats.baseline().prompts, 4);
+    }
+
+    #[test]
+    fn test_hook_counts_add_to_baseline() {
+        let current = PlatformStats {
+            prompts: 2,
+            completions: 1,
+            tools: [("Read".to_string(), 1), ("Edit".to_string(), 3)].into_iter().collect(),
+            thinking_samples: vec![10],
+            output_tokens: 20,
+            ..PlatformStats::default()
+        };
+        let merged = add_baseline(current, &SessionStats::new_resumed(previous()).baseline);
+        assert_eq!((merged.prompts, merged.completions, merged.compressions), (6, 4, 1));
+        assert_eq!(merged.tools["Read"], 6);
+        assert_eq!(merged.tools["Bash"], 2);
+        assert_eq!(merged.tools["Edit"], 3);
+        assert_eq!(merged.thinking_samples, vec![30, 50, 10]);
+        assert_eq!(merged.input_tokens + merged.output_tokens, 1_520);
+
+        // A fresh session has nothing to add
+        let current = PlatformStats { prompts: 2, ..PlatformStats::default() };
+        let fresh = add_baseline(current, SessionStats::new().baseline());
+        assert_eq!(fresh.prompts, 2);
+    }
+
+    #[test]
+    fn test_previous_stats_fall_back_to_history() {
+        use crate::history::SessionRecord;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::at_path(dir.path().join("history.jsonl"));
+        store
+            .append(&SessionRecord { session_id: "abc123".to_string(), prompts: 7, ..SessionRecord::default() })
+            .unwrap();
+        // Codex keeps no per-session stats file, so only the history has it
+        let platform = crate::platforms::platform_for(crate::platforms::PlatformKind::Codex);
+
+        let stats = load_previous_stats(platform.as_ref(), "abc123", Some(&store)).unwrap();
+        assert_eq!(stats.prompts, 7);
+        assert!(load_previous_stats(platform.as_ref(), "missing", Some(&store)).is_none());
+        assert!(load_previous_stats(platform.as_ref(), "abc123", None).is_none());
+    }
+
+    #[test]
+    fn test_resume_chain_keeps_earlier_sessions() {
+        use crate::history::{CarriedCounts, SessionRecord};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::at_path(dir.path().join("history.jsonl"));
+        let platform = crate::platforms::platform_for(crate::platforms::PlatformKind::Codex);
+        let tools = |n| [("Bash".to_string(), n)].into_iter().collect();
+        store
+            .append(&SessionRecord {
+                session_id: "a".into(),
+                prompts: 3,
+                compressions: 1,
+                tools: tools(2),
+                input_tokens: 1_000,
+                output_tokens: 100,
+                ..Default::default()
+            })
+            .unwrap();
+
+        // B resumes A and records only its own counts, with A's carried
+        let resumed = SessionStats::new_resumed(load_previous_stats(platform.as_ref(), "a", Some(&store)).unwrap());
+        store
+            .append(&SessionRecord {
+                session_id: "b".into(),
+                prompts: 2,
+                compressions: 2,
+                tools: tools(1),
+                input_tokens: 500,
+                output_tokens: 50,
+                carried: CarriedCounts::from_stats(resumed.baseline()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // C resuming B starts from A and B together
+        let stats = load_previous_stats(platform.as_ref(), "b", Some(&store)).unwrap();
+        assert_eq!(stats.prompts, 5);
+        assert_eq!(stats.tools["Bash"], 3);
+        assert_eq!(stats.compressions, 3);
+        assert_eq!((stats.input_tokens, stats.output_tokens), (1_500, 150));
+        assert_eq!(SessionStats::new_resumed(stats).total_tokens, 1_650);
+    }
+
+    #[test]
+    fn test_token_budget_pauses_once() {
+        let mut stats = SessionStats::new();
+        stats.platform_stats.state = SessionState::Thinking;
+        stats.total_tokens = 999;
+        assert!(!stats.check_token_budget(Some(1_000)));
+        assert!(!stats.check_token_budget(None));
+
+        stats.total_tokens = 1_000;
+        assert!(stats.check_token_budget(Some(1_000)));
+        assert_eq!(stats.effective_state(), SessionState::Paused);
+        // Already paused: no second interrupt
+        assert!(!stats.check_token_budget(Some(1_000)));
+
+        stats.unpause();
+        assert_eq!(stats.effective_state(), SessionState::Thinking);
+        assert!(!stats.check_token_budget(None));
+    }
+
+    #[test]
+    fn test_budget_percent() {
+        let mut stats = SessionStats::new();
+        assert_eq!(stats.budget_percent(1_000), 0);
+        stats.total_tokens = 799;
+        assert_eq!(stats.budget_percent(1_000), 79);
+        stats.total_tokens = 800;
+        assert_eq!(stats.budget_percent(1_000), 80);
+        stats.total_tokens = 5_000;
+        assert_eq!(stats.budget_percent(1_000), 100);
+        assert_eq!(stats.budget_percent(0), 100);
+    }
+}
@@ -0,0 +1,141 @@
+//! Filesystem watcher for the hook stats file
+//!
+//! The hook script rewrites the platform's stats JSON on every event. Rather
+//! than wait for the next poll, watch it (inotify on Linux, FSEvents on
+//! macOS) so a Stop or prompt shows up in the status bar at once. The parent
+//! directory is watched, since the file may not exist yet and is replaced by
+//! rename. Built only with the `fs-watch` feature; otherwise nothing fires
+//! and the refresh timer alone drives stats.
+
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Signals when the stats file changes
+#[derive(Default)]
+pub struct StatsWatcher {
+    rx: Option<Receiver<()>>,
+    #[cfg(feature = "fs-watch")]
+    _watcher: Option<notify::RecommendedWatcher>,
+}
+
+impl StatsWatcher {
+    /// Start watching `stats_path`, notifying `wake` on every change so an
+    /// idle app loop doesn't wait out its poll timeout
+    ///
+    /// Never fails: if the platform watcher can't be created, it simply
+    /// never fires.
+    pub fn start(stats_path: &Path, wake: Arc<Notify>) -> Self {
+        #[cfg(feature = "fs-watch")]
+        return imp::spawn(stats_path, wake)
+            .map(|(watcher, rx)| Self {
+                rx: Some(rx),
+                _watcher: Some(watcher),
+            })
+            .unwrap_or_default();
+        #[cfg(not(feature = "fs-watch"))]
+        {
+            let _ = (stats_path, wake);
+            Self::default()
+        }
+    }
+
+    /// Whether a filesystem watch is actually running
+    pub fn is_active(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Whether the file changed since the last call (drains the backlog, so a
+    /// burst of writes is one refresh)
+    pub fn try_recv(&self) -> bool {
+        let Some(rx) = &self.rx else {
+            return false;
+        };
+        let mut changed = false;
+        while rx.try_recv().is_ok() {
+            changed = true;
+        }
+        changed
+    }
+}
+
+#[cfg(feature = "fs-watch")]
+mod imp {
+    use std::path::Path;
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::Arc;
+
+    use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use tokio::sync::Notify;
+
+    pub(super) fn spawn(stats_path: &Path, wake: Arc<Notify>) -> Option<(RecommendedWatcher, Receiver<()>)> {
+        let dir = stats_path.parent()?;
+        let stats_path = stats_path.to_path_buf();
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else { return };
+            if !matches!(event.kind, EventKind::Access(_)) && event.paths.contains(&stats_path) {
+                let _ = tx.send(());
+                wake.notify_one();
+            }
+        })
+        .ok()?;
+        watcher.watch(dir, RecursiveMode::NonRecursive).ok()?;
+        Some((watcher, rx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inactive_watcher_never_fires() {
+        let watcher = StatsWatcher::default();
+        assert!(!watcher.is_active());
+        assert!(!watcher.try_recv());
+    }
+
+    #[cfg(feature = "fs-watch")]
+    #[test]
+    fn test_stats_write_fires() {
+        use std::time::{Duration, Instant};
+
+        let dir = tempfile::tempdir().unwrap();
+        let stats_path = dir.path().join("crabigator-stats-test.json");
+        let wake = Arc::new(Notify::new());
+        let watcher = StatsWatcher::start(&stats_path, wake.clone());
+        assert!(watcher.is_active());
+
+        let wait_for_event = || {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while Instant::now() < deadline {
+                if watcher.try_recv() {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            false
+        };
+
+        // Replaced by rename, as the hook script writes it
+        let tmp_path = dir.path().join("crabigator-stats-test.tmp");
+        std::fs::write(&tmp_path, r#"{"prompts": 1}"#).unwrap();
+        std::fs::rename(&tmp_path, &stats_path).unwrap();
+        assert!(wait_for_event());
+        // The app loop was woken too
+        assert!(futures_util::FutureExt::now_or_never(wake.notified()).is_some());
+
+        std::fs::write(&stats_path, r#"{"prompts": 2}"#).unwrap();
+        assert!(wait_for_event());
+
+        // Other files in the directory don't count
+        std::thread::sleep(Duration::from_millis(200));
+        watcher.try_recv();
+        std::fs::write(dir.path().join("unrelated.json"), "{}").unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!watcher.try_recv());
+    }
+}
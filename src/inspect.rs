@@ -1,18 +1,25 @@
 //! Inspect command implementation
 //!
 //! Discovers and displays state from other running crabigator instances.
+//! With `--ssh USER@HOST` the mirror files are found and read on that
+//! machine through `ssh` (`RemoteDiscovery`), for sessions run over SSH.
 
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, metadata};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{Local, TimeZone};
 use serde_json::Value;
 
-use crate::platforms::PlatformStats;
+use crate::capture::read_capture_file;
+use crate::platforms::{HookEvent, PlatformKind, PlatformStats};
+use crate::tags::TagStore;
 use crate::terminal::escape::{ansi, CLEAR_SCREEN_HOME, DIM, RESET};
+use crate::ui::utils::format_duration;
 
 /// Get file status with size info
 fn get_file_status(path: &str) -> String {
@@ -30,6 +37,11 @@ fn get_file_status(path: &str) -> String {
     }
 }
 
+/// Count newline-terminated lines in a capture
+fn bytecount_lines(contents: &[u8]) -> usize {
+    contents.iter().filter(|&&b| b == b'\n').count()
+}
+
 /// Format file size human-readable
 fn format_size(bytes: u64) -> String {
     if bytes < 1024 {
@@ -42,17 +54,35 @@ fn format_size(bytes: u64) -> String {
 }
 
 /// Run the inspect command
-pub fn run_inspect(dir_filter: Option<String>, watch: bool, raw: bool, history: bool) -> Result<()> {
+pub fn run_inspect(
+    dir_filter: Option<String>,
+    watch: bool,
+    raw: bool,
+    history: bool,
+    summary: bool,
+    diff: bool,
+    ssh: Option<String>,
+) -> Result<()> {
+    // Previous publish per mirror file, for --diff
+    let mut previous: HashMap<PathBuf, Value> = HashMap::new();
+
     loop {
-        let instances = discover_instances(&dir_filter)?;
+        let instances = match &ssh {
+            Some(target) => RemoteDiscovery::new().discover_remote(target, dir_filter.as_deref())?,
+            None => discover_instances(&dir_filter)?,
+        };
 
-        if raw {
+        if diff {
+            print_diffs(&instances, &mut previous);
+        } else if raw {
             for (path, data) in &instances {
                 println!("--- {} ---", path.display());
                 println!("{}", serde_json::to_string_pretty(data)?);
             }
         } else if history {
             print_history(&instances)?;
+        } else if summary {
+            print_summary(&aggregate_instances(&instances));
         } else {
             print_pretty(&instances)?;
         }
@@ -61,14 +91,121 @@ pub fn run_inspect(dir_filter: Option<String>, watch: bool, raw: bool, history:
             break;
         }
 
-        // Clear screen and wait before next update
-        print!("{CLEAR_SCREEN_HOME}");
+        // Clear screen and wait before next update (diffs scroll like a log instead)
+        if !diff {
+            print!("{CLEAR_SCREEN_HOME}");
+        }
         thread::sleep(Duration::from_millis(500));
     }
 
     Ok(())
 }
 
+/// Sessions whose mirror hasn't been published for this long are stale
+const STALE_AFTER_SECS: f64 = 10.0;
+
+/// Run the status command
+///
+/// Lists every mirrored session and exits with code 1 when none of the live
+/// ones is running in the current directory (for use in shell scripts).
+pub fn run_status(json: bool) -> Result<()> {
+    let instances = discover_instances(&None)?;
+    let now = now_unix();
+    let cwd = std::env::current_dir()?.to_string_lossy().into_owned();
+
+    if json {
+        let entries: Vec<Value> = instances.iter().map(|(_, data)| status_entry(data)).collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        print_status(&instances);
+    }
+
+    let code = status_exit_code(&instances, &cwd, now);
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+fn now_unix() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Whether a session's mirror was published within the last 10 seconds
+pub fn is_alive(session: &Value) -> bool {
+    is_alive_at(session, now_unix())
+}
+
+fn is_alive_at(session: &Value, now: f64) -> bool {
+    session
+        .get("last_updated")
+        .and_then(|v| v.as_f64())
+        .is_some_and(|ts| now - ts < STALE_AFTER_SECS)
+}
+
+/// 0 if a live session is running in `cwd`, 1 otherwise
+fn status_exit_code(instances: &[(PathBuf, Value)], cwd: &str, now: f64) -> i32 {
+    let matched = instances.iter().any(|(_, data)| {
+        is_alive_at(data, now) && data.get("cwd").and_then(|v| v.as_str()) == Some(cwd)
+    });
+    if matched {
+        0
+    } else {
+        1
+    }
+}
+
+/// Summary of one session for `status --json`
+fn status_entry(data: &Value) -> Value {
+    serde_json::json!({
+        "session_id": data.get("session_id").cloned().unwrap_or(Value::Null),
+        "pid": data.get("pid").cloned().unwrap_or(Value::Null),
+        "cwd": data.get("cwd").cloned().unwrap_or(Value::Null),
+        "platform": data.get("platform").cloned().unwrap_or(Value::Null),
+        "uptime_secs": data.pointer("/launch_timing/uptime_secs").cloned().unwrap_or(Value::Null),
+        "state": data.pointer("/widgets/stats/data/state").cloned().unwrap_or(Value::Null),
+        "alive": is_alive(data),
+    })
+}
+
+fn print_status(instances: &[(PathBuf, Value)]) {
+    if instances.is_empty() {
+        println!("No active crabigator instances found.");
+        return;
+    }
+
+    for (_path, data) in instances {
+        let str_field = |pointer: &str| data.pointer(pointer).and_then(|v| v.as_str()).unwrap_or("unknown");
+        let pid = data
+            .get("pid")
+            .and_then(|v| v.as_u64())
+            .map(|pid| pid.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        let uptime = data
+            .pointer("/launch_timing/uptime_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        let line = format!(
+            "{}  pid {}  {}  {}  {}  {}",
+            str_field("/session_id"),
+            pid,
+            str_field("/platform"),
+            str_field("/widgets/stats/data/state"),
+            format_duration(uptime),
+            str_field("/cwd"),
+        );
+        if is_alive(data) {
+            println!("{}", line);
+        } else {
+            println!("{DIM}{} (stale){RESET}", line);
+        }
+    }
+}
+
 /// Load stats file for a session to get event history
 fn load_stats_for_session(session_id: &str) -> Option<PlatformStats> {
     let stats_path = format!("/tmp/crabigator-stats-{}.json", session_id);
@@ -90,6 +227,51 @@ fn format_timestamp(ts: f64) -> String {
     }
 }
 
+/// `key=value` pairs of an event's details, sorted by key; strings unquoted
+fn format_details(event: &HookEvent) -> String {
+    let Some(details) = &event.details else {
+        return String::new();
+    };
+    let mut pairs: Vec<_> = details.iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(b.0));
+    pairs
+        .into_iter()
+        .map(|(key, value)| match value.as_str() {
+            Some(s) => format!("{}={}", key, s),
+            None => format!("{}={}", key, value),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Rows of the `--history` table, oldest event first: time, event, state
+/// transition and details
+fn history_table(events: &[HookEvent]) -> Vec<String> {
+    let mut events: Vec<&HookEvent> = events.iter().collect();
+    events.sort_by(|a, b| a.ts.total_cmp(&b.ts));
+
+    let mut rows = vec![
+        format!("{:<13} {:<20} {:<25} Details", "Time", "Event", "State"),
+        "-".repeat(78),
+    ];
+    rows.extend(events.into_iter().map(|event| {
+        let transition = match (event.state_before.as_str(), event.state_after.as_str()) {
+            (before, "") => before.to_string(),
+            (before, after) if before == after => before.to_string(),
+            (before, after) => format!("{} → {}", before, after),
+        };
+        let row = format!(
+            "{:<13} {:<20} {:<25} {}",
+            format_timestamp(event.ts),
+            event.event,
+            transition,
+            format_details(event)
+        );
+        row.trim_end().to_string()
+    }));
+    rows
+}
+
 /// Print event history for debugging
 fn print_history(instances: &[(PathBuf, Value)]) -> Result<()> {
     if instances.is_empty() {
@@ -104,7 +286,7 @@ fn print_history(instances: &[(PathBuf, Value)]) -> Result<()> {
             .unwrap_or("unknown");
         let cwd = data.get("cwd").and_then(|v| v.as_str()).unwrap_or("unknown");
 
-        println!("\n=== Session {} ===", session_id);
+        println!("\n=== {} ===", session_heading(data));
         println!("Directory: {}", cwd);
 
         // Load stats file to get event history
@@ -116,32 +298,8 @@ fn print_history(instances: &[(PathBuf, Value)]) -> Result<()> {
                 println!("\n[Event History] {DIM}(empty - hooks may need reinstalling){RESET}");
             } else {
                 println!("\n[Event History] ({} events)", stats.event_history.len());
-                println!(
-                    "  {:<15} {:<20} {:<12} Details",
-                    "Time", "Event", "State Before"
-                );
-                println!("  {}", "-".repeat(70));
-
-                for event in &stats.event_history {
-                    let time_str = format_timestamp(event.ts);
-                    let details_str = event
-                        .details
-                        .as_ref()
-                        .map(|d| {
-                            d.iter()
-                                .map(|(k, v)| format!("{}={}", k, v))
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        })
-                        .unwrap_or_default();
-
-                    println!(
-                        "  {:<15} {:<20} {:<12} {}",
-                        time_str,
-                        event.event,
-                        event.state_before,
-                        details_str
-                    );
+                for row in history_table(&stats.event_history) {
+                    println!("  {}", row);
                 }
             }
         } else {
@@ -170,7 +328,373 @@ fn print_history(instances: &[(PathBuf, Value)]) -> Result<()> {
     Ok(())
 }
 
-fn discover_instances(dir_filter: &Option<String>) -> Result<Vec<(PathBuf, Value)>> {
+/// Totals for one group of instances
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AggregateTotals {
+    pub instances: usize,
+    pub prompts: u64,
+    pub completions: u64,
+    pub tool_calls: u64,
+    pub thinking_seconds: u64,
+}
+
+impl AggregateTotals {
+    fn add(&mut self, stats: &Value) {
+        let field = |name: &str| stats.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+        self.instances += 1;
+        self.prompts += field("prompts");
+        self.completions += field("completions");
+        self.tool_calls += field("tools");
+        self.thinking_seconds += field("thinking_seconds");
+    }
+}
+
+/// Instance with the highest tool call rate
+#[derive(Clone, Debug, PartialEq)]
+pub struct BusiestInstance {
+    pub session_id: String,
+    pub cwd: String,
+    pub tool_calls: u64,
+    pub tools_per_minute: f64,
+}
+
+/// Aggregate statistics across all discovered instances
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AggregateSummary {
+    pub totals: AggregateTotals,
+    /// Per-platform totals, keyed by platform name ("claude", "codex", ...)
+    pub by_platform: BTreeMap<String, AggregateTotals>,
+    pub busiest: Option<BusiestInstance>,
+    pub languages: LanguageAggregate,
+}
+
+/// Change counts per language across instances, most changed first
+pub type LanguageAggregate = Vec<(String, usize)>;
+
+/// Sum the semantic changes in each instance's `by_language` list, merged by
+/// language name
+pub fn aggregate_languages(instances: &[(PathBuf, Value)]) -> LanguageAggregate {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (_path, data) in instances {
+        let Some(languages) = data.pointer("/widgets/changes/data/by_language").and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for entry in languages {
+            let Some(language) = entry.get("language").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let changes = entry.get("changes").and_then(|v| v.as_array()).map_or(0, |c| c.len());
+            if changes > 0 {
+                *counts.entry(language.to_string()).or_default() += changes;
+            }
+        }
+    }
+
+    let mut ranked: LanguageAggregate = counts.into_iter().collect();
+    // Stable sort keeps ties alphabetical
+    ranked.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    ranked
+}
+
+/// Sum stats across mirrored instances
+pub fn aggregate_instances(instances: &[(PathBuf, Value)]) -> AggregateSummary {
+    let mut summary = AggregateSummary::default();
+
+    for (_path, data) in instances {
+        let stats = data.pointer("/widgets/stats/data").unwrap_or(&Value::Null);
+        let platform = data
+            .get("platform")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        summary.totals.add(stats);
+        summary.by_platform.entry(platform).or_default().add(stats);
+
+        // Rate over uptime; skip instances too young to give a meaningful rate
+        let tool_calls = stats.get("tools").and_then(|v| v.as_u64()).unwrap_or(0);
+        let uptime = data
+            .pointer("/launch_timing/uptime_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        if tool_calls == 0 || uptime == 0 {
+            continue;
+        }
+        let tools_per_minute = tool_calls as f64 * 60.0 / uptime as f64;
+        if summary
+            .busiest
+            .as_ref()
+            .is_none_or(|b| tools_per_minute > b.tools_per_minute)
+        {
+            summary.busiest = Some(BusiestInstance {
+                session_id: data
+                    .get("session_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                cwd: data.get("cwd").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+                tool_calls,
+                tools_per_minute,
+            });
+        }
+    }
+
+    summary.languages = aggregate_languages(instances);
+    summary
+}
+
+/// Width of the longest bar in the language chart, in cells
+const LANGUAGE_BAR_WIDTH: usize = 24;
+
+/// Horizontal bar for `count` scaled so `max` fills `width` cells, in half-cell
+/// steps (`█` full, `▌` half); any nonzero count gets at least a half cell
+fn language_bar(count: usize, max: usize, width: usize) -> String {
+    if count == 0 || max == 0 {
+        return String::new();
+    }
+    let halves = ((count * width * 2 + max / 2) / max).max(1);
+    let mut bar = "█".repeat(halves / 2);
+    if halves % 2 == 1 {
+        bar.push('▌');
+    }
+    bar
+}
+
+fn print_summary(summary: &AggregateSummary) {
+    if summary.totals.instances == 0 {
+        println!("No active crabigator instances found.");
+        return;
+    }
+
+    let totals = &summary.totals;
+    println!("\n=== {} active instance{} ===", totals.instances, if totals.instances == 1 { "" } else { "s" });
+    println!("Prompts:     {}", totals.prompts);
+    println!("Completions: {}", totals.completions);
+    println!("Tool calls:  {}", totals.tool_calls);
+    println!("Thinking:    {}", format_duration(totals.thinking_seconds));
+
+    println!("\n[By Platform]");
+    for (platform, t) in &summary.by_platform {
+        let name = PlatformKind::parse(platform)
+            .map(|kind| kind.display_name())
+            .unwrap_or(platform);
+        println!(
+            "  {:<8} {} instance{}  {DIM}prompts{RESET} {}  {DIM}completions{RESET} {}  {DIM}tools{RESET} {}  {DIM}thinking{RESET} {}",
+            name,
+            t.instances,
+            if t.instances == 1 { " " } else { "s" },
+            t.prompts,
+            t.completions,
+            t.tool_calls,
+            format_duration(t.thinking_seconds),
+        );
+    }
+
+    if let Some(busiest) = &summary.busiest {
+        println!("\n[Busiest]");
+        println!(
+            "  {} {DIM}({}){RESET}  {}{:.1} tools/min{RESET} {DIM}({} total){RESET}",
+            busiest.session_id,
+            busiest.cwd,
+            ansi::YELLOW,
+            busiest.tools_per_minute,
+            busiest.tool_calls,
+        );
+    }
+
+    if !summary.languages.is_empty() {
+        println!("\n[Changes by Language]");
+        let max = summary.languages[0].1;
+        let name_width = summary.languages.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+        for (language, count) in &summary.languages {
+            println!(
+                "  {:<name_width$} {}{:<LANGUAGE_BAR_WIDTH$}{RESET} {} change{}",
+                language,
+                ansi::GREEN,
+                language_bar(*count, max, LANGUAGE_BAR_WIDTH),
+                count,
+                if *count == 1 { "" } else { "s" },
+            );
+        }
+    }
+    println!();
+}
+
+/// A single difference between two mirror publishes
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateDiff {
+    Added { field: String, value: Value },
+    Removed { field: String, value: Value },
+    Changed { field: String, old_value: Value, new_value: Value },
+}
+
+impl std::fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateDiff::Added { field, value } => write!(f, "{}+{RESET} {}: {}", ansi::GREEN, field, value),
+            StateDiff::Removed { field, value } => write!(f, "{}-{RESET} {}: {}", ansi::RED, field, value),
+            StateDiff::Changed { field, old_value, new_value } => {
+                write!(f, "{}~{RESET} {}: {} → {}", ansi::YELLOW, field, old_value, new_value)
+            }
+        }
+    }
+}
+
+/// Compare the stats, git files and changes of two mirror publishes
+pub fn diff_mirror_states(old: &Value, new: &Value) -> Vec<StateDiff> {
+    let mut diffs = Vec::new();
+
+    // Stats: flat object, reported by bare field name ("prompts")
+    let empty = serde_json::Map::new();
+    let old_stats = old.pointer("/widgets/stats/data").and_then(|v| v.as_object()).unwrap_or(&empty);
+    let new_stats = new.pointer("/widgets/stats/data").and_then(|v| v.as_object()).unwrap_or(&empty);
+    diff_objects("", old_stats, new_stats, &mut diffs);
+
+    // Git files: matched by path, reported by index
+    let files = |state: &Value| -> Vec<Value> {
+        state
+            .pointer("/widgets/git/data/files")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+    diff_keyed_list("git.files", &files(old), &files(new), |file| file.get("path").cloned(), &mut diffs);
+
+    // Changes: flattened per language, matched by kind + name
+    let changes = |state: &Value| -> BTreeMap<String, Vec<Value>> {
+        let mut by_language = BTreeMap::new();
+        let languages = state.pointer("/widgets/changes/data/by_language").and_then(|v| v.as_array());
+        for language in languages.into_iter().flatten() {
+            let name = language.get("language").and_then(|v| v.as_str()).unwrap_or("?");
+            let entries = language.get("changes").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            by_language.insert(name.to_string(), entries);
+        }
+        by_language
+    };
+    let (old_changes, new_changes) = (changes(old), changes(new));
+    let mut languages: Vec<&String> = old_changes.keys().chain(new_changes.keys()).collect();
+    languages.sort();
+    languages.dedup();
+    for language in languages {
+        let none = Vec::new();
+        diff_keyed_list(
+            &format!("changes.{}", language),
+            old_changes.get(language).unwrap_or(&none),
+            new_changes.get(language).unwrap_or(&none),
+            |change| {
+                let kind = change.get("kind")?.as_str()?;
+                let name = change.get("name")?.as_str()?;
+                Some(Value::String(format!("{} {}", kind, name)))
+            },
+            &mut diffs,
+        );
+    }
+
+    diffs
+}
+
+/// Field-by-field diff of two JSON objects, prefixing field names with `prefix`
+fn diff_objects(
+    prefix: &str,
+    old: &serde_json::Map<String, Value>,
+    new: &serde_json::Map<String, Value>,
+    diffs: &mut Vec<StateDiff>,
+) {
+    for (key, new_value) in new {
+        let field = format!("{}{}", prefix, key);
+        match old.get(key) {
+            None => diffs.push(StateDiff::Added { field, value: new_value.clone() }),
+            Some(old_value) if old_value != new_value => diffs.push(StateDiff::Changed {
+                field,
+                old_value: old_value.clone(),
+                new_value: new_value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, old_value) in old {
+        if !new.contains_key(key) {
+            diffs.push(StateDiff::Removed { field: format!("{}{}", prefix, key), value: old_value.clone() });
+        }
+    }
+}
+
+/// Diff two lists of objects matched by `key`
+///
+/// Additions and removals report the key at the item's index; items present
+/// in both report their changed fields.
+fn diff_keyed_list(
+    name: &str,
+    old: &[Value],
+    new: &[Value],
+    key: impl Fn(&Value) -> Option<Value>,
+    diffs: &mut Vec<StateDiff>,
+) {
+    let empty = serde_json::Map::new();
+    for (index, item) in new.iter().enumerate() {
+        let Some(item_key) = key(item) else { continue };
+        let field = format!("{}[{}]", name, index);
+        match old.iter().find(|o| key(o).as_ref() == Some(&item_key)) {
+            None => diffs.push(StateDiff::Added { field, value: item_key }),
+            Some(previous) => diff_objects(
+                &format!("{}.", field),
+                previous.as_object().unwrap_or(&empty),
+                item.as_object().unwrap_or(&empty),
+                diffs,
+            ),
+        }
+    }
+    for (index, item) in old.iter().enumerate() {
+        let Some(item_key) = key(item) else { continue };
+        if !new.iter().any(|n| key(n).as_ref() == Some(&item_key)) {
+            diffs.push(StateDiff::Removed { field: format!("{}[{}]", name, index), value: item_key });
+        }
+    }
+}
+
+/// Print what changed in each instance since the previous poll
+fn print_diffs(instances: &[(PathBuf, Value)], previous: &mut HashMap<PathBuf, Value>) {
+    let time = Local::now().format("%H:%M:%S");
+    for (path, data) in instances {
+        let session_id = data
+            .get("project_name")
+            .or_else(|| data.get("session_id"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        match previous.get(path) {
+            None => println!("{DIM}{}{RESET} {} {DIM}watching{RESET}", time, session_id),
+            Some(old) => {
+                let diffs = diff_mirror_states(old, data);
+                if !diffs.is_empty() {
+                    println!("{DIM}{}{RESET} {}", time, session_id);
+                    for diff in diffs {
+                        println!("  {}", diff);
+                    }
+                }
+            }
+        }
+    }
+
+    // Forget instances that exited so a restarted session starts a fresh baseline
+    previous.retain(|path, _| instances.iter().any(|(p, _)| p == path));
+    for (path, data) in instances {
+        previous.insert(path.clone(), data.clone());
+    }
+}
+
+/// Whether a session's working directory passes the `inspect` directory filter
+///
+/// Filters with glob characters (`*`, `?`, `[`) must match the whole path,
+/// e.g. `*/packages/*`; anything else matches as a substring.
+fn matches_dir_filter(cwd: &str, filter: &str) -> bool {
+    if filter.contains(['*', '?', '[']) {
+        glob::Pattern::new(filter).is_ok_and(|pattern| pattern.matches(cwd))
+    } else {
+        cwd.contains(filter)
+    }
+}
+
+pub fn discover_instances(dir_filter: &Option<String>) -> Result<Vec<(PathBuf, Value)>> {
     let pattern = "/tmp/crabigator-*/inspect.json";
     let mut instances = vec![];
 
@@ -181,11 +705,12 @@ fn discover_instances(dir_filter: &Option<String>) -> Result<Vec<(PathBuf, Value
                 // Apply directory filter
                 if let Some(filter) = dir_filter {
                     if let Some(cwd) = data.get("cwd").and_then(|v| v.as_str()) {
-                        if !cwd.contains(filter) {
+                        if !matches_dir_filter(cwd, filter) {
                             continue;
                         }
                     }
                 }
+                let data = with_tagged_name(data, &path);
                 instances.push((path, data));
             }
         }
@@ -194,106 +719,686 @@ fn discover_instances(dir_filter: &Option<String>) -> Result<Vec<(PathBuf, Value
     Ok(instances)
 }
 
+/// The instance whose mirror was published most recently
+pub fn latest_instance(instances: &[(PathBuf, Value)]) -> Option<&Value> {
+    let updated = |v: &Value| v.get("last_updated").and_then(|t| t.as_f64()).unwrap_or(0.0);
+    instances
+        .iter()
+        .map(|(_, data)| data)
+        .max_by(|a, b| updated(a).total_cmp(&updated(b)))
+}
+
+/// Fill in `project_name` from `tags.json` next to the mirror
+///
+/// The mirror picks up a new name on its next publish, which only happens
+/// once something else changes; read the tags directly so a rename shows up
+/// straight away.
+fn with_tagged_name(mut data: Value, mirror_path: &Path) -> Value {
+    let name = mirror_path
+        .parent()
+        .and_then(TagStore::read)
+        .and_then(|tags| tags.name);
+    if let (Some(name), Some(object)) = (name, data.as_object_mut()) {
+        object.insert("project_name".to_string(), Value::String(name));
+    }
+    data
+}
+
+/// `name (session_id)` for tagged sessions, else `Session session_id`
+fn session_heading(data: &Value) -> String {
+    let session_id = data
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    match data.get("project_name").and_then(|v| v.as_str()) {
+        Some(name) => format!("{} ({})", name, session_id),
+        None => format!("Session {}", session_id),
+    }
+}
+
+/// Finds and reads mirror files on another machine over SSH
+pub struct RemoteDiscovery {
+    /// Run in place of `ssh` (a stand-in script in tests)
+    ssh_program: String,
+}
+
+/// `ssh` exits with this when the connection itself failed
+const SSH_CONNECTION_FAILED: i32 = 255;
+
+/// Prints every mirror under the remote /tmp as `\0path\0contents`
+const REMOTE_MIRROR_COMMAND: &str = "find /tmp -maxdepth 2 -path '/tmp/crabigator-*' -name 'inspect.json' \
+     -exec printf '\\0%s\\0' {} \\; -exec cat {} \\; 2>/dev/null";
+
+impl RemoteDiscovery {
+    pub fn new() -> Self {
+        Self {
+            ssh_program: "ssh".to_string(),
+        }
+    }
+
+    /// Mirrors on `ssh_target` (`USER@HOST`), keyed `USER@HOST:/tmp/...`
+    ///
+    /// One `ssh` round trip finds and prints every mirror, each as
+    /// `\0path\0contents`. Files that vanish or don't parse between listing
+    /// and reading are skipped, as with local discovery.
+    pub fn discover_remote(&self, ssh_target: &str, dir_filter: Option<&str>) -> Result<Vec<(PathBuf, Value)>> {
+        let output = self.run(ssh_target, REMOTE_MIRROR_COMMAND)?;
+
+        let mut instances = vec![];
+        let mut fields = output.split('\0').skip(1);
+        while let (Some(path), Some(content)) = (fields.next(), fields.next()) {
+            let Ok(data) = serde_json::from_str::<Value>(content) else {
+                continue;
+            };
+            if let Some(filter) = dir_filter {
+                if let Some(cwd) = data.get("cwd").and_then(|v| v.as_str()) {
+                    if !matches_dir_filter(cwd, filter) {
+                        continue;
+                    }
+                }
+            }
+            instances.push((PathBuf::from(format!("{}:{}", ssh_target, path)), data));
+        }
+        Ok(instances)
+    }
+
+    /// Run `command` through the remote shell and return its stdout
+    fn run(&self, ssh_target: &str, command: &str) -> Result<String> {
+        // BatchMode: fail instead of prompting for a password every refresh.
+        // `--` keeps a target starting with `-` from being read as an option.
+        let output = Command::new(&self.ssh_program)
+            .args(["-o", "BatchMode=yes", "--", ssh_target, command])
+            .output()?;
+        if output.status.code() == Some(SSH_CONNECTION_FAILED) {
+            bail!(
+                "ssh {} failed: {}",
+                ssh_target,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl Default for RemoteDiscovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sessions that share a git repository (or a directory, outside git)
+#[derive(Debug, PartialEq)]
+pub struct GitRootGroup {
+    /// Repository root, or the session's own directory when it isn't in one
+    pub root: PathBuf,
+    /// Indices into the instance list, in discovery order
+    pub sessions: Vec<usize>,
+}
+
+/// Nearest ancestor of `dir` (itself included) containing `.git`
+fn find_git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find(|d| d.join(".git").exists()).map(Path::to_path_buf)
+}
+
+/// Group instances by the git root of their `cwd`, ordered by root path
+///
+/// `git_root` is `find_git_root` outside tests.
+fn group_by_git_root(
+    instances: &[(PathBuf, Value)],
+    git_root: impl Fn(&Path) -> Option<PathBuf>,
+) -> Vec<GitRootGroup> {
+    let mut groups: BTreeMap<PathBuf, Vec<usize>> = BTreeMap::new();
+    for (i, (_, data)) in instances.iter().enumerate() {
+        let cwd = Path::new(data.get("cwd").and_then(|v| v.as_str()).unwrap_or("unknown"));
+        let root = git_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+        groups.entry(root).or_default().push(i);
+    }
+    groups
+        .into_iter()
+        .map(|(root, sessions)| GitRootGroup { root, sessions })
+        .collect()
+}
+
 fn print_pretty(instances: &[(PathBuf, Value)]) -> Result<()> {
     if instances.is_empty() {
         println!("No active crabigator instances found.");
         return Ok(());
     }
 
-    for (path, data) in instances {
-        let session_id = data
-            .get("session_id")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown");
-        let cwd = data.get("cwd").and_then(|v| v.as_str()).unwrap_or("unknown");
+    for group in group_by_git_root(instances, find_git_root) {
+        // Only worth a heading when several sessions share the repository
+        if group.sessions.len() > 1 {
+            println!("\n##### {} ({} sessions) #####", group.root.display(), group.sessions.len());
+        }
+        for &i in &group.sessions {
+            let (path, data) = &instances[i];
+            print_instance(path, data);
+        }
+    }
 
-        println!("\n=== Session {} ===", session_id);
-        println!("Directory: {}", cwd);
-        println!("Mirror: {}", path.display());
-
-        // Show launch timing
-        if let Some(timing) = data.get("launch_timing") {
-            let uptime = timing.get("uptime_secs").and_then(|v| v.as_u64()).unwrap_or(0);
-            let git_ms = timing.get("git_time_ms").and_then(|v| v.as_u64());
-            let diff_ms = timing.get("diff_time_ms").and_then(|v| v.as_u64());
-
-            print!("Uptime: {}s", uptime);
-
-            match (git_ms, diff_ms) {
-                (Some(g), Some(d)) => {
-                    let total = g + d;
-                    let color = if total > 1000 {
-                        ansi::YELLOW
-                    } else {
-                        ansi::GREEN
-                    };
-                    println!(" | Initial load: {color}{}ms{RESET} (git: {}ms, diff: {}ms)", total, g, d);
-                }
-                _ => println!(" | Initial load: {DIM}pending...{RESET}"),
+    Ok(())
+}
+
+fn print_instance(path: &Path, data: &Value) {
+    let cwd = data.get("cwd").and_then(|v| v.as_str()).unwrap_or("unknown");
+
+    println!("\n=== {} ===", session_heading(data));
+    println!("Directory: {}", cwd);
+    println!("Mirror: {}", path.display());
+
+    // Show launch timing
+    if let Some(timing) = data.get("launch_timing") {
+        let uptime = timing.get("uptime_secs").and_then(|v| v.as_u64()).unwrap_or(0);
+        let git_ms = timing.get("git_time_ms").and_then(|v| v.as_u64());
+        let diff_ms = timing.get("diff_time_ms").and_then(|v| v.as_u64());
+
+        print!("Uptime: {}s", uptime);
+
+        match (git_ms, diff_ms) {
+            (Some(g), Some(d)) => {
+                let total = g + d;
+                let color = if total > 1000 {
+                    ansi::YELLOW
+                } else {
+                    ansi::GREEN
+                };
+                println!(" | Initial load: {color}{}ms{RESET} (git: {}ms, diff: {}ms)", total, g, d);
             }
+            _ => println!(" | Initial load: {DIM}pending...{RESET}"),
         }
+    }
 
-        // Show capture info
-        if let Some(capture) = data.get("capture") {
-            let enabled = capture.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+    // Show resource usage (debug HUD, only published with --profile)
+    if let Some(hud) = data.pointer("/resources/rendered").and_then(|v| v.as_str()) {
+        println!("{DIM}{}{RESET}", hud);
+    }
 
-            if enabled {
-                println!("\n[Capture]");
+    // Show capture info
+    if let Some(capture) = data.get("capture") {
+        let enabled = capture.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
 
-                if let Some(scrollback) = capture.get("scrollback_path").and_then(|v| v.as_str()) {
-                    let status = get_file_status(scrollback);
-                    println!("  Scrollback: {} {}", scrollback, status);
-                }
+        if enabled {
+            println!("\n[Capture]");
 
-                if let Some(screen) = capture.get("screen_path").and_then(|v| v.as_str()) {
-                    let status = get_file_status(screen);
-                    println!("  Screen:     {} {}", screen, status);
-                }
-            } else {
-                println!("\n[Capture] {DIM}disabled{RESET}");
+            if let Some(scrollback) = capture.get("scrollback_path").and_then(|v| v.as_str()) {
+                let status = get_file_status(scrollback);
+                // Count lines through the decoder so .gz transcripts report real content
+                let lines = read_capture_file(Path::new(scrollback))
+                    .map(|contents| format!(" {DIM}{} lines{RESET}", bytecount_lines(&contents)))
+                    .unwrap_or_default();
+                println!("  Scrollback: {} {}{}", scrollback, status, lines);
             }
+
+            if let Some(screen) = capture.get("screen_path").and_then(|v| v.as_str()) {
+                let status = get_file_status(screen);
+                println!("  Screen:     {} {}", screen, status);
+            }
+        } else {
+            println!("\n[Capture] {DIM}disabled{RESET}");
         }
+    }
 
-        if let Some(widgets) = data.get("widgets") {
-            // Stats
-            if let Some(stats) = widgets.get("stats") {
-                println!("\n[Stats]");
-                if let Some(rendered) = stats.get("rendered").and_then(|v| v.as_array()) {
-                    for line in rendered {
-                        if let Some(s) = line.as_str() {
-                            println!("  {}", s);
-                        }
+    if let Some(widgets) = data.get("widgets") {
+        // Stats
+        if let Some(stats) = widgets.get("stats") {
+            println!("\n[Stats]");
+            if let Some(rendered) = stats.get("rendered").and_then(|v| v.as_array()) {
+                for line in rendered {
+                    if let Some(s) = line.as_str() {
+                        println!("  {}", s);
                     }
                 }
             }
+        }
 
-            // Git
-            if let Some(git) = widgets.get("git") {
-                println!("\n[Git]");
-                if let Some(rendered) = git.get("rendered").and_then(|v| v.as_array()) {
-                    for line in rendered {
-                        if let Some(s) = line.as_str() {
-                            println!("  {}", s);
-                        }
+        // Git
+        if let Some(git) = widgets.get("git") {
+            println!("\n[Git]");
+            if let Some(rendered) = git.get("rendered").and_then(|v| v.as_array()) {
+                for line in rendered {
+                    if let Some(s) = line.as_str() {
+                        println!("  {}", s);
                     }
                 }
             }
+        }
 
-            // Changes
-            if let Some(changes) = widgets.get("changes") {
-                println!("\n[Changes]");
-                if let Some(rendered) = changes.get("rendered").and_then(|v| v.as_array()) {
-                    for line in rendered {
-                        if let Some(s) = line.as_str() {
-                            println!("  {}", s);
-                        }
+        // Changes
+        if let Some(changes) = widgets.get("changes") {
+            println!("\n[Changes]");
+            if let Some(rendered) = changes.get("rendered").and_then(|v| v.as_array()) {
+                for line in rendered {
+                    if let Some(s) = line.as_str() {
+                        println!("  {}", s);
                     }
                 }
             }
         }
+    }
 
-        println!();
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn instance(id: &str, platform: Option<&str>, uptime: u64, stats: Value) -> (PathBuf, Value) {
+        let mut data = json!({
+            "session_id": id,
+            "cwd": format!("/work/{}", id),
+            "launch_timing": { "uptime_secs": uptime },
+            "widgets": { "stats": { "data": stats } },
+        });
+        if let Some(platform) = platform {
+            data["platform"] = json!(platform);
+        }
+        (PathBuf::from(format!("/tmp/crabigator-{}/inspect.json", id)), data)
     }
 
-    Ok(())
+    fn session(cwd: &str, last_updated: f64) -> (PathBuf, Value) {
+        let data = json!({ "session_id": "s", "pid": 42, "cwd": cwd, "last_updated": last_updated });
+        (PathBuf::from("/tmp/crabigator-s/inspect.json"), data)
+    }
+
+    #[test]
+    fn test_history_table_is_chronological() {
+        let event = |ts: f64, name: &str, before: &str, after: &str, details: Value| HookEvent {
+            ts,
+            event: name.to_string(),
+            state_before: before.to_string(),
+            state_after: after.to_string(),
+            details: serde_json::from_value(details).unwrap(),
+        };
+        let events = vec![
+            event(20.0, "Stop", "thinking", "complete", json!({"pending_question": false})),
+            event(10.0, "UserPromptSubmit", "ready", "thinking", Value::Null),
+            event(15.0, "PostToolUse", "thinking", "thinking", json!({"tool": "Bash", "id": "1"})),
+            event(18.0, "PreCompact", "thinking", "", Value::Null),
+        ];
+
+        let rows = history_table(&events);
+        assert_eq!(rows.len(), 6);
+        assert!(rows[0].starts_with("Time"));
+        let names: Vec<_> = rows[2..].iter().map(|r| r.split_whitespace().nth(1).unwrap()).collect();
+        assert_eq!(names, ["UserPromptSubmit", "PostToolUse", "PreCompact", "Stop"]);
+
+        assert!(rows[2].starts_with(&format_timestamp(10.0)));
+        assert!(rows[2].ends_with("ready → thinking"));
+        // Details are sorted, strings unquoted; an unchanged state shows once
+        assert!(rows[3].contains(" thinking "));
+        assert!(!rows[3].contains('→'));
+        assert!(rows[3].ends_with("id=1, tool=Bash"));
+        // Events from hooks that didn't record state_after
+        assert!(rows[4].ends_with("thinking"));
+        assert!(rows[5].contains(" thinking → complete "));
+        assert!(rows[5].ends_with("pending_question=false"));
+    }
+
+    #[test]
+    fn test_tagged_name_replaces_session_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mirror = tmp.path().join("inspect.json");
+        let data = json!({ "session_id": "12345" });
+        assert_eq!(session_heading(&with_tagged_name(data.clone(), &mirror)), "Session 12345");
+
+        let tags = crate::tags::Tags {
+            name: Some("Refactor auth".to_string()),
+            tags: vec![],
+        };
+        TagStore::write(tmp.path(), tags).unwrap();
+        let tagged = with_tagged_name(data, &mirror);
+        assert_eq!(tagged["project_name"], "Refactor auth");
+        assert_eq!(session_heading(&tagged), "Refactor auth (12345)");
+    }
+
+    /// Single-quote `s` for a POSIX shell
+    fn shell_quote(s: &str) -> String {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+
+    /// An `ssh` stand-in that prints `mirrors` the way the remote `find`
+    /// would, logging the arguments before the command
+    fn mock_ssh(dir: &Path, mirrors: &[PathBuf]) -> RemoteDiscovery {
+        use std::os::unix::fs::PermissionsExt;
+
+        let listing: Vec<String> = mirrors.iter().map(|p| p.display().to_string()).collect();
+        let script = format!(
+            "#!/bin/sh\necho \"$3 $4\" >> \"{log}\"\n\
+             for f in {listing}; do printf '\\0%s\\0' \"$f\"; cat \"$f\" 2>/dev/null; done\n",
+            log = dir.join("targets.log").display(),
+            listing = listing.iter().map(|p| shell_quote(p)).collect::<Vec<_>>().join(" "),
+        );
+        let program = dir.join("ssh");
+        fs::write(&program, script).unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+        RemoteDiscovery {
+            ssh_program: program.display().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_remote_discovery_reads_mirrors_over_ssh() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut mirrors = vec![];
+        for (id, cwd) in [("a", "/srv/app"), ("b", "/srv/other"), ("it's", "/srv/app/web")] {
+            let dir = tmp.path().join(format!("crabigator-{}", id));
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("inspect.json");
+            fs::write(&path, json!({ "session_id": id, "cwd": cwd }).to_string()).unwrap();
+            mirrors.push(path);
+        }
+        // Listed but gone by the time it's read
+        mirrors.push(tmp.path().join("crabigator-gone/inspect.json"));
+        let discovery = mock_ssh(tmp.path(), &mirrors);
+
+        let all = discovery.discover_remote("me@box", None).unwrap();
+        let ids: Vec<&str> = all.iter().map(|(_, data)| data["session_id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["a", "b", "it's"]);
+        assert_eq!(all[0].0, PathBuf::from(format!("me@box:{}", mirrors[0].display())));
+
+        let app = discovery.discover_remote("me@box", Some("/srv/app")).unwrap();
+        assert_eq!(app.len(), 2);
+
+        // One round trip per discovery, with the target after `--`
+        let targets = fs::read_to_string(tmp.path().join("targets.log")).unwrap();
+        assert_eq!(targets.lines().collect::<Vec<_>>(), ["-- me@box", "-- me@box"]);
+    }
+
+    #[test]
+    fn test_remote_discovery_reports_connection_failure() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let program = tmp.path().join("ssh");
+        fs::write(&program, "#!/bin/sh\necho 'Connection refused' >&2\nexit 255\n").unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+        let discovery = RemoteDiscovery {
+            ssh_program: program.display().to_string(),
+        };
+        let err = discovery.discover_remote("me@box", None).unwrap_err();
+        assert_eq!(err.to_string(), "ssh me@box failed: Connection refused");
+    }
+
+    #[test]
+    fn test_dir_filter_globs_and_substrings() {
+        assert!(matches_dir_filter("/work/mono/packages/api", "mono"));
+        assert!(matches_dir_filter("/work/mono/packages/api", "*/packages/*"));
+        assert!(matches_dir_filter("/work/mono/packages/web", "/work/mono/packages/[aw]*"));
+        assert!(!matches_dir_filter("/work/mono/apps/web", "*/packages/*"));
+        // Globs match the whole path, not a piece of it
+        assert!(!matches_dir_filter("/work/mono/packages/api", "packages/*"));
+        assert!(!matches_dir_filter("/work/other", "[unclosed"));
+    }
+
+    #[test]
+    fn test_group_by_git_root() {
+        let instances = vec![
+            session("/work/mono/packages/web", 0.0),
+            session("/work/solo", 0.0),
+            session("/work/mono/packages/api", 0.0),
+            session("/tmp/scratch", 0.0),
+        ];
+        let git_root = |dir: &Path| {
+            ["/work/mono", "/work/solo"]
+                .iter()
+                .map(Path::new)
+                .find(|root| dir.starts_with(root))
+                .map(Path::to_path_buf)
+        };
+
+        let groups = group_by_git_root(&instances, git_root);
+        assert_eq!(
+            groups,
+            vec![
+                GitRootGroup { root: PathBuf::from("/tmp/scratch"), sessions: vec![3] },
+                GitRootGroup { root: PathBuf::from("/work/mono"), sessions: vec![0, 2] },
+                GitRootGroup { root: PathBuf::from("/work/solo"), sessions: vec![1] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_git_root_walks_up() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let nested = dir.path().join("packages/api/src");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_git_root(&nested).filter(|r| r.starts_with(dir.path())), None);
+
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        assert_eq!(find_git_root(&nested), Some(dir.path().to_path_buf()));
+    }
+
+    #[test]
+    fn test_is_alive_uses_last_updated() {
+        let (_, data) = session("/work", 1000.0);
+        assert!(is_alive_at(&data, 1009.9));
+        assert!(!is_alive_at(&data, 1010.0));
+        // Mirrors without a timestamp are never considered alive
+        assert!(!is_alive_at(&json!({ "cwd": "/work" }), 1000.0));
+    }
+
+    #[test]
+    fn test_status_exit_code() {
+        let instances = vec![session("/work/a", 1000.0), session("/work/b", 900.0)];
+        assert_eq!(status_exit_code(&instances, "/work/a", 1005.0), 0);
+        // A session in the directory exists but has gone stale
+        assert_eq!(status_exit_code(&instances, "/work/b", 1005.0), 1);
+        // Exact directory match only: subdirectories don't count
+        assert_eq!(status_exit_code(&instances, "/work", 1005.0), 1);
+        assert_eq!(status_exit_code(&[], "/work/a", 1005.0), 1);
+    }
+
+    #[test]
+    fn test_status_entry() {
+        let mut data = session("/work/a", now_unix()).1;
+        data["platform"] = json!("codex");
+        data["launch_timing"] = json!({ "uptime_secs": 75 });
+        data["widgets"] = json!({ "stats": { "data": { "state": "thinking" } } });
+
+        let entry = status_entry(&data);
+        assert_eq!(entry["pid"], 42);
+        assert_eq!(entry["platform"], "codex");
+        assert_eq!(entry["uptime_secs"], 75);
+        assert_eq!(entry["state"], "thinking");
+        assert_eq!(entry["alive"], true);
+        data["last_updated"] = json!(now_unix() - 60.0);
+        assert_eq!(status_entry(&data)["alive"], false);
+    }
+
+    #[test]
+    fn test_aggregate_instances_sums_and_groups() {
+        let instances = vec![
+            instance(
+                "a",
+                Some("claude"),
+                600,
+                json!({ "prompts": 4, "completions": 3, "tools": 20, "thinking_seconds": 90 }),
+            ),
+            instance(
+                "b",
+                Some("codex"),
+                60,
+                json!({ "prompts": 1, "completions": 1, "tools": 5, "thinking_seconds": 10 }),
+            ),
+            instance(
+                "c",
+                Some("claude"),
+                120,
+                json!({ "prompts": 2, "completions": 2, "tools": 0, "thinking_seconds": 30 }),
+            ),
+        ];
+
+        let summary = aggregate_instances(&instances);
+        assert_eq!(
+            summary.totals,
+            AggregateTotals {
+                instances: 3,
+                prompts: 7,
+                completions: 6,
+                tool_calls: 25,
+                thinking_seconds: 130,
+            }
+        );
+
+        let claude = &summary.by_platform["claude"];
+        assert_eq!(claude.instances, 2);
+        assert_eq!(claude.tool_calls, 20);
+        assert_eq!(summary.by_platform["codex"].prompts, 1);
+
+        // b: 5 tools in 1 minute beats a: 20 tools in 10 minutes
+        let busiest = summary.busiest.unwrap();
+        assert_eq!(busiest.session_id, "b");
+        assert!((busiest.tools_per_minute - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_aggregate_instances_tolerates_missing_fields() {
+        let instances = vec![
+            instance("old", None, 0, json!({ "prompts": 2 })),
+            (PathBuf::from("/tmp/crabigator-x/inspect.json"), json!({})),
+        ];
+
+        let summary = aggregate_instances(&instances);
+        assert_eq!(summary.totals.instances, 2);
+        assert_eq!(summary.totals.prompts, 2);
+        assert_eq!(summary.by_platform["unknown"].instances, 2);
+        assert!(summary.busiest.is_none());
+    }
+
+    fn with_languages(mut instance: (PathBuf, Value), languages: &[(&str, usize)]) -> (PathBuf, Value) {
+        let by_language: Vec<Value> = languages
+            .iter()
+            .map(|(language, n)| {
+                let changes: Vec<Value> = (0..*n).map(|i| json!({ "kind": "fn", "name": format!("f{}", i) })).collect();
+                json!({ "language": language, "changes": changes })
+            })
+            .collect();
+        let total: usize = languages.iter().map(|(_, n)| n).sum();
+        instance.1["widgets"]["changes"] = json!({ "data": { "by_language": by_language, "total": total } });
+        instance
+    }
+
+    #[test]
+    fn test_aggregate_languages_merges_overlapping_sets() {
+        let instances = vec![
+            with_languages(instance("a", None, 0, json!({})), &[("Rust", 30), ("Python", 4)]),
+            with_languages(instance("b", None, 0, json!({})), &[("TypeScript", 28), ("Rust", 12)]),
+            with_languages(instance("c", None, 0, json!({})), &[("Python", 7), ("Go", 0)]),
+        ];
+
+        let expected = vec![
+            ("Rust".to_string(), 42),
+            ("TypeScript".to_string(), 28),
+            ("Python".to_string(), 11),
+        ];
+        assert_eq!(aggregate_languages(&instances), expected);
+        assert_eq!(aggregate_instances(&instances).languages, expected);
+    }
+
+    #[test]
+    fn test_aggregate_languages_session_without_changes() {
+        let instances = vec![
+            instance("idle", None, 0, json!({})),
+            with_languages(instance("empty", None, 0, json!({})), &[]),
+            with_languages(instance("busy", None, 0, json!({})), &[("Swift", 2), ("CSS", 2)]),
+        ];
+        assert!(aggregate_languages(&instances[..2]).is_empty());
+        // Ties stay alphabetical
+        assert_eq!(aggregate_languages(&instances), [("CSS".to_string(), 2), ("Swift".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_language_bar_scales_to_longest() {
+        assert_eq!(language_bar(42, 42, 10), "█".repeat(10));
+        assert_eq!(language_bar(21, 42, 10), "█".repeat(5));
+        assert_eq!(language_bar(11, 42, 10), "██▌");
+        assert_eq!(language_bar(1, 1000, 10), "▌");
+        assert_eq!(language_bar(0, 42, 10), "");
+    }
+
+    #[test]
+    fn test_aggregate_instances_empty() {
+        assert_eq!(aggregate_instances(&[]), AggregateSummary::default());
+    }
+
+    fn state(stats: Value, files: Value, by_language: Value) -> Value {
+        json!({
+            "widgets": {
+                "stats": { "data": stats },
+                "git": { "data": { "files": files } },
+                "changes": { "data": { "by_language": by_language } },
+            }
+        })
+    }
+
+    fn file(path: &str, additions: u64) -> Value {
+        json!({ "path": path, "status": "M", "additions": additions, "deletions": 0 })
+    }
+
+    #[test]
+    fn test_diff_mirror_states_stats_and_files() {
+        let old = state(
+            json!({ "prompts": 3, "state": "thinking" }),
+            json!([file("src/a.rs", 1), file("src/b.rs", 2)]),
+            json!([]),
+        );
+        let new = state(
+            json!({ "prompts": 4, "state": "thinking" }),
+            json!([file("src/a.rs", 5), file("src/new.rs", 1)]),
+            json!([]),
+        );
+
+        let diffs = diff_mirror_states(&old, &new);
+        assert_eq!(
+            diffs,
+            vec![
+                StateDiff::Changed { field: "prompts".into(), old_value: json!(3), new_value: json!(4) },
+                StateDiff::Changed {
+                    field: "git.files[0].additions".into(),
+                    old_value: json!(1),
+                    new_value: json!(5),
+                },
+                StateDiff::Added { field: "git.files[1]".into(), value: json!("src/new.rs") },
+                StateDiff::Removed { field: "git.files[1]".into(), value: json!("src/b.rs") },
+            ]
+        );
+
+        let plain: Vec<String> = diffs.iter().map(|d| crate::ui::utils::strip_ansi(&d.to_string())).collect();
+        assert_eq!(plain[0], "~ prompts: 3 → 4");
+        assert_eq!(plain[2], "+ git.files[1]: \"src/new.rs\"");
+        assert_eq!(plain[3], "- git.files[1]: \"src/b.rs\"");
+    }
+
+    #[test]
+    fn test_diff_mirror_states_changes_by_language() {
+        let change = |name: &str, additions: u64| json!({ "kind": "fn", "name": name, "additions": additions });
+        let old = state(json!({}), json!([]), json!([{ "language": "Rust", "changes": [change("parse", 2)] }]));
+        let new = state(
+            json!({}),
+            json!([]),
+            json!([
+                { "language": "Python", "changes": [change("main", 1)] },
+                { "language": "Rust", "changes": [change("parse", 2)] },
+            ]),
+        );
+
+        assert_eq!(
+            diff_mirror_states(&old, &new),
+            vec![StateDiff::Added { field: "changes.Python[0]".into(), value: json!("fn main") }]
+        );
+        assert_eq!(
+            diff_mirror_states(&new, &old),
+            vec![StateDiff::Removed { field: "changes.Python[0]".into(), value: json!("fn main") }]
+        );
+    }
+
+    #[test]
+    fn test_diff_mirror_states_identical_and_missing() {
+        let full = state(json!({ "prompts": 1 }), json!([file("a", 1)]), json!([]));
+        assert!(diff_mirror_states(&full, &full).is_empty());
+        // A publish without widgets (e.g. mid-write) reports everything as removed
+        assert_eq!(diff_mirror_states(&full, &json!({})).len(), 2);
+    }
 }
@@ -0,0 +1,348 @@
+//! End-to-end diff pipeline tests against a real git repository
+//!
+//! The parser unit tests feed hand-written diffs; these run the whole path a
+//! session takes: `git diff` in a scratch repo → `parse_diff_into_files` →
+//! each language's `DiffParser` → `DiffSummary`.
+
+#[cfg(test)]
+mod pipeline {
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Once;
+
+    use anyhow::{bail, Context, Result};
+    use tempfile::TempDir;
+    use tokio::process::Command;
+
+    use crate::git::head_commit;
+    use crate::parsers::{ChangeNode, ChangeType, DiffCache, DiffMode, DiffSummary, DEFAULT_MAX_DIFF_BYTES};
+
+    static INIT_ENV: Once = Once::new();
+
+    const RUST_BASE: &str = "\
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+";
+
+    const TS_BASE: &str = "\
+export class Cart {
+  items: number[] = [];
+
+  total(): number {
+    return this.items.reduce((sum, n) => sum + n, 0);
+  }
+
+  clear(): void {
+    this.items = [];
+  }
+}
+";
+
+    const PY_BASE: &str = "\
+def greet(name):
+    return f\"Hello, {name}\"
+
+
+def farewell(name):
+    return f\"Goodbye, {name}\"
+";
+
+    /// Scratch repo with one committed file per language
+    async fn repo() -> Result<TempDir> {
+        INIT_ENV.call_once(|| {
+            std::env::set_var("GIT_CONFIG_GLOBAL", "/dev/null");
+            std::env::set_var("GIT_CONFIG_SYSTEM", "/dev/null");
+            std::env::set_var("GIT_ATTR_NOSYSTEM", "1");
+        });
+
+        let temp_dir = TempDir::new().context("create temp dir")?;
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("src"))?;
+        fs::write(dir.join("src/math.rs"), RUST_BASE)?;
+        fs::write(dir.join("src/cart.ts"), TS_BASE)?;
+        fs::write(dir.join("src/greet.py"), PY_BASE)?;
+
+        run_git(dir, &["init", "-b", "main"]).await?;
+        run_git(dir, &["config", "user.name", "Crabigator Tests"]).await?;
+        run_git(dir, &["config", "user.email", "tests@example.com"]).await?;
+        run_git(dir, &["config", "commit.gpgsign", "false"]).await?;
+        run_git(dir, &["add", "."]).await?;
+        run_git(dir, &["commit", "-m", "base"]).await?;
+        Ok(temp_dir)
+    }
+
+    async fn run_git(dir: &Path, args: &[&str]) -> Result<()> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .await
+            .with_context(|| format!("run git {:?}", args))?;
+        if !output.status.success() {
+            bail!("git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    /// Depth-first search for a change named `name` in `language`
+    fn find<'a>(summary: &'a DiffSummary, language: &str, name: &str) -> Option<&'a ChangeNode> {
+        fn walk<'a>(nodes: &'a [ChangeNode], name: &str) -> Option<&'a ChangeNode> {
+            nodes
+                .iter()
+                .find_map(|n| if n.name == name { Some(n) } else { walk(&n.children, name) })
+        }
+        summary
+            .files
+            .iter()
+            .filter(|f| f.language == language)
+            .find_map(|f| walk(&f.changes, name))
+    }
+
+    fn languages(summary: &DiffSummary) -> Vec<String> {
+        summary.by_language().into_iter().map(|l| l.language).collect()
+    }
+
+    #[tokio::test]
+    async fn test_added_rust_function() -> Result<()> {
+        let repo = repo().await?;
+        fs::write(
+            repo.path().join("src/math.rs"),
+            format!("{}\npub fn subtract(a: i32, b: i32) -> i32 {{\n    a - b\n}}\n", RUST_BASE),
+        )?;
+
+        let summary =
+            DiffSummary::new().refresh_in_dir(repo.path(), DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(languages(&summary), vec!["Rust"]);
+        let subtract = find(&summary, "Rust", "subtract").context("subtract in summary")?;
+        assert_eq!(subtract.change_type, ChangeType::Added);
+        assert_eq!(subtract.file_path.as_deref(), Some("src/math.rs"));
+        // The blank line before the new function lands in add's hunk; it's never "added"
+        assert!(find(&summary, "Rust", "add").is_none_or(|add| add.change_type != ChangeType::Added));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_modified_typescript_method() -> Result<()> {
+        let repo = repo().await?;
+        fs::write(
+            repo.path().join("src/cart.ts"),
+            TS_BASE.replace("sum + n, 0", "sum + Math.max(n, 0), 0"),
+        )?;
+
+        let summary =
+            DiffSummary::new().refresh_in_dir(repo.path(), DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        // TypeScript is grouped with JavaScript
+        assert_eq!(languages(&summary), vec!["JavaScript"]);
+        let total = find(&summary, "JavaScript", "total").context("total in summary")?;
+        assert_eq!(total.change_type, ChangeType::Modified);
+        assert!(find(&summary, "JavaScript", "clear").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deleted_python_function() -> Result<()> {
+        let repo = repo().await?;
+        let without_farewell = PY_BASE.split("\n\n\n").next().unwrap().to_string() + "\n";
+        fs::write(repo.path().join("src/greet.py"), without_farewell)?;
+
+        let summary =
+            DiffSummary::new().refresh_in_dir(repo.path(), DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(languages(&summary), vec!["Python"]);
+        let farewell = find(&summary, "Python", "farewell").context("farewell in summary")?;
+        assert_eq!(farewell.change_type, ChangeType::Deleted);
+        assert!(find(&summary, "Python", "greet").is_none_or(|greet| greet.change_type != ChangeType::Deleted));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_changes_across_languages() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        fs::write(dir.join("src/math.rs"), RUST_BASE.replace("a + b", "b + a"))?;
+        fs::write(dir.join("src/cart.ts"), TS_BASE.replace("this.items = [];", "this.items.length = 0;"))?;
+        fs::write(dir.join("src/greet.py"), PY_BASE.replace("Hello", "Hi"))?;
+        // Staged changes are part of the summary too
+        run_git(dir, &["add", "src/greet.py"]).await?;
+
+        let summary = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert!(!summary.loading);
+        assert_eq!(languages(&summary), vec!["JavaScript", "Python", "Rust"]);
+        assert!(find(&summary, "Rust", "add").is_some());
+        assert!(find(&summary, "JavaScript", "clear").is_some());
+        assert!(find(&summary, "Python", "greet").is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_reparses_only_when_diff_changes() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        fs::write(dir.join("src/math.rs"), RUST_BASE.replace("a + b", "b + a"))?;
+
+        let cache = DiffCache::default();
+        let (first, recomputed) = cache.refresh(dir, DiffMode::Working, None).await?;
+        assert!(recomputed);
+        let (second, recomputed) = cache.refresh(dir, DiffMode::Working, None).await?;
+        assert!(!recomputed);
+        assert_eq!(languages(&second), languages(&first));
+
+        fs::write(dir.join("src/greet.py"), PY_BASE.replace("Hello", "Hi"))?;
+        let (third, recomputed) = cache.refresh(dir, DiffMode::Working, None).await?;
+        assert!(recomputed);
+        assert_eq!(languages(&third), vec!["Python", "Rust"]);
+        Ok(())
+    }
+
+    /// Language groups with their changes, for comparing two summaries
+    fn snapshot(summary: &DiffSummary) -> String {
+        format!(
+            "{:?} words +{} -{} {:?}",
+            summary.by_language(),
+            summary.total_words_added,
+            summary.total_words_deleted,
+            summary.file_stats
+        )
+    }
+
+    #[tokio::test]
+    async fn test_incremental_refresh_matches_full() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        fs::write(dir.join("src/math.rs"), RUST_BASE.replace("a + b", "b + a"))?;
+        fs::write(dir.join("src/greet.py"), PY_BASE.replace("Hello", "Hi"))?;
+        fs::write(dir.join("src/cart.ts"), TS_BASE.replace("this.items = [];", "this.items.length = 0;"))?;
+        let previous = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+
+        // One file edited further, one reverted
+        fs::write(
+            dir.join("src/math.rs"),
+            format!("{}\npub fn double(a: i32) -> i32 {{\n    a * 2\n}}\n", RUST_BASE.replace("a + b", "b + a")),
+        )?;
+        fs::write(dir.join("src/greet.py"), PY_BASE)?;
+
+        let incremental =
+            DiffSummary::refresh_incremental(dir, &previous, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        let full = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(snapshot(&incremental), snapshot(&full));
+        assert!(find(&incremental, "Rust", "double").is_some());
+        assert!(!languages(&incremental).contains(&"Python".to_string()));
+
+        // Nothing changed since: the previous summary is reused as-is
+        let again =
+            DiffSummary::refresh_incremental(dir, &incremental, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(snapshot(&again), snapshot(&full));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incremental_refresh_falls_back_when_most_files_change() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        fs::write(dir.join("src/math.rs"), RUST_BASE.replace("a + b", "b + a"))?;
+        let previous = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+
+        fs::write(dir.join("src/greet.py"), PY_BASE.replace("Hello", "Hi"))?;
+        fs::write(dir.join("src/cart.ts"), TS_BASE.replace("sum + n, 0", "sum + n * 2, 0"))?;
+        run_git(dir, &["add", "src/cart.ts"]).await?;
+
+        let incremental =
+            DiffSummary::refresh_incremental(dir, &previous, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        let full = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(snapshot(&incremental), snapshot(&full));
+        assert_eq!(languages(&incremental), vec!["JavaScript", "Python", "Rust"]);
+
+        // A summary that never loaded always gets a full refresh
+        let from_loading = DiffSummary::refresh_incremental(
+            dir,
+            &DiffSummary::new(),
+            DiffMode::Working,
+            None,
+            DEFAULT_MAX_DIFF_BYTES,
+        ).await?;
+        assert_eq!(snapshot(&from_loading), snapshot(&full));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incremental_refresh_sees_line_rewritten_in_place() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        let with_fn = |name: &str| format!("{}\npub fn {}(a: i32) -> i32 {{\n    a * 2\n}}\n", RUST_BASE, name);
+        fs::write(dir.join("src/math.rs"), with_fn("double"))?;
+        fs::write(dir.join("src/greet.py"), PY_BASE.replace("Hello", "Hi"))?;
+        fs::write(dir.join("src/cart.ts"), TS_BASE.replace("this.items = [];", "this.items.length = 0;"))?;
+        let previous = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+
+        // Same line counts as before, different text
+        fs::write(dir.join("src/math.rs"), with_fn("twice"))?;
+
+        let incremental =
+            DiffSummary::refresh_incremental(dir, &previous, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        let full = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(snapshot(&incremental), snapshot(&full));
+        assert!(find(&incremental, "Rust", "twice").is_some());
+        assert!(find(&incremental, "Rust", "double").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_binary_file_listed_as_binary() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        fs::write(dir.join("logo.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")?;
+        run_git(dir, &["add", "logo.png"]).await?;
+        fs::write(dir.join("src/math.rs"), RUST_BASE.replace("a + b", "b + a"))?;
+
+        let summary = DiffSummary::new().refresh_in_dir(dir, DiffMode::Working, None, DEFAULT_MAX_DIFF_BYTES).await?;
+        let binary = find(&summary, "Other", "(binary)").context("binary change listed")?;
+        assert!(binary.is_binary());
+        assert_eq!(binary.file_path.as_deref(), Some("logo.png"));
+        assert!(find(&summary, "Rust", "add").is_some());
+        Ok(())
+    }
+
+    fn paths(summary: &DiffSummary) -> Vec<&str> {
+        let mut paths: Vec<&str> = summary.file_stats.keys().map(String::as_str).collect();
+        paths.dedup();
+        paths
+    }
+
+    #[tokio::test]
+    async fn test_session_mode_includes_work_committed_since_start() -> Result<()> {
+        let repo = repo().await?;
+        let dir = repo.path();
+        let start = head_commit(dir).await.context("HEAD after base commit")?;
+
+        // Committed during the session, then staged, then left unstaged
+        fs::write(dir.join("src/math.rs"), RUST_BASE.replace("a + b", "b + a"))?;
+        run_git(dir, &["commit", "-am", "swap"]).await?;
+        fs::write(dir.join("src/cart.ts"), TS_BASE.replace("sum + n, 0", "sum + n * 2, 0"))?;
+        run_git(dir, &["add", "src/cart.ts"]).await?;
+        fs::write(dir.join("src/greet.py"), PY_BASE.replace("Hello", "Hi"))?;
+
+        let loading = DiffSummary::new();
+        let refresh = |mode, commit| loading.refresh_in_dir(dir, mode, commit, DEFAULT_MAX_DIFF_BYTES);
+        let working = refresh(DiffMode::Working, Some(start.as_str())).await?;
+        assert_eq!(paths(&working), vec!["src/cart.ts", "src/greet.py"]);
+
+        let session = refresh(DiffMode::Session, Some(start.as_str())).await?;
+        assert_eq!(paths(&session), vec!["src/cart.ts", "src/greet.py", "src/math.rs"]);
+        assert_eq!(session.file_stats["src/math.rs"].additions, 1);
+
+        let all = refresh(DiffMode::All, Some(start.as_str())).await?;
+        assert_eq!(paths(&all), paths(&session));
+        assert_eq!(snapshot(&all), snapshot(&session));
+
+        // With no start commit (e.g. an empty repo) Session reads like Working
+        let fallback = refresh(DiffMode::Session, None).await?;
+        assert_eq!(snapshot(&fallback), snapshot(&working));
+
+        // Incremental refreshes diff against the same base
+        let start = Some(start.as_str());
+        let incremental =
+            DiffSummary::refresh_incremental(dir, &session, DiffMode::Session, start, DEFAULT_MAX_DIFF_BYTES).await?;
+        assert_eq!(snapshot(&incremental), snapshot(&session));
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+//! Integrations with terminal multiplexers and shell prompts
+//!
+//! - **zellij**: layout that runs crabigator next to an `inspect --watch` pane
+//! - **starship**: prompt module showing the active session's state
+
+#[cfg(feature = "zellij")]
+pub mod zellij;
+
+pub mod starship;
@@ -0,0 +1,105 @@
+//! Starship prompt module
+//!
+//! `crabigator starship` prints a one-line status for the most recently
+//! active session, read from its mirror (`/tmp/crabigator-*/inspect.json`),
+//! e.g. `🦀 thinking 4p`. It exits with 1 when no live session is found, so a
+//! Starship custom module with `when` hides itself. `crabigator starship
+//! --config` prints that module for `~/.config/starship.toml`.
+//!
+//! The line follows `starship_format` in config.toml (`{state}`, `{prompts}`,
+//! `{completions}`, `{tools}`, `{branch}`, `{platform}`).
+
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::inspect::{discover_instances, is_alive, latest_instance};
+
+/// Default template: crab, state, prompt count
+pub const DEFAULT_FORMAT: &str = "🦀 {state} {prompts}p";
+
+/// Custom module for `~/.config/starship.toml`
+pub const STARSHIP_CONFIG: &str = r#"[custom.crabigator]
+command = "crabigator starship"
+when = "crabigator starship"
+shell = ["sh"]
+format = "[$output]($style) "
+style = "bold yellow"
+description = "State of the running crabigator session"
+"#;
+
+/// Fill the template placeholders from a session's mirror JSON
+fn render(format: &str, data: &Value) -> String {
+    let stats = data.pointer("/widgets/stats/data").unwrap_or(&Value::Null);
+    let text = |value: Option<&Value>| value.and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let count = |name: &str| stats.get(name).and_then(|v| v.as_u64()).unwrap_or(0).to_string();
+    let branch = match data.pointer("/widgets/git/data/is_repo").and_then(|v| v.as_bool()) {
+        Some(false) => String::new(),
+        _ => text(data.pointer("/widgets/git/data/branch")),
+    };
+
+    let line = format
+        .replace("{state}", &text(stats.get("state")))
+        .replace("{prompts}", &count("prompts"))
+        .replace("{completions}", &count("completions"))
+        .replace("{tools}", &count("tools"))
+        .replace("{branch}", &branch)
+        .replace("{platform}", &text(data.get("platform")));
+    line.trim().to_string()
+}
+
+/// Print the status line (exit 1 without a live session), or with `print_config`
+/// the Starship module
+pub fn run_starship(print_config: bool, config: &Config) -> anyhow::Result<()> {
+    if print_config {
+        print!("{}", STARSHIP_CONFIG);
+        return Ok(());
+    }
+
+    let instances = discover_instances(&None)?;
+    let Some(session) = latest_instance(&instances).filter(|data| is_alive(data)) else {
+        std::process::exit(1);
+    };
+    let format = config.starship_format.as_deref().unwrap_or(DEFAULT_FORMAT);
+    println!("{}", render(format, session));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn session(state: &str, prompts: u64, branch: Option<&str>) -> Value {
+        json!({
+            "platform": "claude",
+            "widgets": {
+                "stats": { "data": { "state": state, "prompts": prompts, "completions": 3, "tools": 17 } },
+                "git": { "data": { "branch": branch.unwrap_or(""), "is_repo": branch.is_some() } },
+            },
+        })
+    }
+
+    #[test]
+    fn test_default_format_by_state() {
+        assert_eq!(render(DEFAULT_FORMAT, &session("thinking", 4, Some("main"))), "🦀 thinking 4p");
+        assert_eq!(render(DEFAULT_FORMAT, &session("ready", 0, None)), "🦀 ready 0p");
+        assert_eq!(render(DEFAULT_FORMAT, &session("permission", 12, None)), "🦀 permission 12p");
+        assert_eq!(render(DEFAULT_FORMAT, &session("complete", 7, None)), "🦀 complete 7p");
+    }
+
+    #[test]
+    fn test_custom_format() {
+        let format = "{platform}:{state} {completions}c {tools}t {branch}";
+        assert_eq!(render(format, &session("question", 2, Some("feat/x"))), "claude:question 3c 17t feat/x");
+        // Outside a repo the branch placeholder is empty
+        assert_eq!(render(format, &session("interrupted", 2, None)), "claude:interrupted 3c 17t");
+    }
+
+    #[test]
+    fn test_starship_config_is_valid_toml() {
+        let table: toml::Table = toml::from_str(STARSHIP_CONFIG).unwrap();
+        let module = &table["custom"]["crabigator"];
+        assert_eq!(module["command"].as_str(), Some("crabigator starship"));
+        assert_eq!(module["when"].as_str(), Some("crabigator starship"));
+    }
+}
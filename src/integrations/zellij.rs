@@ -0,0 +1,160 @@
+//! Zellij layout
+//!
+//! `crabigator zellij-plugin` prints a Zellij YAML layout with two panes:
+//! crabigator running the assistant (85% of the height) and
+//! `crabigator inspect --watch` below it (15%), which follows the session
+//! through its mirror file. `--install` writes the layout to
+//! `~/.config/zellij/layouts/crabigator.yaml` instead, so
+//! `zellij --layout crabigator` opens it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::platforms::PlatformKind;
+
+/// Height of the assistant pane; the inspect pane gets the rest
+const MAIN_PANE_PERCENT: u8 = 85;
+
+/// Split direction of a Zellij layout node
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// Children stacked top to bottom
+    Horizontal,
+    /// Children side by side
+    Vertical,
+}
+
+/// Root of a layout file
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ZellijLayout {
+    pub direction: Direction,
+    pub parts: Vec<ZellijPane>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ZellijPane {
+    pub direction: Direction,
+    pub split_size: SplitSize,
+    pub run: PaneRun,
+}
+
+/// `split_size: { Percent: 85 }`
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SplitSize {
+    #[serde(rename = "Percent")]
+    pub percent: u8,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaneRun {
+    pub command: RunCommand,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunCommand {
+    pub cmd: String,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+}
+
+/// Builds the crabigator layout for one platform and directory
+pub struct ZellijLayoutGenerator {
+    platform: PlatformKind,
+    cwd: PathBuf,
+}
+
+impl ZellijLayoutGenerator {
+    pub fn new(platform: PlatformKind, cwd: &Path) -> Self {
+        Self {
+            platform,
+            cwd: cwd.to_path_buf(),
+        }
+    }
+
+    pub fn layout(&self) -> ZellijLayout {
+        let pane = |percent: u8, args: Vec<String>| ZellijPane {
+            direction: Direction::Vertical,
+            split_size: SplitSize { percent },
+            run: PaneRun {
+                command: RunCommand {
+                    cmd: "crabigator".to_string(),
+                    args,
+                    cwd: self.cwd.clone(),
+                },
+            },
+        };
+        let cwd = self.cwd.to_string_lossy().into_owned();
+        ZellijLayout {
+            direction: Direction::Horizontal,
+            parts: vec![
+                pane(MAIN_PANE_PERCENT, vec!["--platform".to_string(), self.platform.as_str().to_string()]),
+                // Only the session started in this directory
+                pane(100 - MAIN_PANE_PERCENT, vec!["inspect".to_string(), "--watch".to_string(), cwd]),
+            ],
+        }
+    }
+
+    pub fn generate_layout(&self) -> String {
+        serde_yaml::to_string(&self.layout()).expect("layout serializes")
+    }
+}
+
+/// `~/.config/zellij/layouts/crabigator.yaml`
+fn install_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".config/zellij/layouts/crabigator.yaml")
+}
+
+/// Print the layout, or write it where Zellij looks for layouts
+pub fn run_zellij_plugin(platform: PlatformKind, install: bool) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let layout = ZellijLayoutGenerator::new(platform, &cwd).generate_layout();
+    if !install {
+        print!("{}", layout);
+        return Ok(());
+    }
+
+    let path = install_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(&path, layout).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    println!("Start it with: zellij --layout crabigator");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_round_trips() {
+        let generator = ZellijLayoutGenerator::new(PlatformKind::Codex, Path::new("/work/app"));
+        let yaml = generator.generate_layout();
+        let parsed: ZellijLayout = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, generator.layout());
+
+        assert_eq!(parsed.direction, Direction::Horizontal);
+        let [main, status] = &parsed.parts[..] else {
+            panic!("expected two panes, got {}", parsed.parts.len());
+        };
+        assert_eq!((main.split_size.percent, status.split_size.percent), (85, 15));
+        assert_eq!(main.run.command.args, ["--platform", "codex"]);
+        assert_eq!(status.run.command.args, ["inspect", "--watch", "/work/app"]);
+        assert_eq!(status.run.command.cwd, Path::new("/work/app"));
+    }
+
+    #[test]
+    fn test_layout_yaml_shape() {
+        let yaml = ZellijLayoutGenerator::new(PlatformKind::Claude, Path::new("/work/app")).generate_layout();
+        let value: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(value["direction"].as_str(), Some("Horizontal"));
+        assert_eq!(value["parts"][0]["split_size"]["Percent"].as_u64(), Some(85));
+        assert_eq!(value["parts"][1]["run"]["command"]["cmd"].as_str(), Some("crabigator"));
+    }
+}
@@ -0,0 +1,40 @@
+//! crabigator's modules, shared by the binary (`main.rs`), the benches and
+//! the fuzz targets
+
+pub mod app;
+pub mod background;
+pub mod banner;
+pub mod capture;
+pub mod cli;
+pub mod cloud;
+pub mod completions;
+pub mod config;
+pub mod coordinator;
+pub mod copy_stats;
+pub mod doctor;
+pub mod export;
+pub mod git;
+pub mod history;
+pub mod hooks;
+pub mod ide;
+pub mod inspect;
+pub mod integrations;
+pub mod logging;
+pub mod mirror;
+pub mod mode;
+pub mod notify;
+pub mod parsers;
+pub mod platforms;
+pub mod replay;
+pub mod search;
+pub mod summary;
+pub mod system;
+pub mod tags;
+pub mod terminal;
+pub mod tmux;
+pub mod ui;
+
+#[cfg(test)]
+mod fixtures_tests;
+#[cfg(test)]
+mod integration_tests;
@@ -0,0 +1,162 @@
+//! Debug logging through `tracing`
+//!
+//! Off by default: without `CRABIGATOR_LOG` no subscriber is installed and
+//! the `tracing` macros cost next to nothing. `CRABIGATOR_LOG=trace` (or
+//! `debug`, `info`, `warn`, `error`) writes events at that level and above to
+//! `/tmp/crabigator-{session_id}.log`, as text, or one JSON object per line
+//! with `CRABIGATOR_LOG_FORMAT=json`. Nothing goes to stderr, which would
+//! land in the middle of the CLI's output.
+
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Log level; unset (or `off`) disables logging
+pub const LOG_ENV_VAR: &str = "CRABIGATOR_LOG";
+
+/// `json` for JSON lines, anything else for text
+pub const LOG_FORMAT_ENV_VAR: &str = "CRABIGATOR_LOG_FORMAT";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some(v) if v.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// Level named by `CRABIGATOR_LOG`, or None to leave logging off
+fn parse_level(value: Option<&str>) -> Option<LevelFilter> {
+    match value?.trim().to_ascii_lowercase().as_str() {
+        "trace" => Some(LevelFilter::TRACE),
+        "debug" => Some(LevelFilter::DEBUG),
+        "info" => Some(LevelFilter::INFO),
+        "warn" => Some(LevelFilter::WARN),
+        "error" => Some(LevelFilter::ERROR),
+        _ => None,
+    }
+}
+
+/// Log file for a session
+pub fn log_path(session_id: &str) -> PathBuf {
+    PathBuf::from(format!("/tmp/crabigator-{}.log", session_id))
+}
+
+fn subscriber<W>(level: LevelFilter, format: LogFormat, writer: W) -> Box<dyn tracing::Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer)
+        .with_ansi(false);
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.json().finish()),
+    }
+}
+
+/// Install the file subscriber if `CRABIGATOR_LOG` asks for one
+///
+/// Returns the log file path when logging is on.
+pub fn init(session_id: &str) -> Option<PathBuf> {
+    let level = parse_level(std::env::var(LOG_ENV_VAR).ok().as_deref())?;
+    let format = LogFormat::parse(std::env::var(LOG_FORMAT_ENV_VAR).ok().as_deref());
+    let path = log_path(session_id);
+    let file = OpenOptions::new().create(true).append(true).open(&path).ok()?;
+    tracing::subscriber::set_global_default(subscriber(level, format, Mutex::new(file))).ok()?;
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::Arc;
+
+    /// Collects everything the subscriber writes
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Log one warning and one trace event inside a session span
+    fn capture(level: LevelFilter, format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        tracing::subscriber::with_default(subscriber(level, format, buffer.clone()), || {
+            let span = tracing::info_span!("session", session_id = tracing::field::Empty);
+            let _entered = span.enter();
+            tracing::Span::current().record("session_id", "123abc");
+            tracing::warn!(error = "disk full", "capture failed");
+            tracing::trace!(bytes = 42, "pty write");
+        });
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level(Some("trace")), Some(LevelFilter::TRACE));
+        assert_eq!(parse_level(Some(" WARN ")), Some(LevelFilter::WARN));
+        assert_eq!(parse_level(Some("off")), None);
+        assert_eq!(parse_level(Some("")), None);
+        assert_eq!(parse_level(None), None);
+        assert_eq!(LogFormat::parse(Some("JSON")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+    }
+
+    #[test]
+    fn test_text_output_has_fields() {
+        let output = capture(LevelFilter::TRACE, LogFormat::Text);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", output);
+        assert!(lines[0].contains("WARN"));
+        assert!(lines[0].contains("session{session_id=\"123abc\"}"), "{}", lines[0]);
+        assert!(lines[0].contains("capture failed error=\"disk full\""), "{}", lines[0]);
+        assert!(lines[1].contains("TRACE"));
+        assert!(lines[1].contains("pty write bytes=42"), "{}", lines[1]);
+
+        // Below the level, events are dropped
+        let output = capture(LevelFilter::WARN, LogFormat::Text);
+        assert_eq!(output.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_json_output_has_fields() {
+        let output = capture(LevelFilter::TRACE, LogFormat::Json);
+        let events: Vec<serde_json::Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["level"], "WARN");
+        assert_eq!(events[0]["fields"]["message"], "capture failed");
+        assert_eq!(events[0]["fields"]["error"], "disk full");
+        assert_eq!(events[0]["span"]["session_id"], "123abc");
+        assert_eq!(events[1]["fields"]["bytes"], 42);
+    }
+}
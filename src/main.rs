@@ -1,27 +1,7 @@
-mod app;
-mod banner;
-mod capture;
-mod cli;
-mod cloud;
-mod config;
-mod git;
-mod hooks;
-mod ide;
-mod inspect;
-mod mirror;
-mod mode;
-mod parsers;
-mod platforms;
-mod terminal;
-mod ui;
-
-#[cfg(test)]
-mod fixtures_tests;
-
 use anyhow::Result;
 use crossterm::{
     cursor::Show,
-    event::{DisableBracketedPaste, EnableBracketedPaste},
+    event::{DisableBracketedPaste, DisableFocusChange, EnableBracketedPaste, EnableFocusChange},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, size as terminal_size},
 };
@@ -31,9 +11,16 @@ use std::panic;
 use std::sync::atomic::Ordering;
 use std::time::Instant;
 
-use crate::app::App;
-use crate::banner::{print_session_banner, print_session_end_line};
-use crate::cli::{parse_args, resolve_platform, Command, DebugTimer};
+use crabigator::app::{App, AppOptions, StatusBarEnabled};
+use crabigator::banner::{exit_message, print_session_banner, print_session_end_line};
+use crabigator::cli::{parse_args, resolve_platform, Command, DebugTimer};
+use crabigator::config::{platform_env_warnings, Config};
+use crabigator::platforms::PlatformError;
+use crabigator::terminal::SignalWatcher;
+use crabigator::{
+    cloud, completions, config, copy_stats, doctor, export, hooks, inspect, integrations, logging, platforms, replay,
+    search, summary, tags, terminal,
+};
 
 fn setup_terminal() -> Result<(u16, u16)> {
     let mut stdout = stdout();
@@ -45,12 +32,13 @@ fn setup_terminal() -> Result<(u16, u16)> {
 
     enable_raw_mode()?;
 
-    // Enable bracketed paste
+    // Enable bracketed paste, and focus events so we can idle in the background
     // Primary screen buffer (no alternate screen) - allows native scrollback
     // Disable mouse capture to allow native text selection
     execute!(
         stdout,
-        EnableBracketedPaste
+        EnableBracketedPaste,
+        EnableFocusChange
     )?;
 
     Ok((cols, rows))
@@ -69,6 +57,7 @@ fn restore_terminal(total_rows: u16) -> Result<()> {
     execute!(
         stdout,
         DisableBracketedPaste,
+        DisableFocusChange,
         Show
     )?;
     stdout.flush()?;
@@ -87,6 +76,7 @@ fn setup_panic_handler() {
         let _ = execute!(
             stdout,
             DisableBracketedPaste,
+            DisableFocusChange,
             Show
         );
         // Ensure we're on a fresh line
@@ -106,10 +96,61 @@ fn generate_session_id() -> String {
     format!("{:x}{:x}", pid, timestamp % 0xFFFFFFFF)
 }
 
+/// Explain a hook install failure in terms of what the user can do about it
+fn hook_error_message(error: &PlatformError) -> String {
+    match error {
+        PlatformError::InvalidConfig { path, reason } => format!(
+            "{} is invalid ({}). Crabigator won't overwrite it; fix the file by hand and restart.",
+            path.display(),
+            reason
+        ),
+        PlatformError::ScriptWriteFailed { path, source } => format!(
+            "could not write {} ({}). Check the file permissions on that path.",
+            path.display(),
+            source
+        ),
+        PlatformError::HookRegistrationFailed { .. } | PlatformError::VersionMismatch { .. } => {
+            format!("{}. Run `crabigator reinstall` to reset the hooks.", error)
+        }
+    }
+}
+
+/// Remove and reinstall the platform's hooks (`crabigator reinstall`)
+fn reinstall_hooks(kind: platforms::PlatformKind, dry_run: bool) -> Result<()> {
+    let platform = platforms::platform_for(kind);
+    let plan = platform.reinstall_plan()?;
+    if plan.is_empty() {
+        println!("{} has no hooks to reinstall.", kind.display_name());
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("Would reinstall {} hooks:", kind.display_name());
+        for step in &plan {
+            println!("  {}", step);
+        }
+        return Ok(());
+    }
+
+    platform.force_reinstall()?;
+    println!("Reinstalled {} hooks.", kind.display_name());
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = parse_args();
 
+    // config.toml is read once here. One that fails to parse falls back to
+    // defaults; values that parse but make no sense are reset one by one.
+    let (config, config_warnings) = match Config::load_with_warnings() {
+        Ok((config, warnings)) => {
+            let warnings = warnings.into_iter().map(|w| format!("config.toml: {}", w)).collect();
+            (config, warnings)
+        }
+        Err(e) => (Config::default(), vec![format!("{:#}; using default settings.", e)]),
+    };
+
     // Handle subcommands that don't need the full app setup
     match args.command {
         Command::Inspect {
@@ -117,14 +158,81 @@ async fn main() -> Result<()> {
             watch,
             raw,
             history,
+            summary,
+            diff,
+            ssh,
+        } => {
+            return inspect::run_inspect(dir_filter, watch, raw, history, summary, diff, ssh);
+        }
+        Command::Status { json } => {
+            return inspect::run_status(json);
+        }
+        Command::Reinstall { platform, dry_run } => {
+            // An explicit --platform here shouldn't change the saved default
+            let kind = platform.unwrap_or_else(|| resolve_platform(None, &config));
+            return reinstall_hooks(kind, dry_run);
+        }
+        Command::Search(options) => {
+            return search::run_search(options);
+        }
+        Command::Replay {
+            session_id,
+            file,
+            format,
+            speed,
+        } => {
+            return replay::run_replay(session_id, file, format, speed);
+        }
+        Command::Config(action) => {
+            return config::run_config(action);
+        }
+        Command::Completions(shell) => {
+            completions::run_completions(shell);
+            return Ok(());
+        }
+        Command::Summary { date, project } => {
+            return summary::run_summary(date, project);
+        }
+        Command::Tag {
+            session_id,
+            current,
+            name,
+            tags,
         } => {
-            return inspect::run_inspect(dir_filter, watch, raw, history);
+            return tags::run_tag(session_id, current, name, tags);
+        }
+        Command::Starship { config: print_config } => {
+            return integrations::starship::run_starship(print_config, &config);
+        }
+        Command::CopyStats { session_id } => {
+            return copy_stats::run_copy_stats(session_id);
+        }
+        Command::Diagram { session_id, format } => {
+            return export::run_diagram(&session_id, format);
+        }
+        Command::Queue { show } => {
+            return cloud::run_queue(show);
+        }
+        Command::Doctor { platform } => {
+            let platform = platform.unwrap_or_else(|| resolve_platform(None, &config));
+            return doctor::run_doctor(platform, &config, &config_warnings);
+        }
+        Command::ZellijPlugin { platform, install } => {
+            let platform = platform.unwrap_or_else(|| resolve_platform(None, &config));
+            #[cfg(feature = "zellij")]
+            return integrations::zellij::run_zellij_plugin(platform, install);
+            #[cfg(not(feature = "zellij"))]
+            {
+                let _ = (platform, install);
+                eprintln!("This build of crabigator doesn't include the zellij feature.");
+                std::process::exit(1);
+            }
         }
         Command::Run => {}
     }
 
     // Resolve platform from args, env, or config
-    let platform_kind = resolve_platform(args.platform);
+    let platform_kind = resolve_platform(args.platform, &config);
 
     // Generate and set session ID before anything else
     // This ensures the CLI and our stats loading use the same ID
@@ -135,34 +243,58 @@ async fn main() -> Result<()> {
         env::set_var("CRABIGATOR_PROFILE", "1");
     }
 
+    // Debug log (CRABIGATOR_LOG); every event carries the session ID
+    let log_path = logging::init(&session_id);
+    let _session_span = tracing::info_span!("session", session_id = tracing::field::Empty).entered();
+    tracing::Span::current().record("session_id", session_id.as_str());
+
     let timer = DebugTimer::new(args.profile);
 
+    // Widget colors render as RGB on true color terminals
+    let color_mode = terminal::color_detect::ColorMode::detect();
+    let theme = config.theme.resolve();
+    let palette = terminal::theme::Palette::new(theme, color_mode);
+
     timer.log("args parsed");
     timer.log(&format!("session_id={}", session_id));
     timer.log(&format!("platform={}", platform_kind.display_name()));
+    timer.log(&format!("color_mode={:?}", color_mode));
+    timer.log(&format!("theme={}", theme.name()));
+    if let Some(path) = &log_path {
+        timer.log(&format!("log={}", path.display()));
+    }
 
     // Install/update platform hooks in background thread (fire and forget)
     // Don't block startup - hooks will be ready by the time the CLI needs them
     {
         let timer = timer.clone();
+        let watch_hook_script = config.watch_hook_script;
         std::thread::spawn(move || {
             timer.hook_state.store(1, Ordering::SeqCst);
             let begin = Instant::now();
             timer.log("hook install started");
 
             let result = std::panic::catch_unwind(|| {
-                let platform = platforms::platform_for(platform_kind);
-                platform.ensure_hooks_installed()
+                platforms::platform_for(platform_kind).ensure_hooks_installed()
             });
 
             match result {
                 Ok(Ok(())) => {
                     timer.hook_state.store(2, Ordering::SeqCst);
                     timer.duration("hook install finished", begin.elapsed());
+                    // The watch runs for the rest of the session on its own thread
+                    if watch_hook_script {
+                        let spawned = std::thread::Builder::new()
+                            .name("hook-watch".into())
+                            .spawn(move || platforms::platform_for(platform_kind).watch_hook_script());
+                        if let Err(e) = spawned {
+                            tracing::warn!(error = %e, "failed to start the hook script watch");
+                        }
+                    }
                 }
                 Ok(Err(e)) => {
                     timer.hook_state.store(3, Ordering::SeqCst);
-                    timer.set_hook_error(e.to_string());
+                    timer.set_hook_error(hook_error_message(&e));
                     timer.duration("hook install failed", begin.elapsed());
                 }
                 Err(_) => {
@@ -179,25 +311,65 @@ async fn main() -> Result<()> {
     setup_panic_handler();
     timer.duration("setup panic handler", begin.elapsed());
 
+    for warning in &config_warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Extra environment for the CLI; warn about secrets while we can still print
+    let platform_env = config.platform_env(&args.env_overrides);
+    for warning in platform_env_warnings(&platform_env) {
+        eprintln!("{}", warning);
+    }
+
+    let status_bar = if args.status_bar { StatusBarEnabled::Yes } else { StatusBarEnabled::No };
+
     // Get terminal size and print session banner BEFORE raw mode
     let (cols, _) = terminal_size()?;
-    print_session_banner(&session_id, platform_kind, cols);
+    let resumed_from = hooks::previous_session_id();
+    print_session_banner(
+        &session_id,
+        platform_kind,
+        cols,
+        status_bar == StatusBarEnabled::No,
+        resumed_from.as_deref(),
+        &palette,
+    );
 
     let begin = Instant::now();
     let (cols, rows) = match setup_terminal() {
         Ok(size) => size,
         Err(e) => {
             let _ = disable_raw_mode();
-            let _ = execute!(stdout(), DisableBracketedPaste, Show);
+            let _ = execute!(stdout(), DisableBracketedPaste, DisableFocusChange, Show);
             return Err(e);
         }
     };
     timer.duration("setup terminal", begin.elapsed());
 
-    let (result, final_rows) = {
+    // Resizes crossterm misses, and hangups; stopped before the terminal is restored
+    let signal_watcher = match SignalWatcher::start() {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to watch SIGWINCH/SIGHUP");
+            None
+        }
+    };
+
+    let (result, final_rows, exit_status) = {
         let begin = Instant::now();
         let platform = platforms::platform_for(platform_kind);
-        let app_result = App::new(cols, rows, platform, args.platform_args, args.capture).await;
+        let options = AppOptions {
+            platform_args: args.platform_args,
+            capture: args.capture,
+            record: args.record,
+            tmux_status_file: args.tmux_status_file,
+            platform_env,
+            status_bar,
+            max_git_files: args.max_git_files,
+            diff_mode: args.diff_mode,
+            palette,
+        };
+        let app_result = App::new(cols, rows, platform, &config, options).await;
         timer.duration("App::new", begin.elapsed());
 
         match app_result {
@@ -208,26 +380,38 @@ async fn main() -> Result<()> {
                 let run_result = app.run().await;
                 timer.duration("app.run", begin.elapsed());
                 let total_rows = app.total_rows;
-                (run_result, total_rows)
+                (run_result, total_rows, app.exit_status.take())
             }
             Err(e) => {
+                if let Some(watcher) = signal_watcher {
+                    watcher.stop();
+                }
                 let _ = restore_terminal(rows);
                 return Err(e);
             }
         }
     };
 
+    if let Some(watcher) = signal_watcher {
+        watcher.stop();
+    }
     let begin = Instant::now();
     let _ = disable_raw_mode();
     let restore_result = restore_terminal(final_rows);
     timer.duration("restore terminal", begin.elapsed());
 
     if restore_result.is_err() {
-        let _ = execute!(stdout(), DisableBracketedPaste, Show);
+        let _ = execute!(stdout(), DisableBracketedPaste, DisableFocusChange, Show);
     }
+    terminal::exit_if_hung_up();
 
     println!();
 
+    // Why the assistant CLI stopped (nothing if the user quit crabigator first)
+    if let Some(exit) = &exit_status {
+        println!("{}", exit_message(exit, platform_kind));
+    }
+
     // Dump startup trace after terminal restore (to stdout, visible in scrollback)
     timer.dump();
 
@@ -249,7 +433,7 @@ async fn main() -> Result<()> {
 
     // Print session end line with platform and date (get fresh terminal width)
     let end_cols = terminal_size().map(|(c, _)| c).unwrap_or(cols);
-    print_session_end_line(platform_kind, end_cols);
+    print_session_end_line(platform_kind, end_cols, exit_status.as_ref(), &palette);
 
     result
 }
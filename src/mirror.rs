@@ -15,6 +15,8 @@ use serde::Serialize;
 use crate::git::GitState;
 use crate::hooks::SessionStats;
 use crate::parsers::{ChangeType, DiffSummary};
+use crate::system::ProcessStats;
+use crate::tags::TagStore;
 
 /// Minimum interval between publishes (1 second)
 const PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
@@ -30,11 +32,22 @@ pub struct WidgetMirror<T: Serialize> {
 #[derive(Serialize)]
 pub struct MirrorState {
     pub session_id: String,
+    /// PID of the crabigator process publishing this mirror
+    pub pid: u32,
     pub cwd: String,
+    /// Assistant platform ("claude", "codex")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Name given with `crabigator tag --name`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
     pub terminal_title: Option<String>,
     pub last_updated: f64,
     pub capture: CaptureMirror,
     pub launch_timing: LaunchTimingMirror,
+    /// Memory and hook cost (only with --profile)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourcesMirror>,
     pub widgets: MirrorWidgets,
 }
 
@@ -49,6 +62,18 @@ pub struct LaunchTimingMirror {
     pub diff_time_ms: Option<u64>,
 }
 
+/// Resource usage, shown by `inspect` as the debug HUD row
+#[derive(Serialize, Clone)]
+pub struct ResourcesMirror {
+    pub self_rss_kb: u64,
+    pub child_rss_kb: Option<u64>,
+    /// Average hook run time (ms)
+    pub hook_avg_ms: Option<f64>,
+    /// Estimated hook CPU time: tool calls × average hook run time (ms)
+    pub hook_cpu_ms: Option<f64>,
+    pub rendered: String,
+}
+
 /// Capture file info
 #[derive(Serialize, Clone)]
 pub struct CaptureMirror {
@@ -76,6 +101,15 @@ pub struct StatsMirrorData {
     pub completions: u32,
     pub tools: u32,
     pub compressions: u32,
+    pub paste_stats: PasteStatsMirror,
+}
+
+/// Aggregate size of text pasted into the prompt
+#[derive(Serialize)]
+pub struct PasteStatsMirror {
+    pub total_bytes: usize,
+    pub paste_count: usize,
+    pub largest_bytes: usize,
 }
 
 /// Simplified git data for JSON
@@ -120,11 +154,34 @@ pub struct ChangeMirror {
     pub line_number: Option<usize>,
 }
 
+/// Serialized mirror state waiting to be written, so the file I/O can run off
+/// the main loop
+pub struct MirrorWrite {
+    path: PathBuf,
+    json: String,
+}
+
+impl MirrorWrite {
+    /// Write the mirror file, creating the session directory if needed
+    pub fn write(self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Atomic write via temp file + rename
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, &self.json)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
 /// Publisher that handles throttled state mirroring
 pub struct MirrorPublisher {
     enabled: bool,
     session_id: String,
     cwd: String,
+    platform: Option<String>,
     capture: CaptureMirror,
     last_publish: Instant,
     last_hash: u64,
@@ -132,6 +189,16 @@ pub struct MirrorPublisher {
 }
 
 impl MirrorPublisher {
+    /// Point the published scrollback path at the capture file actually in use
+    pub fn set_scrollback_path(&mut self, path: std::path::PathBuf) {
+        self.capture.scrollback_path = path.to_string_lossy().to_string();
+    }
+
+    /// Record which assistant platform this session runs
+    pub fn set_platform(&mut self, platform: &str) {
+        self.platform = Some(platform.to_string());
+    }
+
     pub fn new(enabled: bool, session_id: String, cwd: String, capture_enabled: bool) -> Self {
         let session_dir = format!("/tmp/crabigator-{}", session_id);
         let capture = CaptureMirror {
@@ -145,6 +212,7 @@ impl MirrorPublisher {
             enabled,
             session_id,
             cwd,
+            platform: None,
             capture,
             // Allow immediate first publish
             last_publish: Instant::now() - Duration::from_secs(10),
@@ -163,8 +231,8 @@ impl MirrorPublisher {
         self.session_dir().join("inspect.json")
     }
 
-    /// Attempt to publish if conditions are met (enabled, changed, throttle elapsed)
-    /// Returns true if publish occurred
+    /// Serialize the state if conditions are met (enabled, changed, throttle
+    /// elapsed); the returned `MirrorWrite` puts it on disk
     pub fn maybe_publish(
         &mut self,
         stats: &SessionStats,
@@ -173,20 +241,21 @@ impl MirrorPublisher {
         terminal_title: Option<&str>,
         initial_git_time_ms: Option<u64>,
         initial_diff_time_ms: Option<u64>,
-    ) -> Result<bool> {
+    ) -> Result<Option<MirrorWrite>> {
         if !self.enabled {
-            return Ok(false);
+            return Ok(None);
         }
 
         // Check throttle
         if self.last_publish.elapsed() < PUBLISH_INTERVAL {
-            return Ok(false);
+            return Ok(None);
         }
 
         // Compute hash for change detection
-        let hash = self.compute_hash(stats, git, diff, terminal_title);
+        let project_name = TagStore::read(&self.session_dir()).and_then(|tags| tags.name);
+        let hash = self.compute_hash(stats, git, diff, terminal_title, project_name.as_deref());
         if hash == self.last_hash {
-            return Ok(false);
+            return Ok(None);
         }
 
         // Publish
@@ -195,29 +264,27 @@ impl MirrorPublisher {
             git_time_ms: initial_git_time_ms,
             diff_time_ms: initial_diff_time_ms,
         };
-        let state = self.build_state(stats, git, diff, terminal_title, launch_timing);
+        let state = self.build_state(stats, git, diff, terminal_title, project_name, launch_timing);
         let json = serde_json::to_string_pretty(&state)?;
 
-        // Ensure session directory exists
-        let session_dir = self.session_dir();
-        fs::create_dir_all(&session_dir)?;
-
-        // Atomic write via temp file + rename
-        let path = self.mirror_path();
-        let tmp_path = path.with_extension("tmp");
-        fs::write(&tmp_path, &json)?;
-        fs::rename(&tmp_path, &path)?;
-
         self.last_publish = Instant::now();
         self.last_hash = hash;
-        Ok(true)
+        Ok(Some(MirrorWrite { path: self.mirror_path(), json }))
     }
 
-    fn compute_hash(&self, stats: &SessionStats, git: &GitState, diff: &DiffSummary, terminal_title: Option<&str>) -> u64 {
+    fn compute_hash(
+        &self,
+        stats: &SessionStats,
+        git: &GitState,
+        diff: &DiffSummary,
+        terminal_title: Option<&str>,
+        project_name: Option<&str>,
+    ) -> u64 {
         let mut hasher = DefaultHasher::new();
 
-        // Hash terminal title
+        // Hash terminal title and session name
         terminal_title.hash(&mut hasher);
+        project_name.hash(&mut hasher);
 
         // Hash key fields from stats
         stats.work_seconds.hash(&mut hasher);
@@ -226,6 +293,7 @@ impl MirrorPublisher {
         stats.platform_stats.completions.hash(&mut hasher);
         stats.platform_stats.total_tool_calls().hash(&mut hasher);
         stats.platform_stats.compressions.hash(&mut hasher);
+        stats.paste_events.len().hash(&mut hasher);
         format!("{:?}", stats.platform_stats.state).hash(&mut hasher);
         stats.platform_stats.mode.as_str().hash(&mut hasher);
 
@@ -253,12 +321,28 @@ impl MirrorPublisher {
         hasher.finish()
     }
 
+    fn build_resources(stats: &SessionStats) -> Option<ResourcesMirror> {
+        if std::env::var("CRABIGATOR_PROFILE").is_err() {
+            return None;
+        }
+        let process = ProcessStats::current()?;
+        let hook_avg_ms = stats.platform_stats.hook_avg_ms();
+        Some(ResourcesMirror {
+            self_rss_kb: process.self_rss_kb,
+            child_rss_kb: process.child_rss_kb,
+            hook_avg_ms,
+            hook_cpu_ms: hook_avg_ms.map(|avg| avg * stats.platform_stats.total_tool_calls() as f64),
+            rendered: process.hud_line(hook_avg_ms),
+        })
+    }
+
     fn build_state(
         &self,
         stats: &SessionStats,
         git: &GitState,
         diff: &DiffSummary,
         terminal_title: Option<&str>,
+        project_name: Option<String>,
         launch_timing: LaunchTimingMirror,
     ) -> MirrorState {
         let timestamp = SystemTime::now()
@@ -268,11 +352,15 @@ impl MirrorPublisher {
 
         MirrorState {
             session_id: self.session_id.clone(),
+            pid: std::process::id(),
             cwd: self.cwd.clone(),
+            platform: self.platform.clone(),
+            project_name,
             terminal_title: terminal_title.map(String::from),
             last_updated: timestamp,
             capture: self.capture.clone(),
             launch_timing,
+            resources: Self::build_resources(stats),
             widgets: MirrorWidgets {
                 stats: WidgetMirror {
                     data: StatsMirrorData {
@@ -284,6 +372,11 @@ impl MirrorPublisher {
                         completions: stats.platform_stats.completions,
                         tools: stats.platform_stats.total_tool_calls(),
                         compressions: stats.platform_stats.compressions,
+                        paste_stats: PasteStatsMirror {
+                            total_bytes: stats.total_pasted_bytes(),
+                            paste_count: stats.paste_events.len(),
+                            largest_bytes: stats.largest_paste_bytes(),
+                        },
                     },
                     rendered: render_stats_preview(stats),
                 },
@@ -0,0 +1,226 @@
+//! Completion notifications
+//!
+//! When the assistant goes from thinking to complete, optionally ring the
+//! terminal bell or post a desktop notification (`notify_on_complete` in
+//! config.toml), so a long task finishing isn't missed while you're in
+//! another window. Kitty and WezTerm can post the notification themselves
+//! through OSC 777, which also works over SSH.
+
+use std::io::Write;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::hooks::SessionStats;
+use crate::terminal::escape;
+
+/// How to announce that the assistant finished
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyMethod {
+    /// Stay quiet
+    #[default]
+    None,
+    /// Ring the terminal bell (BEL)
+    Bell,
+    /// Desktop notification (osascript on macOS, notify-send on Linux)
+    System,
+    /// Desktop notification posted by the terminal (OSC 777); falls back to
+    /// `System` in terminals without it
+    Osc777,
+}
+
+/// Terminals known to handle `OSC 777 ; notify`
+const OSC777_TERM_PROGRAMS: [&str; 2] = ["kitty", "WezTerm"];
+
+/// Whether a terminal with this `TERM_PROGRAM` posts OSC 777 notifications
+fn supports_osc777(term_program: Option<&str>) -> bool {
+    term_program.is_some_and(|program| OSC777_TERM_PROGRAMS.contains(&program))
+}
+
+/// Sends the configured completion notification
+pub struct NotificationManager {
+    method: NotifyMethod,
+}
+
+impl NotificationManager {
+    pub fn new(method: NotifyMethod) -> Self {
+        let method = match method {
+            NotifyMethod::Osc777 if !supports_osc777(std::env::var("TERM_PROGRAM").ok().as_deref()) => {
+                NotifyMethod::System
+            }
+            method => method,
+        };
+        Self { method }
+    }
+
+    /// Announce a Thinking → Complete transition
+    ///
+    /// The bell and OSC 777 go to `out` (the real terminal); system
+    /// notifications are spawned in the background and failures are ignored.
+    pub fn notify_completion(&self, stats: &SessionStats, out: &mut dyn Write) -> Result<()> {
+        match self.method {
+            NotifyMethod::None => {}
+            NotifyMethod::Bell => {
+                out.write_all(b"\x07")?;
+                out.flush()?;
+            }
+            NotifyMethod::System => {
+                spawn_system_notification(NOTIFICATION_TITLE, &completion_body(stats));
+            }
+            NotifyMethod::Osc777 => {
+                out.write_all(escape::osc777_notify(NOTIFICATION_TITLE, &osc777_body(stats)).as_bytes())?;
+                out.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const NOTIFICATION_TITLE: &str = "Crabigator";
+
+/// `Task complete · session 12m · 37 tool calls`
+fn completion_body(stats: &SessionStats) -> String {
+    let tools = stats.platform_stats.total_tool_calls();
+    let plural = if tools == 1 { "" } else { "s" };
+    format!(
+        "Task complete · session {} · {} tool call{}",
+        stats.format_work(),
+        tools,
+        plural
+    )
+}
+
+/// `Task complete · 4 prompts · 4 completions · 37 tool calls`
+fn osc777_body(stats: &SessionStats) -> String {
+    let count = |n: u32, noun: &str| format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" });
+    let platform = &stats.platform_stats;
+    format!(
+        "Task complete · {} · {} · {}",
+        count(platform.prompts, "prompt"),
+        count(platform.completions, "completion"),
+        count(platform.total_tool_calls(), "tool call")
+    )
+}
+
+/// Quote `s` as an AppleScript string literal
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(target_os = "macos")]
+fn spawn_system_notification(title: &str, body: &str) {
+    let script = format!(
+        "display notification {} with title {}",
+        applescript_string(body),
+        applescript_string(title)
+    );
+    spawn_detached("osascript", vec!["-e".to_string(), script]);
+}
+
+#[cfg(target_os = "linux")]
+fn spawn_system_notification(title: &str, body: &str) {
+    spawn_detached("notify-send", vec![title.to_string(), body.to_string()]);
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn spawn_system_notification(_title: &str, _body: &str) {}
+
+/// Run `program` without blocking the main loop; tokio reaps it
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn spawn_detached(program: &'static str, args: Vec<String>) {
+    tokio::spawn(async move {
+        let _ = tokio::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(work_seconds: u64, tools: &[(&str, u32)]) -> SessionStats {
+        let mut stats = SessionStats::new();
+        stats.work_seconds = work_seconds;
+        for (tool, count) in tools {
+            stats.platform_stats.tools.insert(tool.to_string(), *count);
+        }
+        stats
+    }
+
+    #[test]
+    fn test_completion_body() {
+        assert_eq!(
+            completion_body(&stats(12 * 60 + 4, &[("Read", 30), ("Edit", 7)])),
+            "Task complete · session 12m · 37 tool calls"
+        );
+        assert_eq!(
+            completion_body(&stats(5, &[("Bash", 1)])),
+            "Task complete · session just now · 1 tool call"
+        );
+    }
+
+    #[test]
+    fn test_osc777_notification() {
+        let mut stats = stats(60, &[("Read", 30), ("Edit", 7)]);
+        stats.platform_stats.prompts = 4;
+        stats.platform_stats.completions = 1;
+        let manager = NotificationManager {
+            method: NotifyMethod::Osc777,
+        };
+        let mut out = Vec::new();
+        manager.notify_completion(&stats, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b]777;notify;Crabigator;Task complete · 4 prompts · 1 completion · 37 tool calls\x07"
+        );
+    }
+
+    #[test]
+    fn test_osc777_detection() {
+        assert!(supports_osc777(Some("kitty")));
+        assert!(supports_osc777(Some("WezTerm")));
+        assert!(!supports_osc777(Some("Apple_Terminal")));
+        assert!(!supports_osc777(None));
+    }
+
+    #[test]
+    fn test_applescript_quoting() {
+        assert_eq!(applescript_string("plain"), "\"plain\"");
+        assert_eq!(applescript_string(r#"say "hi" \ bye"#), r#""say \"hi\" \\ bye""#);
+    }
+
+    #[test]
+    fn test_bell_rings_terminal() {
+        let mut out = Vec::new();
+        NotificationManager::new(NotifyMethod::Bell)
+            .notify_completion(&stats(0, &[]), &mut out)
+            .unwrap();
+        assert_eq!(out, b"\x07");
+
+        let mut out = Vec::new();
+        NotificationManager::new(NotifyMethod::None)
+            .notify_completion(&stats(0, &[]), &mut out)
+            .unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_method_from_config() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            method: NotifyMethod,
+        }
+        let parse = |s: &str| toml::from_str::<Wrapper>(&format!("method = \"{}\"", s)).unwrap().method;
+        assert_eq!(parse("bell"), NotifyMethod::Bell);
+        assert_eq!(parse("system"), NotifyMethod::System);
+        assert_eq!(parse("osc777"), NotifyMethod::Osc777);
+        assert_eq!(parse("none"), NotifyMethod::None);
+    }
+}
@@ -2,20 +2,34 @@
 //!
 //! Parses git diffs to extract semantic information about code changes.
 
+mod cache;
+mod css;
 mod generic;
+mod hunk;
+mod json;
 mod objc;
+#[cfg(test)]
+mod proptests;
 mod python;
 mod rust;
 mod summary;
 mod swift;
 mod types;
 mod typescript;
+mod yaml;
 
+pub use cache::DiffCache;
+pub use css::CssParser;
 pub use generic::GenericParser;
+pub use json::JsonParser;
 pub use objc::ObjCParser;
 pub use python::PythonParser;
 pub use rust::RustParser;
-pub use summary::{DiffParser, DiffSummary};
+pub use summary::{DiffMode, DiffParser, DiffSummary, DEFAULT_MAX_DIFF_BYTES};
+pub use summary::parse_diff_into_files;
 pub use swift::SwiftParser;
 pub use types::{ChangeNode, ChangeType, LanguageChanges, NodeKind};
+#[cfg(test)]
+pub use types::FileChanges;
 pub use typescript::TypeScriptParser;
+pub use yaml::YamlParser;
@@ -0,0 +1,135 @@
+//! Cache of the last computed diff summary
+//!
+//! Git refreshes fire every few seconds and whenever `.git` changes, but the
+//! diff itself usually hasn't moved. Running every language parser over an
+//! unchanged diff is wasted work, so the summary is keyed by a hash of the raw
+//! `git diff` output and only reparsed when that hash changes.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use super::summary::read_diff;
+use super::{DiffMode, DiffSummary, DEFAULT_MAX_DIFF_BYTES};
+
+/// Last diff hash and its summary, shared by cloning
+#[derive(Clone)]
+pub struct DiffCache {
+    last: Arc<Mutex<Option<(u64, DiffSummary)>>>,
+    /// Bytes of diff parsed per refresh (config `max_diff_bytes`)
+    max_diff_bytes: usize,
+}
+
+impl DiffCache {
+    pub fn new(max_diff_bytes: usize) -> Self {
+        Self {
+            last: Arc::default(),
+            max_diff_bytes,
+        }
+    }
+
+    /// Summarize the working tree of `dir`, reusing the cached summary when
+    /// the diff is unchanged
+    ///
+    /// Returns the summary and whether it was recomputed.
+    pub async fn refresh(
+        &self,
+        dir: &Path,
+        mode: DiffMode,
+        session_start_commit: Option<&str>,
+    ) -> Result<(DiffSummary, bool)> {
+        let Some(combined_diff) = read_diff(dir, mode, session_start_commit).await? else {
+            return Ok((DiffSummary::default(), false));
+        };
+        Ok(self.summarize(&combined_diff))
+    }
+
+    /// Reparse only the files whose diff changed since `previous` (see
+    /// `DiffSummary::refresh_incremental`); the cache is left as it is
+    pub async fn refresh_incremental(
+        &self,
+        dir: &Path,
+        previous: &DiffSummary,
+        mode: DiffMode,
+        session_start_commit: Option<&str>,
+    ) -> Result<DiffSummary> {
+        DiffSummary::refresh_incremental(dir, previous, mode, session_start_commit, self.max_diff_bytes).await
+    }
+
+    /// Summary for `combined_diff`, parsing only on a cache miss
+    fn summarize(&self, combined_diff: &str) -> (DiffSummary, bool) {
+        let hash = hash_diff(combined_diff);
+        let mut last = self.last.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some((cached_hash, summary)) = last.as_ref() {
+            if *cached_hash == hash {
+                return (summary.clone(), false);
+            }
+        }
+
+        let summary = DiffSummary::from_diff(combined_diff, self.max_diff_bytes);
+        *last = Some((hash, summary.clone()));
+        (summary, true)
+    }
+}
+
+impl Default for DiffCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DIFF_BYTES)
+    }
+}
+
+fn hash_diff(diff: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    diff.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,7 @@
+ pub fn add(a: i32, b: i32) -> i32 {
+     a + b
+ }
++
++pub fn sub(a: i32, b: i32) -> i32 {
++    a - b
++}
+";
+
+    #[test]
+    fn test_unchanged_diff_is_a_hit() {
+        let cache = DiffCache::default();
+        let (first, recomputed) = cache.summarize(DIFF);
+        assert!(recomputed);
+        assert_eq!(first.files.len(), 1);
+
+        let (second, recomputed) = cache.summarize(DIFF);
+        assert!(!recomputed);
+        assert_eq!(second.files.len(), first.files.len());
+        assert_eq!(second.files[0].path, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_changed_diff_is_a_miss() {
+        let cache = DiffCache::default();
+        assert!(cache.summarize(DIFF).1);
+
+        let (summary, recomputed) = cache.summarize(&DIFF.replace("a - b", "b - a"));
+        assert!(recomputed);
+        assert_eq!(summary.files.len(), 1);
+
+        // Clones share the cache, so the background task's result is reused
+        let clone = cache.clone();
+        assert!(!clone.summarize(&DIFF.replace("a - b", "b - a")).1);
+        assert!(clone.summarize("").1);
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use super::hunk::HunkLineTracker;
+use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
+
+pub struct CssParser;
+
+impl DiffParser for CssParser {
+    fn language(&self) -> &'static str {
+        "CSS/SCSS"
+    }
+
+    fn supports(&self, filename: &str) -> bool {
+        filename.ends_with(".css") || filename.ends_with(".scss")
+    }
+
+    fn extract_function_from_context(&self, context: &str) -> Option<String> {
+        // CSS hunk context is the enclosing rule: ".card > .title {" or "@mixin button($color) {"
+        block_declaration(context).map(|(_, name)| name)
+    }
+
+    fn parse(&self, diff: &str, filename: &str) -> Vec<ChangeNode> {
+        let file_path = Some(filename.to_string());
+        // Key: (kind, name), Value: (change_type, additions, deletions)
+        let mut change_map: HashMap<(NodeKind, String), (ChangeType, usize, usize)> = HashMap::new();
+        // New-file line of each entry's first changed line
+        let mut first_lines: HashMap<(NodeKind, String), usize> = HashMap::new();
+        let mut lines = HunkLineTracker::new();
+
+        // Open blocks in the new file, innermost last. Removed lines don't
+        // change nesting since they aren't part of the file we're tracking.
+        let mut scope: Vec<(NodeKind, String)> = Vec::new();
+
+        for line in diff.lines() {
+            // Check for hunk headers with rule context
+            if let Some(context) = lines.header(line) {
+                scope.clear();
+                if let Some(key) = block_declaration(context) {
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    scope.push(key);
+                }
+                continue;
+            }
+
+            let is_added = line.starts_with('+') && !line.starts_with("+++");
+            let is_removed = line.starts_with('-') && !line.starts_with("---");
+            let is_context = line.starts_with(' ');
+            let line_number = lines.advance(line);
+
+            if !is_added && !is_removed && !is_context {
+                continue;
+            }
+
+            let content = &line[1..];
+            let declared = block_declaration(content);
+
+            if is_added || is_removed {
+                if let Some(ref key) = declared {
+                    let entry = change_map.entry(key.clone()).or_insert((
+                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
+                        0,
+                        0,
+                    ));
+                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
+                } else if let Some(key) = scope.last() {
+                    // Declarations and closing braces count toward the enclosing rule
+                    let entry = change_map
+                        .entry(key.clone())
+                        .or_insert((ChangeType::Modified, 0, 0));
+                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
+                }
+            }
+
+            if is_removed {
+                continue;
+            }
+
+            // Track nesting from the new file's braces
+            if let Some(key) = declared {
+                scope.push(key);
+            }
+            let closes = content.matches('}').count();
+            let opens = content.matches('{').count().saturating_sub(1);
+            for _ in opens..closes {
+                scope.pop();
+            }
+        }
+
+        change_map
+            .into_iter()
+            .map(|((kind, name), (change_type, additions, deletions))| ChangeNode {
+                line_number: first_lines.get(&(kind.clone(), name.clone())).copied(),
+                kind,
+                name,
+                change_type,
+                additions,
+                deletions,
+                file_path: file_path.clone(),
+                children: Vec::new(),
+            })
+            .filter(ChangeNode::has_changed_lines)
+            .collect()
+    }
+}
+
+/// Classify a line that opens a block: selector, `@media`, or SCSS `@mixin`/`@function`
+fn block_declaration(content: &str) -> Option<(NodeKind, String)> {
+    let (head, _) = content.split_once('{')?;
+    // Interpolation like "#{$prefix}-card" isn't a block opening
+    if head.ends_with('#') {
+        return None;
+    }
+    let head = head.split_whitespace().collect::<Vec<_>>().join(" ");
+    if head.is_empty() {
+        return None;
+    }
+
+    if let Some(rest) = head.strip_prefix('@') {
+        let (keyword, args) = rest.split_once(' ').unwrap_or((rest, ""));
+        return match keyword {
+            "mixin" | "function" => {
+                let name = args.split('(').next().unwrap_or(args).trim();
+                Some((NodeKind::Function, name.to_string()))
+            }
+            "media" => Some((NodeKind::MediaQuery, head)),
+            _ => Some((NodeKind::Other, head)),
+        };
+    }
+
+    // SCSS nested properties ("font: { family: serif; }") aren't rules
+    if head.ends_with(':') {
+        return None;
+    }
+    Some((NodeKind::Selector, head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+
+    #[test]
+    fn test_selector_and_media_changes() {
+        let diff = "\
+@@ -1,9 +1,10 @@
+ .card {
+-  padding: 4px;
++  padding: 8px;
+ }
+ @media (max-width: 600px) {
+   .card {
++    padding: 2px;
+   }
+ }
++.card > .title { font-weight: bold; }
+";
+        let changes = CssParser.parse(diff, "styles.css");
+        let card = find(&changes, ".card");
+        assert_eq!(card.kind, NodeKind::Selector);
+        assert_eq!(card.additions, 2);
+        assert_eq!(card.deletions, 1);
+        assert_eq!(card.line_number, Some(2));
+        assert!(!changes.iter().any(|c| c.kind == NodeKind::MediaQuery));
+        let title = find(&changes, ".card > .title");
+        assert_eq!(title.change_type, ChangeType::Added);
+        assert_eq!(title.line_number, Some(9));
+    }
+
+    #[test]
+    fn test_scss_mixins_and_functions() {
+        let diff = "\
+@@ -1,6 +1,7 @@
++@mixin button($color) {
++  background: $color;
++}
+ @function rem($px) {
+-  @return $px / 16px * 1rem;
++  @return math.div($px, 16px) * 1rem;
+ }
+";
+        let changes = CssParser.parse(diff, "theme.scss");
+        let button = find(&changes, "button");
+        assert_eq!(button.kind, NodeKind::Function);
+        assert_eq!(button.change_type, ChangeType::Added);
+        let rem = find(&changes, "rem");
+        assert_eq!(rem.kind, NodeKind::Function);
+        assert_eq!(rem.additions, 1);
+        assert_eq!(rem.deletions, 1);
+    }
+
+    #[test]
+    fn test_media_query_in_hunk_header() {
+        let diff = "\
+@@ -10,3 +10,3 @@ @media (prefers-color-scheme: dark) {
+-  body { color: white; }
++  body { color: #eee; }
+";
+        let changes = CssParser.parse(diff, "dark.css");
+        let body = find(&changes, "body");
+        assert_eq!(body.kind, NodeKind::Selector);
+        // The enclosing query only provides context; it has no changed lines of its own
+        assert!(!changes.iter().any(|c| c.kind == NodeKind::MediaQuery));
+    }
+}
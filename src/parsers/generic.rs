@@ -1,4 +1,7 @@
-use super::{ChangeNode, DiffParser};
+use std::collections::HashMap;
+
+use super::hunk::HunkLineTracker;
+use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
 
 pub struct GenericParser;
 
@@ -17,10 +20,78 @@ impl DiffParser for GenericParser {
         None
     }
 
-    fn parse(&self, _diff: &str, _filename: &str) -> Vec<ChangeNode> {
-        // Generic parser returns nothing - semantic changes are only
-        // meaningful for files with language-specific parsers.
-        // Line counts are already shown in the git widget.
-        Vec::new()
+    fn parse(&self, diff: &str, filename: &str) -> Vec<ChangeNode> {
+        // Without a grammar the only structure is the line git puts after a
+        // hunk's `@@` (its funcname heuristic). Hunks without one report
+        // nothing; line counts are already shown in the git widget.
+        let file_path = Some(filename.to_string());
+        // Context -> (additions, deletions, first changed line)
+        let mut change_map: HashMap<String, (usize, usize, usize)> = HashMap::new();
+        let mut lines = HunkLineTracker::new();
+        let mut current_context: Option<String> = None;
+
+        for line in diff.lines() {
+            if let Some(context) = lines.header(line) {
+                let context = context.trim();
+                current_context = (!context.is_empty()).then(|| context.to_string());
+                continue;
+            }
+
+            let is_added = line.starts_with('+') && !line.starts_with("+++");
+            let is_removed = line.starts_with('-') && !line.starts_with("---");
+            let line_number = lines.advance(line);
+
+            if !is_added && !is_removed {
+                continue;
+            }
+
+            if let Some(ref context) = current_context {
+                let entry = change_map.entry(context.clone()).or_insert((0, 0, line_number));
+                if is_added { entry.0 += 1; } else { entry.1 += 1; }
+            }
+        }
+
+        change_map
+            .into_iter()
+            .map(|(name, (additions, deletions, line_number))| ChangeNode {
+                kind: NodeKind::Other,
+                name,
+                change_type: ChangeType::Modified,
+                additions,
+                deletions,
+                file_path: file_path.clone(),
+                line_number: Some(line_number),
+                children: Vec::new(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hunk_context_changes_carry_line_numbers() {
+        let diff = "\
+--- a/notes.txt
++++ b/notes.txt
+@@ -3,4 +3,5 @@ Installation
+ Run the installer.
+-Then restart.
++Then log out
++and back in.
+ Done.
+@@ -40,2 +41,2 @@
+-old footer
++new footer
+";
+        let changes = GenericParser.parse(diff, "notes.txt");
+        assert_eq!(changes.len(), 1);
+        let install = &changes[0];
+        assert_eq!(install.name, "Installation");
+        assert_eq!(install.line_number, Some(4));
+        assert_eq!((install.additions, install.deletions), (2, 1));
+        assert_eq!(install.file_path.as_deref(), Some("notes.txt"));
     }
 }
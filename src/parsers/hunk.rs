@@ -0,0 +1,89 @@
+//! Hunk headers and new-file line numbers
+//!
+//! `@@ -12,4 +12,6 @@ fn context()` starts a hunk; the `+12` is the new-file
+//! line of its first added or context line. Parsers that report where a
+//! symbol is use `HunkLineTracker` to carry that number through the hunk.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Captures: 1=new_line_start, 2=context
+static HUNK_HEADER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^@@ -\d+(?:,\d+)? \+(\d+)(?:,\d+)? @@\s*(.*)$").unwrap());
+
+/// New-file start line and context (the text after the closing `@@`) of a
+/// hunk header, or None when `line` isn't one
+pub fn parse_hunk_header(line: &str) -> Option<(usize, &str)> {
+    let caps = HUNK_HEADER_RE.captures(line)?;
+    let start = caps[1].parse().unwrap_or(1);
+    let context = caps.get(2).map_or("", |m| m.as_str());
+    Some((start, context))
+}
+
+/// New-file line numbers through the hunks of one file's diff
+pub struct HunkLineTracker {
+    /// Line number in the new file of the next added or context line
+    next_line: usize,
+}
+
+impl HunkLineTracker {
+    pub fn new() -> Self {
+        Self { next_line: 1 }
+    }
+
+    /// If `line` is a hunk header, restart numbering at its new-file start
+    /// and return its context
+    pub fn header<'a>(&mut self, line: &'a str) -> Option<&'a str> {
+        let (start, context) = parse_hunk_header(line)?;
+        self.next_line = start;
+        Some(context)
+    }
+
+    /// New-file line of a body line, moving past it. Removed lines sit where
+    /// the next new-file line would be.
+    pub fn advance(&mut self, line: &str) -> usize {
+        let line_number = self.next_line;
+        let is_added = line.starts_with('+') && !line.starts_with("+++");
+        if is_added || line.starts_with(' ') {
+            self.next_line += 1;
+        }
+        line_number
+    }
+
+    /// Line the next added or context line will get
+    pub fn next_line(&self) -> usize {
+        self.next_line
+    }
+}
+
+impl Default for HunkLineTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hunk_header() {
+        assert_eq!(parse_hunk_header("@@ -1,3 +4,5 @@ fn main() {"), Some((4, "fn main() {")));
+        assert_eq!(parse_hunk_header("@@ -1 +1 @@"), Some((1, "")));
+        assert_eq!(parse_hunk_header("+@@ -1 +1 @@"), None);
+    }
+
+    #[test]
+    fn test_tracker_numbers_new_file_lines() {
+        let mut lines = HunkLineTracker::new();
+        assert_eq!(lines.header("@@ -10,3 +20,4 @@ class Foo:"), Some("class Foo:"));
+        assert_eq!(lines.advance(" context"), 20);
+        assert_eq!(lines.advance("-removed"), 21);
+        assert_eq!(lines.advance("+added"), 21);
+        assert_eq!(lines.advance("+added"), 22);
+        assert_eq!(lines.next_line(), 23);
+        assert_eq!(lines.header("@@ -40 +50 @@"), Some(""));
+        assert_eq!(lines.advance("+added"), 50);
+    }
+}
@@ -0,0 +1,297 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::types::Tally;
+use super::{ChangeNode, DiffParser, NodeKind};
+
+pub struct JsonParser;
+
+impl DiffParser for JsonParser {
+    fn language(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn supports(&self, filename: &str) -> bool {
+        filename.ends_with(".json") || filename.ends_with(".jsonc")
+    }
+
+    fn extract_function_from_context(&self, _context: &str) -> Option<String> {
+        // Git's default hunk context never matches a quoted key
+        None
+    }
+
+    fn parse(&self, diff: &str, filename: &str) -> Vec<ChangeNode> {
+        let key_re = Regex::new(r#"^(\s*)"((?:[^"\\]|\\.)*)"\s*:"#).unwrap();
+        let unit = indent_unit(diff, &key_re);
+
+        // Top-level keys, each with its nested `parent.child` paths
+        let mut keys: HashMap<String, (Tally, HashMap<String, Tally>)> = HashMap::new();
+        // Nested keys whose top-level parent is outside the hunk
+        let mut orphans: HashMap<String, Tally> = HashMap::new();
+
+        // Open keys as (depth, name) in the old and new file, outermost first.
+        // Depth comes from indentation, so hunks starting mid-object still nest.
+        let mut old: Vec<(usize, String)> = Vec::new();
+        let mut new: Vec<(usize, String)> = Vec::new();
+
+        for line in diff.lines() {
+            if line.starts_with("@@") {
+                old.clear();
+                new.clear();
+                continue;
+            }
+
+            let is_added = line.starts_with('+') && !line.starts_with("+++");
+            let is_removed = line.starts_with('-') && !line.starts_with("---");
+            let is_context = line.starts_with(' ');
+            if !is_added && !is_removed && !is_context {
+                continue;
+            }
+
+            let content = &line[1..];
+            let trimmed = content.trim();
+            // JSONC comment lines only count toward the enclosing key
+            let is_comment = trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*');
+            let declared = if is_comment {
+                None
+            } else {
+                key_re.captures(content).map(|caps| {
+                    let depth = (caps[1].len() / unit).max(1);
+                    (depth, caps[2].to_string())
+                })
+            };
+
+            if !is_context {
+                let path = if is_added { &new } else { &old };
+                let line_path: Vec<(usize, String)> = match &declared {
+                    Some((depth, key)) => path
+                        .iter()
+                        .filter(|(d, _)| d < depth)
+                        .cloned()
+                        .chain([(*depth, key.clone())])
+                        .collect(),
+                    // Closing brackets still belong to the container they close
+                    None => path.clone(),
+                };
+                record(&mut keys, &mut orphans, &line_path, is_added, declared.is_some());
+            }
+
+            for (path, applies) in [(&mut old, !is_added), (&mut new, !is_removed)] {
+                if !applies {
+                    continue;
+                }
+                if let Some((depth, key)) = &declared {
+                    path.retain(|(d, _)| d < depth);
+                    if opens_container(trimmed) {
+                        path.push((*depth, key.clone()));
+                    }
+                } else if !is_comment && closes_container(trimmed) {
+                    let depth = (content.len() - content.trim_start().len()) / unit;
+                    path.retain(|(d, _)| *d < depth);
+                }
+            }
+        }
+
+        let file_path = Some(filename.to_string());
+        let node = |kind: NodeKind, name: String, tally: &Tally, children: Vec<ChangeNode>| ChangeNode {
+            kind,
+            name,
+            change_type: tally.change_type(),
+            additions: tally.additions,
+            deletions: tally.deletions,
+            file_path: file_path.clone(),
+            line_number: None,
+            children,
+        };
+
+        let mut changes: Vec<ChangeNode> = keys
+            .into_iter()
+            .map(|(key, (tally, nested))| {
+                let mut children: Vec<ChangeNode> = nested
+                    .iter()
+                    .map(|(name, child)| node(NodeKind::Module, name.clone(), child, Vec::new()))
+                    .collect();
+                children.sort_by(|a, b| a.name.cmp(&b.name));
+                node(NodeKind::Const, key, &tally, children)
+            })
+            .collect();
+        changes.extend(
+            orphans
+                .into_iter()
+                .map(|(name, tally)| node(NodeKind::Module, name, &tally, Vec::new())),
+        );
+        changes
+    }
+}
+
+/// Attribute a changed line to its top-level key and `parent.child` path
+///
+/// `path` is the line's enclosing keys (ending with the key it declares, if
+/// any); `declares` is true when the line itself declares the last key.
+fn record(
+    keys: &mut HashMap<String, (Tally, HashMap<String, Tally>)>,
+    orphans: &mut HashMap<String, Tally>,
+    path: &[(usize, String)],
+    is_added: bool,
+    declares: bool,
+) {
+    let Some((depth, top)) = path.first() else {
+        return;
+    };
+    if *depth > 1 {
+        // The top-level key is outside the hunk; report the nested key alone
+        orphans.entry(top.clone()).or_default().count(is_added, declares && path.len() == 1);
+        return;
+    }
+
+    let (tally, nested) = keys.entry(top.clone()).or_default();
+    tally.count(is_added, declares && path.len() == 1);
+    if let Some((_, child)) = path.get(1) {
+        nested
+            .entry(format!("{}.{}", top, child))
+            .or_default()
+            .count(is_added, declares && path.len() == 2);
+    }
+}
+
+fn opens_container(trimmed: &str) -> bool {
+    let code = trimmed.split(" //").next().unwrap_or(trimmed).trim_end();
+    code.ends_with('{') || code.ends_with('[')
+}
+
+fn closes_container(trimmed: &str) -> bool {
+    trimmed.starts_with('}') || trimmed.starts_with(']')
+}
+
+/// Spaces per nesting level
+///
+/// Learned from the first key nested under an opener, or the first closing
+/// bracket dedented from the key before it; defaults to 2.
+fn indent_unit(diff: &str, key_re: &Regex) -> usize {
+    let mut opener: Option<usize> = None;
+    let mut last_key: Option<usize> = None;
+    for line in diff.lines() {
+        let Some(content) = line.get(1..) else {
+            continue;
+        };
+        let trimmed = content.trim();
+        let indent = content.len() - content.trim_start().len();
+
+        if key_re.is_match(content) {
+            if let Some(parent) = opener.take() {
+                if indent > parent {
+                    return indent - parent;
+                }
+            }
+            if opens_container(trimmed) {
+                opener = Some(indent);
+            }
+            last_key = Some(indent);
+        } else if closes_container(trimmed) {
+            if let Some(key) = last_key.filter(|key| *key > indent) {
+                return key - indent;
+            }
+        }
+    }
+    2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+    use crate::parsers::ChangeType;
+
+    #[test]
+    fn test_package_json_dependency_additions() {
+        let diff = r#"@@ -1,12 +1,14 @@
+ {
+   "name": "web",
+-  "version": "1.2.0",
++  "version": "1.3.0",
+   "dependencies": {
+-    "react": "^18.2.0",
++    "react": "^18.3.1",
++    "lodash": "^4.17.21",
+     "zod": "^3.22.0"
+   },
++  "engines": {
++    "node": ">=20"
++  },
+   "scripts": {
+"#;
+        let changes = JsonParser.parse(diff, "package.json");
+
+        let version = find(&changes, "version");
+        assert_eq!(version.kind, NodeKind::Const);
+        assert_eq!(version.change_type, ChangeType::Modified);
+
+        let deps = find(&changes, "dependencies");
+        assert_eq!(deps.change_type, ChangeType::Modified);
+        assert_eq!((deps.additions, deps.deletions), (2, 1));
+        let lodash = find(&deps.children, "dependencies.lodash");
+        assert_eq!(lodash.kind, NodeKind::Module);
+        assert_eq!(lodash.change_type, ChangeType::Added);
+        assert_eq!(find(&deps.children, "dependencies.react").change_type, ChangeType::Modified);
+        assert_eq!(deps.children.len(), 2);
+
+        let engines = find(&changes, "engines");
+        assert_eq!(engines.change_type, ChangeType::Added);
+        assert_eq!(engines.additions, 3);
+        assert_eq!(find(&engines.children, "engines.node").change_type, ChangeType::Added);
+
+        assert!(!changes.iter().any(|c| c.name == "name" || c.name == "scripts"));
+    }
+
+    #[test]
+    fn test_four_space_indent_and_orphaned_keys() {
+        // The hunk starts inside "compilerOptions", so its parent is unknown
+        let diff = r#"@@ -20,6 +20,7 @@
+         "strict": true,
+-        "target": "es2020",
++        "target": "es2022",
++        "noEmit": true,
+     },
+     "include": [
+-        "src"
++        "src",
++        "tests"
+     ]
+"#;
+        let changes = JsonParser.parse(diff, "tsconfig.json");
+        // The indent unit is learned from the dedented closing brace
+        let target = find(&changes, "target");
+        assert_eq!(target.kind, NodeKind::Module);
+        assert_eq!(target.change_type, ChangeType::Modified);
+        assert_eq!(find(&changes, "noEmit").change_type, ChangeType::Added);
+
+        let include = find(&changes, "include");
+        assert_eq!(include.kind, NodeKind::Const);
+        assert_eq!((include.additions, include.deletions), (2, 1));
+        assert!(include.children.is_empty());
+    }
+
+    #[test]
+    fn test_jsonc_comments_count_toward_their_key() {
+        let diff = r#"@@ -1,5 +1,6 @@
+ {
+   "editor.formatOnSave": true,
+   "files.exclude": {
++    // Build output
+     "**/target": true // rust
+   }
+"#;
+        let changes = JsonParser.parse(diff, ".vscode/settings.jsonc");
+        let exclude = find(&changes, "files.exclude");
+        assert_eq!(exclude.change_type, ChangeType::Modified);
+        assert_eq!(exclude.additions, 1);
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn test_supports_json_and_jsonc() {
+        assert!(JsonParser.supports("package.json"));
+        assert!(JsonParser.supports("tsconfig.jsonc"));
+        assert!(!JsonParser.supports("data.jsonl"));
+    }
+}
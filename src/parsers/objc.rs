@@ -1,6 +1,7 @@
 use regex::Regex;
 use std::collections::HashMap;
 
+use super::hunk::HunkLineTracker;
 use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
 
 pub struct ObjCParser;
@@ -19,64 +20,74 @@ impl DiffParser for ObjCParser {
     fn extract_function_from_context(&self, context: &str) -> Option<String> {
         // Objective-C hunk context patterns:
         // "- (void)methodName" or "+ (id)classMethod:"
-        // "@interface ClassName" or "@implementation ClassName"
-        let method_re = Regex::new(r"^[-+]\s*\([^)]+\)\s*(\w+)").unwrap();
-        let interface_re = Regex::new(r"@interface\s+(\w+)").unwrap();
-        let impl_re = Regex::new(r"@implementation\s+(\w+)").unwrap();
+        // "@interface ClassName" or "@implementation ClassName (Category)"
+        // "[self methodName:argument]" message sends
+        let interface_re = Regex::new(r"@interface\s+(\w+)(?:\s*\(\s*(\w+)\s*\))?").unwrap();
+        let impl_re = Regex::new(r"@implementation\s+(\w+)(?:\s*\(\s*(\w+)\s*\))?").unwrap();
         let protocol_re = Regex::new(r"@protocol\s+(\w+)").unwrap();
 
-        if let Some(caps) = method_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
+        if let Some(selector) = method_selector(context) {
+            return Some(selector);
         }
         if let Some(caps) = interface_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
+            return Some(category_name(&caps));
         }
         if let Some(caps) = impl_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
+            return Some(category_name(&caps));
         }
         if let Some(caps) = protocol_re.captures(context) {
             return caps.get(1).map(|m| m.as_str().to_string());
         }
+        if let Some(selector) = message_send_selector(context) {
+            return Some(selector);
+        }
         None
     }
 
     fn parse(&self, diff: &str, filename: &str) -> Vec<ChangeNode> {
         let file_path = Some(filename.to_string());
         let mut change_map: HashMap<(NodeKind, String), (ChangeType, usize, usize)> = HashMap::new();
+        // New-file line of each entry's first changed line
+        let mut first_lines: HashMap<(NodeKind, String), usize> = HashMap::new();
+        let mut lines = HunkLineTracker::new();
 
         // Regex patterns for Objective-C constructs
-        // Method: - (returnType)methodName or + (returnType)methodName
-        let method_re = Regex::new(r"^\s*[-+]\s*\([^)]+\)\s*(\w+)").unwrap();
-        let interface_re = Regex::new(r"^\s*@interface\s+(\w+)").unwrap();
-        let impl_re = Regex::new(r"^\s*@implementation\s+(\w+)").unwrap();
-        let protocol_re = Regex::new(r"^\s*@protocol\s+(\w+)").unwrap();
-        let hunk_re = Regex::new(r"^@@[^@]+@@\s*(.*)$").unwrap();
+        // Methods ("- (returnType)name:(type)arg") are handled by method_selector()
+        // @interface Foo, @interface Foo (Category), @interface Foo () class extensions
+        let interface_re = Regex::new(r"^\s*@interface\s+(\w+)(?:\s*\(\s*(\w+)?\s*\))?").unwrap();
+        let impl_re = Regex::new(r"^\s*@implementation\s+(\w+)(?:\s*\(\s*(\w+)\s*\))?").unwrap();
+        // Forward declarations ("@protocol Foo;") aren't definitions
+        let protocol_re = Regex::new(r"^\s*@protocol\s+(\w+)\s*(?:<[^>]*>)?\s*$").unwrap();
+        let property_re = Regex::new(r"^\s*@property\b").unwrap();
+        // C function definitions start at column 0: "static NSString *MakeKey(id obj) {"
+        let c_function_re =
+            Regex::new(r"^(?:(?:static|inline|extern|const)\s+)*[A-Za-z_][\w<>]*[\s\*]+\**(\w+)\s*\([^;]*$").unwrap();
+        let swift_name_re = Regex::new(r"NS_SWIFT_NAME\s*\(([^)]*(?:\)[^)]*)?)\)").unwrap();
 
         let mut current_context: Option<(NodeKind, String)> = None;
+        // NS_SWIFT_NAME annotations changed per symbol: name -> (added, removed)
+        let mut swift_names: HashMap<(NodeKind, String), HashMap<String, (usize, usize)>> = HashMap::new();
 
         for line in diff.lines() {
             // Check for hunk headers with function context
-            if let Some(caps) = hunk_re.captures(line) {
-                if let Some(context) = caps.get(1) {
-                    let context_str = context.as_str();
-                    if let Some(method_caps) = method_re.captures(context_str) {
-                        let name = method_caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                        current_context = Some((NodeKind::Method, name.to_string()));
-                        let key = (NodeKind::Method, name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0));
-                    } else if let Some(impl_caps) = impl_re.captures(context_str) {
-                        let name = impl_caps.get(1).map(|m| m.as_str()).unwrap_or("Unknown");
-                        current_context = Some((NodeKind::Impl, name.to_string()));
-                        let key = (NodeKind::Impl, name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0));
-                    } else if let Some(iface_caps) = interface_re.captures(context_str) {
-                        let name = iface_caps.get(1).map(|m| m.as_str()).unwrap_or("Unknown");
-                        current_context = Some((NodeKind::Class, name.to_string()));
-                        let key = (NodeKind::Class, name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0));
-                    } else {
-                        current_context = None;
-                    }
+            if let Some(context_str) = lines.header(line) {
+                if let Some(selector) = method_selector(context_str) {
+                    let key = (NodeKind::Method, selector);
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    current_context = Some(key);
+                } else if let Some(impl_caps) = impl_re.captures(context_str) {
+                    let key = (NodeKind::Impl, category_name(&impl_caps));
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    current_context = Some(key);
+                } else if let Some(iface_caps) = interface_re.captures(context_str) {
+                    let key = (NodeKind::Class, category_name(&iface_caps));
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    current_context = Some(key);
+                } else if let Some(fn_caps) = c_function_re.captures(context_str) {
+                    let name = fn_caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
+                    let key = (NodeKind::Function, name.to_string());
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    current_context = Some(key);
                 } else {
                     current_context = None;
                 }
@@ -86,22 +97,24 @@ impl DiffParser for ObjCParser {
             let is_added = line.starts_with('+') && !line.starts_with("+++");
             let is_removed = line.starts_with('-') && !line.starts_with("---");
             let is_context = line.starts_with(' ');
+            let line_number = lines.advance(line);
 
             // Check context lines for method/class definitions to track current scope
             if is_context {
                 let content = &line[1..];
-                if let Some(caps) = method_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Method, name.to_string()));
+                if let Some(selector) = method_selector(content) {
+                    current_context = Some((NodeKind::Method, selector));
                 } else if let Some(caps) = impl_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Impl, name.to_string()));
+                    current_context = Some((NodeKind::Impl, category_name(&caps)));
                 } else if let Some(caps) = interface_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Class, name.to_string()));
+                    current_context = Some((NodeKind::Class, category_name(&caps)));
                 } else if let Some(caps) = protocol_re.captures(content) {
                     let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
                     current_context = Some((NodeKind::Trait, name.to_string()));
+                } else if let Some(caps) = c_function_re.captures(content) {
+                    if !is_control_keyword(&caps[1]) {
+                        current_context = Some((NodeKind::Function, caps[1].to_string()));
+                    }
                 }
                 continue;
             }
@@ -112,11 +125,12 @@ impl DiffParser for ObjCParser {
 
             let content = &line[1..];
             let mut found_definition = false;
+            // Symbol defined on this line (for attaching NS_SWIFT_NAME changes)
+            let mut declared: Option<(NodeKind, String)> = None;
 
-            // Check for @interface
+            // Check for @interface (including categories and class extensions)
             if let Some(caps) = interface_re.captures(content) {
-                let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                let key = (NodeKind::Class, name.to_string());
+                let key = (NodeKind::Class, category_name(&caps));
                 let entry = change_map.entry(key.clone()).or_insert((
                     if is_added { ChangeType::Added } else { ChangeType::Deleted },
                     0, 0,
@@ -129,8 +143,7 @@ impl DiffParser for ObjCParser {
             // Check for @implementation
             if !found_definition {
                 if let Some(caps) = impl_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Impl, name.to_string());
+                    let key = (NodeKind::Impl, category_name(&caps));
                     let entry = change_map.entry(key.clone()).or_insert((
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0, 0,
@@ -156,11 +169,24 @@ impl DiffParser for ObjCParser {
                 }
             }
 
+            // Check for @property (single line, so scope stays with the class)
+            if !found_definition && property_re.is_match(content) {
+                if let Some(name) = property_name(content) {
+                    let key = (NodeKind::Const, name);
+                    let entry = change_map.entry(key.clone()).or_insert((
+                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
+                        0, 0,
+                    ));
+                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    declared = Some(key);
+                    found_definition = true;
+                }
+            }
+
             // Check for methods
             if !found_definition {
-                if let Some(caps) = method_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Method, name.to_string());
+                if let Some(selector) = method_selector(content) {
+                    let key = (NodeKind::Method, selector);
                     let entry = change_map.entry(key.clone()).or_insert((
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0, 0,
@@ -171,6 +197,22 @@ impl DiffParser for ObjCParser {
                 }
             }
 
+            // Check for C functions
+            if !found_definition {
+                if let Some(caps) = c_function_re.captures(content) {
+                    if !is_control_keyword(&caps[1]) {
+                        let key = (NodeKind::Function, caps[1].to_string());
+                        let entry = change_map.entry(key.clone()).or_insert((
+                            if is_added { ChangeType::Added } else { ChangeType::Deleted },
+                            0, 0,
+                        ));
+                        if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                        current_context = Some(key);
+                        found_definition = true;
+                    }
+                }
+            }
+
             // If not a definition line, add to current context
             if !found_definition {
                 if let Some(ref key) = current_context {
@@ -184,20 +226,338 @@ impl DiffParser for ObjCParser {
                     }
                 }
             }
+
+            // Whichever entry counted this line
+            if let Some(key) = declared.as_ref().or(current_context.as_ref()) {
+                first_lines.entry(key.clone()).or_insert(line_number);
+            }
+
+            // Swift interop renames are tracked as children of the annotated symbol
+            if let Some(caps) = swift_name_re.captures(content) {
+                if let Some(key) = declared.or_else(|| current_context.clone()) {
+                    let swift_name = format!("NS_SWIFT_NAME({})", caps[1].trim());
+                    let counts = swift_names.entry(key).or_default().entry(swift_name).or_default();
+                    if is_added { counts.0 += 1; } else { counts.1 += 1; }
+                }
+            }
         }
 
         change_map
             .into_iter()
-            .map(|((kind, name), (change_type, additions, deletions))| ChangeNode {
-                kind,
-                name,
-                change_type,
-                additions,
-                deletions,
-                file_path: file_path.clone(),
-                line_number: None, // TODO: extract from hunk headers
-                children: Vec::new(),
+            .map(|(key, (change_type, additions, deletions))| {
+                let mut children: Vec<ChangeNode> = swift_names
+                    .remove(&key)
+                    .unwrap_or_default()
+                    .into_iter()
+                    // The same annotation on both sides means the line changed for other reasons
+                    .filter(|(_, (added, removed))| added != removed)
+                    .map(|(name, (added, removed))| ChangeNode {
+                        kind: NodeKind::Other,
+                        name,
+                        change_type: if removed == 0 { ChangeType::Added } else { ChangeType::Deleted },
+                        additions: added,
+                        deletions: removed,
+                        file_path: file_path.clone(),
+                        line_number: None,
+                        children: Vec::new(),
+                    })
+                    .collect();
+                children.sort_by(|a, b| a.name.cmp(&b.name));
+                let line_number = first_lines.get(&key).copied();
+                let (kind, name) = key;
+                ChangeNode {
+                    kind,
+                    name,
+                    change_type,
+                    additions,
+                    deletions,
+                    file_path: file_path.clone(),
+                    line_number,
+                    children,
+                }
             })
+            .filter(ChangeNode::has_changed_lines)
             .collect()
     }
 }
+
+/// Name for an @interface/@implementation match: "Foo" or "Foo (Category)"
+fn category_name(caps: &regex::Captures) -> String {
+    let class = caps.get(1).map(|m| m.as_str()).unwrap_or("Unknown");
+    match caps.get(2) {
+        Some(category) => format!("{} ({})", class, category.as_str()),
+        None => class.to_string(),
+    }
+}
+
+fn is_control_keyword(name: &str) -> bool {
+    matches!(name, "if" | "for" | "while" | "switch" | "return" | "sizeof")
+}
+
+/// Skip a balanced parenthesized group at the start of `s`, returning the rest
+fn skip_parens(s: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(&s[i + 1..]);
+                }
+            }
+            _ if depth == 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Extract the full selector from a method declaration.
+///
+/// `- (void)setName:(NSString *)name forKey:(id)key;` becomes `setName:forKey:`,
+/// `+ (instancetype)sharedInstance` becomes `sharedInstance`. Handles block-typed
+/// return values and parameters like `(void (^)(NSError *))completion`.
+fn method_selector(line: &str) -> Option<String> {
+    let rest = line.trim_start().strip_prefix(['-', '+'])?.trim_start();
+    let rest = skip_parens(rest)?;
+
+    let mut parts: Vec<&str> = Vec::new();
+    let mut first_word: Option<&str> = None;
+    let mut depth = 0usize;
+    let mut word_start: Option<usize> = None;
+    let mut last_word: Option<&str> = None;
+
+    for (i, c) in rest.char_indices() {
+        if depth == 0 && (c.is_alphanumeric() || c == '_') {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            let word = &rest[start..i];
+            first_word.get_or_insert(word);
+            last_word = Some(word);
+        }
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 => {
+                if let Some(word) = last_word.take() {
+                    parts.push(word);
+                }
+            }
+            '{' | ';' if depth == 0 => break,
+            // Whitespace keeps the last word so "name :" still counts as a keyword
+            c if depth == 0 && !c.is_whitespace() => last_word = None,
+            _ => {}
+        }
+    }
+    if let Some(start) = word_start {
+        first_word.get_or_insert(&rest[start..]);
+    }
+
+    if parts.is_empty() {
+        first_word.map(|w| w.to_string())
+    } else {
+        Some(parts.iter().map(|p| format!("{}:", p)).collect())
+    }
+}
+
+/// Extract the selector from a message send like `[self loadItems:items animated:YES]`
+fn message_send_selector(context: &str) -> Option<String> {
+    let start = context.find('[')?;
+    let inner = &context[start + 1..];
+
+    // Skip the receiver (identifier, property chain, or nested send)
+    let inner = inner.trim_start();
+    let receiver_end = if inner.starts_with('[') {
+        let mut depth = 0usize;
+        let mut end = None;
+        for (i, c) in inner.char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(i + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        end?
+    } else {
+        inner.find(|c: char| c.is_whitespace())?
+    };
+    let message = &inner[receiver_end..];
+
+    let mut parts: Vec<&str> = Vec::new();
+    let mut first_word: Option<&str> = None;
+    let mut depth = 0usize;
+    let mut word_start: Option<usize> = None;
+    for (i, c) in message.char_indices() {
+        if depth == 0 && (c.is_alphanumeric() || c == '_') {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        let word = word_start.take().map(|s| &message[s..i]);
+        if let Some(w) = word {
+            first_word.get_or_insert(w);
+        }
+        match c {
+            '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' if depth > 0 => depth -= 1,
+            ']' => break,
+            ':' if depth == 0 => {
+                if let Some(w) = word {
+                    parts.push(w);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if parts.is_empty() {
+        first_word.map(|w| w.to_string())
+    } else {
+        Some(parts.iter().map(|p| format!("{}:", p)).collect())
+    }
+}
+
+/// Extract the property name from an `@property` declaration.
+///
+/// Handles plain (`NSString *name;`), block-typed (`void (^completion)(NSError *);`)
+/// and annotated (`... name NS_SWIFT_NAME(title);`) properties.
+fn property_name(line: &str) -> Option<String> {
+    let block_re = Regex::new(r"\(\s*\^\s*(\w+)\s*\)").unwrap();
+    let trailing_macro_re = Regex::new(r"\s+[A-Z][A-Z0-9_]*(?:\([^)]*(?:\)[^)]*)?\))?\s*$").unwrap();
+    let name_re = Regex::new(r"(\w+)\s*$").unwrap();
+
+    let decl = line.trim_start().strip_prefix("@property")?;
+    let decl = decl.trim_start();
+    // Skip attributes: "(nonatomic, copy)"
+    let decl = if decl.starts_with('(') { skip_parens(decl)? } else { decl };
+    let decl = decl.split(';').next().unwrap_or(decl);
+
+    if let Some(caps) = block_re.captures(decl) {
+        return Some(caps[1].to_string());
+    }
+
+    let mut decl = decl.to_string();
+    while let Some(m) = trailing_macro_re.find(&decl) {
+        decl.truncate(m.start());
+    }
+    name_re.captures(&decl).map(|caps| caps[1].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+
+    #[test]
+    fn test_method_selectors() {
+        assert_eq!(method_selector("+ (instancetype)sharedInstance"), Some("sharedInstance".to_string()));
+        assert_eq!(
+            method_selector("- (void)setName:(NSString *)name forKey:(id)key;"),
+            Some("setName:forKey:".to_string())
+        );
+        assert_eq!(
+            method_selector("- (void (^)(void))handlerWithCompletion:(void (^)(NSError *error))completion {"),
+            Some("handlerWithCompletion:".to_string())
+        );
+        assert_eq!(
+            method_selector("- (void)reload NS_SWIFT_NAME(refresh());"),
+            Some("reload".to_string())
+        );
+        assert_eq!(method_selector("    return x - (y);"), None);
+    }
+
+    #[test]
+    fn test_message_send_context() {
+        let parser = ObjCParser;
+        assert_eq!(
+            parser.extract_function_from_context("    [self loadItems:items animated:YES];"),
+            Some("loadItems:animated:".to_string())
+        );
+        assert_eq!(
+            parser.extract_function_from_context("[[Store shared] flush]"),
+            Some("flush".to_string())
+        );
+    }
+
+    #[test]
+    fn test_category_implementation_changes() {
+        let diff = "\
+@@ -1,4 +1,8 @@
++@implementation NSString (Crabigator)
++
++- (NSString *)crab_trimmed {
++    return [self stringByTrimmingCharactersInSet:NSCharacterSet.whitespaceCharacterSet];
++}
+";
+        let changes = ObjCParser.parse(diff, "NSString+Crabigator.m");
+        let category = find(&changes, "NSString (Crabigator)");
+        assert_eq!(category.kind, NodeKind::Impl);
+        assert_eq!(category.change_type, ChangeType::Added);
+        assert_eq!(category.line_number, Some(1));
+        let method = find(&changes, "crab_trimmed");
+        assert_eq!(method.kind, NodeKind::Method);
+        assert_eq!(method.additions, 3);
+        assert_eq!(method.line_number, Some(3));
+    }
+
+    #[test]
+    fn test_protocol_methods() {
+        let diff = "\
+@@ -1,5 +1,6 @@
+ @protocol StoreDelegate <NSObject>
+ - (void)storeDidChange:(Store *)store;
++- (void)store:(Store *)store didFailWithError:(NSError *)error;
+ @end
++@protocol Forwarded;
+";
+        let changes = ObjCParser.parse(diff, "Store.h");
+        let method = find(&changes, "store:didFailWithError:");
+        assert_eq!(method.change_type, ChangeType::Added);
+        // Forward declarations aren't protocol definitions
+        assert!(!changes.iter().any(|c| c.name == "Forwarded"));
+    }
+
+    #[test]
+    fn test_block_typed_property_changes() {
+        let diff = "\
+@@ -1,3 +1,3 @@
+ @interface Loader : NSObject
+-@property (nonatomic, copy) void (^completion)(NSError *error);
++@property (nonatomic, copy) void (^completion)(NSError *error, BOOL cancelled);
++@property (nonatomic, strong) NSString *title NS_SWIFT_NAME(displayTitle);
+ @end
+";
+        let changes = ObjCParser.parse(diff, "Loader.h");
+        let completion = find(&changes, "completion");
+        assert_eq!(completion.kind, NodeKind::Const);
+        assert_eq!(completion.additions, 1);
+        assert_eq!(completion.deletions, 1);
+
+        let title = find(&changes, "title");
+        assert_eq!(title.children.len(), 1);
+        assert_eq!(title.children[0].name, "NS_SWIFT_NAME(displayTitle)");
+    }
+
+    #[test]
+    fn test_c_functions_in_implementation_files() {
+        let diff = "\
+@@ -1,3 +1,5 @@
++static NSString *MakeKey(id object) {
++    return [object description];
++}
+ @implementation Cache
+";
+        let changes = ObjCParser.parse(diff, "Cache.mm");
+        let function = find(&changes, "MakeKey");
+        assert_eq!(function.kind, NodeKind::Function);
+        assert_eq!(function.additions, 3);
+    }
+}
@@ -0,0 +1,164 @@
+//! Property-based tests for the diff parsers
+//!
+//! Generates diffs from random `+`/`-`/context lines mixing language constructs
+//! with arbitrary noise, then checks invariants every parser must uphold.
+
+use proptest::prelude::*;
+
+use super::summary::parse_diff_into_files;
+use super::{
+    ChangeNode, CssParser, DiffParser, GenericParser, JsonParser, ObjCParser, PythonParser, RustParser,
+    SwiftParser, TypeScriptParser, YamlParser,
+};
+
+fn ident() -> impl Strategy<Value = String> {
+    "[a-zA-Z_][a-zA-Z0-9_]{0,8}"
+}
+
+fn rust_line() -> impl Strategy<Value = String> {
+    prop_oneof![
+        ident().prop_map(|n| format!("fn {}(x: u32) -> u32 {{", n)),
+        ident().prop_map(|n| format!("pub async fn {}<'a, T: Clone>(x: &'a T) {{", n)),
+        ident().prop_map(|n| format!("pub struct {}<T> {{", n)),
+        ident().prop_map(|n| format!("enum {} {{", n)),
+        ident().prop_map(|n| format!("impl<'a> {}<'a> {{", n)),
+        ident().prop_map(|n| format!("impl Display for {} {{", n)),
+        ident().prop_map(|n| format!("pub trait {} {{", n)),
+        ident().prop_map(|n| format!("mod {};", n)),
+        ident().prop_map(|n| format!("const {}: u32 = 1;", n.to_uppercase())),
+        ident().prop_map(|n| format!("where {}: Clone,", n)),
+        Just("}".to_string()),
+        Just("    let c = 'x';".to_string()),
+    ]
+}
+
+fn python_line() -> impl Strategy<Value = String> {
+    prop_oneof![
+        ident().prop_map(|n| format!("def {}(self, x):", n)),
+        ident().prop_map(|n| format!("    async def {}():", n)),
+        ident().prop_map(|n| format!("class {}(Base):", n)),
+        ident().prop_map(|n| format!("import {}", n)),
+        ident().prop_map(|n| format!("from {} import (", n)),
+        ident().prop_map(|n| format!("    {},", n)),
+        Just(")".to_string()),
+        Just("@decorator".to_string()),
+    ]
+}
+
+fn typescript_line() -> impl Strategy<Value = String> {
+    prop_oneof![
+        ident().prop_map(|n| format!("function {}<T>(x: T) {{", n)),
+        ident().prop_map(|n| format!("export class {}<T extends U = V> {{", n)),
+        ident().prop_map(|n| format!("interface {}<K, V> {{", n)),
+        ident().prop_map(|n| format!("type {}<T> = (x: T) => T;", n)),
+        ident().prop_map(|n| format!("const {} = async () => {{", n)),
+        Just("export default function() {".to_string()),
+        Just("<<<>>>".to_string()),
+    ]
+}
+
+fn other_line() -> impl Strategy<Value = String> {
+    prop_oneof![
+        ident().prop_map(|n| format!("func {}(_ x: Int) -> Int {{", n)),
+        ident().prop_map(|n| format!("@objc actor {} {{", n)),
+        ident().prop_map(|n| format!("- (void){}:(id)x with:(id)y;", n)),
+        ident().prop_map(|n| format!("@interface {} (Cat)", n)),
+        ident().prop_map(|n| format!(".{} > a:hover {{", n)),
+        Just("@media (max-width: 600px) {".to_string()),
+        Just("[self foo:1 bar:2];".to_string()),
+        ident().prop_map(|n| format!("{}:", n)),
+        ident().prop_map(|n| format!("  - name: {}", n)),
+        ident().prop_map(|n| format!("base: &{}", n)),
+        ident().prop_map(|n| format!("  \"{}\": {{", n)),
+        ident().prop_map(|n| format!("    \"{}\": \"^1.0\",", n)),
+        Just("  },".to_string()),
+        ".{0,40}",
+    ]
+}
+
+fn diff_line() -> impl Strategy<Value = String> {
+    let body = prop_oneof![rust_line(), python_line(), typescript_line(), other_line()];
+    let prefix = prop_oneof![Just('+'), Just('-'), Just(' ')];
+    prop_oneof![
+        8 => (prefix, body).prop_map(|(p, b)| format!("{}{}", p, b)),
+        1 => body_context().prop_map(|ctx| format!("@@ -1,3 +1,4 @@ {}", ctx)),
+        1 => Just(String::new()),
+    ]
+}
+
+fn body_context() -> impl Strategy<Value = String> {
+    prop_oneof![rust_line(), python_line(), typescript_line(), Just(String::new())]
+}
+
+fn diff() -> impl Strategy<Value = String> {
+    prop::collection::vec(diff_line(), 0..40).prop_map(|lines| lines.join("\n"))
+}
+
+fn parsers() -> Vec<(Box<dyn DiffParser>, &'static str)> {
+    vec![
+        (Box::new(RustParser), "src/lib.rs"),
+        (Box::new(TypeScriptParser), "src/app.ts"),
+        (Box::new(PythonParser), "pkg/mod.py"),
+        (Box::new(SwiftParser), "App/View.swift"),
+        (Box::new(ObjCParser), "App/View.m"),
+        (Box::new(CssParser), "styles/app.scss"),
+        (Box::new(YamlParser), "deploy/app.yaml"),
+        (Box::new(JsonParser), "package.json"),
+        (Box::new(GenericParser), "README.md"),
+    ]
+}
+
+/// Check the per-node invariants, recursing into children
+fn check_node(node: &ChangeNode, filename: &str) -> Result<(), TestCaseError> {
+    prop_assert!(!node.name.is_empty(), "empty name: {:?}", node);
+    prop_assert!(node.additions + node.deletions > 0, "empty change entry: {:?}", node);
+    prop_assert_eq!(node.file_path.as_deref(), Some(filename));
+    for child in &node.children {
+        check_node(child, filename)?;
+    }
+    Ok(())
+}
+
+proptest! {
+    // Parsers compile their regexes per call, which is slow in debug builds
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn test_parsers_uphold_node_invariants(diff in diff()) {
+        for (parser, filename) in parsers() {
+            for node in parser.parse(&diff, filename) {
+                check_node(&node, filename)?;
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsers_never_panic_on_arbitrary_text(text in ".{0,400}") {
+        for (parser, filename) in parsers() {
+            let _ = parser.parse(&text, filename);
+            let _ = parser.extract_function_from_context(&text);
+        }
+    }
+
+    #[test]
+    fn test_parse_diff_into_files_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let text = String::from_utf8_lossy(&bytes);
+        for (filename, _) in parse_diff_into_files(&text) {
+            prop_assert!(!filename.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_diff_into_files_splits_headers(
+        names in prop::collection::vec("[a-z]{1,8}\\.rs", 1..5),
+        body in diff(),
+    ) {
+        let diff: String = names
+            .iter()
+            .map(|n| format!("diff --git a/{n} b/{n}\n{body}\n"))
+            .collect();
+        let files = parse_diff_into_files(&diff);
+        let parsed: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        prop_assert_eq!(parsed, names.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+}
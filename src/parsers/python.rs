@@ -1,6 +1,7 @@
 use regex::Regex;
 use std::collections::HashMap;
 
+use super::hunk::HunkLineTracker;
 use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
 
 pub struct PythonParser;
@@ -33,27 +34,33 @@ impl DiffParser for PythonParser {
         // Track changes with their line counts
         // Key: (kind, name), Value: (change_type, additions, deletions)
         let mut change_map: HashMap<(NodeKind, String), (ChangeType, usize, usize)> = HashMap::new();
+        // New-file line of each entry's first changed line
+        let mut first_lines: HashMap<(NodeKind, String), usize> = HashMap::new();
 
         // Regex patterns for Python constructs
         let class_re = Regex::new(r"^class\s+(\w+)").unwrap();
         let def_re = Regex::new(r"^(\s*)(?:async\s+)?def\s+(\w+)").unwrap();
-        let hunk_re = Regex::new(r"^@@[^@]+@@\s*(.*)$").unwrap();
+        let import_re = Regex::new(r"^\s*import\s+(.+)$").unwrap();
+        let from_import_re = Regex::new(r"^\s*from\s+(\S+)\s+import\b(.*)$").unwrap();
 
         // Current context: which function/class we're inside
         let mut current_context: Option<(NodeKind, String)> = None;
+        let mut lines = HunkLineTracker::new();
+
+        // First pass collects every changed import line as (module, is_added, line);
+        // they're merged by module after the diff is walked
+        let mut import_changes: Vec<(String, bool, usize)> = Vec::new();
+        // Module of an open `from x import (` block spanning multiple lines
+        let mut open_import_block: Option<String> = None;
 
         for line in diff.lines() {
             // Check for hunk headers with function context
-            if let Some(caps) = hunk_re.captures(line) {
-                if let Some(context) = caps.get(1) {
-                    let context_str = context.as_str();
-                    if let Some(fn_name) = self.extract_function_from_context(context_str) {
-                        let key = (NodeKind::Function, fn_name.clone());
-                        change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
-                        current_context = Some(key);
-                    } else {
-                        current_context = None;
-                    }
+            if let Some(context) = lines.header(line) {
+                open_import_block = None;
+                if let Some(fn_name) = self.extract_function_from_context(context) {
+                    let key = (NodeKind::Function, fn_name.clone());
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    current_context = Some(key);
                 } else {
                     current_context = None;
                 }
@@ -63,6 +70,45 @@ impl DiffParser for PythonParser {
             let is_added = line.starts_with('+') && !line.starts_with("+++");
             let is_removed = line.starts_with('-') && !line.starts_with("---");
             let is_context = line.starts_with(' ');
+            let line_number = lines.advance(line);
+
+            // Multi-line imports: symbols inside the parens belong to the open module
+            if let Some(module) = open_import_block.clone() {
+                let content = line.get(1..).unwrap_or("");
+                if content.contains(')') {
+                    open_import_block = None;
+                }
+                if is_added || is_removed {
+                    import_changes.push((module, is_added, line_number));
+                }
+                continue;
+            }
+            if is_context || is_added || is_removed {
+                let content = &line[1..];
+                if let Some(caps) = from_import_re.captures(content) {
+                    let module = format!("from {}", &caps[1]);
+                    let rest = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+                    if rest.contains('(') && !rest.contains(')') {
+                        open_import_block = Some(module.clone());
+                    }
+                    if !is_context {
+                        import_changes.push((module, is_added, line_number));
+                    }
+                    continue;
+                }
+                if let Some(caps) = import_re.captures(content) {
+                    if !is_context {
+                        // `import os, sys as system` names two modules
+                        for module in caps[1].split(',') {
+                            let module = module.split_whitespace().next().unwrap_or("");
+                            if !module.is_empty() {
+                                import_changes.push((module.to_string(), is_added, line_number));
+                            }
+                        }
+                    }
+                    continue;
+                }
+            }
 
             // Check context lines for function/class definitions to track current scope
             if is_context {
@@ -100,6 +146,7 @@ impl DiffParser for PythonParser {
                     0,
                 ));
                 if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                first_lines.entry(key.clone()).or_insert(line_number);
                 current_context = Some(key);
                 found_definition = true;
             }
@@ -121,6 +168,7 @@ impl DiffParser for PythonParser {
                         0,
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
                     current_context = Some(key);
                     found_definition = true;
                 }
@@ -137,23 +185,130 @@ impl DiffParser for PythonParser {
                     } else {
                         entry.2 += 1;
                     }
+                    first_lines.entry(key.clone()).or_insert(line_number);
                 }
             }
         }
 
+        // Second pass: merge import changes by module
+        for (module, is_added, line_number) in import_changes {
+            first_lines.entry((NodeKind::Module, module.clone())).or_insert(line_number);
+            let entry = change_map
+                .entry((NodeKind::Module, module))
+                .or_insert((if is_added { ChangeType::Added } else { ChangeType::Deleted }, 0, 0));
+            if is_added { entry.1 += 1; } else { entry.2 += 1; }
+            if entry.1 > 0 && entry.2 > 0 {
+                entry.0 = ChangeType::Modified;
+            }
+        }
+
         // Convert map to vec of ChangeNodes
         change_map
             .into_iter()
             .map(|((kind, name), (change_type, additions, deletions))| ChangeNode {
+                line_number: first_lines.get(&(kind.clone(), name.clone())).copied(),
                 kind,
                 name,
                 change_type,
                 additions,
                 deletions,
                 file_path: file_path.clone(),
-                line_number: None, // TODO: extract from hunk headers
                 children: Vec::new(),
             })
+            .filter(ChangeNode::has_changed_lines)
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+
+    #[test]
+    fn test_plain_imports_are_modules() {
+        let diff = "\
+@@ -1,2 +1,3 @@
+ import os
++import json
+-import pickle, shelve
+";
+        let changes = PythonParser.parse(diff, "app.py");
+        let json = find(&changes, "json");
+        assert_eq!(json.kind, NodeKind::Module);
+        assert_eq!(json.change_type, ChangeType::Added);
+        assert_eq!(find(&changes, "pickle").change_type, ChangeType::Deleted);
+        assert_eq!(find(&changes, "shelve").change_type, ChangeType::Deleted);
+        assert!(!changes.iter().any(|c| c.name == "os"));
+    }
+
+    #[test]
+    fn test_from_imports_merge_by_module() {
+        let diff = "\
+@@ -1,2 +1,3 @@
+-from pathlib import Path
++from pathlib import Path, PurePath
++from pathlib import PosixPath
+";
+        let changes = PythonParser.parse(diff, "app.py");
+        assert_eq!(changes.len(), 1);
+        let pathlib = find(&changes, "from pathlib");
+        assert_eq!(pathlib.change_type, ChangeType::Modified);
+        assert_eq!(pathlib.additions, 2);
+        assert_eq!(pathlib.deletions, 1);
+    }
+
+    #[test]
+    fn test_multi_line_imports_attribute_to_open_module() {
+        let diff = "\
+@@ -1,6 +1,6 @@
+ from typing import (
+     Any,
+-    Dict,
++    Mapping,
+ )
+ 
+ def load():
++    return {}
+";
+        let changes = PythonParser.parse(diff, "app.py");
+        let typing = find(&changes, "from typing");
+        assert_eq!(typing.additions, 1);
+        assert_eq!(typing.deletions, 1);
+        // Lines after the closing paren go back to normal scope tracking
+        assert_eq!(find(&changes, "load").additions, 1);
+    }
+
+    #[test]
+    fn test_future_imports_are_tracked() {
+        let diff = "\
+@@ -1,1 +1,2 @@
++from __future__ import annotations
+ import os
+";
+        let changes = PythonParser.parse(diff, "app.py");
+        let future = find(&changes, "from __future__");
+        assert_eq!(future.kind, NodeKind::Module);
+        assert_eq!(future.change_type, ChangeType::Added);
+    }
+
+    #[test]
+    fn test_line_numbers_come_from_hunk_headers() {
+        let diff = "\
+@@ -10,4 +10,5 @@ class Cart:
+     def total(self):
+-        return sum(self.items)
++        return sum(i.price for i in self.items)
+ 
++    def clear(self):
+@@ -40,2 +41,3 @@ def load():
+     data = read()
++    validate(data)
+";
+        let changes = PythonParser.parse(diff, "cart.py");
+        // The first changed line, not the hunk start
+        assert_eq!(find(&changes, "total").line_number, Some(11));
+        assert_eq!(find(&changes, "clear").line_number, Some(13));
+        assert_eq!(find(&changes, "load").line_number, Some(42));
+    }
+}
@@ -1,6 +1,7 @@
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use super::hunk::HunkLineTracker;
 use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
 
 pub struct RustParser;
@@ -50,50 +51,50 @@ impl DiffParser for RustParser {
         let trait_re = Regex::new(r"^\s*(pub\s+)?trait\s+(\w+)").unwrap();
         let mod_re = Regex::new(r"^\s*(pub\s+)?mod\s+(\w+)").unwrap();
         let const_re = Regex::new(r"^\s*(pub\s+)?const\s+(\w+)").unwrap();
-        // Pattern for hunk headers: @@ -old,count +new,count @@ context
-        // Captures: 1=new_line_start, 2=context
-        let hunk_re = Regex::new(r"^@@ -\d+(?:,\d+)? \+(\d+)(?:,\d+)? @@\s*(.*)$").unwrap();
+        let lifetime_re = Regex::new(r"'([a-z_]\w*)").unwrap();
+        // Inline `where T: Trait` clause, or a bare `where` opening a multi-line block
+        let where_re = Regex::new(r"\bwhere\b\s*(.*)$").unwrap();
 
         // Current context: which function/impl we're inside
         let mut current_context: Option<(NodeKind, String)> = None;
-        // Track current line number in the new file
-        let mut current_line: usize = 0;
+        let mut lines = HunkLineTracker::new();
         let file_path = Some(filename.to_string());
 
+        // Signature details seen in the current hunk, compared when the hunk ends
+        let mut hunk_signatures: HashMap<(NodeKind, String), HunkSignature> = HashMap::new();
+        // Child nodes (lifetimes, where clauses) keyed by their enclosing symbol
+        let mut children: HashMap<(NodeKind, String), Vec<ChangeNode>> = HashMap::new();
+        // Inside a multi-line `where` block (until the opening brace or semicolon)
+        let mut in_where = false;
+
         for line in diff.lines() {
             // Check for hunk headers with function context
-            if let Some(caps) = hunk_re.captures(line) {
-                // Extract new file line number from hunk header
-                if let Some(line_num) = caps.get(1) {
-                    current_line = line_num.as_str().parse().unwrap_or(1);
+            if let Some(context_str) = lines.header(line) {
+                flush_signature_children(&mut hunk_signatures, &mut children, &file_path);
+                in_where = false;
+                let hunk_start = lines.next_line();
+                // Try to extract function name from context
+                if let Some(fn_caps) = fn_re.captures(context_str) {
+                    let fn_name = fn_caps.get(3).map(|m| m.as_str()).unwrap_or("unknown");
+                    current_context = Some((NodeKind::Function, fn_name.to_string()));
+                    // Pre-register as modified (will be updated with line counts)
+                    let key = (NodeKind::Function, fn_name.to_string());
+                    change_map.entry(key).or_insert((ChangeType::Modified, 0, 0, Some(hunk_start)));
                 }
-                if let Some(context) = caps.get(2) {
-                    let context_str = context.as_str();
-                    // Try to extract function name from context
-                    if let Some(fn_caps) = fn_re.captures(context_str) {
-                        let fn_name = fn_caps.get(3).map(|m| m.as_str()).unwrap_or("unknown");
-                        current_context = Some((NodeKind::Function, fn_name.to_string()));
-                        // Pre-register as modified (will be updated with line counts)
-                        let key = (NodeKind::Function, fn_name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0, Some(current_line)));
-                    }
-                    // Check for impl block in context
-                    else if let Some(impl_caps) = impl_re.captures(context_str) {
-                        let type_name = impl_caps.get(2).map(|m| m.as_str()).unwrap_or("Unknown");
-                        let trait_name = impl_caps.get(1).map(|m| m.as_str());
-                        let name = if let Some(trait_n) = trait_name {
-                            format!("{} for {}", trait_n, type_name)
-                        } else {
-                            type_name.to_string()
-                        };
-                        current_context = Some((NodeKind::Impl, name.clone()));
-                        let key = (NodeKind::Impl, name);
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0, Some(current_line)));
+                // Check for impl block in context
+                else if let Some(impl_caps) = impl_re.captures(context_str) {
+                    let type_name = impl_caps.get(2).map(|m| m.as_str()).unwrap_or("Unknown");
+                    let trait_name = impl_caps.get(1).map(|m| m.as_str());
+                    let name = if let Some(trait_n) = trait_name {
+                        format!("{} for {}", trait_n, type_name)
                     } else {
-                        // No function context in hunk header
-                        current_context = None;
-                    }
+                        type_name.to_string()
+                    };
+                    current_context = Some((NodeKind::Impl, name.clone()));
+                    let key = (NodeKind::Impl, name);
+                    change_map.entry(key).or_insert((ChangeType::Modified, 0, 0, Some(hunk_start)));
                 } else {
+                    // No function context in hunk header
                     current_context = None;
                 }
                 continue;
@@ -102,11 +103,12 @@ impl DiffParser for RustParser {
             let is_added = line.starts_with('+') && !line.starts_with("+++");
             let is_removed = line.starts_with('-') && !line.starts_with("---");
             let is_context = line.starts_with(' ');
+            let line_number = lines.advance(line);
 
             // Check context lines for function/struct/impl definitions to track current scope
             if is_context {
-                current_line += 1; // Context lines appear in new file
                 let content = &line[1..];
+                in_where = update_where_block(in_where, content, &where_re);
                 // Check for impl blocks in context
                 if let Some(caps) = impl_re.captures(content) {
                     let type_name = caps.get(2).map(|m| m.as_str()).unwrap_or("Unknown");
@@ -145,11 +147,6 @@ impl DiffParser for RustParser {
                 continue;
             }
 
-            // Increment line number for added lines (they appear in new file)
-            if is_added {
-                current_line += 1;
-            }
-
             let content = &line[1..]; // Strip the +/- prefix
 
             // Check if this line defines a new construct
@@ -169,7 +166,7 @@ impl DiffParser for RustParser {
                     if is_added { ChangeType::Added } else { ChangeType::Deleted },
                     0,
                     0,
-                    if is_added { Some(current_line) } else { None },
+                    if is_added { Some(line_number) } else { None },
                 ));
                 if is_added { entry.1 += 1; } else { entry.2 += 1; }
                 current_context = Some(key);
@@ -185,7 +182,7 @@ impl DiffParser for RustParser {
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0,
                         0,
-                        if is_added { Some(current_line) } else { None },
+                        if is_added { Some(line_number) } else { None },
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
                     current_context = Some(key);
@@ -202,7 +199,7 @@ impl DiffParser for RustParser {
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0,
                         0,
-                        if is_added { Some(current_line) } else { None },
+                        if is_added { Some(line_number) } else { None },
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
                     current_context = Some(key);
@@ -219,7 +216,7 @@ impl DiffParser for RustParser {
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0,
                         0,
-                        if is_added { Some(current_line) } else { None },
+                        if is_added { Some(line_number) } else { None },
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
                     current_context = Some(key);
@@ -236,7 +233,7 @@ impl DiffParser for RustParser {
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0,
                         0,
-                        if is_added { Some(current_line) } else { None },
+                        if is_added { Some(line_number) } else { None },
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
                     current_context = Some(key);
@@ -253,7 +250,7 @@ impl DiffParser for RustParser {
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0,
                         0,
-                        if is_added { Some(current_line) } else { None },
+                        if is_added { Some(line_number) } else { None },
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
                     current_context = Some(key);
@@ -270,7 +267,7 @@ impl DiffParser for RustParser {
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
                         0,
                         0,
-                        if is_added { Some(current_line) } else { None },
+                        if is_added { Some(line_number) } else { None },
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
                     found_definition = true;
@@ -290,21 +287,259 @@ impl DiffParser for RustParser {
                     }
                 }
             }
+
+            // Record lifetimes and where predicates against the enclosing symbol
+            if let Some(ref key) = current_context {
+                let sig = hunk_signatures.entry(key.clone()).or_default();
+                if key.0 == NodeKind::Function && fn_re.is_match(content) {
+                    let lifetimes = extract_lifetimes(content, &lifetime_re);
+                    if is_added {
+                        sig.added_lifetimes = Some(lifetimes);
+                    } else {
+                        sig.removed_lifetimes = Some(lifetimes);
+                    }
+                }
+
+                let predicates = if let Some(caps) = where_re.captures(content) {
+                    caps.get(1).map(|m| split_where_predicates(m.as_str())).unwrap_or_default()
+                } else if in_where {
+                    split_where_predicates(content)
+                } else {
+                    Vec::new()
+                };
+                if is_added {
+                    sig.added_where.extend(predicates);
+                } else {
+                    sig.removed_where.extend(predicates);
+                }
+            }
+            in_where = update_where_block(in_where, content, &where_re);
         }
+        flush_signature_children(&mut hunk_signatures, &mut children, &file_path);
 
         // Convert map to vec of ChangeNodes
         change_map
             .into_iter()
-            .map(|((kind, name), (change_type, additions, deletions, line_number))| ChangeNode {
-                kind,
-                name,
-                change_type,
-                additions,
-                deletions,
-                file_path: file_path.clone(),
-                line_number,
-                children: Vec::new(),
+            .map(|(key, (change_type, additions, deletions, line_number))| {
+                let children = children.remove(&key).unwrap_or_default();
+                let (kind, name) = key;
+                ChangeNode {
+                    kind,
+                    name,
+                    change_type,
+                    additions,
+                    deletions,
+                    file_path: file_path.clone(),
+                    line_number,
+                    children,
+                }
             })
+            .filter(ChangeNode::has_changed_lines)
             .collect()
     }
 }
+
+/// Lifetimes and where predicates seen on removed/added lines of one symbol within a hunk
+#[derive(Default)]
+struct HunkSignature {
+    removed_lifetimes: Option<BTreeSet<String>>,
+    added_lifetimes: Option<BTreeSet<String>>,
+    removed_where: BTreeSet<String>,
+    added_where: BTreeSet<String>,
+}
+
+/// Collect lifetime names from a signature, ignoring char literals like `'a'`
+fn extract_lifetimes(content: &str, lifetime_re: &Regex) -> BTreeSet<String> {
+    lifetime_re
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let m = caps.get(1)?;
+            if content[m.end()..].starts_with('\'') {
+                return None;
+            }
+            Some(m.as_str().to_string())
+        })
+        .filter(|name| name != "static" && name != "_")
+        .collect()
+}
+
+/// Split `T: Clone, U: Debug {` into normalized predicates
+fn split_where_predicates(clause: &str) -> Vec<String> {
+    let clause = clause.split(['{', ';']).next().unwrap_or("");
+    clause
+        .split(',')
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Track whether the next line is inside a multi-line `where` block
+fn update_where_block(in_where: bool, content: &str, where_re: &Regex) -> bool {
+    let opens = where_re.is_match(content) || in_where;
+    opens && !content.contains('{') && !content.contains(';')
+}
+
+/// Turn the signature differences of the finished hunk into child nodes
+fn flush_signature_children(
+    hunk_signatures: &mut HashMap<(NodeKind, String), HunkSignature>,
+    children: &mut HashMap<(NodeKind, String), Vec<ChangeNode>>,
+    file_path: &Option<String>,
+) {
+    for (key, sig) in hunk_signatures.drain() {
+        let mut nodes = Vec::new();
+
+        // Lifetimes only count when the signature appears on both sides of the hunk
+        if let (Some(removed), Some(added)) = (&sig.removed_lifetimes, &sig.added_lifetimes) {
+            if removed != added {
+                nodes.push(signature_child(
+                    "lifetimes",
+                    added.difference(removed).count(),
+                    removed.difference(added).count(),
+                    file_path,
+                ));
+            }
+        }
+
+        if sig.removed_where != sig.added_where {
+            nodes.push(signature_child(
+                "where",
+                sig.added_where.difference(&sig.removed_where).count(),
+                sig.removed_where.difference(&sig.added_where).count(),
+                file_path,
+            ));
+        }
+
+        let existing = children.entry(key).or_default();
+        for node in nodes {
+            match existing.iter_mut().find(|c| c.name == node.name) {
+                Some(c) => {
+                    c.additions += node.additions;
+                    c.deletions += node.deletions;
+                }
+                None => existing.push(node),
+            }
+        }
+    }
+}
+
+fn signature_child(name: &str, additions: usize, deletions: usize, file_path: &Option<String>) -> ChangeNode {
+    let change_type = match (additions, deletions) {
+        (_, 0) => ChangeType::Added,
+        (0, _) => ChangeType::Deleted,
+        _ => ChangeType::Modified,
+    };
+    ChangeNode {
+        kind: NodeKind::Other,
+        name: name.to_string(),
+        change_type,
+        additions,
+        deletions,
+        file_path: file_path.clone(),
+        line_number: None,
+        children: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn children_of<'a>(changes: &'a [ChangeNode], name: &str) -> &'a [ChangeNode] {
+        &changes
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("no change named {}", name))
+            .children
+    }
+
+    #[test]
+    fn test_added_definition_line_number() {
+        let diff = "\
+@@ -10,2 +10,5 @@ impl Parser {
+     }
++
++    fn reset(&mut self) {
++    }
+";
+        let changes = RustParser.parse(diff, "src/lib.rs");
+        let reset = changes.iter().find(|c| c.name == "reset").unwrap();
+        assert_eq!(reset.line_number, Some(12));
+    }
+
+    #[test]
+    fn test_lifetime_addition_adds_child() {
+        let diff = "\
+@@ -1,3 +1,3 @@
+-fn foo<'a>(x: &str) -> &str {
++fn foo<'a, 'b>(x: &'a str) -> &'b str {
+     x
+ }
+";
+        let changes = RustParser.parse(diff, "src/lib.rs");
+        let children = children_of(&changes, "foo");
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].name, "lifetimes");
+        assert_eq!(children[0].kind, NodeKind::Other);
+        assert_eq!(children[0].change_type, ChangeType::Added);
+        assert_eq!(children[0].additions, 1);
+    }
+
+    #[test]
+    fn test_lifetime_removal_adds_child() {
+        let diff = "\
+@@ -1,3 +1,3 @@
+-pub fn first<'a>(items: &'a [u8]) -> &'a u8 {
++pub fn first(items: &[u8]) -> &u8 {
+     &items[0]
+ }
+";
+        let changes = RustParser.parse(diff, "src/lib.rs");
+        let children = children_of(&changes, "first");
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].name, "lifetimes");
+        assert_eq!(children[0].change_type, ChangeType::Deleted);
+    }
+
+    #[test]
+    fn test_unchanged_lifetimes_and_char_literals_are_ignored() {
+        let diff = "\
+@@ -1,3 +1,3 @@
+-fn split<'a>(s: &'a str) -> &'a str { s.split('a').next().unwrap() }
++fn split<'a>(s: &'a str) -> &'a str { s.split('b').next().unwrap() }
+";
+        let changes = RustParser.parse(diff, "src/lib.rs");
+        assert!(children_of(&changes, "split").is_empty());
+    }
+
+    #[test]
+    fn test_where_clause_changes_add_child() {
+        let diff = "\
+@@ -1,6 +1,7 @@
+ pub fn render<T>(value: T) -> String
+ where
+     T: Display,
++    T: Debug,
+ {
+     format!(\"{}\", value)
+ }
+";
+        let changes = RustParser.parse(diff, "src/lib.rs");
+        let children = children_of(&changes, "render");
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].name, "where");
+        assert_eq!(children[0].change_type, ChangeType::Added);
+
+        let diff = "\
+@@ -1,3 +1,3 @@
+-pub struct Cache<K> where K: Hash {
++pub struct Cache<K> {
+     map: HashMap<K, u64>,
+ }
+";
+        let changes = RustParser.parse(diff, "src/lib.rs");
+        let children = children_of(&changes, "Cache");
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].name, "where");
+        assert_eq!(children[0].change_type, ChangeType::Deleted);
+    }
+}
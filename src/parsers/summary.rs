@@ -1,12 +1,66 @@
 //! Diff summary and parser trait
 
 use anyhow::Result;
-use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 use tokio::process::Command;
 
-use super::types::{ChangeNode, ChangeType, FileChanges, LanguageChanges, NodeKind};
-use super::{GenericParser, ObjCParser, PythonParser, RustParser, SwiftParser, TypeScriptParser};
+use crate::ui::utils::strip_ansi;
+
+use super::hunk::parse_hunk_header;
+use super::types::{is_test_file, ChangeNode, ChangeType, FileChanges, LanguageChanges, NodeKind};
+use super::{
+    CssParser, GenericParser, JsonParser, ObjCParser, PythonParser, RustParser, SwiftParser, TypeScriptParser,
+    YamlParser,
+};
+
+/// Which changes the diff summary covers (`diff_mode` / `--diff-mode`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffMode {
+    /// Uncommitted work: unstaged (`git diff`) plus staged (`git diff --cached`)
+    #[default]
+    Working,
+    /// Working tree against the commit that was HEAD when the session
+    /// started (`git diff <commit>`), so work committed since still shows
+    Session,
+    /// Same span as `Session`, read as staged (index against the session
+    /// start commit) plus unstaged, so staged work is kept apart as in `Working`
+    All,
+}
+
+impl DiffMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "working" => Some(Self::Working),
+            "session" => Some(Self::Session),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Working => "working",
+            Self::Session => "session",
+            Self::All => "all",
+        }
+    }
+
+    /// Extra `git diff` arguments for each diff read in this mode; the outputs
+    /// are joined. Without a session start commit (no commits yet, or not a
+    /// repository) every mode reads like `Working`.
+    fn diff_args(self, session_start_commit: Option<&str>) -> Vec<Vec<&str>> {
+        match (self, session_start_commit) {
+            (DiffMode::Session, Some(commit)) => vec![vec![commit]],
+            (DiffMode::All, Some(commit)) => vec![vec![], vec!["--cached", commit]],
+            _ => vec![vec![], vec!["--cached"]],
+        }
+    }
+}
 
 /// Trait for language-specific diff parsers
 pub trait DiffParser: Send + Sync {
@@ -24,6 +78,97 @@ pub trait DiffParser: Send + Sync {
 pub struct DiffSummary {
     pub files: Vec<FileChanges>,
     pub loading: bool,
+    /// Words on `+` lines across all files (see `count_words_in_diff`)
+    pub total_words_added: usize,
+    /// Words on `-` lines across all files
+    pub total_words_deleted: usize,
+    /// Line and word counts per changed file, staged and unstaged combined
+    pub file_stats: BTreeMap<String, FileDiffStats>,
+}
+
+/// Line and word counts for one file's diff
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FileDiffStats {
+    pub additions: usize,
+    pub deletions: usize,
+    pub words_added: usize,
+    pub words_deleted: usize,
+    /// Hash of the file's diff text (see `section_hash`), so incremental
+    /// refreshes notice edits that keep the line counts
+    pub diff_hash: u64,
+}
+
+/// Above this fraction of files changed, an incremental refresh reparses everything
+const INCREMENTAL_MAX_CHANGED: f64 = 0.5;
+
+/// Default cap on diff bytes parsed per refresh (config `max_diff_bytes`)
+pub const DEFAULT_MAX_DIFF_BYTES: usize = 5 * 1024 * 1024;
+
+/// Path of the placeholder file listed when a diff was cut short
+pub const TRUNCATED_PATH: &str = "... (truncated)";
+
+/// Languages that often change together in one project, found from the
+/// extensions of the changed files
+///
+/// The parsers label files by extension alone, and `TypeScriptParser`
+/// reports `.ts` as JavaScript. In a project migrating from JavaScript to
+/// TypeScript, or an iOS/macOS app mixing Swift and Objective-C, each half is
+/// shown as its own language.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProjectLanguageProfile {
+    pub swift: bool,
+    pub objc: bool,
+    pub javascript: bool,
+    pub typescript: bool,
+}
+
+impl ProjectLanguageProfile {
+    pub fn detect(files: &[FileChanges]) -> Self {
+        let mut profile = Self::default();
+        for file in files {
+            match extension(&file.path) {
+                "swift" => profile.swift = true,
+                "m" | "mm" => profile.objc = true,
+                "js" | "jsx" | "mjs" | "cjs" => profile.javascript = true,
+                "ts" | "tsx" | "mts" | "cts" => profile.typescript = true,
+                _ => {}
+            }
+        }
+        profile
+    }
+
+    /// Swift and Objective-C (`.m`/`.mm`) changed together
+    pub fn is_mixed_apple(self) -> bool {
+        self.swift && self.objc
+    }
+
+    /// JavaScript and TypeScript changed together
+    pub fn is_js_ts_migration(self) -> bool {
+        self.javascript && self.typescript
+    }
+}
+
+fn extension(path: &str) -> &str {
+    path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("")
+}
+
+/// Relabel parsed files for the project they're in (`ProjectLanguageProfile`)
+///
+/// Run over the whole set of files after parsing, and again whenever the set
+/// changes: TypeScript files go back under JavaScript once no JavaScript is
+/// left in the diff.
+pub fn coerce_language_names(files: &mut [FileChanges]) {
+    let profile = ProjectLanguageProfile::detect(files);
+    for file in files.iter_mut() {
+        let language = match (file.language.as_str(), extension(&file.path)) {
+            ("JavaScript" | "TypeScript", "ts" | "tsx" | "mts" | "cts") if profile.is_js_ts_migration() => "TypeScript",
+            ("JavaScript" | "TypeScript", _) => "JavaScript",
+            (_, "swift") if profile.is_mixed_apple() => "Swift",
+            (_, "m" | "mm" | "h") if profile.is_mixed_apple() => "Objective-C",
+            _ => continue,
+        };
+        file.language = language.to_string();
+    }
 }
 
 impl DiffSummary {
@@ -50,6 +195,11 @@ impl DiffSummary {
                     .and_modify(|existing| {
                         existing.additions += change.additions;
                         existing.deletions += change.deletions;
+                        for child in &change.children {
+                            if !existing.children.iter().any(|c| c.name == child.name) {
+                                existing.children.push(child.clone());
+                            }
+                        }
                     })
                     .or_insert_with(|| change.clone());
             }
@@ -77,6 +227,16 @@ impl DiffSummary {
     pub fn total_changes(&self) -> usize {
         self.files.iter().map(|f| f.changes.len()).sum()
     }
+
+    /// Number of changes in test files
+    pub fn test_changes(&self) -> usize {
+        self.files.iter().filter(|f| f.is_test).map(|f| f.changes.len()).sum()
+    }
+
+    /// Words added plus words deleted
+    pub fn total_words(&self) -> usize {
+        self.total_words_added + self.total_words_deleted
+    }
 }
 
 impl DiffSummary {
@@ -87,41 +247,112 @@ impl DiffSummary {
         }
     }
 
-    pub async fn refresh(&self) -> Result<Self> {
-        let cwd = std::env::current_dir()?;
-        self.refresh_in_dir(&cwd).await
-    }
-
-    pub async fn refresh_in_dir(&self, dir: &Path) -> Result<Self> {
+    pub async fn refresh_in_dir(
+        &self,
+        dir: &Path,
+        mode: DiffMode,
+        session_start_commit: Option<&str>,
+        max_bytes: usize,
+    ) -> Result<Self> {
         let profile = std::env::var("CRABIGATOR_PROFILE").is_ok();
         let start = std::time::Instant::now();
-        let mut summary = DiffSummary::default();
 
-        // Get the diff output
-        let output = Command::new("git")
-            .args(["diff", "--no-color"])
-            .current_dir(dir)
-            .output()
-            .await?;
+        let Some(combined_diff) = read_diff(dir, mode, session_start_commit).await? else {
+            return Ok(DiffSummary::default());
+        };
+        let summary = Self::from_diff(&combined_diff, max_bytes);
 
-        if !output.status.success() {
+        if profile && start.elapsed().as_millis() > 100 {
+            if let Ok(mut f) = std::fs::OpenOptions::new().create(true).append(true).open("/tmp/crabigator-profile.log") {
+                use std::io::Write;
+                let _ = writeln!(f, "[profile] DiffSummary::refresh took {:?}", start.elapsed());
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Refresh by reparsing only the files whose diff changed since `previous`
+    ///
+    /// The diff is read in full and each file's section hashed; files that
+    /// appeared, disappeared or whose hash differs from `previous.file_stats`
+    /// are parsed again and the rest keep their previous changes. Falls back
+    /// to a full reparse when more than half the files changed, or when
+    /// there's nothing to build on.
+    pub async fn refresh_incremental(
+        dir: &Path,
+        previous: &DiffSummary,
+        mode: DiffMode,
+        session_start_commit: Option<&str>,
+        max_bytes: usize,
+    ) -> Result<Self> {
+        if previous.loading {
+            return DiffSummary::new().refresh_in_dir(dir, mode, session_start_commit, max_bytes).await;
+        }
+        let Some(combined_diff) = read_diff(dir, mode, session_start_commit).await? else {
+            return Ok(DiffSummary::default());
+        };
+
+        let sections = diff_sections(&combined_diff);
+        let mut current: BTreeMap<&str, u64> = BTreeMap::new();
+        for (path, section) in &sections {
+            let hash = current.entry(path).or_default();
+            *hash = section_hash(*hash, section);
+        }
+        let changed: Vec<&str> = current
+            .iter()
+            .filter(|(path, hash)| previous.file_stats.get(**path).map(|s| s.diff_hash) != Some(**hash))
+            .map(|(path, _)| *path)
+            .collect();
+        let removed = previous.file_stats.keys().filter(|path| !current.contains_key(path.as_str())).count();
+
+        let total = current.len().max(previous.file_stats.len());
+        if total > 0 && (changed.len() + removed) as f64 / total as f64 > INCREMENTAL_MAX_CHANGED {
+            return Ok(Self::from_diff(&combined_diff, max_bytes));
+        }
+
+        let mut summary = previous.clone();
+        if changed.is_empty() && removed == 0 {
             return Ok(summary);
         }
+        let keep = |path: &String| current.contains_key(path.as_str()) && !changed.contains(&path.as_str());
+        summary.files.retain(|f| keep(&f.path));
+        summary.file_stats.retain(|path, _| keep(path));
 
-        let diff_output = String::from_utf8_lossy(&output.stdout);
+        if !changed.is_empty() {
+            let diff: String = sections
+                .iter()
+                .filter(|(path, _)| changed.contains(path))
+                .map(|(_, section)| *section)
+                .collect();
+            let fresh = Self::from_diff(&diff, max_bytes);
+            summary.files.extend(fresh.files);
+            summary.file_stats.extend(fresh.file_stats);
+        }
+        coerce_language_names(&mut summary.files);
 
-        // Also get staged changes
-        let staged_output = Command::new("git")
-            .args(["diff", "--cached", "--no-color"])
-            .current_dir(dir)
-            .output()
-            .await?;
+        summary.total_words_added = summary.file_stats.values().map(|s| s.words_added).sum();
+        summary.total_words_deleted = summary.file_stats.values().map(|s| s.words_deleted).sum();
+        Ok(summary)
+    }
 
-        let staged_diff = String::from_utf8_lossy(&staged_output.stdout);
-        let combined_diff = format!("{}\n{}", diff_output, staged_diff);
+    /// Parse combined `git diff` output (e.g. unstaged + `--cached`)
+    ///
+    /// Diffs over `max_bytes` are parsed up to the last whole file that
+    /// fits, followed by a `TRUNCATED_PATH` placeholder.
+    pub fn from_diff(combined_diff: &str, max_bytes: usize) -> Self {
+        let mut summary = DiffSummary::default();
+
+        // A huge generated file could tie up the refresh task for seconds
+        let (combined_diff, truncated) = truncate_diff_at_file_boundary(combined_diff, max_bytes);
+
+        for (path, section) in diff_sections(combined_diff) {
+            let stats = summary.file_stats.entry(path.to_string()).or_default();
+            stats.diff_hash = section_hash(stats.diff_hash, section);
+        }
 
         // Parse the diff into file chunks
-        let file_diffs = parse_diff_into_files(&combined_diff);
+        let file_diffs = parse_diff_into_files(combined_diff);
 
         // Create parsers
         let parsers: Vec<Box<dyn DiffParser>> = vec![
@@ -130,10 +361,37 @@ impl DiffSummary {
             Box::new(PythonParser),
             Box::new(SwiftParser),
             Box::new(ObjCParser),
+            Box::new(CssParser),
+            Box::new(YamlParser),
+            Box::new(JsonParser),
             Box::new(GenericParser),
         ];
 
         for (filename, file_diff) in file_diffs {
+            if file_diff == BINARY_DIFF {
+                summary.file_stats.entry(filename.clone()).or_default();
+                let parser = parsers.iter().find(|p| p.supports(&filename)).unwrap_or(&parsers[parsers.len() - 1]);
+                summary.files.push(FileChanges {
+                    changes: vec![ChangeNode::binary(&filename)],
+                    is_test: is_test_file(&filename),
+                    path: filename,
+                    language: parser.language().to_string(),
+                });
+                continue;
+            }
+
+            let (words_added, words_deleted) = count_words_in_diff(&file_diff);
+            summary.total_words_added += words_added;
+            summary.total_words_deleted += words_deleted;
+
+            // A file with staged and unstaged changes appears twice
+            let (additions, deletions) = count_lines_in_diff(&file_diff);
+            let stats = summary.file_stats.entry(filename.clone()).or_default();
+            stats.additions += additions;
+            stats.deletions += deletions;
+            stats.words_added += words_added;
+            stats.words_deleted += words_deleted;
+
             // Find the appropriate parser
             let parser = parsers
                 .iter()
@@ -157,6 +415,7 @@ impl DiffSummary {
 
             if !changes.is_empty() {
                 summary.files.push(FileChanges {
+                    is_test: is_test_file(&filename),
                     path: filename,
                     language,
                     changes,
@@ -164,17 +423,70 @@ impl DiffSummary {
             }
         }
 
-        if profile && start.elapsed().as_millis() > 100 {
-            if let Ok(mut f) = std::fs::OpenOptions::new().create(true).append(true).open("/tmp/crabigator-profile.log") {
-                use std::io::Write;
-                let _ = writeln!(f, "[profile] DiffSummary::refresh took {:?}", start.elapsed());
-            }
+        coerce_language_names(&mut summary.files);
+
+        if truncated {
+            summary.files.push(FileChanges {
+                path: TRUNCATED_PATH.to_string(),
+                language: GenericParser.language().to_string(),
+                changes: vec![ChangeNode {
+                    kind: NodeKind::Other,
+                    name: "diff too large".to_string(),
+                    change_type: ChangeType::Modified,
+                    additions: 0,
+                    deletions: 0,
+                    file_path: None,
+                    line_number: None,
+                    children: Vec::new(),
+                }],
+                is_test: false,
+            });
         }
 
-        Ok(summary)
+        summary
     }
 }
 
+/// Cut `diff` to at most `max_bytes`, ending before the last `diff --git`
+/// header that fits so no file is parsed half-way
+///
+/// Returns the kept prefix and whether anything was dropped. When even the
+/// first file is over the limit, nothing is kept.
+pub fn truncate_diff_at_file_boundary(diff: &str, max_bytes: usize) -> (&str, bool) {
+    if diff.len() <= max_bytes {
+        return (diff, false);
+    }
+    let end = diff
+        .match_indices("diff --git")
+        .map(|(idx, _)| idx)
+        .take_while(|&idx| idx <= max_bytes)
+        .filter(|&idx| idx == 0 || diff.as_bytes()[idx - 1] == b'\n')
+        .last()
+        .unwrap_or(0);
+    (&diff[..end], true)
+}
+
+/// The diffs of `dir` that `mode` covers, joined; None when `git diff` fails
+/// (not a repository)
+pub(super) async fn read_diff(dir: &Path, mode: DiffMode, session_start_commit: Option<&str>) -> Result<Option<String>> {
+    let mut diffs = Vec::new();
+    for args in mode.diff_args(session_start_commit) {
+        let output = Command::new("git")
+            .args(["diff", "--no-renames"])
+            .args(args)
+            .args(["--no-color", "--"])
+            .current_dir(dir)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+        diffs.push(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    Ok(Some(diffs.join("\n")))
+}
+
 /// Parse hunk headers and context lines to detect modifications inside existing functions
 fn parse_hunk_modifications(diff: &str, parser: &dyn DiffParser, filename: &str) -> Vec<ChangeNode> {
     use std::collections::HashMap;
@@ -183,28 +495,21 @@ fn parse_hunk_modifications(diff: &str, parser: &dyn DiffParser, filename: &str)
 
     // Track changes with their line counts and line number: (additions, deletions, line_number)
     let mut change_map: HashMap<String, (usize, usize, Option<usize>)> = HashMap::new();
-    // Pattern captures: 1=new_line_start, 2=context
-    let hunk_re = Regex::new(r"^@@ -\d+(?:,\d+)? \+(\d+)(?:,\d+)? @@\s*(.*)$").unwrap();
-
     let mut in_hunk = false;
     let mut current_hunk_func: Option<String> = None;
     let mut current_hunk_line: Option<usize> = None;
 
     for line in diff.lines() {
         // Check for hunk header
-        if let Some(caps) = hunk_re.captures(line) {
+        if let Some((start, context)) = parse_hunk_header(line) {
             in_hunk = true;
             current_hunk_func = None;
-
-            // Extract line number from capture group 1
-            current_hunk_line = caps.get(1).and_then(|m| m.as_str().parse().ok());
+            current_hunk_line = Some(start);
 
             // Try to extract function from hunk header context (if present)
-            if let Some(context) = caps.get(2) {
-                let context_str = context.as_str().trim();
-                if !context_str.is_empty() {
-                    current_hunk_func = parser.extract_function_from_context(context_str);
-                }
+            let context_str = context.trim();
+            if !context_str.is_empty() {
+                current_hunk_func = parser.extract_function_from_context(context_str);
             }
             continue;
         }
@@ -255,7 +560,12 @@ fn parse_hunk_modifications(diff: &str, parser: &dyn DiffParser, filename: &str)
         .collect()
 }
 
-fn parse_diff_into_files(diff: &str) -> Vec<(String, String)> {
+/// Stands in for the diff of a binary file (`Binary files a/x and b/x differ`)
+pub(crate) const BINARY_DIFF: &str = "__BINARY__";
+
+/// Split a `git diff` into `(path, diff)` per file; binary files get
+/// `BINARY_DIFF` as their diff
+pub fn parse_diff_into_files(diff: &str) -> Vec<(String, String)> {
     let mut files = Vec::new();
     let mut current_file = String::new();
     let mut current_diff = String::new();
@@ -274,6 +584,10 @@ fn parse_diff_into_files(diff: &str) -> Vec<(String, String)> {
                 current_file = String::new();
             }
             current_diff = String::new();
+        } else if current_diff == BINARY_DIFF {
+            // Nothing after the binary marker belongs to a text diff
+        } else if line.starts_with("Binary files ") && line.ends_with(" differ") {
+            current_diff = BINARY_DIFF.to_string();
         } else {
             current_diff.push_str(line);
             current_diff.push('\n');
@@ -287,3 +601,338 @@ fn parse_diff_into_files(diff: &str) -> Vec<(String, String)> {
 
     files
 }
+
+/// Split a `git diff` at its `diff --git` headers into `(path, section)` per
+/// file, each section starting with its header. Paths are read as
+/// `parse_diff_into_files` reads them; a file in both the staged and unstaged
+/// diff has two sections.
+fn diff_sections(diff: &str) -> Vec<(&str, &str)> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git") {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts.push(diff.len());
+
+    starts
+        .windows(2)
+        .filter_map(|bounds| {
+            let section = &diff[bounds[0]..bounds[1]];
+            let path = section.lines().next()?.split(" b/").nth(1)?;
+            (!path.is_empty()).then_some((path, section))
+        })
+        .collect()
+}
+
+/// Fold one section of a file's diff into its running hash. Trailing blank
+/// lines are left out, since joining the staged and unstaged diffs adds one.
+fn section_hash(previous: u64, section: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (previous, section.trim_end_matches('\n')).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Count added and deleted lines of a single file's diff
+fn count_lines_in_diff(diff: &str) -> (usize, usize) {
+    diff.lines().fold((0, 0), |(added, deleted), line| {
+        if line.starts_with('+') && !line.starts_with("+++") {
+            (added + 1, deleted)
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            (added, deleted + 1)
+        } else {
+            (added, deleted)
+        }
+    })
+}
+
+/// Count words on added and deleted lines of a diff, as `(added, deleted)`
+///
+/// A word is a whitespace-delimited token with at least one letter or digit,
+/// so braces, operators, comment markers and `\` line continuations don't
+/// count. ANSI escape sequences are stripped first.
+pub fn count_words_in_diff(diff: &str) -> (usize, usize) {
+    let mut added = 0;
+    let mut deleted = 0;
+
+    for line in diff.lines() {
+        let line = strip_ansi(line);
+        let counter = if line.starts_with('+') && !line.starts_with("+++") {
+            &mut added
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            &mut deleted
+        } else {
+            continue;
+        };
+        *counter += line[1..]
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+    }
+
+    (added, deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_words_in_prose() {
+        let diff = "\
+--- a/README.md
++++ b/README.md
+@@ -1,3 +1,3 @@
+ # Crabigator
+-A wrapper for Claude Code.
++A thin wrapper around Claude Code and Codex.
+";
+        assert_eq!(count_words_in_diff(diff), (8, 5));
+    }
+
+    #[test]
+    fn test_count_words_skips_noise_in_code() {
+        let diff = "\
+@@ -1,4 +1,5 @@
+ fn main() {
+-    let x = 1;
++    let x = compute(1, 2) \\
++        + 3;
++    // -> done
+ }
+";
+        // `=`, `+`, `\\`, `//` and `->` aren't words
+        assert_eq!(count_words_in_diff(diff), (6, 3));
+    }
+
+    #[test]
+    fn test_count_words_ignores_blank_lines_and_ansi() {
+        let diff = "+\n-\n+   \n+\x1b[32mhello\x1b[0m world\n\\ No newline at end of file\n";
+        assert_eq!(count_words_in_diff(diff), (2, 0));
+        assert_eq!(count_words_in_diff(""), (0, 0));
+    }
+
+    #[test]
+    fn test_summary_totals_words_across_files() {
+        let diff = "\
+diff --git a/a.md b/a.md
+--- a/a.md
++++ b/a.md
+@@ -1 +1 @@
+-one two
++one two three
+diff --git a/b.txt b/b.txt
+--- a/b.txt
++++ b/b.txt
+@@ -0,0 +1 @@
++four five
+";
+        let summary = DiffSummary::from_diff(diff, DEFAULT_MAX_DIFF_BYTES);
+        assert_eq!((summary.total_words_added, summary.total_words_deleted), (5, 2));
+        assert_eq!(summary.total_words(), 7);
+        let stats = summary.file_stats["a.md"];
+        assert_eq!((stats.additions, stats.deletions, stats.words_added), (1, 1, 3));
+    }
+
+    const SWIFT_AND_OBJC: &str = "\
+diff --git a/App/ProfileView.swift b/App/ProfileView.swift
+--- a/App/ProfileView.swift
++++ b/App/ProfileView.swift
+@@ -0,0 +1,3 @@
++func loadAvatar() {
++    fetch()
++}
+diff --git a/Legacy/NetworkClient.m b/Legacy/NetworkClient.m
+--- a/Legacy/NetworkClient.m
++++ b/Legacy/NetworkClient.m
+@@ -0,0 +1,3 @@
++- (void)fetchProfile {
++    [self send];
++}
+diff --git a/Legacy/App-Bridging-Header.h b/Legacy/App-Bridging-Header.h
+--- a/Legacy/App-Bridging-Header.h
++++ b/Legacy/App-Bridging-Header.h
+@@ -0,0 +1 @@
++@interface NetworkClient : NSObject
+";
+
+    #[test]
+    fn test_mixed_swift_and_objc_project() {
+        let summary = DiffSummary::from_diff(SWIFT_AND_OBJC, DEFAULT_MAX_DIFF_BYTES);
+        let profile = ProjectLanguageProfile::detect(&summary.files);
+        assert!(profile.is_mixed_apple());
+        assert!(!profile.is_js_ts_migration());
+
+        let languages: Vec<_> = summary.by_language().into_iter().map(|l| l.language).collect();
+        assert_eq!(languages, vec!["Objective-C", "Swift"]);
+        let header = summary.files.iter().find(|f| f.path.ends_with(".h")).unwrap();
+        assert_eq!(header.language, "Objective-C");
+    }
+
+    fn js_file(path: &str) -> FileChanges {
+        FileChanges {
+            path: path.to_string(),
+            language: "JavaScript".to_string(),
+            changes: Vec::new(),
+            is_test: false,
+        }
+    }
+
+    #[test]
+    fn test_js_ts_migration_shows_both_languages() {
+        let diff = "\
+diff --git a/src/cart.js b/src/cart.js
+--- a/src/cart.js
++++ b/src/cart.js
+@@ -0,0 +1 @@
++function total() {}
+diff --git a/src/cart.ts b/src/cart.ts
+--- a/src/cart.ts
++++ b/src/cart.ts
+@@ -0,0 +1 @@
++function total(): number {}
+";
+        let summary = DiffSummary::from_diff(diff, DEFAULT_MAX_DIFF_BYTES);
+        let languages: Vec<_> = summary.by_language().into_iter().map(|l| l.language).collect();
+        assert_eq!(languages, vec!["JavaScript", "TypeScript"]);
+
+        // TypeScript alone stays under JavaScript, as the parser labels it
+        let mut files = vec![js_file("src/cart.ts"), js_file("src/view.tsx")];
+        coerce_language_names(&mut files);
+        assert!(files.iter().all(|f| f.language == "JavaScript"));
+
+        // Once the JavaScript file is gone from the diff, so is the split
+        let mut files = vec![js_file("src/cart.js"), js_file("src/cart.ts")];
+        coerce_language_names(&mut files);
+        assert_eq!(files[1].language, "TypeScript");
+        files.remove(0);
+        coerce_language_names(&mut files);
+        assert_eq!(files[0].language, "JavaScript");
+    }
+
+    const BINARY_AND_TEXT: &str = "\
+diff --git a/assets/logo.png b/assets/logo.png
+index 3f2a1b0..9c8d7e6 100644
+Binary files a/assets/logo.png and b/assets/logo.png differ
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ pub fn add(a: i32, b: i32) -> i32 {
++    // sum
+     a + b
+ }
+";
+
+    #[test]
+    fn test_binary_files_get_marker_diff() {
+        let files = parse_diff_into_files(BINARY_AND_TEXT);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0], ("assets/logo.png".to_string(), BINARY_DIFF.to_string()));
+        assert!(files[1].1.contains("+    // sum"));
+
+        // New binary files diff against /dev/null
+        let added = "diff --git a/icon.ico b/icon.ico\nnew file mode 100644\nBinary files /dev/null and b/icon.ico differ\n";
+        assert_eq!(parse_diff_into_files(added)[0].1, BINARY_DIFF);
+    }
+
+    #[test]
+    fn test_binary_files_listed_as_single_change() {
+        let summary = DiffSummary::from_diff(BINARY_AND_TEXT, DEFAULT_MAX_DIFF_BYTES);
+        let binary = summary.files.iter().find(|f| f.path == "assets/logo.png").unwrap();
+        assert_eq!(binary.changes.len(), 1);
+        let change = &binary.changes[0];
+        assert!(change.is_binary());
+        assert_eq!(change.name, "(binary)");
+        assert_eq!((change.additions, change.deletions), (0, 0));
+        assert_eq!(change.change_type, ChangeType::Modified);
+        assert_eq!(change.file_path.as_deref(), Some("assets/logo.png"));
+
+        assert_eq!(summary.total_changes(), 2);
+        let stats = summary.file_stats["assets/logo.png"];
+        assert_eq!(stats, FileDiffStats { diff_hash: stats.diff_hash, ..FileDiffStats::default() });
+    }
+
+    const TWO_FILES: &str = "\
+diff --git a/a.rs b/a.rs
++fn a() {}
+diff --git a/b.rs b/b.rs
++fn b() {}
+";
+
+    #[test]
+    fn test_truncate_mid_file_drops_the_partial_file() {
+        let second = TWO_FILES.find("diff --git a/b.rs").unwrap();
+        let (kept, truncated) = truncate_diff_at_file_boundary(TWO_FILES, second + 5);
+        assert!(truncated);
+        assert_eq!(kept, "diff --git a/a.rs b/a.rs\n+fn a() {}\n");
+
+        // Inside the first file there's no whole file to keep
+        assert_eq!(truncate_diff_at_file_boundary(TWO_FILES, 10), ("", true));
+    }
+
+    #[test]
+    fn test_truncate_at_file_boundary() {
+        let second = TWO_FILES.find("diff --git a/b.rs").unwrap();
+        assert_eq!(truncate_diff_at_file_boundary(TWO_FILES, second), (&TWO_FILES[..second], true));
+        // Fits exactly, or with room to spare
+        assert_eq!(truncate_diff_at_file_boundary(TWO_FILES, TWO_FILES.len()), (TWO_FILES, false));
+        assert_eq!(truncate_diff_at_file_boundary(TWO_FILES, usize::MAX), (TWO_FILES, false));
+    }
+
+    #[test]
+    fn test_truncated_summary_lists_placeholder() {
+        let second = TWO_FILES.find("diff --git a/b.rs").unwrap();
+        let summary = DiffSummary::from_diff(TWO_FILES, second + 5);
+        assert!(!summary.loading);
+        let paths: Vec<&str> = summary.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["a.rs", TRUNCATED_PATH]);
+        assert_eq!(summary.files[1].changes[0].name, "diff too large");
+        assert!(!summary.file_stats.contains_key("b.rs"));
+
+        let full = DiffSummary::from_diff(TWO_FILES, TWO_FILES.len());
+        assert!(full.files.iter().all(|f| f.path != TRUNCATED_PATH));
+    }
+
+    #[test]
+    fn test_truncate_ignores_header_text_inside_lines() {
+        let diff = "diff --git a/a.md b/a.md\n+see diff --git output\n+more\n";
+        assert_eq!(truncate_diff_at_file_boundary(diff, 40), ("", true));
+    }
+
+    #[test]
+    fn test_diff_mode_reads() {
+        assert_eq!(DiffMode::Working.diff_args(Some("abc")), vec![vec![], vec!["--cached"]]);
+        assert_eq!(DiffMode::Session.diff_args(Some("abc")), vec![vec!["abc"]]);
+        assert_eq!(DiffMode::All.diff_args(Some("abc")), vec![vec![], vec!["--cached", "abc"]]);
+        // Nothing to diff against yet
+        assert_eq!(DiffMode::Session.diff_args(None), DiffMode::Working.diff_args(None));
+        assert_eq!(DiffMode::parse("Session"), Some(DiffMode::Session));
+        assert_eq!(DiffMode::parse("staged"), None);
+    }
+
+    #[test]
+    fn test_diff_sections_hash_each_file() {
+        let unstaged = "diff --git a/src/a.rs b/src/a.rs\n@@ -1 +1 @@\n-a\n+b\n\
+diff --git a/src/b.rs b/src/b.rs\n@@ -1 +1 @@\n-x\n+y\n";
+        let staged = "diff --git a/src/a.rs b/src/a.rs\n@@ -3 +3 @@\n-c\n+d\n";
+        let combined = [unstaged, staged].join("\n");
+        let sections = diff_sections(&combined);
+        let paths: Vec<&str> = sections.iter().map(|(path, _)| *path).collect();
+        assert_eq!(paths, vec!["src/a.rs", "src/b.rs", "src/a.rs"]);
+        assert!(sections[0].1.ends_with("+b\n"));
+
+        let summary = DiffSummary::from_diff(&combined, DEFAULT_MAX_DIFF_BYTES);
+        let a_hash = section_hash(section_hash(0, sections[0].1), sections[2].1);
+        assert_eq!(summary.file_stats["src/a.rs"].diff_hash, a_hash);
+        assert_eq!(summary.file_stats["src/b.rs"].diff_hash, section_hash(0, sections[1].1));
+
+        // Same line counts, different text
+        let rewritten = combined.replace("+y", "+z");
+        let summary_after = DiffSummary::from_diff(&rewritten, DEFAULT_MAX_DIFF_BYTES);
+        assert_ne!(summary_after.file_stats["src/b.rs"].diff_hash, summary.file_stats["src/b.rs"].diff_hash);
+        assert_eq!(summary_after.file_stats["src/a.rs"].diff_hash, a_hash);
+    }
+}
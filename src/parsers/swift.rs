@@ -1,6 +1,7 @@
 use regex::Regex;
 use std::collections::HashMap;
 
+use super::hunk::HunkLineTracker;
 use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
 
 pub struct SwiftParser;
@@ -17,71 +18,39 @@ impl DiffParser for SwiftParser {
     fn extract_function_from_context(&self, context: &str) -> Option<String> {
         // Swift hunk context patterns:
         // "func name(" or "private func name(" or "public func name("
-        // "class Name" or "struct Name" or "enum Name"
-        let fn_re = Regex::new(r"(?:(?:public|private|internal|fileprivate|open)\s+)?(?:static\s+)?func\s+(\w+)").unwrap();
-        let class_re = Regex::new(r"(?:(?:public|private|internal|fileprivate|open)\s+)?(?:final\s+)?class\s+(\w+)").unwrap();
-        let struct_re = Regex::new(r"(?:(?:public|private|internal|fileprivate|open)\s+)?struct\s+(\w+)").unwrap();
-        let enum_re = Regex::new(r"(?:(?:public|private|internal|fileprivate|open)\s+)?enum\s+(\w+)").unwrap();
-        let extension_re = Regex::new(r"extension\s+(\w+)").unwrap();
-
-        if let Some(caps) = fn_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
-        }
-        if let Some(caps) = class_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
-        }
-        if let Some(caps) = struct_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
-        }
-        if let Some(caps) = enum_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
-        }
-        if let Some(caps) = extension_re.captures(context) {
-            return caps.get(1).map(|m| m.as_str().to_string());
-        }
-        None
+        // "func process(_ event: some EventProtocol) async throws -> Result<Output, Error>"
+        // "class Name", "struct Name", "enum Name", "actor Name", "protocol Name"
+        let patterns = SwiftPatterns::new(false);
+        patterns.declaration(context).map(|(_, name)| name)
     }
 
     fn parse(&self, diff: &str, filename: &str) -> Vec<ChangeNode> {
         let file_path = Some(filename.to_string());
         let mut change_map: HashMap<(NodeKind, String), (ChangeType, usize, usize)> = HashMap::new();
+        // New-file line of each entry's first changed line
+        let mut first_lines: HashMap<(NodeKind, String), usize> = HashMap::new();
+        let mut lines = HunkLineTracker::new();
 
-        // Regex patterns for Swift constructs
-        let fn_re = Regex::new(r"^\s*(?:(?:public|private|internal|fileprivate|open)\s+)?(?:static\s+)?(?:override\s+)?func\s+(\w+)").unwrap();
-        let class_re = Regex::new(r"^\s*(?:(?:public|private|internal|fileprivate|open)\s+)?(?:final\s+)?class\s+(\w+)").unwrap();
-        let struct_re = Regex::new(r"^\s*(?:(?:public|private|internal|fileprivate|open)\s+)?struct\s+(\w+)").unwrap();
-        let enum_re = Regex::new(r"^\s*(?:(?:public|private|internal|fileprivate|open)\s+)?enum\s+(\w+)").unwrap();
-        let protocol_re = Regex::new(r"^\s*(?:(?:public|private|internal|fileprivate|open)\s+)?protocol\s+(\w+)").unwrap();
-        let extension_re = Regex::new(r"^\s*extension\s+(\w+)").unwrap();
-        let hunk_re = Regex::new(r"^@@[^@]+@@\s*(.*)$").unwrap();
+        let patterns = SwiftPatterns::new(true);
 
         let mut current_context: Option<(NodeKind, String)> = None;
+        // Enclosing type (class/struct/actor/extension...), for property wrapper lines
+        let mut current_type: Option<(NodeKind, String)> = None;
+        // Changed attribute-only lines ("@MainActor") waiting for the declaration they annotate,
+        // as (is_added, line_number)
+        let mut pending_attributes: Vec<(bool, usize)> = Vec::new();
 
         for line in diff.lines() {
             // Check for hunk headers with function context
-            if let Some(caps) = hunk_re.captures(line) {
-                if let Some(context) = caps.get(1) {
-                    let context_str = context.as_str();
-                    if let Some(fn_caps) = fn_re.captures(context_str) {
-                        let fn_name = fn_caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                        current_context = Some((NodeKind::Function, fn_name.to_string()));
-                        let key = (NodeKind::Function, fn_name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0));
-                    } else if let Some(class_caps) = class_re.captures(context_str) {
-                        let name = class_caps.get(1).map(|m| m.as_str()).unwrap_or("Unknown");
-                        current_context = Some((NodeKind::Class, name.to_string()));
-                        let key = (NodeKind::Class, name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0));
-                    } else if let Some(ext_caps) = extension_re.captures(context_str) {
-                        let name = ext_caps.get(1).map(|m| m.as_str()).unwrap_or("Unknown");
-                        current_context = Some((NodeKind::Impl, name.to_string()));
-                        let key = (NodeKind::Impl, name.to_string());
-                        change_map.entry(key).or_insert((ChangeType::Modified, 0, 0));
-                    } else {
-                        current_context = None;
+            if let Some(context) = lines.header(line) {
+                flush_attributes(&mut pending_attributes, current_context.as_ref(), &mut change_map, &mut first_lines);
+                current_type = None;
+                current_context = patterns.declaration(context).filter(|(kind, _)| *kind != NodeKind::Other);
+                if let Some(ref key) = current_context {
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    if is_type_kind(&key.0) {
+                        current_type = Some(key.clone());
                     }
-                } else {
-                    current_context = None;
                 }
                 continue;
             }
@@ -89,28 +58,20 @@ impl DiffParser for SwiftParser {
             let is_added = line.starts_with('+') && !line.starts_with("+++");
             let is_removed = line.starts_with('-') && !line.starts_with("---");
             let is_context = line.starts_with(' ');
+            let line_number = lines.advance(line);
 
             // Check context lines for function/class definitions to track current scope
             if is_context {
                 let content = &line[1..];
-                if let Some(caps) = fn_re.captures(content) {
-                    let fn_name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Function, fn_name.to_string()));
-                } else if let Some(caps) = class_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Class, name.to_string()));
-                } else if let Some(caps) = struct_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Struct, name.to_string()));
-                } else if let Some(caps) = enum_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Enum, name.to_string()));
-                } else if let Some(caps) = extension_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Impl, name.to_string()));
-                } else if let Some(caps) = protocol_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    current_context = Some((NodeKind::Trait, name.to_string()));
+                if let Some(key) = patterns.declaration(content) {
+                    flush_attributes(&mut pending_attributes, Some(&key), &mut change_map, &mut first_lines);
+                    if is_type_kind(&key.0) {
+                        current_type = Some(key.clone());
+                    }
+                    current_context = Some(key);
+                } else if !patterns.attribute_only_re.is_match(content) {
+                    let key = current_context.as_ref();
+                    flush_attributes(&mut pending_attributes, key, &mut change_map, &mut first_lines);
                 }
                 continue;
             }
@@ -120,123 +81,279 @@ impl DiffParser for SwiftParser {
             }
 
             let content = &line[1..];
-            let mut found_definition = false;
 
-            // Check for classes
-            if let Some(caps) = class_re.captures(content) {
-                let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                let key = (NodeKind::Class, name.to_string());
+            // Attribute-only lines belong to whatever declaration follows them
+            if patterns.attribute_only_re.is_match(content) {
+                pending_attributes.push((is_added, line_number));
+                continue;
+            }
+
+            if let Some(key) = patterns.declaration(content) {
                 let entry = change_map.entry(key.clone()).or_insert((
                     if is_added { ChangeType::Added } else { ChangeType::Deleted },
                     0, 0,
                 ));
                 if is_added { entry.1 += 1; } else { entry.2 += 1; }
-                current_context = Some(key);
-                found_definition = true;
-            }
-
-            // Check for structs
-            if !found_definition {
-                if let Some(caps) = struct_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Struct, name.to_string());
-                    let entry = change_map.entry(key.clone()).or_insert((
-                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
-                        0, 0,
-                    ));
-                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
-                    current_context = Some(key);
-                    found_definition = true;
+                flush_attributes(&mut pending_attributes, Some(&key), &mut change_map, &mut first_lines);
+                first_lines.entry(key.clone()).or_insert(line_number);
+                if is_type_kind(&key.0) {
+                    current_type = Some(key.clone());
                 }
-            }
-
-            // Check for enums
-            if !found_definition {
-                if let Some(caps) = enum_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Enum, name.to_string());
-                    let entry = change_map.entry(key.clone()).or_insert((
-                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
-                        0, 0,
-                    ));
-                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                // Macros are single declarations, not scopes
+                if key.0 != NodeKind::Other {
                     current_context = Some(key);
-                    found_definition = true;
                 }
+                continue;
             }
 
-            // Check for protocols
-            if !found_definition {
-                if let Some(caps) = protocol_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Trait, name.to_string());
-                    let entry = change_map.entry(key.clone()).or_insert((
-                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
-                        0, 0,
-                    ));
-                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
-                    current_context = Some(key);
-                    found_definition = true;
-                }
-            }
+            flush_attributes(&mut pending_attributes, current_context.as_ref(), &mut change_map, &mut first_lines);
 
-            // Check for extensions
-            if !found_definition {
-                if let Some(caps) = extension_re.captures(content) {
-                    let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Impl, name.to_string());
-                    let entry = change_map.entry(key.clone()).or_insert((
-                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
-                        0, 0,
-                    ));
-                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
-                    current_context = Some(key);
-                    found_definition = true;
-                }
-            }
-
-            // Check for functions
-            if !found_definition {
-                if let Some(caps) = fn_re.captures(content) {
-                    let fn_name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
-                    let key = (NodeKind::Function, fn_name.to_string());
-                    let entry = change_map.entry(key.clone()).or_insert((
-                        if is_added { ChangeType::Added } else { ChangeType::Deleted },
-                        0, 0,
-                    ));
-                    if is_added { entry.1 += 1; } else { entry.2 += 1; }
-                    current_context = Some(key);
-                    found_definition = true;
-                }
-            }
+            // Property wrappers (@Observable, @Bindable, @State...) count toward the enclosing type
+            let target = if patterns.wrapped_property_re.is_match(content) {
+                current_type.as_ref().or(current_context.as_ref())
+            } else {
+                current_context.as_ref()
+            };
 
             // If not a definition line, add to current context
-            if !found_definition {
-                if let Some(ref key) = current_context {
-                    let entry = change_map
-                        .entry(key.clone())
-                        .or_insert((ChangeType::Modified, 0, 0));
-                    if is_added {
-                        entry.1 += 1;
-                    } else {
-                        entry.2 += 1;
-                    }
+            if let Some(key) = target {
+                let entry = change_map
+                    .entry(key.clone())
+                    .or_insert((ChangeType::Modified, 0, 0));
+                if is_added {
+                    entry.1 += 1;
+                } else {
+                    entry.2 += 1;
                 }
+                first_lines.entry(key.clone()).or_insert(line_number);
             }
         }
+        flush_attributes(&mut pending_attributes, current_context.as_ref(), &mut change_map, &mut first_lines);
 
         change_map
             .into_iter()
             .map(|((kind, name), (change_type, additions, deletions))| ChangeNode {
+                line_number: first_lines.get(&(kind.clone(), name.clone())).copied(),
                 kind,
                 name,
                 change_type,
                 additions,
                 deletions,
                 file_path: file_path.clone(),
-                line_number: None, // TODO: extract from hunk headers
                 children: Vec::new(),
             })
+            .filter(ChangeNode::has_changed_lines)
             .collect()
     }
 }
+
+/// Attributes and modifiers that may precede a declaration keyword, e.g.
+/// `@MainActor public final`, `@available(iOS 17, *) nonisolated`, `distributed`
+const DECL_PREFIX: &str = r"(?:@\w+(?:\([^)]*\))?\s+)*(?:(?:public|private|internal|fileprivate|open|package|static|class|final|override|nonisolated|mutating|nonmutating|convenience|required|dynamic|lazy|indirect|distributed|(?:private|fileprivate|internal)\(set\))\s+)*";
+
+/// Compiled regexes for Swift declarations
+struct SwiftPatterns {
+    fn_re: Regex,
+    class_re: Regex,
+    actor_re: Regex,
+    struct_re: Regex,
+    enum_re: Regex,
+    protocol_re: Regex,
+    extension_re: Regex,
+    macro_re: Regex,
+    /// A line holding only attributes, such as `@MainActor` or `@available(iOS 17, *)`
+    attribute_only_re: Regex,
+    /// A stored property with an attribute: `@Bindable var model: Model`
+    wrapped_property_re: Regex,
+}
+
+impl SwiftPatterns {
+    /// `anchored` requires declarations to start the line (diff content);
+    /// hunk header contexts are matched anywhere
+    fn new(anchored: bool) -> Self {
+        let start = if anchored { r"^\s*" } else { "" };
+        let decl = |keyword: &str| {
+            Regex::new(&format!(r"{}{}{}\s+(\w+)", start, DECL_PREFIX, keyword)).unwrap()
+        };
+        Self {
+            fn_re: decl("func"),
+            class_re: decl("class"),
+            actor_re: decl("actor"),
+            struct_re: decl("struct"),
+            enum_re: decl("enum"),
+            protocol_re: decl("protocol"),
+            extension_re: decl("extension"),
+            macro_re: decl("macro"),
+            attribute_only_re: Regex::new(r"^\s*(?:@\w+(?:\([^)]*\))?\s*)+$").unwrap(),
+            wrapped_property_re: Regex::new(&format!(r"^\s*@\w+(?:\([^)]*\))?\s+{}(?:var|let)\s", DECL_PREFIX)).unwrap(),
+        }
+    }
+
+    /// Match a declaration (at the start of the line when anchored)
+    fn declaration(&self, content: &str) -> Option<(NodeKind, String)> {
+        // Functions first so "class func" and "static func" aren't read as types
+        let checks: [(&Regex, NodeKind); 8] = [
+            (&self.fn_re, NodeKind::Function),
+            (&self.macro_re, NodeKind::Other),
+            (&self.class_re, NodeKind::Class),
+            (&self.actor_re, NodeKind::Class),
+            (&self.struct_re, NodeKind::Struct),
+            (&self.enum_re, NodeKind::Enum),
+            (&self.protocol_re, NodeKind::Trait),
+            (&self.extension_re, NodeKind::Impl),
+        ];
+        for (re, kind) in checks {
+            if let Some(caps) = re.captures(content) {
+                let name = caps.get(1).map(|m| m.as_str()).unwrap_or("unknown");
+                // "class var", "class let" are type properties, not classes
+                if matches!(name, "var" | "let" | "func" | "subscript" | "init") {
+                    continue;
+                }
+                let name = if kind == NodeKind::Other {
+                    format!("macro {}", name)
+                } else {
+                    name.to_string()
+                };
+                return Some((kind, name));
+            }
+        }
+        None
+    }
+}
+
+fn is_type_kind(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Struct | NodeKind::Enum | NodeKind::Trait | NodeKind::Impl
+    )
+}
+
+/// Attribute pending attribute-only lines to the declaration they annotate
+fn flush_attributes(
+    pending: &mut Vec<(bool, usize)>,
+    key: Option<&(NodeKind, String)>,
+    change_map: &mut HashMap<(NodeKind, String), (ChangeType, usize, usize)>,
+    first_lines: &mut HashMap<(NodeKind, String), usize>,
+) {
+    if let Some(key) = key {
+        for &(is_added, line_number) in pending.iter() {
+            let entry = change_map
+                .entry(key.clone())
+                .or_insert((ChangeType::Modified, 0, 0));
+            if is_added { entry.1 += 1; } else { entry.2 += 1; }
+            first_lines.entry(key.clone()).or_insert(line_number);
+        }
+    }
+    pending.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+
+    #[test]
+    fn test_async_throws_hunk_context() {
+        let parser = SwiftParser;
+        assert_eq!(
+            parser.extract_function_from_context(
+                "func process(_ event: some EventProtocol) async throws -> Result<Output, Error> {"
+            ),
+            Some("process".to_string())
+        );
+        assert_eq!(
+            parser.extract_function_from_context("@MainActor final class ViewModel {"),
+            Some("ViewModel".to_string())
+        );
+        assert_eq!(
+            parser.extract_function_from_context("public class func make() -> Self {"),
+            Some("make".to_string())
+        );
+    }
+
+    #[test]
+    fn test_actor_isolation_changes() {
+        let diff = "\
+@@ -1,6 +1,8 @@
+-actor Cache {
++distributed actor Cache {
+     var items: [String: Data] = [:]
+ 
++    @MainActor
+     func reload() async throws {
+-        items = [:]
++        items.removeAll()
+     }
+ }
+";
+        let changes = SwiftParser.parse(diff, "Cache.swift");
+        let cache = find(&changes, "Cache");
+        assert_eq!(cache.kind, NodeKind::Class);
+        assert_eq!(cache.additions, 1);
+        assert_eq!(cache.deletions, 1);
+        assert_eq!(cache.line_number, Some(1));
+
+        // The attribute line is absorbed into the declaration it annotates
+        let reload = find(&changes, "reload");
+        assert_eq!(reload.additions, 2);
+        assert_eq!(reload.deletions, 1);
+        assert_eq!(reload.line_number, Some(4));
+    }
+
+    #[test]
+    fn test_protocol_witness_changes() {
+        let diff = "\
+@@ -1,8 +1,8 @@
+ protocol EventHandler {
+-    func handle(_ event: Event) throws
++    func handle(_ event: Event) async throws
+ }
+ 
+ extension Logger: EventHandler {
+     func handle(_ event: Event) async throws {
+-        print(event)
++        await write(event)
+     }
+ }
+";
+        let changes = SwiftParser.parse(diff, "Handler.swift");
+        let handle = find(&changes, "handle");
+        assert_eq!(handle.kind, NodeKind::Function);
+        assert_eq!(handle.additions, 2);
+        assert_eq!(handle.deletions, 2);
+    }
+
+    #[test]
+    fn test_macro_definitions() {
+        let diff = "\
+@@ -1,2 +1,3 @@
++@freestanding(expression)
++public macro stringify<T>(_ value: T) -> (T, String) = #externalMacro(module: \"Macros\", type: \"StringifyMacro\")
+";
+        let changes = SwiftParser.parse(diff, "Macros.swift");
+        let stringify = find(&changes, "macro stringify");
+        assert_eq!(stringify.kind, NodeKind::Other);
+        assert_eq!(stringify.change_type, ChangeType::Added);
+        assert_eq!(stringify.additions, 2);
+    }
+
+    #[test]
+    fn test_property_wrappers_track_enclosing_type() {
+        let diff = "\
+@@ -1,8 +1,8 @@
+ @Observable
+ final class Settings {
+     func reset() {
+         theme = .system
+     }
+-    @ObservationIgnored var cache = Cache()
++    @ObservationIgnored private var cache = Cache()
+ }
+";
+        let changes = SwiftParser.parse(diff, "Settings.swift");
+        let settings = find(&changes, "Settings");
+        assert_eq!(settings.additions, 1);
+        assert_eq!(settings.deletions, 1);
+        assert!(!changes.iter().any(|c| c.name == "reset" && c.additions > 0));
+    }
+}
@@ -1,6 +1,6 @@
 //! Shared types for diff parsing
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub enum NodeKind {
     Class,
     Function,
@@ -11,20 +11,26 @@ pub enum NodeKind {
     Impl,
     Module,
     Const,
+    /// CSS selector (".card > .title")
+    Selector,
+    /// CSS `@media` query block
+    MediaQuery,
+    #[default]
     Other,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum ChangeType {
     /// A new definition was added
     Added,
     /// Code was modified inside an existing definition
+    #[default]
     Modified,
     /// A definition was deleted
     Deleted,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct ChangeNode {
     pub kind: NodeKind,
     pub name: String,
@@ -35,16 +41,123 @@ pub struct ChangeNode {
     pub file_path: Option<String>,
     /// Line number where the symbol is defined (1-indexed)
     pub line_number: Option<usize>,
-    #[allow(dead_code)]
+    /// Finer-grained changes within this symbol (e.g., generic parameter lists)
     pub children: Vec<ChangeNode>,
 }
 
+/// Name of the single change listed for a binary file
+pub const BINARY_CHANGE_NAME: &str = "(binary)";
+
+impl ChangeNode {
+    /// Placeholder for a binary file, whose diff has no lines to parse
+    pub fn binary(file_path: &str) -> Self {
+        Self {
+            kind: NodeKind::Other,
+            name: BINARY_CHANGE_NAME.to_string(),
+            change_type: ChangeType::Modified,
+            additions: 0,
+            deletions: 0,
+            file_path: Some(file_path.to_string()),
+            line_number: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Whether this is the placeholder for a binary file
+    pub fn is_binary(&self) -> bool {
+        self.kind == NodeKind::Other && self.name == BINARY_CHANGE_NAME
+    }
+
+    /// Whether the symbol has changed lines of its own; one seen only in hunk
+    /// context (the `@@` header or unchanged lines) has none and isn't listed
+    pub fn has_changed_lines(&self) -> bool {
+        self.additions + self.deletions > 0
+    }
+
+    /// Whether the change is in a test file (see `is_test_file`)
+    pub fn is_test(&self) -> bool {
+        self.file_path.as_deref().is_some_and(is_test_file)
+    }
+
+    /// A modified function in `path`, for tests to adjust with `..`
+    #[cfg(test)]
+    pub fn fixture(name: &str, path: &str) -> Self {
+        Self {
+            kind: NodeKind::Function,
+            name: name.to_string(),
+            file_path: Some(path.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The change in `changes` named `name`, for tests
+#[cfg(test)]
+pub fn find<'a>(changes: &'a [ChangeNode], name: &str) -> &'a ChangeNode {
+    changes
+        .iter()
+        .find(|c| c.name == name)
+        .unwrap_or_else(|| panic!("no change named {}", name))
+}
+
+/// Line counts for one key or node, as the JSON and YAML parsers tally them;
+/// `added`/`removed` record whether its own declaring line was added or removed
+#[derive(Default)]
+pub struct Tally {
+    pub additions: usize,
+    pub deletions: usize,
+    pub added: bool,
+    pub removed: bool,
+}
+
+impl Tally {
+    pub fn count(&mut self, is_added: bool, declares: bool) {
+        if is_added {
+            self.additions += 1;
+            self.added |= declares;
+        } else {
+            self.deletions += 1;
+            self.removed |= declares;
+        }
+    }
+
+    pub fn change_type(&self) -> ChangeType {
+        match (self.added, self.removed) {
+            (true, false) => ChangeType::Added,
+            (false, true) => ChangeType::Deleted,
+            _ => ChangeType::Modified,
+        }
+    }
+}
+
+/// Whether a file name follows a common test naming convention:
+/// `*_test.rs`, `*_spec.ts`/`*.spec.ts`, `*.test.js`/`*.test.tsx`, `test_*.py`/
+/// `*_test.py`, `*Test.java`/`*Tests.java`, `*Spec.kt`/`*Test.kt`
+pub fn is_test_file(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let Some((stem, ext)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    match ext {
+        "rs" | "go" => stem.ends_with("_test"),
+        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => {
+            stem.ends_with(".test") || stem.ends_with(".spec") || stem.ends_with("_spec") || stem.ends_with("_test")
+        }
+        "py" => stem.starts_with("test_") || stem.ends_with("_test"),
+        "java" | "kt" | "swift" => stem.ends_with("Test") || stem.ends_with("Tests") || stem.ends_with("Spec"),
+        "rb" => stem.ends_with("_spec") || stem.ends_with("_test"),
+        _ => false,
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FileChanges {
     #[allow(dead_code)]
     pub path: String,
     pub language: String,
     pub changes: Vec<ChangeNode>,
+    /// The file is a test (`is_test_file`)
+    pub is_test: bool,
 }
 
 /// Changes grouped by language for display
@@ -53,3 +166,45 @@ pub struct LanguageChanges {
     pub language: String,
     pub changes: Vec<ChangeNode>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_patterns_rust() {
+        assert!(is_test_file("src/parser_test.rs"));
+        assert!(!is_test_file("src/parser.rs"));
+        assert!(!is_test_file("src/tests.rs"));
+        assert!(!is_test_file("src/test_utils.rs"));
+    }
+
+    #[test]
+    fn test_file_patterns_typescript() {
+        assert!(is_test_file("web/client_spec.ts"));
+        assert!(is_test_file("web/client.spec.ts"));
+        assert!(is_test_file("web/Button.test.tsx"));
+        assert!(is_test_file("lib/util.test.js"));
+        assert!(!is_test_file("web/client.ts"));
+        assert!(!is_test_file("web/spec.ts"));
+        assert!(!is_test_file("web/latest.ts"));
+    }
+
+    #[test]
+    fn test_file_patterns_python() {
+        assert!(is_test_file("tests/test_sync.py"));
+        assert!(is_test_file("sync_test.py"));
+        assert!(!is_test_file("scripts/sync.py"));
+        assert!(!is_test_file("contest.py"));
+        assert!(!is_test_file("test_data.json"));
+    }
+
+    #[test]
+    fn test_file_patterns_java_and_kotlin() {
+        assert!(is_test_file("src/test/java/com/x/ClientTest.java"));
+        assert!(is_test_file("src/test/java/com/x/ClientTests.java"));
+        assert!(is_test_file("app/src/test/ClientSpec.kt"));
+        assert!(!is_test_file("src/main/java/com/x/Client.java"));
+        assert!(!is_test_file("src/main/java/com/x/Contest.java"));
+    }
+}
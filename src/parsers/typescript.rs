@@ -1,6 +1,7 @@
 use regex::Regex;
 use std::collections::HashMap;
 
+use super::hunk::HunkLineTracker;
 use super::{ChangeNode, ChangeType, DiffParser, NodeKind};
 
 pub struct TypeScriptParser;
@@ -76,6 +77,8 @@ impl DiffParser for TypeScriptParser {
         // Track changes with their line counts
         // Key: (kind, name), Value: (change_type, additions, deletions)
         let mut change_map: HashMap<(NodeKind, String), (ChangeType, usize, usize)> = HashMap::new();
+        // New-file line of each entry's first changed line
+        let mut first_lines: HashMap<(NodeKind, String), usize> = HashMap::new();
 
         // Regex patterns for TypeScript/JavaScript constructs
         let class_re = Regex::new(r"^\s*(export\s+)?(abstract\s+)?class\s+(\w+)").unwrap();
@@ -91,23 +94,23 @@ impl DiffParser for TypeScriptParser {
                 .unwrap();
         let interface_re = Regex::new(r"^\s*(export\s+)?interface\s+(\w+)").unwrap();
         let type_re = Regex::new(r"^\s*(export\s+)?type\s+(\w+)").unwrap();
-        let hunk_re = Regex::new(r"^@@[^@]+@@\s*(.*)$").unwrap();
 
         // Current context: which function/class we're inside
         let mut current_context: Option<(NodeKind, String)> = None;
+        let mut lines = HunkLineTracker::new();
+
+        // Generic parameter lists seen on removed/added declaration lines.
+        // Stored as lists so overloads with the same name compare as a set.
+        let mut removed_type_params: HashMap<(NodeKind, String), Vec<Option<String>>> = HashMap::new();
+        let mut added_type_params: HashMap<(NodeKind, String), Vec<Option<String>>> = HashMap::new();
 
         for line in diff.lines() {
             // Check for hunk headers with function context
-            if let Some(caps) = hunk_re.captures(line) {
-                if let Some(context) = caps.get(1) {
-                    let context_str = context.as_str();
-                    if let Some(fn_name) = self.extract_function_from_context(context_str) {
-                        let key = (NodeKind::Function, fn_name.clone());
-                        change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
-                        current_context = Some(key);
-                    } else {
-                        current_context = None;
-                    }
+            if let Some(context) = lines.header(line) {
+                if let Some(fn_name) = self.extract_function_from_context(context) {
+                    let key = (NodeKind::Function, fn_name.clone());
+                    change_map.entry(key.clone()).or_insert((ChangeType::Modified, 0, 0));
+                    current_context = Some(key);
                 } else {
                     current_context = None;
                 }
@@ -117,6 +120,7 @@ impl DiffParser for TypeScriptParser {
             let is_added = line.starts_with('+') && !line.starts_with("+++");
             let is_removed = line.starts_with('-') && !line.starts_with("---");
             let is_context = line.starts_with(' ');
+            let line_number = lines.advance(line);
 
             // Check context lines for function/class definitions to track current scope
             if is_context {
@@ -161,6 +165,8 @@ impl DiffParser for TypeScriptParser {
 
             let content = &line[1..];
             let mut found_definition = false;
+            // Declaration key and the byte offset just past its name (for generic params)
+            let mut declared: Option<((NodeKind, String), usize)> = None;
 
             // Check for class definitions
             if let Some(caps) = class_re.captures(content) {
@@ -172,6 +178,8 @@ impl DiffParser for TypeScriptParser {
                     0,
                 ));
                 if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                first_lines.entry(key.clone()).or_insert(line_number);
+                declared = caps.get(3).map(|m| (key.clone(), m.end()));
                 current_context = Some(key);
                 found_definition = true;
             }
@@ -187,6 +195,8 @@ impl DiffParser for TypeScriptParser {
                         0,
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
+                    declared = caps.get(2).map(|m| (key.clone(), m.end()));
                     current_context = Some(key);
                     found_definition = true;
                 }
@@ -203,6 +213,8 @@ impl DiffParser for TypeScriptParser {
                         0,
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
+                    declared = caps.get(2).map(|m| (key, m.end()));
                     found_definition = true;
                 }
             }
@@ -210,11 +222,8 @@ impl DiffParser for TypeScriptParser {
             // Check for function declarations
             if !found_definition {
                 if let Some(caps) = function_re.captures(content) {
-                    let name = caps
-                        .get(3)
-                        .or_else(|| caps.get(6))
-                        .map(|m| m.as_str())
-                        .unwrap_or("unknown");
+                    let name_match = caps.get(3).or_else(|| caps.get(6));
+                    let name = name_match.map(|m| m.as_str()).unwrap_or("unknown");
                     let key = (NodeKind::Function, name.to_string());
                     let entry = change_map.entry(key.clone()).or_insert((
                         if is_added { ChangeType::Added } else { ChangeType::Deleted },
@@ -222,6 +231,8 @@ impl DiffParser for TypeScriptParser {
                         0,
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
+                    declared = name_match.map(|m| (key.clone(), m.end()));
                     current_context = Some(key);
                     found_definition = true;
                 }
@@ -238,6 +249,7 @@ impl DiffParser for TypeScriptParser {
                         0,
                     ));
                     if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                    first_lines.entry(key.clone()).or_insert(line_number);
                     current_context = Some(key);
                     found_definition = true;
                 }
@@ -261,12 +273,20 @@ impl DiffParser for TypeScriptParser {
                             0,
                         ));
                         if is_added { entry.1 += 1; } else { entry.2 += 1; }
+                        first_lines.entry(key.clone()).or_insert(line_number);
                         current_context = Some(key);
                         found_definition = true;
                     }
                 }
             }
 
+            // Remember the generic parameter list of this declaration
+            if let Some((key, name_end)) = declared {
+                let params = extract_type_params(&content[name_end..]);
+                let side = if is_added { &mut added_type_params } else { &mut removed_type_params };
+                side.entry(key).or_default().push(params);
+            }
+
             // If not a definition line, add to current context
             if !found_definition {
                 if let Some(ref key) = current_context {
@@ -278,6 +298,7 @@ impl DiffParser for TypeScriptParser {
                     } else {
                         entry.2 += 1;
                     }
+                    first_lines.entry(key.clone()).or_insert(line_number);
                 }
             }
         }
@@ -285,16 +306,180 @@ impl DiffParser for TypeScriptParser {
         // Convert map to vec of ChangeNodes
         change_map
             .into_iter()
-            .map(|((kind, name), (change_type, additions, deletions))| ChangeNode {
-                kind,
-                name,
-                change_type,
-                additions,
-                deletions,
-                file_path: file_path.clone(),
-                line_number: None, // TODO: extract from hunk headers
-                children: Vec::new(),
+            .map(|(key, (mut change_type, additions, deletions))| {
+                let mut children = Vec::new();
+                // A declaration on both sides of the diff is a signature change,
+                // not an add/delete pair
+                if let (Some(removed), Some(added)) =
+                    (removed_type_params.get_mut(&key), added_type_params.get_mut(&key))
+                {
+                    change_type = ChangeType::Modified;
+                    removed.sort();
+                    added.sort();
+                    if removed != added {
+                        children.push(ChangeNode {
+                            kind: NodeKind::Other,
+                            name: "<type-params>".to_string(),
+                            change_type: ChangeType::Modified,
+                            additions: added.len(),
+                            deletions: removed.len(),
+                            file_path: file_path.clone(),
+                            line_number: None,
+                            children: Vec::new(),
+                        });
+                    }
+                }
+                let line_number = first_lines.get(&key).copied();
+                let (kind, name) = key;
+                ChangeNode {
+                    kind,
+                    name,
+                    change_type,
+                    additions,
+                    deletions,
+                    file_path: file_path.clone(),
+                    line_number,
+                    children,
+                }
             })
+            .filter(ChangeNode::has_changed_lines)
             .collect()
     }
 }
+
+/// Extract the generic parameter list that immediately follows a declaration name.
+///
+/// Given the text after `Repository` in `interface Repository<T, E extends Error> {`,
+/// returns `Some("T, E extends Error")`. Whitespace is normalized so that
+/// reformatting alone doesn't register as a change.
+fn extract_type_params(rest: &str) -> Option<String> {
+    let rest = rest.trim_start();
+    if !rest.starts_with('<') {
+        return None;
+    }
+
+    let mut depth = 0usize;
+    let mut prev = '\0';
+    for (i, c) in rest.char_indices() {
+        match c {
+            '<' => depth += 1,
+            // Skip the `>` of arrow functions in defaults like `<T = () => void>`
+            '>' if prev != '=' => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner = &rest[1..i];
+                    return Some(inner.split_whitespace().collect::<Vec<_>>().join(" "));
+                }
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+
+    #[test]
+    fn test_interface_generic_params_change_adds_child() {
+        let diff = "\
+@@ -1,3 +1,3 @@
+-export interface Repository<T> {
++export interface Repository<T, E extends Error> {
+   find(id: string): T;
+ }
+";
+        let changes = TypeScriptParser.parse(diff, "repo.ts");
+        let repo = find(&changes, "Repository");
+        assert_eq!(repo.kind, NodeKind::Trait);
+        assert_eq!(repo.change_type, ChangeType::Modified);
+        assert_eq!(repo.children.len(), 1);
+        assert_eq!(repo.children[0].name, "<type-params>");
+        assert_eq!(repo.children[0].kind, NodeKind::Other);
+    }
+
+    #[test]
+    fn test_function_overload_generic_change_adds_child() {
+        let diff = "\
+@@ -1,3 +1,3 @@
+ function parse<T>(input: string): T;
+-function parse<T>(input: Buffer): T;
++function parse<T extends object>(input: Buffer): T;
+";
+        let changes = TypeScriptParser.parse(diff, "parse.ts");
+        let parse = find(&changes, "parse");
+        assert_eq!(parse.children.len(), 1);
+        assert_eq!(parse.children[0].name, "<type-params>");
+    }
+
+    #[test]
+    fn test_body_only_change_has_no_type_params_child() {
+        let diff = "\
+@@ -1,2 +1,2 @@
+-export interface Repository<T> { find(id: string): T }
++export interface Repository<T> { find(id: number): T }
+";
+        let changes = TypeScriptParser.parse(diff, "repo.ts");
+        assert!(find(&changes, "Repository").children.is_empty());
+    }
+
+    #[test]
+    fn test_utility_type_changes() {
+        // Changing the aliased type doesn't touch the alias's own parameters
+        let diff = "\
+@@ -1,1 +1,1 @@
+-export type Options<T> = Partial<T>;
++export type Options<T> = Partial<T & Extra>;
+";
+        let changes = TypeScriptParser.parse(diff, "options.ts");
+        assert!(find(&changes, "type Options").children.is_empty());
+
+        let diff = "\
+@@ -1,1 +1,1 @@
+-export type Options<T> = Partial<T>;
++export type Options<T, E = Extra> = Partial<T & E>;
+";
+        let changes = TypeScriptParser.parse(diff, "options.ts");
+        let options = find(&changes, "type Options");
+        assert_eq!(options.children.len(), 1);
+        assert_eq!(options.children[0].name, "<type-params>");
+    }
+
+    #[test]
+    fn test_extract_type_params_handles_nesting_and_arrows() {
+        assert_eq!(extract_type_params("<T> {"), Some("T".to_string()));
+        assert_eq!(
+            extract_type_params("<T extends Map<string, number>> {"),
+            Some("T extends Map<string, number>".to_string())
+        );
+        assert_eq!(
+            extract_type_params("<T = () => void>("),
+            Some("T = () => void".to_string())
+        );
+        assert_eq!(extract_type_params(" {"), None);
+    }
+
+    #[test]
+    fn test_line_numbers_come_from_hunk_headers() {
+        let diff = "\
+@@ -5,4 +5,6 @@ export class Cart {
+   total(): number {
+-    return this.items.length;
++    return this.items.reduce((sum, n) => sum + n, 0);
+   }
++
++  clear(): void {}
+@@ -30,3 +32,4 @@ export class Cart {
+ export function checkout(cart: Cart) {
++  cart.clear();
+ }
+";
+        let changes = TypeScriptParser.parse(diff, "cart.ts");
+        assert_eq!(find(&changes, "total").line_number, Some(6));
+        assert_eq!(find(&changes, "clear").line_number, Some(9));
+        assert_eq!(find(&changes, "checkout").line_number, Some(33));
+    }
+}
@@ -0,0 +1,279 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::types::Tally;
+use super::{ChangeNode, DiffParser, NodeKind};
+
+pub struct YamlParser;
+
+/// Where a line sits in one side (old or new) of the file
+#[derive(Clone, Default)]
+struct Scope {
+    /// Enclosing top-level key ("spec", "jobs")
+    key: Option<String>,
+    /// Enclosing `- name:` list item: (dash indent, name)
+    item: Option<(usize, String)>,
+}
+
+impl DiffParser for YamlParser {
+    fn language(&self) -> &'static str {
+        "YAML"
+    }
+
+    fn supports(&self, filename: &str) -> bool {
+        filename.ends_with(".yaml") || filename.ends_with(".yml")
+    }
+
+    fn extract_function_from_context(&self, context: &str) -> Option<String> {
+        // Git's default hunk context is the last unindented line, usually a top-level key
+        top_level_key(context)
+    }
+
+    fn parse(&self, diff: &str, filename: &str) -> Vec<ChangeNode> {
+        let hunk_re = Regex::new(r"^@@[^@]+@@\s*(.*)$").unwrap();
+        let anchor_re = Regex::new(r"&([A-Za-z0-9_.\-]+)").unwrap();
+
+        // Top-level keys with their `- name:` items, plus anchors
+        let mut keys: HashMap<String, (Tally, HashMap<String, Tally>)> = HashMap::new();
+        let mut anchors: HashMap<String, Tally> = HashMap::new();
+
+        // Scope in the old and new file; context lines advance both
+        let mut old = Scope::default();
+        let mut new = Scope::default();
+
+        for line in diff.lines() {
+            if let Some(caps) = hunk_re.captures(line) {
+                let key = caps.get(1).and_then(|c| top_level_key(c.as_str()));
+                old = Scope { key, item: None };
+                new = old.clone();
+                continue;
+            }
+
+            let is_added = line.starts_with('+') && !line.starts_with("+++");
+            let is_removed = line.starts_with('-') && !line.starts_with("---");
+            let is_context = line.starts_with(' ');
+            if !is_added && !is_removed && !is_context {
+                continue;
+            }
+
+            let content = &line[1..];
+            let declared_key = top_level_key(content);
+            let declared_item = list_item_name(content);
+
+            // Advance the scope of each side this line belongs to
+            for (scope, applies) in [(&mut old, !is_added), (&mut new, !is_removed)] {
+                if applies {
+                    update_scope(scope, content, &declared_key, &declared_item);
+                }
+            }
+
+            if is_context {
+                continue;
+            }
+
+            let scope = if is_added { &new } else { &old };
+            if let Some(key) = &scope.key {
+                let (tally, items) = keys.entry(key.clone()).or_default();
+                tally.count(is_added, declared_key.is_some());
+                if let Some((_, item)) = &scope.item {
+                    let declares = declared_item.as_ref().is_some_and(|(_, name)| name == item);
+                    items.entry(item.clone()).or_default().count(is_added, declares);
+                }
+            }
+            for caps in anchor_re.captures_iter(content) {
+                anchors.entry(format!("&{}", &caps[1])).or_default().count(is_added, true);
+            }
+        }
+
+        let file_path = Some(filename.to_string());
+        let node = |kind: NodeKind, name: String, tally: &Tally, children: Vec<ChangeNode>| ChangeNode {
+            kind,
+            name,
+            change_type: tally.change_type(),
+            additions: tally.additions,
+            deletions: tally.deletions,
+            file_path: file_path.clone(),
+            line_number: None,
+            children,
+        };
+
+        let mut changes: Vec<ChangeNode> = keys
+            .into_iter()
+            .map(|(key, (tally, items))| {
+                let mut children: Vec<ChangeNode> = items
+                    .iter()
+                    .map(|(name, item)| node(NodeKind::Other, name.clone(), item, Vec::new()))
+                    .collect();
+                children.sort_by(|a, b| a.name.cmp(&b.name));
+                node(NodeKind::Const, key, &tally, children)
+            })
+            .collect();
+        changes.extend(
+            anchors
+                .into_iter()
+                .map(|(name, tally)| node(NodeKind::Other, name, &tally, Vec::new())),
+        );
+        changes
+    }
+}
+
+fn update_scope(
+    scope: &mut Scope,
+    content: &str,
+    declared_key: &Option<String>,
+    declared_item: &Option<(usize, String)>,
+) {
+    if content.trim() == "---" {
+        // New document in a multi-document stream
+        *scope = Scope::default();
+        return;
+    }
+    if let Some(key) = declared_key {
+        *scope = Scope {
+            key: Some(key.clone()),
+            item: None,
+        };
+        return;
+    }
+    if let Some(item) = declared_item {
+        scope.item = Some(item.clone());
+        return;
+    }
+    // A shallower (or sibling) line ends the current list item
+    let trimmed = content.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return;
+    }
+    let indent = content.len() - trimmed.len();
+    if scope.item.as_ref().is_some_and(|(item_indent, _)| indent <= *item_indent) {
+        scope.item = None;
+    }
+}
+
+/// An unindented `key:` line (not a list item, comment, or directive)
+fn top_level_key(content: &str) -> Option<String> {
+    if content.starts_with(|c: char| c.is_whitespace() || matches!(c, '-' | '#' | '%' | '{' | '[')) {
+        return None;
+    }
+    let (key, rest) = content.split_once(':')?;
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// A `- name: value` list item: (dash indent, value)
+fn list_item_name(content: &str) -> Option<(usize, String)> {
+    let trimmed = content.trim_start();
+    let indent = content.len() - trimmed.len();
+    let value = trimmed.strip_prefix("- ")?.trim_start().strip_prefix("name:")?;
+    let value = value.split(" #").next().unwrap_or(value).trim();
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    (!value.is_empty()).then(|| (indent, value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::types::find;
+    use crate::parsers::ChangeType;
+
+    #[test]
+    fn test_kubernetes_manifest_changes() {
+        let diff = "\
+@@ -5,14 +5,16 @@ metadata:
+   labels:
+     app: web
+ spec:
+-  replicas: 2
++  replicas: 3
+   template:
+     spec:
+       containers:
+         - name: web
+-          image: nginx:1.25
++          image: nginx:1.27
++        - name: metrics
++          image: prom/exporter:0.15
+         - name: sidecar
+           image: envoy:1.30
+@@ -40,3 +42,5 @@ spec:
+     port: 80
++---
++kind: ConfigMap
+";
+        let changes = YamlParser.parse(diff, "deploy/web.yaml");
+
+        let spec = find(&changes, "spec");
+        assert_eq!(spec.kind, NodeKind::Const);
+        assert_eq!(spec.change_type, ChangeType::Modified);
+        assert_eq!((spec.additions, spec.deletions), (4, 2));
+
+        let names: Vec<&str> = spec.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["metrics", "web"]);
+        assert_eq!(find(&spec.children, "metrics").change_type, ChangeType::Added);
+        assert_eq!(find(&spec.children, "web").change_type, ChangeType::Modified);
+
+        // A new document's keys are separate from the previous document's
+        assert_eq!(find(&changes, "kind").change_type, ChangeType::Added);
+        assert!(!changes.iter().any(|c| c.name == "metadata"));
+    }
+
+    #[test]
+    fn test_github_actions_job_and_step_names() {
+        let diff = "\
+@@ -8,12 +8,12 @@ on:
+ jobs:
+   test:
+-    name: Test
++    name: Unit tests
+     runs-on: ubuntu-latest
+     steps:
+       - uses: actions/checkout@v4
+-      - name: Run tests
++      - name: Run unit tests
+         run: cargo test
+";
+        let changes = YamlParser.parse(diff, ".github/workflows/ci.yml");
+        let jobs = find(&changes, "jobs");
+        assert_eq!((jobs.additions, jobs.deletions), (2, 2));
+
+        // Renaming a step shows up as the old item going and the new one arriving
+        assert_eq!(find(&jobs.children, "Run tests").change_type, ChangeType::Deleted);
+        assert_eq!(find(&jobs.children, "Run unit tests").change_type, ChangeType::Added);
+        assert_eq!(jobs.children.len(), 2);
+    }
+
+    #[test]
+    fn test_top_level_keys_and_anchors() {
+        let diff = "\
+@@ -1,6 +1,8 @@
++defaults: &defaults
++  adapter: postgres
+ development:
+-  <<: *base
++  <<: *defaults
+   database: dev
+-version: 1
++version: 2
+";
+        let changes = YamlParser.parse(diff, "config/database.yml");
+        assert_eq!(find(&changes, "defaults").change_type, ChangeType::Added);
+        assert_eq!(find(&changes, "version").change_type, ChangeType::Modified);
+        let development = find(&changes, "development");
+        assert_eq!((development.additions, development.deletions), (1, 1));
+
+        let anchor = find(&changes, "&defaults");
+        assert_eq!(anchor.kind, NodeKind::Other);
+        assert_eq!(anchor.change_type, ChangeType::Added);
+    }
+
+    #[test]
+    fn test_hunk_context_names_the_top_level_key() {
+        assert_eq!(YamlParser.extract_function_from_context("services:"), Some("services".into()));
+        assert_eq!(YamlParser.extract_function_from_context("- name: x"), None);
+        assert_eq!(YamlParser.extract_function_from_context("url: http://x"), Some("url".into()));
+        assert_eq!(YamlParser.extract_function_from_context("http://x"), None);
+    }
+}
@@ -1,12 +1,19 @@
 //! Platform abstraction layer
 //!
 //! Defines a common interface for different AI assistant platforms.
-//! Currently supports Claude Code and Codex CLI, with extensibility for future platforms.
+//! Currently supports Claude Code, Codex CLI and Cline, with extensibility for future platforms.
 
 pub mod claude_code;
+pub mod cline;
 pub mod codex_cli;
 
 use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -17,13 +24,18 @@ pub enum PlatformKind {
     #[default]
     Claude,
     Codex,
+    Cline,
 }
 
 impl PlatformKind {
+    /// Every supported platform, in display order
+    pub const ALL: [Self; 3] = [Self::Claude, Self::Codex, Self::Cline];
+
     pub fn parse(value: &str) -> Option<Self> {
         match value.to_ascii_lowercase().as_str() {
             "claude" | "claude-code" | "claude_code" => Some(Self::Claude),
             "codex" | "codecs" | "openai" => Some(Self::Codex),
+            "cline" | "claude-dev" => Some(Self::Cline),
             _ => None,
         }
     }
@@ -32,6 +44,7 @@ impl PlatformKind {
         match self {
             Self::Claude => "claude",
             Self::Codex => "codex",
+            Self::Cline => "cline",
         }
     }
 
@@ -43,10 +56,61 @@ impl PlatformKind {
         match self {
             Self::Claude => "Claude",
             Self::Codex => "Codex",
+            Self::Cline => "Cline",
+        }
+    }
+
+    /// 16×16 PNG logo, drawn in terminals that support inline images
+    pub fn logo_png(self) -> &'static [u8] {
+        match self {
+            Self::Claude => include_bytes!("platforms/claude_code/logo.png"),
+            Self::Codex => include_bytes!("platforms/codex_cli/logo.png"),
+            Self::Cline => include_bytes!("platforms/cline/logo.png"),
         }
     }
 }
 
+/// Where a platform's CLI binary was found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinarySource {
+    /// `[platform.binaries]` in config.toml
+    Config,
+    /// A directory on PATH
+    SearchPath,
+}
+
+/// Find the CLI binary: a path configured in `[platform.binaries]` wins over
+/// looking `command` up in `search_path` (the value of PATH)
+pub fn resolve_binary(
+    configured: Option<&Path>,
+    command: &str,
+    search_path: Option<&OsStr>,
+) -> Option<(PathBuf, BinarySource)> {
+    if let Some(path) = configured {
+        return Some((path.to_path_buf(), BinarySource::Config));
+    }
+    std::env::split_paths(search_path?)
+        .map(|dir| dir.join(command))
+        .find(|path| is_executable(path))
+        .map(|path| (path, BinarySource::SearchPath))
+}
+
+/// A regular file with an execute bit set
+pub fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
 /// Session state - common states across supported assistants
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -64,6 +128,8 @@ pub enum SessionState {
     Complete,
     /// The user interrupted the assistant (ESC/Ctrl+C during thinking)
     Interrupted,
+    /// Crabigator stopped input because the token budget ran out
+    Paused,
 }
 
 /// Claude Code operating mode (cycles via Shift+Tab)
@@ -89,6 +155,15 @@ impl ClaudeMode {
     }
 }
 
+/// A single tool call on the session timeline
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Tool name (e.g., "Read", "Bash")
+    pub tool: String,
+    /// Unix timestamp when the call finished
+    pub ts: f64,
+}
+
 /// A single hook event entry for debugging
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct HookEvent {
@@ -99,6 +174,9 @@ pub struct HookEvent {
     /// State before the event was processed
     #[serde(default)]
     pub state_before: String,
+    /// State the event left the session in (empty from hooks older than this field)
+    #[serde(default)]
+    pub state_after: String,
     /// Additional event-specific details
     #[serde(default)]
     pub details: Option<HashMap<String, serde_json::Value>>,
@@ -142,18 +220,25 @@ pub struct PlatformStats {
     pub subagent_messages: u32,
     /// Number of context compressions
     pub compressions: u32,
+    /// Unix timestamp of the most recent compression
+    #[serde(default)]
+    pub last_compressed_at: Option<f64>,
     /// Tool usage counts by tool name
     #[serde(default)]
     pub tools: HashMap<String, u32>,
-    /// Unix timestamps of tool calls (for sparkline visualization)
+    /// Tool calls by name and time (for the sparkline and per-tool heatmap);
+    /// the hooks keep the most recent 1000
     #[serde(default)]
-    pub tool_timestamps: Vec<f64>,
+    pub tool_calls: Vec<ToolCall>,
     /// Current session state
     #[serde(default)]
     pub state: SessionState,
     /// Unix timestamp when idle state began (complete/question)
     #[serde(default)]
     pub idle_since: Option<f64>,
+    /// Unix timestamp of the last finished tool call (or prompt submit)
+    #[serde(default)]
+    pub last_tool_timestamp: Option<f64>,
     /// Unix timestamp of last update
     pub last_updated: Option<f64>,
     /// Rolling log of hook events for debugging
@@ -168,6 +253,22 @@ pub struct PlatformStats {
     /// Model name (e.g., "claude-opus-4-5-20251101")
     #[serde(default)]
     pub model: Option<String>,
+    /// Total time spent inside hook invocations (ms)
+    #[serde(default)]
+    pub hook_ms_total: f64,
+    /// Number of hook invocations timed in `hook_ms_total`
+    #[serde(default)]
+    pub hook_runs: u32,
+    /// Seconds from each prompt to its completion, oldest first
+    #[serde(default)]
+    pub thinking_samples: Vec<u64>,
+    /// Input tokens across the session's responses, prompt cache reads and
+    /// writes included (Claude, from the transcript)
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// Output tokens across the session's responses
+    #[serde(default)]
+    pub output_tokens: u64,
 }
 
 impl PlatformStats {
@@ -176,16 +277,60 @@ impl PlatformStats {
         self.tools.values().sum()
     }
 
-    /// Get top N tools by usage count
-    #[allow(dead_code)]
-    pub fn top_tools(&self, n: usize) -> Vec<(&str, u32)> {
-        let mut sorted: Vec<_> = self.tools.iter().map(|(k, v)| (k.as_str(), *v)).collect();
-        sorted.sort_by(|a, b| b.1.cmp(&a.1));
+    /// Average hook run time (ms), None if the platform doesn't time its hooks
+    pub fn hook_avg_ms(&self) -> Option<f64> {
+        (self.hook_runs > 0).then(|| self.hook_ms_total / self.hook_runs as f64)
+    }
+
+    /// Get top N tools by usage count (ties broken by name)
+    pub fn top_tools(&self, n: usize) -> Vec<(String, u32)> {
+        let mut sorted: Vec<_> = self.tools.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
         sorted.truncate(n);
         sorted
     }
 }
 
+/// Why installing a platform's hooks failed
+///
+/// Converts into `anyhow::Error` (via `std::error::Error`) for callers that
+/// don't care which step broke.
+#[derive(Debug)]
+pub enum PlatformError {
+    /// A config file we'd have to rewrite can't be parsed; we refuse to overwrite it
+    InvalidConfig { path: PathBuf, reason: String },
+    /// The hook script (or its directory or metadata) couldn't be written
+    ScriptWriteFailed { path: PathBuf, source: io::Error },
+    /// The hook couldn't be registered for an event
+    HookRegistrationFailed { event: String, reason: String },
+    /// The installed hooks still report another version after installing
+    VersionMismatch { found: String, expected: String },
+}
+
+impl fmt::Display for PlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConfig { path, reason } => write!(f, "{} is invalid: {}", path.display(), reason),
+            Self::ScriptWriteFailed { path, source } => write!(f, "failed to write {}: {}", path.display(), source),
+            Self::HookRegistrationFailed { event, reason } => {
+                write!(f, "failed to register {} hook: {}", event, reason)
+            }
+            Self::VersionMismatch { found, expected } => {
+                write!(f, "installed hooks are version {}, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlatformError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ScriptWriteFailed { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
 /// Trait for platform-specific implementations
 pub trait Platform {
     /// Platform identifier
@@ -196,18 +341,117 @@ pub trait Platform {
     fn command(&self) -> &'static str;
 
     /// Ensure hooks are installed and up-to-date
-    fn ensure_hooks_installed(&self) -> Result<()>;
+    fn ensure_hooks_installed(&self) -> Result<(), PlatformError>;
+
+    /// Remove every trace of our hooks, then install them fresh
+    /// (default: the platform has no hooks to reinstall)
+    fn force_reinstall(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Describe what `force_reinstall` would change, without touching anything
+    fn reinstall_plan(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
 
     /// Load current stats from the platform's data source
     fn load_stats(&self, cwd: &str) -> Result<PlatformStats>;
 
+    /// File `load_stats` reads, if the stats come from one file that can be
+    /// watched for changes (default: none)
+    fn stats_path(&self, _cwd: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Stats file the hook wrote for a given session, so a resumed session can
+    /// start from it (default: none, stats aren't kept per session)
+    fn session_stats_path(&self, _session_id: &str) -> Option<PathBuf> {
+        None
+    }
+
     /// Clean up stats file on exit (default: no-op)
     fn cleanup_stats(&self, _cwd: &str) {}
+
+    /// Watch the installed hook script and keep hand edits from being
+    /// reverted by the next install (config `watch_hook_script`). Blocks for
+    /// as long as the watch runs (default: returns at once)
+    fn watch_hook_script(&self) {}
 }
 
 pub fn platform_for(kind: PlatformKind) -> Box<dyn Platform> {
     match kind {
         PlatformKind::Claude => Box::new(claude_code::ClaudeCodePlatform::new()),
         PlatformKind::Codex => Box::new(codex_cli::CodexPlatform::new()),
+        PlatformKind::Cline => Box::new(cline::ClinePlatform::new()),
+    }
+}
+
+/// Serializes `atomic_write` within this process; the pid in the temp name
+/// keeps separate crabigator processes apart
+static ATOMIC_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Temp file `atomic_write` writes before renaming over `path`
+fn atomic_write_temp_path(path: &Path) -> PathBuf {
+    path.with_extension(format!("{}.tmp", std::process::id()))
+}
+
+/// Replace `path` via a temp file + rename, so the assistant CLI never reads
+/// a half-written config. On failure the original is untouched and the temp
+/// file is removed.
+pub fn atomic_write(path: &Path, contents: &str) -> io::Result<()> {
+    let _guard = ATOMIC_WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let tmp_path = atomic_write_temp_path(path);
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn executable(dir: &Path, name: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(name);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_resolve_binary_prefers_config() {
+        let bin = tempfile::tempdir().unwrap();
+        let on_path = executable(bin.path(), "claude");
+        let search_path = bin.path().as_os_str();
+
+        let configured = Path::new("/opt/homebrew/bin/claude");
+        assert_eq!(
+            resolve_binary(Some(configured), "claude", Some(search_path)),
+            Some((configured.to_path_buf(), BinarySource::Config))
+        );
+        assert_eq!(
+            resolve_binary(None, "claude", Some(search_path)),
+            Some((on_path, BinarySource::SearchPath))
+        );
+        assert_eq!(resolve_binary(None, "codex", Some(search_path)), None);
+        assert_eq!(resolve_binary(None, "claude", None), None);
+    }
+
+    #[test]
+    fn test_resolve_binary_skips_non_executables() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        fs::write(first.path().join("codex"), "").unwrap();
+        let codex = executable(second.path(), "codex");
+        let search_path = std::env::join_paths([first.path(), second.path()]).unwrap();
+        assert_eq!(resolve_binary(None, "codex", Some(&search_path)), Some((codex, BinarySource::SearchPath)));
+        assert!(!is_executable(first.path()));
     }
 }
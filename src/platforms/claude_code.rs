@@ -6,8 +6,8 @@
 mod hook_script;
 
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use chrono::Utc;
@@ -15,9 +15,19 @@ use md5::{Digest, Md5};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use super::{Platform, PlatformKind, PlatformStats};
+use crate::coordinator::SessionCoordinator;
+use super::{Platform, PlatformError, PlatformKind, PlatformStats};
 use hook_script::{script_with_version, HOOK_VERSION};
 
+/// Hook events we register our script for
+const HOOK_EVENTS: [&str; 6] = ["PermissionRequest", "PostToolUse", "Stop", "SubagentStop", "PreCompact", "UserPromptSubmit"];
+
+/// Extra reads of a stats file that doesn't parse, in case the hook is mid-write
+const STATS_READ_RETRIES: usize = 3;
+
+/// Pause before each of those reads
+const STATS_RETRY_DELAY: Duration = Duration::from_millis(10);
+
 /// Metadata about installed hooks
 #[derive(Debug, Serialize, Deserialize)]
 struct HooksMeta {
@@ -27,6 +37,19 @@ struct HooksMeta {
     script_hash: String,
     installed_at: String,
     script_path: String,
+    /// The installed script was edited by hand and `script_hash` follows the
+    /// edit; only a `HOOK_VERSION` change reinstalls it
+    #[serde(default)]
+    user_modified: bool,
+    /// Hash of the script we shipped at install, kept while `script_hash`
+    /// follows a hand edit. `HOOK_VERSION` only moves with the crate version,
+    /// so this is how an edited install notices a newer shipped script.
+    #[serde(default)]
+    shipped_hash: String,
+    /// Lock file the hook holds around each stats update (`flock`), with
+    /// `{key}` for the session ID or cwd hash of the stats file
+    #[serde(default)]
+    lock_path: String,
 }
 
 /// Claude Code platform implementation
@@ -40,7 +63,11 @@ pub struct ClaudeCodePlatform {
 impl ClaudeCodePlatform {
     pub fn new() -> Self {
         let home = dirs::home_dir().expect("Could not find home directory");
-        let claude_dir = home.join(".claude");
+        Self::with_claude_dir(home.join(".claude"))
+    }
+
+    /// Use a Claude config directory other than ~/.claude
+    fn with_claude_dir(claude_dir: PathBuf) -> Self {
         let crabigator_dir = claude_dir.join("crabigator");
 
         Self {
@@ -64,17 +91,8 @@ impl ClaudeCodePlatform {
         self.claude_dir.join("settings.json")
     }
 
-    fn atomic_write(&self, path: &PathBuf, contents: &str) -> Result<()> {
-        let tmp_path = path.with_extension("tmp");
-        let mut file = fs::File::create(&tmp_path)
-            .with_context(|| format!("Failed to create temp file {}", tmp_path.display()))?;
-        file.write_all(contents.as_bytes())
-            .with_context(|| format!("Failed to write temp file {}", tmp_path.display()))?;
-        file.sync_all()
-            .with_context(|| format!("Failed to flush temp file {}", tmp_path.display()))?;
-        fs::rename(&tmp_path, path)
-            .with_context(|| format!("Failed to rename {} to {}", tmp_path.display(), path.display()))?;
-        Ok(())
+    fn atomic_write(&self, path: &Path, contents: &str) -> std::io::Result<()> {
+        super::atomic_write(path, contents)
     }
 
     /// Compute hash of the hook script content for change detection
@@ -97,9 +115,16 @@ impl ClaudeCodePlatform {
             Ok(content) => {
                 match serde_json::from_str::<HooksMeta>(&content) {
                     Ok(meta) => {
-                        // Check both version and script hash
+                        if Self::shipped_script_changed(&meta) {
+                            tracing::warn!(
+                                script = %script_path.display(),
+                                "hook script was edited by hand and crabigator now ships a different one; \
+                                 run `crabigator reinstall` to take it (and drop the edit)"
+                            );
+                        }
+                        // Check both version and script hash (unless the user owns the script)
                         meta.installed_version == HOOK_VERSION
-                            && meta.script_hash == Self::script_content_hash()
+                            && (meta.user_modified || meta.script_hash == Self::script_content_hash())
                     }
                     Err(_) => false,
                 }
@@ -140,47 +165,46 @@ impl ClaudeCodePlatform {
         }
     }
 
-    fn hooks_registered(&self) -> Result<bool> {
-        let settings_path = self.settings_path();
-        if !settings_path.exists() {
-            return Ok(false);
-        }
-
-        let content = fs::read_to_string(&settings_path)
-            .with_context(|| format!("Failed to read {}", settings_path.display()))?;
-        let settings: Value = serde_json::from_str(&content).with_context(|| {
-            format!(
-                "{} contains invalid JSON; refusing to overwrite",
-                settings_path.display()
-            )
-        })?;
+    fn hooks_registered(&self) -> Result<bool, PlatformError> {
+        Ok(self.missing_hook_event()?.is_none())
+    }
 
+    /// First event our hook isn't registered for (None when all are)
+    fn missing_hook_event(&self) -> Result<Option<&'static str>, PlatformError> {
+        let Some(settings) = self.load_settings()? else {
+            return Ok(Some(HOOK_EVENTS[0]));
+        };
         let script_path_str = self.script_path().to_string_lossy().to_string();
-        let hook_events = ["PermissionRequest", "PostToolUse", "Stop", "SubagentStop", "PreCompact", "UserPromptSubmit"];
 
-        Ok(hook_events
+        Ok(HOOK_EVENTS
             .iter()
-            .all(|event| Self::settings_has_our_hook(&settings, event, &script_path_str)))
+            .copied()
+            .find(|event| !Self::settings_has_our_hook(&settings, event, &script_path_str)))
     }
 
     /// Install or update hooks
-    fn install_hooks(&self) -> Result<()> {
+    fn install_hooks(&self) -> Result<(), PlatformError> {
         // Create crabigator directory
-        fs::create_dir_all(&self.crabigator_dir)
-            .context("Failed to create crabigator directory")?;
+        fs::create_dir_all(&self.crabigator_dir).map_err(|source| PlatformError::ScriptWriteFailed {
+            path: self.crabigator_dir.clone(),
+            source,
+        })?;
 
         // Write hook script with version embedded
         let script_path = self.script_path();
-        fs::write(&script_path, script_with_version())
-            .context("Failed to write hook script")?;
+        let script_write_failed = |source| PlatformError::ScriptWriteFailed {
+            path: script_path.clone(),
+            source,
+        };
+        fs::write(&script_path, script_with_version()).map_err(script_write_failed)?;
 
         // Make script executable on Unix
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&script_path)?.permissions();
+            let mut perms = fs::metadata(&script_path).map_err(script_write_failed)?.permissions();
             perms.set_mode(0o755);
-            fs::set_permissions(&script_path, perms)?;
+            fs::set_permissions(&script_path, perms).map_err(script_write_failed)?;
         }
 
         // Update settings.json
@@ -192,16 +216,155 @@ impl ClaudeCodePlatform {
             script_hash: Self::script_content_hash(),
             installed_at: Utc::now().to_rfc3339(),
             script_path: script_path.to_string_lossy().to_string(),
+            user_modified: false,
+            shipped_hash: Self::script_content_hash(),
+            lock_path: Self::stats_lock_path(&Self::stats_file_for("{key}")).to_string_lossy().to_string(),
         };
-        let meta_content = serde_json::to_string_pretty(&meta)?;
-        fs::write(self.meta_path(), meta_content)
-            .context("Failed to write hooks metadata")?;
+        let meta_content = serde_json::to_string_pretty(&meta).expect("hooks metadata serializes");
+        fs::write(self.meta_path(), meta_content).map_err(|source| PlatformError::ScriptWriteFailed {
+            path: self.meta_path(),
+            source,
+        })?;
+
+        Ok(())
+    }
 
+    /// Point `meta` at a changed copy of the script
+    ///
+    /// Returns whether anything changed. A script edited back to exactly what
+    /// we ship is ours again.
+    #[cfg_attr(not(feature = "fs-watch"), allow(dead_code))]
+    fn adopt_script_edit(meta: &mut HooksMeta, script: &str) -> bool {
+        let hash = Self::md5_hash_prefix(script, 32);
+        if hash == meta.script_hash {
+            return false;
+        }
+        meta.user_modified = hash != Self::script_content_hash();
+        meta.script_hash = hash;
+        true
+    }
+
+    /// The user's edit is kept, but the script we ship has changed since it
+    /// was installed
+    fn shipped_script_changed(meta: &HooksMeta) -> bool {
+        meta.user_modified && meta.shipped_hash != Self::script_content_hash()
+    }
+
+    /// Record the installed script's current hash in hooks-meta.json, leaving
+    /// the script itself alone
+    #[cfg_attr(not(feature = "fs-watch"), allow(dead_code))]
+    fn sync_script_hash(&self) -> Result<bool> {
+        let script = fs::read_to_string(self.script_path())?;
+        let mut meta: HooksMeta = serde_json::from_str(&fs::read_to_string(self.meta_path())?)?;
+        if !Self::adopt_script_edit(&mut meta, &script) {
+            return Ok(false);
+        }
+        let meta_content = serde_json::to_string_pretty(&meta).expect("hooks metadata serializes");
+        self.atomic_write(&self.meta_path(), &meta_content)?;
+        Ok(true)
+    }
+
+    /// Check that an install actually took effect
+    fn verify_install(&self) -> Result<(), PlatformError> {
+        if !self.is_current_version() {
+            let found = fs::read_to_string(self.meta_path())
+                .ok()
+                .and_then(|content| serde_json::from_str::<HooksMeta>(&content).ok())
+                .map(|meta| meta.installed_version)
+                .unwrap_or_else(|| "unknown".to_string());
+            return Err(PlatformError::VersionMismatch {
+                found,
+                expected: HOOK_VERSION.to_string(),
+            });
+        }
+        if let Some(event) = self.missing_hook_event()? {
+            return Err(PlatformError::HookRegistrationFailed {
+                event: event.to_string(),
+                reason: format!("not present in {} after install", self.settings_path().display()),
+            });
+        }
         Ok(())
     }
 
+    /// Whether a settings.json hook entry runs our script (at any install path)
+    fn is_crabigator_hook(hook: &Value, script_path_str: &str) -> bool {
+        hook.get("command")
+            .and_then(|c| c.as_str())
+            .is_some_and(|cmd| cmd == script_path_str || cmd.ends_with("crabigator/stats-hook.py"))
+    }
+
+    /// settings.json can't be used as-is; we refuse to overwrite it
+    fn invalid_settings(&self, reason: impl Into<String>) -> PlatformError {
+        PlatformError::InvalidConfig {
+            path: self.settings_path(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Load settings.json, or None if it doesn't exist
+    fn load_settings(&self) -> Result<Option<Value>, PlatformError> {
+        let settings_path = self.settings_path();
+        if !settings_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&settings_path)
+            .map_err(|e| self.invalid_settings(format!("unreadable ({})", e)))?;
+        let settings = serde_json::from_str(&content)
+            .map_err(|e| self.invalid_settings(format!("not valid JSON ({})", e)))?;
+        Ok(Some(settings))
+    }
+
+    /// Strip our hooks from every event in settings, returning how many were removed
+    ///
+    /// Entries left with no hooks are dropped; other tools' hooks are untouched.
+    fn strip_our_hooks(settings: &mut Value, script_path_str: &str) -> usize {
+        let Some(hooks) = settings.get_mut("hooks").and_then(|h| h.as_object_mut()) else {
+            return 0;
+        };
+
+        let mut removed = 0;
+        for event_arr in hooks.values_mut() {
+            let Some(arr) = event_arr.as_array_mut() else { continue };
+            let mut emptied = false;
+            for entry in arr.iter_mut() {
+                let Some(hooks_arr) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) else { continue };
+                let before = hooks_arr.len();
+                hooks_arr.retain(|hook| !Self::is_crabigator_hook(hook, script_path_str));
+                removed += before - hooks_arr.len();
+                emptied |= before > 0 && hooks_arr.is_empty();
+            }
+            if emptied {
+                arr.retain(|entry| {
+                    entry.get("hooks")
+                        .and_then(|h| h.as_array())
+                        .map(|hooks_arr| !hooks_arr.is_empty())
+                        .unwrap_or(true)
+                });
+            }
+        }
+        removed
+    }
+
+    /// Remove our hook entries from settings.json
+    ///
+    /// Idempotent: leaves the file untouched when there is nothing to remove.
+    fn remove_hooks_from_settings(&self) -> Result<usize> {
+        let Some(mut settings) = self.load_settings()? else {
+            return Ok(0);
+        };
+        let script_path_str = self.script_path().to_string_lossy().to_string();
+        let removed = Self::strip_our_hooks(&mut settings, &script_path_str);
+        if removed > 0 {
+            let settings_path = self.settings_path();
+            let settings_content = serde_json::to_string_pretty(&settings)?;
+            self.atomic_write(&settings_path, &settings_content)
+                .with_context(|| format!("Failed to write {}", settings_path.display()))?;
+        }
+        Ok(removed)
+    }
+
     /// Merge our hook configuration into settings.json
-    fn merge_settings(&self) -> Result<()> {
+    fn merge_settings(&self) -> Result<(), PlatformError> {
         let settings_path = self.settings_path();
         let script_path = self.script_path();
         let script_path_str = script_path.to_string_lossy().to_string();
@@ -209,23 +372,17 @@ impl ClaudeCodePlatform {
         let mut changed = false;
 
         // Load existing settings or create new. If settings.json is invalid, refuse to overwrite.
-        let mut settings: Value = if settings_path.exists() {
-            let content = fs::read_to_string(&settings_path)
-                .with_context(|| format!("Failed to read {}", settings_path.display()))?;
-            serde_json::from_str(&content).with_context(|| {
-                format!(
-                    "{} contains invalid JSON; refusing to overwrite",
-                    settings_path.display()
-                )
-            })?
-        } else {
-            changed = true;
-            json!({})
+        let mut settings: Value = match self.load_settings()? {
+            Some(settings) => settings,
+            None => {
+                changed = true;
+                json!({})
+            }
         };
 
-        settings
-            .as_object_mut()
-            .context("settings.json root must be a JSON object")?;
+        if !settings.is_object() {
+            return Err(self.invalid_settings("root must be a JSON object"));
+        }
 
         // Ensure hooks object exists
         if settings.get("hooks").is_none() {
@@ -233,11 +390,9 @@ impl ClaudeCodePlatform {
             changed = true;
         }
         if !settings["hooks"].is_object() {
-            anyhow::bail!("settings.json hooks field must be a JSON object; refusing to overwrite");
+            return Err(self.invalid_settings("hooks field must be a JSON object"));
         }
 
-        // Hook events we need to register
-        let hook_events = ["PermissionRequest", "PostToolUse", "Stop", "SubagentStop", "PreCompact", "UserPromptSubmit"];
         // Events that require matcher="*" to catch all tool types
         let events_with_matcher = ["PermissionRequest", "PostToolUse"];
 
@@ -249,15 +404,15 @@ impl ClaudeCodePlatform {
 
         // For each event type, ensure our hook is registered.
         // We identify our hook by its `command` path and never remove other hooks.
-        for event in hook_events {
+        for event in HOOK_EVENTS {
             if !settings["hooks"].as_object().unwrap().contains_key(event) {
                 settings["hooks"][event] = json!([]);
                 changed = true;
             }
 
-            let arr = settings["hooks"][event]
-                .as_array_mut()
-                .with_context(|| format!("settings.json hooks.{} must be a JSON array", event))?;
+            let Some(arr) = settings["hooks"][event].as_array_mut() else {
+                return Err(self.invalid_settings(format!("hooks.{} must be a JSON array", event)));
+            };
 
             let is_our_hook = |hook: &Value| {
                 hook.get("command")
@@ -386,9 +541,12 @@ impl ClaudeCodePlatform {
         }
 
         // Write back settings
-        let settings_content = serde_json::to_string_pretty(&settings)?;
+        let settings_content = serde_json::to_string_pretty(&settings).expect("settings serialize");
         self.atomic_write(&settings_path, &settings_content)
-            .with_context(|| format!("Failed to write {}", settings_path.display()))?;
+            .map_err(|e| PlatformError::HookRegistrationFailed {
+                event: HOOK_EVENTS.join(", "),
+                reason: format!("failed to write {} ({})", settings_path.display(), e),
+            })?;
 
         Ok(())
     }
@@ -405,12 +563,38 @@ impl ClaudeCodePlatform {
     /// Get stats file path - uses session ID from env var if available, otherwise cwd hash
     fn stats_file_path(cwd: &str) -> PathBuf {
         if let Ok(session_id) = std::env::var("CRABIGATOR_SESSION_ID") {
-            PathBuf::from(format!("/tmp/crabigator-stats-{}.json", session_id))
+            Self::stats_file_for(&session_id)
         } else {
             // Fallback to cwd hash if no session ID
             let hash = Self::md5_hash_prefix(cwd, 12);
-            PathBuf::from(format!("/tmp/crabigator-stats-{}.json", hash))
+            Self::stats_file_for(&hash)
+        }
+    }
+
+    fn stats_file_for(key: &str) -> PathBuf {
+        PathBuf::from(format!("/tmp/crabigator-stats-{}.json", key))
+    }
+
+    /// Lock file the hook takes before reading and saving `stats_path`
+    fn stats_lock_path(stats_path: &Path) -> PathBuf {
+        stats_path.with_extension("lock")
+    }
+
+    /// Parse the stats `read` returns, reading again up to
+    /// `STATS_READ_RETRIES` times, `STATS_RETRY_DELAY` apart, while the
+    /// content doesn't parse (the hook may be mid-write). Stats that never
+    /// parse read as the defaults.
+    fn load_stats_with_retry(mut read: impl FnMut() -> std::io::Result<String>) -> Result<PlatformStats> {
+        for attempt in 0..=STATS_READ_RETRIES {
+            if attempt > 0 {
+                std::thread::sleep(STATS_RETRY_DELAY);
+            }
+            let content = read().context("Failed to read stats file")?;
+            if let Ok(stats) = serde_json::from_str(&content) {
+                return Ok(stats);
+            }
         }
+        Ok(PlatformStats::default())
     }
 }
 
@@ -429,16 +613,35 @@ impl Platform for ClaudeCodePlatform {
         PlatformKind::Claude.command()
     }
 
-    fn ensure_hooks_installed(&self) -> Result<()> {
-        if self.is_current_version() {
-            match self.hooks_registered() {
-                Ok(true) => return Ok(()),
-                Ok(false) => {}
-                Err(e) => return Err(e),
-            }
+    fn ensure_hooks_installed(&self) -> Result<(), PlatformError> {
+        if self.is_current_version() && self.hooks_registered()? {
+            return Ok(());
         }
+
+        // Another instance starting alongside this one may be installing too
+        let _guard = match SessionCoordinator::new(&self.crabigator_dir).try_acquire_install_lock() {
+            Ok(Some(guard)) => {
+                // It may have just finished
+                if self.is_current_version() && self.hooks_registered()? {
+                    return Ok(());
+                }
+                Some(guard)
+            }
+            Ok(None) => {
+                tracing::info!("another crabigator instance is installing hooks, skipping");
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "install lock unavailable, installing without it");
+                None
+            }
+        };
         self.install_hooks()?;
-        Ok(())
+        self.verify_install()
+    }
+
+    fn stats_path(&self, cwd: &str) -> Option<PathBuf> {
+        Some(Self::stats_file_path(cwd))
     }
 
     fn load_stats(&self, cwd: &str) -> Result<PlatformStats> {
@@ -448,25 +651,128 @@ impl Platform for ClaudeCodePlatform {
             return Ok(PlatformStats::default());
         }
 
-        let content = fs::read_to_string(&stats_path)
-            .context("Failed to read stats file")?;
-
-        let stats: PlatformStats = serde_json::from_str(&content)
-            .unwrap_or_default();
+        Self::load_stats_with_retry(|| fs::read_to_string(&stats_path))
+    }
 
-        Ok(stats)
+    fn session_stats_path(&self, session_id: &str) -> Option<PathBuf> {
+        Some(Self::stats_file_for(session_id))
     }
 
     fn cleanup_stats(&self, cwd: &str) {
         let stats_path = Self::stats_file_path(cwd);
+        let _ = fs::remove_file(Self::stats_lock_path(&stats_path));
         let _ = fs::remove_file(stats_path);
     }
+
+    fn force_reinstall(&self) -> Result<()> {
+        self.remove_hooks_from_settings()?;
+        for path in [self.script_path(), self.meta_path()] {
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+        }
+        self.install_hooks()?;
+        self.verify_install()?;
+        Ok(())
+    }
+
+    fn reinstall_plan(&self) -> Result<Vec<String>> {
+        let mut plan = Vec::new();
+        if let Some(mut settings) = self.load_settings()? {
+            let script_path_str = self.script_path().to_string_lossy().to_string();
+            let removed = Self::strip_our_hooks(&mut settings, &script_path_str);
+            if removed > 0 {
+                plan.push(format!("remove {} hook entries from {}", removed, self.settings_path().display()));
+            }
+        }
+        for path in [self.script_path(), self.meta_path()] {
+            if path.exists() {
+                plan.push(format!("remove {}", path.display()));
+            }
+        }
+        plan.push(format!("install {}", self.script_path().display()));
+        plan.push(format!("register hooks in {}", self.settings_path().display()));
+        plan.push(format!("write {}", self.meta_path().display()));
+        Ok(plan)
+    }
+
+    #[cfg(feature = "fs-watch")]
+    fn watch_hook_script(&self) {
+        use notify::{Event, EventKind, RecursiveMode, Watcher};
+        use std::sync::mpsc;
+
+        // Editors often save by renaming a new file over the old one, so
+        // watch the directory rather than the file
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let Ok(mut watcher) = notify::recommended_watcher(tx) else {
+            return;
+        };
+        if watcher.watch(&self.crabigator_dir, RecursiveMode::NonRecursive).is_err() {
+            return;
+        }
+        let script_path = self.script_path();
+        for event in rx.into_iter().flatten() {
+            if !matches!(event.kind, EventKind::Access(_)) && event.paths.contains(&script_path) {
+                let _ = self.sync_script_hash();
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_hook_rotates_event_history() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        if Command::new("python3").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("stats_hook.py");
+        fs::write(&script, script_with_version()).unwrap();
+        let session_id = format!("test-history-{}", std::process::id());
+        let stats_path = PathBuf::from(format!("/tmp/crabigator-stats-{}.json", session_id));
+
+        // A full history: the next event pushes out the oldest
+        let history: Vec<Value> = (0..1000)
+            .map(|i| {
+                json!({"ts": i as f64, "event": "PostToolUse", "state_before": "thinking", "state_after": "thinking"})
+            })
+            .collect();
+        let stats = json!({"completions": 1, "subagent_messages": 0, "compressions": 0, "state": "complete",
+            "event_history": history});
+        fs::write(&stats_path, stats.to_string()).unwrap();
+
+        let mut hook = Command::new("python3")
+            .arg(&script)
+            .env("CRABIGATOR_SESSION_ID", &session_id)
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        hook.stdin
+            .take()
+            .unwrap()
+            .write_all(br#"{"hook_event_name": "UserPromptSubmit", "cwd": "/tmp"}"#)
+            .unwrap();
+        hook.wait().unwrap();
+
+        let content = fs::read_to_string(&stats_path).unwrap();
+        let _ = fs::remove_file(&stats_path);
+        let _ = fs::remove_file(stats_path.with_extension("lock"));
+        let _ = fs::remove_dir_all(format!("/tmp/crabigator-{}", session_id));
+
+        let stats: PlatformStats = serde_json::from_str(&content).unwrap();
+        assert_eq!(stats.event_history.len(), 1000);
+        assert_eq!(stats.event_history[0].ts, 1.0);
+        let latest = stats.event_history.last().unwrap();
+        assert_eq!(latest.event, "UserPromptSubmit");
+        assert_eq!((latest.state_before.as_str(), latest.state_after.as_str()), ("complete", "thinking"));
+    }
+
     #[test]
     fn test_md5_hash_prefix() {
         // Test that our hash matches Python's hashlib.md5
@@ -475,10 +781,304 @@ mod tests {
         // The actual hash value would need to be verified against Python
     }
 
+    fn temp_platform() -> (tempfile::TempDir, ClaudeCodePlatform) {
+        let dir = tempfile::tempdir().unwrap();
+        let platform = ClaudeCodePlatform::with_claude_dir(dir.path().join(".claude"));
+        fs::create_dir_all(&platform.claude_dir).unwrap();
+        (dir, platform)
+    }
+
+    fn read_settings(platform: &ClaudeCodePlatform) -> Value {
+        serde_json::from_str(&fs::read_to_string(platform.settings_path()).unwrap()).unwrap()
+    }
+
+    fn count_our_hooks(settings: &Value, event: &str) -> usize {
+        settings["hooks"][event]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry["hooks"].as_array())
+            .flatten()
+            .filter(|hook| hook["command"].as_str().is_some_and(|c| c.ends_with("crabigator/stats-hook.py")))
+            .count()
+    }
+
+    fn read_meta(platform: &ClaudeCodePlatform) -> HooksMeta {
+        serde_json::from_str(&fs::read_to_string(platform.meta_path()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_adopt_script_edit() {
+        let mut meta = HooksMeta {
+            installed_version: HOOK_VERSION.to_string(),
+            script_hash: ClaudeCodePlatform::script_content_hash(),
+            installed_at: String::new(),
+            script_path: String::new(),
+            user_modified: false,
+            shipped_hash: ClaudeCodePlatform::script_content_hash(),
+            lock_path: String::new(),
+        };
+        let shipped = script_with_version();
+        assert!(!ClaudeCodePlatform::adopt_script_edit(&mut meta, &shipped));
+
+        let edited = format!("{}\n# my tweak\n", shipped);
+        assert!(ClaudeCodePlatform::adopt_script_edit(&mut meta, &edited));
+        assert!(meta.user_modified);
+        assert_eq!(meta.script_hash, ClaudeCodePlatform::md5_hash_prefix(&edited, 32));
+        assert!(!ClaudeCodePlatform::adopt_script_edit(&mut meta, &edited));
+
+        // Reverting the edit hands the script back
+        assert!(ClaudeCodePlatform::adopt_script_edit(&mut meta, &shipped));
+        assert!(!meta.user_modified);
+    }
+
+    #[test]
+    fn test_edited_script_survives_reinstall_check() {
+        let (_dir, platform) = temp_platform();
+        platform.ensure_hooks_installed().unwrap();
+        assert!(!platform.sync_script_hash().unwrap());
+
+        let edited = format!("{}\n# my tweak\n", script_with_version());
+        fs::write(platform.script_path(), &edited).unwrap();
+        assert!(platform.sync_script_hash().unwrap());
+        assert!(read_meta(&platform).user_modified);
+
+        platform.ensure_hooks_installed().unwrap();
+        assert_eq!(fs::read_to_string(platform.script_path()).unwrap(), edited);
+        assert!(!ClaudeCodePlatform::shipped_script_changed(&read_meta(&platform)));
+
+        // Shipping a different script is noticed, but the edit is kept
+        let mut meta = read_meta(&platform);
+        meta.shipped_hash = "older".to_string();
+        assert!(ClaudeCodePlatform::shipped_script_changed(&meta));
+        fs::write(platform.meta_path(), serde_json::to_string(&meta).unwrap()).unwrap();
+        assert!(platform.is_current_version());
+
+        // A new hook version still overwrites it
+        let mut meta = read_meta(&platform);
+        meta.installed_version = "0".to_string();
+        fs::write(platform.meta_path(), serde_json::to_string(&meta).unwrap()).unwrap();
+        platform.ensure_hooks_installed().unwrap();
+        assert_eq!(fs::read_to_string(platform.script_path()).unwrap(), script_with_version());
+        assert!(!read_meta(&platform).user_modified);
+    }
+
+    #[test]
+    fn test_force_reinstall_cleans_up_duplicates() {
+        let (_dir, platform) = temp_platform();
+        let ours = platform.script_path().to_string_lossy().to_string();
+        let settings = json!({
+            "model": "opus",
+            "hooks": {
+                "Stop": [
+                    { "hooks": [{ "type": "command", "command": ours }] },
+                    { "hooks": [{ "type": "command", "command": ours }, { "type": "command", "command": "notify.sh" }] },
+                ],
+                "PostToolUse": [
+                    { "matcher": "Bash", "hooks": [{ "type": "command", "command": "/old/home/.claude/crabigator/stats-hook.py" }] },
+                ],
+            }
+        });
+        fs::write(platform.settings_path(), settings.to_string()).unwrap();
+        fs::create_dir_all(&platform.crabigator_dir).unwrap();
+        fs::write(platform.script_path(), "corrupted").unwrap();
+
+        platform.force_reinstall().unwrap();
+
+        let settings = read_settings(&platform);
+        assert_eq!(settings["model"], "opus");
+        for event in HOOK_EVENTS {
+            assert_eq!(count_our_hooks(&settings, event), 1, "{}", event);
+        }
+        // Other tools' hooks survive, and the stale matcher entry is gone
+        assert!(settings.to_string().contains("notify.sh"));
+        assert!(!settings.to_string().contains("/old/home"));
+        assert_eq!(fs::read_to_string(platform.script_path()).unwrap(), script_with_version());
+        assert!(platform.is_current_version());
+        assert!(platform.hooks_registered().unwrap());
+    }
+
+    #[test]
+    fn test_remove_hooks_is_idempotent() {
+        let (_dir, platform) = temp_platform();
+        // No settings file: nothing to do, and nothing gets created
+        assert_eq!(platform.remove_hooks_from_settings().unwrap(), 0);
+        assert!(!platform.settings_path().exists());
+
+        // No crabigator entries: the file is left byte-for-byte as it was
+        let foreign = "{\n  \"hooks\": {\"Stop\": [{\"hooks\": [{\"command\": \"notify.sh\"}]}]}\n}";
+        fs::write(platform.settings_path(), foreign).unwrap();
+        assert_eq!(platform.remove_hooks_from_settings().unwrap(), 0);
+        assert_eq!(fs::read_to_string(platform.settings_path()).unwrap(), foreign);
+
+        // Remove after install, then again
+        platform.install_hooks().unwrap();
+        assert_eq!(platform.remove_hooks_from_settings().unwrap(), HOOK_EVENTS.len());
+        assert_eq!(platform.remove_hooks_from_settings().unwrap(), 0);
+        let settings = read_settings(&platform);
+        assert!(HOOK_EVENTS.iter().all(|event| count_our_hooks(&settings, event) == 0));
+        assert_eq!(settings["hooks"]["Stop"][0]["hooks"][0]["command"], "notify.sh");
+    }
+
+    #[test]
+    fn test_remove_hooks_refuses_invalid_json() {
+        let (_dir, platform) = temp_platform();
+        fs::write(platform.settings_path(), "{ not json").unwrap();
+        assert!(platform.remove_hooks_from_settings().is_err());
+        assert!(platform.force_reinstall().is_err());
+        assert_eq!(fs::read_to_string(platform.settings_path()).unwrap(), "{ not json");
+    }
+
+    #[test]
+    fn test_ensure_hooks_installed_error_variants() {
+        let (_dir, platform) = temp_platform();
+        fs::write(platform.settings_path(), "[1, 2").unwrap();
+        match platform.ensure_hooks_installed() {
+            Err(PlatformError::InvalidConfig { path, .. }) => assert_eq!(path, platform.settings_path()),
+            other => panic!("expected InvalidConfig, got {:?}", other),
+        }
+
+        fs::write(platform.settings_path(), r#"{"hooks": {"Stop": {}}}"#).unwrap();
+        assert!(matches!(platform.ensure_hooks_installed(), Err(PlatformError::InvalidConfig { .. })));
+
+        // A file where the crabigator directory should be blocks the script write
+        fs::remove_file(platform.settings_path()).unwrap();
+        fs::remove_dir_all(&platform.crabigator_dir).unwrap();
+        fs::write(&platform.crabigator_dir, "").unwrap();
+        match platform.ensure_hooks_installed() {
+            Err(PlatformError::ScriptWriteFailed { path, .. }) => assert_eq!(path, platform.crabigator_dir),
+            other => panic!("expected ScriptWriteFailed, got {:?}", other),
+        }
+
+        fs::remove_file(&platform.crabigator_dir).unwrap();
+        platform.ensure_hooks_installed().unwrap();
+        assert!(platform.hooks_registered().unwrap());
+    }
+
+    #[test]
+    fn test_reinstall_plan_changes_nothing() {
+        let (_dir, platform) = temp_platform();
+        platform.install_hooks().unwrap();
+        let before = fs::read_to_string(platform.settings_path()).unwrap();
+
+        let plan = platform.reinstall_plan().unwrap();
+        assert!(plan[0].starts_with("remove 6 hook entries from "));
+        assert!(plan.iter().any(|step| step.ends_with("stats-hook.py") && step.starts_with("remove ")));
+        assert_eq!(fs::read_to_string(platform.settings_path()).unwrap(), before);
+        assert!(platform.script_path().exists());
+    }
+
+    #[test]
+    fn test_atomic_write_replaces_contents() {
+        let (_dir, platform) = temp_platform();
+        let path = platform.settings_path();
+        platform.atomic_write(&path, "first").unwrap();
+        platform.atomic_write(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert!(!crate::platforms::atomic_write_temp_path(&path).exists());
+    }
+
+    #[test]
+    fn test_atomic_write_failure_keeps_original() {
+        let (_dir, platform) = temp_platform();
+        let path = platform.settings_path();
+        fs::write(&path, "original").unwrap();
+        // A directory in the temp file's place makes the write fail before the rename
+        let tmp_path = crate::platforms::atomic_write_temp_path(&path);
+        fs::create_dir(&tmp_path).unwrap();
+        assert!(platform.atomic_write(&path, "replacement").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+    }
+
+    #[test]
+    fn test_atomic_write_removes_temp_file_on_failure() {
+        let (_dir, platform) = temp_platform();
+        // Renaming a file over a non-empty directory fails after the temp file is written
+        let path = platform.claude_dir.join("occupied");
+        fs::create_dir_all(path.join("child")).unwrap();
+        assert!(platform.atomic_write(&path, "contents").is_err());
+        assert!(path.join("child").is_dir());
+        assert!(!crate::platforms::atomic_write_temp_path(&path).exists());
+    }
+
+    #[test]
+    fn test_atomic_write_concurrent_writers() {
+        let (_dir, platform) = temp_platform();
+        let path = platform.settings_path();
+        let contents: Vec<String> = (0..10).map(|i| format!("{{\"writer\": {}}}", i).repeat(i + 1)).collect();
+        std::thread::scope(|scope| {
+            for content in &contents {
+                let (platform, path) = (&platform, &path);
+                scope.spawn(move || platform.atomic_write(path, content).unwrap());
+            }
+        });
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains(&written), "corrupted: {}", written);
+        let leftovers: Vec<_> = fs::read_dir(&platform.claude_dir).unwrap().collect();
+        assert_eq!(leftovers.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_settings_is_idempotent() {
+        let (_dir, platform) = temp_platform();
+        let foreign = json!({ "hooks": { "Stop": [{ "hooks": [{ "type": "command", "command": "notify.sh" }] }] } });
+        fs::write(platform.settings_path(), foreign.to_string()).unwrap();
+
+        platform.merge_settings().unwrap();
+        let once = fs::read_to_string(platform.settings_path()).unwrap();
+        platform.merge_settings().unwrap();
+        assert_eq!(fs::read_to_string(platform.settings_path()).unwrap(), once);
+
+        let settings = read_settings(&platform);
+        for event in HOOK_EVENTS {
+            assert_eq!(count_our_hooks(&settings, event), 1, "{}", event);
+        }
+        assert_eq!(settings["hooks"]["Stop"][0]["hooks"][0]["command"], "notify.sh");
+    }
+
     #[test]
     fn test_stats_file_path() {
         let path = ClaudeCodePlatform::stats_file_path("/Users/test/project");
         assert!(path.to_string_lossy().starts_with("/tmp/crabigator-stats-"));
         assert!(path.to_string_lossy().ends_with(".json"));
+        assert_eq!(
+            ClaudeCodePlatform::stats_lock_path(&ClaudeCodePlatform::stats_file_for("abc")),
+            PathBuf::from("/tmp/crabigator-stats-abc.lock")
+        );
+    }
+
+    #[test]
+    fn test_load_stats_retries_partial_writes() {
+        let saved = serde_json::to_string(&PlatformStats { prompts: 4, ..Default::default() }).unwrap();
+        // The first two reads catch the hook mid-write
+        let mut reads = vec![saved[..saved.len() / 2].to_string(), String::new(), saved.clone()].into_iter();
+        let mut count = 0;
+        let stats = ClaudeCodePlatform::load_stats_with_retry(|| {
+            count += 1;
+            Ok(reads.next().unwrap())
+        })
+        .unwrap();
+        assert_eq!(stats.prompts, 4);
+        assert_eq!(count, 3);
+
+        // Never parses: every retry is used, then the defaults
+        let mut count = 0;
+        let stats = ClaudeCodePlatform::load_stats_with_retry(|| {
+            count += 1;
+            Ok("{".to_string())
+        })
+        .unwrap();
+        assert_eq!(stats.prompts, 0);
+        assert_eq!(count, 1 + STATS_READ_RETRIES);
+
+        let err = ClaudeCodePlatform::load_stats_with_retry(|| Err(std::io::ErrorKind::PermissionDenied.into()));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_meta_records_lock_path() {
+        let (_dir, platform) = temp_platform();
+        platform.install_hooks().unwrap();
+        assert_eq!(read_meta(&platform).lock_path, "/tmp/crabigator-stats-{key}.lock");
     }
 }
@@ -12,6 +12,7 @@ State machine:
 """
 # crabigator-hook-version: {VERSION}
 
+import fcntl
 import json
 import hashlib
 import os
@@ -20,7 +21,16 @@ import time
 from pathlib import Path
 
 # Maximum number of events to keep in history
-MAX_EVENT_HISTORY = 100
+MAX_EVENT_HISTORY = 1000
+
+# Maximum number of per-turn thinking times to keep
+MAX_THINKING_SAMPLES = 500
+
+# Maximum number of timed tool calls to keep (the counts in "tools" cover all)
+MAX_TOOL_CALLS = 1000
+
+# Transcript usage fields that count as input: uncached, cache reads, cache writes
+INPUT_TOKEN_FIELDS = ("input_tokens", "cache_read_input_tokens", "cache_creation_input_tokens")
 
 def debug_log(session_id: str, message: str):
     """Write debug message to hook log file."""
@@ -43,6 +53,24 @@ def get_stats_file(cwd: str) -> Path:
     cwd_hash = hashlib.md5(cwd.encode()).hexdigest()[:12]
     return Path(f"/tmp/crabigator-stats-{cwd_hash}.json")
 
+def lock_stats(stats_file: Path):
+    """Take an exclusive lock for a read-modify-write of the stats file.
+
+    Parallel tool calls fire PostToolUse hooks at once; unlocked, two of them
+    read the same stats and the later save drops the other's update. Returns
+    the lock file (closing it releases the lock), or None if it can't be taken.
+    """
+    try:
+        lock_file = open(stats_file.with_suffix('.lock'), 'w')
+    except OSError:
+        return None
+    try:
+        fcntl.flock(lock_file, fcntl.LOCK_EX)
+    except OSError:
+        lock_file.close()
+        return None
+    return lock_file
+
 def add_event(stats: dict, event: str, details: dict = None):
     """Add an event to the history log with timestamp."""
     if "event_history" not in stats:
@@ -62,6 +90,12 @@ def add_event(stats: dict, event: str, details: dict = None):
     if len(stats["event_history"]) > MAX_EVENT_HISTORY:
         stats["event_history"] = stats["event_history"][-MAX_EVENT_HISTORY:]
 
+def finish_event(stats: dict):
+    """Record the state the latest event left the session in."""
+    history = stats.get("event_history")
+    if history:
+        history[-1]["state_after"] = stats.get("state", "ready")
+
 def load_stats(stats_file: Path) -> dict:
     """Load existing stats or return defaults."""
     if stats_file.exists():
@@ -75,11 +109,13 @@ def load_stats(stats_file: Path) -> dict:
         "completions": 0,
         "subagent_messages": 0,
         "compressions": 0,
+        "last_compressed_at": None,
         "tools": {},
-        "tool_timestamps": [],
+        "tool_calls": [],
         "state": "ready",
         "pending_question": False,
         "idle_since": None,
+        "last_tool_timestamp": None,
         "last_updated": None,
         "model": None,
     }
@@ -113,6 +149,43 @@ def extract_model_from_transcript(transcript_path: str) -> str | None:
     except Exception:
         return None
 
+def add_transcript_usage(stats: dict, transcript_path: str):
+    """Add the token usage of transcript lines written since the last event.
+
+    Reads from the offset saved in stats, so each line is counted once; a
+    message split over several lines repeats its usage, so only the first
+    line of each message id counts. Input includes prompt cache reads and
+    writes, which with caching are most of it.
+    """
+    try:
+        path = Path(transcript_path)
+        if stats.get("transcript_path") != transcript_path:
+            stats["transcript_path"] = transcript_path
+            stats["transcript_offset"] = 0
+        with open(path, 'rb') as f:
+            f.seek(stats.get("transcript_offset", 0))
+            content = f.read()
+        # Leave a partly written last line for the next event
+        end = content.rfind(b'\n') + 1
+        stats["transcript_offset"] = stats.get("transcript_offset", 0) + end
+
+        for line in content[:end].decode('utf-8', errors='ignore').split('\n'):
+            if '"usage"' not in line:
+                continue
+            try:
+                message = json.loads(line).get("message") or {}
+            except json.JSONDecodeError:
+                continue
+            usage = message.get("usage")
+            if not isinstance(usage, dict) or message.get("id") == stats.get("last_usage_id"):
+                continue
+            stats["last_usage_id"] = message.get("id")
+            input_tokens = sum(usage.get(key) or 0 for key in INPUT_TOKEN_FIELDS)
+            stats["input_tokens"] = stats.get("input_tokens", 0) + input_tokens
+            stats["output_tokens"] = stats.get("output_tokens", 0) + usage.get("output_tokens", 0)
+    except (OSError, ValueError):
+        pass
+
 def save_stats(stats_file: Path, stats: dict):
     """Atomically save stats to file."""
     stats["last_updated"] = time.time()
@@ -132,6 +205,7 @@ def save_stats(stats_file: Path, stats: dict):
             pass
 
 def main():
+    started = time.perf_counter()
     session_id = os.environ.get("CRABIGATOR_SESSION_ID", "")
     try:
         data = json.load(sys.stdin)
@@ -146,6 +220,8 @@ def main():
     debug_log(session_id, f"RAW_DATA: {json.dumps(data)}")
 
     stats_file = get_stats_file(cwd)
+    # Held until the stats are saved, so concurrent hooks take turns
+    lock = lock_stats(stats_file)
     stats = load_stats(stats_file)
 
     # Extract model from transcript if not already known
@@ -155,6 +231,8 @@ def main():
         if model:
             stats["model"] = model
             debug_log(session_id, f"  extracted model={model}")
+    if transcript_path:
+        add_transcript_usage(stats, transcript_path)
 
     debug_log(session_id, f"  state_before={stats.get('state', 'ready')} file={stats_file}")
 
@@ -183,9 +261,14 @@ def main():
         tool_name = data.get("tool_name", "unknown")
         add_event(stats, event, {"tool": tool_name})
         stats["tools"][tool_name] = stats["tools"].get(tool_name, 0) + 1
-        if "tool_timestamps" not in stats:
-            stats["tool_timestamps"] = []
-        stats["tool_timestamps"].append(time.time())
+        now = time.time()
+        tool_calls = stats.setdefault("tool_calls", [])
+        tool_calls.append({"tool": tool_name, "ts": now})
+        stats["tool_calls"] = tool_calls[-MAX_TOOL_CALLS:]
+        # Written by older hooks, before tool_calls
+        stats.pop("tool_timestamps", None)
+        # Start the per-operation timer shown next to the throbber
+        stats["last_tool_timestamp"] = now
         # Mark if this was a question tool
         if tool_name == "AskUserQuestion":
             stats["pending_question"] = True
@@ -205,6 +288,12 @@ def main():
             stats["state"] = "complete"
         # Start idle timer
         stats["idle_since"] = time.time()
+        # Record how long this turn took, prompt to completion
+        thinking_start = stats.pop("thinking_start_ts", None)
+        if thinking_start is not None:
+            samples = stats.setdefault("thinking_samples", [])
+            samples.append(max(0, round(time.time() - thinking_start)))
+            stats["thinking_samples"] = samples[-MAX_THINKING_SAMPLES:]
         # Clear permission data
         stats.pop("permission", None)
 
@@ -215,6 +304,7 @@ def main():
     elif event == "PreCompact":
         add_event(stats, event)
         stats["compressions"] += 1
+        stats["last_compressed_at"] = time.time()
 
     elif event == "UserPromptSubmit":
         # User submitted input, Claude starts thinking
@@ -223,6 +313,10 @@ def main():
         stats["state"] = "thinking"
         stats["pending_question"] = False
         stats["idle_since"] = None
+        # Time the first operation of the turn from the prompt
+        stats["last_tool_timestamp"] = time.time()
+        # Time the whole turn for the thinking average
+        stats["thinking_start_ts"] = time.time()
         # Clear permission data
         stats.pop("permission", None)
 
@@ -230,8 +324,14 @@ def main():
         # Log unhandled events for debugging
         add_event(stats, event, {"unhandled": True})
 
+    finish_event(stats)
     debug_log(session_id, f"  state_after={stats.get('state', 'ready')}")
+    # Track our own run time for the --profile resource summary
+    stats["hook_ms_total"] = stats.get("hook_ms_total", 0.0) + (time.perf_counter() - started) * 1000
+    stats["hook_runs"] = stats.get("hook_runs", 0) + 1
     save_stats(stats_file, stats)
+    if lock:
+        lock.close()
     debug_log(session_id, f"  saved to {stats_file}")
     sys.exit(0)
 
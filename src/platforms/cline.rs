@@ -0,0 +1,391 @@
+//! Cline platform implementation
+//!
+//! Cline (formerly Claude Dev) is a VS Code extension. It reads hook commands
+//! from `cline_hooks.json` inside its extension directory
+//! (`~/.vscode/extensions/saoudrizwan.claude-dev-<version>/`) and runs them
+//! with a JSON event on stdin for `onToolUse`, `onApiRequest` and
+//! `onChatComplete`. Our hook script folds those events into a session JSON
+//! file that `load_stats` reads back as `PlatformStats`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use md5::{Digest, Md5};
+use serde_json::{json, Value};
+
+use super::{Platform, PlatformError, PlatformKind, PlatformStats};
+
+/// Extension directories are named `<publisher>.<name>-<version>`
+const EXTENSION_PREFIX: &str = "saoudrizwan.claude-dev-";
+
+/// Hook config file inside the extension directory
+const HOOK_CONFIG_FILE: &str = "cline_hooks.json";
+
+/// Hook events we register our script for
+const HOOK_EVENTS: [&str; 3] = ["onToolUse", "onApiRequest", "onChatComplete"];
+
+/// Current hook version - should match Cargo.toml version
+const HOOK_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Python hook script content (loaded from stats_hook.py at compile time)
+const HOOK_SCRIPT: &str = include_str!("cline/stats_hook.py");
+
+/// An installed copy of the Cline extension
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClineExtension {
+    /// Extension directory under `~/.vscode/extensions`
+    pub path: PathBuf,
+    /// Version from the directory name (e.g. "3.17.4")
+    pub version: String,
+}
+
+impl ClineExtension {
+    /// Path to the extension's hook config
+    pub fn hook_config_path(&self) -> PathBuf {
+        self.path.join(HOOK_CONFIG_FILE)
+    }
+}
+
+/// Find the newest Cline extension under a VS Code directory (`~/.vscode`)
+///
+/// VS Code keeps old versions around until it restarts, so several may be
+/// installed at once. Platform-specific builds carry a suffix
+/// (`saoudrizwan.claude-dev-3.17.4-darwin-arm64`) that isn't part of the version.
+pub fn find_extension(vscode_dir: &Path) -> Option<ClineExtension> {
+    let entries = fs::read_dir(vscode_dir.join("extensions")).ok()?;
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rest = name.strip_prefix(EXTENSION_PREFIX)?;
+            let version = rest.split('-').next().unwrap_or(rest);
+            if !version.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            Some(ClineExtension {
+                path: entry.path(),
+                version: version.to_string(),
+            })
+        })
+        .max_by_key(|ext| version_key(&ext.version))
+}
+
+/// Numeric components of a dotted version, for ordering "3.10.0" after "3.9.2"
+fn version_key(version: &str) -> Vec<u64> {
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+/// Add our script to every hook event in a Cline hook config
+///
+/// Other hooks and unrelated keys are kept. Returns whether anything changed,
+/// or why the config can't be merged into.
+fn merge_hook_config(config: &mut Value, script_path_str: &str) -> Result<bool, String> {
+    let Some(root) = config.as_object_mut() else {
+        return Err("root must be a JSON object".to_string());
+    };
+    let hooks = root.entry("hooks").or_insert_with(|| json!({}));
+    let Some(hooks) = hooks.as_object_mut() else {
+        return Err("\"hooks\" must be an object".to_string());
+    };
+
+    let mut changed = false;
+    for event in HOOK_EVENTS {
+        let entries = hooks.entry(event).or_insert_with(|| json!([]));
+        let Some(entries) = entries.as_array_mut() else {
+            return Err(format!("\"hooks.{}\" must be an array", event));
+        };
+        let registered = entries
+            .iter()
+            .any(|hook| hook.get("command").and_then(|c| c.as_str()) == Some(script_path_str));
+        if !registered {
+            entries.push(json!({ "command": script_path_str }));
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+/// Cline platform implementation
+pub struct ClinePlatform {
+    /// Path to ~/.vscode, where the extension is installed
+    vscode_dir: PathBuf,
+    /// Path to ~/.cline/crabigator, where the hook script lives (outside the
+    /// extension directory, which is replaced on every update)
+    crabigator_dir: PathBuf,
+}
+
+impl ClinePlatform {
+    pub fn new() -> Self {
+        let home = dirs::home_dir().expect("Could not find home directory");
+        Self::with_dirs(home.join(".vscode"), home.join(".cline").join("crabigator"))
+    }
+
+    fn with_dirs(vscode_dir: PathBuf, crabigator_dir: PathBuf) -> Self {
+        Self {
+            vscode_dir,
+            crabigator_dir,
+        }
+    }
+
+    /// Get path to hook script
+    fn script_path(&self) -> PathBuf {
+        self.crabigator_dir.join("stats-hook.py")
+    }
+
+    fn script_content() -> String {
+        HOOK_SCRIPT.replace("{VERSION}", HOOK_VERSION)
+    }
+
+    /// Write the hook script if it's missing or out of date
+    fn install_script(&self) -> Result<(), PlatformError> {
+        let script_path = self.script_path();
+        let content = Self::script_content();
+        if fs::read_to_string(&script_path).is_ok_and(|existing| existing == content) {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.crabigator_dir).map_err(|source| PlatformError::ScriptWriteFailed {
+            path: self.crabigator_dir.clone(),
+            source,
+        })?;
+        let script_write_failed = |source| PlatformError::ScriptWriteFailed {
+            path: script_path.clone(),
+            source,
+        };
+        fs::write(&script_path, content).map_err(script_write_failed)?;
+
+        // Make script executable on Unix
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(&script_path).map_err(script_write_failed)?.permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&script_path, perms).map_err(script_write_failed)?;
+        }
+        Ok(())
+    }
+
+    /// Register our script in the extension's cline_hooks.json
+    fn register_hooks(&self, extension: &ClineExtension) -> Result<(), PlatformError> {
+        let config_path = extension.hook_config_path();
+        let invalid = |reason: String| PlatformError::InvalidConfig {
+            path: config_path.clone(),
+            reason,
+        };
+
+        let mut config = match fs::read_to_string(&config_path) {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(|e| invalid(format!("not valid JSON ({})", e)))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => json!({}),
+            Err(e) => return Err(invalid(format!("unreadable ({})", e))),
+        };
+
+        let script_path_str = self.script_path().to_string_lossy().to_string();
+        let changed = merge_hook_config(&mut config, &script_path_str).map_err(invalid)?;
+        if changed || !config_path.exists() {
+            let content = serde_json::to_string_pretty(&config).expect("hook config serializes");
+            super::atomic_write(&config_path, &content).map_err(|e| PlatformError::HookRegistrationFailed {
+                event: HOOK_EVENTS.join(", "),
+                reason: format!("failed to write {} ({})", config_path.display(), e),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Session file path: `CRABIGATOR_CLINE_SESSION_PATH`, else keyed by cwd
+    /// hash (matches the Python hook). The hook runs inside VS Code, which
+    /// never sees our environment, so the session ID can't be part of the key.
+    fn session_file_path(cwd: &str) -> PathBuf {
+        if let Ok(path) = std::env::var("CRABIGATOR_CLINE_SESSION_PATH") {
+            return PathBuf::from(path);
+        }
+        let mut hasher = Md5::new();
+        hasher.update(cwd.as_bytes());
+        let hex: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        PathBuf::from(format!("/tmp/crabigator-cline-{}.json", &hex[..12]))
+    }
+}
+
+impl Default for ClinePlatform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Platform for ClinePlatform {
+    fn kind(&self) -> PlatformKind {
+        PlatformKind::Cline
+    }
+
+    fn command(&self) -> &'static str {
+        PlatformKind::Cline.command()
+    }
+
+    fn ensure_hooks_installed(&self) -> Result<(), PlatformError> {
+        // Without the extension there's no config to register in; the CLI
+        // still runs, just without stats
+        let Some(extension) = find_extension(&self.vscode_dir) else {
+            return Ok(());
+        };
+        self.install_script()?;
+        self.register_hooks(&extension)
+    }
+
+    fn force_reinstall(&self) -> Result<()> {
+        let script_path = self.script_path();
+        if script_path.exists() {
+            fs::remove_file(&script_path).with_context(|| format!("Failed to remove {}", script_path.display()))?;
+        }
+        self.ensure_hooks_installed()?;
+        Ok(())
+    }
+
+    fn reinstall_plan(&self) -> Result<Vec<String>> {
+        let mut plan = Vec::new();
+        let script_path = self.script_path();
+        if script_path.exists() {
+            plan.push(format!("remove {}", script_path.display()));
+        }
+        match find_extension(&self.vscode_dir) {
+            Some(extension) => {
+                plan.push(format!("install {}", script_path.display()));
+                plan.push(format!("register hooks in {}", extension.hook_config_path().display()));
+            }
+            None => plan.push(format!("no Cline extension found under {}", self.vscode_dir.display())),
+        }
+        Ok(plan)
+    }
+
+    fn stats_path(&self, cwd: &str) -> Option<PathBuf> {
+        Some(Self::session_file_path(cwd))
+    }
+
+    fn load_stats(&self, cwd: &str) -> Result<PlatformStats> {
+        let session_path = Self::session_file_path(cwd);
+        if !session_path.exists() {
+            return Ok(PlatformStats::default());
+        }
+        let content = fs::read_to_string(&session_path)
+            .with_context(|| format!("Failed to read {}", session_path.display()))?;
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    fn cleanup_stats(&self, cwd: &str) {
+        // A configured location belongs to the user
+        if std::env::var_os("CRABIGATOR_CLINE_SESSION_PATH").is_none() {
+            let _ = fs::remove_file(Self::session_file_path(cwd));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::SessionState;
+
+    fn temp_platform() -> (tempfile::TempDir, ClinePlatform) {
+        let dir = tempfile::tempdir().unwrap();
+        let platform = ClinePlatform::with_dirs(dir.path().join(".vscode"), dir.path().join(".cline/crabigator"));
+        (dir, platform)
+    }
+
+    fn install_extension(platform: &ClinePlatform, dir_name: &str) -> PathBuf {
+        let path = platform.vscode_dir.join("extensions").join(dir_name);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_find_extension_picks_newest_version() {
+        let (_dir, platform) = temp_platform();
+        assert_eq!(find_extension(&platform.vscode_dir), None);
+
+        install_extension(&platform, "saoudrizwan.claude-dev-3.9.2");
+        let newest = install_extension(&platform, "saoudrizwan.claude-dev-3.10.0-darwin-arm64");
+        install_extension(&platform, "rust-lang.rust-analyzer-0.3.2000");
+        install_extension(&platform, "saoudrizwan.claude-dev-nightly");
+        fs::write(platform.vscode_dir.join("extensions/saoudrizwan.claude-dev-9.0.0"), "").unwrap();
+
+        let extension = find_extension(&platform.vscode_dir).unwrap();
+        assert_eq!(extension.version, "3.10.0");
+        assert_eq!(extension.hook_config_path(), newest.join("cline_hooks.json"));
+    }
+
+    #[test]
+    fn test_merge_hook_config_keeps_other_hooks() {
+        let mut config = json!({
+            "enabled": true,
+            "hooks": { "onToolUse": [{ "command": "notify.sh" }] }
+        });
+        assert_eq!(merge_hook_config(&mut config, "/x/stats-hook.py"), Ok(true));
+        assert_eq!(config["enabled"], true);
+        assert_eq!(config["hooks"]["onToolUse"][0]["command"], "notify.sh");
+        for event in HOOK_EVENTS {
+            let entries = config["hooks"][event].as_array().unwrap();
+            assert_eq!(entries.iter().filter(|e| e["command"] == "/x/stats-hook.py").count(), 1, "{}", event);
+        }
+
+        // Merging again is a no-op
+        let before = config.clone();
+        assert_eq!(merge_hook_config(&mut config, "/x/stats-hook.py"), Ok(false));
+        assert_eq!(config, before);
+
+        assert!(merge_hook_config(&mut json!([]), "/x").is_err());
+        assert!(merge_hook_config(&mut json!({"hooks": []}), "/x").is_err());
+        assert!(merge_hook_config(&mut json!({"hooks": {"onChatComplete": {}}}), "/x").is_err());
+    }
+
+    #[test]
+    fn test_ensure_hooks_installed() {
+        let (_dir, platform) = temp_platform();
+        // No extension: nothing to do, nothing written
+        platform.ensure_hooks_installed().unwrap();
+        assert!(!platform.script_path().exists());
+
+        let ext_dir = install_extension(&platform, "saoudrizwan.claude-dev-3.17.4");
+        let config_path = ext_dir.join("cline_hooks.json");
+        fs::write(&config_path, "{ not json").unwrap();
+        match platform.ensure_hooks_installed() {
+            Err(PlatformError::InvalidConfig { path, .. }) => assert_eq!(path, config_path),
+            other => panic!("expected InvalidConfig, got {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "{ not json");
+
+        fs::remove_file(&config_path).unwrap();
+        platform.ensure_hooks_installed().unwrap();
+        assert_eq!(fs::read_to_string(platform.script_path()).unwrap(), ClinePlatform::script_content());
+        let config: Value = serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        let script = platform.script_path().to_string_lossy().to_string();
+        assert_eq!(config["hooks"]["onChatComplete"][0]["command"], script.as_str());
+    }
+
+    #[test]
+    fn test_session_file_keyed_by_cwd_hash() {
+        // Same key as the hook's hashlib.md5(cwd).hexdigest()[:12]
+        assert_eq!(
+            ClinePlatform::session_file_path("/home/me/project"),
+            PathBuf::from("/tmp/crabigator-cline-7eb7c5d1a12f.json")
+        );
+    }
+
+    #[test]
+    fn test_session_file_parses_as_stats() {
+        let session = json!({
+            "prompts": 2,
+            "completions": 1,
+            "subagent_messages": 0,
+            "compressions": 0,
+            "tools": { "read_file": 3, "execute_command": 1 },
+            "state": "complete",
+            "model": "claude-sonnet-4",
+            "last_updated": 1700000000.0,
+        });
+        let stats: PlatformStats = serde_json::from_value(session).unwrap();
+        assert_eq!(stats.total_tool_calls(), 4);
+        assert_eq!(stats.state, SessionState::Complete);
+        assert_eq!(stats.model.as_deref(), Some("claude-sonnet-4"));
+    }
+}
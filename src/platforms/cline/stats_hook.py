@@ -0,0 +1,168 @@
+#!/usr/bin/env python3
+"""
+Crabigator stats hook for Cline
+Handles: onToolUse, onApiRequest, onChatComplete
+
+Cline passes the event as JSON on stdin, e.g.
+  {"event": "onToolUse", "tool": "read_file", "cwd": "/path", "model": "..."}
+
+State machine:
+  - ready: Initial state (nothing happened yet)
+  - thinking: Cline sent an API request or is running tools
+  - complete: Cline finished the chat turn
+"""
+# crabigator-hook-version: {VERSION}
+
+import fcntl
+import json
+import hashlib
+import os
+import sys
+import time
+from pathlib import Path
+
+# Maximum number of events to keep in history
+MAX_EVENT_HISTORY = 1000
+
+# Maximum number of per-turn thinking times to keep
+MAX_THINKING_SAMPLES = 500
+
+# Maximum number of timed tool calls to keep (the counts in "tools" cover all)
+MAX_TOOL_CALLS = 1000
+
+def get_session_file(cwd: str) -> Path:
+    """Session file path: explicit override, else cwd hash.
+
+    VS Code runs this hook without crabigator's environment, so the session ID
+    isn't available here; crabigator keys its side on the same hash.
+    """
+    override = os.environ.get("CRABIGATOR_CLINE_SESSION_PATH")
+    if override:
+        return Path(override)
+    cwd_hash = hashlib.md5(cwd.encode()).hexdigest()[:12]
+    return Path(f"/tmp/crabigator-cline-{cwd_hash}.json")
+
+def lock_stats(path: Path):
+    """Take an exclusive lock for a read-modify-write of the session file.
+
+    Cline can fire hooks for several tool calls at once; unlocked, two of them
+    read the same stats and the later save drops the other's update. Returns
+    the lock file (closing it releases the lock), or None if it can't be taken.
+    """
+    try:
+        lock_file = open(path.with_suffix('.lock'), 'w')
+    except OSError:
+        return None
+    try:
+        fcntl.flock(lock_file, fcntl.LOCK_EX)
+    except OSError:
+        lock_file.close()
+        return None
+    return lock_file
+
+def load_stats(path: Path) -> dict:
+    """Load existing stats or return defaults."""
+    if path.exists():
+        try:
+            with open(path) as f:
+                return json.load(f)
+        except (json.JSONDecodeError, IOError):
+            pass
+    return {
+        "prompts": 0,
+        "completions": 0,
+        "subagent_messages": 0,
+        "compressions": 0,
+        "tools": {},
+        "tool_calls": [],
+        "state": "ready",
+        "idle_since": None,
+        "last_updated": None,
+        "event_history": [],
+    }
+
+def save_stats(path: Path, stats: dict):
+    """Write stats atomically so crabigator never reads half a file."""
+    tmp_path = path.with_suffix(".tmp")
+    with open(tmp_path, "w") as f:
+        json.dump(stats, f)
+    os.replace(tmp_path, path)
+
+def add_event(stats: dict, event: str, details: dict = None):
+    """Add an event to the history log with timestamp."""
+    entry = {
+        "ts": time.time(),
+        "event": event,
+        "state_before": stats.get("state", "ready"),
+    }
+    if details:
+        entry["details"] = details
+    history = stats.setdefault("event_history", [])
+    history.append(entry)
+    stats["event_history"] = history[-MAX_EVENT_HISTORY:]
+
+def finish_event(stats: dict):
+    """Record the state the latest event left the session in."""
+    history = stats.get("event_history")
+    if history:
+        history[-1]["state_after"] = stats.get("state", "ready")
+
+def main():
+    try:
+        payload = json.load(sys.stdin)
+    except (json.JSONDecodeError, ValueError):
+        return
+
+    event = payload.get("event", "")
+    cwd = payload.get("cwd") or os.getcwd()
+    path = get_session_file(cwd)
+    # Held until the stats are saved, so concurrent hooks take turns
+    lock = lock_stats(path)
+    stats = load_stats(path)
+    now = time.time()
+
+    if event == "onToolUse":
+        tool = payload.get("tool") or "unknown"
+        add_event(stats, event, {"tool": tool})
+        tools = stats.setdefault("tools", {})
+        tools[tool] = tools.get(tool, 0) + 1
+        tool_calls = stats.setdefault("tool_calls", [])
+        tool_calls.append({"tool": tool, "ts": now})
+        stats["tool_calls"] = tool_calls[-MAX_TOOL_CALLS:]
+        stats.pop("tool_timestamps", None)
+        stats["last_tool_timestamp"] = now
+        stats["state"] = "thinking"
+        stats["idle_since"] = None
+    elif event == "onApiRequest":
+        add_event(stats, event)
+        # The first request of a turn is the user's prompt going out
+        if stats.get("state") != "thinking":
+            stats["prompts"] = stats.get("prompts", 0) + 1
+            stats["thinking_start_ts"] = now
+        stats["state"] = "thinking"
+        stats["idle_since"] = None
+    elif event == "onChatComplete":
+        add_event(stats, event)
+        stats["completions"] = stats.get("completions", 0) + 1
+        stats["state"] = "complete"
+        stats["idle_since"] = now
+        thinking_start = stats.pop("thinking_start_ts", None)
+        if thinking_start is not None:
+            samples = stats.setdefault("thinking_samples", [])
+            samples.append(max(0, round(now - thinking_start)))
+            stats["thinking_samples"] = samples[-MAX_THINKING_SAMPLES:]
+    else:
+        if lock:
+            lock.close()
+        return
+
+    finish_event(stats)
+    if payload.get("model"):
+        stats["model"] = payload["model"]
+    stats["last_updated"] = now
+    save_stats(path, stats)
+    if lock:
+        lock.close()
+
+if __name__ == "__main__":
+    main()
@@ -14,7 +14,7 @@ use anyhow::{Context, Result};
 use chrono::{Datelike, Local};
 use serde_json::Value;
 
-use super::{Platform, PlatformKind, PlatformStats};
+use super::{Platform, PlatformError, PlatformKind, PlatformStats};
 use log_parser::{
     parse_timestamp, reset_state, set_last_updated, update_from_log,
     CodexState, SessionCandidate, SessionMetaInfo,
@@ -234,7 +234,7 @@ impl Platform for CodexPlatform {
         PlatformKind::Codex.command()
     }
 
-    fn ensure_hooks_installed(&self) -> Result<()> {
+    fn ensure_hooks_installed(&self) -> Result<(), PlatformError> {
         // Codex CLI does not currently support Crabigator hooks.
         Ok(())
     }
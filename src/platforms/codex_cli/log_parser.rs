@@ -7,7 +7,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde_json::Value;
 
-use crate::platforms::{PlatformStats, SessionState};
+use crate::platforms::{PlatformStats, SessionState, ToolCall};
 
 /// Source of a message count
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -138,20 +138,55 @@ fn handle_response_item(state: &mut CodexState, value: &Value) {
                 Some("assistant") => {
                     record_completion(state, MessageSource::ResponseItem);
                 }
-                Some("user") => {
-                    if !is_bootstrap_message(payload) {
-                        record_prompt(state, MessageSource::ResponseItem);
-                    }
+                Some("user") if !is_bootstrap_message(payload) => {
+                    record_prompt(state, MessageSource::ResponseItem);
                 }
                 _ => {}
             }
         }
-        Some("function_call") => {
-            if let Some(name) = payload.get("name").and_then(|v| v.as_str()) {
-                record_tool_call(state, name);
+        Some(item_type) => {
+            for name in tool_call_names(item_type, payload) {
+                record_tool_call(state, &name);
             }
         }
-        _ => {}
+        None => {}
+    }
+}
+
+/// Tool calls made by a response item, one name per call
+///
+/// Function and custom tools are counted under their own name. Built-in tools
+/// are keyed by the tool: computer use by action (`computer_use.click`), web
+/// and file search once per query, code interpreter once per executed cell.
+fn tool_call_names(item_type: &str, payload: &serde_json::Map<String, Value>) -> Vec<String> {
+    let str_field = |value: Option<&Value>, key: &str| {
+        value.and_then(|v| v.get(key)).and_then(|v| v.as_str()).map(str::to_string)
+    };
+    let per_query = |name: &str, queries: Option<&Value>| {
+        let n = queries.and_then(|q| q.as_array()).map_or(1, |q| q.len().max(1));
+        vec![name.to_string(); n]
+    };
+    let action = payload.get("action");
+
+    match item_type {
+        "function_call" | "custom_tool_call" => payload
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(|name| vec![name.to_string()])
+            .unwrap_or_default(),
+        "local_shell_call" => vec!["shell".to_string()],
+        "computer_call" => {
+            let kind = str_field(action, "type").unwrap_or_else(|| "unknown".to_string());
+            vec![format!("computer_use.{}", kind)]
+        }
+        "web_search_call" => per_query("web_search", action.and_then(|a| a.get("queries"))),
+        "file_search_call" => per_query("file_search", payload.get("queries")),
+        "code_interpreter_call" => {
+            // Older items carry one cell in `code`; batched ones list `cells`
+            let cells = payload.get("cells").and_then(|c| c.as_array()).map_or(1, |c| c.len().max(1));
+            vec!["code_interpreter".to_string(); cells]
+        }
+        _ => Vec::new(),
     }
 }
 
@@ -208,7 +243,12 @@ fn record_completion(state: &mut CodexState, source: MessageSource) {
 fn record_tool_call(state: &mut CodexState, name: &str) {
     let entry = state.stats.tools.entry(name.to_string()).or_insert(0);
     *entry = entry.saturating_add(1);
-    state.stats.tool_timestamps.push(now_unix());
+    let now = now_unix();
+    state.stats.tool_calls.push(ToolCall {
+        tool: name.to_string(),
+        ts: now,
+    });
+    state.stats.last_tool_timestamp = Some(now);
     if is_question_tool(name) {
         set_state(state, SessionState::Question);
     } else {
@@ -268,3 +308,74 @@ pub fn reset_state(state: &mut CodexState, path: PathBuf, session_started_at: Op
     state.stats = PlatformStats::default();
     set_last_updated(state);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools_after(lines: &[&str]) -> Vec<(String, u32)> {
+        let mut state = CodexState::default();
+        for line in lines {
+            update_from_log(&mut state, line);
+        }
+        state.stats.top_tools(10)
+    }
+
+    fn tools(pairs: &[(&str, u32)]) -> Vec<(String, u32)> {
+        pairs.iter().map(|(name, n)| (name.to_string(), *n)).collect()
+    }
+
+    #[test]
+    fn test_function_and_custom_tools_use_their_name() {
+        let counts = tools_after(&[
+            r#"{"type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{}"}}"#,
+            r#"{"type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{}"}}"#,
+            r#"{"type":"response_item","payload":{"type":"custom_tool_call","name":"apply_patch","input":"*** Begin Patch"}}"#,
+            r#"{"type":"response_item","payload":{"type":"local_shell_call","action":{"type":"exec","command":["ls"]}}}"#,
+        ]);
+        assert_eq!(counts, tools(&[("shell", 3), ("apply_patch", 1)]));
+    }
+
+    #[test]
+    fn test_computer_use_counts_by_action() {
+        let counts = tools_after(&[
+            r#"{"type":"response_item","payload":{"type":"computer_call","action":{"type":"click","x":10,"y":20}}}"#,
+            r#"{"type":"response_item","payload":{"type":"computer_call","action":{"type":"click","x":5,"y":5}}}"#,
+            r#"{"type":"response_item","payload":{"type":"computer_call","action":{"type":"screenshot"}}}"#,
+            r#"{"type":"response_item","payload":{"type":"computer_call"}}"#,
+        ]);
+        assert_eq!(
+            counts,
+            tools(&[("computer_use.click", 2), ("computer_use.screenshot", 1), ("computer_use.unknown", 1)])
+        );
+    }
+
+    #[test]
+    fn test_searches_count_each_query() {
+        let counts = tools_after(&[
+            r#"{"type":"response_item","payload":{"type":"web_search_call","action":{"type":"search","query":"tokio select"}}}"#,
+            r#"{"type":"response_item","payload":{"type":"web_search_call","action":{"type":"search","queries":["a","b"]}}}"#,
+            r#"{"type":"response_item","payload":{"type":"file_search_call","queries":["config","hooks","tests"]}}"#,
+        ]);
+        assert_eq!(counts, tools(&[("file_search", 3), ("web_search", 3)]));
+    }
+
+    #[test]
+    fn test_code_interpreter_counts_cells() {
+        let counts = tools_after(&[
+            r#"{"type":"response_item","payload":{"type":"code_interpreter_call","code":"print(1)"}}"#,
+            r#"{"type":"response_item","payload":{"type":"code_interpreter_call","cells":[{"code":"a=1"},{"code":"a+1"}]}}"#,
+        ]);
+        assert_eq!(counts, tools(&[("code_interpreter", 3)]));
+    }
+
+    #[test]
+    fn test_non_tool_items_are_ignored() {
+        let counts = tools_after(&[
+            r#"{"type":"response_item","payload":{"type":"reasoning","summary":[]}}"#,
+            r#"{"type":"response_item","payload":{"type":"function_call_output","output":"ok"}}"#,
+            r#"{"type":"response_item","payload":{"type":"message","role":"assistant","content":[]}}"#,
+        ]);
+        assert!(counts.is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! Replay command implementation
+//!
+//! Streams a captured session back to the terminal. Timed recordings
+//! (`session.rec`) and JSON-lines recordings (`session.jsonl`, or
+//! `--format jsonl`) are played with their original pacing scaled by
+//! `--speed`; plain transcripts (`scrollback.log`, or `.gz`) are printed as-is.
+
+use std::fs;
+use std::io::{stdout, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::capture::{
+    decode_json_lines, decode_timed_chunks, is_timed_recording, read_capture_file, saved_json_lines_path,
+    saved_recording_path, CaptureFormat, JsonFrame, TimedChunk,
+};
+
+/// Longest pause between chunks, so idle stretches don't stall playback
+const MAX_GAP: Duration = Duration::from_secs(5);
+
+/// Run the replay command
+pub fn run_replay(
+    session_id: Option<String>,
+    file: Option<String>,
+    format: Option<CaptureFormat>,
+    speed: f64,
+) -> Result<()> {
+    let path = match (file, session_id) {
+        (Some(file), _) => PathBuf::from(file),
+        (None, Some(id)) => find_session_recording(&id, format)
+            .with_context(|| format!("no capture found for session {}", id))?,
+        (None, None) => find_latest_recording(format).context("no captured sessions found in /tmp")?,
+    };
+
+    let is_json_lines = match format {
+        Some(format) => format == CaptureFormat::JsonLines,
+        None => path.extension().is_some_and(|ext| ext == "jsonl"),
+    };
+    if is_json_lines {
+        let text = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        return play(json_frames_to_chunks(decode_json_lines(&text)?), speed);
+    }
+
+    if !is_timed_recording(&path) {
+        // Plain transcript (possibly gzipped): no timing information, just dump it
+        let contents = read_capture_file(&path).with_context(|| format!("read {}", path.display()))?;
+        stdout().write_all(&contents)?;
+        return Ok(());
+    }
+
+    let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+    play(decode_timed_chunks(&bytes)?, speed)
+}
+
+/// PTY output frames as chunks timed from the first frame (resizes are skipped:
+/// playback stays at the current terminal size)
+fn json_frames_to_chunks(frames: Vec<JsonFrame>) -> Vec<TimedChunk> {
+    let mut start = None;
+    frames
+        .into_iter()
+        .filter_map(|frame| match frame {
+            JsonFrame::PtyOutput { ts, data } => {
+                let start = *start.get_or_insert(ts);
+                let elapsed_ms = ((ts - start).max(0.0) * 1000.0).round() as u64;
+                Some(TimedChunk { elapsed_ms, data })
+            }
+            JsonFrame::Resize { .. } => None,
+        })
+        .collect()
+}
+
+/// Write chunks out with their original gaps scaled by `speed` (0 = no waiting)
+fn play(chunks: Vec<TimedChunk>, speed: f64) -> Result<()> {
+    let mut out = stdout();
+    let mut last_ms = 0u64;
+
+    for chunk in chunks {
+        if speed > 0.0 {
+            let gap_ms = chunk.elapsed_ms.saturating_sub(last_ms) as f64 / speed;
+            let gap = Duration::from_millis(gap_ms as u64).min(MAX_GAP);
+            if !gap.is_zero() {
+                out.flush()?;
+                thread::sleep(gap);
+            }
+        }
+        last_ms = chunk.elapsed_ms;
+        out.write_all(&chunk.data)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Find the best capture for a session: live recording, saved recording, then
+/// scrollback (only JSON-lines recordings with `--format jsonl`)
+fn find_session_recording(session_id: &str, format: Option<CaptureFormat>) -> Option<PathBuf> {
+    let dir = PathBuf::from(format!("/tmp/crabigator-{}", session_id));
+    let json_lines = [dir.join("session.jsonl"), saved_json_lines_path(session_id)];
+    if format == Some(CaptureFormat::JsonLines) {
+        return json_lines.into_iter().find(|p| p.exists());
+    }
+    [
+        dir.join("session.rec"),
+        saved_recording_path(session_id),
+        dir.join("scrollback.log"),
+        dir.join("scrollback.log.gz"),
+    ]
+    .into_iter()
+    .chain(json_lines.into_iter().filter(|_| format.is_none()))
+    .find(|p| p.exists())
+}
+
+/// Find the most recently modified recording across all sessions
+fn find_latest_recording(format: Option<CaptureFormat>) -> Option<PathBuf> {
+    let patterns: &[&str] = match format {
+        Some(CaptureFormat::JsonLines) => &["/tmp/crabigator-*/session.jsonl", "/tmp/crabigator-*.jsonl"],
+        Some(_) => &["/tmp/crabigator-*/session.rec", "/tmp/crabigator-*.rec"],
+        None => &[
+            "/tmp/crabigator-*/session.rec",
+            "/tmp/crabigator-*.rec",
+            "/tmp/crabigator-*/session.jsonl",
+            "/tmp/crabigator-*.jsonl",
+        ],
+    };
+    patterns
+        .iter()
+        .filter_map(|p| glob::glob(p).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Parse a `--speed` value, rejecting negatives and non-numbers
+pub fn parse_speed(value: &str) -> Result<f64> {
+    let speed: f64 = value
+        .parse()
+        .with_context(|| format!("invalid speed: {}", value))?;
+    if !speed.is_finite() || speed < 0.0 {
+        bail!("speed must be a non-negative number, got {}", value);
+    }
+    Ok(speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_frames_to_chunks() {
+        let frames = vec![
+            JsonFrame::Resize { ts: 99.0, cols: 80, rows: 24 },
+            JsonFrame::PtyOutput { ts: 100.0, data: b"a".to_vec() },
+            JsonFrame::PtyOutput { ts: 100.25, data: b"b".to_vec() },
+            JsonFrame::Resize { ts: 101.0, cols: 120, rows: 40 },
+            JsonFrame::PtyOutput { ts: 102.5, data: b"c".to_vec() },
+        ];
+        let chunks = json_frames_to_chunks(frames);
+        let timing: Vec<(u64, &[u8])> = chunks.iter().map(|c| (c.elapsed_ms, c.data.as_slice())).collect();
+        assert_eq!(timing, [(0, b"a".as_slice()), (250, b"b"), (2500, b"c")]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Search command implementation
+//!
+//! `crabigator search PATTERN [SESSION_ID]` greps captured scrollback logs
+//! (plain or gzipped), after stripping any escape sequences left in them,
+//! and prints matches grep-style with surrounding context.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use regex::{Regex, RegexBuilder};
+
+use crate::capture::read_capture_file;
+use crate::terminal::escape::{ansi, DIM, RESET};
+use crate::ui::utils::strip_ansi;
+
+/// Lines of context shown around each match unless `-C` says otherwise
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Parsed `crabigator search` arguments
+#[derive(Clone, Debug)]
+pub struct SearchOptions {
+    pub pattern: String,
+    /// Only search this session (all captured sessions when None)
+    pub session_id: Option<String>,
+    /// Lines of context before and after each match
+    pub context: usize,
+    pub ignore_case: bool,
+    /// Select lines that don't match
+    pub invert: bool,
+}
+
+/// Run the search command; exits 1 when nothing matched, like grep
+pub fn run_search(options: SearchOptions) -> Result<()> {
+    let re = RegexBuilder::new(&options.pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .with_context(|| format!("invalid pattern: {}", options.pattern))?;
+
+    let logs = scrollback_logs(options.session_id.as_deref());
+    if logs.is_empty() {
+        match &options.session_id {
+            Some(id) => anyhow::bail!("no scrollback log found for session {}", id),
+            None => anyhow::bail!("no scrollback logs found in /tmp"),
+        }
+    }
+
+    let mut total = 0;
+    for (session_id, path) in logs {
+        let raw = read_capture_file(&path).with_context(|| format!("read {}", path.display()))?;
+        // Drop escapes, then the controls (\r, BEL, backspace) that would
+        // garble printed lines
+        let text = strip_ansi(&String::from_utf8_lossy(&raw));
+        let text = text.replace(|c: char| c.is_control() && !matches!(c, '\n' | '\t'), "");
+        let lines: Vec<&str> = text.lines().collect();
+
+        let groups = match_groups(&lines, &re, options.invert, options.context);
+        if groups.is_empty() {
+            continue;
+        }
+        print_header(&session_id, &path);
+        for (i, group) in groups.iter().enumerate() {
+            if i > 0 {
+                println!("{DIM}--{RESET}");
+            }
+            for &(idx, is_match) in group {
+                print_line(idx + 1, lines[idx], is_match, (!options.invert).then_some(&re));
+            }
+        }
+        println!();
+        total += groups.iter().flatten().filter(|(_, is_match)| *is_match).count();
+    }
+
+    if total == 0 {
+        eprintln!("No matches for {:?}", options.pattern);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Scrollback logs to search as (session ID, path), oldest session first
+fn scrollback_logs(session_id: Option<&str>) -> Vec<(String, PathBuf)> {
+    let pattern = match session_id {
+        Some(id) => format!("/tmp/crabigator-{}/scrollback.log*", id),
+        None => "/tmp/crabigator-*/scrollback.log*".to_string(),
+    };
+    let mut logs: Vec<(String, PathBuf)> = glob::glob(&pattern)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log" || ext == "gz"))
+        .filter_map(|path| Some((session_id_from_path(&path)?, path)))
+        .collect();
+    logs.sort_by_key(|(_, path)| modified(path));
+    logs
+}
+
+/// `/tmp/crabigator-<id>/scrollback.log` -> `<id>`
+fn session_id_from_path(path: &Path) -> Option<String> {
+    let dir = path.parent()?.file_name()?.to_str()?;
+    dir.strip_prefix("crabigator-").map(str::to_string)
+}
+
+fn modified(path: &Path) -> Option<DateTime<Local>> {
+    fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::from)
+}
+
+/// Selected lines with their context, as runs of (line index, selected)
+///
+/// Runs whose context windows overlap or touch are merged, as grep does.
+fn match_groups(lines: &[&str], re: &Regex, invert: bool, context: usize) -> Vec<Vec<(usize, bool)>> {
+    let selected: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| re.is_match(line) != invert)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut groups: Vec<Vec<(usize, bool)>> = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    let mut ranges = Vec::new();
+    for &i in &selected {
+        let start = i.saturating_sub(context);
+        let end = (i + context).min(lines.len() - 1);
+        current = match current {
+            Some((s, e)) if start <= e + 1 => Some((s, e.max(end))),
+            Some(range) => {
+                ranges.push(range);
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    ranges.extend(current);
+
+    for (start, end) in ranges {
+        groups.push(
+            (start..=end)
+                .map(|i| (i, selected.binary_search(&i).is_ok()))
+                .collect(),
+        );
+    }
+    groups
+}
+
+fn print_header(session_id: &str, path: &Path) {
+    let when = modified(path)
+        .map(|t| format!("  {}", t.format("%Y-%m-%d %H:%M")))
+        .unwrap_or_default();
+    println!("{}{}{}{DIM}{}{RESET}", ansi::GREEN, session_id, RESET, when);
+}
+
+/// Print one line as `42:text` (selected) or `42-text` (context)
+fn print_line(number: usize, line: &str, selected: bool, highlight: Option<&Regex>) {
+    if !selected {
+        println!("{DIM}{:>6}-{}{RESET}", number, line);
+        return;
+    }
+    let text = match highlight {
+        Some(re) => re
+            .replace_all(line, |caps: &regex::Captures| format!("{}{}{}", ansi::YELLOW, &caps[0], RESET))
+            .into_owned(),
+        None => line.to_string(),
+    };
+    println!("{}{:>6}:{}{}", ansi::GREEN, number, RESET, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines() -> Vec<&'static str> {
+        (0..12)
+            .map(|i| match i {
+                2 => "error: first",
+                4 => "ERROR: second",
+                10 => "error: third",
+                _ => "ok",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping_context_merges() {
+        let re = Regex::new("error").unwrap();
+        let groups = match_groups(&lines(), &re, false, 1);
+        let spans: Vec<(usize, usize)> = groups.iter().map(|g| (g[0].0, g[g.len() - 1].0)).collect();
+        assert_eq!(spans, vec![(1, 3), (9, 11)]);
+        assert_eq!(groups[0], vec![(1, false), (2, true), (3, false)]);
+
+        // Case-insensitive: lines 2 and 4 share a window once context reaches 1
+        let re = RegexBuilder::new("error").case_insensitive(true).build().unwrap();
+        let groups = match_groups(&lines(), &re, false, 1);
+        assert_eq!(groups[0].iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_invert_and_zero_context() {
+        let re = Regex::new("^ok$").unwrap();
+        let groups = match_groups(&lines(), &re, true, 0);
+        let selected: Vec<usize> = groups.iter().flatten().filter(|(_, m)| *m).map(|(i, _)| *i).collect();
+        assert_eq!(selected, vec![2, 4, 10]);
+        assert!(groups.iter().flatten().all(|(_, m)| *m));
+    }
+
+    #[test]
+    fn test_session_id_from_path() {
+        let path = Path::new("/tmp/crabigator-20261016-abc123/scrollback.log.gz");
+        assert_eq!(session_id_from_path(path).as_deref(), Some("20261016-abc123"));
+        assert_eq!(session_id_from_path(Path::new("/tmp/other/scrollback.log")), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Daily project summary
+//!
+//! `crabigator summary [--date YYYY-MM-DD] [--project PATH]` prints a
+//! Markdown report of the sessions recorded in the history store for one day
+//! and project: total time, prompts, most-used tools, files modified, and a
+//! section per session. The output is meant for pasting into a standup note
+//! or a PR description.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate, TimeZone};
+
+use crate::history::{HistoryStore, SessionRecord};
+use crate::ui::utils::format_duration;
+
+/// Tools listed in the project-wide ranking
+const TOP_TOOLS: usize = 5;
+
+/// Tools listed for each session
+const SESSION_TOP_TOOLS: usize = 3;
+
+/// Builds Markdown summaries from the history store
+pub struct SessionSummaryGenerator<'a> {
+    db: &'a HistoryStore,
+}
+
+impl<'a> SessionSummaryGenerator<'a> {
+    pub fn new(db: &'a HistoryStore) -> Self {
+        Self { db }
+    }
+
+    /// Summary of sessions that started on `date` in `cwd` or below it
+    pub fn generate_markdown(&self, date: NaiveDate, cwd: &Path) -> String {
+        let mut out = format!("# Crabigator summary for {}\n\nProject: `{}`\n\n", date, cwd.display());
+        let sessions = match self.db.sessions_on(date, cwd) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                let _ = writeln!(out, "_Could not read session history: {}_", e);
+                return out;
+            }
+        };
+        if sessions.is_empty() {
+            out.push_str("No sessions recorded.\n");
+            return out;
+        }
+
+        let total_secs: u64 = sessions.iter().map(SessionRecord::duration_secs).sum();
+        let prompts: u32 = sessions.iter().map(|s| s.prompts).sum();
+        let mut tools: BTreeMap<&str, u32> = BTreeMap::new();
+        for session in &sessions {
+            for (tool, count) in &session.tools {
+                *tools.entry(tool).or_default() += count;
+            }
+        }
+        let files: BTreeSet<&str> = sessions
+            .iter()
+            .flat_map(|s| s.files_modified.iter().map(String::as_str))
+            .collect();
+
+        let _ = writeln!(out, "- **Sessions:** {}", sessions.len());
+        let _ = writeln!(out, "- **Total time:** {}", format_duration(total_secs));
+        let _ = writeln!(out, "- **Prompts:** {}", prompts);
+        let _ = writeln!(out, "- **Files modified:** {}", files.len());
+
+        let top = top_tools(&tools, TOP_TOOLS);
+        if !top.is_empty() {
+            out.push_str("\n## Top tools\n\n| Tool | Calls |\n| --- | ---: |\n");
+            for (tool, count) in top {
+                let _ = writeln!(out, "| {} | {} |", tool, count);
+            }
+        }
+
+        if !files.is_empty() {
+            out.push_str("\n## Files modified\n\n");
+            for file in &files {
+                let _ = writeln!(out, "- `{}`", file);
+            }
+        }
+
+        out.push_str("\n## Sessions\n");
+        for session in &sessions {
+            let start = Local
+                .timestamp_opt(session.started_at as i64, 0)
+                .single()
+                .map(|t| t.format("%H:%M").to_string())
+                .unwrap_or_else(|| "??:??".to_string());
+            let name = session.project_name.as_ref().map(|n| format!(" · {}", n)).unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "\n### {} · {}{} ({})\n",
+                start,
+                format_duration(session.duration_secs()),
+                name,
+                session.platform
+            );
+            if session.cwd != cwd.to_string_lossy() {
+                let _ = writeln!(out, "- Directory: `{}`", session.cwd);
+            }
+            let _ = writeln!(out, "- Prompts: {}", session.prompts);
+            let session_tools: BTreeMap<&str, u32> = session.tools.iter().map(|(t, c)| (t.as_str(), *c)).collect();
+            let top = top_tools(&session_tools, SESSION_TOP_TOOLS);
+            if !top.is_empty() {
+                let list: Vec<String> = top.iter().map(|(tool, count)| format!("{} ({})", tool, count)).collect();
+                let _ = writeln!(out, "- Top tools: {}", list.join(", "));
+            }
+        }
+        out
+    }
+}
+
+/// The `limit` most-called tools, ties broken by name
+fn top_tools<'t>(tools: &BTreeMap<&'t str, u32>, limit: usize) -> Vec<(&'t str, u32)> {
+    let mut ranked: Vec<(&str, u32)> = tools.iter().map(|(t, c)| (*t, *c)).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Print the summary for `date` (default today) and `project` (default cwd)
+pub fn run_summary(date: Option<NaiveDate>, project: Option<PathBuf>) -> Result<()> {
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    let project = match project {
+        Some(path) => path.canonicalize().unwrap_or(path),
+        None => std::env::current_dir()?,
+    };
+    let store = HistoryStore::open_default();
+    print!("{}", SessionSummaryGenerator::new(&store).generate_markdown(date, &project));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(hour: u32, minutes: u64, cwd: &str, tools: &[(&str, u32)], files: &[&str]) -> SessionRecord {
+        let started_at = Local.with_ymd_and_hms(2026, 3, 14, hour, 5, 0).unwrap().timestamp() as f64;
+        SessionRecord {
+            session_id: format!("session-{}", hour),
+            platform: "claude".to_string(),
+            cwd: cwd.to_string(),
+            project_name: None,
+            started_at,
+            ended_at: started_at + (minutes * 60) as f64,
+            prompts: hour,
+            completions: hour,
+            thinking_seconds: 0,
+            tools: tools.iter().map(|(t, c)| (t.to_string(), *c)).collect(),
+            files_modified: files.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn store_with(records: &[SessionRecord]) -> (tempfile::TempDir, HistoryStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::at_path(dir.path().join("history.jsonl"));
+        for record in records {
+            store.append(record).unwrap();
+        }
+        (dir, store)
+    }
+
+    #[test]
+    fn test_summary_aggregates_sessions() {
+        let mut web = session(
+            14,
+            80,
+            "/work/app/web",
+            &[("Read", 5), ("Write", 3), ("Bash", 3), ("Glob", 1), ("Task", 1)],
+            &["src/main.rs", "web/index.ts"],
+        );
+        web.project_name = Some("Landing page".to_string());
+        let (_dir, store) = store_with(&[
+            session(
+                9,
+                45,
+                "/work/app",
+                &[("Edit", 12), ("Read", 9), ("Bash", 4), ("Grep", 2)],
+                &["src/main.rs", "src/lib.rs"],
+            ),
+            web,
+            // Sessions in other projects are left out
+            session(10, 30, "/work/other", &[("Edit", 50)], &["README.md"]),
+        ]);
+        let date = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let markdown = SessionSummaryGenerator::new(&store).generate_markdown(date, Path::new("/work/app"));
+
+        assert!(markdown.starts_with("# Crabigator summary for 2026-03-14\n"));
+        assert!(markdown.contains("- **Sessions:** 2\n"));
+        assert!(markdown.contains("- **Total time:** 2h 05m\n"));
+        assert!(markdown.contains("- **Prompts:** 23\n"));
+        assert!(markdown.contains("- **Files modified:** 3\n"));
+        // Read 14, Edit 12, Bash 7, Write 3, Grep 2; Glob and Task drop off
+        let tools = markdown.split("## Top tools").nth(1).unwrap();
+        let rows: Vec<&str> = tools.lines().filter(|l| l.starts_with("| ")).skip(2).collect();
+        assert_eq!(
+            rows[..5],
+            ["| Read | 14 |", "| Edit | 12 |", "| Bash | 7 |", "| Write | 3 |", "| Grep | 2 |"]
+        );
+        assert!(!markdown.contains("| Glob |"));
+        assert!(!markdown.contains("README.md"));
+
+        assert!(markdown.contains("\n### 09:05 · 45m (claude)\n"));
+        assert!(markdown.contains("- Top tools: Edit (12), Read (9), Bash (4)\n"));
+        assert!(markdown.contains("\n### 14:05 · 1h 20m · Landing page (claude)\n"));
+        assert!(markdown.contains("- Directory: `/work/app/web`\n"));
+    }
+
+    #[test]
+    fn test_summary_without_sessions() {
+        let (_dir, store) = store_with(&[session(9, 10, "/work/app", &[], &[])]);
+        let date = NaiveDate::from_ymd_opt(2026, 3, 15).unwrap();
+        let markdown = SessionSummaryGenerator::new(&store).generate_markdown(date, Path::new("/work/app"));
+        assert!(markdown.ends_with("No sessions recorded.\n"));
+    }
+}
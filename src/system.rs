@@ -0,0 +1,10 @@
+//! System introspection
+//!
+//! - Process resource usage (memory of crabigator and the assistant CLI)
+//! - Clipboard copy (pbcopy, clip.exe, wl-copy, xclip, xsel)
+
+pub mod clipboard;
+pub mod resource;
+
+pub use clipboard::copy_to_clipboard;
+pub use resource::ProcessStats;
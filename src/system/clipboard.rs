@@ -0,0 +1,132 @@
+//! System clipboard
+//!
+//! Copies through the platform's clipboard command (`pbcopy`, `clip.exe`,
+//! `wl-copy`, `xclip`, `xsel`), so no clipboard library or display connection
+//! is linked in.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Result};
+
+/// Where crabigator is running, for picking clipboard commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardEnv {
+    MacOs,
+    /// Windows Subsystem for Linux (`WSL_DISTRO_NAME` set)
+    Wsl,
+    /// `XDG_SESSION_TYPE=wayland`
+    Wayland,
+    /// X11, or Linux without a session type
+    X11,
+}
+
+impl ClipboardEnv {
+    pub fn detect() -> Self {
+        Self::from_vars(
+            cfg!(target_os = "macos"),
+            std::env::var("WSL_DISTRO_NAME").ok(),
+            std::env::var("XDG_SESSION_TYPE").ok(),
+        )
+    }
+
+    fn from_vars(macos: bool, wsl_distro: Option<String>, session_type: Option<String>) -> Self {
+        if macos {
+            Self::MacOs
+        } else if wsl_distro.is_some_and(|name| !name.is_empty()) {
+            Self::Wsl
+        } else if session_type.as_deref() == Some("wayland") {
+            Self::Wayland
+        } else {
+            Self::X11
+        }
+    }
+
+    /// Commands to try, in order, each with its arguments
+    pub fn commands(self) -> &'static [&'static [&'static str]] {
+        const XCLIP: &[&str] = &["xclip", "-selection", "clipboard"];
+        const XSEL: &[&str] = &["xsel", "--clipboard", "--input"];
+        match self {
+            Self::MacOs => &[&["pbcopy"]],
+            Self::Wsl => &[&["clip.exe"], XCLIP, XSEL],
+            Self::Wayland => &[&["wl-copy"], XCLIP, XSEL],
+            Self::X11 => &[XCLIP, XSEL],
+        }
+    }
+}
+
+/// Writes text to the clipboard with the first command that works
+pub struct ClipboardWriter;
+
+impl ClipboardWriter {
+    /// Copy `text`; an error only if none of the commands worked
+    pub fn copy(text: &str) -> Result<()> {
+        Self::copy_with(ClipboardEnv::detect().commands(), text)
+    }
+
+    fn copy_with(commands: &[&[&str]], text: &str) -> Result<()> {
+        if commands.iter().any(|command| run(command, text)) {
+            return Ok(());
+        }
+        let names: Vec<&str> = commands.iter().map(|command| command[0]).collect();
+        bail!("No clipboard command worked (tried {})", names.join(", "))
+    }
+}
+
+/// Pipe `text` into `command`; false if it is missing or fails
+fn run(command: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// Copy `text` to the clipboard; false if no clipboard command worked
+pub fn copy_to_clipboard(text: &str) -> bool {
+    ClipboardWriter::copy(text).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_detection() {
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(ClipboardEnv::from_vars(true, some("Ubuntu"), some("wayland")), ClipboardEnv::MacOs);
+        assert_eq!(ClipboardEnv::from_vars(false, some("Ubuntu"), some("x11")), ClipboardEnv::Wsl);
+        assert_eq!(ClipboardEnv::from_vars(false, some(""), some("wayland")), ClipboardEnv::Wayland);
+        assert_eq!(ClipboardEnv::from_vars(false, None, some("x11")), ClipboardEnv::X11);
+        assert_eq!(ClipboardEnv::from_vars(false, None, None), ClipboardEnv::X11);
+    }
+
+    #[test]
+    fn test_command_order() {
+        let first = |env: ClipboardEnv| env.commands()[0][0];
+        assert_eq!(first(ClipboardEnv::MacOs), "pbcopy");
+        assert_eq!(first(ClipboardEnv::Wsl), "clip.exe");
+        assert_eq!(first(ClipboardEnv::Wayland), "wl-copy");
+        // Both X11 tools are tried, xclip first, and every Linux list falls back to them
+        let x11: Vec<&str> = ClipboardEnv::X11.commands().iter().map(|c| c[0]).collect();
+        assert_eq!(x11, ["xclip", "xsel"]);
+        assert!(ClipboardEnv::Wsl.commands().ends_with(ClipboardEnv::X11.commands()));
+    }
+
+    #[test]
+    fn test_falls_through_to_a_working_command() {
+        let missing: &[&str] = &["crabigator-no-such-clipboard"];
+        let failing: &[&str] = &["false"];
+        let working: &[&str] = &["cat"];
+        assert!(ClipboardWriter::copy_with(&[missing, failing, working], "text").is_ok());
+
+        let err = ClipboardWriter::copy_with(&[missing, failing], "text").unwrap_err();
+        assert_eq!(err.to_string(), "No clipboard command worked (tried crabigator-no-such-clipboard, false)");
+    }
+}
@@ -0,0 +1,150 @@
+//! Process resource usage for the --profile debug output
+//!
+//! Reports crabigator's own RSS and that of the assistant CLI running in the
+//! PTY. Linux reads `/proc/<pid>/status`; macOS has no procfs, so it asks
+//! `ps` (which wraps the `sysctl` process table). Readings are cached for a
+//! second since they're polled from the draw path.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a reading stays fresh
+const CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// PID of the PTY child (0 until registered)
+static CHILD_PID: AtomicU32 = AtomicU32::new(0);
+
+static CACHE: Mutex<Option<(Instant, ProcessStats)>> = Mutex::new(None);
+
+/// Resident memory of crabigator and its assistant CLI
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProcessStats {
+    /// Our own resident set size (KB)
+    pub self_rss_kb: u64,
+    /// The PTY child's resident set size (KB), if it could be read
+    pub child_rss_kb: Option<u64>,
+}
+
+impl ProcessStats {
+    /// Current usage, cached for one second
+    ///
+    /// Returns None on platforms (or sandboxes) where our own RSS can't be read.
+    pub fn current() -> Option<Self> {
+        let mut cache = CACHE.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some((at, stats)) = *cache {
+            if at.elapsed() < CACHE_TTL {
+                return Some(stats);
+            }
+        }
+
+        let child_pid = CHILD_PID.load(Ordering::Relaxed);
+        let stats = Self {
+            self_rss_kb: rss_kb(std::process::id())?,
+            child_rss_kb: (child_pid != 0).then(|| rss_kb(child_pid)).flatten(),
+        };
+        *cache = Some((Instant::now(), stats));
+        Some(stats)
+    }
+
+    /// One-line summary: `Mem: 24MB | Child: 312MB | Hooks: 3ms avg`
+    pub fn hud_line(&self, hook_avg_ms: Option<f64>) -> String {
+        let child = self
+            .child_rss_kb
+            .map(|kb| format!("{}MB", kb / 1024))
+            .unwrap_or_else(|| "-".to_string());
+        let hooks = hook_avg_ms
+            .map(|ms| format!("{:.0}ms avg", ms))
+            .unwrap_or_else(|| "-".to_string());
+        format!("Mem: {}MB | Child: {} | Hooks: {}", self.self_rss_kb / 1024, child, hooks)
+    }
+}
+
+/// Register the assistant CLI's PID so its memory is reported too
+pub fn track_child(pid: Option<u32>) {
+    CHILD_PID.store(pid.unwrap_or(0), Ordering::Relaxed);
+}
+
+#[cfg(target_os = "linux")]
+fn rss_kb(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    parse_proc_status_rss(&status)
+}
+
+#[cfg(target_os = "macos")]
+fn rss_kb(pid: u32) -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_ps_rss(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn rss_kb(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Parse the `VmRSS` line of `/proc/<pid>/status` (always reported in kB)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_status_rss(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|kb| kb.parse().ok())
+}
+
+/// Parse `ps -o rss=` output (KB, possibly padded)
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ps_rss(output: &str) -> Option<u64> {
+    output.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_status_rss() {
+        let status = "Name:\tcrabigator\nVmPeak:\t  512000 kB\nVmRSS:\t   24576 kB\nThreads:\t8\n";
+        assert_eq!(parse_proc_status_rss(status), Some(24576));
+        // Kernel threads have no VmRSS line
+        assert_eq!(parse_proc_status_rss("Name:\tkthreadd\nState:\tS (sleeping)\n"), None);
+        assert_eq!(parse_proc_status_rss("VmRSS:\tgarbage kB\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ps_rss() {
+        assert_eq!(parse_ps_rss("  319488\n"), Some(319488));
+        // Exited process: ps prints nothing
+        assert_eq!(parse_ps_rss(""), None);
+    }
+
+    #[test]
+    fn test_hud_line() {
+        let stats = ProcessStats {
+            self_rss_kb: 24 * 1024 + 100,
+            child_rss_kb: Some(312 * 1024),
+        };
+        assert_eq!(stats.hud_line(Some(3.2)), "Mem: 24MB | Child: 312MB | Hooks: 3ms avg");
+
+        let no_child = ProcessStats {
+            child_rss_kb: None,
+            ..stats
+        };
+        assert_eq!(no_child.hud_line(None), "Mem: 24MB | Child: - | Hooks: -");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_current_reads_own_rss() {
+        let stats = ProcessStats::current().unwrap();
+        assert!(stats.self_rss_kb > 0);
+        // Second call within the TTL is served from the cache
+        assert_eq!(ProcessStats::current(), Some(stats));
+    }
+}
@@ -0,0 +1,155 @@
+//! Session names and tags
+//!
+//! Session IDs (`{pid}{timestamp}`) say nothing about what a session is for.
+//! `crabigator tag SESSION_ID --name "Refactor auth"` (or `--current` for the
+//! most recently active session) writes `tags.json` into the session
+//! directory. The mirror publishes the name as `project_name`, inspect shows
+//! it in place of the ID, and the history record keeps it after exit.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::platforms::atomic_write;
+
+/// Contents of `tags.json`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tags {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Reads and writes `tags.json` in a session directory
+pub struct TagStore;
+
+const TAGS_FILE: &str = "tags.json";
+
+impl TagStore {
+    /// Tags for the session in `session_dir`, if it has been tagged
+    pub fn read(session_dir: &Path) -> Option<Tags> {
+        let content = fs::read_to_string(session_dir.join(TAGS_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Replace the session's tags (atomically, so readers never see half a
+    /// file)
+    pub fn write(session_dir: &Path, tags: Tags) -> Result<()> {
+        let path = session_dir.join(TAGS_FILE);
+        atomic_write(&path, &serde_json::to_string_pretty(&tags)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Session directory for `session_id`, refusing IDs that would point
+/// outside `/tmp/crabigator-*`
+pub fn session_dir(session_id: &str) -> Result<PathBuf> {
+    if session_id.is_empty() || session_id.contains('/') || session_id.contains("..") {
+        bail!("Invalid session ID: {:?}", session_id);
+    }
+    Ok(PathBuf::from(format!("/tmp/crabigator-{}", session_id)))
+}
+
+/// The session whose mirror was published most recently
+fn latest_session(instances: &[(PathBuf, serde_json::Value)]) -> Option<String> {
+    crate::inspect::latest_instance(instances)
+        .and_then(|data| data.get("session_id").and_then(|v| v.as_str()).map(String::from))
+}
+
+/// Merge `name` and `tags` into the session's existing tags
+fn apply(existing: Option<Tags>, name: Option<String>, tags: &[String]) -> Tags {
+    let mut merged = existing.unwrap_or_default();
+    if name.is_some() {
+        merged.name = name;
+    }
+    for tag in tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+    merged
+}
+
+/// Name or tag a session (`current` picks the most recently active one)
+pub fn run_tag(session_id: Option<String>, current: bool, name: Option<String>, tags: Vec<String>) -> Result<()> {
+    let session_id = match session_id {
+        Some(id) if !current => id,
+        _ => match latest_session(&crate::inspect::discover_instances(&None)?) {
+            Some(id) => id,
+            None => bail!("No running crabigator sessions found"),
+        },
+    };
+    let dir = session_dir(&session_id)?;
+    if !dir.is_dir() {
+        bail!("No session directory at {}", dir.display());
+    }
+
+    let merged = apply(TagStore::read(&dir), name, &tags);
+    TagStore::write(&dir, merged.clone())?;
+    match &merged.name {
+        Some(name) => println!("Session {} is now \"{}\"", session_id, name),
+        None => println!("Tagged session {}", session_id),
+    }
+    if !merged.tags.is_empty() {
+        println!("Tags: {}", merged.tags.join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tags_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(TagStore::read(dir.path()), None);
+
+        let tags = Tags {
+            name: Some("Refactor auth module".to_string()),
+            tags: vec!["backend".to_string()],
+        };
+        TagStore::write(dir.path(), tags.clone()).unwrap();
+        assert_eq!(TagStore::read(dir.path()), Some(tags));
+        // Only tags.json is left behind
+        let files: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(files, ["tags.json"]);
+
+        fs::write(dir.path().join("tags.json"), "{\"name\": ").unwrap();
+        assert_eq!(TagStore::read(dir.path()), None);
+    }
+
+    #[test]
+    fn test_session_dir_rejects_paths() {
+        assert_eq!(session_dir("123abc").unwrap(), PathBuf::from("/tmp/crabigator-123abc"));
+        assert!(session_dir("../etc").is_err());
+        assert!(session_dir("x/../../etc").is_err());
+        assert!(session_dir("").is_err());
+    }
+
+    #[test]
+    fn test_apply_merges() {
+        let existing = Tags {
+            name: Some("Old".to_string()),
+            tags: vec!["a".to_string()],
+        };
+        let merged = apply(Some(existing.clone()), None, &["a".to_string(), "b".to_string()]);
+        assert_eq!(merged.name.as_deref(), Some("Old"));
+        assert_eq!(merged.tags, ["a", "b"]);
+        assert_eq!(apply(Some(existing), Some("New".to_string()), &[]).name.as_deref(), Some("New"));
+    }
+
+    #[test]
+    fn test_latest_session() {
+        let instance = |id: &str, updated: f64| {
+            (PathBuf::new(), json!({ "session_id": id, "last_updated": updated }))
+        };
+        let instances = [instance("old", 100.0), instance("new", 300.0), instance("mid", 200.0)];
+        assert_eq!(latest_session(&instances).as_deref(), Some("new"));
+        assert_eq!(latest_session(&[]), None);
+    }
+}
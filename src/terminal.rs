@@ -2,18 +2,37 @@
 //!
 //! Groups all terminal-related functionality:
 //! - ANSI escape sequences
+//! - Color depth detection (true color, 256, 16)
 //! - DSR (Device Status Report) handling
+//! - Focus reporting
 //! - Input encoding
+//! - Kitty graphics protocol (inline images)
+//! - DEC private mode scanning (mouse tracking, focus reporting)
+//! - Mouse event forwarding
 //! - OSC (Operating System Command) scanning
-//! - PTY management
+//! - PTY management (plus a scripted mock PTY for tests)
+//! - SIGWINCH / SIGHUP handling outside crossterm's event stream
 
+pub mod color_detect;
 pub mod dsr;
 pub mod escape;
+pub mod focus;
 pub mod input;
+pub mod kitty;
+#[cfg(test)]
+pub mod mock;
+pub mod modes;
+pub mod mouse;
 pub mod osc;
 pub mod pty;
+pub mod signals;
+pub mod theme;
 
 pub use dsr::{DsrChunk, DsrHandler};
+pub use focus::{encode_focus, FocusScanner};
 pub use input::forward_key_to_pty;
+pub use kitty::KittyGraphicsBackend;
+pub use mouse::{encode_mouse_event, MouseModeTracker};
 pub use osc::OscScanner;
-pub use pty::PlatformPty;
+pub use pty::{PlatformPty, PtyBackend, PtyExit};
+pub use signals::{exit_if_hung_up, SignalWatcher, SIGNALS};
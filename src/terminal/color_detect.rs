@@ -0,0 +1,219 @@
+//! Terminal color depth detection
+//!
+//! Widget colors are picked from the 256-color palette. Terminals that
+//! advertise 24-bit color (`COLORTERM=truecolor` or `24bit`) get the same
+//! palette entries as RGB, which also lets the throbber fade through a
+//! gradient; bare 16-color consoles get the nearest basic ANSI color. The
+//! mode is detected once at startup and drawn through a
+//! `theme::Palette`; the default, used in tests, is 256-color.
+
+use super::escape::color::{fg256, fg_rgb};
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit `ESC [ 38;2;R;G;B m`
+    TrueColor,
+    /// 256-color palette `ESC [ 38;5;N m`
+    #[default]
+    Color256,
+    /// Basic ANSI colors `ESC [ 30-37/90-97 m`
+    Color16,
+}
+
+/// TERM values for consoles limited to the 16 basic colors
+const BASIC_TERMS: [&str; 6] = ["linux", "vt100", "vt220", "ansi", "cons25", "xterm-color"];
+
+impl ColorMode {
+    /// Detect the color depth from `COLORTERM` and `TERM`
+    pub fn detect() -> Self {
+        Self::detect_from(|key| std::env::var(key).ok())
+    }
+
+    fn detect_from(env: impl Fn(&str) -> Option<String>) -> Self {
+        let colorterm = env("COLORTERM").unwrap_or_default().to_ascii_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+        let term = env("TERM").unwrap_or_default();
+        if BASIC_TERMS.contains(&term.as_str()) {
+            Self::Color16
+        } else {
+            Self::Color256
+        }
+    }
+
+    /// Foreground escape for a 256-color palette entry
+    pub fn fg(self, color: u8) -> String {
+        match self {
+            Self::TrueColor => {
+                let (r, g, b) = palette_rgb(color);
+                fg_rgb(r, g, b)
+            }
+            Self::Color256 => fg256(color),
+            Self::Color16 => format!("\x1b[{}m", basic_sgr(color, 30)),
+        }
+    }
+
+    /// Background escape for a 256-color palette entry
+    pub fn bg(self, color: u8) -> String {
+        match self {
+            Self::TrueColor => {
+                let (r, g, b) = palette_rgb(color);
+                format!("\x1b[48;2;{};{};{}m", r, g, b)
+            }
+            Self::Color256 => format!("\x1b[48;5;{}m", color),
+            Self::Color16 => format!("\x1b[{}m", basic_sgr(color, 40)),
+        }
+    }
+}
+
+/// xterm's RGB values for the 16 basic colors
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels of the 6×6×6 color cube (palette 16-231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB value xterm uses for a 256-color palette entry
+pub fn palette_rgb(color: u8) -> (u8, u8, u8) {
+    match color {
+        0..=15 => BASIC_RGB[color as usize],
+        16..=231 => {
+            let idx = color - 16;
+            (
+                CUBE_LEVELS[(idx / 36) as usize],
+                CUBE_LEVELS[(idx / 6 % 6) as usize],
+                CUBE_LEVELS[(idx % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (color - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Palette entry (color cube or gray ramp) nearest an RGB color
+pub fn nearest_palette(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (i32::from(**level) - i32::from(c)).abs())
+            .map(|(idx, _)| idx as u8)
+            .unwrap_or(0)
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let average = ((u16::from(r) + u16::from(g) + u16::from(b)) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    let distance = |color: u8| {
+        let (pr, pg, pb) = palette_rgb(color);
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, pr) + d(g, pg) + d(b, pb)
+    };
+    if distance(gray) < distance(cube) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// SGR parameter for the basic color nearest a palette entry
+///
+/// `base` is 30 (foreground) or 40 (background); bright colors are 60 higher.
+fn basic_sgr(color: u8, base: u8) -> u8 {
+    match basic_index(color) {
+        idx @ 0..=7 => base + idx,
+        idx => base + 60 + idx - 8,
+    }
+}
+
+/// Nearest of the 16 basic colors to a palette entry (0-7 normal, 8-15 bright)
+fn basic_index(color: u8) -> u8 {
+    if color < 16 {
+        return color;
+    }
+    let (r, g, b) = palette_rgb(color);
+    let distance = |&(br, bg, bb): &(u8, u8, u8)| {
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, br) + d(g, bg) + d(b, bb)
+    };
+    BASIC_RGB
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, rgb)| distance(rgb))
+        .map(|(idx, _)| idx as u8)
+        .unwrap_or(7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |key: &str| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(ColorMode::detect_from(env(&[("COLORTERM", "truecolor")])), ColorMode::TrueColor);
+        assert_eq!(
+            ColorMode::detect_from(env(&[("COLORTERM", "24bit"), ("TERM", "linux")])),
+            ColorMode::TrueColor
+        );
+        assert_eq!(ColorMode::detect_from(env(&[("TERM", "xterm-256color")])), ColorMode::Color256);
+        assert_eq!(ColorMode::detect_from(env(&[("TERM", "linux")])), ColorMode::Color16);
+        // Unknown terminals keep the 256-color palette we've always used
+        assert_eq!(ColorMode::detect_from(env(&[])), ColorMode::Color256);
+    }
+
+    #[test]
+    fn test_truecolor_escapes() {
+        // 83 is the cube's (1, 5, 1) -> (95, 255, 95)
+        assert_eq!(ColorMode::TrueColor.fg(83), "\x1b[38;2;95;255;95m");
+        assert_eq!(ColorMode::TrueColor.bg(236), "\x1b[48;2;48;48;48m");
+        assert_eq!(ColorMode::TrueColor.fg(9), "\x1b[38;2;255;0;0m");
+    }
+
+    #[test]
+    fn test_256_color_escapes() {
+        assert_eq!(ColorMode::Color256.fg(83), "\x1b[38;5;83m");
+        assert_eq!(ColorMode::Color256.bg(236), "\x1b[48;5;236m");
+    }
+
+    #[test]
+    fn test_16_color_escapes() {
+        // Pure green, dark gray background, salmon red
+        assert_eq!(ColorMode::Color16.fg(46), "\x1b[92m");
+        assert_eq!(ColorMode::Color16.bg(236), "\x1b[40m");
+        assert_eq!(ColorMode::Color16.fg(203), "\x1b[91m");
+        // Basic entries map to themselves
+        assert_eq!(ColorMode::Color16.fg(2), "\x1b[32m");
+        assert_eq!(ColorMode::Color16.fg(15), "\x1b[97m");
+    }
+
+    #[test]
+    fn test_palette_rgb() {
+        assert_eq!(palette_rgb(16), (0, 0, 0));
+        assert_eq!(palette_rgb(231), (255, 255, 255));
+        assert_eq!(palette_rgb(232), (8, 8, 8));
+        assert_eq!(palette_rgb(255), (238, 238, 238));
+    }
+}
@@ -1,7 +1,27 @@
 //! DSR (Device Status Report) handler
 //!
 //! Handles terminal DSR responses for CLIs that request cursor position.
-//! Parses ESC [ 6 n and ESC [ ? 6 n sequences.
+//! Parses ESC [ 6 n and ESC [ ? 6 n sequences. Also follows DECCKM
+//! (ESC [ ? 1 h / ESC [ ? 1 l), which changes how cursor keys are encoded,
+//! and bracketed paste (ESC [ ? 2004 h / l).
+
+use super::modes::DecModeScanner;
+
+/// DECCKM private mode number
+const CURSOR_KEYS_MODE: u16 = 1;
+
+/// Bracketed paste private mode number
+const BRACKETED_PASTE_MODE: u16 = 2004;
+
+/// Cursor key encoding the child asked for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorMode {
+    /// Arrows as `CSI A`..`CSI D`
+    #[default]
+    Normal,
+    /// Arrows as `SS3 A`..`SS3 D` (DECCKM set)
+    Application,
+}
 
 /// Parse state for DSR sequence detection
 #[derive(Clone, Copy, Debug)]
@@ -29,6 +49,9 @@ pub enum DsrChunk {
 pub struct DsrHandler {
     state: DsrParseState,
     pending: Vec<u8>,
+    modes: DecModeScanner,
+    cursor_mode: CursorMode,
+    bracketed_paste: bool,
 }
 
 impl DsrHandler {
@@ -36,9 +59,22 @@ impl DsrHandler {
         Self {
             state: DsrParseState::Idle,
             pending: Vec::new(),
+            modes: DecModeScanner::new(),
+            cursor_mode: CursorMode::Normal,
+            bracketed_paste: false,
         }
     }
 
+    /// Cursor key encoding last selected by the child (DECCKM)
+    pub fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    /// Whether the child wants pasted text wrapped in `CSI 200~`/`CSI 201~`
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
     fn reset_with_byte(&mut self, current: &mut Vec<u8>, byte: u8) {
         if !self.pending.is_empty() {
             current.extend_from_slice(&self.pending);
@@ -56,6 +92,15 @@ impl DsrHandler {
     /// Scan data for DSR sequences.
     /// Returns chunks of output and DSR requests.
     pub fn scan(&mut self, data: &[u8]) -> Vec<DsrChunk> {
+        let (mut cursor_mode, mut bracketed_paste) = (self.cursor_mode, self.bracketed_paste);
+        self.modes.scan(data, |mode, set| match mode {
+            CURSOR_KEYS_MODE => cursor_mode = if set { CursorMode::Application } else { CursorMode::Normal },
+            BRACKETED_PASTE_MODE => bracketed_paste = set,
+            _ => {}
+        });
+        self.cursor_mode = cursor_mode;
+        self.bracketed_paste = bracketed_paste;
+
         let mut chunks = Vec::new();
         let mut current = Vec::new();
         for &byte in data {
@@ -50,40 +50,21 @@ pub const DIM: &str = "\x1b[2m";
 /// Reset all text attributes
 pub const RESET: &str = "\x1b[0m";
 
-// === Color Helpers ===
-
-/// Set foreground color using 256-color palette
-#[inline]
-pub fn fg(color: u8) -> String {
-    format!("\x1b[38;5;{}m", color)
-}
-
-/// Set background color using 256-color palette
-#[inline]
-pub fn bg(color: u8) -> String {
-    format!("\x1b[48;5;{}m", color)
-}
-
-// === Precomputed Foreground Colors (256-color palette) ===
-
-/// Cyan foreground (45)
-pub const FG_CYAN: &str = "\x1b[38;5;45m";
-
-/// Blue foreground (33)
-pub const FG_BLUE: &str = "\x1b[38;5;33m";
-
-/// Purple foreground (141)
-pub const FG_PURPLE: &str = "\x1b[38;5;141m";
-
-/// Orange foreground (179)
-pub const FG_ORANGE: &str = "\x1b[38;5;179m";
-
-/// Gray foreground (245)
-pub const FG_GRAY: &str = "\x1b[38;5;245m";
-
 // === Named Colors (256-color palette) ===
 
 pub mod color {
+    /// Foreground from the 256-color palette, whatever the color mode
+    #[inline]
+    pub fn fg256(color: u8) -> String {
+        format!("\x1b[38;5;{}m", color)
+    }
+
+    /// 24-bit foreground; only for true color terminals
+    #[inline]
+    pub fn fg_rgb(r: u8, g: u8, b: u8) -> String {
+        format!("\x1b[38;2;{};{};{}m", r, g, b)
+    }
+
     /// Green (83) - Success, clean status, additions, active
     pub const GREEN: u8 = 83;
 
@@ -110,6 +91,9 @@ pub mod color {
     /// Cyan (45) - Untracked files, folder bars
     pub const CYAN: u8 = 45;
 
+    /// Light cyan (123) - Test file changes
+    pub const LIGHT_CYAN: u8 = 123;
+
     /// Blue (39) - Session time, function
     pub const BLUE: u8 = 39;
 
@@ -152,6 +136,9 @@ pub mod ansi {
 
     /// Yellow foreground (33)
     pub const YELLOW: &str = "\x1b[33m";
+
+    /// Red foreground (31)
+    pub const RED: &str = "\x1b[31m";
 }
 
 // === OSC 8 Hyperlinks ===
@@ -165,6 +152,27 @@ pub fn hyperlink(url: &str, text: &str) -> String {
     format!("\x1b]8;;{}\x07{}\x1b]8;;\x07", url, text)
 }
 
+// === Window Title ===
+
+/// Set the window/tab title (OSC 2)
+///
+/// Control characters are dropped so a title can't end the sequence early.
+pub fn set_terminal_title(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]2;{}\x07", title)
+}
+
+// === Desktop Notifications ===
+
+/// Ask the terminal to post a desktop notification (OSC 777, Kitty/WezTerm)
+///
+/// The title ends at the first `;`, so any in it become `,`; control
+/// characters are dropped from both parts.
+pub fn osc777_notify(title: &str, body: &str) -> String {
+    let clean = |s: &str| s.chars().filter(|c| !c.is_control()).collect::<String>();
+    format!("\x1b]777;notify;{};{}\x07", clean(title).replace(';', ","), clean(body))
+}
+
 // === Screen Control ===
 
 /// Clear entire screen
@@ -213,6 +221,22 @@ pub mod key {
     /// Option+Delete - delete word forward (ESC d)
     pub const ALT_DELETE: [u8; 2] = [ESC, b'd'];
 
+    // === Focus Reports (mode 1004) ===
+
+    /// Window gained focus (CSI I)
+    pub const FOCUS_IN: [u8; 3] = [ESC, b'[', b'I'];
+
+    /// Window lost focus (CSI O)
+    pub const FOCUS_OUT: [u8; 3] = [ESC, b'[', b'O'];
+
+    // === Bracketed Paste (mode 2004) ===
+
+    /// Start of pasted text (CSI 200 ~)
+    pub const PASTE_START: [u8; 6] = [ESC, b'[', b'2', b'0', b'0', b'~'];
+
+    /// End of pasted text (CSI 201 ~)
+    pub const PASTE_END: [u8; 6] = [ESC, b'[', b'2', b'0', b'1', b'~'];
+
     // === Arrow Keys ===
 
     /// Arrow key without modifiers (CSI direction)
@@ -222,6 +246,12 @@ pub mod key {
         vec![ESC, b'[', direction]
     }
 
+    /// Arrow key in application cursor mode, DECCKM (SS3 direction)
+    #[inline]
+    pub fn arrow_application(direction: u8) -> Vec<u8> {
+        vec![ESC, b'O', direction]
+    }
+
     /// Arrow key with modifiers (CSI 1 ; modifier direction)
     #[inline]
     pub fn arrow_modified(direction: u8, modifier: u8) -> Vec<u8> {
@@ -237,6 +267,12 @@ pub mod key {
         vec![ESC, b'[', key]
     }
 
+    /// Home or End key in application cursor mode (SS3 H or SS3 F)
+    #[inline]
+    pub fn home_end_application(key: u8) -> Vec<u8> {
+        vec![ESC, b'O', key]
+    }
+
     /// Home or End key with modifiers (CSI 1 ; modifier key)
     #[inline]
     pub fn home_end_modified(key: u8, modifier: u8) -> Vec<u8> {
@@ -314,3 +350,27 @@ pub mod key {
         format!("\x1b[9;{}~", modifier).into_bytes()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_terminal_title() {
+        assert_eq!(set_terminal_title("✓ crabigator - main"), "\x1b]2;✓ crabigator - main\x07");
+        assert_eq!(set_terminal_title(""), "\x1b]2;\x07");
+        // A stray BEL or ESC would terminate the sequence early
+        assert_eq!(set_terminal_title("evil\x07\x1b[2Jname"), "\x1b]2;evil[2Jname\x07");
+    }
+
+    #[test]
+    fn test_osc777_notify() {
+        assert_eq!(
+            osc777_notify("Crabigator", "Task complete"),
+            "\x1b]777;notify;Crabigator;Task complete\x07"
+        );
+        // Semicolons are only safe in the body
+        assert_eq!(osc777_notify("a;b", "c;d"), "\x1b]777;notify;a,b;c;d\x07");
+        assert_eq!(osc777_notify("t\x07", "b\x1b\\"), "\x1b]777;notify;t;b\\\x07");
+    }
+}
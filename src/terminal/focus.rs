@@ -0,0 +1,83 @@
+//! Focus reporting
+//!
+//! Crabigator turns on focus events for itself (to poll less while in the
+//! background). Programs in the PTY that want them too enable mode 1004;
+//! `FocusScanner` watches PTY output for that, and only then are focus
+//! changes passed on as `CSI I` / `CSI O`.
+
+use super::escape::key;
+use super::modes::DecModeScanner;
+
+/// xterm focus report mode
+const FOCUS_MODE: u16 = 1004;
+
+/// Tracks whether the child asked for focus reports
+pub struct FocusScanner {
+    scanner: DecModeScanner,
+    enabled: bool,
+}
+
+impl FocusScanner {
+    pub fn new() -> Self {
+        Self {
+            scanner: DecModeScanner::new(),
+            enabled: false,
+        }
+    }
+
+    /// Whether focus changes should be forwarded
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Update from a chunk of PTY output
+    pub fn scan(&mut self, data: &[u8]) {
+        let mut enabled = self.enabled;
+        self.scanner.scan(data, |mode, set| {
+            if mode == FOCUS_MODE {
+                enabled = set;
+            }
+        });
+        self.enabled = enabled;
+    }
+}
+
+impl Default for FocusScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Focus report for the PTY
+pub fn encode_focus(gained: bool) -> &'static [u8] {
+    if gained {
+        &key::FOCUS_IN
+    } else {
+        &key::FOCUS_OUT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_sequences() {
+        assert_eq!(encode_focus(true), b"\x1b[I");
+        assert_eq!(encode_focus(false), b"\x1b[O");
+    }
+
+    #[test]
+    fn test_scanner_follows_mode_1004() {
+        let mut scanner = FocusScanner::new();
+        assert!(!scanner.is_enabled());
+
+        scanner.scan(b"prompt> \x1b[?2004h\x1b[?1004h");
+        assert!(scanner.is_enabled());
+        scanner.scan(b"\x1b[?1000l");
+        assert!(scanner.is_enabled());
+        scanner.scan(b"\x1b[?10");
+        scanner.scan(b"04l");
+        assert!(!scanner.is_enabled());
+    }
+}
@@ -6,12 +6,16 @@
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+use super::dsr::CursorMode;
 use super::escape::key;
-use super::pty::PlatformPty;
+use super::pty::PtyBackend;
 
 /// Forward a key event to the PTY with proper encoding
-pub fn forward_key_to_pty(key: KeyEvent, pty: &mut PlatformPty) -> Result<()> {
-    let bytes = encode_key(key);
+///
+/// `cursor_mode` is the child's DECCKM setting (`DsrHandler::cursor_mode`);
+/// unmodified arrows and Home/End are sent as SS3 in application mode.
+pub fn forward_key_to_pty(key: KeyEvent, cursor_mode: CursorMode, pty: &mut dyn PtyBackend) -> Result<()> {
+    let bytes = encode_key(key, cursor_mode);
     if !bytes.is_empty() {
         pty.write(&bytes)?;
     }
@@ -19,7 +23,7 @@ pub fn forward_key_to_pty(key: KeyEvent, pty: &mut PlatformPty) -> Result<()> {
 }
 
 /// Encode a key event into bytes for the PTY
-fn encode_key(key: KeyEvent) -> Vec<u8> {
+fn encode_key(key: KeyEvent, cursor_mode: CursorMode) -> Vec<u8> {
     let has_shift = key.modifiers.contains(KeyModifiers::SHIFT);
     let has_alt = key.modifiers.contains(KeyModifiers::ALT);
     let has_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
@@ -39,12 +43,12 @@ fn encode_key(key: KeyEvent) -> Vec<u8> {
         KeyCode::Tab => encode_tab(has_shift, has_ctrl, modifier_code),
         KeyCode::BackTab => key::BACK_TAB.to_vec(),
         KeyCode::Esc => vec![key::ESC],
-        KeyCode::Up => encode_arrow(b'A', has_modifiers, modifier_code),
-        KeyCode::Down => encode_arrow(b'B', has_modifiers, modifier_code),
-        KeyCode::Right => encode_arrow(b'C', has_modifiers, modifier_code),
-        KeyCode::Left => encode_arrow(b'D', has_modifiers, modifier_code),
-        KeyCode::Home => encode_home_end(b'H', has_modifiers, modifier_code),
-        KeyCode::End => encode_home_end(b'F', has_modifiers, modifier_code),
+        KeyCode::Up => encode_arrow(b'A', has_modifiers, modifier_code, cursor_mode),
+        KeyCode::Down => encode_arrow(b'B', has_modifiers, modifier_code, cursor_mode),
+        KeyCode::Right => encode_arrow(b'C', has_modifiers, modifier_code, cursor_mode),
+        KeyCode::Left => encode_arrow(b'D', has_modifiers, modifier_code, cursor_mode),
+        KeyCode::Home => encode_home_end(b'H', has_modifiers, modifier_code, cursor_mode),
+        KeyCode::End => encode_home_end(b'F', has_modifiers, modifier_code, cursor_mode),
         KeyCode::PageUp => encode_page(5, has_modifiers, modifier_code),
         KeyCode::PageDown => encode_page(6, has_modifiers, modifier_code),
         KeyCode::Delete => encode_delete(has_alt, has_ctrl, has_shift, has_modifiers, modifier_code),
@@ -97,17 +101,21 @@ fn encode_tab(has_shift: bool, has_ctrl: bool, modifier_code: u8) -> Vec<u8> {
     }
 }
 
-fn encode_arrow(direction: u8, has_modifiers: bool, modifier_code: u8) -> Vec<u8> {
+fn encode_arrow(direction: u8, has_modifiers: bool, modifier_code: u8, cursor_mode: CursorMode) -> Vec<u8> {
     if has_modifiers {
         key::arrow_modified(direction, modifier_code)
+    } else if cursor_mode == CursorMode::Application {
+        key::arrow_application(direction)
     } else {
         key::arrow(direction)
     }
 }
 
-fn encode_home_end(key_byte: u8, has_modifiers: bool, modifier_code: u8) -> Vec<u8> {
+fn encode_home_end(key_byte: u8, has_modifiers: bool, modifier_code: u8, cursor_mode: CursorMode) -> Vec<u8> {
     if has_modifiers {
         key::home_end_modified(key_byte, modifier_code)
+    } else if cursor_mode == CursorMode::Application {
+        key::home_end_application(key_byte)
     } else {
         key::home_end(key_byte)
     }
@@ -189,3 +197,52 @@ fn encode_function_key(n: u8, has_modifiers: bool, modifier_code: u8) -> Vec<u8>
         key::f5_f12(base_code)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::DsrHandler;
+
+    fn encode(code: KeyCode, modifiers: KeyModifiers, cursor_mode: CursorMode) -> Vec<u8> {
+        encode_key(KeyEvent::new(code, modifiers), cursor_mode)
+    }
+
+    #[test]
+    fn test_application_mode_arrows() {
+        let app = CursorMode::Application;
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::NONE, app), b"\x1bOA");
+        assert_eq!(encode(KeyCode::Down, KeyModifiers::NONE, app), b"\x1bOB");
+        assert_eq!(encode(KeyCode::Right, KeyModifiers::NONE, app), b"\x1bOC");
+        assert_eq!(encode(KeyCode::Left, KeyModifiers::NONE, app), b"\x1bOD");
+        assert_eq!(encode(KeyCode::Home, KeyModifiers::NONE, app), b"\x1bOH");
+        assert_eq!(encode(KeyCode::End, KeyModifiers::NONE, app), b"\x1bOF");
+        // Modified arrows keep the CSI form in either mode
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::SHIFT, app), b"\x1b[1;2A");
+    }
+
+    #[test]
+    fn test_normal_mode_arrows() {
+        let normal = CursorMode::Normal;
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::NONE, normal), b"\x1b[A");
+        assert_eq!(encode(KeyCode::Left, KeyModifiers::NONE, normal), b"\x1b[D");
+        assert_eq!(encode(KeyCode::Home, KeyModifiers::NONE, normal), b"\x1b[H");
+    }
+
+    #[test]
+    fn test_decckm_from_pty_output() {
+        let mut dsr = DsrHandler::new();
+        assert_eq!(dsr.cursor_mode(), CursorMode::Normal);
+
+        // Split across reads, with a DSR request in the same output
+        dsr.scan(b"menu\x1b[?");
+        dsr.scan(b"1h\x1b[6n");
+        assert_eq!(dsr.cursor_mode(), CursorMode::Application);
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::NONE, dsr.cursor_mode()), b"\x1bOA");
+
+        dsr.scan(b"\x1b[?1l");
+        assert_eq!(dsr.cursor_mode(), CursorMode::Normal);
+        // Other private modes leave it alone
+        dsr.scan(b"\x1b[?1000h\x1b[?25l");
+        assert_eq!(dsr.cursor_mode(), CursorMode::Normal);
+    }
+}
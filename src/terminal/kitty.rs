@@ -0,0 +1,201 @@
+//! Kitty graphics protocol
+//!
+//! Kitty (and terminals that speak its protocol) can draw PNG images inline
+//! via `ESC _ G ... ESC \` sequences. We use it to draw the platform logo at
+//! the left end of the separator above the status widgets. The image stays on
+//! screen until deleted, so it's uploaded once and only redrawn after a
+//! resize. Built only with the `kitty-graphics` feature; otherwise the
+//! backend is never active.
+
+use std::io::Write;
+
+use anyhow::Result;
+use base64::Engine;
+
+use super::escape;
+
+/// Largest base64 payload allowed in one escape sequence
+const CHUNK_SIZE: usize = 4096;
+
+/// Image id for the logo, so it can be replaced and deleted by id
+const LOGO_IMAGE_ID: u32 = 7;
+
+/// Draws images when running inside Kitty
+pub struct KittyGraphicsBackend {
+    active: bool,
+    drawn: bool,
+}
+
+impl KittyGraphicsBackend {
+    /// Detect Kitty from the environment
+    pub fn new() -> Self {
+        #[cfg(feature = "kitty-graphics")]
+        let active = is_kitty(|key| std::env::var(key).ok());
+        #[cfg(not(feature = "kitty-graphics"))]
+        let active = false;
+
+        Self { active, drawn: false }
+    }
+
+    /// A backend that never draws
+    #[cfg(test)]
+    pub fn inactive() -> Self {
+        Self {
+            active: false,
+            drawn: false,
+        }
+    }
+
+    /// Draw the logo two cells wide at the start of `row`, unless it is
+    /// already on screen
+    pub fn draw_logo_once(&mut self, stdout: &mut impl Write, image_bytes: &[u8], row: u16) -> Result<()> {
+        if !self.active || self.drawn {
+            return Ok(());
+        }
+        write!(stdout, "{}{}", escape::CURSOR_SAVE, escape::cursor_to(row, 1))?;
+        display_image(stdout, image_bytes, 1, 2)?;
+        write!(stdout, "{}", escape::CURSOR_RESTORE)?;
+        stdout.flush()?;
+        self.drawn = true;
+        Ok(())
+    }
+
+    /// Remove the logo; the next draw uploads it again
+    pub fn clear(&mut self, stdout: &mut impl Write) -> Result<()> {
+        if self.active && self.drawn {
+            write!(stdout, "{}", delete_image(LOGO_IMAGE_ID))?;
+            stdout.flush()?;
+        }
+        self.drawn = false;
+        Ok(())
+    }
+}
+
+impl Default for KittyGraphicsBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Kitty sets `TERM=xterm-kitty` and `KITTY_WINDOW_ID`; tmux swallows the
+/// graphics sequences, so it never counts
+#[cfg_attr(not(feature = "kitty-graphics"), allow(dead_code))]
+fn is_kitty(env: impl Fn(&str) -> Option<String>) -> bool {
+    if env("TMUX").is_some() {
+        return false;
+    }
+    env("TERM").as_deref() == Some("xterm-kitty") || env("KITTY_WINDOW_ID").is_some()
+}
+
+/// Upload a PNG and place it at the cursor, scaled to `rows` × `cols` cells
+///
+/// The cursor doesn't move and the terminal sends no reply, so nothing ends
+/// up on the child's stdin.
+pub fn display_image(stdout: &mut impl Write, image_bytes: &[u8], rows: u16, cols: u16) -> Result<()> {
+    write!(stdout, "{}", encode_image(image_bytes, LOGO_IMAGE_ID, rows, cols))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Chunked transmit-and-display sequence for a PNG
+///
+/// Control keys go on the first chunk only; every chunk carries `m=1`
+/// except the last.
+fn encode_image(image_bytes: &[u8], id: u32, rows: u16, cols: u16) -> String {
+    let payload = base64::engine::general_purpose::STANDARD.encode(image_bytes);
+    let chunks: Vec<&str> = if payload.is_empty() {
+        vec![""]
+    } else {
+        // base64 is ASCII, so byte offsets are char boundaries
+        payload.as_bytes().chunks(CHUNK_SIZE).map(|c| std::str::from_utf8(c).unwrap_or("")).collect()
+    };
+
+    let mut out = String::with_capacity(payload.len() + chunks.len() * 16 + 64);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Gf=100,a=T,t=d,i={},q=2,C=1,c={},r={},m={};{}\x1b\\",
+                id, cols, rows, more, chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+/// Delete an image and all its placements
+fn delete_image(id: u32) -> String {
+    format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_image_is_one_chunk() {
+        let encoded = encode_image(b"PNG", 7, 1, 2);
+        assert_eq!(encoded, "\x1b_Gf=100,a=T,t=d,i=7,q=2,C=1,c=2,r=1,m=0;UE5H\x1b\\");
+    }
+
+    #[test]
+    fn test_large_image_is_chunked() {
+        // 3 bytes -> 4 base64 chars, so this is 4096 + 4096 + 8 chars
+        let image = vec![0u8; (CHUNK_SIZE * 2 + 8) / 4 * 3];
+        let encoded = encode_image(&image, 7, 1, 2);
+        let sequences: Vec<&str> = encoded.split("\x1b\\").filter(|s| !s.is_empty()).collect();
+
+        assert_eq!(sequences.len(), 3);
+        assert!(sequences[0].starts_with("\x1b_Gf=100,a=T,t=d,i=7,q=2,C=1,c=2,r=1,m=1;"));
+        assert!(sequences[1].starts_with("\x1b_Gm=1;"));
+        assert!(sequences[2].starts_with("\x1b_Gm=0;"));
+
+        let payload: String = sequences.iter().map(|s| s.split_once(';').unwrap().1).collect();
+        assert_eq!(payload.len(), CHUNK_SIZE * 2 + 8);
+        assert_eq!(base64::engine::general_purpose::STANDARD.decode(payload).unwrap(), image);
+    }
+
+    #[test]
+    fn test_delete_sequence() {
+        assert_eq!(delete_image(7), "\x1b_Ga=d,d=I,i=7,q=2\x1b\\");
+    }
+
+    #[test]
+    fn test_kitty_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+        };
+        assert!(is_kitty(env(&[("TERM", "xterm-kitty")])));
+        assert!(is_kitty(env(&[("TERM", "xterm-256color"), ("KITTY_WINDOW_ID", "1")])));
+        assert!(!is_kitty(env(&[("TERM", "xterm-256color")])));
+        assert!(!is_kitty(env(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux-0/default,1,0")])));
+    }
+
+    #[test]
+    fn test_logo_drawn_once_until_cleared() {
+        let mut backend = KittyGraphicsBackend {
+            active: true,
+            drawn: false,
+        };
+        let mut out = Vec::new();
+        backend.draw_logo_once(&mut out, b"PNG", 20).unwrap();
+        let first = out.len();
+        assert!(first > 0);
+
+        backend.draw_logo_once(&mut out, b"PNG", 20).unwrap();
+        assert_eq!(out.len(), first);
+
+        assert!(String::from_utf8_lossy(&out).starts_with("\x1b[s\x1b[20;1H\x1b_G"));
+
+        backend.clear(&mut out).unwrap();
+        assert!(String::from_utf8_lossy(&out).ends_with(&delete_image(LOGO_IMAGE_ID)));
+        backend.draw_logo_once(&mut out, b"PNG", 20).unwrap();
+        assert!(out.len() > first * 2);
+
+        let mut out = Vec::new();
+        KittyGraphicsBackend::inactive().draw_logo_once(&mut out, b"PNG", 20).unwrap();
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Scripted PTY for driving the app loop in tests
+//!
+//! `MockPlatformPty` stands in for `PlatformPty`: instead of spawning a CLI it
+//! replays pre-scripted output frames and terminal events, and records what
+//! the app writes to it and how it gets resized.
+
+use anyhow::Result;
+use crossterm::event::{Event, KeyEvent};
+use futures_util::future::BoxFuture;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use super::pty::{PtyBackend, PtyExit};
+
+/// What the app did to the mock PTY
+#[derive(Debug, Default)]
+pub struct MockRecord {
+    /// Bytes written to the CLI (forwarded keys, pastes, DSR replies)
+    pub written: Vec<u8>,
+    /// Every `resize(cols, rows)` call, in order
+    pub resizes: Vec<(u16, u16)>,
+}
+
+pub struct MockPlatformPty {
+    frames: VecDeque<Vec<u8>>,
+    events: VecDeque<Event>,
+    output_tx: Option<mpsc::Sender<Vec<u8>>>,
+    parser: vt100::Parser,
+    record: Arc<Mutex<MockRecord>>,
+    /// How long to keep "running" once the script is exhausted
+    idle: Duration,
+    finished_at: Option<Instant>,
+    /// Reported by `exit_status` once the script is done
+    exit: PtyExit,
+}
+
+impl MockPlatformPty {
+    /// Script one output frame per poll, followed by the key events
+    pub fn new(frames: Vec<Vec<u8>>, keys: Vec<KeyEvent>) -> Self {
+        Self {
+            frames: frames.into(),
+            events: keys.into_iter().map(Event::Key).collect(),
+            output_tx: None,
+            parser: vt100::Parser::new(24, 80, 0),
+            record: Arc::default(),
+            idle: Duration::ZERO,
+            finished_at: None,
+            exit: PtyExit::Code(0),
+        }
+    }
+
+    /// Report `exit` as the CLI's exit status when it ends
+    pub fn with_exit(mut self, exit: PtyExit) -> Self {
+        self.exit = exit;
+        self
+    }
+
+    /// Append a non-key event (resize, paste, ...) to the script
+    pub fn with_event(mut self, event: Event) -> Self {
+        self.events.push_back(event);
+        self
+    }
+
+    /// Keep the CLI "alive" for `idle` after the last scripted step
+    pub fn with_idle(mut self, idle: Duration) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Attach the channel the app reads PTY output from
+    pub fn connect(&mut self, output_tx: mpsc::Sender<Vec<u8>>) {
+        self.output_tx = Some(output_tx);
+    }
+
+    /// Shared handle to the recorded writes and resizes
+    pub fn record(&self) -> Arc<Mutex<MockRecord>> {
+        self.record.clone()
+    }
+
+    fn script_done(&self) -> bool {
+        self.frames.is_empty() && self.events.is_empty()
+    }
+
+    /// Play the next step of the script
+    fn step(&mut self) -> Option<Event> {
+        let event = match self.frames.pop_front() {
+            Some(frame) => {
+                if let Some(tx) = &self.output_tx {
+                    let _ = tx.try_send(frame);
+                }
+                None
+            }
+            None => self.events.pop_front(),
+        };
+
+        if self.script_done() && self.finished_at.is_none() {
+            self.finished_at = Some(Instant::now());
+        }
+        event
+    }
+}
+
+impl PtyBackend for MockPlatformPty {
+    fn is_running(&self) -> bool {
+        !self.script_done() || self.finished_at.is_none_or(|at| at.elapsed() < self.idle)
+    }
+
+    fn process_output(&mut self, data: &[u8]) {
+        self.parser.process(data);
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.record.lock().unwrap().written.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+        self.record.lock().unwrap().resizes.push((cols, rows));
+        self.parser.set_size(rows, cols);
+        Ok(())
+    }
+
+    fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+
+    fn exit_status(&self) -> Option<PtyExit> {
+        (!self.is_running()).then(|| self.exit.clone())
+    }
+
+    /// Each poll waits out `timeout`, then emits one frame or one event
+    fn next_event(&mut self, timeout: Duration) -> BoxFuture<'_, Result<Option<Event>>> {
+        Box::pin(async move {
+            tokio::time::sleep(timeout).await;
+            Ok(self.step())
+        })
+    }
+}
+
+/// In-memory terminal the app can draw into
+#[derive(Clone, Default)]
+pub struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl SharedOutput {
+    /// Everything written so far, lossily decoded
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
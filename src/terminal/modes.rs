@@ -0,0 +1,102 @@
+//! DEC private mode scanner
+//!
+//! Follows `ESC [ ? Pn ; ... h` (set) and `ESC [ ? Pn ; ... l` (reset) in PTY
+//! output, so crabigator knows which input modes the child turned on (mouse
+//! tracking, focus reporting). Bytes are only observed, never altered.
+
+#[derive(Clone, Copy, Debug)]
+enum ScanState {
+    Idle,
+    Esc,
+    Csi,
+    Private,
+}
+
+/// Longest parameter list kept while scanning a private mode sequence
+const MAX_PARAMS_LEN: usize = 32;
+
+/// Incremental parser for DEC private mode changes
+///
+/// Sequences may be split across reads, so parse state carries over between
+/// calls to `scan`.
+pub struct DecModeScanner {
+    state: ScanState,
+    params: Vec<u8>,
+}
+
+impl DecModeScanner {
+    pub fn new() -> Self {
+        Self {
+            state: ScanState::Idle,
+            params: Vec::with_capacity(MAX_PARAMS_LEN),
+        }
+    }
+
+    /// Call `on_mode(mode, set)` for every private mode set or reset in `data`
+    pub fn scan(&mut self, data: &[u8], mut on_mode: impl FnMut(u16, bool)) {
+        for &byte in data {
+            self.state = match (self.state, byte) {
+                (_, 0x1b) => ScanState::Esc,
+                (ScanState::Esc, b'[') => ScanState::Csi,
+                (ScanState::Csi, b'?') => {
+                    self.params.clear();
+                    ScanState::Private
+                }
+                (ScanState::Private, b'0'..=b'9' | b';') if self.params.len() < MAX_PARAMS_LEN => {
+                    self.params.push(byte);
+                    ScanState::Private
+                }
+                (ScanState::Private, b'h' | b'l') => {
+                    let set = byte == b'h';
+                    for mode in self.params.split(|&b| b == b';') {
+                        if let Some(mode) = std::str::from_utf8(mode).ok().and_then(|m| m.parse().ok()) {
+                            on_mode(mode, set);
+                        }
+                    }
+                    ScanState::Idle
+                }
+                _ => ScanState::Idle,
+            };
+        }
+    }
+}
+
+impl Default for DecModeScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modes(chunks: &[&[u8]]) -> Vec<(u16, bool)> {
+        let mut scanner = DecModeScanner::new();
+        let mut seen = Vec::new();
+        for chunk in chunks {
+            scanner.scan(chunk, |mode, set| seen.push((mode, set)));
+        }
+        seen
+    }
+
+    #[test]
+    fn test_set_and_reset() {
+        assert_eq!(modes(&[b"a\x1b[?25lb\x1b[?1004h"]), vec![(25, false), (1004, true)]);
+        assert_eq!(modes(&[b"\x1b[?1000;1006h"]), vec![(1000, true), (1006, true)]);
+    }
+
+    #[test]
+    fn test_split_across_reads() {
+        assert_eq!(modes(&[b"\x1b", b"[?10", b"04l"]), vec![(1004, false)]);
+    }
+
+    #[test]
+    fn test_ignores_other_sequences() {
+        // Public modes (no `?`), SGR, and interrupted sequences
+        assert!(modes(&[b"\x1b[4h\x1b[1;31m\x1b[?10\x1b[0m"]).is_empty());
+        // Overlong parameter lists are dropped rather than buffered
+        let long = format!("\x1b[?{}h", "1;".repeat(40));
+        assert!(modes(&[long.as_bytes()]).is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+//! Mouse forwarding
+//!
+//! The real terminal sends mouse reports only once the CLI (or a TUI it runs)
+//! turns mouse tracking on, since that mode-set passes straight through to
+//! it. Crossterm decodes those reports into `MouseEvent`s, so they have to be
+//! re-encoded for the PTY in whichever encoding the child asked for.
+//! `MouseModeTracker` follows the DECSET/DECRST sequences in PTY output to
+//! know whether tracking is on and which encoding is active.
+
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+use super::modes::DecModeScanner;
+
+/// Wire format for mouse reports
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MouseEncoding {
+    /// X10 style `ESC [ M b x y`, one byte per value (coordinates up to 223)
+    #[default]
+    Normal,
+    /// Mode 1005: like `Normal`, but values are UTF-8 encoded (up to 2015)
+    Utf8,
+    /// Mode 1006: `ESC [ < b ; x ; y M` (press) or `m` (release)
+    Sgr,
+}
+
+/// Largest value `Normal` fits in one byte after the +32 offset
+const NORMAL_MAX: u32 = 255 - 32;
+
+/// Largest value `Utf8` fits in a two-byte UTF-8 sequence after the +32 offset
+const UTF8_MAX: u32 = 0x7ff - 32;
+
+/// Encode `event` as the terminal would report it to the child
+///
+/// Returns nothing when the coordinates are out of range for the encoding.
+pub fn encode_mouse_event(event: MouseEvent, encoding: MouseEncoding) -> Vec<u8> {
+    let (button, release) = button_code(event.kind);
+    let code = button | modifier_bits(event.modifiers);
+    let col = u32::from(event.column) + 1;
+    let row = u32::from(event.row) + 1;
+
+    match encoding {
+        MouseEncoding::Sgr => {
+            let final_byte = if release { 'm' } else { 'M' };
+            format!("\x1b[<{};{};{}{}", code, col, row, final_byte).into_bytes()
+        }
+        MouseEncoding::Normal | MouseEncoding::Utf8 => {
+            // Legacy encodings have no release per button, only "button 3"
+            let code = if release { 3 | (code & !0b11) } else { code };
+            let max = if encoding == MouseEncoding::Normal { NORMAL_MAX } else { UTF8_MAX };
+            if col > max || row > max {
+                return Vec::new();
+            }
+
+            let mut bytes = b"\x1b[M".to_vec();
+            for value in [code, col, row] {
+                let value = value + 32;
+                if encoding == MouseEncoding::Normal {
+                    bytes.push(value as u8);
+                } else {
+                    let mut buf = [0; 4];
+                    let c = char::from_u32(value).unwrap_or(' ');
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+            bytes
+        }
+    }
+}
+
+/// xterm button code, and whether this is a release
+fn button_code(kind: MouseEventKind) -> (u32, bool) {
+    let button = |b: MouseButton| match b {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+    match kind {
+        MouseEventKind::Down(b) => (button(b), false),
+        MouseEventKind::Up(b) => (button(b), true),
+        MouseEventKind::Drag(b) => (button(b) + 32, false),
+        MouseEventKind::Moved => (3 + 32, false),
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
+    }
+}
+
+fn modifier_bits(modifiers: KeyModifiers) -> u32 {
+    let mut bits = 0;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        bits |= 4;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        bits |= 8;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        bits |= 16;
+    }
+    bits
+}
+
+/// Follows mouse mode changes in PTY output
+pub struct MouseModeTracker {
+    scanner: DecModeScanner,
+    tracking: bool,
+    encoding: MouseEncoding,
+}
+
+impl MouseModeTracker {
+    pub fn new() -> Self {
+        Self {
+            scanner: DecModeScanner::new(),
+            tracking: false,
+            encoding: MouseEncoding::Normal,
+        }
+    }
+
+    /// Whether the child has mouse tracking on (modes 9, 1000, 1002, 1003)
+    pub fn is_tracking(&self) -> bool {
+        self.tracking
+    }
+
+    /// Encoding the child asked for
+    pub fn encoding(&self) -> MouseEncoding {
+        self.encoding
+    }
+
+    /// Update the mouse mode from a chunk of PTY output
+    pub fn scan(&mut self, data: &[u8]) {
+        let (mut tracking, mut encoding) = (self.tracking, self.encoding);
+        self.scanner.scan(data, |mode, set| match mode {
+            9 | 1000 | 1002 | 1003 => tracking = set,
+            1005 => set_encoding(&mut encoding, MouseEncoding::Utf8, set),
+            1006 => set_encoding(&mut encoding, MouseEncoding::Sgr, set),
+            _ => {}
+        });
+        (self.tracking, self.encoding) = (tracking, encoding);
+    }
+}
+
+fn set_encoding(current: &mut MouseEncoding, encoding: MouseEncoding, set: bool) {
+    if set {
+        *current = encoding;
+    } else if *current == encoding {
+        *current = MouseEncoding::Normal;
+    }
+}
+
+impl Default for MouseModeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const LEFT_DOWN: MouseEventKind = MouseEventKind::Down(MouseButton::Left);
+    const LEFT_UP: MouseEventKind = MouseEventKind::Up(MouseButton::Left);
+
+    #[test]
+    fn test_normal_encoding() {
+        // Crossterm columns/rows are 0-based; reports are 1-based
+        assert_eq!(encode_mouse_event(event(LEFT_DOWN, 0, 0), MouseEncoding::Normal), b"\x1b[M !!");
+        assert_eq!(encode_mouse_event(event(LEFT_UP, 0, 0), MouseEncoding::Normal), b"\x1b[M#!!");
+        assert_eq!(
+            encode_mouse_event(event(LEFT_DOWN, 222, 222), MouseEncoding::Normal),
+            b"\x1b[M \xff\xff"
+        );
+        // (255, 255) doesn't fit in a byte
+        assert!(encode_mouse_event(event(LEFT_DOWN, 254, 254), MouseEncoding::Normal).is_empty());
+    }
+
+    #[test]
+    fn test_utf8_encoding() {
+        assert_eq!(encode_mouse_event(event(LEFT_DOWN, 0, 0), MouseEncoding::Utf8), b"\x1b[M !!");
+        // 255 + 32 = U+011F
+        assert_eq!(
+            encode_mouse_event(event(LEFT_DOWN, 254, 254), MouseEncoding::Utf8),
+            "\x1b[M \u{11f}\u{11f}".as_bytes()
+        );
+        assert_eq!(
+            encode_mouse_event(event(LEFT_UP, 254, 254), MouseEncoding::Utf8),
+            "\x1b[M#\u{11f}\u{11f}".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_sgr_encoding() {
+        assert_eq!(encode_mouse_event(event(LEFT_DOWN, 0, 0), MouseEncoding::Sgr), b"\x1b[<0;1;1M");
+        assert_eq!(encode_mouse_event(event(LEFT_UP, 0, 0), MouseEncoding::Sgr), b"\x1b[<0;1;1m");
+        assert_eq!(
+            encode_mouse_event(event(LEFT_DOWN, 254, 254), MouseEncoding::Sgr),
+            b"\x1b[<0;255;255M"
+        );
+        assert_eq!(
+            encode_mouse_event(event(MouseEventKind::Up(MouseButton::Right), 254, 254), MouseEncoding::Sgr),
+            b"\x1b[<2;255;255m"
+        );
+    }
+
+    #[test]
+    fn test_buttons_and_modifiers() {
+        let mut scroll = event(MouseEventKind::ScrollUp, 4, 9);
+        assert_eq!(encode_mouse_event(scroll, MouseEncoding::Sgr), b"\x1b[<64;5;10M");
+        scroll.modifiers = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+        assert_eq!(encode_mouse_event(scroll, MouseEncoding::Sgr), b"\x1b[<84;5;10M");
+
+        let drag = event(MouseEventKind::Drag(MouseButton::Middle), 0, 0);
+        assert_eq!(encode_mouse_event(drag, MouseEncoding::Sgr), b"\x1b[<33;1;1M");
+        let moved = event(MouseEventKind::Moved, 0, 0);
+        assert_eq!(encode_mouse_event(moved, MouseEncoding::Normal), b"\x1b[MC!!");
+    }
+
+    #[test]
+    fn test_tracker_follows_mode_changes() {
+        let mut tracker = MouseModeTracker::new();
+        assert!(!tracker.is_tracking());
+
+        tracker.scan(b"hello\x1b[?1000h");
+        assert!(tracker.is_tracking());
+        assert_eq!(tracker.encoding(), MouseEncoding::Normal);
+
+        tracker.scan(b"\x1b[?1005h");
+        assert_eq!(tracker.encoding(), MouseEncoding::Utf8);
+
+        // Split across reads, combined parameters
+        tracker.scan(b"\x1b[?1002;10");
+        tracker.scan(b"06h");
+        assert_eq!(tracker.encoding(), MouseEncoding::Sgr);
+
+        // Resetting an encoding that isn't active leaves it alone
+        tracker.scan(b"\x1b[?1005l");
+        assert_eq!(tracker.encoding(), MouseEncoding::Sgr);
+        tracker.scan(b"\x1b[?1006l\x1b[?1002l");
+        assert_eq!(tracker.encoding(), MouseEncoding::Normal);
+        assert!(!tracker.is_tracking());
+
+        // Other private modes are ignored
+        tracker.scan(b"\x1b[?25h\x1b[?2004h");
+        assert!(!tracker.is_tracking());
+    }
+}
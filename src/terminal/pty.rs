@@ -1,15 +1,98 @@
 use anyhow::Result;
+use crossterm::event::{Event, EventStream};
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
 use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use std::collections::HashMap;
 use std::env;
 use std::io::{Read, Write};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// What the app loop needs from the assistant's PTY
+///
+/// Implemented by `PlatformPty` for real sessions and by the mock PTY in tests,
+/// which replays scripted output and input instead of spawning a CLI.
+pub trait PtyBackend: Send {
+    /// Whether the CLI process is still alive
+    fn is_running(&self) -> bool;
+    /// Feed output through our vt100 parser (for screen capture and DSR replies)
+    fn process_output(&mut self, data: &[u8]);
+    /// Write input bytes to the CLI
+    fn write(&mut self, data: &[u8]) -> Result<()>;
+    /// Resize the PTY and the parser
+    fn resize(&mut self, cols: u16, rows: u16) -> Result<()>;
+    /// Current parsed screen
+    fn screen(&self) -> &vt100::Screen;
+    /// Wait up to `timeout` for the next terminal input event. Cancel-safe,
+    /// so the app loop can race it against PTY output.
+    fn next_event(&mut self, timeout: Duration) -> BoxFuture<'_, Result<Option<Event>>>;
+
+    /// PID of the CLI process, if known
+    fn process_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// How the CLI process ended (None while it's still running)
+    fn exit_status(&self) -> Option<PtyExit> {
+        None
+    }
+}
+
+/// How the assistant CLI exited
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PtyExit {
+    /// Exited with this code
+    Code(u32),
+    /// Killed by a signal (`SIGSEGV`, or the system's description if unrecognized)
+    Signal(String),
+}
+
+impl PtyExit {
+    /// Convert portable-pty's status, which only keeps the signal's description
+    fn from_status(status: &portable_pty::ExitStatus) -> Self {
+        if status.success() {
+            return Self::Code(0);
+        }
+        match status.to_string().strip_prefix("Terminated by ") {
+            Some(description) => Self::Signal(signal_name(description).to_string()),
+            None => Self::Code(status.exit_code()),
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        *self == Self::Code(0)
+    }
+}
+
+/// Map a `strsignal` description back to its signal name
+fn signal_name(description: &str) -> &str {
+    match description {
+        "Hangup" => "SIGHUP",
+        "Interrupt" => "SIGINT",
+        "Quit" => "SIGQUIT",
+        "Illegal instruction" => "SIGILL",
+        "Trace/breakpoint trap" | "Trace/BPT trap" => "SIGTRAP",
+        "Aborted" | "Abort trap" => "SIGABRT",
+        "Bus error" => "SIGBUS",
+        "Floating point exception" | "Floating-point exception" => "SIGFPE",
+        "Killed" => "SIGKILL",
+        "Segmentation fault" => "SIGSEGV",
+        "Broken pipe" => "SIGPIPE",
+        "Terminated" => "SIGTERM",
+        other => other,
+    }
+}
+
 pub struct PlatformPty {
     master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
     parser: vt100::Parser,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
     child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    /// Terminal input, opened on the first `next_event`
+    events: Option<EventStream>,
     #[allow(dead_code)]
     scroll_offset: usize,
 }
@@ -19,8 +102,9 @@ impl PlatformPty {
         output_tx: mpsc::Sender<Vec<u8>>,
         cols: u16,
         rows: u16,
-        command: &str,
+        command: &Path,
         extra_args: Vec<String>,
+        env: HashMap<String, String>,
     ) -> Result<Self> {
         let pty_system = native_pty_system();
 
@@ -48,6 +132,11 @@ impl PlatformPty {
             cmd.env(key, value);
         }
 
+        // Then layer on [platform.env] / --set-env
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
         // Override TERM for proper terminal support
         cmd.env("TERM", "xterm-256color");
 
@@ -73,20 +162,11 @@ impl PlatformPty {
             parser,
             writer,
             child,
+            events: None,
             scroll_offset: 0,
         })
     }
 
-    pub fn is_running(&self) -> bool {
-        let mut child = self.child.lock().unwrap();
-        // try_wait returns Ok(Some(status)) if exited, Ok(None) if still running
-        match child.try_wait() {
-            Ok(Some(_)) => false,
-            Ok(None) => true,
-            Err(_) => false,
-        }
-    }
-
     #[allow(dead_code)]
     pub fn scroll_up(&mut self, lines: usize) {
         let max_scroll = self.parser.screen().scrollback();
@@ -133,7 +213,24 @@ impl PlatformPty {
         }
     }
 
-    pub fn process_output(&mut self, data: &[u8]) {
+    #[allow(dead_code)]
+    pub fn parser(&self) -> &vt100::Parser {
+        &self.parser
+    }
+}
+
+impl PtyBackend for PlatformPty {
+    fn is_running(&self) -> bool {
+        let mut child = self.child.lock().unwrap();
+        // try_wait returns Ok(Some(status)) if exited, Ok(None) if still running
+        match child.try_wait() {
+            Ok(Some(_)) => false,
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+
+    fn process_output(&mut self, data: &[u8]) {
         // Wrap in catch_unwind to prevent vt100 parser panics from crashing the app
         // The parser can panic on certain edge cases (e.g., cursor position out of bounds)
         let parser_ptr = &mut self.parser as *mut vt100::Parser;
@@ -143,14 +240,15 @@ impl PlatformPty {
         }));
     }
 
-    pub fn write(&mut self, data: &[u8]) -> Result<()> {
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        tracing::trace!(bytes = data.len(), "pty write");
         let mut writer = self.writer.lock().unwrap();
         writer.write_all(data)?;
         writer.flush()?;
         Ok(())
     }
 
-    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+    fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
         let master = self.master.lock().unwrap();
         master.resize(PtySize {
             rows,
@@ -162,13 +260,50 @@ impl PlatformPty {
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn parser(&self) -> &vt100::Parser {
-        &self.parser
+    fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
     }
 
-    #[allow(dead_code)]
-    pub fn screen(&self) -> &vt100::Screen {
-        self.parser.screen()
+    fn process_id(&self) -> Option<u32> {
+        self.child.lock().unwrap().process_id()
+    }
+
+    fn exit_status(&self) -> Option<PtyExit> {
+        let mut child = self.child.lock().unwrap();
+        // std caches the status once reaped, so this is safe after is_running()
+        match child.try_wait() {
+            Ok(Some(status)) => Some(PtyExit::from_status(&status)),
+            _ => None,
+        }
+    }
+
+    fn next_event(&mut self, timeout: Duration) -> BoxFuture<'_, Result<Option<Event>>> {
+        Box::pin(async move {
+            let events = self.events.get_or_insert_with(EventStream::new);
+            match tokio::time::timeout(timeout, events.next()).await {
+                Ok(Some(event)) => Ok(Some(event?)),
+                Ok(None) | Err(_) => Ok(None),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use portable_pty::ExitStatus;
+
+    #[test]
+    fn test_exit_from_status() {
+        assert_eq!(PtyExit::from_status(&ExitStatus::with_exit_code(0)), PtyExit::Code(0));
+        assert_eq!(PtyExit::from_status(&ExitStatus::with_exit_code(2)), PtyExit::Code(2));
+        assert_eq!(
+            PtyExit::from_status(&ExitStatus::with_signal("Segmentation fault")),
+            PtyExit::Signal("SIGSEGV".to_string())
+        );
+        assert_eq!(
+            PtyExit::from_status(&ExitStatus::with_signal("Real-time signal 3")),
+            PtyExit::Signal("Real-time signal 3".to_string())
+        );
     }
 }
@@ -0,0 +1,144 @@
+//! SIGWINCH and SIGHUP, independent of crossterm's event stream
+//!
+//! Inside tmux or GNU screen a resize can be missed by crossterm's
+//! `Event::Resize` (the signal was masked, or the event queued before the
+//! resize finished). A thread fed by `signal_hook` sets flags that `App::run`
+//! checks every loop iteration instead: a resize request re-reads the terminal
+//! size, a hangup ends the session as if the CLI had exited. Once the terminal
+//! is restored, `exit_if_hung_up` lets that SIGHUP terminate the process.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use anyhow::Result;
+use signal_hook::consts::{SIGHUP, SIGWINCH};
+use signal_hook::iterator::{Handle, Signals};
+
+/// Flags set by the signal thread, taken by the main loop
+pub struct SignalFlags {
+    resize_requested: AtomicBool,
+    hangup: AtomicBool,
+}
+
+impl SignalFlags {
+    pub const fn new() -> Self {
+        Self {
+            resize_requested: AtomicBool::new(false),
+            hangup: AtomicBool::new(false),
+        }
+    }
+
+    fn record(&self, signal: i32) {
+        match signal {
+            SIGWINCH => self.resize_requested.store(true, Ordering::Relaxed),
+            SIGHUP => self.hangup.store(true, Ordering::Relaxed),
+            _ => {}
+        }
+    }
+
+    /// Whether a SIGWINCH arrived since the last call
+    pub fn take_resize_request(&self) -> bool {
+        self.resize_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Whether a SIGHUP has arrived; it stays set, the session is over
+    pub fn hung_up(&self) -> bool {
+        self.hangup.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for SignalFlags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Flags for the process's own signals
+pub static SIGNALS: SignalFlags = SignalFlags::new();
+
+/// Thread turning SIGWINCH and SIGHUP into `SignalFlags`; `stop` before
+/// restoring the terminal
+pub struct SignalWatcher {
+    handle: Handle,
+    thread: JoinHandle<()>,
+    /// Set by `stop`: SIGHUP then gets its default action (terminate) again
+    default_on_hangup: Arc<AtomicBool>,
+}
+
+impl SignalWatcher {
+    /// Watch for signals, recording them in `SIGNALS`
+    pub fn start() -> Result<Self> {
+        Self::start_with(&SIGNALS)
+    }
+
+    fn start_with(flags: &'static SignalFlags) -> Result<Self> {
+        let default_on_hangup = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register_conditional_default(SIGHUP, default_on_hangup.clone())?;
+        let mut signals = Signals::new([SIGWINCH, SIGHUP])?;
+        let handle = signals.handle();
+        let thread = std::thread::Builder::new().name("signals".into()).spawn(move || {
+            for signal in signals.forever() {
+                flags.record(signal);
+            }
+        })?;
+        Ok(Self {
+            handle,
+            thread,
+            default_on_hangup,
+        })
+    }
+
+    /// Stop recording signals; a later SIGHUP terminates the process instead
+    /// of being swallowed by the handler left installed
+    pub fn stop(self) {
+        self.handle.close();
+        let _ = self.thread.join();
+        self.default_on_hangup.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Die of the SIGHUP that ended the session, if one did; call once the
+/// terminal is restored, since printing to a hung-up terminal only fails
+pub fn exit_if_hung_up() {
+    if SIGNALS.hung_up() {
+        let _ = signal_hook::low_level::emulate_default_handler(SIGHUP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_resize_taken_once_and_hangup_sticks() {
+        let flags = SignalFlags::new();
+        assert!(!flags.take_resize_request());
+
+        flags.record(SIGWINCH);
+        flags.record(SIGWINCH);
+        assert!(flags.take_resize_request());
+        assert!(!flags.take_resize_request());
+        assert!(!flags.hung_up());
+
+        flags.record(SIGHUP);
+        assert!(flags.hung_up());
+        assert!(flags.hung_up());
+        assert!(!flags.take_resize_request());
+    }
+
+    #[test]
+    fn test_watcher_sets_resize_flag() {
+        static FLAGS: SignalFlags = SignalFlags::new();
+        let watcher = SignalWatcher::start_with(&FLAGS).unwrap();
+        signal_hook::low_level::raise(SIGWINCH).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !FLAGS.take_resize_request() {
+            assert!(Instant::now() < deadline, "SIGWINCH never reached the flag");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        watcher.stop();
+    }
+}
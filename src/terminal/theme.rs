@@ -0,0 +1,377 @@
+//! Color theme presets
+//!
+//! Widgets pick their colors by name from the 256-color palette
+//! (`escape::color::GREEN` and friends). A theme maps each of those names to
+//! a color of its own: `Dark` keeps the palette entries, `Light` swaps in
+//! darker ones that read on a light background, and the named presets use
+//! their published RGB values (rendered as the nearest palette entry on
+//! terminals without true color). The theme (from `[theme]` in config.toml)
+//! and the detected color mode make up the `Palette` everything draws with.
+
+use super::color_detect::{nearest_palette, ColorMode};
+use super::escape::color::{self, fg_rgb};
+use Color::{Color256 as P, TrueColor as Rgb};
+
+/// A theme color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    /// Entry in the 256-color palette
+    Color256(u8),
+    /// 24-bit RGB
+    TrueColor(u8, u8, u8),
+}
+
+impl Color {
+    /// Foreground escape in `mode`
+    pub fn fg(self, mode: ColorMode) -> String {
+        match (self, mode) {
+            (Color::TrueColor(r, g, b), ColorMode::TrueColor) => fg_rgb(r, g, b),
+            _ => mode.fg(self.palette_index()),
+        }
+    }
+
+    /// Background escape in `mode`
+    pub fn bg(self, mode: ColorMode) -> String {
+        match (self, mode) {
+            (Color::TrueColor(r, g, b), ColorMode::TrueColor) => format!("\x1b[48;2;{};{};{}m", r, g, b),
+            _ => mode.bg(self.palette_index()),
+        }
+    }
+
+    /// The palette entry to use where RGB isn't available
+    fn palette_index(self) -> u8 {
+        match self {
+            Color::Color256(index) => index,
+            Color::TrueColor(r, g, b) => nearest_palette(r, g, b),
+        }
+    }
+}
+
+/// A theme's color for each named palette color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThemeColors {
+    pub fg_green: Color,
+    pub fg_light_green: Color,
+    pub fg_yellow: Color,
+    pub fg_light_yellow: Color,
+    pub fg_orange: Color,
+    pub fg_dark_orange: Color,
+    pub fg_red: Color,
+    pub fg_cyan: Color,
+    pub fg_light_cyan: Color,
+    pub fg_blue: Color,
+    pub fg_light_blue: Color,
+    pub fg_purple: Color,
+    pub fg_pink: Color,
+    pub fg_gray: Color,
+    pub fg_dark_gray: Color,
+    pub fg_faint: Color,
+    pub bg_dark: Color,
+    pub black: Color,
+    pub white: Color,
+}
+
+impl ThemeColors {
+    /// This theme's color for a palette entry; entries without a name (the
+    /// throbber gradient, heatmap ramps) are left alone
+    pub fn resolve(&self, palette: u8) -> Color {
+        match palette {
+            color::GREEN => self.fg_green,
+            color::LIGHT_GREEN => self.fg_light_green,
+            color::YELLOW => self.fg_yellow,
+            color::LIGHT_YELLOW => self.fg_light_yellow,
+            color::ORANGE => self.fg_orange,
+            color::DARK_ORANGE => self.fg_dark_orange,
+            color::RED => self.fg_red,
+            color::CYAN => self.fg_cyan,
+            color::LIGHT_CYAN => self.fg_light_cyan,
+            color::BLUE => self.fg_blue,
+            color::LIGHT_BLUE => self.fg_light_blue,
+            color::PURPLE => self.fg_purple,
+            color::PINK => self.fg_pink,
+            color::GRAY => self.fg_gray,
+            color::DARK_GRAY => self.fg_dark_gray,
+            color::FAINT => self.fg_faint,
+            color::BG_DARK => self.bg_dark,
+            color::BLACK => self.black,
+            color::WHITE => self.white,
+            other => Color::Color256(other),
+        }
+    }
+}
+
+const DARK: ThemeColors = ThemeColors {
+    fg_green: P(color::GREEN),
+    fg_light_green: P(color::LIGHT_GREEN),
+    fg_yellow: P(color::YELLOW),
+    fg_light_yellow: P(color::LIGHT_YELLOW),
+    fg_orange: P(color::ORANGE),
+    fg_dark_orange: P(color::DARK_ORANGE),
+    fg_red: P(color::RED),
+    fg_cyan: P(color::CYAN),
+    fg_light_cyan: P(color::LIGHT_CYAN),
+    fg_blue: P(color::BLUE),
+    fg_light_blue: P(color::LIGHT_BLUE),
+    fg_purple: P(color::PURPLE),
+    fg_pink: P(color::PINK),
+    fg_gray: P(color::GRAY),
+    fg_dark_gray: P(color::DARK_GRAY),
+    fg_faint: P(color::FAINT),
+    bg_dark: P(color::BG_DARK),
+    black: P(color::BLACK),
+    white: P(color::WHITE),
+};
+
+const LIGHT: ThemeColors = ThemeColors {
+    fg_green: P(28),
+    fg_light_green: P(34),
+    fg_yellow: P(136),
+    fg_light_yellow: P(178),
+    fg_orange: P(130),
+    fg_dark_orange: P(166),
+    fg_red: P(160),
+    fg_cyan: P(31),
+    fg_light_cyan: P(37),
+    fg_blue: P(25),
+    fg_light_blue: P(32),
+    fg_purple: P(91),
+    fg_pink: P(163),
+    fg_gray: P(243),
+    fg_dark_gray: P(248),
+    fg_faint: P(240),
+    bg_dark: P(254),
+    black: P(231),
+    white: P(16),
+};
+
+const SOLARIZED: ThemeColors = ThemeColors {
+    fg_green: Rgb(0x85, 0x99, 0x00),
+    fg_light_green: Rgb(0x85, 0x99, 0x00),
+    fg_yellow: Rgb(0xb5, 0x89, 0x00),
+    fg_light_yellow: Rgb(0xb5, 0x89, 0x00),
+    fg_orange: Rgb(0xcb, 0x4b, 0x16),
+    fg_dark_orange: Rgb(0xcb, 0x4b, 0x16),
+    fg_red: Rgb(0xdc, 0x32, 0x2f),
+    fg_cyan: Rgb(0x2a, 0xa1, 0x98),
+    fg_light_cyan: Rgb(0x2a, 0xa1, 0x98),
+    fg_blue: Rgb(0x26, 0x8b, 0xd2),
+    fg_light_blue: Rgb(0x26, 0x8b, 0xd2),
+    fg_purple: Rgb(0x6c, 0x71, 0xc4),
+    fg_pink: Rgb(0xd3, 0x36, 0x82),
+    fg_gray: Rgb(0x83, 0x94, 0x96),
+    fg_dark_gray: Rgb(0x58, 0x6e, 0x75),
+    fg_faint: Rgb(0x93, 0xa1, 0xa1),
+    bg_dark: Rgb(0x07, 0x36, 0x42),
+    black: Rgb(0x00, 0x2b, 0x36),
+    white: Rgb(0xfd, 0xf6, 0xe3),
+};
+
+const DRACULA: ThemeColors = ThemeColors {
+    fg_green: Rgb(0x50, 0xfa, 0x7b),
+    fg_light_green: Rgb(0x50, 0xfa, 0x7b),
+    fg_yellow: Rgb(0xf1, 0xfa, 0x8c),
+    fg_light_yellow: Rgb(0xf1, 0xfa, 0x8c),
+    fg_orange: Rgb(0xff, 0xb8, 0x6c),
+    fg_dark_orange: Rgb(0xff, 0xb8, 0x6c),
+    fg_red: Rgb(0xff, 0x55, 0x55),
+    fg_cyan: Rgb(0x8b, 0xe9, 0xfd),
+    fg_light_cyan: Rgb(0x8b, 0xe9, 0xfd),
+    fg_blue: Rgb(0x8b, 0xe9, 0xfd),
+    fg_light_blue: Rgb(0x8b, 0xe9, 0xfd),
+    fg_purple: Rgb(0xbd, 0x93, 0xf9),
+    fg_pink: Rgb(0xff, 0x79, 0xc6),
+    fg_gray: Rgb(0x62, 0x72, 0xa4),
+    fg_dark_gray: Rgb(0x44, 0x47, 0x5a),
+    fg_faint: Rgb(0xf8, 0xf8, 0xf2),
+    bg_dark: Rgb(0x44, 0x47, 0x5a),
+    black: Rgb(0x28, 0x2a, 0x36),
+    white: Rgb(0xf8, 0xf8, 0xf2),
+};
+
+const NORD: ThemeColors = ThemeColors {
+    fg_green: Rgb(0xa3, 0xbe, 0x8c),
+    fg_light_green: Rgb(0x8f, 0xbc, 0xbb),
+    fg_yellow: Rgb(0xeb, 0xcb, 0x8b),
+    fg_light_yellow: Rgb(0xeb, 0xcb, 0x8b),
+    fg_orange: Rgb(0xd0, 0x87, 0x70),
+    fg_dark_orange: Rgb(0xd0, 0x87, 0x70),
+    fg_red: Rgb(0xbf, 0x61, 0x6a),
+    fg_cyan: Rgb(0x88, 0xc0, 0xd0),
+    fg_light_cyan: Rgb(0x8f, 0xbc, 0xbb),
+    fg_blue: Rgb(0x5e, 0x81, 0xac),
+    fg_light_blue: Rgb(0x81, 0xa1, 0xc1),
+    fg_purple: Rgb(0xb4, 0x8e, 0xad),
+    fg_pink: Rgb(0xb4, 0x8e, 0xad),
+    fg_gray: Rgb(0x4c, 0x56, 0x6a),
+    fg_dark_gray: Rgb(0x43, 0x4c, 0x5e),
+    fg_faint: Rgb(0xd8, 0xde, 0xe9),
+    bg_dark: Rgb(0x3b, 0x42, 0x52),
+    black: Rgb(0x2e, 0x34, 0x40),
+    white: Rgb(0xec, 0xef, 0xf4),
+};
+
+const CATPPUCCIN: ThemeColors = ThemeColors {
+    fg_green: Rgb(0xa6, 0xe3, 0xa1),
+    fg_light_green: Rgb(0x94, 0xe2, 0xd5),
+    fg_yellow: Rgb(0xf9, 0xe2, 0xaf),
+    fg_light_yellow: Rgb(0xf9, 0xe2, 0xaf),
+    fg_orange: Rgb(0xfa, 0xb3, 0x87),
+    fg_dark_orange: Rgb(0xfa, 0xb3, 0x87),
+    fg_red: Rgb(0xf3, 0x8b, 0xa8),
+    fg_cyan: Rgb(0x89, 0xdc, 0xeb),
+    fg_light_cyan: Rgb(0x94, 0xe2, 0xd5),
+    fg_blue: Rgb(0x89, 0xb4, 0xfa),
+    fg_light_blue: Rgb(0x74, 0xc7, 0xec),
+    fg_purple: Rgb(0xcb, 0xa6, 0xf7),
+    fg_pink: Rgb(0xf5, 0xc2, 0xe7),
+    fg_gray: Rgb(0x6c, 0x70, 0x86),
+    fg_dark_gray: Rgb(0x58, 0x5b, 0x70),
+    fg_faint: Rgb(0x93, 0x99, 0xb2),
+    bg_dark: Rgb(0x31, 0x32, 0x44),
+    black: Rgb(0x1e, 0x1e, 0x2e),
+    white: Rgb(0xcd, 0xd6, 0xf4),
+};
+
+/// Theme presets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    /// The palette colors crabigator has always used
+    #[default]
+    Dark,
+    /// Darker colors for light terminal backgrounds
+    Light,
+    Solarized,
+    Dracula,
+    Nord,
+    Catppuccin,
+}
+
+impl Theme {
+    const ALL: [Theme; 6] = [
+        Theme::Dark,
+        Theme::Light,
+        Theme::Solarized,
+        Theme::Dracula,
+        Theme::Nord,
+        Theme::Catppuccin,
+    ];
+
+    /// Preset by name (`dark`, `light`, `solarized`, `dracula`, `nord`,
+    /// `catppuccin`), ignoring case
+    pub fn from_name(name: &str) -> Option<Theme> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+            Theme::Solarized => "solarized",
+            Theme::Dracula => "dracula",
+            Theme::Nord => "nord",
+            Theme::Catppuccin => "catppuccin",
+        }
+    }
+
+    pub fn colors(self) -> &'static ThemeColors {
+        match self {
+            Theme::Dark => &DARK,
+            Theme::Light => &LIGHT,
+            Theme::Solarized => &SOLARIZED,
+            Theme::Dracula => &DRACULA,
+            Theme::Nord => &NORD,
+            Theme::Catppuccin => &CATPPUCCIN,
+        }
+    }
+
+    /// `Light` or `Dark` from the background in `COLORFGBG`
+    pub fn auto_detect() -> Theme {
+        Self::from_colorfgbg(std::env::var("COLORFGBG").ok().as_deref())
+    }
+
+    /// `COLORFGBG` is `fg;bg` (rxvt, Konsole, iTerm2), sometimes `fg;default;bg`;
+    /// a white background (7 or 15) means a light terminal
+    fn from_colorfgbg(value: Option<&str>) -> Theme {
+        let background = value.and_then(|v| v.rsplit(';').next()).and_then(|bg| bg.trim().parse::<u8>().ok());
+        match background {
+            Some(7) | Some(15) => Theme::Light,
+            _ => Theme::Dark,
+        }
+    }
+}
+
+/// A theme as this terminal shows it; picked once at startup and passed to
+/// whatever draws
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Palette {
+    pub theme: Theme,
+    pub mode: ColorMode,
+}
+
+impl Palette {
+    pub fn new(theme: Theme, mode: ColorMode) -> Self {
+        Self { theme, mode }
+    }
+
+    /// Foreground escape for a 256-color palette entry, as the theme colors
+    /// it, in the color mode (RGB on true color terminals, nearest basic
+    /// color on 16-color ones)
+    pub fn fg(&self, color: u8) -> String {
+        self.theme.colors().resolve(color).fg(self.mode)
+    }
+
+    /// Background escape for a 256-color palette entry, as the theme colors it
+    pub fn bg(&self, color: u8) -> String {
+        self.theme.colors().resolve(color).bg(self.mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Theme::from_name("nord"), Some(Theme::Nord));
+        assert_eq!(Theme::from_name("Catppuccin"), Some(Theme::Catppuccin));
+        assert_eq!(Theme::from_name("monokai"), None);
+        for theme in Theme::ALL {
+            assert_eq!(Theme::from_name(theme.name()), Some(theme));
+        }
+    }
+
+    #[test]
+    fn test_dark_keeps_palette() {
+        let dark = Theme::Dark.colors();
+        for named in [color::GREEN, color::RED, color::GRAY, color::BG_DARK] {
+            assert_eq!(dark.resolve(named), Color::Color256(named));
+        }
+        assert_eq!(ColorMode::Color256.fg(color::GREEN), dark.resolve(color::GREEN).fg(ColorMode::Color256));
+    }
+
+    #[test]
+    fn test_theme_color_lookups() {
+        assert_eq!(Theme::Dracula.colors().resolve(color::GREEN), Color::TrueColor(0x50, 0xfa, 0x7b));
+        assert_eq!(Theme::Nord.colors().resolve(color::RED), Color::TrueColor(0xbf, 0x61, 0x6a));
+        assert_eq!(Theme::Light.colors().resolve(color::YELLOW), Color::Color256(136));
+        // Unnamed palette entries pass through
+        assert_eq!(Theme::Nord.colors().resolve(230), Color::Color256(230));
+
+        let green = Theme::Dracula.colors().fg_green;
+        assert_eq!(green.fg(ColorMode::TrueColor), "\x1b[38;2;80;250;123m");
+        // (80, 250, 123) is nearest the cube's (1, 5, 2)
+        assert_eq!(green.fg(ColorMode::Color256), "\x1b[38;5;84m");
+        assert_eq!(green.bg(ColorMode::TrueColor), "\x1b[48;2;80;250;123m");
+    }
+
+    #[test]
+    fn test_auto_detect_from_colorfgbg() {
+        assert_eq!(Theme::from_colorfgbg(Some("0;15")), Theme::Light);
+        assert_eq!(Theme::from_colorfgbg(Some("0;default;7")), Theme::Light);
+        assert_eq!(Theme::from_colorfgbg(Some("15;0")), Theme::Dark);
+        assert_eq!(Theme::from_colorfgbg(Some("garbage")), Theme::Dark);
+        assert_eq!(Theme::from_colorfgbg(None), Theme::Dark);
+    }
+}
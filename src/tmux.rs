@@ -0,0 +1,164 @@
+//! tmux status line integration
+//!
+//! With `--tmux-status-file PATH`, every status bar draw also writes a short
+//! one-line summary to PATH, e.g. `⠋ 12m 4p main`. Show it in tmux with:
+//!
+//! ```text
+//! set -g status-right '#(cat /tmp/crabigator-tmux-status.txt)'
+//! ```
+//!
+//! The line is plain text: tmux styles `#()` output with its own `#[fg=...]`
+//! markup, which can be added to the `tmux_status_format` template.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+use crate::git::GitState;
+use crate::hooks::SessionStats;
+use crate::platforms::SessionState;
+use crate::ui::throbber_frame;
+
+/// Default template: state, session time, prompts, branch
+pub const DEFAULT_FORMAT: &str = "{state} {time} {prompts}p {branch}";
+
+/// Writes the tmux status line to a file whenever it changes
+pub struct TmuxStatusWriter {
+    path: PathBuf,
+    format: String,
+    /// Last line written (skip rewriting identical content)
+    last: Mutex<String>,
+}
+
+impl TmuxStatusWriter {
+    pub fn new(path: PathBuf, format: Option<String>) -> Self {
+        Self {
+            path,
+            format: format.unwrap_or_else(|| DEFAULT_FORMAT.to_string()),
+            last: Mutex::new(String::new()),
+        }
+    }
+
+    /// Render the status line and atomically replace the file if it changed
+    pub fn update(&self, stats: &SessionStats, git: &GitState) -> Result<()> {
+        let line = render(&self.format, stats, git, throbber_frame());
+        let mut last = self.last.lock().unwrap_or_else(|p| p.into_inner());
+        if *last == line {
+            return Ok(());
+        }
+
+        // Atomic write via temp file + rename so tmux never reads a partial line
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, format!("{}\n", line))?;
+        fs::rename(&tmp_path, &self.path)?;
+        *last = line;
+        Ok(())
+    }
+
+    /// Remove the status file on exit so tmux doesn't show a stale session
+    pub fn cleanup(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Fill the template placeholders: `{state}`, `{time}`, `{prompts}`, `{completions}`, `{tools}`, `{branch}`
+fn render(format: &str, stats: &SessionStats, git: &GitState, frame: char) -> String {
+    let platform = &stats.platform_stats;
+    let line = format
+        .replace("{state}", &state_glyph(stats.effective_state(), frame).to_string())
+        .replace("{time}", &format_time(stats.work_seconds))
+        .replace("{prompts}", &platform.prompts.to_string())
+        .replace("{completions}", &platform.completions.to_string())
+        .replace("{tools}", &platform.total_tool_calls().to_string())
+        .replace("{branch}", if git.is_repo { &git.branch } else { "" });
+    line.trim().to_string()
+}
+
+/// Single-character state indicator (throbber frame while thinking)
+fn state_glyph(state: SessionState, frame: char) -> char {
+    match state {
+        SessionState::Ready => '○',
+        SessionState::Thinking => frame,
+        SessionState::Permission | SessionState::Question => '?',
+        SessionState::Complete => '✓',
+        SessionState::Interrupted => '⊘',
+        SessionState::Paused => '⏸',
+    }
+}
+
+/// Compact session time: "0m", "12m", "1h5m"
+fn format_time(secs: u64) -> String {
+    let (hours, mins) = (secs / 3600, (secs % 3600) / 60);
+    if hours > 0 {
+        format!("{}h{}m", hours, mins)
+    } else {
+        format!("{}m", mins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(state: SessionState, work_seconds: u64, prompts: u32) -> SessionStats {
+        let mut stats = SessionStats::new();
+        stats.work_seconds = work_seconds;
+        stats.platform_stats.state = state;
+        stats.platform_stats.prompts = prompts;
+        stats
+    }
+
+    fn repo(branch: &str) -> GitState {
+        GitState {
+            branch: branch.to_string(),
+            is_repo: true,
+            ..GitState::new()
+        }
+    }
+
+    #[test]
+    fn test_render_default_format() {
+        let thinking = stats(SessionState::Thinking, 12 * 60 + 30, 4);
+        assert_eq!(render(DEFAULT_FORMAT, &thinking, &repo("main"), '⠋'), "⠋ 12m 4p main");
+
+        let complete = stats(SessionState::Complete, 3900, 9);
+        assert_eq!(render(DEFAULT_FORMAT, &complete, &repo("feature/x"), '⠋'), "✓ 1h5m 9p feature/x");
+
+        let ready = stats(SessionState::Ready, 5, 0);
+        assert_eq!(render(DEFAULT_FORMAT, &ready, &repo("main"), '⠋'), "○ 0m 0p main");
+    }
+
+    #[test]
+    fn test_render_waiting_and_interrupted() {
+        let mut interrupted = stats(SessionState::Thinking, 60, 1);
+        interrupted.set_interrupted();
+        assert_eq!(render("{state}", &interrupted, &repo("main"), '⠋'), "⊘");
+        assert_eq!(render("{state}", &stats(SessionState::Permission, 0, 0), &repo("main"), '⠋'), "?");
+    }
+
+    #[test]
+    fn test_render_outside_repo_and_custom_format() {
+        let complete = stats(SessionState::Complete, 120, 2);
+        assert_eq!(render(DEFAULT_FORMAT, &complete, &GitState::new(), '⠋'), "✓ 2m 2p");
+        assert_eq!(
+            render("#[fg=green]{state}#[default] {prompts}/{completions}", &complete, &repo("main"), '⠋'),
+            "#[fg=green]✓#[default] 2/0"
+        );
+    }
+
+    #[test]
+    fn test_update_writes_atomically_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status.txt");
+        let writer = TmuxStatusWriter::new(path.clone(), Some("{prompts}p {branch}".to_string()));
+
+        writer.update(&stats(SessionState::Complete, 0, 3), &repo("main")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3p main\n");
+        assert!(!path.with_extension("tmp").exists());
+
+        writer.cleanup();
+        assert!(!path.exists());
+    }
+}
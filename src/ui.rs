@@ -3,17 +3,113 @@
 //! Each widget is responsible for rendering its own section of the status bar.
 //! Widgets use raw ANSI escape sequences for terminal output.
 
+mod budget_warning;
 mod changes;
+mod columns;
 mod git;
+mod help_overlay;
+mod message_overlay;
+pub mod plugin;
+mod renderer;
 pub mod sparkline;
 mod stats;
 mod status_bar;
 pub mod utils;
+mod writer;
 
+use serde::{Deserialize, Serialize};
+
+use crate::platforms::SessionState;
+use crate::terminal::escape::color;
+
+pub use budget_warning::draw_budget_warning;
 pub use changes::draw_changes_widget;
+pub use columns::{column_widths, validate_widget_specs, WidgetName, WidgetSpec};
 pub use git::draw_git_widget;
-pub use stats::draw_stats_widget;
+pub use help_overlay::HelpOverlay;
+pub use message_overlay::MessageOverlay;
+pub use plugin::{AppState, CommandWidgetConfig, CustomWidget};
+pub use renderer::StatusBarRenderer;
+pub use stats::{draw_stats_widget, pulse_dim, throbber_frame};
 pub use status_bar::{draw_status_bar, Layout};
+pub use writer::{ByteWriter, FRAME_CAPACITY};
+
+/// How much detail widgets render
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WidgetMode {
+    /// Full widgets
+    #[default]
+    Normal,
+    /// Narrow terminal: each widget shows a single summary on its header row
+    Compact,
+}
+
+impl WidgetMode {
+    /// Compact when the terminal is narrower than `compact_threshold` columns
+    pub fn for_width(total_cols: u16, compact_threshold: u16) -> Self {
+        if total_cols < compact_threshold {
+            Self::Compact
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Line drawn between the PTY and the widgets (`separator_style` in config.toml)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeparatorStyle {
+    /// `─`
+    Thin,
+    /// `━`
+    #[default]
+    Thick,
+    /// `═`
+    Double,
+    /// `┄`
+    Dotted,
+    /// Blank row
+    None,
+    /// `── crabigator ──`, centered on a thin line
+    Label,
+}
+
+/// The character repeated across the separator row (`Label` pads with `─`)
+pub fn separator_char(style: SeparatorStyle) -> &'static str {
+    match style {
+        SeparatorStyle::Thin | SeparatorStyle::Label => "─",
+        SeparatorStyle::Thick => "━",
+        SeparatorStyle::Double => "═",
+        SeparatorStyle::Dotted => "┄",
+        SeparatorStyle::None => " ",
+    }
+}
+
+/// Separator color for the session state, so it's visible at a glance
+pub fn separator_color(state: SessionState) -> u8 {
+    match state {
+        SessionState::Complete => color::LIGHT_GREEN,
+        SessionState::Thinking => color::GREEN,
+        SessionState::Question => color::ORANGE,
+        SessionState::Permission => color::YELLOW,
+        SessionState::Paused => color::RED,
+        SessionState::Ready | SessionState::Interrupted => color::GRAY,
+    }
+}
+
+/// A full separator row `width` cells wide (no colors)
+pub fn separator_line(style: SeparatorStyle, width: usize) -> String {
+    let ch = separator_char(style);
+    if style != SeparatorStyle::Label {
+        return ch.repeat(width);
+    }
+    let label = " crabigator ";
+    let Some(rest) = width.checked_sub(label.len()) else {
+        return ch.repeat(width);
+    };
+    let left = rest / 2;
+    format!("{}{}{}", ch.repeat(left), label, ch.repeat(rest - left))
+}
 
 /// Common layout parameters for widget rendering
 #[derive(Clone, Copy)]
@@ -24,3 +120,42 @@ pub struct WidgetArea {
     pub width: u16,
     pub height: u16,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separator_styles() {
+        assert_eq!(separator_line(SeparatorStyle::Thin, 4), "────");
+        assert_eq!(separator_line(SeparatorStyle::Thick, 4), "━━━━");
+        assert_eq!(separator_line(SeparatorStyle::Double, 4), "════");
+        assert_eq!(separator_line(SeparatorStyle::Dotted, 4), "┄┄┄┄");
+        assert_eq!(separator_line(SeparatorStyle::None, 4), "    ");
+        assert_eq!(separator_line(SeparatorStyle::Label, 16), "── crabigator ──");
+        assert_eq!(separator_line(SeparatorStyle::Label, 17), "── crabigator ───");
+        // Too narrow for the label: plain thin line
+        assert_eq!(separator_line(SeparatorStyle::Label, 6), "──────");
+    }
+
+    #[test]
+    fn test_separator_style_from_config() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            style: SeparatorStyle,
+        }
+        let parse = |s: &str| toml::from_str::<Wrapper>(&format!("style = \"{}\"", s)).unwrap().style;
+        assert_eq!(parse("double"), SeparatorStyle::Double);
+        assert_eq!(parse("none"), SeparatorStyle::None);
+        assert_eq!(parse("label"), SeparatorStyle::Label);
+    }
+
+    #[test]
+    fn test_separator_color_follows_state() {
+        assert_eq!(separator_color(SessionState::Complete), color::LIGHT_GREEN);
+        assert_eq!(separator_color(SessionState::Thinking), color::GREEN);
+        assert_eq!(separator_color(SessionState::Question), color::ORANGE);
+        assert_eq!(separator_color(SessionState::Permission), color::YELLOW);
+        assert_eq!(separator_color(SessionState::Ready), color::GRAY);
+    }
+}
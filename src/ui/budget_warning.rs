@@ -0,0 +1,96 @@
+//! Token budget warning - replaces the widgets while input is paused
+//!
+//! Drawn when the session's tokens reach `token_budget`, until `Ctrl+A u`
+//! lifts the limit.
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::terminal::escape::{self, color, BOLD, RESET};
+use super::utils::{strip_ansi_len, take_graphemes};
+use super::Layout;
+
+/// Token count as `950`, `12.5k` or `2.0M`
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..=999 => tokens.to_string(),
+        1_000..=999_999 if tokens.is_multiple_of(1_000) => format!("{}k", tokens / 1_000),
+        1_000..=999_999 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+fn warning_text(budget: u64) -> String {
+    format!(
+        "⚠ Token budget exhausted ({} tokens). Press Ctrl+A u to unset limit.",
+        format_tokens(budget)
+    )
+}
+
+/// Draw the warning as a red band across the middle of the status area
+pub fn draw_budget_warning(stdout: &mut impl Write, layout: &Layout, budget: u64) -> Result<()> {
+    write!(stdout, "{}", escape::CURSOR_SAVE)?;
+
+    let width = layout.total_cols as usize;
+    // Two rows tall: the message and a blank row under it
+    let text_row = (layout.status_rows / 2).max(1);
+    for row in 1..layout.status_rows {
+        write!(stdout, "{}", escape::cursor_to(layout.pty_rows + 1 + row, 1))?;
+        if row == text_row || row == text_row + 1 {
+            let text = if row == text_row {
+                format!(" {}", take_graphemes(&warning_text(budget), width.saturating_sub(1)))
+            } else {
+                String::new()
+            };
+            let pad = width.saturating_sub(strip_ansi_len(&text));
+            let (bg, fg) = (layout.palette.bg(color::RED), layout.palette.fg(color::WHITE));
+            write!(stdout, "{}{}{}{}{:pad$}{}", bg, fg, BOLD, text, "", RESET, pad = pad)?;
+        } else {
+            write!(stdout, "{:width$}", "", width = width)?;
+        }
+    }
+
+    write!(stdout, "{}", escape::CURSOR_RESTORE)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::strip_ansi;
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(200_000), "200k");
+        assert_eq!(format_tokens(12_500), "12.5k");
+        assert_eq!(format_tokens(2_000_000), "2.0M");
+    }
+
+    #[test]
+    fn test_draw_fills_status_rows() {
+        let layout = Layout {
+            pty_rows: 20,
+            total_cols: 100,
+            status_rows: 5,
+            max_git_files: None,
+            compact_threshold: 80,
+            separator_style: crate::ui::SeparatorStyle::default(),
+            max_session_minutes: None,
+            token_budget: Some(200_000),
+            show_word_count: false,
+            hide_test_changes: false,
+            custom_widget_width: 24,
+            widgets: Vec::new(),
+            palette: crate::terminal::theme::Palette::default(),
+        };
+        let mut buf = Vec::new();
+        draw_budget_warning(&mut buf, &layout, 200_000).unwrap();
+        let plain = strip_ansi(&String::from_utf8(buf).unwrap());
+
+        assert!(plain.contains("⚠ Token budget exhausted (200k tokens). Press Ctrl+A u to unset limit."));
+        assert_eq!(plain.chars().count(), 4 * 100);
+    }
+}
@@ -3,17 +3,18 @@
 //! Shows parsed semantic changes (functions, classes, etc.) from git diffs,
 //! organized by programming language with per-change line stats.
 
-use std::io::{Stdout, Write};
+use std::io::Write;
 use std::path::Path;
 
 use anyhow::Result;
 
 use crate::ide::IdeKind;
 use crate::parsers::{ChangeNode, ChangeType, DiffSummary, LanguageChanges, NodeKind};
-use crate::terminal::escape::{self, color, fg, hyperlink, RESET};
+use crate::terminal::escape::{self, color, hyperlink, RESET};
+use crate::terminal::theme::Palette;
 
-use super::utils::{digit_count, strip_ansi_len, truncate_middle};
-use super::WidgetArea;
+use super::utils::{digit_count, display_width, strip_ansi_len, truncate_middle};
+use super::{WidgetArea, WidgetMode};
 
 /// Priority order for node kinds (lower = higher priority, appears first)
 fn kind_priority(kind: &NodeKind) -> u8 {
@@ -27,13 +28,17 @@ fn kind_priority(kind: &NodeKind) -> u8 {
         NodeKind::Impl => 6,
         NodeKind::Module => 7,
         NodeKind::Const => 8,
-        NodeKind::Other => 9,
+        NodeKind::Selector | NodeKind::MediaQuery => 9,
+        NodeKind::Other => 10,
     }
 }
 
-/// Get icon and color for a node kind
-fn get_kind_icon(kind: &NodeKind) -> (&'static str, u8) {
-    match kind {
+/// Get icon and color for a change (by its node kind; binary files get their own)
+fn get_kind_icon(change: &ChangeNode) -> (&'static str, u8) {
+    if change.is_binary() {
+        return ("📦", color::GRAY);
+    }
+    match change.kind {
         NodeKind::Function | NodeKind::Method => ("ƒ", color::BLUE),
         NodeKind::Class => ("◆", color::PURPLE),
         NodeKind::Struct => ("◇", color::CYAN),
@@ -42,10 +47,23 @@ fn get_kind_icon(kind: &NodeKind) -> (&'static str, u8) {
         NodeKind::Impl => ("◊", color::CYAN),
         NodeKind::Module => ("□", color::GRAY),
         NodeKind::Const => ("•", color::GRAY),
+        NodeKind::Selector => ("{", color::BLUE),
+        NodeKind::MediaQuery => ("◈", color::PURPLE),
         NodeKind::Other => ("·", color::DARK_GRAY),
     }
 }
 
+/// Name shown for a change; binary placeholders show their file name too
+fn display_name(change: &ChangeNode) -> String {
+    match change.file_path.as_deref() {
+        Some(path) if change.is_binary() => {
+            let file_name = path.rsplit('/').next().unwrap_or(path);
+            format!("{} {}", file_name, change.name)
+        }
+        _ => change.name.clone(),
+    }
+}
+
 /// Dynamic column widths computed from actual change data
 #[derive(Clone, Copy)]
 struct StatsColumnWidths {
@@ -87,36 +105,77 @@ impl StatsColumnWidths {
 }
 
 
+/// Word counts at or below this aren't worth a header mention
+const WORD_COUNT_THRESHOLD: usize = 100;
+
 /// Draw the changes widget at the given position
+#[allow(clippy::too_many_arguments)]
 pub fn draw_changes_widget(
-    stdout: &mut Stdout,
+    stdout: &mut impl Write,
     area: WidgetArea,
     diff_summary: &DiffSummary,
     terminal_title: Option<&str>,
     ide: IdeKind,
     cwd: &Path,
+    show_word_count: bool,
+    hide_test_changes: bool,
+    mode: WidgetMode,
+    palette: &Palette,
 ) -> Result<()> {
     write!(stdout, "{}", escape::cursor_to(area.pty_rows + 1 + area.row, area.col + 1))?;
 
     // Get changes grouped by language
-    let by_language = diff_summary.by_language();
+    let mut by_language = diff_summary.by_language();
+    if hide_test_changes {
+        for lang in &mut by_language {
+            lang.changes.retain(|c| !c.is_test());
+        }
+        by_language.retain(|lang| !lang.changes.is_empty());
+    }
+
+    if mode == WidgetMode::Compact {
+        // Narrow terminal: just the total change count on the header row
+        let summary = match area.row {
+            1 if diff_summary.loading => format!("{}...{}", palette.fg(color::GRAY), RESET),
+            1 => {
+                let mut total = diff_summary.total_changes();
+                if hide_test_changes {
+                    total -= diff_summary.test_changes();
+                }
+                let change_word = if total == 1 { "change" } else { "changes" };
+                format!("{}{} {}{}", palette.fg(color::ORANGE), total, change_word, RESET)
+            }
+            _ => String::new(),
+        };
+        let pad = (area.width as usize).saturating_sub(strip_ansi_len(&summary));
+        write!(stdout, "{}{:pad$}", summary, "", pad = pad)?;
+        return Ok(());
+    }
 
     // For row == 1, show header: "Language, N changes" on left, terminal title on right
     if area.row == 1 {
         // Build left side: language + count or loading indicator
         let left = if diff_summary.loading {
-            format!("{}Changes{} {}...{}", fg(color::ORANGE), RESET, fg(color::GRAY), RESET)
+            format!("{}Changes{} {}...{}", palette.fg(color::ORANGE), RESET, palette.fg(color::GRAY), RESET)
         } else if let Some(first_lang) = by_language.first() {
             let total: usize = by_language.iter().map(|l| l.changes.len()).sum();
+            let tests: usize = by_language.iter().map(|l| count_tests(&l.changes)).sum();
             let change_word = if total == 1 { "change" } else { "changes" };
+            let words = if show_word_count {
+                format_word_count(diff_summary.total_words())
+            } else {
+                String::new()
+            };
             format!(
-                "{}{}{} {}{} {}{}",
-                fg(color::ORANGE),
+                "{}{}{} {}{} {}{}{}{}",
+                palette.fg(color::ORANGE),
                 first_lang.language,
                 RESET,
-                fg(color::GRAY),
+                palette.fg(color::GRAY),
                 total,
                 change_word,
+                format_test_count(tests),
+                words,
                 RESET
             )
         } else {
@@ -127,7 +186,7 @@ pub fn draw_changes_widget(
 
         // Build right side: terminal title if available (light blue for subtle distinction)
         let right = terminal_title
-            .map(|t| format!("{}{}{}", fg(color::LIGHT_BLUE), t, RESET))
+            .map(|t| format!("{}{}{}", palette.fg(color::LIGHT_BLUE), t, RESET))
             .unwrap_or_default();
         let right_len = strip_ansi_len(&right);
 
@@ -142,7 +201,7 @@ pub fn draw_changes_widget(
     }
 
     // Build rows to display
-    let rows_data = build_rows_for_display(&by_language, area.width, area.height, ide, cwd);
+    let rows_data = build_rows_for_display(&by_language, area.width, area.height, ide, cwd, palette);
 
     // Row index (0-based from row 1)
     let row_idx = (area.row - 1) as usize;
@@ -160,6 +219,32 @@ pub fn draw_changes_widget(
     Ok(())
 }
 
+/// Changes in test files
+fn count_tests(changes: &[ChangeNode]) -> usize {
+    changes.iter().filter(|c| c.is_test()).count()
+}
+
+/// ` (1 test)` header suffix, empty without test changes
+fn format_test_count(tests: usize) -> String {
+    match tests {
+        0 => String::new(),
+        1 => " (1 test)".to_string(),
+        n => format!(" ({} tests)", n),
+    }
+}
+
+/// Marker in front of changes in test files
+const TEST_MARKER: &str = "T";
+
+/// ` · ~340 words` header suffix, empty for small diffs
+fn format_word_count(words: usize) -> String {
+    if words > WORD_COUNT_THRESHOLD {
+        format!(" · ~{} words", words)
+    } else {
+        String::new()
+    }
+}
+
 /// Formatted item with its display width
 struct FormattedItem {
     text: String,
@@ -173,6 +258,7 @@ fn build_rows_for_display(
     height: u16,
     ide: IdeKind,
     cwd: &Path,
+    palette: &Palette,
 ) -> Vec<String> {
     let mut rows = Vec::new();
     let available_rows = height.saturating_sub(2) as usize; // -2 for separator and first row
@@ -199,7 +285,8 @@ fn build_rows_for_display(
         // Add language header
         let count = lang_changes.changes.len();
         let label = if count == 1 { "change" } else { "changes" };
-        let header = format_header(&lang_changes.language, count, label, width as usize);
+        let tests = count_tests(&lang_changes.changes);
+        let header = format_header(&lang_changes.language, count, label, tests, width as usize, palette);
         rows.push(header);
 
         if rows.len() >= available_rows {
@@ -216,18 +303,28 @@ fn build_rows_for_display(
             let overhead = 5 + stats_widths.total_width();
             let name_width = (width as usize).saturating_sub(overhead).max(10);
 
+            // Only expand child nodes when every parent and child gets its own row
+            let rows_with_children: usize = sorted_changes.iter().map(|c| 1 + c.children.len()).sum();
+            let show_children = rows_with_children <= remaining_rows;
+
             for change in &sorted_changes {
                 if rows.len() >= available_rows {
                     break;
                 }
-                let item = format_change_entry(change, name_width, &stats_widths, ide, cwd);
+                let item = format_change_entry(change, 0, show_children, name_width, &stats_widths, ide, cwd, palette);
                 rows.push(item);
+
+                if show_children {
+                    for child in &change.children {
+                        rows.push(format_change_entry(child, 1, true, name_width, &stats_widths, ide, cwd, palette));
+                    }
+                }
             }
         } else {
             // Too many changes - use ragged/wrapped display
             let items: Vec<FormattedItem> = sorted_changes
                 .iter()
-                .map(|c| format_change_compact(c, ide, cwd))
+                .map(|c| format_change_compact(c, ide, cwd, palette))
                 .collect();
 
             // Pack items into rows with 2-space margin
@@ -241,7 +338,7 @@ fn build_rows_for_display(
                     if remaining > 0 {
                         rows.push(format!(
                             "{}  ... and {} more{}",
-                            fg(color::DARK_GRAY),
+                            palette.fg(color::DARK_GRAY),
                             remaining,
                             RESET
                         ));
@@ -258,16 +355,17 @@ fn build_rows_for_display(
 }
 
 /// Format a language header row
-fn format_header(language: &str, count: usize, label: &str, width: usize) -> String {
-    // Match the first row format: "Language N changes" with count in gray
+fn format_header(language: &str, count: usize, label: &str, tests: usize, width: usize, palette: &Palette) -> String {
+    // Match the first row format: "Language N changes (M tests)" with count in gray
     let content = format!(
-        "{}{}{} {}{} {}{}",
-        fg(color::ORANGE),
+        "{}{}{} {}{} {}{}{}",
+        palette.fg(color::ORANGE),
         language,
         RESET,
-        fg(color::GRAY),
+        palette.fg(color::GRAY),
         count,
         label,
+        format_test_count(tests),
         RESET
     );
     let content_len = strip_ansi_len(&content);
@@ -276,14 +374,22 @@ fn format_header(language: &str, count: usize, label: &str, width: usize) -> Str
 }
 
 /// Format a single change entry with aligned columns (for one-per-row display)
+///
+/// `depth` indents child nodes beneath their parent; the name column shrinks
+/// by the indent so stats stay aligned. When children aren't expanded onto
+/// their own rows, a dim "+N children" count follows the name instead.
+#[allow(clippy::too_many_arguments)]
 fn format_change_entry(
     change: &ChangeNode,
+    depth: usize,
+    children_expanded: bool,
     name_width: usize,
     stats_widths: &StatsColumnWidths,
     ide: IdeKind,
     cwd: &Path,
+    palette: &Palette,
 ) -> String {
-    let (icon, icon_color) = get_kind_icon(&change.kind);
+    let (icon, icon_color) = get_kind_icon(change);
 
     // Modifier for change type: + for added, ~ for modified, - for deleted
     let (modifier, modifier_color) = match change.change_type {
@@ -292,9 +398,25 @@ fn format_change_entry(
         ChangeType::Deleted => ("-", color::RED),
     };
 
-    let name = truncate_middle(&change.name, name_width);
-    let name_char_count = name.chars().count();
-    let name_padding = name_width.saturating_sub(name_char_count);
+    let indent = "  ".repeat(depth);
+    // Children share their parent's file, so only the parent is marked
+    let test_marker = if depth == 0 && change.is_test() {
+        format!("{}{}{}", palette.fg(color::LIGHT_CYAN), TEST_MARKER, RESET)
+    } else {
+        String::new()
+    };
+    let child_count = if children_expanded { 0 } else { change.children.len() };
+    let child_suffix = match child_count {
+        0 => String::new(),
+        1 => " +1 child".to_string(),
+        n => format!(" +{} children", n),
+    };
+    // Wide icons take room from the name so stats stay aligned
+    let icon_extra = display_width(icon).saturating_sub(1);
+    let marker_width = if test_marker.is_empty() { 0 } else { TEST_MARKER.len() };
+    let name_width = name_width.saturating_sub(indent.len() + marker_width + child_suffix.len() + icon_extra);
+    let name = truncate_middle(&display_name(change), name_width);
+    let name_padding = name_width.saturating_sub(display_width(&name));
 
     // Wrap name in hyperlink if we have file path info
     let linked_name = if let Some(ref path) = change.file_path {
@@ -311,20 +433,25 @@ fn format_change_entry(
         change.deletions,
         stats_widths.del_num,
         stats_widths.add_num,
+        palette,
     );
 
     format!(
-        "{}{}{}{}{}{} {}{:pad$}{}",
-        fg(modifier_color), modifier, RESET,
-        fg(icon_color), icon, RESET,
-        linked_name, "", stats,
+        "{}{}{}{}{}{}{}{} {}{}{}{}{:pad$}{}",
+        indent,
+        test_marker,
+        palette.fg(modifier_color), modifier, RESET,
+        palette.fg(icon_color), icon, RESET,
+        linked_name,
+        palette.fg(color::DARK_GRAY), child_suffix, RESET,
+        "", stats,
         pad = name_padding
     )
 }
 
 /// Format a compact change entry (for ragged/wrapped display)
-fn format_change_compact(change: &ChangeNode, ide: IdeKind, cwd: &Path) -> FormattedItem {
-    let (icon, icon_color) = get_kind_icon(&change.kind);
+fn format_change_compact(change: &ChangeNode, ide: IdeKind, cwd: &Path, palette: &Palette) -> FormattedItem {
+    let (icon, icon_color) = get_kind_icon(change);
 
     let (modifier, modifier_color) = match change.change_type {
         ChangeType::Added => ("+", color::GREEN),
@@ -333,7 +460,12 @@ fn format_change_compact(change: &ChangeNode, ide: IdeKind, cwd: &Path) -> Forma
     };
 
     // Truncate name for compact display
-    let name = truncate_middle(&change.name, 20);
+    let name = truncate_middle(&display_name(change), 20);
+    let (test_marker, marker_width) = if change.is_test() {
+        (format!("{}{}{}", palette.fg(color::LIGHT_CYAN), TEST_MARKER, RESET), TEST_MARKER.len())
+    } else {
+        (String::new(), 0)
+    };
 
     // Wrap name in hyperlink if we have file path info
     let linked_name = if let Some(ref path) = change.file_path {
@@ -347,12 +479,12 @@ fn format_change_compact(change: &ChangeNode, ide: IdeKind, cwd: &Path) -> Forma
     // Compact stats (no alignment)
     let stats = if change.additions > 0 || change.deletions > 0 {
         let del = if change.deletions > 0 {
-            format!("{}−{}{}", fg(color::RED), change.deletions, RESET)
+            format!("{}−{}{}", palette.fg(color::RED), change.deletions, RESET)
         } else {
             String::new()
         };
         let add = if change.additions > 0 {
-            format!("{}+{}{}", fg(color::GREEN), change.additions, RESET)
+            format!("{}+{}{}", palette.fg(color::GREEN), change.additions, RESET)
         } else {
             String::new()
         };
@@ -362,9 +494,10 @@ fn format_change_compact(change: &ChangeNode, ide: IdeKind, cwd: &Path) -> Forma
     };
 
     let text = format!(
-        "{}{}{}{}{}{}{}{}",
-        fg(modifier_color), modifier, RESET,
-        fg(icon_color), icon, RESET,
+        "{}{}{}{}{}{}{}{}{}",
+        test_marker,
+        palette.fg(modifier_color), modifier, RESET,
+        palette.fg(icon_color), icon, RESET,
         linked_name, stats
     );
 
@@ -375,7 +508,8 @@ fn format_change_compact(change: &ChangeNode, ide: IdeKind, cwd: &Path) -> Forma
     } else {
         0
     };
-    let width = 1 + 1 + name.chars().count() + stats_width; // modifier + icon + name + stats
+    // marker + modifier + icon + name + stats
+    let width = marker_width + 1 + display_width(icon) + display_width(&name) + stats_width;
 
     FormattedItem { text, width }
 }
@@ -386,6 +520,7 @@ fn format_change_stats(
     deletions: usize,
     del_width: usize,
     add_width: usize,
+    palette: &Palette,
 ) -> String {
     if additions == 0 && deletions == 0 {
         // No stats to show - just padding
@@ -401,13 +536,13 @@ fn format_change_stats(
     let add_padding = add_width.saturating_sub(add_num_width);
 
     let del_str = if deletions > 0 {
-        format!("{}−{}{}", fg(color::RED), deletions, RESET)
+        format!("{}−{}{}", palette.fg(color::RED), deletions, RESET)
     } else {
         String::new()
     };
 
     let add_str = if additions > 0 {
-        format!("{}+{}{}", fg(color::GREEN), additions, RESET)
+        format!("{}+{}{}", palette.fg(color::GREEN), additions, RESET)
     } else {
         String::new()
     };
@@ -469,3 +604,141 @@ fn pack_items_into_rows(items: &[FormattedItem], max_width: usize) -> Vec<Packed
 
     rows
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{CssParser, DiffParser};
+    use crate::ui::utils::strip_ansi;
+
+    #[test]
+    fn test_css_changes_render_with_selector_icons() {
+        let diff = "\
+@@ -1,6 +1,7 @@
+ .card {
+-  padding: 4px;
++  padding: 8px;
+ }
++@media (max-width: 600px) {
++  .card { padding: 2px; }
++}
+";
+        let changes = CssParser.parse(diff, "styles.css");
+        let by_language = vec![LanguageChanges {
+            language: CssParser.language().to_string(),
+            changes,
+        }];
+
+        let rows = build_rows_for_display(&by_language, 60, 10, IdeKind::None, Path::new("/repo"), &Palette::default());
+        let plain: Vec<String> = rows.iter().map(|r| strip_ansi(r)).collect();
+
+        assert!(plain[0].starts_with("CSS/SCSS 2 changes"));
+        // The nested .card rule inside @media merges into the top-level .card
+        assert!(plain.iter().any(|r| r.starts_with("~{ .card")));
+        assert!(plain.iter().any(|r| r.starts_with("+◈ @media (max-width: 600px)")));
+    }
+
+    #[test]
+    fn test_binary_files_render_with_package_icon() {
+        let edited = ChangeNode { additions: 3, ..ChangeNode::fixture("add", "src/lib.rs") };
+        let by_language = vec![LanguageChanges {
+            language: "Other".to_string(),
+            changes: vec![ChangeNode::binary("assets/logo.png"), edited],
+        }];
+
+        let rows = build_rows_for_display(&by_language, 60, 10, IdeKind::None, Path::new("/repo"), &Palette::default());
+        let plain: Vec<String> = rows.iter().map(|r| strip_ansi(r)).collect();
+
+        let binary = plain.iter().find(|r| r.contains("📦")).expect("binary row");
+        assert!(binary.starts_with("~📦 logo.png (binary)"), "{:?}", plain);
+        // The wide icon doesn't push the row past the ones around it
+        let function = plain.iter().find(|r| r.contains("ƒ add")).expect("function row");
+        assert_eq!(display_width(binary), display_width(function));
+    }
+
+    #[test]
+    fn test_selectors_sort_after_code_constructs() {
+        assert!(kind_priority(&NodeKind::Selector) > kind_priority(&NodeKind::Const));
+        assert!(kind_priority(&NodeKind::MediaQuery) > kind_priority(&NodeKind::Function));
+        assert!(kind_priority(&NodeKind::Other) > kind_priority(&NodeKind::Selector));
+    }
+
+    #[test]
+    fn test_header_shows_word_count_when_enabled() {
+        let diff = "\
+@@ -0,0 +1 @@
++.card { padding: 8px; }
+";
+        let summary = DiffSummary {
+            files: vec![crate::parsers::FileChanges {
+                path: "styles.css".to_string(),
+                language: CssParser.language().to_string(),
+                changes: CssParser.parse(diff, "styles.css"),
+                is_test: false,
+            }],
+            total_words_added: 300,
+            total_words_deleted: 40,
+            ..Default::default()
+        };
+        let area = WidgetArea { pty_rows: 0, col: 0, row: 1, width: 60, height: 5 };
+        let header = |summary: &DiffSummary, show: bool| {
+            let mut buf = Vec::new();
+            draw_changes_widget(
+                &mut buf,
+                area,
+                summary,
+                None,
+                IdeKind::None,
+                Path::new("/repo"),
+                show,
+                false,
+                WidgetMode::Normal,
+                &Palette::default(),
+            )
+            .unwrap();
+            strip_ansi(&String::from_utf8(buf).unwrap()).trim().to_string()
+        };
+
+        assert_eq!(header(&summary, true), "CSS/SCSS 1 change · ~340 words");
+        assert_eq!(header(&summary, false), "CSS/SCSS 1 change");
+        let small = DiffSummary { total_words_added: 60, total_words_deleted: 40, ..summary.clone() };
+        assert_eq!(header(&small, true), "CSS/SCSS 1 change");
+    }
+
+    #[test]
+    fn test_file_changes_are_marked_counted_and_hideable() {
+        let function = |name: &str, path: &str| ChangeNode { additions: 4, ..ChangeNode::fixture(name, path) };
+        let file = |path: &str, changes: Vec<ChangeNode>| crate::parsers::FileChanges {
+            path: path.to_string(),
+            language: "Rust".to_string(),
+            changes,
+            is_test: path.ends_with("_test.rs"),
+        };
+        let summary = DiffSummary {
+            files: vec![
+                file("src/parse.rs", vec![function("parse", "src/parse.rs"), function("lex", "src/parse.rs")]),
+                file("src/parse_test.rs", vec![function("parses_empty", "src/parse_test.rs")]),
+            ],
+            ..Default::default()
+        };
+        let render = |row: u16, hide: bool| {
+            let area = WidgetArea { pty_rows: 0, col: 0, row, width: 60, height: 8 };
+            let mut buf = Vec::new();
+            let (cwd, palette) = (Path::new("/repo"), Palette::default());
+            let mode = WidgetMode::Normal;
+            draw_changes_widget(&mut buf, area, &summary, None, IdeKind::None, cwd, false, hide, mode, &palette)
+                .unwrap();
+            strip_ansi(&String::from_utf8(buf).unwrap()).trim().to_string()
+        };
+
+        assert_eq!(render(1, false), "Rust 3 changes (1 test)");
+        let rows: Vec<String> = (2..6).map(|row| render(row, false)).collect();
+        assert!(rows.iter().any(|r| r.starts_with("T~ƒ parses_empty")), "{:?}", rows);
+        assert!(rows.iter().any(|r| r.starts_with("~ƒ parse ")), "{:?}", rows);
+
+        assert_eq!(render(1, true), "Rust 2 changes");
+        let rows: Vec<String> = (2..6).map(|row| render(row, true)).collect();
+        assert!(!rows.iter().any(|r| r.contains("parses_empty")), "{:?}", rows);
+        assert_eq!(summary.test_changes(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+//! Column order and widths for the built-in widgets (`[layout] widgets`)
+//!
+//! Each entry names a widget, optionally with a share of the width:
+//! `widgets = ["git:40", "changes:40", "stats:20"]`. Entries without a
+//! percentage split what the others leave equally.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Narrowest a configured column gets
+pub const MIN_COLUMN_WIDTH: u16 = 10;
+
+/// A built-in status bar widget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WidgetName {
+    Stats,
+    Git,
+    Changes,
+}
+
+impl WidgetName {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "stats" => Some(Self::Stats),
+            "git" => Some(Self::Git),
+            "changes" => Some(Self::Changes),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stats => "stats",
+            Self::Git => "git",
+            Self::Changes => "changes",
+        }
+    }
+}
+
+/// One `layout.widgets` entry: `"git"` or `"git:40"`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WidgetSpec {
+    pub name: WidgetName,
+    /// Share of the width in percent; None splits the remainder
+    pub width_percent: Option<u8>,
+}
+
+impl TryFrom<String> for WidgetSpec {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (name, percent) = match value.split_once(':') {
+            Some((name, percent)) => (name, Some(percent)),
+            None => (value.as_str(), None),
+        };
+        let name = WidgetName::parse(name.trim())
+            .ok_or_else(|| format!("unknown widget `{}` (expected stats, git or changes)", name.trim()))?;
+        let width_percent = match percent {
+            Some(percent) => match percent.trim().parse::<u8>() {
+                Ok(p) if (1..=100).contains(&p) => Some(p),
+                _ => return Err(format!("`{}`: width must be a percentage from 1 to 100", value)),
+            },
+            None => None,
+        };
+        Ok(Self { name, width_percent })
+    }
+}
+
+impl From<WidgetSpec> for String {
+    fn from(spec: WidgetSpec) -> Self {
+        spec.to_string()
+    }
+}
+
+impl fmt::Display for WidgetSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.width_percent {
+            Some(percent) => write!(f, "{}:{}", self.name.as_str(), percent),
+            None => write!(f, "{}", self.name.as_str()),
+        }
+    }
+}
+
+/// Problems with a `layout.widgets` list: repeated widgets, or percentages
+/// adding up to more than 100
+pub fn validate_widget_specs(specs: &[WidgetSpec]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (i, spec) in specs.iter().enumerate() {
+        if specs[..i].iter().any(|s| s.name == spec.name) {
+            errors.push(format!("layout.widgets lists `{}` more than once", spec.name.as_str()));
+        }
+    }
+    let total: u32 = specs.iter().filter_map(|s| s.width_percent).map(u32::from).sum();
+    if total > 100 {
+        errors.push(format!("layout.widgets widths add up to {}% (at most 100%)", total));
+    }
+    errors
+}
+
+/// Width of each column in `specs` out of `available` columns
+///
+/// Percentages take their share (at least `MIN_COLUMN_WIDTH`), entries without
+/// one split the rest equally, and the last column takes any rounding left
+/// over. Too narrow for the minimums, the widest columns shrink first.
+pub fn column_widths(specs: &[WidgetSpec], available: u16) -> Vec<u16> {
+    if specs.is_empty() {
+        return Vec::new();
+    }
+
+    let mut widths: Vec<u16> = specs
+        .iter()
+        .map(|s| match s.width_percent {
+            Some(p) => ((available as u32 * p as u32 / 100) as u16).max(MIN_COLUMN_WIDTH),
+            None => 0,
+        })
+        .collect();
+    let flexible = specs.iter().filter(|s| s.width_percent.is_none()).count() as u16;
+    let fixed: u16 = widths.iter().sum();
+    if let Some(share) = available.saturating_sub(fixed).checked_div(flexible) {
+        let share = share.max(MIN_COLUMN_WIDTH);
+        for (width, spec) in widths.iter_mut().zip(specs) {
+            if spec.width_percent.is_none() {
+                *width = share;
+            }
+        }
+    }
+
+    let total: u16 = widths.iter().sum();
+    if total < available {
+        if let Some(last) = widths.last_mut() {
+            *last += available - total;
+        }
+    } else if total > available {
+        let mut excess = total - available;
+        while excess > 0 {
+            let Some(widest) = widths.iter_mut().filter(|w| **w > 1).max_by_key(|w| **w) else {
+                break;
+            };
+            *widest -= 1;
+            excess -= 1;
+        }
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(entries: &[&str]) -> Vec<WidgetSpec> {
+        entries.iter().map(|e| WidgetSpec::try_from(e.to_string()).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_widget_specs() {
+        let parsed = specs(&["git:40", "changes", "stats:20"]);
+        assert_eq!(parsed[0], WidgetSpec { name: WidgetName::Git, width_percent: Some(40) });
+        assert_eq!(parsed[1], WidgetSpec { name: WidgetName::Changes, width_percent: None });
+        assert_eq!(parsed[2].to_string(), "stats:20");
+
+        assert!(WidgetSpec::try_from("clock".to_string()).unwrap_err().contains("unknown widget `clock`"));
+        assert!(WidgetSpec::try_from("git:0".to_string()).is_err());
+        assert!(WidgetSpec::try_from("git:abc".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_validate_widget_specs() {
+        assert!(validate_widget_specs(&specs(&["git:40", "changes:40", "stats:20"])).is_empty());
+        assert_eq!(validate_widget_specs(&specs(&["git:60", "changes:50"])).len(), 1);
+        assert_eq!(validate_widget_specs(&specs(&["git", "stats", "git"])).len(), 1);
+    }
+
+    #[test]
+    fn test_column_widths_from_percentages() {
+        assert_eq!(column_widths(&specs(&["git:40", "changes:40", "stats:20"]), 100), vec![40, 40, 20]);
+        // Rounding goes to the last column
+        assert_eq!(column_widths(&specs(&["git:40", "changes:40", "stats:20"]), 103), vec![41, 41, 21]);
+    }
+
+    #[test]
+    fn test_column_widths_split_remainder_equally() {
+        assert_eq!(column_widths(&specs(&["stats:20", "git", "changes"]), 100), vec![20, 40, 40]);
+        assert_eq!(column_widths(&specs(&["git", "changes"]), 81), vec![40, 41]);
+        assert_eq!(column_widths(&specs(&["changes"]), 120), vec![120]);
+    }
+
+    #[test]
+    fn test_column_widths_minimum() {
+        // 5% of 100 is below the minimum
+        assert_eq!(column_widths(&specs(&["stats:5", "git", "changes"]), 100), vec![10, 45, 45]);
+        // Percentages leaving nothing still give the flexible column its minimum
+        assert_eq!(column_widths(&specs(&["git:50", "changes:50", "stats"]), 100), vec![45, 45, 10]);
+        // Too narrow for every minimum: fill exactly what there is
+        assert_eq!(column_widths(&specs(&["git", "changes", "stats"]), 24).iter().sum::<u16>(), 24);
+    }
+}
@@ -2,17 +2,21 @@
 //!
 //! Shows branch name and file status with change bars.
 //! Automatically uses multiple columns when there are more files than rows.
+//! Staged files are listed first, split from unstaged ones by a separator row.
+//! Submodules get a cyan `⊟` and the number of new commits instead of a bar.
 
-use std::io::{Stdout, Write};
+use std::borrow::Cow;
+use std::io::Write;
 use std::path::Path;
 
 use anyhow::Result;
 
 use crate::ide::IdeKind;
-use crate::terminal::escape::{self, color, fg, hyperlink, RESET};
+use crate::terminal::escape::{self, color, hyperlink, RESET};
+use crate::terminal::theme::Palette;
 use crate::git::{FileStatus, GitState};
-use super::utils::{compute_unique_display_names, create_folder_bar, digit_count, format_diff_stats, format_diff_stats_aligned, get_filename, strip_ansi_len, truncate_path};
-use super::WidgetArea;
+use super::utils::{compute_unique_display_names, create_folder_bar, digit_count, display_width, format_diff_stats, format_diff_stats_aligned, get_filename, strip_ansi_len, truncate_path};
+use super::{WidgetArea, WidgetMode};
 
 /// Dynamic column widths computed from actual file data
 #[derive(Clone, Copy)]
@@ -31,7 +35,7 @@ impl StatsColumnWidths {
         let mut max_add = 0usize;
 
         for file in files {
-            if !file.is_folder {
+            if !file.is_folder && !file.is_submodule {
                 max_del = max_del.max(file.deletions);
                 max_add = max_add.max(file.additions);
             }
@@ -80,19 +84,79 @@ impl StatsColumnWidths {
     }
 }
 
+/// Size used for ranking and bar scaling: file count for folders, lines otherwise
+fn change_weight(file: &FileStatus) -> usize {
+    if file.is_folder {
+        file.file_count
+    } else {
+        file.total_changes()
+    }
+}
+
+/// Apply the `max_git_files` display limit
+///
+/// Under the limit the files keep their git order. Over it, the largest
+/// changes are kept and the number of hidden files is returned alongside.
+fn limit_files(files: &[FileStatus], max_files: Option<usize>) -> (Cow<'_, [FileStatus]>, usize) {
+    match max_files {
+        Some(max) if files.len() > max => {
+            let mut ranked = files.to_vec();
+            ranked.sort_by_key(|f| std::cmp::Reverse(change_weight(f)));
+            ranked.truncate(max);
+            (Cow::Owned(ranked), files.len() - max)
+        }
+        _ => (Cow::Borrowed(files), 0),
+    }
+}
+
+/// List staged files ahead of unstaged ones (keeping order within each group)
+///
+/// Also returns the index of the first unstaged file when both groups are
+/// present, which is where the separator row goes.
+fn group_staged(files: Cow<'_, [FileStatus]>) -> (Cow<'_, [FileStatus]>, Option<usize>) {
+    let staged = files.iter().filter(|f| f.staged).count();
+    if staged == 0 || staged == files.len() {
+        return (files, None);
+    }
+    let mut grouped = files.into_owned();
+    grouped.sort_by_key(|f| !f.staged);
+    (Cow::Owned(grouped), Some(staged))
+}
 
 /// Draw the git widget at the given position
+///
+/// `max_files` caps the listed files (the header still counts all of them).
+#[allow(clippy::too_many_arguments)]
 pub fn draw_git_widget(
-    stdout: &mut Stdout,
+    stdout: &mut impl Write,
     area: WidgetArea,
     git_state: &GitState,
+    max_files: Option<usize>,
     ide: IdeKind,
     cwd: &Path,
+    mode: WidgetMode,
+    palette: &Palette,
 ) -> Result<()> {
     write!(stdout, "{}", escape::cursor_to(area.pty_rows + 1 + area.row, area.col + 1))?;
 
     let files = &git_state.files;
 
+    if mode == WidgetMode::Compact {
+        // Narrow terminal: just the file count on the header row
+        let summary = match area.row {
+            1 if git_state.loading => format!("{}...{}", palette.fg(color::GRAY), RESET),
+            1 if files.is_empty() => format!("{}✓ Clean{}", palette.fg(color::GREEN), RESET),
+            1 => {
+                let label = if files.len() == 1 { "file" } else { "files" };
+                format!("{}{} {}{}", palette.fg(color::YELLOW), files.len(), label, RESET)
+            }
+            _ => String::new(),
+        };
+        let pad = (area.width as usize).saturating_sub(strip_ansi_len(&summary));
+        write!(stdout, "{}{:pad$}", summary, "", pad = pad)?;
+        return Ok(());
+    }
+
     if area.row == 1 {
         // Header with branch name on left, status on right
         let branch = if git_state.branch.is_empty() {
@@ -100,18 +164,34 @@ pub fn draw_git_widget(
         } else {
             &git_state.branch
         };
-        let left = format!("{} {}{}", fg(color::LIGHT_GREEN), truncate_path(branch, 15), RESET);
+        let branch = truncate_path(branch, 15);
+        // Branch name links to its PR or tree page on the remote
+        let branch = match &git_state.branch_url {
+            Some(url) => hyperlink(url, &branch),
+            None => branch,
+        };
+        let worktree = if git_state.is_worktree {
+            format!(" {}(worktree){}", palette.fg(color::GRAY), RESET)
+        } else {
+            String::new()
+        };
+        let left = format!("{} {}{}{}", palette.fg(color::LIGHT_GREEN), branch, RESET, worktree);
         let left_len = strip_ansi_len(&left);
 
         // Right side: loading, "✓ Clean", or file count
         let right = if git_state.loading {
-            format!("{}...{}", fg(color::GRAY), RESET)
+            format!("{}...{}", palette.fg(color::GRAY), RESET)
         } else if files.is_empty() {
-            format!("{}✓ Clean{}", fg(color::GREEN), RESET)
+            format!("{}✓ Clean{}", palette.fg(color::GREEN), RESET)
         } else {
             let count = files.len();
-            let label = if count == 1 { "file" } else { "files" };
-            format!("{}{} {}{}", fg(color::YELLOW), count, label, RESET)
+            let staged = files.iter().filter(|f| f.staged).count();
+            if staged > 0 && staged < count {
+                format!("{}{} staged, {} unstaged{}", palette.fg(color::YELLOW), staged, count - staged, RESET)
+            } else {
+                let label = if count == 1 { "file" } else { "files" };
+                format!("{}{} {}{}", palette.fg(color::YELLOW), count, label, RESET)
+            }
         };
         let right_len = strip_ansi_len(&right);
 
@@ -126,14 +206,17 @@ pub fn draw_git_widget(
         return Ok(());
     }
 
-    // Compute unique display names for all files
-    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
-    let display_names = compute_unique_display_names(&paths);
+    let (files, hidden) = limit_files(files, max_files);
+    let (files, first_unstaged) = group_staged(files);
+    let files = files.as_ref();
+    let overflow = (hidden > 0).then(|| format!("{}… and {} more{}", palette.fg(color::DARK_GRAY), hidden, RESET));
+
+    let display_names = display_names(files);
 
     // Calculate max changes for scaling the bar graph
     let max_changes = files
         .iter()
-        .map(|f| if f.is_folder { f.file_count } else { f.total_changes() })
+        .map(change_weight)
         .max()
         .unwrap_or(1)
         .max(1);
@@ -144,17 +227,38 @@ pub fn draw_git_widget(
     // Available data rows (subtract 2: one for separator row 0, one for header row 1)
     let available_rows = area.height.saturating_sub(2) as usize;
     let num_files = files.len();
+    // The "… and N more" indicator takes the slot after the last file
+    let num_entries = num_files + usize::from(overflow.is_some());
 
     // Row index (0-based, row 2 = index 0)
     let row_idx = (area.row - 2) as usize;
 
-    // Decide layout: columns or single-line
-    if available_rows > 0 && num_files <= available_rows {
-        // Single column - simple case
-        if row_idx < num_files {
+    // Decide layout: columns or single-line (the separator doesn't count here)
+    if available_rows > 0 && num_entries <= available_rows {
+        // Single column - simple case. The staged/unstaged separator gets its
+        // own row when there's one to spare; otherwise the groups just abut.
+        let separator_at = first_unstaged.filter(|_| num_entries < available_rows);
+        let row_idx = match separator_at {
+            Some(at) if row_idx == at => {
+                let label = truncate_path("── staged / unstaged ──", area.width as usize);
+                let pad = (area.width as usize).saturating_sub(display_width(&label));
+                write!(stdout, "{}{}{}{:pad$}", palette.fg(color::DARK_GRAY), label, RESET, "", pad = pad)?;
+                return Ok(());
+            }
+            Some(at) if row_idx > at => row_idx - 1,
+            _ => row_idx,
+        };
+        let item = if row_idx < num_files {
             let file = &files[row_idx];
             let display_name = &display_names[row_idx];
-            let item = format_file_entry(file, display_name, area.width as usize, max_changes, &stats_widths, ide, cwd);
+            let width = area.width as usize;
+            Some(format_file_entry(file, display_name, width, max_changes, &stats_widths, ide, cwd, palette))
+        } else if row_idx == num_files {
+            overflow.clone()
+        } else {
+            None
+        };
+        if let Some(item) = item {
             write!(stdout, "{}", item)?;
             let content_len = strip_ansi_len(&item);
             let pad = (area.width as usize).saturating_sub(content_len);
@@ -164,23 +268,24 @@ pub fn draw_git_widget(
         }
     } else if available_rows > 0 {
         // Multi-column layout
-        let num_cols = num_files.div_ceil(available_rows);
+        let num_cols = num_entries.div_ceil(available_rows);
 
         // Pre-compute natural widths for all entries (without truncation)
         let natural_widths: Vec<usize> = files
             .iter()
             .enumerate()
             .map(|(i, file)| {
-                let entry = format_file_entry_natural(file, &display_names[i], max_changes, &stats_widths);
+                let entry = format_file_entry_natural(file, &display_names[i], max_changes, &stats_widths, palette);
                 strip_ansi_len(&entry)
             })
+            .chain(overflow.iter().map(|o| strip_ansi_len(o)))
             .collect();
 
         // Calculate column widths based on actual content
         let mut col_widths: Vec<usize> = vec![0; num_cols];
         for (col_idx, col_width) in col_widths.iter_mut().enumerate() {
             let start = col_idx * available_rows;
-            let end = (start + available_rows).min(num_files);
+            let end = (start + available_rows).min(num_entries);
             for nw in natural_widths.iter().take(end).skip(start) {
                 *col_width = (*col_width).max(*nw);
             }
@@ -197,7 +302,7 @@ pub fn draw_git_widget(
             // Apply extra space to column widths (cap name portion at 30 chars)
             for (col_idx, col_width) in col_widths.iter_mut().enumerate() {
                 let max_name_in_col = (col_idx * available_rows..((col_idx + 1) * available_rows).min(num_files))
-                    .map(|i| display_names[i].chars().count())
+                    .map(|i| display_width(&display_names[i]))
                     .max()
                     .unwrap_or(0);
                 // Name width = col_width - 11 (icon + spaces + max bar)
@@ -214,10 +319,17 @@ pub fn draw_git_widget(
             let mut output = String::new();
             for (col_idx, col_width) in col_widths.iter().enumerate() {
                 let file_idx = col_idx * available_rows + row_idx;
-                if file_idx < num_files {
+                let item = if file_idx < num_files {
                     let file = &files[file_idx];
                     let display_name = &display_names[file_idx];
-                    let item = format_file_entry(file, display_name, *col_width, max_changes, &stats_widths, ide, cwd);
+                    let width = *col_width;
+                    Some(format_file_entry(file, display_name, width, max_changes, &stats_widths, ide, cwd, palette))
+                } else if file_idx == num_files {
+                    overflow.clone()
+                } else {
+                    None
+                };
+                if let Some(item) = item {
                     let item_len = strip_ansi_len(&item);
                     output.push_str(&item);
                     // Pad to column width
@@ -235,7 +347,8 @@ pub fn draw_git_widget(
             let items: Vec<String> = files
                 .iter()
                 .enumerate()
-                .map(|(i, file)| format_file_compact(file, &display_names[i], max_changes, ide, cwd))
+                .map(|(i, file)| format_file_compact(file, &display_names[i], max_changes, ide, cwd, palette))
+                .chain(overflow.clone())
                 .collect();
             let item_widths: Vec<usize> = items.iter().map(|s| strip_ansi_len(s)).collect();
 
@@ -290,22 +403,62 @@ pub fn draw_git_widget(
     Ok(())
 }
 
+/// Minimal unique display names (see `compute_unique_display_names`);
+/// submodules keep their whole path and don't take part
+fn display_names(files: &[FileStatus]) -> Vec<String> {
+    let paths: Vec<&str> = files.iter().filter(|f| !f.is_submodule).map(|f| f.path.as_str()).collect();
+    let mut unique = compute_unique_display_names(&paths).into_iter();
+    files
+        .iter()
+        .map(|f| if f.is_submodule { f.path.clone() } else { unique.next().unwrap_or_default() })
+        .collect()
+}
+
+/// `3 commits` for a submodule at other commits, or `modified` when only its
+/// content changed
+fn submodule_label(file: &FileStatus, palette: &Palette) -> String {
+    let text = match file.submodule_commits {
+        Some(1) => "1 commit".to_string(),
+        Some(n) => format!("{} commits", n),
+        None => "modified".to_string(),
+    };
+    format!("{}{}{}", palette.fg(color::CYAN), text, RESET)
+}
+
 /// Format a file entry compactly (icon + name + stats) for wrapped mode
-fn format_file_compact(file: &FileStatus, display_name: &str, max_changes: usize, ide: IdeKind, cwd: &Path) -> String {
-    let (icon, icon_color) = get_status_icon_color(&file.status);
+fn format_file_compact(
+    file: &FileStatus,
+    display_name: &str,
+    max_changes: usize,
+    ide: IdeKind,
+    cwd: &Path,
+    palette: &Palette,
+) -> String {
+    let (icon, icon_color) = file_icon_color(file);
 
-    if file.is_folder {
+    if file.is_submodule {
+        format!("{}{}{}{} {}", palette.fg(icon_color), icon, RESET, display_name, submodule_label(file, palette))
+    } else if file.is_folder {
         let folder_name = get_filename(file.path.trim_end_matches('/'));
-        let bar = create_folder_bar(file.file_count, max_changes, 4);
-        format!("{}{}{}{}/ {}", fg(icon_color), icon, RESET, folder_name, bar)
+        let bar = create_folder_bar(file.file_count, max_changes, 4, palette);
+        format!("{}{}{}{}/ {}", palette.fg(icon_color), icon, RESET, folder_name, bar)
     } else {
         // Compact: just numbers, no bar
-        let stats = format_diff_stats(file.additions, file.deletions, max_changes, 0);
+        let stats = format_diff_stats(file.additions, file.deletions, max_changes, 0, palette);
         // Make file name a clickable hyperlink
         let abs_path = cwd.join(&file.path).to_string_lossy().to_string();
         let url = ide.file_url(&abs_path, None);
         let linked_name = hyperlink(&url, display_name);
-        format!("{}{}{}{} {}", fg(icon_color), icon, RESET, linked_name, stats)
+        format!("{}{}{}{} {}", palette.fg(icon_color), icon, RESET, linked_name, stats)
+    }
+}
+
+/// Icon and color for an entry: `⊟` for submodules, otherwise by status
+fn file_icon_color(file: &FileStatus) -> (&'static str, u8) {
+    if file.is_submodule {
+        ("⊟", color::CYAN)
+    } else {
+        get_status_icon_color(&file.status)
     }
 }
 
@@ -322,20 +475,28 @@ fn get_status_icon_color(status: &str) -> (&'static str, u8) {
 
 /// Format a file entry at its natural width (no truncation) to measure actual size
 #[allow(unused_variables)]
-fn format_file_entry_natural(file: &FileStatus, display_name: &str, max_changes: usize, stats_widths: &StatsColumnWidths) -> String {
-    let (icon, icon_color) = get_status_icon_color(&file.status);
-
-    if file.is_folder {
+fn format_file_entry_natural(
+    file: &FileStatus,
+    display_name: &str,
+    max_changes: usize,
+    stats_widths: &StatsColumnWidths,
+    palette: &Palette,
+) -> String {
+    let (icon, icon_color) = file_icon_color(file);
+
+    if file.is_submodule {
+        format!("{}{}{} {} {}", palette.fg(icon_color), icon, RESET, display_name, submodule_label(file, palette))
+    } else if file.is_folder {
         let folder_name = get_filename(file.path.trim_end_matches('/'));
         let count_display = if file.file_count == 0 {
-            format!("{}0 files{}", fg(color::DARK_GRAY), RESET)
+            format!("{}0 files{}", palette.fg(color::DARK_GRAY), RESET)
         } else {
-            format!("{}{} files{}", fg(color::GRAY), file.file_count, RESET)
+            format!("{}{} files{}", palette.fg(color::GRAY), file.file_count, RESET)
         };
-        let bar = create_folder_bar(file.file_count, max_changes, 8);
+        let bar = create_folder_bar(file.file_count, max_changes, 8, palette);
         format!(
             "{}{}{} {}/ {} {}",
-            fg(icon_color), icon, RESET, folder_name, count_display, bar
+            palette.fg(icon_color), icon, RESET, folder_name, count_display, bar
         )
     } else {
         // Natural width with aligned stats columns
@@ -347,34 +508,54 @@ fn format_file_entry_natural(file: &FileStatus, display_name: &str, max_changes:
             stats_widths.del_bar,
             stats_widths.add_bar,
             stats_widths.add_num,
+            palette,
         );
         format!(
             "{}{}{} {} {}",
-            fg(icon_color), icon, RESET, display_name, stats
+            palette.fg(icon_color), icon, RESET, display_name, stats
         )
     }
 }
 
 /// Format a single file entry for display
-#[allow(unused_variables)]
-fn format_file_entry(file: &FileStatus, display_name: &str, col_width: usize, max_changes: usize, stats_widths: &StatsColumnWidths, ide: IdeKind, cwd: &Path) -> String {
+#[allow(unused_variables, clippy::too_many_arguments)]
+fn format_file_entry(
+    file: &FileStatus,
+    display_name: &str,
+    col_width: usize,
+    max_changes: usize,
+    stats_widths: &StatsColumnWidths,
+    ide: IdeKind,
+    cwd: &Path,
+    palette: &Palette,
+) -> String {
     // Status icon
-    let (icon, icon_color) = get_status_icon_color(&file.status);
+    let (icon, icon_color) = file_icon_color(file);
 
-    if file.is_folder {
+    if file.is_submodule {
+        // Submodule display: "⊟ vendor/lib[padded] 3 commits"
+        let label = submodule_label(file, palette);
+        let name_width = col_width.saturating_sub(3 + strip_ansi_len(&label));
+        let truncated_name = truncate_path(display_name, name_width);
+        let name_padding = name_width.saturating_sub(display_width(&truncated_name));
+        format!(
+            "{}{}{} {}{:pad$} {}",
+            palette.fg(icon_color), icon, RESET, truncated_name, "", label, pad = name_padding
+        )
+    } else if file.is_folder {
         // Folder display: "? folder_name/ N files +++++"
         let folder_name = get_filename(file.path.trim_end_matches('/'));
         let folder_display = format!("{}/", folder_name);
 
         // File count in gray, or dim if 0
         let count_display = if file.file_count == 0 {
-            format!("{}0 files{}", fg(color::DARK_GRAY), RESET)
+            format!("{}0 files{}", palette.fg(color::DARK_GRAY), RESET)
         } else {
-            format!("{}{} files{}", fg(color::GRAY), file.file_count, RESET)
+            format!("{}{} files{}", palette.fg(color::GRAY), file.file_count, RESET)
         };
 
         // Create folder bar (cyan) scaled relative to max
-        let bar = create_folder_bar(file.file_count, max_changes, 8);
+        let bar = create_folder_bar(file.file_count, max_changes, 8, palette);
 
         // Calculate available width for folder name
         let count_len = if file.file_count == 0 { 7 } else { format!("{} files", file.file_count).len() };
@@ -383,7 +564,7 @@ fn format_file_entry(file: &FileStatus, display_name: &str, col_width: usize, ma
 
         format!(
             "{}{}{} {} {} {}",
-            fg(icon_color), icon, RESET, truncated_folder, count_display, bar
+            palette.fg(icon_color), icon, RESET, truncated_folder, count_display, bar
         )
     } else {
         // Regular file display with aligned stats columns
@@ -394,8 +575,7 @@ fn format_file_entry(file: &FileStatus, display_name: &str, col_width: usize, ma
         let truncated_name = truncate_path(display_name, name_width);
 
         // Pad filename to fixed width so stats columns align
-        let name_char_count = truncated_name.chars().count();
-        let name_padding = name_width.saturating_sub(name_char_count);
+        let name_padding = name_width.saturating_sub(display_width(&truncated_name));
 
         // Make file name a clickable hyperlink
         let abs_path = cwd.join(&file.path).to_string_lossy().to_string();
@@ -411,11 +591,177 @@ fn format_file_entry(file: &FileStatus, display_name: &str, col_width: usize, ma
             stats_widths.del_bar,
             stats_widths.add_bar,
             stats_widths.add_num,
+            palette,
         );
 
         format!(
             "{}{}{} {}{:pad$} {}",
-            fg(icon_color), icon, RESET, linked_name, "", stats, pad = name_padding
+            palette.fg(icon_color), icon, RESET, linked_name, "", stats, pad = name_padding
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::strip_ansi;
+
+    fn file(path: &str, additions: usize) -> FileStatus {
+        FileStatus::fixture(path, additions, 0)
+    }
+
+    fn files() -> Vec<FileStatus> {
+        [3, 40, 1, 12, 7, 25, 2].iter().enumerate().map(|(i, &n)| file(&format!("f{}.rs", i), n)).collect()
+    }
+
+    fn paths(files: &[FileStatus]) -> Vec<&str> {
+        files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_limit_files_keeps_largest() {
+        let all = files();
+
+        let (shown, hidden) = limit_files(&all, Some(1));
+        assert_eq!(paths(&shown), vec!["f1.rs"]);
+        assert_eq!(hidden, 6);
+
+        let (shown, hidden) = limit_files(&all, Some(5));
+        assert_eq!(paths(&shown), vec!["f1.rs", "f5.rs", "f3.rs", "f4.rs", "f0.rs"]);
+        assert_eq!(hidden, 2);
+    }
+
+    #[test]
+    fn test_limit_files_at_or_above_count_keeps_order() {
+        let all = files();
+        for max in [Some(all.len()), Some(100), None] {
+            let (shown, hidden) = limit_files(&all, max);
+            assert!(matches!(shown, Cow::Borrowed(_)));
+            assert_eq!(paths(&shown), paths(&all));
+            assert_eq!(hidden, 0);
+        }
+    }
+
+    fn staged_file(path: &str, additions: usize) -> FileStatus {
+        FileStatus {
+            staged: true,
+            ..file(path, additions)
+        }
+    }
+
+    fn draw_rows(state: &GitState, height: u16) -> Vec<String> {
+        (1..height)
+            .map(|row| {
+                let mut buf = Vec::new();
+                let area = WidgetArea { pty_rows: 0, col: 0, row, width: 40, height };
+                let cwd = Path::new("/repo");
+                let (mode, palette) = (WidgetMode::Normal, Palette::default());
+                draw_git_widget(&mut buf, area, state, None, IdeKind::None, cwd, mode, &palette).unwrap();
+                strip_ansi(&String::from_utf8(buf).unwrap()).trim_end().to_string()
+            })
+            .collect()
+    }
+
+    fn repo(files: Vec<FileStatus>) -> GitState {
+        GitState {
+            files,
+            branch: "main".to_string(),
+            is_repo: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_staged_files_listed_first_with_separator() {
+        let state = repo(vec![
+            file("dirty.rs", 9),
+            staged_file("ready.rs", 5),
+            file("wip.rs", 3),
+            staged_file("done.rs", 1),
+        ]);
+        let rows = draw_rows(&state, 7);
+
+        assert!(rows[0].ends_with("2 staged, 2 unstaged"));
+        assert!(rows[1].contains("ready.rs"));
+        assert!(rows[2].contains("done.rs"));
+        assert_eq!(rows[3], "── staged / unstaged ──");
+        assert!(rows[4].contains("dirty.rs"));
+        assert!(rows[5].contains("wip.rs"));
+    }
+
+    #[test]
+    fn test_separator_dropped_when_rows_are_full() {
+        // Four files fill the four data rows exactly: no room for the separator,
+        // but the layout stays single-column
+        let state = repo(vec![file("a.rs", 4), staged_file("b.rs", 3), file("c.rs", 2), staged_file("d.rs", 1)]);
+        let rows = draw_rows(&state, 6);
+
+        let listed: Vec<&str> = rows[1..].iter().map(|r| r.split_whitespace().nth(1).unwrap()).collect();
+        assert_eq!(listed, vec!["b.rs", "d.rs", "a.rs", "c.rs"]);
+    }
+
+    #[test]
+    fn test_single_group_keeps_file_count_header() {
+        let rows = draw_rows(&repo(vec![staged_file("a.rs", 2), staged_file("b.rs", 1)]), 5);
+        assert!(rows[0].ends_with("2 files"));
+        assert!(!rows.iter().any(|r| r.contains("staged / unstaged")));
+    }
+
+    #[test]
+    fn test_submodules_show_commits_and_full_path() {
+        let submodule = |path: &str, commits: Option<usize>| FileStatus {
+            is_submodule: true,
+            submodule_commits: commits,
+            ..file(path, 1)
+        };
+        let state = repo(vec![
+            file("docs/lib", 4),
+            submodule("vendor/lib", Some(3)),
+            submodule("third_party/lib", None),
+        ]);
+        let rows = draw_rows(&state, 5);
+
+        // The file keeps its short name even though the submodules end in `lib` too
+        assert!(rows[1].starts_with("● lib "), "{:?}", rows[1]);
+        assert!(rows[2].starts_with("⊟ vendor/lib"), "{:?}", rows[2]);
+        assert!(rows[2].ends_with("3 commits"), "{:?}", rows[2]);
+        assert!(!rows[2].contains('+'), "{:?}", rows[2]);
+        assert!(rows[3].starts_with("⊟ third_party/lib") && rows[3].ends_with("modified"), "{:?}", rows[3]);
+    }
+
+    #[test]
+    fn test_worktree_marked_in_header() {
+        let mut state = repo(vec![file("a.rs", 2)]);
+        assert!(draw_rows(&state, 3)[0].starts_with(" main "));
+        assert!(!draw_rows(&state, 3)[0].contains("(worktree)"));
+
+        state.is_worktree = true;
+        state.worktree_name = Some("feature".to_string());
+        assert!(draw_rows(&state, 3)[0].starts_with(" main (worktree)"));
+    }
+
+    #[test]
+    fn test_overflow_indicator_follows_last_file() {
+        let state = GitState {
+            files: files(),
+            branch: "main".to_string(),
+            is_repo: true,
+            ..Default::default()
+        };
+        let row = |row: u16| {
+            let mut buf = Vec::new();
+            let area = WidgetArea { pty_rows: 0, col: 0, row, width: 40, height: 6 };
+            let cwd = Path::new("/repo");
+            let (mode, palette) = (WidgetMode::Normal, Palette::default());
+            draw_git_widget(&mut buf, area, &state, Some(2), IdeKind::None, cwd, mode, &palette).unwrap();
+            strip_ansi(&String::from_utf8(buf).unwrap())
+        };
+
+        // Header still counts every file
+        assert!(row(1).contains("7 files"));
+        assert!(row(2).contains("f1.rs"));
+        assert!(row(3).contains("f5.rs"));
+        assert_eq!(row(4).trim(), "… and 5 more");
+        assert!(row(5).trim().is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! Help overlay - lists the Ctrl+A prefix bindings
+//!
+//! Shown in place of the status widgets for a few seconds after `Ctrl+A ?`.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::terminal::escape::{self, color, RESET};
+use super::utils::{strip_ansi_len, take_graphemes};
+use super::Layout;
+
+/// Bindings available after pressing Ctrl+A
+const HELP_TEXT: &str = "q=quit, a=Ctrl+A, r=refresh git, t=toggle capture, s=send screen, m=commit msg, \
+                         c=copy stats, u=unset budget, h=help";
+
+/// Temporary overlay listing the prefix key bindings
+pub struct HelpOverlay {
+    text: &'static str,
+    expires_at: Instant,
+}
+
+impl HelpOverlay {
+    /// Show the overlay for `timeout` from now
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            text: HELP_TEXT,
+            expires_at: Instant::now() + timeout,
+        }
+    }
+
+    /// Whether the overlay should still be drawn
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(Instant::now())
+    }
+
+    fn is_active_at(&self, now: Instant) -> bool {
+        now < self.expires_at
+    }
+
+    /// Draw the overlay over the status area (below the separator)
+    pub fn draw(&self, stdout: &mut impl Write, layout: &Layout) -> Result<()> {
+        write!(stdout, "{}", escape::CURSOR_SAVE)?;
+
+        let width = layout.total_cols as usize;
+        for row in 1..layout.status_rows {
+            write!(stdout, "{}", escape::cursor_to(layout.pty_rows + 1 + row, 1))?;
+            let line = if row == 1 {
+                let label = format!("{} Ctrl+A{} then: ", layout.palette.fg(color::LIGHT_BLUE), RESET);
+                let room = width.saturating_sub(strip_ansi_len(&label));
+                let text = take_graphemes(self.text, room);
+                format!("{}{}{}{}", label, layout.palette.fg(color::GRAY), text, RESET)
+            } else {
+                String::new()
+            };
+            let pad = width.saturating_sub(strip_ansi_len(&line));
+            write!(stdout, "{}{:pad$}", line, "", pad = pad)?;
+        }
+
+        write!(stdout, "{}", escape::CURSOR_RESTORE)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::strip_ansi;
+
+    #[test]
+    fn test_overlay_expires_after_timeout() {
+        let overlay = HelpOverlay::new(Duration::from_secs(5));
+        let shown = overlay.expires_at - Duration::from_secs(5);
+
+        assert!(overlay.is_active());
+        assert!(overlay.is_active_at(shown + Duration::from_millis(4999)));
+        assert!(!overlay.is_active_at(shown + Duration::from_secs(5)));
+        assert!(!overlay.is_active_at(shown + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_zero_timeout_is_never_active() {
+        assert!(!HelpOverlay::new(Duration::ZERO).is_active());
+    }
+
+    #[test]
+    fn test_draw_lists_bindings() {
+        let layout = Layout {
+            pty_rows: 20,
+            total_cols: 140,
+            status_rows: 4,
+            max_git_files: None,
+            compact_threshold: 80,
+            separator_style: crate::ui::SeparatorStyle::default(),
+            max_session_minutes: None,
+            token_budget: None,
+            show_word_count: false,
+            hide_test_changes: false,
+            custom_widget_width: 24,
+            widgets: Vec::new(),
+            palette: crate::terminal::theme::Palette::default(),
+        };
+        let mut buf = Vec::new();
+        HelpOverlay::new(Duration::from_secs(5)).draw(&mut buf, &layout).unwrap();
+        let plain = strip_ansi(&String::from_utf8(buf).unwrap());
+
+        assert!(plain.contains(
+            "Ctrl+A then: q=quit, a=Ctrl+A, r=refresh git, t=toggle capture, s=send screen, m=commit msg, \
+             c=copy stats, u=unset budget, h=help"
+        ));
+        // Every status row below the separator is overwritten
+        assert_eq!(plain.chars().count(), 3 * 140);
+    }
+}
@@ -0,0 +1,109 @@
+//! One-line message shown in place of the status widgets
+//!
+//! For a few seconds after `Ctrl+A m` it shows the suggested commit subject
+//! for the staged changes, and after `Ctrl+A c` the copied session stats,
+//! like the help overlay.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::terminal::escape::{self, color, RESET};
+use crate::terminal::theme::Palette;
+use super::utils::{strip_ansi_len, take_graphemes};
+use super::Layout;
+
+/// Temporary overlay with a labelled message (commit suggestion or stats)
+pub struct MessageOverlay {
+    label: &'static str,
+    /// Suggested subject; empty when nothing is staged
+    suggestion: String,
+    /// Whether the suggestion made it onto the clipboard
+    copied: bool,
+    expires_at: Instant,
+}
+
+impl MessageOverlay {
+    /// Show `suggestion` for `timeout` from now
+    pub fn new(suggestion: String, copied: bool, timeout: Duration) -> Self {
+        Self {
+            label: "Commit message",
+            suggestion,
+            copied,
+            expires_at: Instant::now() + timeout,
+        }
+    }
+
+    /// Show the session stats line copied by `Ctrl+A c`
+    pub fn stats(summary: String, copied: bool, timeout: Duration) -> Self {
+        Self {
+            label: "Session stats",
+            ..Self::new(summary, copied, timeout)
+        }
+    }
+
+    /// Whether the overlay should still be drawn
+    pub fn is_active(&self) -> bool {
+        Instant::now() < self.expires_at
+    }
+
+    fn line(&self, width: usize, palette: &Palette) -> String {
+        let label = format!("{} {}{}: ", palette.fg(color::LIGHT_BLUE), self.label, RESET);
+        if self.suggestion.is_empty() {
+            return format!("{}{}nothing staged{}", label, palette.fg(color::GRAY), RESET);
+        }
+        let note = if self.copied { " (copied)" } else { " (clipboard unavailable)" };
+        let room = width.saturating_sub(strip_ansi_len(&label) + note.len());
+        let text = take_graphemes(&self.suggestion, room);
+        format!("{}{}{}{}{}", label, text, palette.fg(color::GRAY), note, RESET)
+    }
+
+    /// Draw the overlay over the status area (below the separator)
+    pub fn draw(&self, stdout: &mut impl Write, layout: &Layout) -> Result<()> {
+        write!(stdout, "{}", escape::CURSOR_SAVE)?;
+
+        let width = layout.total_cols as usize;
+        for row in 1..layout.status_rows {
+            write!(stdout, "{}", escape::cursor_to(layout.pty_rows + 1 + row, 1))?;
+            let line = if row == 1 { self.line(width, &layout.palette) } else { String::new() };
+            let pad = width.saturating_sub(strip_ansi_len(&line));
+            write!(stdout, "{}{:pad$}", line, "", pad = pad)?;
+        }
+
+        write!(stdout, "{}", escape::CURSOR_RESTORE)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::strip_ansi;
+
+    #[test]
+    fn test_line_shows_suggestion_and_copy_state() {
+        let palette = Palette::default();
+        let widget = MessageOverlay::new("feat(src/ui): add render".to_string(), true, Duration::from_secs(5));
+        assert!(widget.is_active());
+        assert_eq!(strip_ansi(&widget.line(100, &palette)), " Commit message: feat(src/ui): add render (copied)");
+
+        let widget = MessageOverlay::new("fix: update parse".to_string(), false, Duration::ZERO);
+        assert!(!widget.is_active());
+        let line = strip_ansi(&widget.line(100, &palette));
+        assert_eq!(line, " Commit message: fix: update parse (clipboard unavailable)");
+
+        let widget = MessageOverlay::new(String::new(), false, Duration::from_secs(5));
+        assert_eq!(strip_ansi(&widget.line(100, &palette)), " Commit message: nothing staged");
+
+        let widget = MessageOverlay::stats("3 prompts".to_string(), true, Duration::from_secs(5));
+        assert_eq!(strip_ansi(&widget.line(100, &palette)), " Session stats: 3 prompts (copied)");
+    }
+
+    #[test]
+    fn test_long_suggestion_is_truncated_to_width() {
+        let widget = MessageOverlay::new("x".repeat(200), true, Duration::from_secs(5));
+        assert_eq!(strip_ansi_len(&widget.line(60, &Palette::default())), 60);
+    }
+}
@@ -0,0 +1,385 @@
+//! Custom status bar widgets
+//!
+//! `CustomWidget` is the extension point for widgets beyond stats, git and
+//! changes. Custom widgets get a column each to the right of the changes
+//! widget, as many as fit (see `Layout::custom_widget_width`). The only
+//! implementation so far is `CommandWidget`, built from the
+//! `[custom_widgets]` table in config.toml, which shows a shell command's
+//! output.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::terminal::escape::{self, color, RESET};
+use crate::terminal::theme::Palette;
+
+use super::utils::{strip_ansi, strip_ansi_len, take_graphemes};
+use super::WidgetArea;
+
+/// How often a command widget reruns its command, unless configured
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a command widget's command may run before it is killed
+pub const COMMAND_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Read-only view of the app that custom widgets draw from
+pub struct AppState<'a> {
+    pub palette: &'a Palette,
+}
+
+/// A status bar widget that isn't built in
+///
+/// `draw` is called once per status row, like the built-in widgets: row 1 is
+/// the header, rows 2.. the body. It must fill exactly `area.width` cells and
+/// must not block; slow work belongs on a thread, at most every
+/// `refresh_interval`.
+pub trait CustomWidget: Send {
+    /// Shown in the widget's header
+    fn name(&self) -> &str;
+
+    /// Draw one row of the widget at `area.row`
+    fn draw(&self, stdout: &mut dyn Write, area: WidgetArea, state: &AppState) -> Result<()>;
+
+    /// How often the widget's content is refreshed
+    fn refresh_interval(&self) -> Duration;
+}
+
+/// One `[custom_widgets]` entry: a command, or a table with its refresh interval
+///
+/// ```toml
+/// [custom_widgets]
+/// todo = "grep -c TODO TODO.md"
+/// docker = { command = "docker ps --format '{{.Names}}'", refresh_secs = 10 }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CommandWidgetConfig {
+    Command(String),
+    Detailed {
+        command: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        refresh_secs: Option<u64>,
+    },
+}
+
+impl CommandWidgetConfig {
+    pub fn command(&self) -> &str {
+        match self {
+            Self::Command(command) | Self::Detailed { command, .. } => command,
+        }
+    }
+
+    pub fn refresh_interval(&self) -> Duration {
+        match self {
+            Self::Detailed {
+                refresh_secs: Some(secs),
+                ..
+            } => Duration::from_secs((*secs).max(1)),
+            _ => DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+}
+
+/// Build the widgets configured in `[custom_widgets]`, in name order
+pub fn widgets_from_config(
+    config: &BTreeMap<String, CommandWidgetConfig>,
+    cwd: &Path,
+) -> Vec<Box<dyn CustomWidget>> {
+    config
+        .iter()
+        .map(|(name, entry)| {
+            Box::new(CommandWidget::new(name, entry.command(), cwd, entry.refresh_interval())) as Box<dyn CustomWidget>
+        })
+        .collect()
+}
+
+/// Result of the last run of a widget's command
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandOutput {
+    /// The command hasn't finished its first run
+    Pending,
+    /// Trimmed stdout of a successful run
+    Text(String),
+    /// Killed after `COMMAND_TIMEOUT`
+    TimedOut,
+    /// Couldn't be started, or exited non-zero
+    Failed(String),
+}
+
+struct CommandCache {
+    output: CommandOutput,
+    last_run: Option<Instant>,
+    running: bool,
+}
+
+/// Widget showing the stdout of a shell command
+///
+/// The command runs under `sh -c` in the session's directory on a background
+/// thread, at most once per `refresh_interval`; draws show the cached result.
+pub struct CommandWidget {
+    name: String,
+    command: String,
+    cwd: PathBuf,
+    refresh_interval: Duration,
+    timeout: Duration,
+    cache: Arc<Mutex<CommandCache>>,
+}
+
+impl CommandWidget {
+    pub fn new(name: &str, command: &str, cwd: &Path, refresh_interval: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            cwd: cwd.to_path_buf(),
+            refresh_interval,
+            timeout: COMMAND_TIMEOUT,
+            cache: Arc::new(Mutex::new(CommandCache {
+                output: CommandOutput::Pending,
+                last_run: None,
+                running: false,
+            })),
+        }
+    }
+
+    /// The last result, or `Pending` before the first run finishes
+    pub fn output(&self) -> CommandOutput {
+        self.cache.lock().unwrap_or_else(|p| p.into_inner()).output.clone()
+    }
+
+    /// Start a run on a background thread when one is due and none is running
+    fn refresh_if_due(&self) {
+        let mut cache = self.cache.lock().unwrap_or_else(|p| p.into_inner());
+        let due = cache.last_run.is_none_or(|last| last.elapsed() >= self.refresh_interval());
+        if cache.running || !due {
+            return;
+        }
+        cache.running = true;
+        cache.last_run = Some(Instant::now());
+        drop(cache);
+
+        let (command, cwd, timeout) = (self.command.clone(), self.cwd.clone(), self.timeout);
+        let shared = Arc::clone(&self.cache);
+        thread::spawn(move || {
+            let output = run_command(&command, &cwd, timeout);
+            let mut cache = shared.lock().unwrap_or_else(|p| p.into_inner());
+            cache.output = output;
+            cache.running = false;
+        });
+    }
+}
+
+impl CustomWidget for CommandWidget {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn draw(&self, stdout: &mut dyn Write, area: WidgetArea, state: &AppState) -> Result<()> {
+        self.refresh_if_due();
+        write!(stdout, "{}", escape::cursor_to(area.pty_rows + 1 + area.row, area.col + 1))?;
+
+        let width = area.width as usize;
+        let content = if area.row == 1 {
+            format!("{}{}{}", state.palette.fg(color::PURPLE), take_graphemes(self.name(), width), RESET)
+        } else {
+            let line_idx = area.row.saturating_sub(2) as usize;
+            match self.output() {
+                CommandOutput::Text(text) => {
+                    let line = text.lines().nth(line_idx).unwrap_or("");
+                    take_graphemes(line, width).to_string()
+                }
+                CommandOutput::Pending if line_idx == 0 => {
+                    format!("{}{}{}", state.palette.fg(color::GRAY), take_graphemes("…", width), RESET)
+                }
+                CommandOutput::TimedOut if line_idx == 0 => {
+                    format!("{}{}{}", state.palette.fg(color::ORANGE), take_graphemes("timed out", width), RESET)
+                }
+                CommandOutput::Failed(reason) if line_idx == 0 => {
+                    format!("{}{}{}", state.palette.fg(color::RED), take_graphemes(&reason, width), RESET)
+                }
+                _ => String::new(),
+            }
+        };
+
+        let pad = width.saturating_sub(strip_ansi_len(&content));
+        write!(stdout, "{}{:pad$}", content, "", pad = pad)?;
+        Ok(())
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+}
+
+/// Run `command` under `sh -c`, killing it once `timeout` passes
+///
+/// Stdout is read on its own thread: a background process the command left
+/// behind can hold the pipe open after the shell itself is killed, and that
+/// must not hold up the caller.
+pub fn run_command(command: &str, cwd: &Path, timeout: Duration) -> CommandOutput {
+    let deadline = Instant::now() + timeout;
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return CommandOutput::Failed(e.to_string()),
+    };
+
+    let mut pipe = child.stdout.take().expect("stdout is piped");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        let _ = tx.send(bytes);
+    });
+
+    let timed_out = |child: &mut std::process::Child| {
+        let _ = child.kill();
+        let _ = child.wait();
+        CommandOutput::TimedOut
+    };
+
+    let Ok(bytes) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) else {
+        return timed_out(&mut child);
+    };
+
+    // Stdout is closed, so the command is on its way out
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(5)),
+            Ok(None) => return timed_out(&mut child),
+            Err(e) => return CommandOutput::Failed(e.to_string()),
+        }
+    };
+    if !status.success() {
+        return CommandOutput::Failed(match status.code() {
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        });
+    }
+    CommandOutput::Text(strip_ansi(String::from_utf8_lossy(&bytes).trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cwd() -> PathBuf {
+        std::env::temp_dir()
+    }
+
+    fn wait_for_output(widget: &CommandWidget) -> CommandOutput {
+        let start = Instant::now();
+        while widget.output() == CommandOutput::Pending && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        widget.output()
+    }
+
+    fn render(widget: &CommandWidget, rows: u16) -> Vec<String> {
+        let state = AppState {
+            palette: &Palette::default(),
+        };
+        let mut buf = Vec::new();
+        for row in 1..rows {
+            let area = WidgetArea {
+                pty_rows: 0,
+                col: 0,
+                row,
+                width: 12,
+                height: rows,
+            };
+            widget.draw(&mut buf, area, &state).unwrap();
+        }
+        let mut parser = vt100::Parser::new(rows, 12, 0);
+        parser.process(&buf);
+        parser.screen().rows(0, 12).skip(1).collect()
+    }
+
+    #[test]
+    fn test_run_command_output_and_failures() {
+        assert_eq!(
+            run_command("printf '  one\\ntwo  \\n'", &cwd(), COMMAND_TIMEOUT),
+            CommandOutput::Text("one\ntwo".to_string())
+        );
+        assert_eq!(run_command("exit 3", &cwd(), COMMAND_TIMEOUT), CommandOutput::Failed("exit 3".to_string()));
+    }
+
+    #[test]
+    fn test_run_command_times_out() {
+        let start = Instant::now();
+        assert_eq!(run_command("sleep 5", &cwd(), Duration::from_millis(200)), CommandOutput::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(2), "took {:?}", start.elapsed());
+
+        // A leftover background process holding stdout doesn't hold us up either
+        let start = Instant::now();
+        assert_eq!(run_command("echo hi; sleep 5", &cwd(), Duration::from_millis(200)), CommandOutput::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(2), "took {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn test_command_widget_draws_cached_output() {
+        let widget = CommandWidget::new("todo", "echo 3 open; echo 1 done", &cwd(), Duration::from_secs(60));
+        let rows = render(&widget, 4);
+        assert_eq!(rows[0].trim_end(), "todo");
+        assert_eq!(wait_for_output(&widget), CommandOutput::Text("3 open\n1 done".to_string()));
+
+        let rows = render(&widget, 4);
+        assert_eq!(rows[1].trim_end(), "3 open");
+        assert_eq!(rows[2].trim_end(), "1 done");
+    }
+
+    #[test]
+    fn test_command_widget_shows_timeout() {
+        let mut widget = CommandWidget::new("slow", "sleep 5", &cwd(), Duration::from_secs(60));
+        widget.timeout = Duration::from_millis(100);
+        render(&widget, 3);
+        assert_eq!(wait_for_output(&widget), CommandOutput::TimedOut);
+        assert_eq!(render(&widget, 3)[1].trim_end(), "timed out");
+    }
+
+    #[test]
+    fn test_command_widget_reruns_only_when_due() {
+        let dir = tempfile::tempdir().unwrap();
+        let widget = CommandWidget::new("runs", "echo run >> runs.txt; wc -l < runs.txt", dir.path(), Duration::from_secs(60));
+        widget.refresh_if_due();
+        assert_eq!(wait_for_output(&widget), CommandOutput::Text("1".to_string()));
+        widget.refresh_if_due();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(widget.output(), CommandOutput::Text("1".to_string()));
+    }
+
+    #[test]
+    fn test_config_entries() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            custom_widgets: BTreeMap<String, CommandWidgetConfig>,
+        }
+        let parsed: Wrapper = toml::from_str(
+            "[custom_widgets]\ntodo = \"grep -c TODO TODO.md\"\ndocker = { command = \"docker ps\", refresh_secs = 10 }\n",
+        )
+        .unwrap();
+        let todo = &parsed.custom_widgets["todo"];
+        assert_eq!(todo.command(), "grep -c TODO TODO.md");
+        assert_eq!(todo.refresh_interval(), DEFAULT_REFRESH_INTERVAL);
+        assert_eq!(parsed.custom_widgets["docker"].refresh_interval(), Duration::from_secs(10));
+
+        let widgets = widgets_from_config(&parsed.custom_widgets, &cwd());
+        let names: Vec<&str> = widgets.iter().map(|w| w.name()).collect();
+        assert_eq!(names, ["docker", "todo"]);
+    }
+}
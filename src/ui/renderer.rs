@@ -0,0 +1,215 @@
+//! Skip status bar writes that wouldn't change the screen
+//!
+//! PTY output schedules a redraw every 100ms, and most of those frames are
+//! byte-for-byte the frame before. Rewriting the same rows still makes some
+//! terminals repaint them, which shows as flicker next to a busy CLI.
+//! `StatusBarRenderer` keeps the last frame written and only hands a new one to
+//! the terminal when it differs.
+//!
+//! An unchanged frame is only safe to skip while the rows still show it, so the
+//! renderer is invalidated when something else may have drawn over them (a
+//! resize, or a screen clear in the CLI's output), and it writes at least once
+//! every `KEYFRAME_INTERVAL` to cover anything it doesn't detect. PTY reads can
+//! end mid-sequence, so an escape left unfinished at the end of one chunk is
+//! finished with the next, as `DsrHandler` does.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use super::utils::find_escape;
+use super::writer::ByteWriter;
+
+/// Longest an unchanged frame goes without being written again
+pub const KEYFRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest unfinished escape carried over to the next chunk; anything longer
+/// isn't one of the sequences `scan_escapes` looks for
+const MAX_PENDING_ESCAPE: usize = 32;
+
+/// Writes status bar frames, dropping repeats of the last one
+pub struct StatusBarRenderer {
+    /// Last frame written to the terminal, empty once invalidated
+    previous: Vec<u8>,
+    last_write: Option<Instant>,
+    /// Unfinished escape at the end of the last chunk `scan_output` saw
+    pending: Vec<u8>,
+}
+
+impl StatusBarRenderer {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::with_capacity(super::writer::FRAME_CAPACITY),
+            last_write: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Write `rendered` to `out` unless it repeats the last frame; the buffer
+    /// is emptied either way. Returns whether anything was written.
+    pub fn flush_if_changed(&mut self, out: &mut impl Write, rendered: &mut ByteWriter) -> io::Result<bool> {
+        let bytes = rendered.as_bytes();
+        if bytes.is_empty() {
+            return Ok(false);
+        }
+        let fresh = self.last_write.is_some_and(|t| t.elapsed() < KEYFRAME_INTERVAL);
+        if fresh && self.previous == bytes {
+            rendered.flush_into(&mut io::sink())?;
+            return Ok(false);
+        }
+
+        self.previous.clear();
+        self.previous.extend_from_slice(bytes);
+        self.last_write = Some(Instant::now());
+        rendered.flush_into(out)?;
+        Ok(true)
+    }
+
+    /// Forget the last frame so the next one is written even if unchanged
+    pub fn invalidate(&mut self) {
+        self.previous.clear();
+    }
+
+    /// Invalidate when passthrough output may have drawn over the status rows
+    pub fn scan_output(&mut self, bytes: &[u8]) {
+        let joined;
+        let bytes = if self.pending.is_empty() {
+            bytes
+        } else {
+            joined = [self.pending.as_slice(), bytes].concat();
+            joined.as_slice()
+        };
+        self.pending.clear();
+        match scan_escapes(bytes) {
+            EscapeScan::Clears => self.invalidate(),
+            EscapeScan::Unfinished(start) if bytes.len() - start <= MAX_PENDING_ESCAPE => {
+                self.pending.extend_from_slice(&bytes[start..]);
+            }
+            EscapeScan::Unfinished(_) | EscapeScan::Clean => {}
+        }
+    }
+}
+
+impl Default for StatusBarRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What `scan_escapes` found in a chunk of output
+#[derive(Debug, PartialEq)]
+enum EscapeScan {
+    /// A sequence that can reach below the scroll region
+    Clears,
+    /// Nothing yet, but the escape starting at this offset runs past the end
+    Unfinished(usize),
+    Clean,
+}
+
+/// Look for a sequence that can reach below the scroll region: erase in
+/// display (`CSI J`, which ignores margins), a full reset (`ESC c`), a margin
+/// change (`CSI r`), or an alternate screen switch
+fn scan_escapes(bytes: &[u8]) -> EscapeScan {
+    let mut offset = 0;
+    while let Some(pos) = find_escape(&bytes[offset..]) {
+        let start = offset + pos;
+        let rest = &bytes[start + 1..];
+        offset = start + 1;
+        match rest.first() {
+            None => return EscapeScan::Unfinished(start),
+            Some(b'c') => return EscapeScan::Clears,
+            Some(b'[') => {
+                let params_len = rest[1..].iter().take_while(|&&b| matches!(b, b'0'..=b'9' | b';' | b'?')).count();
+                let params = &rest[1..1 + params_len];
+                match rest.get(1 + params_len) {
+                    None => return EscapeScan::Unfinished(start),
+                    Some(b'J' | b'r') => return EscapeScan::Clears,
+                    Some(b'h' | b'l') if is_alternate_screen(params) => return EscapeScan::Clears,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    EscapeScan::Clean
+}
+
+/// `?47`, `?1047` and `?1049` switch to or from the alternate screen
+fn is_alternate_screen(params: &[u8]) -> bool {
+    params
+        .strip_prefix(b"?")
+        .is_some_and(|modes| modes.split(|&b| b == b';').any(|m| matches!(m, b"47" | b"1047" | b"1049")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(text: &str) -> ByteWriter {
+        let mut frame = ByteWriter::new();
+        frame.write_all(text.as_bytes()).unwrap();
+        frame
+    }
+
+    #[test]
+    fn test_unchanged_frame_written_once() {
+        let mut renderer = StatusBarRenderer::new();
+        let mut out = Vec::new();
+        assert!(renderer.flush_if_changed(&mut out, &mut frame("\x1b[30;1HReady")).unwrap());
+        let mut repeat = frame("\x1b[30;1HReady");
+        assert!(!renderer.flush_if_changed(&mut out, &mut repeat).unwrap());
+        assert!(repeat.as_bytes().is_empty());
+        assert!(renderer.flush_if_changed(&mut out, &mut frame("\x1b[30;1HThinking")).unwrap());
+        assert_eq!(out, b"\x1b[30;1HReady\x1b[30;1HThinking");
+    }
+
+    #[test]
+    fn test_invalidate_rewrites_same_frame() {
+        let mut renderer = StatusBarRenderer::new();
+        let mut out = Vec::new();
+        renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap();
+        renderer.scan_output(b"plain output\r\n");
+        assert!(!renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap());
+        renderer.scan_output(b"\x1b[H\x1b[2J");
+        assert!(renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap());
+        assert_eq!(out, b"barbar");
+    }
+
+    #[test]
+    fn test_scan_escapes() {
+        let clears = |bytes: &[u8]| scan_escapes(bytes) == EscapeScan::Clears;
+        assert!(clears(b"\x1b[2J"));
+        assert!(clears(b"text\x1b[J"));
+        assert!(clears(b"\x1bc"));
+        assert!(clears(b"\x1b[r"));
+        assert!(clears(b"\x1b[?1049h"));
+        assert!(clears(b"\x1b[?25l\x1b[?1049l"));
+        // Line erases and cursor moves stay inside the scroll region
+        assert!(!clears(b"\x1b[2K\x1b[1A\x1b[G"));
+        assert!(!clears(b"\x1b[?2004h\x1b[1;32mok\x1b[0m"));
+
+        assert_eq!(scan_escapes(b"ok\x1b"), EscapeScan::Unfinished(2));
+        assert_eq!(scan_escapes(b"\x1b[0mok\x1b[?10"), EscapeScan::Unfinished(6));
+        assert_eq!(scan_escapes(b"\x1b[0mok"), EscapeScan::Clean);
+    }
+
+    #[test]
+    fn test_clear_split_across_chunks_invalidates() {
+        let mut renderer = StatusBarRenderer::new();
+        let mut out = Vec::new();
+        renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap();
+        renderer.scan_output(b"output\x1b[2");
+        assert!(!renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap());
+        renderer.scan_output(b"J");
+        assert!(renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap());
+
+        renderer.scan_output(b"\x1b");
+        renderer.scan_output(b"c");
+        assert!(renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap());
+
+        // A finished sequence isn't carried into the next chunk
+        renderer.scan_output(b"\x1b[2");
+        renderer.scan_output(b"K");
+        renderer.scan_output(b"J");
+        assert!(!renderer.flush_if_changed(&mut out, &mut frame("bar")).unwrap());
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Renders time-series data as a compact Unicode sparkline using block characters.
 
-use crate::terminal::escape::{color, fg, RESET};
+use crate::terminal::escape::{color, RESET};
+use crate::terminal::theme::Palette;
 
 /// Unicode block characters for sparkline levels (8 levels)
 const BLOCKS: &[char] = &[' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
@@ -18,7 +19,7 @@ const SPARKLINE_MAX: u32 = 10;
 ///
 /// # Returns
 /// A colored string representing the sparkline
-pub fn render_sparkline(bins: &[u32], width: usize) -> String {
+pub fn render_sparkline(bins: &[u32], width: usize, palette: &Palette) -> String {
     if bins.is_empty() || width == 0 {
         return String::new();
     }
@@ -27,12 +28,12 @@ pub fn render_sparkline(bins: &[u32], width: usize) -> String {
     let has_activity = bins.iter().any(|&c| c > 0);
     if !has_activity {
         // No activity - show empty sparkline
-        return format!("{}{}{}", fg(color::GRAY), " ".repeat(width.min(bins.len())), RESET);
+        return format!("{}{}{}", palette.fg(color::GRAY), " ".repeat(width.min(bins.len())), RESET);
     }
 
     // Build the sparkline string
     let mut result = String::new();
-    result.push_str(&fg(color::ORANGE));
+    result.push_str(&palette.fg(color::ORANGE));
 
     for &count in bins.iter().take(width) {
         let level = if count == 0 {
@@ -49,6 +50,26 @@ pub fn render_sparkline(bins: &[u32], width: usize) -> String {
     result
 }
 
+/// Heatmap shading from idle to busiest (5 levels)
+const HEAT: &[char] = &['·', '░', '▒', '▓', '█'];
+
+/// Render one heatmap row, shaded relative to `max` (the busiest cell overall)
+pub fn render_heatmap_row(bins: &[u32], max: u32, width: usize, palette: &Palette) -> String {
+    let mut result = String::new();
+    for &count in bins.iter().take(width) {
+        if count == 0 || max == 0 {
+            result.push_str(&format!("{}{}", palette.fg(color::DARK_GRAY), HEAT[0]));
+        } else {
+            let level = (count as f64 / max as f64 * 4.0).ceil() as usize;
+            result.push_str(&format!("{}{}", palette.fg(color::ORANGE), HEAT[level.clamp(1, 4)]));
+        }
+    }
+    if !result.is_empty() {
+        result.push_str(RESET);
+    }
+    result
+}
+
 /// Bin timestamps into fixed-width buckets
 ///
 /// # Arguments
@@ -91,14 +112,14 @@ mod tests {
 
     #[test]
     fn test_render_empty() {
-        assert_eq!(render_sparkline(&[], 10), "");
-        assert_eq!(render_sparkline(&[0, 0, 0], 10).contains(' '), true);
+        assert_eq!(render_sparkline(&[], 10, &Palette::default()), "");
+        assert!(render_sparkline(&[0, 0, 0], 10, &Palette::default()).contains(' '));
     }
 
     #[test]
     fn test_render_single_peak() {
         let bins = vec![0, 0, 5, 0, 0];
-        let result = render_sparkline(&bins, 5);
+        let result = render_sparkline(&bins, 5, &Palette::default());
         // 5 tools = half of max (10), so should be mid-height block
         assert!(result.contains('▄') || result.contains('▅'));
     }
@@ -106,11 +127,21 @@ mod tests {
     #[test]
     fn test_render_full_height() {
         let bins = vec![0, 0, 10, 0, 0];
-        let result = render_sparkline(&bins, 5);
+        let result = render_sparkline(&bins, 5, &Palette::default());
         // 10 tools = full height
         assert!(result.contains('█'));
     }
 
+    #[test]
+    fn test_render_heatmap_row() {
+        let plain = crate::ui::utils::strip_ansi(&render_heatmap_row(&[0, 1, 2, 3, 4, 8], 8, 10, &Palette::default()));
+        assert_eq!(plain, "·░░▒▒█");
+        assert_eq!(render_heatmap_row(&[], 8, 10, &Palette::default()), "");
+        // Width truncates the oldest-first row from the right
+        let plain = crate::ui::utils::strip_ansi(&render_heatmap_row(&[8, 0, 8], 8, 2, &Palette::default()));
+        assert_eq!(plain, "█·");
+    }
+
     #[test]
     fn test_bin_timestamps() {
         let start = 1000.0;
@@ -2,24 +2,50 @@
 //!
 //! Shows session state, duration, messages, tool calls, compressions, and cloud status.
 
-use std::io::{Stdout, Write};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 
 use crate::cloud::CloudStatus;
-use crate::terminal::escape::{self, color, fg, RESET};
+use crate::terminal::color_detect::ColorMode;
+use crate::terminal::escape::{self, color, RESET};
+use crate::terminal::theme::Palette;
 use crate::hooks::SessionStats;
 use crate::platforms::SessionState;
-use super::sparkline::render_sparkline;
-use super::utils::strip_ansi_len;
-use super::WidgetArea;
+use super::sparkline::{render_heatmap_row, render_sparkline};
+use super::utils::{pad_to_width, strip_ansi_len, take_graphemes, truncate_middle};
+use super::{WidgetArea, WidgetMode};
+
+/// Widget height at which the tools sparkline becomes a per-tool heatmap
+const HEATMAP_MIN_HEIGHT: u16 = 8;
+
+/// Width of the tool name column in heatmap rows
+const HEATMAP_LABEL_WIDTH: usize = 6;
+
+/// Widest tool name shown next to the tools sparkline
+const TOP_TOOL_WIDTH: usize = 16;
+
+/// Narrowest sparkline worth keeping when the busiest tool is shown beside it
+const MIN_SPARKLINE_WIDTH: usize = 10;
+
+/// The session countdown turns orange with this much time left
+const SESSION_WARNING: Duration = Duration::from_secs(5 * 60);
+
+/// Compactions closer together than this mean the context is filling up fast
+const COMPACTION_WARNING_SECS: u64 = 600;
+
+/// Token budget use (percent) from which the stats widget shows it
+const BUDGET_WARNING_PERCENT: u8 = 80;
+
+/// Cells in the token budget bar
+const BUDGET_BAR_WIDTH: usize = 5;
 
 /// Braille spinner frames for the thinking animation
 const THROBBER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
 /// Get current throbber frame based on time (10 FPS)
-fn throbber_frame() -> char {
+pub fn throbber_frame() -> char {
     let millis = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -28,6 +54,65 @@ fn throbber_frame() -> char {
     THROBBER[frame]
 }
 
+/// Throbber color: a green that pulses (0,200,0) → (0,255,0) → (0,200,0)
+/// once per spinner cycle on true color terminals, plain green elsewhere
+fn throbber_color(palette: &Palette) -> String {
+    if palette.mode != ColorMode::TrueColor {
+        return palette.fg(color::GREEN);
+    }
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let (r, g, b) = throbber_gradient(millis);
+    color::fg_rgb(r, g, b)
+}
+
+/// Gradient step at `millis`: brightest halfway through each spinner cycle
+fn throbber_gradient(millis: u128) -> (u8, u8, u8) {
+    let cycle_ms = THROBBER.len() as u128 * 100;
+    let phase = (millis % cycle_ms) as f64 / cycle_ms as f64;
+    // Triangle wave: 0 at the cycle's ends, 1 in the middle
+    let level = 1.0 - (2.0 * phase - 1.0).abs();
+    (0, 200 + (55.0 * level).round() as u8, 0)
+}
+
+/// Whether a pulsing element is in its dim half (one beat per second)
+pub fn pulse_dim() -> bool {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    (millis / 500) % 2 == 1
+}
+
+/// Time left before `max_minutes` have passed since `started_at` (None once it's up)
+pub fn time_remaining(started_at: Instant, max_minutes: u64) -> Option<Duration> {
+    time_remaining_at(started_at, max_minutes, Instant::now())
+}
+
+fn time_remaining_at(started_at: Instant, max_minutes: u64, now: Instant) -> Option<Duration> {
+    let budget = Duration::from_secs(max_minutes * 60);
+    budget
+        .checked_sub(now.saturating_duration_since(started_at))
+        .filter(|left| !left.is_zero())
+}
+
+/// Countdown text: `1h 5m`, `45m`, `30s`
+pub fn format_time_remaining(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        return format!("{}s", secs);
+    }
+    // Round up so a fresh 90 minute budget reads 1h 30m, not 1h 29m
+    let mins = secs.div_ceil(60);
+    match (mins / 60, mins % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
 /// Calculate idle seconds from idle_since timestamp
 fn idle_seconds(idle_since: Option<f64>) -> Option<u64> {
     let since = idle_since?;
@@ -35,11 +120,18 @@ fn idle_seconds(idle_since: Option<f64>) -> Option<u64> {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs_f64();
-    let secs = (now - since) as u64;
-    if secs >= 60 {
-        Some(secs)
-    } else {
-        None
+    Some((now - since).max(0.0) as u64)
+}
+
+/// Format idle time coarsely so the row doesn't tick every second
+///
+/// `just now` for the first 10s, then `10s`/`30s`, then minutes (`1m`, `1h2m`).
+fn format_idle_duration(secs: u64) -> String {
+    match secs {
+        0..=9 => "just now".to_string(),
+        10..=29 => "10s".to_string(),
+        30..=59 => "30s".to_string(),
+        _ => format_duration_compact(secs),
     }
 }
 
@@ -82,36 +174,78 @@ fn format_elapsed(timestamp: Option<f64>) -> String {
     }
 }
 
+/// When the context was last compacted: the hook's timestamp, or when we
+/// saw the count change for hooks that don't record one
+fn last_compaction(stats: &SessionStats) -> Option<f64> {
+    stats.platform_stats.last_compressed_at.or(stats.compressions_changed_at)
+}
+
+/// Format time since the last compaction, e.g. `(5m ago)`
+fn format_compaction_age(secs: u64) -> String {
+    if secs < 60 {
+        "(just now)".to_string()
+    } else {
+        format!("({} ago)", format_duration_compact(secs))
+    }
+}
+
+/// Compaction age (with a leading space) colored as a warning when recent
+fn compaction_age(stats: &SessionStats, palette: &Palette) -> String {
+    match elapsed_since(last_compaction(stats)) {
+        Some(secs) => {
+            let color = if secs < COMPACTION_WARNING_SECS { color::YELLOW } else { color::GRAY };
+            format!(" {}{}{}", palette.fg(color), format_compaction_age(secs), RESET)
+        }
+        None => String::new(),
+    }
+}
+
 /// Format the state indicator for the header row
-fn format_state_indicator(state: SessionState) -> String {
+///
+/// While thinking, `elapsed` (seconds since the last tool call) is appended
+/// so long-running operations show progress: `⠋ [12s]`.
+fn format_state_indicator(state: SessionState, elapsed: Option<u64>, palette: &Palette) -> String {
     match state {
         SessionState::Ready => {
-            format!("{}○ Ready{}", fg(color::GRAY), RESET)
-        }
-        SessionState::Thinking => {
-            format!("{}{}{}", fg(color::GREEN), throbber_frame(), RESET)
+            format!("{}○ Ready{}", palette.fg(color::GRAY), RESET)
         }
+        SessionState::Thinking => match elapsed {
+            Some(secs) => format!(
+                "{}{}{} {}[{}s]{}",
+                throbber_color(palette), throbber_frame(), RESET,
+                palette.fg(color::GRAY), secs, RESET
+            ),
+            None => format!("{}{}{}", throbber_color(palette), throbber_frame(), RESET),
+        },
         SessionState::Permission => {
-            format!("{}» ? «{} Perm", fg(color::YELLOW), RESET)
+            format!("{}» ? «{} Perm", palette.fg(color::YELLOW), RESET)
         }
         SessionState::Question => {
-            format!("{}» ? «{} Ask", fg(color::ORANGE), RESET)
+            format!("{}» ? «{} Ask", palette.fg(color::ORANGE), RESET)
         }
         SessionState::Complete => {
-            format!("{}✓ Complete{}", fg(color::PURPLE), RESET)
+            format!("{}✓ Complete{}", palette.fg(color::PURPLE), RESET)
         }
         SessionState::Interrupted => {
-            format!("{}⊘ Interrupted{}", fg(color::RED), RESET)
+            format!("{}⊘ Interrupted{}", palette.fg(color::RED), RESET)
+        }
+        SessionState::Paused => {
+            format!("{}⏸ Paused{}", palette.fg(color::RED), RESET)
         }
     }
 }
 
 /// Draw the stats widget at the given position
+#[allow(clippy::too_many_arguments)]
 pub fn draw_stats_widget(
-    stdout: &mut Stdout,
+    stdout: &mut impl Write,
     area: WidgetArea,
     stats: &SessionStats,
     cloud_status: Option<&CloudStatus>,
+    max_session_minutes: Option<u64>,
+    token_budget: Option<u64>,
+    mode: WidgetMode,
+    palette: &Palette,
 ) -> Result<()> {
     write!(stdout, "{}", escape::cursor_to(area.pty_rows + 1 + area.row, area.col + 1))?;
 
@@ -119,10 +253,13 @@ pub fn draw_stats_widget(
     // Compact mode: header + 2 rows with abbreviated two-column layout
     let compact = area.height <= 5;
 
-    let content = if compact {
-        draw_compact_row(area.row, area.width, stats, cloud_status)
+    let content = if mode == WidgetMode::Compact {
+        draw_narrow_row(area.row, area.width, stats, palette)
+    } else if compact {
+        draw_compact_row(area.row, area.width, stats, cloud_status, palette)
     } else {
-        draw_normal_row(area.row, area.width, stats, cloud_status)
+        let (row, width, height) = (area.row, area.width, area.height);
+        draw_normal_row(row, width, height, stats, cloud_status, max_session_minutes, token_budget, palette)
     };
 
     write!(stdout, "{}", content)?;
@@ -134,33 +271,55 @@ pub fn draw_stats_widget(
 }
 
 /// Format cloud status as header text
-fn format_cloud_header(cloud_status: Option<&CloudStatus>) -> String {
+fn format_cloud_header(cloud_status: Option<&CloudStatus>, palette: &Palette) -> String {
     match cloud_status {
         Some(status) if status.connected => {
-            format!("{} Live{}", fg(color::GREEN), RESET)
+            format!("{} Live{}", palette.fg(color::GREEN), RESET)
         }
         Some(status) if status.reconnect_attempts > 0 => {
-            format!("{} Retry{}", fg(color::ORANGE), RESET)
+            format!("{} Retry {}s{}", palette.fg(color::ORANGE), status.backoff_secs, RESET)
         }
         Some(_) => {
-            format!("{} Offline{}", fg(color::RED), RESET)
+            format!("{} Offline{}", palette.fg(color::RED), RESET)
         }
         None => {
-            format!("{} Local{}", fg(color::GRAY), RESET)
+            format!("{} Local{}", palette.fg(color::GRAY), RESET)
         }
     }
 }
 
+/// Draw a row for a narrow terminal: state and session time on the header row
+///
+/// The session time is dropped when it doesn't fit next to the state.
+fn draw_narrow_row(row: u16, width: u16, stats: &SessionStats, palette: &Palette) -> String {
+    if row != 1 {
+        return String::new();
+    }
+    let state = format_state_indicator(stats.effective_state(), stats.elapsed_thinking_secs(), palette);
+    let duration = format!("{}{}{}", palette.fg(color::BLUE), stats.format_work(), RESET);
+    if strip_ansi_len(&state) + 1 + strip_ansi_len(&duration) <= width as usize {
+        format!("{} {}", state, duration)
+    } else {
+        state
+    }
+}
+
 /// Draw a row in compact mode (two-column layout with separator)
-fn draw_compact_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Option<&CloudStatus>) -> String {
+fn draw_compact_row(
+    row: u16,
+    width: u16,
+    stats: &SessionStats,
+    cloud_status: Option<&CloudStatus>,
+    palette: &Palette,
+) -> String {
     // Split width into two columns with a separator
     let half = (width as usize) / 2;
 
     match row {
         1 => {
             // Header: cloud status on left, state indicator on right
-            let header = format_cloud_header(cloud_status);
-            let state = format_state_indicator(stats.effective_state());
+            let header = format_cloud_header(cloud_status, palette);
+            let state = format_state_indicator(stats.effective_state(), stats.elapsed_thinking_secs(), palette);
             let header_len = strip_ansi_len(&header);
             let state_len = strip_ansi_len(&state);
             let gap = (width as usize).saturating_sub(header_len + state_len);
@@ -170,25 +329,25 @@ fn draw_compact_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Op
             // Row 2: Left column = Session + Thinking, Right column = Prompts + Completions
             let sess = format!(
                 "{}◆ Sess{} {}{}{}",
-                fg(color::GRAY), RESET,
-                fg(color::BLUE), stats.format_work(), RESET
+                palette.fg(color::GRAY), RESET,
+                palette.fg(color::BLUE), stats.format_work(), RESET
             );
             let thinking_val = stats.format_thinking().unwrap_or_else(|| "—".to_string());
             let think = format!(
                 "{}◇ Thnk{} {}{}{}",
-                fg(color::GRAY), RESET,
-                fg(color::GREEN), thinking_val, RESET
+                palette.fg(color::GRAY), RESET,
+                palette.fg(color::GREEN), thinking_val, RESET
             );
 
             let prm = format!(
                 "{}▸ Pmt{} {}{}{}",
-                fg(color::GRAY), RESET,
-                fg(color::LIGHT_BLUE), stats.platform_stats.prompts, RESET
+                palette.fg(color::GRAY), RESET,
+                palette.fg(color::LIGHT_BLUE), stats.platform_stats.prompts, RESET
             );
             let cmp = format!(
                 "{}◂ Fin{} {}{}{}",
-                fg(color::GRAY), RESET,
-                fg(color::LIGHT_BLUE), stats.platform_stats.completions, RESET
+                palette.fg(color::GRAY), RESET,
+                palette.fg(color::LIGHT_BLUE), stats.platform_stats.completions, RESET
             );
 
             // Left side: Session and Thinking with gap between
@@ -207,7 +366,7 @@ fn draw_compact_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Op
             format!(
                 "{}{}│{}{}",
                 left,
-                fg(color::DARK_GRAY),
+                palette.fg(color::DARK_GRAY),
                 RESET,
                 right
             )
@@ -216,27 +375,26 @@ fn draw_compact_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Op
             // Row 3: Tools sparkline on left, compressions on right if any
             let compressions = stats.platform_stats.compressions;
 
-            let label = format!("{}⚙{} ", fg(color::GRAY), RESET);
+            let label = format!("{}⚙{} ", palette.fg(color::GRAY), RESET);
             let label_len = strip_ansi_len(&label);
 
             if compressions > 0 {
                 // Sparkline takes left half, compressions on right
                 let sparkline_width = half.saturating_sub(label_len + 1); // -1 for separator
                 let bins = stats.tool_usage_bins(sparkline_width);
-                let sparkline = render_sparkline(&bins, sparkline_width);
+                let sparkline = render_sparkline(&bins, sparkline_width, palette);
 
-                let elapsed = format_elapsed(stats.compressions_changed_at);
                 let comp_label = format!(
-                    "{}⊜ Cmp{} {}{}{}{}{}{}",
-                    fg(color::GRAY), RESET,
-                    fg(color::PINK), compressions, RESET,
-                    fg(color::GRAY), elapsed, RESET
+                    "{}⊜ Cmp{} {}{}{}{}",
+                    palette.fg(color::GRAY), RESET,
+                    palette.fg(color::PINK), compressions, RESET,
+                    compaction_age(stats, palette)
                 );
 
                 format!(
                     "{}{}{}│{}{}",
                     label, sparkline,
-                    fg(color::DARK_GRAY),
+                    palette.fg(color::DARK_GRAY),
                     RESET,
                     comp_label
                 )
@@ -244,7 +402,7 @@ fn draw_compact_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Op
                 // No compressions - sparkline spans full width
                 let sparkline_width = (width as usize).saturating_sub(label_len);
                 let bins = stats.tool_usage_bins(sparkline_width);
-                let sparkline = render_sparkline(&bins, sparkline_width);
+                let sparkline = render_sparkline(&bins, sparkline_width, palette);
                 format!("{}{}", label, sparkline)
             }
         }
@@ -252,13 +410,63 @@ fn draw_compact_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Op
     }
 }
 
+/// Number of heatmap rows to show in place of the tools sparkline
+///
+/// Needs a tall widget and a per-tool timeline (older hook scripts only
+/// record bare timestamps). Every row past 8 goes to another tool, so
+/// compactions and idle keep their place below the heatmap.
+fn heatmap_rows(height: u16, stats: &SessionStats) -> u16 {
+    if height < HEATMAP_MIN_HEIGHT || stats.platform_stats.tool_calls.is_empty() {
+        0
+    } else {
+        (height - HEATMAP_MIN_HEIGHT).max(1)
+    }
+}
+
+/// Draw one heatmap row: truncated tool name, then its activity over the session
+fn draw_heatmap_row(index: usize, n_tools: usize, width: u16, stats: &SessionStats, palette: &Palette) -> String {
+    let cells_width = (width as usize).saturating_sub(HEATMAP_LABEL_WIDTH + 1);
+    let rows = stats.tool_heatmap_bins(n_tools, cells_width);
+    let Some((tool, bins)) = rows.get(index) else {
+        return String::new();
+    };
+    let max = rows.iter().flat_map(|(_, bins)| bins).copied().max().unwrap_or(0);
+    let name = pad_to_width(take_graphemes(tool, HEATMAP_LABEL_WIDTH), HEATMAP_LABEL_WIDTH);
+    format!(
+        "{}{}{} {}",
+        palette.fg(color::GRAY), name, RESET,
+        render_heatmap_row(bins, max, cells_width, palette)
+    )
+}
+
 /// Draw a row in normal mode (full labels, single column)
-fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Option<&CloudStatus>) -> String {
+#[allow(clippy::too_many_arguments)]
+fn draw_normal_row(
+    row: u16,
+    width: u16,
+    height: u16,
+    stats: &SessionStats,
+    cloud_status: Option<&CloudStatus>,
+    max_session_minutes: Option<u64>,
+    token_budget: Option<u64>,
+    palette: &Palette,
+) -> String {
+    let heatmap_rows = heatmap_rows(height, stats);
+    let row = if heatmap_rows > 0 && row >= 6 {
+        if row < 6 + heatmap_rows {
+            return draw_heatmap_row((row - 6) as usize, heatmap_rows as usize, width, stats, palette);
+        }
+        // Rows below the heatmap shift down to make room for it
+        row - heatmap_rows + 1
+    } else {
+        row
+    };
+
     match row {
         1 => {
             // Header: cloud status on left, state indicator on right
-            let header = format_cloud_header(cloud_status);
-            let state = format_state_indicator(stats.effective_state());
+            let header = format_cloud_header(cloud_status, palette);
+            let state = format_state_indicator(stats.effective_state(), stats.elapsed_thinking_secs(), palette);
             let header_len = strip_ansi_len(&header);
             let state_len = strip_ansi_len(&state);
             let gap = (width as usize).saturating_sub(header_len + state_len);
@@ -266,8 +474,8 @@ fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Opt
         }
         2 => {
             // Session/work time (right-aligned)
-            let label = format!("{}◆ Session{}", fg(color::GRAY), RESET);
-            let value = format!("{}{}{}", fg(color::BLUE), stats.format_work(), RESET);
+            let label = format!("{}◆ Session{}", palette.fg(color::GRAY), RESET);
+            let value = format!("{}{}{}", palette.fg(color::BLUE), stats.format_work(), RESET);
             let label_len = strip_ansi_len(&label);
             let value_len = strip_ansi_len(&value);
             let gap = (width as usize).saturating_sub(label_len + value_len);
@@ -275,9 +483,9 @@ fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Opt
         }
         3 => {
             // Thinking time (always show, with dash when no thinking yet)
-            let label = format!("{}◇ Thinking{}", fg(color::GRAY), RESET);
+            let label = format!("{}◇ Thinking{}", palette.fg(color::GRAY), RESET);
             let thinking_value = stats.format_thinking().unwrap_or_else(|| "—".to_string());
-            let value = format!("{}{}{}", fg(color::GREEN), thinking_value, RESET);
+            let value = format!("{}{}{}", palette.fg(color::GREEN), thinking_value, RESET);
             let label_len = strip_ansi_len(&label);
             let value_len = strip_ansi_len(&value);
             let gap = (width as usize).saturating_sub(label_len + value_len);
@@ -287,11 +495,11 @@ fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Opt
             // Prompts: count left-aligned after label, timer right-aligned
             let label = format!(
                 "{}▸ Prompts{} {}{}{}",
-                fg(color::GRAY), RESET,
-                fg(color::LIGHT_BLUE), stats.platform_stats.prompts, RESET
+                palette.fg(color::GRAY), RESET,
+                palette.fg(color::LIGHT_BLUE), stats.platform_stats.prompts, RESET
             );
             let elapsed = format_elapsed(stats.prompts_changed_at);
-            let timer = format!("{}{}{}", fg(color::GRAY), elapsed, RESET);
+            let timer = format!("{}{}{}", palette.fg(color::GRAY), elapsed, RESET);
             let label_len = strip_ansi_len(&label);
             let timer_len = strip_ansi_len(&timer);
             let gap = (width as usize).saturating_sub(label_len + timer_len);
@@ -301,11 +509,11 @@ fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Opt
             // Completions: count left-aligned after label, timer right-aligned
             let label = format!(
                 "{}◂ Completions{} {}{}{}",
-                fg(color::GRAY), RESET,
-                fg(color::LIGHT_BLUE), stats.platform_stats.completions, RESET
+                palette.fg(color::GRAY), RESET,
+                palette.fg(color::LIGHT_BLUE), stats.platform_stats.completions, RESET
             );
             let elapsed = format_elapsed(stats.completions_changed_at);
-            let timer = format!("{}{}{}", fg(color::GRAY), elapsed, RESET);
+            let timer = format!("{}{}{}", palette.fg(color::GRAY), elapsed, RESET);
             let label_len = strip_ansi_len(&label);
             let timer_len = strip_ansi_len(&timer);
             let gap = (width as usize).saturating_sub(label_len + timer_len);
@@ -313,44 +521,54 @@ fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Opt
         }
         6 => {
             // Tool usage sparkline (spans from after label to right edge)
-            let label = format!("{}⚙ Tools{} ", fg(color::GRAY), RESET);
+            let label = format!("{}⚙ Tools{} ", palette.fg(color::GRAY), RESET);
             let label_len = strip_ansi_len(&label);
-            let sparkline_width = (width as usize).saturating_sub(label_len);
+            // Busiest tool on the right, if the sparkline keeps a useful width
+            let top_tool = stats
+                .platform_stats
+                .top_tools(1)
+                .into_iter()
+                .next()
+                .map(|(tool, count)| {
+                    format!(
+                        " {}{}{} {}{}{}",
+                        palette.fg(color::GRAY), truncate_middle(&tool, TOP_TOOL_WIDTH), RESET,
+                        palette.fg(color::PURPLE), count, RESET
+                    )
+                })
+                .filter(|top| (width as usize) >= label_len + strip_ansi_len(top) + MIN_SPARKLINE_WIDTH)
+                .unwrap_or_default();
+            let sparkline_width = (width as usize).saturating_sub(label_len + strip_ansi_len(&top_tool));
             let bins = stats.tool_usage_bins(sparkline_width);
-            let sparkline = render_sparkline(&bins, sparkline_width);
-            format!("{}{}", label, sparkline)
+            let sparkline = render_sparkline(&bins, sparkline_width, palette);
+            format!("{}{}{}", label, sparkline, top_tool)
         }
         7 => {
-            // Compactions (only show if > 0)
+            // Compactions with time since the last one (only show if > 0)
             let compressions = stats.platform_stats.compressions;
             if compressions > 0 {
-                let label = format!(
-                    "{}⊜ Compactions{} {}{}{}",
-                    fg(color::GRAY), RESET,
-                    fg(color::PINK), compressions, RESET
-                );
-                let elapsed = format_elapsed(stats.compressions_changed_at);
-                let timer = format!("{}{}{}", fg(color::GRAY), elapsed, RESET);
-                let label_len = strip_ansi_len(&label);
-                let timer_len = strip_ansi_len(&timer);
-                let gap = (width as usize).saturating_sub(label_len + timer_len);
-                format!("{}{:gap$}{}", label, "", timer, gap = gap)
+                format!(
+                    "{}⊜ Compact{} {}{}{}{}",
+                    palette.fg(color::GRAY), RESET,
+                    palette.fg(color::PINK), compressions, RESET,
+                    compaction_age(stats, palette)
+                )
             } else {
                 String::new()
             }
         }
         8 => {
-            // Idle time (only show when complete/question/interrupted state and idle > 60s)
+            // Idle time (only show when complete/question/interrupted state)
             let is_idle_state = matches!(
                 stats.effective_state(),
-                SessionState::Complete | SessionState::Question | SessionState::Interrupted
+                SessionState::Complete | SessionState::Question | SessionState::Interrupted | SessionState::Paused
             );
             if is_idle_state {
                 if let Some(secs) = idle_seconds(stats.platform_stats.idle_since) {
                     format!(
                         "{}◇ Idle{} {}{}{}",
-                        fg(color::GRAY), RESET,
-                        fg(color::GRAY), format_duration_compact(secs), RESET
+                        palette.fg(color::GRAY), RESET,
+                        palette.fg(color::GRAY), format_idle_duration(secs), RESET
                     )
                 } else {
                     String::new()
@@ -359,6 +577,299 @@ fn draw_normal_row(row: u16, width: u16, stats: &SessionStats, cloud_status: Opt
                 String::new()
             }
         }
+        9 => {
+            // Countdown to the session budget (only when max_session_minutes is set),
+            // then the token budget once most of it is used
+            let countdown = max_session_minutes
+                .map(|max| format_session_countdown(time_remaining(stats.started_at(), max), palette));
+            let budget = token_budget
+                .map(|budget| stats.budget_percent(budget))
+                .filter(|percent| *percent >= BUDGET_WARNING_PERCENT)
+                .map(|percent| format_budget_bar(percent, palette));
+            match (countdown, budget) {
+                (Some(countdown), Some(budget)) => format!("{}  {}", countdown, budget),
+                (countdown, budget) => countdown.or(budget).unwrap_or_default(),
+            }
+        }
+        10 => {
+            // Pasted text (only show once something was pasted)
+            if stats.paste_events.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "{}Pasted: {} total, {} max{}",
+                    palette.fg(color::GRAY),
+                    format_bytes(stats.total_pasted_bytes()),
+                    format_bytes(stats.largest_paste_bytes()),
+                    RESET
+                )
+            }
+        }
+        11 => {
+            // Average and median thinking time per turn (once there are two to compare)
+            match (stats.avg_thinking_secs(), stats.median_thinking_secs()) {
+                (Some(avg), Some(median)) if stats.thinking_time_samples.len() >= 2 => format!(
+                    "{}◇ Per turn{} {}avg {} | med {}{}",
+                    palette.fg(color::GRAY), RESET,
+                    palette.fg(color::GREEN), format_turn_secs(avg), format_turn_secs(median), RESET
+                ),
+                _ => String::new(),
+            }
+        }
         _ => String::new(),
     }
 }
+
+/// `⏳ 45m left`: orange in the last five minutes, flashing red once time is up
+fn format_session_countdown(remaining: Option<Duration>, palette: &Palette) -> String {
+    match remaining {
+        Some(left) => {
+            let value_color = if left <= SESSION_WARNING { color::ORANGE } else { color::GRAY };
+            format!("{}⏳ {} left{}", palette.fg(value_color), format_time_remaining(left), RESET)
+        }
+        None => {
+            let dim = if pulse_dim() { escape::DIM } else { "" };
+            format!("{}{}⏳ Time's up{}", palette.fg(color::RED), dim, RESET)
+        }
+    }
+}
+
+/// `Budget: 80% ████░`: orange, red once the budget is used up
+fn format_budget_bar(percent: u8, palette: &Palette) -> String {
+    let filled = (percent as usize * BUDGET_BAR_WIDTH).div_ceil(100).min(BUDGET_BAR_WIDTH);
+    let value_color = if percent >= 100 { color::RED } else { color::ORANGE };
+    format!(
+        "{}Budget:{} {}{}% {}{}{}{}",
+        palette.fg(color::GRAY), RESET,
+        palette.fg(value_color), percent,
+        "█".repeat(filled), palette.fg(color::DARK_GRAY), "░".repeat(BUDGET_BAR_WIDTH - filled), RESET
+    )
+}
+
+/// Per-turn thinking time as `45s`, `3m12s` or `1h5m`
+fn format_turn_secs(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 if !secs.is_multiple_of(60) => format!("{}m{}s", secs / 60, secs % 60),
+        _ => format_duration_compact(secs),
+    }
+}
+
+/// Byte count as `512B`, `4.2KB` or `1.3MB`
+fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KB {
+        format!("{}B", bytes)
+    } else if b < KB * KB {
+        format!("{:.1}KB", b / KB)
+    } else {
+        format!("{:.1}MB", b / (KB * KB))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::utils::strip_ansi;
+
+    #[test]
+    fn test_thinking_indicator_shows_elapsed() {
+        for (secs, expected) in [(0, "[0s]"), (30, "[30s]"), (90, "[90s]")] {
+            let plain = strip_ansi(&format_state_indicator(SessionState::Thinking, Some(secs), &Palette::default()));
+            assert!(THROBBER.contains(&plain.chars().next().unwrap()));
+            assert_eq!(&plain[plain.len() - expected.len()..], expected);
+            assert_eq!(plain.chars().count(), 2 + expected.len());
+        }
+    }
+
+    #[test]
+    fn test_throbber_gradient_pulses_once_per_cycle() {
+        assert_eq!(throbber_gradient(0), (0, 200, 0));
+        assert_eq!(throbber_gradient(250), (0, 228, 0));
+        assert_eq!(throbber_gradient(500), (0, 255, 0));
+        assert_eq!(throbber_gradient(750), (0, 228, 0));
+        assert_eq!(throbber_gradient(1000), (0, 200, 0));
+        // Tests render in 256-color mode, where the throbber stays plain green
+        assert_eq!(throbber_color(&Palette::default()), Palette::default().fg(color::GREEN));
+    }
+
+    fn stats_with_tool_calls(tools: &[&str]) -> SessionStats {
+        let mut stats = SessionStats::new();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+        for tool in tools {
+            stats.platform_stats.tool_calls.push(crate::platforms::ToolCall {
+                tool: tool.to_string(),
+                ts: now,
+            });
+        }
+        stats.platform_stats.compressions = 2;
+        stats
+    }
+
+    #[test]
+    fn test_heatmap_replaces_sparkline_when_tall() {
+        let stats = stats_with_tool_calls(&["Read", "TodoWrite", "Read"]);
+        let palette = Palette::default();
+        let row = |row, height| strip_ansi(&draw_normal_row(row, 20, height, &stats, None, None, None, &palette));
+
+        // Height 10 leaves room for two tool rows, busiest first
+        assert!(row(6, 10).starts_with("Read   "));
+        assert!(row(7, 10).starts_with("TodoWr "));
+        assert_eq!(row(7, 10).chars().count(), 20);
+        assert!(row(8, 10).starts_with("⊜ Compact 2"));
+
+        // Too short for the heatmap: the sparkline stays
+        assert!(row(6, 7).starts_with("⚙ Tools"));
+        assert!(row(7, 7).starts_with("⊜ Compact 2"));
+    }
+
+    #[test]
+    fn test_heatmap_label_pads_by_display_width() {
+        let stats = stats_with_tool_calls(&["日本語ツール", "🇺🇸Flag"]);
+        for row in [0, 1] {
+            let plain = strip_ansi(&draw_heatmap_row(row, 2, 20, &stats, &Palette::default()));
+            assert_eq!(strip_ansi_len(&plain), 20, "{:?}", plain);
+        }
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(4300), "4.2KB");
+        assert_eq!(format_bytes(1843), "1.8KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5MB");
+    }
+
+    #[test]
+    fn test_paste_row_only_after_paste() {
+        let mut stats = SessionStats::new();
+        assert_eq!(draw_normal_row(10, 40, 12, &stats, None, None, None, &Palette::default()), "");
+        stats.record_paste(2457);
+        stats.record_paste(1843);
+        let plain = strip_ansi(&draw_normal_row(10, 40, 12, &stats, None, None, None, &Palette::default()));
+        assert_eq!(plain, "Pasted: 4.2KB total, 2.4KB max");
+    }
+
+    #[test]
+    fn test_thinking_average_row() {
+        let mut stats = SessionStats::new();
+        stats.thinking_time_samples = vec![45];
+        assert_eq!(draw_normal_row(11, 40, 13, &stats, None, None, None, &Palette::default()), "");
+
+        stats.thinking_time_samples = vec![20, 32, 83];
+        let plain = strip_ansi(&draw_normal_row(11, 40, 13, &stats, None, None, None, &Palette::default()));
+        assert_eq!(plain, "◇ Per turn avg 45s | med 32s");
+
+        assert_eq!(format_turn_secs(59.6), "1m");
+        assert_eq!(format_turn_secs(192.0), "3m12s");
+        assert_eq!(format_turn_secs(3900.0), "1h5m");
+    }
+
+    #[test]
+    fn test_time_remaining() {
+        let start = Instant::now();
+        let at = |pct: u64| time_remaining_at(start, 60, start + Duration::from_secs(36 * pct));
+        assert_eq!(at(0), Some(Duration::from_secs(3600)));
+        assert_eq!(at(50), Some(Duration::from_secs(1800)));
+        assert_eq!(at(95), Some(Duration::from_secs(180)));
+        assert_eq!(at(100), None);
+        assert_eq!(at(105), None);
+        // A session started just now has (almost) the whole budget
+        assert!(time_remaining(Instant::now(), 10).unwrap() > Duration::from_secs(599));
+    }
+
+    #[test]
+    fn test_format_time_remaining() {
+        assert_eq!(format_time_remaining(Duration::from_secs(45 * 60)), "45m");
+        assert_eq!(format_time_remaining(Duration::from_secs(44 * 60 + 1)), "45m");
+        assert_eq!(format_time_remaining(Duration::from_secs(3900)), "1h 5m");
+        assert_eq!(format_time_remaining(Duration::from_secs(3599)), "1h");
+        assert_eq!(format_time_remaining(Duration::from_secs(30)), "30s");
+    }
+
+    #[test]
+    fn test_session_countdown_colors() {
+        let palette = Palette::default();
+        let plenty = format_session_countdown(Some(Duration::from_secs(45 * 60)), &palette);
+        assert_eq!(strip_ansi(&plenty), "⏳ 45m left");
+        assert!(plenty.starts_with(&palette.fg(color::GRAY)));
+        let low = format_session_countdown(Some(Duration::from_secs(4 * 60)), &palette);
+        assert!(low.starts_with(&palette.fg(color::ORANGE)));
+        let up = format_session_countdown(None, &palette);
+        assert_eq!(strip_ansi(&up), "⏳ Time's up");
+        assert!(up.starts_with(&palette.fg(color::RED)));
+    }
+
+    #[test]
+    fn test_countdown_row_needs_limit() {
+        let stats = SessionStats::new();
+        assert_eq!(draw_normal_row(9, 40, 12, &stats, None, None, None, &Palette::default()), "");
+        let plain = strip_ansi(&draw_normal_row(9, 40, 12, &stats, None, Some(90), None, &Palette::default()));
+        assert_eq!(plain, "⏳ 1h 30m left");
+    }
+
+    #[test]
+    fn test_budget_row_within_twenty_percent() {
+        let mut stats = SessionStats::new();
+        stats.total_tokens = 79_000;
+        assert_eq!(draw_normal_row(9, 40, 12, &stats, None, None, Some(100_000), &Palette::default()), "");
+        stats.total_tokens = 80_000;
+        let plain = strip_ansi(&draw_normal_row(9, 40, 12, &stats, None, None, Some(100_000), &Palette::default()));
+        assert_eq!(plain, "Budget: 80% ████░");
+        stats.total_tokens = 250_000;
+        let plain = strip_ansi(&draw_normal_row(9, 60, 12, &stats, None, Some(90), Some(100_000), &Palette::default()));
+        assert_eq!(plain, "⏳ 1h 30m left  Budget: 100% █████");
+    }
+
+    #[test]
+    fn test_heatmap_needs_tool_timeline() {
+        let stats = SessionStats::new();
+        let plain = strip_ansi(&draw_normal_row(6, 20, 10, &stats, None, None, None, &Palette::default()));
+        assert!(plain.starts_with("⚙ Tools"));
+    }
+
+    #[test]
+    fn test_format_compaction_age() {
+        assert_eq!(format_compaction_age(0), "(just now)");
+        assert_eq!(format_compaction_age(59), "(just now)");
+        assert_eq!(format_compaction_age(300), "(5m ago)");
+        assert_eq!(format_compaction_age(3720), "(1h2m ago)");
+    }
+
+    #[test]
+    fn test_recent_compaction_is_a_warning() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+        let mut stats = SessionStats::new();
+        stats.platform_stats.compressions = 3;
+
+        stats.platform_stats.last_compressed_at = Some(now - 300.0);
+        let row = draw_normal_row(7, 40, 8, &stats, None, None, None, &Palette::default());
+        assert_eq!(strip_ansi(&row), "⊜ Compact 3 (5m ago)");
+        assert!(row.contains(&format!("{}(5m ago)", Palette::default().fg(color::YELLOW))));
+
+        stats.platform_stats.last_compressed_at = Some(now - 1800.0);
+        let row = draw_normal_row(7, 40, 8, &stats, None, None, None, &Palette::default());
+        assert!(row.contains(&format!("{}(30m ago)", Palette::default().fg(color::GRAY))));
+    }
+
+    #[test]
+    fn test_format_idle_duration() {
+        assert_eq!(format_idle_duration(0), "just now");
+        assert_eq!(format_idle_duration(9), "just now");
+        assert_eq!(format_idle_duration(10), "10s");
+        assert_eq!(format_idle_duration(59), "30s");
+        assert_eq!(format_idle_duration(60), "1m");
+        assert_eq!(format_idle_duration(3601), "1h");
+    }
+
+    #[test]
+    fn test_indicator_without_elapsed() {
+        let plain = strip_ansi(&format_state_indicator(SessionState::Thinking, None, &Palette::default()));
+        assert_eq!(plain.chars().count(), 1);
+        // Elapsed time is only meaningful while thinking
+        let complete = strip_ansi(&format_state_indicator(SessionState::Complete, Some(30), &Palette::default()));
+        assert_eq!(complete, "✓ Complete");
+    }
+}
@@ -1,8 +1,14 @@
 //! Status bar rendering
 //!
 //! Coordinates the layout and rendering of all status bar widgets.
+//! Terminals narrower than `compact_threshold` switch every widget to
+//! `WidgetMode::Compact`, a one-line summary each. Custom widgets get a
+//! column each after the changes widget, as many as leave the built-in
+//! widgets `MIN_BUILTIN_WIDTH` columns. `layout.widgets` in config.toml
+//! replaces the adaptive stats | git | changes columns with a fixed order and
+//! widths (see `columns.rs`).
 
-use std::io::{Stdout, Write};
+use std::io::Write;
 use std::path::Path;
 
 use anyhow::Result;
@@ -12,21 +18,63 @@ use crate::git::GitState;
 use crate::hooks::SessionStats;
 use crate::ide::IdeKind;
 use crate::parsers::DiffSummary;
-use crate::terminal::escape::{self, color, RESET};
+use crate::platforms::SessionState;
+use crate::terminal::escape::{self, color, DIM, RESET};
+use crate::terminal::theme::Palette;
 
-use super::{draw_changes_widget, draw_git_widget, draw_stats_widget, WidgetArea};
+use super::{
+    column_widths, draw_changes_widget, draw_git_widget, draw_stats_widget, pulse_dim, separator_color,
+    separator_line, AppState, CustomWidget, SeparatorStyle, WidgetArea, WidgetMode, WidgetName, WidgetSpec,
+};
+
+/// Columns the stats, git and changes widgets keep before custom widgets get any
+const MIN_BUILTIN_WIDTH: u16 = 80;
 
 /// Layout information needed for rendering widgets
 pub struct Layout {
     pub pty_rows: u16,
     pub total_cols: u16,
     pub status_rows: u16,
+    /// Cap on files listed in the git widget (None = all)
+    pub max_git_files: Option<usize>,
+    /// Widths below this render compact widgets
+    pub compact_threshold: u16,
+    /// Separator row style
+    pub separator_style: SeparatorStyle,
+    /// Session length budget shown as a countdown in the stats widget
+    pub max_session_minutes: Option<u64>,
+    /// Token budget; the stats widget shows its use once past 80%
+    pub token_budget: Option<u64>,
+    /// Append `~N words` to the changes header
+    pub show_word_count: bool,
+    /// Leave changes in test files out of the changes widget
+    pub hide_test_changes: bool,
+    /// Width of each custom widget column
+    pub custom_widget_width: u16,
+    /// Built-in widget columns in order (empty = adaptive stats | git | changes)
+    pub widgets: Vec<WidgetSpec>,
+    /// Colors everything in the status bar is drawn with
+    pub palette: Palette,
+}
+
+impl Layout {
+    /// How many of `requested` custom widget columns fit (none in compact mode)
+    pub fn custom_widget_slots(&self, requested: usize) -> usize {
+        if WidgetMode::for_width(self.total_cols, self.compact_threshold) == WidgetMode::Compact
+            || self.custom_widget_width == 0
+        {
+            return 0;
+        }
+        let spare = self.total_cols.saturating_sub(MIN_BUILTIN_WIDTH);
+        // Each column also takes a separator
+        requested.min((spare / (self.custom_widget_width + 1)) as usize)
+    }
 }
 
 /// Draw the entire status bar area with all widgets
 #[allow(clippy::too_many_arguments)]
 pub fn draw_status_bar(
-    stdout: &mut Stdout,
+    stdout: &mut impl Write,
     layout: &Layout,
     session_stats: &SessionStats,
     git_state: &GitState,
@@ -35,38 +83,64 @@ pub fn draw_status_bar(
     ide: IdeKind,
     cwd: &Path,
     cloud_status: Option<&CloudStatus>,
+    custom_widgets: &[Box<dyn CustomWidget>],
 ) -> Result<()> {
+    // No status area (--no-status-bar)
+    if layout.status_rows == 0 {
+        return Ok(());
+    }
+
     // Save cursor position
     write!(stdout, "{}", escape::CURSOR_SAVE)?;
 
     // Move to status area (below the scroll region)
     write!(stdout, "{}", escape::cursor_to(layout.pty_rows + 1, 1))?;
 
-    // Draw thick separator line (matching banner style)
-    write!(stdout, "{}{}", escape::bg(color::BG_DARK), escape::fg(color::DARK_GRAY))?;
-    for _ in 0..layout.total_cols {
-        write!(stdout, "━")?;
+    let mode = WidgetMode::for_width(layout.total_cols, layout.compact_threshold);
+    let palette = &layout.palette;
+
+    // Separator line colored by session state (pulsing while thinking), or just a marker when narrow
+    let state = session_stats.effective_state();
+    write!(stdout, "{}{}", palette.bg(color::BG_DARK), palette.fg(separator_color(state)))?;
+    if state == SessionState::Thinking && pulse_dim() {
+        write!(stdout, "{}", DIM)?;
+    }
+    if mode == WidgetMode::Compact {
+        let pad = layout.total_cols.saturating_sub(1) as usize;
+        write!(stdout, "▸{}{:pad$}", RESET, "", pad = pad)?;
+    } else {
+        let line = separator_line(layout.separator_style, layout.total_cols as usize);
+        write!(stdout, "{}{}", line, RESET)?;
     }
-    write!(stdout, "{}", RESET)?;
 
     // Calculate column widths based on available height
     // In compact mode (short terminal), stats gets more width for two-column layout
     let compact = layout.status_rows <= 5;
-    let stats_width = if compact {
+    let stats_width = if mode == WidgetMode::Compact {
+        // Narrow terminal: stats summary gets 2/5, git and changes split the rest
+        layout.total_cols.saturating_sub(2) * 2 / 5
+    } else if compact {
         // Wider stats for two-column layout: ~30% of width, min 36 chars
         ((layout.total_cols as f32) * 0.30).max(36.0) as u16
     } else {
         // Normal: ~17% of width, min 24 chars
         ((layout.total_cols as f32) * 0.17).max(24.0) as u16
     };
-    let remaining = layout.total_cols.saturating_sub(stats_width + 2); // 2 for separators
+    let custom_slots = layout.custom_widget_slots(custom_widgets.len());
+    let custom_total = custom_slots as u16 * (layout.custom_widget_width + 1);
+    let remaining = layout.total_cols.saturating_sub(stats_width + 2 + custom_total); // 2 for separators
 
     // Check if git needs multiple columns (files > available rows)
     let git_available_rows = layout.status_rows.saturating_sub(2) as usize; // -2 for separator + header
-    let git_needs_multi_column = git_state.files.len() > git_available_rows;
+    // With max_git_files the widget shows at most max + 1 entries (files plus "… and N more")
+    let git_entries = match layout.max_git_files {
+        Some(max) if git_state.files.len() > max => max + 1,
+        _ => git_state.files.len(),
+    };
+    let git_needs_multi_column = git_entries > git_available_rows;
 
     // Flex ratio: git gets 4/8 if multi-column, 3/8 if single-column
-    let (git_width, changes_width) = if git_needs_multi_column {
+    let (git_width, changes_width) = if git_needs_multi_column || mode == WidgetMode::Compact {
         // 4:4 split (50/50)
         let git_w = remaining / 2;
         (git_w, remaining - git_w)
@@ -76,60 +150,81 @@ pub fn draw_status_bar(
         (git_w, remaining - git_w)
     };
 
+    let columns: Vec<(WidgetName, u16)> = if layout.widgets.is_empty() {
+        vec![
+            (WidgetName::Stats, stats_width),
+            (WidgetName::Git, git_width),
+            (WidgetName::Changes, changes_width),
+        ]
+    } else {
+        // One separator between each pair of columns
+        let separators = layout.widgets.len().saturating_sub(1) as u16;
+        let available = layout.total_cols.saturating_sub(separators + custom_total);
+        let widths = column_widths(&layout.widgets, available);
+        layout.widgets.iter().map(|spec| spec.name).zip(widths).collect()
+    };
+
     // Draw content rows
     for row in 1..layout.status_rows {
         write!(stdout, "{}", escape::cursor_to(layout.pty_rows + 1 + row, 1))?;
 
-        // Stats column (leftmost, fixed width)
-        draw_stats_widget(
-            stdout,
-            WidgetArea {
-                pty_rows: layout.pty_rows,
-                col: 0,
-                row,
-                width: stats_width,
-                height: layout.status_rows,
-            },
-            session_stats,
-            cloud_status,
-        )?;
-
-        // Separator
-        write!(stdout, "{}│{}", escape::fg(color::DARK_GRAY), RESET)?;
-
-        // Git column
-        draw_git_widget(
-            stdout,
-            WidgetArea {
+        let mut col = 0;
+        for (i, &(name, width)) in columns.iter().enumerate() {
+            if i > 0 {
+                write!(stdout, "{}│{}", palette.fg(color::DARK_GRAY), RESET)?;
+                col += 1;
+            }
+            let area = WidgetArea {
                 pty_rows: layout.pty_rows,
-                col: stats_width + 1,
+                col,
                 row,
-                width: git_width,
+                width,
                 height: layout.status_rows,
-            },
-            git_state,
-            ide,
-            cwd,
-        )?;
-
-        // Separator
-        write!(stdout, "{}│{}", escape::fg(color::DARK_GRAY), RESET)?;
-
-        // Changes column (rightmost)
-        draw_changes_widget(
-            stdout,
-            WidgetArea {
+            };
+            match name {
+                WidgetName::Stats => draw_stats_widget(
+                    stdout,
+                    area,
+                    session_stats,
+                    cloud_status,
+                    layout.max_session_minutes,
+                    layout.token_budget,
+                    mode,
+                    palette,
+                )?,
+                WidgetName::Git => {
+                    draw_git_widget(stdout, area, git_state, layout.max_git_files, ide, cwd, mode, palette)?
+                }
+                WidgetName::Changes => draw_changes_widget(
+                    stdout,
+                    area,
+                    diff_summary,
+                    terminal_title,
+                    ide,
+                    cwd,
+                    layout.show_word_count,
+                    layout.hide_test_changes,
+                    mode,
+                    palette,
+                )?,
+            }
+            col += width;
+        }
+
+        // Custom widgets, one column each to the right
+        let state = AppState { palette };
+        for widget in &custom_widgets[..custom_slots] {
+            write!(stdout, "{}│{}", palette.fg(color::DARK_GRAY), RESET)?;
+            let area = WidgetArea {
                 pty_rows: layout.pty_rows,
-                col: stats_width + git_width + 2,
+                col: col + 1,
                 row,
-                width: changes_width,
+                width: layout.custom_widget_width,
                 height: layout.status_rows,
-            },
-            diff_summary,
-            terminal_title,
-            ide,
-            cwd,
-        )?;
+            };
+            widget.draw(stdout, area, &state)?;
+            col += layout.custom_widget_width + 1;
+        }
     }
 
     // Restore cursor position
@@ -138,3 +233,161 @@ pub fn draw_status_bar(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::FileStatus;
+    use crate::parsers::{ChangeNode, FileChanges};
+
+    const PTY_ROWS: u16 = 10;
+    const STATUS_ROWS: u16 = 6;
+
+    fn git_state() -> GitState {
+        let file = |path: &str| FileStatus::fixture(path, 3, 1);
+        GitState {
+            files: vec![file("src/app.rs"), file("src/ui.rs"), file("README.md")],
+            branch: "main".to_string(),
+            is_repo: true,
+            ..Default::default()
+        }
+    }
+
+    fn diff_summary() -> DiffSummary {
+        let change = |name: &str| ChangeNode {
+            additions: 3,
+            deletions: 1,
+            ..ChangeNode::fixture(name, "src/app.rs")
+        };
+        DiffSummary {
+            files: vec![FileChanges {
+                path: "src/app.rs".to_string(),
+                language: "Rust".to_string(),
+                changes: vec![change("run"), change("draw")],
+                is_test: false,
+            }],
+            loading: false,
+            ..Default::default()
+        }
+    }
+
+    /// Render the status bar at `cols` wide and return its rows as plain text
+    fn render(cols: u16) -> Vec<String> {
+        render_with(cols, SeparatorStyle::default())
+    }
+
+    fn render_with(cols: u16, separator_style: SeparatorStyle) -> Vec<String> {
+        render_layout(layout(cols, separator_style))
+    }
+
+    fn layout(cols: u16, separator_style: SeparatorStyle) -> Layout {
+        Layout {
+            pty_rows: PTY_ROWS,
+            total_cols: cols,
+            status_rows: STATUS_ROWS,
+            max_git_files: None,
+            compact_threshold: 80,
+            separator_style,
+            max_session_minutes: None,
+            token_budget: None,
+            show_word_count: false,
+            hide_test_changes: false,
+            custom_widget_width: 24,
+            widgets: Vec::new(),
+            palette: Palette::default(),
+        }
+    }
+
+    fn render_layout(layout: Layout) -> Vec<String> {
+        let cols = layout.total_cols;
+        let mut buf = Vec::new();
+        draw_status_bar(
+            &mut buf,
+            &layout,
+            &SessionStats::new(),
+            &git_state(),
+            &diff_summary(),
+            None,
+            IdeKind::None,
+            Path::new("/repo"),
+            None,
+            &[],
+        )
+        .unwrap();
+
+        let mut parser = vt100::Parser::new(PTY_ROWS + STATUS_ROWS, cols, 0);
+        parser.process(&buf);
+        parser.screen().rows(0, cols).skip(PTY_ROWS as usize).collect()
+    }
+
+    #[test]
+    fn test_mode_follows_threshold() {
+        assert_eq!(WidgetMode::for_width(79, 80), WidgetMode::Compact);
+        assert_eq!(WidgetMode::for_width(80, 80), WidgetMode::Normal);
+        assert_eq!(WidgetMode::for_width(40, 0), WidgetMode::Normal);
+    }
+
+    #[test]
+    fn test_compact_widgets_at_narrow_widths() {
+        for cols in [40, 60, 79] {
+            let rows = render(cols);
+            assert_eq!(rows[0].trim_end(), "▸", "separator at {} cols", cols);
+
+            // One summary per widget on the header row, in stats | git | changes order
+            let header: Vec<&str> = rows[1].split('│').map(str::trim).collect();
+            assert_eq!(header.len(), 3, "header at {} cols: {:?}", cols, rows[1]);
+            assert!(header[0].starts_with("○ Ready"), "stats at {} cols: {:?}", cols, header[0]);
+            assert_eq!(header[1], "3 files");
+            assert_eq!(header[2], "2 changes");
+
+            // Everything below the header is blank apart from the column separators
+            for row in &rows[2..] {
+                assert!(row.chars().all(|c| c == ' ' || c == '│'), "row at {} cols: {:?}", cols, row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_widgets_at_threshold() {
+        let rows = render(80);
+        assert!(rows[0].chars().all(|c| c == '━'));
+        assert_eq!(rows[0].chars().count(), 80);
+        assert!(rows[2..].iter().any(|r| r.contains("app.rs")));
+    }
+
+    #[test]
+    fn test_custom_widget_slots() {
+        let width = |total_cols: u16| layout(total_cols, SeparatorStyle::default());
+        // The built-in widgets keep 80 columns; each custom one takes 24 + a separator
+        assert_eq!(width(80).custom_widget_slots(3), 0);
+        assert_eq!(width(105).custom_widget_slots(3), 1);
+        assert_eq!(width(200).custom_widget_slots(3), 3);
+        assert_eq!(width(200).custom_widget_slots(0), 0);
+        assert_eq!(width(60).custom_widget_slots(3), 0);
+    }
+
+    #[test]
+    fn test_configured_widget_order() {
+        let mut layout = layout(100, SeparatorStyle::default());
+        layout.widgets = ["changes:50", "git", "stats:20"]
+            .iter()
+            .map(|s| WidgetSpec::try_from(s.to_string()).unwrap())
+            .collect();
+        let rows = render_layout(layout);
+        // Percentages of the 98 columns left after two separators; git gets the rest
+        let header: Vec<&str> = rows[1].split('│').collect();
+        assert_eq!(header.iter().map(|c| c.chars().count()).collect::<Vec<_>>(), vec![49, 30, 19]);
+        assert!(header[1].contains("3 files"), "{:?}", header);
+        assert!(header[0].starts_with("Rust 2 changes"), "{:?}", header);
+        assert!(header[2].contains("Ready"), "{:?}", header);
+    }
+
+    #[test]
+    fn test_separator_style_is_drawn() {
+        let rows = render_with(80, SeparatorStyle::Label);
+        assert_eq!(rows[0], format!("{} crabigator {}", "─".repeat(34), "─".repeat(34)));
+        assert_eq!(render_with(80, SeparatorStyle::None)[0].trim(), "");
+        // Compact mode keeps its marker whatever the style
+        assert_eq!(render_with(60, SeparatorStyle::Double)[0].trim_end(), "▸");
+    }
+}
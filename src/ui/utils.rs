@@ -3,31 +3,33 @@
 //! This module contains helper functions used across the application
 //! for string manipulation, formatting, and other pure computations.
 
-use unicode_width::UnicodeWidthChar;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::terminal::escape::{color, fg, RESET};
+use crate::terminal::escape::{color, RESET};
+use crate::terminal::theme::Palette;
 
 /// Truncate a path string, showing the end with ellipsis if too long
+///
+/// `max_len` is in display cells; the path is cut on grapheme boundaries.
 pub fn truncate_path(path: &str, max_len: usize) -> String {
-    let char_count = path.chars().count();
-    if char_count <= max_len {
+    if display_width(path) <= max_len {
         path.to_string()
     } else if max_len <= 3 {
         "...".to_string()
     } else {
-        // Show end of path (more useful)
-        // Skip (char_count - (max_len - 1)) characters to show the last (max_len - 1) chars
-        let skip = char_count - (max_len - 1);
-        let suffix: String = path.chars().skip(skip).collect();
-        format!("…{}", suffix)
+        // Show end of path (more useful): the last (max_len - 1) cells
+        format!("…{}", take_graphemes_from_end(path, max_len - 1))
     }
 }
 
 /// Truncate a string with ellipsis at ~30% from the beginning
 /// e.g., "very_long_function_name_here" -> "very_lon…name_here"
+///
+/// `max_len` is in display cells; the string is cut on grapheme boundaries
+/// so emoji sequences and combining marks are never split.
 pub fn truncate_middle(s: &str, max_len: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_len {
+    if display_width(s) <= max_len {
         s.to_string()
     } else if max_len <= 1 {
         "…".to_string()
@@ -37,12 +39,54 @@ pub fn truncate_middle(s: &str, max_len: usize) -> String {
         let prefix_len = (available * 30) / 100;
         let suffix_len = available - prefix_len;
 
-        let prefix: String = s.chars().take(prefix_len).collect();
-        let suffix: String = s.chars().skip(char_count - suffix_len).collect();
+        let prefix = take_graphemes(s, prefix_len);
+        let suffix = take_graphemes_from_end(s, suffix_len);
         format!("{}…{}", prefix, suffix)
     }
 }
 
+/// Display width of `s` in terminal cells, summed per grapheme cluster
+///
+/// Counting chars over-counts combining marks and emoji sequences (a flag is
+/// two regional indicators but one 2-cell glyph) and under-counts CJK.
+pub fn display_width(s: &str) -> usize {
+    s.graphemes(true).map(|g| g.width()).sum()
+}
+
+/// Pad `s` with spaces on the right to `width` cells
+pub fn pad_to_width(s: &str, width: usize) -> String {
+    let pad = width.saturating_sub(display_width(s));
+    format!("{}{}", s, " ".repeat(pad))
+}
+
+/// Longest prefix of whole graphemes fitting in `max_width` cells
+pub fn take_graphemes(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    let mut end = 0;
+    for (idx, grapheme) in s.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > max_width {
+            break;
+        }
+        end = idx + grapheme.len();
+    }
+    &s[..end]
+}
+
+/// Longest suffix of whole graphemes fitting in `max_width` cells
+fn take_graphemes_from_end(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    let mut start = s.len();
+    for (idx, grapheme) in s.grapheme_indices(true).rev() {
+        width += grapheme.width();
+        if width > max_width {
+            break;
+        }
+        start = idx;
+    }
+    &s[start..]
+}
+
 /// Extract just the filename from a path
 pub fn get_filename(path: &str) -> &str {
     path.rsplit('/').next().unwrap_or(path)
@@ -113,10 +157,11 @@ pub fn format_diff_stats(
     deletions: usize,
     _max_changes: usize,
     show_bar: usize,
+    palette: &Palette,
 ) -> String {
     let total = additions + deletions;
     if total == 0 {
-        return format!("{}·{}", fg(color::DARK_GRAY), RESET);
+        return format!("{}·{}", palette.fg(color::DARK_GRAY), RESET);
     }
 
     let mut result = String::new();
@@ -136,7 +181,7 @@ pub fn format_diff_stats(
     // Format: −N ▓▓████ +M
     // Deletions on left
     if deletions > 0 {
-        result.push_str(&format!("{}−{}{}", fg(color::RED), deletions, RESET));
+        result.push_str(&format!("{}−{}{}", palette.fg(color::RED), deletions, RESET));
     }
 
     // Bar in middle (if requested)
@@ -145,10 +190,10 @@ pub fn format_diff_stats(
             result.push(' ');
         }
         if del_bar > 0 {
-            result.push_str(&format!("{}{}{}", fg(color::RED), "▓".repeat(del_bar), RESET));
+            result.push_str(&format!("{}{}{}", palette.fg(color::RED), "▓".repeat(del_bar), RESET));
         }
         if add_bar > 0 {
-            result.push_str(&format!("{}{}{}", fg(color::GREEN), "█".repeat(add_bar), RESET));
+            result.push_str(&format!("{}{}{}", palette.fg(color::GREEN), "█".repeat(add_bar), RESET));
         }
         if additions > 0 {
             result.push(' ');
@@ -159,7 +204,7 @@ pub fn format_diff_stats(
 
     // Additions on right
     if additions > 0 {
-        result.push_str(&format!("{}+{}{}", fg(color::GREEN), additions, RESET));
+        result.push_str(&format!("{}+{}{}", palette.fg(color::GREEN), additions, RESET));
     }
 
     result
@@ -169,6 +214,7 @@ pub fn format_diff_stats(
 /// Columns: [del_num] [del_bar|add_bar] [add_num]
 /// Each column is padded to specified widths for alignment across rows
 /// Bar columns have separate widths: del_bar extends LEFT, add_bar extends RIGHT
+#[allow(clippy::too_many_arguments)]
 pub fn format_diff_stats_aligned(
     additions: usize,
     deletions: usize,
@@ -177,13 +223,14 @@ pub fn format_diff_stats_aligned(
     del_bar_width: usize,   // width for left bar column (deletions)
     add_bar_width: usize,   // width for right bar column (additions)
     add_num_width: usize,   // width for addition number column (including +)
+    palette: &Palette,
 ) -> String {
     let total = additions + deletions;
     if total == 0 {
         // Center a dot in the total width
         let total_width = del_num_width + 1 + del_bar_width + add_bar_width + 1 + add_num_width;
         let pad = total_width / 2;
-        return format!("{:>pad$}{}·{}{:pad$}", "", fg(color::DARK_GRAY), RESET, "", pad = pad);
+        return format!("{:>pad$}{}·{}{:pad$}", "", palette.fg(color::DARK_GRAY), RESET, "", pad = pad);
     }
 
     let mut result = String::new();
@@ -202,7 +249,7 @@ pub fn format_diff_stats_aligned(
 
     // Deletion number column (right-aligned)
     if deletions > 0 {
-        let del_str = format!("{}−{}{}", fg(color::RED), deletions, RESET);
+        let del_str = format!("{}−{}{}", palette.fg(color::RED), deletions, RESET);
         let actual_width = 1 + digit_count(deletions);
         let pad = del_num_width.saturating_sub(actual_width);
         result.push_str(&format!("{:pad$}{}", "", del_str, pad = pad));
@@ -218,12 +265,14 @@ pub fn format_diff_stats_aligned(
         let left_pad = del_bar_width.saturating_sub(del_bar);
         result.push_str(&format!("{:left_pad$}", "", left_pad = left_pad));
         if del_bar > 0 {
-            result.push_str(&format!("{}{}{}", fg(color::RED), "▓".repeat(del_bar.min(del_bar_width)), RESET));
+            let bar = "▓".repeat(del_bar.min(del_bar_width));
+            result.push_str(&format!("{}{}{}", palette.fg(color::RED), bar, RESET));
         }
 
         // Right bar: green left-aligned (grows rightward from center)
         if add_bar > 0 {
-            result.push_str(&format!("{}{}{}", fg(color::GREEN), "█".repeat(add_bar.min(add_bar_width)), RESET));
+            let bar = "█".repeat(add_bar.min(add_bar_width));
+            result.push_str(&format!("{}{}{}", palette.fg(color::GREEN), bar, RESET));
         }
         let right_pad = add_bar_width.saturating_sub(add_bar);
         result.push_str(&format!("{:right_pad$}", "", right_pad = right_pad));
@@ -236,7 +285,7 @@ pub fn format_diff_stats_aligned(
 
     // Addition number column (left-aligned)
     if additions > 0 {
-        let add_str = format!("{}+{}{}", fg(color::GREEN), additions, RESET);
+        let add_str = format!("{}+{}{}", palette.fg(color::GREEN), additions, RESET);
         result.push_str(&add_str);
         let actual_width = 1 + digit_count(additions);
         let pad = add_num_width.saturating_sub(actual_width);
@@ -248,6 +297,17 @@ pub fn format_diff_stats_aligned(
     result
 }
 
+/// Format seconds as "1h 05m", "12m" or "45s"
+pub fn format_duration(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 /// Count digits in a number (for width calculation)
 pub fn digit_count(n: usize) -> usize {
     if n == 0 {
@@ -259,65 +319,111 @@ pub fn digit_count(n: usize) -> usize {
 
 /// Create a bar showing folder size (cyan colored for untracked folders)
 /// Scaled relative to max_count (which could be file changes or file counts)
-pub fn create_folder_bar(file_count: usize, max_count: usize, max_width: usize) -> String {
+pub fn create_folder_bar(file_count: usize, max_count: usize, max_width: usize, palette: &Palette) -> String {
     if file_count == 0 {
-        return format!("{}{}{}", fg(color::DARK_GRAY), "·".repeat(max_width.min(2)), RESET);
+        return format!("{}{}{}", palette.fg(color::DARK_GRAY), "·".repeat(max_width.min(2)), RESET);
     }
 
     let max_count = max_count.max(1);
     let scaled = ((file_count as f64 / max_count as f64) * max_width as f64).ceil() as usize;
     let bar_width = scaled.min(max_width).max(1);
 
-    format!("{}{}{}", fg(color::CYAN), "+".repeat(bar_width), RESET)
+    format!("{}{}{}", palette.fg(color::CYAN), "+".repeat(bar_width), RESET)
 }
 
 /// Calculate display width excluding ANSI escape sequences
-/// Uses Unicode width to properly handle wide characters (e.g., ▣ = 2 columns)
-/// Handles both CSI sequences (\x1b[...m) and OSC sequences (\x1b]...\x07)
+/// Measured per grapheme cluster, so wide characters (e.g., ▣ = 2 columns),
+/// flags and combining marks take the cells the terminal gives them
 pub fn strip_ansi_len(s: &str) -> usize {
-    let mut len = 0;
-    let mut chars = s.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '\x1b' {
-            // Check next char to determine escape type
-            match chars.peek() {
-                Some('[') => {
-                    // CSI sequence: \x1b[...m
-                    chars.next(); // consume '['
-                    while let Some(&nc) = chars.peek() {
-                        chars.next();
-                        if nc == 'm' {
-                            break;
-                        }
-                    }
-                }
-                Some(']') => {
-                    // OSC sequence: \x1b]...(\x07 or \x1b\\)
-                    chars.next(); // consume ']'
-                    while let Some(&nc) = chars.peek() {
-                        if nc == '\x07' {
-                            chars.next();
-                            break;
-                        } else if nc == '\x1b' {
-                            chars.next();
-                            if chars.peek() == Some(&'\\') {
-                                chars.next();
-                            }
-                            break;
-                        }
-                        chars.next();
-                    }
-                }
-                _ => {
-                    // Unknown escape, skip just the escape char
-                }
+    // Most labels carry no escapes at all: measure them in place
+    if find_escape(s.as_bytes()).is_none() {
+        return display_width(s);
+    }
+    display_width(&strip_ansi(s))
+}
+
+/// Remove ANSI escape sequences, keeping only the visible text
+/// Handles CSI sequences (\x1b[...m and other final bytes), OSC sequences such as
+/// OSC 8 hyperlinks (\x1b]...\x07 or \x1b]...\x1b\\), DCS/SOS/PM/APC strings,
+/// charset selections like \x1b(B and two-byte escapes like \x1b7
+pub fn strip_ansi(s: &str) -> String {
+    let mut visible = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = find_escape(rest.as_bytes()) {
+        visible.push_str(&rest[..start]);
+        rest = &rest[start + escape_len(&rest.as_bytes()[start..])..];
+    }
+    visible.push_str(rest);
+    visible
+}
+
+/// Length of the escape sequence at the start of `bytes` (which begins with ESC)
+///
+/// Every byte the sequence ends on is ASCII, so the remainder starts on a
+/// char boundary.
+fn escape_len(bytes: &[u8]) -> usize {
+    match bytes.get(1) {
+        Some(b'[') => {
+            // CSI sequence: \x1b[ params final-byte (0x40..=0x7e, e.g. 'm')
+            match bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) {
+                Some(end) => end + 3,
+                None => bytes.len(),
             }
-        } else {
-            len += c.width().unwrap_or(0);
         }
+        Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+            // OSC (]), DCS (P), SOS (X), PM (^), APC (_) strings,
+            // terminated by ST (\x1b\\) or BEL (\x07)
+            match bytes[2..].iter().position(|b| matches!(b, 0x07 | 0x1b)) {
+                Some(end) if bytes[end + 2] == 0x1b && bytes.get(end + 3) == Some(&b'\\') => end + 4,
+                Some(end) => end + 3,
+                None => bytes.len(),
+            }
+        }
+        Some(0x20..=0x2f) => {
+            // nF escapes (\x1b(B, \x1b#8): intermediates, then one final byte
+            match bytes[2..].iter().position(|b| !(0x20..=0x2f).contains(b)) {
+                Some(end) if bytes[end + 2].is_ascii() => end + 3,
+                Some(end) => end + 2,
+                None => bytes.len(),
+            }
+        }
+        // Two-byte escapes: \x1b7 (save cursor), \x1bc (reset), \x1b= (keypad)
+        Some(0x30..=0x7e) => 2,
+        // Unknown escape, skip just the escape char
+        _ => 1,
+    }
+}
+
+/// Index of the first ESC byte in `bytes`
+///
+/// The status bar measures every label on every redraw; on Apple Silicon the
+/// scan compares 16 bytes at a time with NEON.
+#[cfg(target_arch = "aarch64")]
+pub fn find_escape(bytes: &[u8]) -> Option<usize> {
+    use std::arch::aarch64::{vceqq_u8, vdupq_n_u8, vld1q_u8, vmaxvq_u8};
+
+    let mut chunks = bytes.chunks_exact(16);
+    let mut offset = 0;
+    for chunk in chunks.by_ref() {
+        // SAFETY: NEON is part of the aarch64 baseline, and `chunk` holds
+        // exactly the 16 bytes `vld1q_u8` reads
+        let found = unsafe { vmaxvq_u8(vceqq_u8(vld1q_u8(chunk.as_ptr()), vdupq_n_u8(0x1b))) } != 0;
+        if found {
+            return find_escape_scalar(chunk).map(|i| offset + i);
+        }
+        offset += 16;
     }
-    len
+    find_escape_scalar(chunks.remainder()).map(|i| offset + i)
+}
+
+/// Index of the first ESC byte in `bytes`
+#[cfg(not(target_arch = "aarch64"))]
+pub fn find_escape(bytes: &[u8]) -> Option<usize> {
+    find_escape_scalar(bytes)
+}
+
+fn find_escape_scalar(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|&b| b == 0x1b)
 }
 
 #[cfg(test)]
@@ -355,6 +461,59 @@ mod tests {
         assert!(result.starts_with('…'));
     }
 
+    #[test]
+    fn test_truncate_middle_cjk() {
+        // Each ideograph is two cells wide; never exceed max_len cells
+        let s = "日本語のファイル名です";
+        let result = truncate_middle(s, 10);
+        assert!(result.width() <= 10);
+        assert_eq!(result, "日…名です");
+        assert_eq!(truncate_middle("日本", 4), "日本");
+    }
+
+    #[test]
+    fn test_truncate_middle_arabic() {
+        // Right-to-left text is truncated in logical order like any other
+        let s = "مرحبا_بالعالم_الجميل";
+        let result = truncate_middle(s, 10);
+        assert_eq!(result.width(), 10);
+        assert!(result.starts_with("مر"));
+        assert!(result.ends_with("الجميل"));
+    }
+
+    #[test]
+    fn test_truncate_middle_skin_tone_emoji() {
+        // 👍🏽 is two code points but one grapheme: keep or drop it whole
+        let s = "👍🏽👍🏽👍🏽👍🏽👍🏽👍🏽";
+        let result = truncate_middle(s, 7);
+        assert!(result.width() <= 7);
+        for grapheme in result.graphemes(true).filter(|g| *g != "…") {
+            assert_eq!(grapheme, "👍🏽");
+        }
+    }
+
+    #[test]
+    fn test_truncate_zero_width_joiner() {
+        // Family emoji joined with ZWJ is a single grapheme
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let s = format!("{}_family_photo_{}.png", family, family);
+        let result = truncate_middle(&s, 12);
+        assert!(result.width() <= 12);
+        for grapheme in result.graphemes(true).filter(|g| g.contains('\u{200d}')) {
+            assert_eq!(grapheme, family);
+        }
+
+        let path = truncate_path(&format!("photos/{}/{}", family, family), 4);
+        assert_eq!(path, format!("…/{}", family));
+    }
+
+    #[test]
+    fn test_truncate_combining_marks() {
+        // "é" as e + combining acute is one cell; the mark stays with its base
+        let s = "cafe\u{301}_cafe\u{301}_cafe\u{301}";
+        assert_eq!(truncate_path(s, 6), "…_cafe\u{301}");
+    }
+
     #[test]
     fn test_get_filename() {
         assert_eq!(get_filename("src/app.rs"), "app.rs");
@@ -373,6 +532,104 @@ mod tests {
         assert_eq!(strip_ansi_len("\x1b[32m\x1b]8;;url\x07text\x1b]8;;\x07\x1b[0m"), 4);
     }
 
+    #[test]
+    fn test_strip_ansi_len_hyperlinks() {
+        // The URL inside hyperlink() output must not count toward width
+        let link = crate::terminal::escape::hyperlink("https://example.com/long/path", "foo");
+        assert_eq!(strip_ansi_len(&link), 3);
+        assert_eq!(strip_ansi_len(&format!("\x1b[31m{}\x1b[0m bar", link)), 7);
+        // ST-terminated form
+        assert_eq!(strip_ansi_len("\x1b]8;;https://example.com/long/path\x1b\\foo\x1b]8;;\x1b\\"), 3);
+    }
+
+    #[test]
+    fn test_strip_ansi_len_other_sequences() {
+        // Non-SGR CSI (erase line, cursor movement)
+        assert_eq!(strip_ansi_len("\x1b[2Kab\x1b[3Dc"), 3);
+        // DCS and PM strings
+        assert_eq!(strip_ansi_len("\x1bPq#0;2;0;0;0\x1b\\x"), 1);
+        assert_eq!(strip_ansi_len("\x1b^private message\x1b\\ok"), 2);
+        // APC (used by kitty graphics)
+        assert_eq!(strip_ansi_len("\x1b_Gf=100;AAAA\x1b\\"), 0);
+    }
+
+    #[test]
+    fn test_strip_ansi_unterminated_sequences() {
+        assert_eq!(strip_ansi("ab\x1b[31"), "ab");
+        assert_eq!(strip_ansi("ab\x1b]8;;url"), "ab");
+        assert_eq!(strip_ansi("ab\x1b"), "ab");
+        // A lone ESC ends an OSC string without consuming what follows
+        assert_eq!(strip_ansi("\x1b]0;title\x1bxy"), "xy");
+        // Parameters are skipped whole, multi-byte characters included
+        assert_eq!(strip_ansi("\x1b[é;1mok"), "ok");
+    }
+
+    #[test]
+    fn test_strip_ansi_charset_and_two_byte_escapes() {
+        assert_eq!(strip_ansi("a\x1b(Bb\x1b#8c\x1b7d\x1bce\x1b=f"), "abcdef");
+        assert_eq!(strip_ansi("\x1b(é"), "é");
+    }
+
+    #[test]
+    fn test_find_escape_matches_scalar() {
+        // ESC at every offset across several 16-byte chunks, in strings whose
+        // lengths leave every possible remainder
+        for len in 0..70 {
+            for at in 0..=len {
+                let mut bytes = "ab日".repeat(len).into_bytes();
+                bytes.truncate(len);
+                if at < len {
+                    bytes[at] = 0x1b;
+                    bytes[len - 1] = 0x1b;
+                }
+                assert_eq!(find_escape(&bytes), find_escape_scalar(&bytes), "len {} at {}", len, at);
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_width_scripts() {
+        // CJK ideographs are two cells each
+        assert_eq!(display_width("日本語"), 6);
+        // Arabic letters are one cell each
+        assert_eq!(display_width("مرحبا"), 5);
+        // Braille spinner frames and box drawing are narrow
+        assert_eq!(display_width("⠋"), 1);
+        assert_eq!(display_width("─"), 1);
+        assert_eq!(display_width("──────"), 6);
+        // A flag is two regional indicators rendered as one wide glyph
+        assert_eq!(display_width("🇺🇸"), 2);
+        assert_eq!("🇺🇸".chars().count(), 2);
+        // Combining accent adds no width
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn test_strip_ansi_len_graphemes() {
+        assert_eq!(strip_ansi_len("\x1b[31m日本語\x1b[0m"), 6);
+        assert_eq!(strip_ansi_len("\x1b[36m⠋\x1b[0m Thinking"), 10);
+        assert_eq!(strip_ansi_len("\x1b[90m─── \x1b[0m🇺🇸"), 6);
+        assert_eq!(strip_ansi_len("مرحبا\x1b[0m"), 5);
+    }
+
+    #[test]
+    fn test_take_graphemes_and_pad() {
+        assert_eq!(take_graphemes("日本語", 5), "日本");
+        // Never split a flag in half
+        assert_eq!(take_graphemes("🇺🇸🇯🇵", 3), "🇺🇸");
+        assert_eq!(take_graphemes("⠋⠙⠹", 2), "⠋⠙");
+        assert_eq!(pad_to_width("日本", 6), "日本  ");
+        assert_eq!(pad_to_width("🇺🇸", 3), "🇺🇸 ");
+        assert_eq!(pad_to_width("toolong", 3), "toolong");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(12 * 60 + 30), "12m");
+        assert_eq!(format_duration(3600 + 5 * 60), "1h 05m");
+    }
+
     #[test]
     fn test_compute_unique_display_names_no_duplicates() {
         let paths = vec!["src/app.rs", "src/main.rs", "src/lib.rs"];
@@ -0,0 +1,91 @@
+//! Frame buffer for status bar redraws
+//!
+//! Widgets write a frame's escape sequences and text into a `ByteWriter`,
+//! which is then handed to the terminal in one `write_all`. The buffer keeps
+//! its allocation between frames, so a steady-state redraw allocates nothing
+//! for output and the terminal never sees half a frame.
+
+use std::io::{self, Write};
+
+/// Bytes reserved up front: a full-width status bar with all widgets is a few
+/// KiB of text and escapes
+pub const FRAME_CAPACITY: usize = 16 * 1024;
+
+/// Pre-allocated output buffer that widgets draw into
+pub struct ByteWriter {
+    buf: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::with_capacity(FRAME_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Bytes written since the last `flush_into`
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Write the buffered frame to `out` and empty the buffer, keeping its
+    /// capacity for the next frame
+    pub fn flush_into(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let result = out.write_all(&self.buf).and_then(|()| out.flush());
+        self.buf.clear();
+        result
+    }
+}
+
+impl Default for ByteWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for ByteWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Buffered bytes go out with `flush_into`
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_is_written_whole_and_buffer_reused() {
+        let mut frame = ByteWriter::with_capacity(64);
+        let label = "status";
+        write!(frame, "\x1b[1;1H{}", label).unwrap();
+        assert_eq!(frame.as_bytes(), b"\x1b[1;1Hstatus");
+
+        let mut out = Vec::new();
+        frame.flush_into(&mut out).unwrap();
+        assert_eq!(out, b"\x1b[1;1Hstatus");
+        assert!(frame.as_bytes().is_empty());
+        assert!(frame.buf.capacity() >= 64);
+
+        // Nothing buffered: nothing written
+        frame.flush_into(&mut out).unwrap();
+        assert_eq!(out.len(), 12);
+    }
+}
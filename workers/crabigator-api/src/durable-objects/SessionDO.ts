@@ -135,8 +135,11 @@ export class SessionDO implements DurableObject {
 
         server.addEventListener('message', async (event) => {
             try {
-                const data = JSON.parse(event.data as string) as SessionEvent;
-                await this.handleEvent(data);
+                // Desktop batches events: a frame is either one event or an array of them
+                const data = JSON.parse(event.data as string) as SessionEvent | SessionEvent[];
+                for (const item of Array.isArray(data) ? data : [data]) {
+                    await this.handleEvent(item);
+                }
             } catch (error) {
                 console.error('Error handling WebSocket message:', error);
             }